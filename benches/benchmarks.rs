@@ -61,7 +61,7 @@ fn benchmark_tool_system(c: &mut Criterion) {
         })
     });
 
-    group.bench_function("create_tool_system", |b| b.iter(|| ToolSystem::new()));
+    group.bench_function("create_tool_system", |b| b.iter(ToolSystem::new));
 
     let tool_system = ToolSystem::new();
 
@@ -106,7 +106,7 @@ fn benchmark_memory_scaling(c: &mut Criterion) {
 fn benchmark_autonomous_agent(c: &mut Criterion) {
     let mut group = c.benchmark_group("Autonomous");
 
-    group.bench_function("create_agent", |b| b.iter(|| AutonomousAgent::new()));
+    group.bench_function("create_agent", |b| b.iter(AutonomousAgent::new));
 
     group.finish();
 }
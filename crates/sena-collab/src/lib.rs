@@ -6,6 +6,7 @@ pub mod orchestrator;
 pub mod permission;
 pub mod routing;
 pub mod session;
+pub mod transcript;
 
 pub use agent::{AgentCapability, AgentInfo, AgentRegistry, CollabAgent};
 pub use consensus::{
@@ -28,3 +29,6 @@ pub use routing::{
 pub use session::{
     CollabSession, Participant, SessionConfig, SessionManager, SessionState, SessionType,
 };
+pub use transcript::{
+    Transcript, TranscriptEvent, TranscriptEventKind, TranscriptFormat, TranscriptParticipant,
+};
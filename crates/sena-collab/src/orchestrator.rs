@@ -5,10 +5,12 @@ use sena_providers::{AIProvider, ChatRequest, Message};
 
 use crate::{
     agent::AgentInfo,
+    consensus::{Proposal, ProposalState, Vote},
     error::{CollabError, Result},
     message::{AgentStatus, CollabMessage, MessageContent, RequestPayload, ResponsePayload},
     permission::{Permission, PermissionSet},
     session::{CollabSession, SessionManager, SessionState},
+    transcript::{Transcript, TranscriptFormat},
 };
 
 pub struct CollabOrchestrator {
@@ -18,8 +20,12 @@ pub struct CollabOrchestrator {
 
 impl CollabOrchestrator {
     pub fn new(max_sessions: usize) -> Self {
+        Self::with_session_manager(SessionManager::new(max_sessions))
+    }
+
+    pub fn with_session_manager(manager: SessionManager) -> Self {
         Self {
-            session_manager: Arc::new(RwLock::new(SessionManager::new(max_sessions))),
+            session_manager: Arc::new(RwLock::new(manager)),
             providers: std::collections::HashMap::new(),
         }
     }
@@ -29,6 +35,11 @@ impl CollabOrchestrator {
         self.providers.insert(id, provider);
     }
 
+    /// Value (such as a provider API key) redacted from exported transcripts
+    pub fn add_secret(&mut self, secret: impl AsRef<str>) {
+        sena_providers::redact::register_secret(secret.as_ref());
+    }
+
     pub async fn create_session(&self, name: &str, host_provider: &str) -> Result<String> {
         let provider = self
             .providers
@@ -96,6 +107,7 @@ impl CollabOrchestrator {
 
         let message = CollabMessage::chat(session_id, sender_id, content);
         session.add_message(message);
+        let _ = manager.save();
 
         Ok(())
     }
@@ -156,6 +168,7 @@ impl CollabOrchestrator {
                     session.add_message(response.clone());
                 }
             }
+            let _ = manager.save();
         }
 
         Ok(responses)
@@ -216,11 +229,82 @@ impl CollabOrchestrator {
                 session.add_message(request_msg);
                 session.add_message(response_msg.clone());
             }
+            let _ = manager.save();
         }
 
         Ok(response_msg)
     }
 
+    /// Open a proposal for a vote. Without explicit voters every current
+    /// participant must vote.
+    pub async fn propose(&self, session_id: &str, mut proposal: Proposal) -> Result<String> {
+        let mut manager = self.session_manager.write().await;
+        let session = manager
+            .get_session_mut(session_id)
+            .ok_or_else(|| CollabError::SessionNotFound(session_id.into()))?;
+
+        if !session.is_active() {
+            return Err(CollabError::InvalidState("Session is not active".into()));
+        }
+        if !session.has_permission(&proposal.proposer_id, Permission::SendMessages) {
+            return Err(CollabError::PermissionDenied(
+                "Agent cannot create proposals".into(),
+            ));
+        }
+
+        if proposal.required_voters.is_empty() {
+            proposal.required_voters = session
+                .participants()
+                .iter()
+                .map(|p| p.agent.id.clone())
+                .collect();
+        }
+        proposal.session_id = session_id.to_string();
+        proposal.start_voting()?;
+        let proposal_id = proposal.id.clone();
+        session.add_proposal(proposal);
+        let _ = manager.save();
+
+        Ok(proposal_id)
+    }
+
+    /// Cast a vote, returning the proposal's state afterwards
+    pub async fn vote(
+        &self,
+        session_id: &str,
+        proposal_id: &str,
+        vote: Vote,
+    ) -> Result<ProposalState> {
+        let mut manager = self.session_manager.write().await;
+        let session = manager
+            .get_session_mut(session_id)
+            .ok_or_else(|| CollabError::SessionNotFound(session_id.into()))?;
+        let proposal = session.get_proposal_mut(proposal_id).ok_or_else(|| {
+            CollabError::InvalidState(format!("Proposal not found: {}", proposal_id))
+        })?;
+
+        proposal.cast_vote(vote)?;
+        let state = proposal.state;
+        let _ = manager.save();
+
+        Ok(state)
+    }
+
+    /// Full session history in `format`, oldest first, with registered
+    /// secrets and credential-like values redacted
+    pub async fn export_transcript(
+        &self,
+        session_id: &str,
+        format: TranscriptFormat,
+    ) -> Result<String> {
+        let manager = self.session_manager.read().await;
+        let session = manager
+            .get_session(session_id)
+            .ok_or_else(|| CollabError::SessionNotFound(session_id.into()))?;
+
+        Ok(Transcript::from_session(session).redact().render(format))
+    }
+
     pub async fn get_session_summary(&self, session_id: &str) -> Result<SessionSummary> {
         let manager = self.session_manager.read().await;
         let session = manager
@@ -349,10 +433,167 @@ pub struct ParticipantSummary {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::consensus::VoteChoice;
+    use async_trait::async_trait;
+    use sena_providers::{
+        provider::ChatStream, ChatResponse, ModelInfo, ProviderCapabilities, ProviderError,
+        ProviderMetadata, ProviderStatus,
+    };
+
+    struct EchoProvider {
+        id: String,
+        capabilities: ProviderCapabilities,
+    }
+
+    impl EchoProvider {
+        fn new(id: &str) -> Arc<Self> {
+            Arc::new(Self {
+                id: id.to_string(),
+                capabilities: ProviderCapabilities::default(),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl AIProvider for EchoProvider {
+        fn provider_id(&self) -> &str {
+            &self.id
+        }
+
+        fn display_name(&self) -> &str {
+            &self.id
+        }
+
+        fn capabilities(&self) -> &ProviderCapabilities {
+            &self.capabilities
+        }
+
+        fn default_model(&self) -> &str {
+            "echo-model"
+        }
+
+        fn available_models(&self) -> &[ModelInfo] {
+            &self.capabilities.models
+        }
+
+        fn status(&self) -> ProviderStatus {
+            ProviderStatus::Connected
+        }
+
+        fn provider_metadata(&self) -> ProviderMetadata {
+            ProviderMetadata::new(&self.id, &self.id)
+        }
+
+        async fn chat(&self, _request: ChatRequest) -> sena_providers::Result<ChatResponse> {
+            Ok(ChatResponse::new(
+                &self.id,
+                "echo-model",
+                "Agreed, ship the cache layer".into(),
+            ))
+        }
+
+        async fn chat_stream(&self, _request: ChatRequest) -> sena_providers::Result<ChatStream> {
+            Err(ProviderError::StreamingError("not supported".into()))
+        }
+    }
 
     #[test]
     fn test_orchestrator_creation() {
         let orchestrator = CollabOrchestrator::new(10);
         assert!(orchestrator.providers.is_empty());
     }
+
+    #[tokio::test]
+    async fn test_markdown_transcript_orders_messages_and_proposal_outcome() {
+        let sessions_file = std::env::temp_dir()
+            .join(format!("sena-collab-{}", uuid::Uuid::new_v4()))
+            .join("sessions.json");
+        let mut orchestrator = CollabOrchestrator::with_session_manager(SessionManager::with_file(
+            sessions_file.clone(),
+            10,
+        ));
+        orchestrator.register_provider(EchoProvider::new("claude"));
+        orchestrator.register_provider(EchoProvider::new("openai"));
+        orchestrator.add_secret("sk-test-secret-value");
+
+        let session_id = orchestrator
+            .create_session("Cache design", "claude")
+            .await
+            .unwrap();
+        let reviewer = orchestrator
+            .join_session(&session_id, "openai")
+            .await
+            .unwrap();
+        orchestrator.start_session(&session_id).await.unwrap();
+        let host = orchestrator.get_session_summary(&session_id).await.unwrap();
+        let host = host
+            .participants
+            .iter()
+            .find(|p| p.is_host)
+            .unwrap()
+            .agent_id
+            .clone();
+
+        orchestrator
+            .send_message(
+                &session_id,
+                &host,
+                "Should we add a cache layer? key sk-test-secret-value",
+            )
+            .await
+            .unwrap();
+        let replies = orchestrator
+            .broadcast_to_agents(&session_id, &host, "Please weigh in")
+            .await
+            .unwrap();
+        assert_eq!(replies.len(), 1);
+
+        let proposal_id = orchestrator
+            .propose(
+                &session_id,
+                Proposal::new(
+                    &session_id,
+                    &host,
+                    "Add cache layer",
+                    "LRU in front of the DB",
+                ),
+            )
+            .await
+            .unwrap();
+        for voter in [&host, &reviewer] {
+            orchestrator
+                .vote(
+                    &session_id,
+                    &proposal_id,
+                    Vote::new(voter, VoteChoice::Approve),
+                )
+                .await
+                .unwrap();
+        }
+
+        let markdown = orchestrator
+            .export_transcript(&session_id, TranscriptFormat::Markdown)
+            .await
+            .unwrap();
+        let position = |needle: &str| {
+            markdown
+                .find(needle)
+                .unwrap_or_else(|| panic!("missing {:?} in\n{}", needle, markdown))
+        };
+        let question = position("Should we add a cache layer?");
+        let reply = position("Agreed, ship the cache layer");
+        let proposal = position("Add cache layer (Majority)");
+        let outcome = position("Add cache layer approved");
+        assert!(question < reply && reply < proposal && proposal < outcome);
+        assert!(!markdown.contains("sk-test-secret-value"));
+
+        let json = orchestrator
+            .export_transcript(&session_id, TranscriptFormat::Json)
+            .await
+            .unwrap();
+        let transcript: Transcript = serde_json::from_str(&json).unwrap();
+        assert_eq!(transcript.participants.len(), 2);
+
+        let _ = std::fs::remove_dir_all(sessions_file.parent().unwrap());
+    }
 }
@@ -6,6 +6,7 @@ use uuid::Uuid;
 
 use crate::{
     agent::AgentInfo,
+    consensus::Proposal,
     error::{CollabError, Result},
     message::{AgentStatus, CollabMessage},
     permission::{Permission, PermissionSet},
@@ -66,6 +67,8 @@ pub struct CollabSession {
     participants: HashMap<String, Participant>,
     messages: Vec<CollabMessage>,
     context: HashMap<String, serde_json::Value>,
+    #[serde(default)]
+    proposals: Vec<Proposal>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -110,6 +113,7 @@ impl CollabSession {
             participants,
             messages: Vec::new(),
             context: HashMap::new(),
+            proposals: Vec::new(),
         }
     }
 
@@ -249,6 +253,19 @@ impl CollabSession {
             .collect()
     }
 
+    pub fn add_proposal(&mut self, proposal: Proposal) {
+        self.proposals.push(proposal);
+        self.updated_at = chrono::Utc::now();
+    }
+
+    pub fn proposals(&self) -> &[Proposal] {
+        &self.proposals
+    }
+
+    pub fn get_proposal_mut(&mut self, proposal_id: &str) -> Option<&mut Proposal> {
+        self.proposals.iter_mut().find(|p| p.id == proposal_id)
+    }
+
     pub fn set_context(&mut self, key: &str, value: serde_json::Value) {
         self.context.insert(key.to_string(), value);
         self.updated_at = chrono::Utc::now();
@@ -310,6 +327,12 @@ impl SessionManager {
             .join("collab")
             .join("sessions.json");
 
+        Self::with_file(sessions_file, max_sessions)
+    }
+
+    /// Manager persisting sessions to `sessions_file` instead of the
+    /// default `~/.claude/collab/sessions.json`
+    pub fn with_file(sessions_file: PathBuf, max_sessions: usize) -> Self {
         let mut manager = Self {
             sessions: HashMap::new(),
             max_sessions,
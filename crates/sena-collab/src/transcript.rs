@@ -0,0 +1,278 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    consensus::{Proposal, ProposalState},
+    message::{CollabMessage, MessageContent},
+    session::{CollabSession, SessionState},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TranscriptFormat {
+    Markdown,
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptEventKind {
+    Message,
+    System,
+    Request,
+    Response,
+    ToolCall,
+    ToolResult,
+    Context,
+    Status,
+    Proposal,
+    Vote,
+    Outcome,
+}
+
+impl TranscriptEventKind {
+    fn label(&self) -> &'static str {
+        match self {
+            TranscriptEventKind::Message => "message",
+            TranscriptEventKind::System => "system",
+            TranscriptEventKind::Request => "request",
+            TranscriptEventKind::Response => "response",
+            TranscriptEventKind::ToolCall => "tool call",
+            TranscriptEventKind::ToolResult => "tool result",
+            TranscriptEventKind::Context => "context",
+            TranscriptEventKind::Status => "status",
+            TranscriptEventKind::Proposal => "proposal",
+            TranscriptEventKind::Vote => "vote",
+            TranscriptEventKind::Outcome => "consensus",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptEvent {
+    pub timestamp: DateTime<Utc>,
+    pub actor: String,
+    pub kind: TranscriptEventKind,
+    pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recipient: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptParticipant {
+    pub agent_id: String,
+    pub provider: String,
+    pub model: String,
+    pub is_host: bool,
+}
+
+/// Everything that happened in a session, oldest first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Transcript {
+    pub session_id: String,
+    pub name: String,
+    pub state: SessionState,
+    pub created_at: DateTime<Utc>,
+    pub participants: Vec<TranscriptParticipant>,
+    pub events: Vec<TranscriptEvent>,
+}
+
+impl Transcript {
+    pub fn from_session(session: &CollabSession) -> Self {
+        let mut participants: Vec<TranscriptParticipant> = session
+            .participants()
+            .iter()
+            .map(|p| TranscriptParticipant {
+                agent_id: p.agent.id.clone(),
+                provider: p.agent.provider.clone(),
+                model: p.agent.model.clone(),
+                is_host: p.is_host,
+            })
+            .collect();
+        participants.sort_by(|a, b| b.is_host.cmp(&a.is_host).then(a.agent_id.cmp(&b.agent_id)));
+
+        let mut events: Vec<TranscriptEvent> =
+            session.messages().iter().map(message_event).collect();
+        for proposal in session.proposals() {
+            events.extend(proposal_events(proposal));
+        }
+        // Stable, so events sharing a timestamp keep their recorded order
+        events.sort_by_key(|event| event.timestamp);
+
+        Self {
+            session_id: session.id.clone(),
+            name: session.name.clone(),
+            state: session.state,
+            created_at: session.created_at,
+            participants,
+            events,
+        }
+    }
+
+    /// Mask registered secrets and anything that looks like a credential
+    pub fn redact(mut self) -> Self {
+        for event in &mut self.events {
+            event.text = sena_providers::redact(&event.text);
+        }
+        self
+    }
+
+    pub fn render(&self, format: TranscriptFormat) -> String {
+        match format {
+            TranscriptFormat::Json => serde_json::to_string_pretty(self).unwrap_or_default(),
+            TranscriptFormat::Markdown => self.to_markdown(),
+        }
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut output = format!("# Transcript: {}\n\n", self.name);
+        output.push_str(&format!("- Session: `{}`\n", self.session_id));
+        output.push_str(&format!("- State: {:?}\n", self.state));
+        output.push_str(&format!("- Created: {}\n", self.created_at.to_rfc3339()));
+        output.push_str("\n## Participants\n\n");
+        for participant in &self.participants {
+            output.push_str(&format!(
+                "- `{}` — {} / {}{}\n",
+                participant.agent_id,
+                participant.provider,
+                participant.model,
+                if participant.is_host { " (host)" } else { "" }
+            ));
+        }
+
+        output.push_str("\n## Timeline\n");
+        if self.events.is_empty() {
+            output.push_str("\nNo activity recorded.\n");
+        }
+        for event in &self.events {
+            let recipient = event
+                .recipient
+                .as_ref()
+                .map(|r| format!(" → `{}`", r))
+                .unwrap_or_default();
+            output.push_str(&format!(
+                "\n**{}** · `{}`{} · {}\n\n",
+                event.timestamp.format("%Y-%m-%d %H:%M:%S"),
+                event.actor,
+                recipient,
+                event.kind.label()
+            ));
+            for line in event.text.lines() {
+                output.push_str(&format!("> {}\n", line));
+            }
+        }
+        output
+    }
+}
+
+fn message_event(message: &CollabMessage) -> TranscriptEvent {
+    let (kind, text) = match &message.content {
+        MessageContent::Text(text) if message.sender_id == "system" => {
+            (TranscriptEventKind::System, text.clone())
+        }
+        MessageContent::Text(text) => (TranscriptEventKind::Message, text.clone()),
+        MessageContent::Request(request) => (
+            TranscriptEventKind::Request,
+            format!("{:?}: {}", request.request_type, request.description),
+        ),
+        MessageContent::Response(response) => (
+            TranscriptEventKind::Response,
+            if response.success {
+                response.content.clone()
+            } else {
+                format!("Failed: {}", response.content)
+            },
+        ),
+        MessageContent::ToolCall(call) => (
+            TranscriptEventKind::ToolCall,
+            format!("{}({})", call.tool_name, call.arguments),
+        ),
+        MessageContent::ToolResult(result) => (
+            TranscriptEventKind::ToolResult,
+            match &result.error {
+                Some(error) => format!("{} failed: {}", result.tool_name, error),
+                None => format!("{} → {}", result.tool_name, result.result),
+            },
+        ),
+        MessageContent::ContextUpdate(update) => (
+            TranscriptEventKind::Context,
+            format!(
+                "{:?} {} = {}",
+                update.operation, update.context_key, update.context_value
+            ),
+        ),
+        MessageContent::Status(status) => (
+            TranscriptEventKind::Status,
+            match &status.current_task {
+                Some(task) => format!("{:?}: {}", status.agent_status, task),
+                None => format!("{:?}", status.agent_status),
+            },
+        ),
+    };
+
+    TranscriptEvent {
+        timestamp: message.created_at,
+        actor: message.sender_id.clone(),
+        kind,
+        text,
+        recipient: message.recipient_id.clone(),
+    }
+}
+
+fn proposal_events(proposal: &Proposal) -> Vec<TranscriptEvent> {
+    let mut events = vec![TranscriptEvent {
+        timestamp: proposal.created_at,
+        actor: proposal.proposer_id.clone(),
+        kind: TranscriptEventKind::Proposal,
+        text: format!(
+            "{} ({:?})\n{}",
+            proposal.title, proposal.strategy, proposal.description
+        ),
+        recipient: None,
+    }];
+
+    let mut votes: Vec<_> = proposal.votes.values().collect();
+    votes.sort_by_key(|vote| vote.timestamp);
+    for vote in &votes {
+        let text = match &vote.reasoning {
+            Some(reasoning) => format!("{:?} on {}: {}", vote.choice, proposal.title, reasoning),
+            None => format!("{:?} on {}", vote.choice, proposal.title),
+        };
+        events.push(TranscriptEvent {
+            timestamp: vote.timestamp,
+            actor: vote.voter_id.clone(),
+            kind: TranscriptEventKind::Vote,
+            text,
+            recipient: None,
+        });
+    }
+
+    let outcome = match proposal.state {
+        ProposalState::Approved => "approved",
+        ProposalState::Rejected => "rejected",
+        ProposalState::Expired => "expired",
+        ProposalState::Pending | ProposalState::Voting => return events,
+    };
+    let result = proposal.calculate_result();
+    let decided_at = match proposal.state {
+        ProposalState::Expired => proposal.deadline,
+        _ => votes.last().map(|vote| vote.timestamp),
+    }
+    .unwrap_or(proposal.created_at);
+    events.push(TranscriptEvent {
+        timestamp: decided_at,
+        actor: "consensus".to_string(),
+        kind: TranscriptEventKind::Outcome,
+        text: format!(
+            "{} {}: {:.1} approve / {:.1} reject, {} abstain (ratio {:.2}, threshold {:.2})",
+            proposal.title,
+            outcome,
+            result.approve_weight,
+            result.reject_weight,
+            result.abstain_count,
+            result.approval_ratio,
+            result.threshold
+        ),
+        recipient: None,
+    });
+    events
+}
@@ -4,15 +4,19 @@ use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    health::{check_endpoint, HealthInfo},
     metadata::{claude_metadata, ProviderMetadata},
-    provider::{AIProvider, ChatStream},
-    ChatRequest, ChatResponse, FinishReason, Message, MessageContent, ModelInfo,
+    provider::{ensure_vision, AIProvider, ChatStream},
+    timeout::{idle_timeout, with_timeout, RequestTimeout},
+    warmup::{open_connection, shared_client},
+    ChatRequest, ChatResponse, FinishReason, ImageData, Message, MessageContent, ModelInfo,
     ProviderCapabilities, ProviderConfig, ProviderError, ProviderStatus, Result, Role, StreamChunk,
-    Usage,
+    Tool, ToolCall, Usage,
 };
 
 const CLAUDE_API_URL: &str = "https://api.anthropic.com/v1/messages";
 const CLAUDE_API_VERSION: &str = "2023-06-01";
+const CLAUDE_MODELS_URL: &str = "https://api.anthropic.com/v1/models?limit=100";
 
 pub struct ClaudeProvider {
     client: Client,
@@ -26,14 +30,13 @@ impl ClaudeProvider {
         let api_key = config
             .get_api_key()
             .ok_or_else(|| ProviderError::NotConfigured("ANTHROPIC_API_KEY not set".into()))?;
+        // Masked in every error from here on, wherever the API echoes it
+        crate::redact::register_secret(&api_key);
 
-        let client = Client::builder()
-            .default_headers(Self::build_headers(&api_key)?)
-            .timeout(std::time::Duration::from_secs(
-                config.timeout_secs.unwrap_or(120),
-            ))
-            .build()
-            .map_err(|e| ProviderError::NetworkError(e.to_string()))?;
+        let client = shared_client(
+            config.timeout_secs.unwrap_or(120),
+            Self::build_headers(&api_key)?,
+        )?;
 
         let capabilities = Self::build_capabilities();
 
@@ -84,6 +87,8 @@ impl ClaudeProvider {
                     supports_vision: true,
                     supports_tools: true,
                     supports_streaming: true,
+                    price_per_1k_input: None,
+                    price_per_1k_output: None,
                 },
                 ModelInfo {
                     id: "claude-3-5-haiku-20241022".into(),
@@ -93,8 +98,13 @@ impl ClaudeProvider {
                     supports_vision: true,
                     supports_tools: true,
                     supports_streaming: true,
+                    price_per_1k_input: None,
+                    price_per_1k_output: None,
                 },
-            ],
+            ]
+            .into_iter()
+            .map(ModelInfo::with_pricing)
+            .collect(),
         }
     }
 
@@ -109,6 +119,23 @@ impl ClaudeProvider {
                         system_prompt = Some(text.to_string());
                     }
                 }
+                Role::Assistant if !message.tool_calls.is_empty() => {
+                    let mut parts = Vec::with_capacity(message.tool_calls.len() + 1);
+                    if let Some(text) = message.content.as_text().filter(|t| !t.is_empty()) {
+                        parts.push(ClaudeContentPart::Text { text: text.into() });
+                    }
+                    parts.extend(message.tool_calls.iter().map(|call| {
+                        ClaudeContentPart::ToolUse {
+                            id: call.id.clone(),
+                            name: call.function.name.clone(),
+                            input: call.arguments().unwrap_or_else(|_| serde_json::json!({})),
+                        }
+                    }));
+                    claude_messages.push(ClaudeMessage {
+                        role: "assistant".into(),
+                        content: ClaudeContent::Parts(parts),
+                    });
+                }
                 Role::User | Role::Assistant => {
                     claude_messages.push(ClaudeMessage {
                         role: match message.role {
@@ -119,7 +146,29 @@ impl ClaudeProvider {
                         content: self.convert_content(&message.content),
                     });
                 }
-                Role::Tool => {}
+                Role::Tool => {
+                    let result = ClaudeContentPart::ToolResult {
+                        tool_use_id: message.tool_call_id.clone().unwrap_or_default(),
+                        content: message.content.as_text().unwrap_or_default().to_string(),
+                    };
+                    // Results of parallel calls share one user turn
+                    match claude_messages.last_mut() {
+                        Some(ClaudeMessage {
+                            role,
+                            content: ClaudeContent::Parts(parts),
+                        }) if role == "user"
+                            && parts
+                                .iter()
+                                .all(|p| matches!(p, ClaudeContentPart::ToolResult { .. })) =>
+                        {
+                            parts.push(result)
+                        }
+                        _ => claude_messages.push(ClaudeMessage {
+                            role: "user".into(),
+                            content: ClaudeContent::Parts(vec![result]),
+                        }),
+                    }
+                }
             }
         }
 
@@ -132,18 +181,11 @@ impl ClaudeProvider {
             MessageContent::Parts(parts) => {
                 let claude_parts: Vec<ClaudeContentPart> = parts
                     .iter()
-                    .map(|part| match part {
+                    .filter_map(|part| match part {
                         crate::ContentPart::Text { text } => {
-                            ClaudeContentPart::Text { text: text.clone() }
+                            Some(ClaudeContentPart::Text { text: text.clone() })
                         }
-                        crate::ContentPart::ImageUrl { image_url } => ClaudeContentPart::Image {
-                            source: ImageSource {
-                                source_type: "url".into(),
-                                url: Some(image_url.url.clone()),
-                                media_type: None,
-                                data: None,
-                            },
-                        },
+                        _ => part.image_data().map(Self::convert_image),
                     })
                     .collect();
                 ClaudeContent::Parts(claude_parts)
@@ -151,11 +193,30 @@ impl ClaudeProvider {
         }
     }
 
+    fn convert_image(image: ImageData<'_>) -> ClaudeContentPart {
+        let source = match image {
+            ImageData::Url { url, .. } => ImageSource {
+                source_type: "url".into(),
+                url: Some(url.to_string()),
+                media_type: None,
+                data: None,
+            },
+            ImageData::Base64 { mime, data } => ImageSource {
+                source_type: "base64".into(),
+                url: None,
+                media_type: Some(mime.to_string()),
+                data: Some(data.to_string()),
+            },
+        };
+        ClaudeContentPart::Image { source }
+    }
+
     fn parse_finish_reason(stop_reason: &str) -> FinishReason {
         match stop_reason {
             "end_turn" | "stop_sequence" => FinishReason::Stop,
             "max_tokens" => FinishReason::Length,
             "tool_use" => FinishReason::ToolCalls,
+            "refusal" => FinishReason::ContentFilter,
             _ => FinishReason::Stop,
         }
     }
@@ -194,132 +255,176 @@ impl AIProvider for ClaudeProvider {
         claude_metadata()
     }
 
-    async fn chat(&self, request: ChatRequest) -> Result<ChatResponse> {
-        let model = request
-            .model
-            .as_deref()
-            .unwrap_or_else(|| self.default_model());
-        let (system, messages) = self.convert_messages(&request.messages);
-
-        let claude_request = ClaudeRequest {
-            model: model.into(),
-            messages,
-            system,
-            max_tokens: request.max_tokens.unwrap_or(4096),
-            temperature: request.temperature,
-            top_p: request.top_p,
-            stop_sequences: request.stop,
-            stream: false,
-        };
-
-        let response = self
-            .client
-            .post(CLAUDE_API_URL)
-            .json(&claude_request)
-            .send()
-            .await?;
-
+    async fn list_models_remote(&self) -> Result<Vec<ModelInfo>> {
+        let response = self.client.get(CLAUDE_MODELS_URL).send().await?;
         if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_default();
-
-            return match status.as_u16() {
-                401 => Err(ProviderError::AuthenticationFailed(error_text)),
-                429 => Err(ProviderError::RateLimited {
-                    retry_after_secs: 60,
-                }),
-                _ => Err(ProviderError::RequestFailed(format!(
-                    "{}: {}",
-                    status, error_text
-                ))),
-            };
+            return Err(ProviderError::from_http_response(response).await);
         }
 
-        let claude_response: ClaudeResponse = response.json().await?;
-
-        let content = claude_response
-            .content
-            .iter()
-            .filter_map(|block| {
-                if let ClaudeContentBlock::Text { text } = block {
-                    Some(text.clone())
-                } else {
-                    None
+        let list: ClaudeModelList = response.json().await?;
+        Ok(list
+            .data
+            .into_iter()
+            .map(|entry| {
+                let mut model = self.capabilities.model_info("claude", &entry.id);
+                if let Some(name) = entry.display_name {
+                    model.name = name;
                 }
+                model
             })
-            .collect::<Vec<_>>()
-            .join("");
-
-        Ok(ChatResponse {
-            id: claude_response.id,
-            provider: "claude".into(),
-            model: claude_response.model,
-            content,
-            role: Role::Assistant,
-            tool_calls: None,
-            usage: Usage {
-                prompt_tokens: claude_response.usage.input_tokens,
-                completion_tokens: claude_response.usage.output_tokens,
-                total_tokens: claude_response.usage.input_tokens
-                    + claude_response.usage.output_tokens,
-            },
-            created_at: chrono::Utc::now(),
-            finish_reason: claude_response
-                .stop_reason
-                .map(|r| Self::parse_finish_reason(&r)),
+            .collect())
+    }
+
+    async fn warm_up(&self) -> Result<()> {
+        open_connection(&self.client, CLAUDE_API_URL).await
+    }
+
+    async fn health_check(&self) -> Result<HealthInfo> {
+        check_endpoint(self.provider_id(), self.client.get(CLAUDE_MODELS_URL)).await
+    }
+
+    async fn chat(&self, request: ChatRequest) -> Result<ChatResponse> {
+        let timeout = request.timeout;
+        with_timeout(timeout, async move {
+            let model = request
+                .model
+                .as_deref()
+                .unwrap_or_else(|| self.default_model());
+            ensure_vision(self, &request, model)?;
+            let (system, messages) = self.convert_messages(&request.messages);
+
+            let claude_request = ClaudeRequest {
+                model: model.into(),
+                messages,
+                system,
+                max_tokens: request.max_tokens.unwrap_or(4096),
+                temperature: request.temperature,
+                top_p: request.top_p,
+                stop_sequences: request.stop,
+                tools: convert_tools(request.tools.as_deref()),
+                stream: false,
+            };
+
+            let response = self
+                .client
+                .post(CLAUDE_API_URL)
+                .request_timeout(timeout)
+                .json(&claude_request)
+                .send()
+                .await?;
+
+            if !response.status().is_success() {
+                return Err(ProviderError::from_http_response(response).await);
+            }
+
+            let claude_response: ClaudeResponse = response.json().await?;
+            Ok(into_chat_response(claude_response))
         })
+        .await
     }
 
     async fn chat_stream(&self, request: ChatRequest) -> Result<ChatStream> {
-        let model = request
-            .model
-            .as_deref()
-            .unwrap_or_else(|| self.default_model());
-        let (system, messages) = self.convert_messages(&request.messages);
-
-        let claude_request = ClaudeRequest {
-            model: model.into(),
-            messages,
-            system,
-            max_tokens: request.max_tokens.unwrap_or(4096),
-            temperature: request.temperature,
-            top_p: request.top_p,
-            stop_sequences: request.stop,
-            stream: true,
-        };
+        let timeout = request.timeout;
+        let stream = with_timeout(timeout, async move {
+            let model = request
+                .model
+                .as_deref()
+                .unwrap_or_else(|| self.default_model());
+            ensure_vision(self, &request, model)?;
+            let (system, messages) = self.convert_messages(&request.messages);
+
+            let claude_request = ClaudeRequest {
+                model: model.into(),
+                messages,
+                system,
+                max_tokens: request.max_tokens.unwrap_or(4096),
+                temperature: request.temperature,
+                top_p: request.top_p,
+                stop_sequences: request.stop,
+                tools: convert_tools(request.tools.as_deref()),
+                stream: true,
+            };
 
-        let response = self
-            .client
-            .post(CLAUDE_API_URL)
-            .json(&claude_request)
-            .send()
-            .await?;
+            let response = self
+                .client
+                .post(CLAUDE_API_URL)
+                .json(&claude_request)
+                .send()
+                .await?;
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_default();
-
-            return match status.as_u16() {
-                401 => Err(ProviderError::AuthenticationFailed(error_text)),
-                429 => Err(ProviderError::RateLimited {
-                    retry_after_secs: 60,
-                }),
-                _ => Err(ProviderError::RequestFailed(format!(
-                    "{}: {}",
-                    status, error_text
-                ))),
-            };
-        }
+            if !response.status().is_success() {
+                return Err(ProviderError::from_http_response(response).await);
+            }
 
-        let model_clone = model.to_string();
-        let stream = response.bytes_stream().map(move |chunk| {
-            let chunk = chunk.map_err(|e| ProviderError::StreamingError(e.to_string()))?;
-            let text = String::from_utf8_lossy(&chunk);
+            let model_clone = model.to_string();
+            let stream = response.bytes_stream().map(move |chunk| {
+                let chunk = chunk.map_err(|e| ProviderError::StreamingError(e.to_string()))?;
+                let text = String::from_utf8_lossy(&chunk);
 
-            parse_sse_chunk(&text, &model_clone)
-        });
+                parse_sse_chunk(&text, &model_clone)
+            });
 
-        Ok(Box::pin(stream))
+            Ok(Box::pin(stream))
+        })
+        .await?;
+        Ok(idle_timeout(stream, timeout))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ClaudeModelList {
+    data: Vec<ClaudeModelEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClaudeModelEntry {
+    id: String,
+    display_name: Option<String>,
+}
+
+fn convert_tools(tools: Option<&[Tool]>) -> Option<Vec<ClaudeTool>> {
+    let tools = tools.filter(|t| !t.is_empty())?;
+    Some(
+        tools
+            .iter()
+            .map(|tool| ClaudeTool {
+                name: tool.function.name.clone(),
+                description: tool.function.description.clone(),
+                input_schema: tool.parameters_schema(),
+            })
+            .collect(),
+    )
+}
+
+fn into_chat_response(claude_response: ClaudeResponse) -> ChatResponse {
+    let mut content = String::new();
+    let mut tool_calls = Vec::new();
+    for block in claude_response.content {
+        match block {
+            ClaudeContentBlock::Text { text } => content.push_str(&text),
+            ClaudeContentBlock::ToolUse { id, name, input } => {
+                tool_calls.push(ToolCall::function(id, name, input.to_string()))
+            }
+        }
+    }
+
+    ChatResponse {
+        id: claude_response.id,
+        provider: "claude".into(),
+        model: claude_response.model,
+        content,
+        role: Role::Assistant,
+        tool_calls,
+        usage: Usage {
+            prompt_tokens: claude_response.usage.input_tokens,
+            completion_tokens: claude_response.usage.output_tokens,
+            total_tokens: claude_response.usage.input_tokens + claude_response.usage.output_tokens,
+        },
+        created_at: chrono::Utc::now(),
+        finish_reason: claude_response
+            .stop_reason
+            .map(|r| ClaudeProvider::parse_finish_reason(&r)),
+        skipped_providers: Vec::new(),
     }
 }
 
@@ -363,6 +468,18 @@ fn parse_sse_chunk(text: &str, model: &str) -> Result<StreamChunk> {
                             })
                         }
                     }
+                    ClaudeStreamEvent::MessageDelta { delta, .. } => Ok(StreamChunk {
+                        id: String::new(),
+                        provider: "claude".into(),
+                        model: model.into(),
+                        delta: String::new(),
+                        is_final: false,
+                        usage: None,
+                        finish_reason: delta
+                            .get("stop_reason")
+                            .and_then(|r| r.as_str())
+                            .map(ClaudeProvider::parse_finish_reason),
+                    }),
                     ClaudeStreamEvent::MessageStop => Ok(StreamChunk {
                         id: String::new(),
                         provider: "claude".into(),
@@ -370,7 +487,7 @@ fn parse_sse_chunk(text: &str, model: &str) -> Result<StreamChunk> {
                         delta: String::new(),
                         is_final: true,
                         usage: None,
-                        finish_reason: Some(FinishReason::Stop),
+                        finish_reason: None,
                     }),
                     _ => Ok(StreamChunk {
                         id: String::new(),
@@ -410,9 +527,19 @@ struct ClaudeRequest {
     top_p: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     stop_sequences: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tools: Option<Vec<ClaudeTool>>,
     stream: bool,
 }
 
+#[derive(Debug, Serialize)]
+struct ClaudeTool {
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    input_schema: serde_json::Value,
+}
+
 #[derive(Debug, Serialize)]
 struct ClaudeMessage {
     role: String,
@@ -433,6 +560,17 @@ enum ClaudeContentPart {
     Text { text: String },
     #[serde(rename = "image")]
     Image { source: ImageSource },
+    #[serde(rename = "tool_use")]
+    ToolUse {
+        id: String,
+        name: String,
+        input: serde_json::Value,
+    },
+    #[serde(rename = "tool_result")]
+    ToolResult {
+        tool_use_id: String,
+        content: String,
+    },
 }
 
 #[derive(Debug, Serialize)]
@@ -456,7 +594,6 @@ struct ClaudeResponse {
     usage: ClaudeUsage,
 }
 
-#[allow(dead_code)]
 #[derive(Debug, Deserialize)]
 #[serde(tag = "type")]
 enum ClaudeContentBlock {
@@ -472,6 +609,7 @@ enum ClaudeContentBlock {
 
 #[derive(Debug, Deserialize)]
 struct ClaudeUsage {
+    #[serde(default)]
     input_tokens: u32,
     output_tokens: u32,
 }
@@ -517,6 +655,82 @@ enum ClaudeDelta {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ContentPart;
+
+    #[test]
+    fn test_max_tokens_stop_reason_marks_response_truncated() {
+        let response: ClaudeResponse = serde_json::from_str(
+            r#"{"id":"msg_1","model":"claude-sonnet-4-5","content":[{"type":"text","text":"Once upon"}],"stop_reason":"max_tokens","usage":{"input_tokens":5,"output_tokens":2}}"#,
+        )
+        .unwrap();
+        let response = into_chat_response(response);
+        assert_eq!(response.finish_reason, Some(FinishReason::Length));
+        assert!(response.is_truncated());
+
+        let chunk = parse_sse_chunk(
+            r#"data: {"type":"message_delta","delta":{"stop_reason":"max_tokens"},"usage":{"output_tokens":2}}"#,
+            "claude-sonnet-4-5",
+        )
+        .unwrap();
+        assert_eq!(chunk.finish_reason, Some(FinishReason::Length));
+    }
+
+    #[test]
+    fn test_image_parts_serialize_as_claude_image_sources() {
+        let provider =
+            ClaudeProvider::new(ProviderConfig::new("claude").with_api_key("sk-ant-test")).unwrap();
+        let message = Message::user_with_images(
+            "Describe these",
+            vec![
+                ContentPart::image("iVBORw0KGgo=", "image/png"),
+                ContentPart::image("https://example.com/cat.jpg", "image/jpeg"),
+            ],
+        );
+        let (_, messages) = provider.convert_messages(&[message]);
+
+        assert_eq!(
+            serde_json::to_value(&messages[0].content).unwrap(),
+            serde_json::json!([
+                {"type": "image", "source": {"type": "base64", "media_type": "image/png", "data": "iVBORw0KGgo="}},
+                {"type": "image", "source": {"type": "url", "url": "https://example.com/cat.jpg"}},
+                {"type": "text", "text": "Describe these"}
+            ])
+        );
+    }
+
+    #[test]
+    fn test_tool_use_round_trip() {
+        let response: ClaudeResponse = serde_json::from_str(
+            r#"{"id":"msg_1","model":"claude-sonnet-4-5","content":[{"type":"tool_use","id":"toolu_1","name":"file_exists","input":{"path":"/tmp"}}],"stop_reason":"tool_use","usage":{"input_tokens":5,"output_tokens":2}}"#,
+        )
+        .unwrap();
+        let response = into_chat_response(response);
+        assert_eq!(response.tool_calls.len(), 1);
+        assert_eq!(response.tool_calls[0].id, "toolu_1");
+        assert_eq!(
+            response.tool_calls[0].arguments().unwrap(),
+            serde_json::json!({"path": "/tmp"})
+        );
+
+        let provider =
+            ClaudeProvider::new(ProviderConfig::new("claude").with_api_key("sk-ant-test")).unwrap();
+        let (_, messages) = provider.convert_messages(&[
+            Message::user("Does /tmp exist?"),
+            Message::assistant_with_tool_calls("", response.tool_calls),
+            Message::tool_result("toolu_1", "file_exists", r#"{"exists":true}"#),
+        ]);
+        assert_eq!(
+            serde_json::to_value(&messages[1..]).unwrap(),
+            serde_json::json!([
+                {"role": "assistant", "content": [
+                    {"type": "tool_use", "id": "toolu_1", "name": "file_exists", "input": {"path": "/tmp"}}
+                ]},
+                {"role": "user", "content": [
+                    {"type": "tool_result", "tool_use_id": "toolu_1", "content": "{\"exists\":true}"}
+                ]}
+            ])
+        );
+    }
 
     #[test]
     fn test_build_capabilities() {
@@ -0,0 +1,79 @@
+use futures::future::{BoxFuture, FutureExt, Shared};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+
+use crate::{ChatRequest, ChatResponse, Result};
+
+type SharedCall = Shared<BoxFuture<'static, Result<ChatResponse>>>;
+
+/// Lets concurrent identical chat requests share one provider call. Only
+/// in-flight calls are shared; once a call finishes the next identical
+/// request starts a fresh one.
+#[derive(Default)]
+pub struct RequestCoalescer {
+    in_flight: Mutex<HashMap<String, SharedCall>>,
+}
+
+/// Removes the leader's entry when its call finishes or is dropped
+struct InFlightGuard<'a> {
+    coalescer: &'a RequestCoalescer,
+    key: String,
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut in_flight) = self.coalescer.in_flight.lock() {
+            in_flight.remove(&self.key);
+        }
+    }
+}
+
+impl RequestCoalescer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Key identifying identical requests to one provider. `None` when the
+    /// request cannot be serialized, which disables sharing for it.
+    pub fn key(provider_id: &str, request: &ChatRequest) -> Option<String> {
+        serde_json::to_string(request)
+            .ok()
+            .map(|body| format!("{}\n{}", provider_id, body))
+    }
+
+    /// Number of calls currently shared
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.lock().map(|calls| calls.len()).unwrap_or(0)
+    }
+
+    /// Await the in-flight call for `key`, or start `call` and share it.
+    /// The flag is `true` for the caller that started the call.
+    pub async fn run<F>(&self, key: String, call: F) -> (Result<ChatResponse>, bool)
+    where
+        F: Future<Output = Result<ChatResponse>> + Send + 'static,
+    {
+        let (shared, guard) = {
+            let Ok(mut in_flight) = self.in_flight.lock() else {
+                return (call.await, true);
+            };
+            match in_flight.get(&key) {
+                Some(shared) => (shared.clone(), None),
+                None => {
+                    let shared = call.boxed().shared();
+                    in_flight.insert(key.clone(), shared.clone());
+                    (
+                        shared,
+                        Some(InFlightGuard {
+                            coalescer: self,
+                            key,
+                        }),
+                    )
+                }
+            }
+        };
+
+        let result = shared.await;
+        (result, guard.is_some())
+    }
+}
@@ -3,6 +3,8 @@ use std::collections::HashMap;
 use std::env;
 use std::path::PathBuf;
 
+use crate::{redact, retry::RetryPolicy};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProviderConfig {
     pub provider_id: String,
@@ -61,16 +63,18 @@ impl ProviderConfig {
         self
     }
 
+    /// The configured key, or the one in `api_key_env`
     pub fn get_api_key(&self) -> Option<String> {
-        if let Some(key) = &self.api_key {
-            return Some(key.clone());
-        }
-
-        if let Some(env_var) = &self.api_key_env {
-            return env::var(env_var).ok();
+        match (&self.api_key, &self.api_key_env) {
+            (Some(key), _) => Some(key.clone()),
+            (None, Some(env_var)) => env::var(env_var).ok(),
+            (None, None) => None,
         }
+    }
 
-        None
+    /// Masked form of the API key for display, e.g. `sk-...abcd`
+    pub fn redacted_api_key(&self) -> Option<String> {
+        self.get_api_key().map(|key| redact::preview(&key))
     }
 }
 
@@ -84,6 +88,46 @@ pub struct ProvidersConfig {
     pub fallback_chain: Vec<String>,
     #[serde(default)]
     pub cost_optimization: bool,
+    /// Let `chat_with_fallback` move past a provider that blocks or refuses
+    /// the content instead of returning the block
+    #[serde(default)]
+    pub fallback_on_block: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub aliases: HashMap<String, String>,
+    /// Retries for transient provider errors (rate limits, 5xx, timeouts)
+    #[serde(default)]
+    pub retry: RetryPolicy,
+    /// Timeout in seconds for requests that don't set their own
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_timeout_secs: Option<u64>,
+}
+
+/// Target of a model alias: `provider/model`, or a bare model id
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModelAlias {
+    pub provider: Option<String>,
+    pub model: Option<String>,
+}
+
+impl ModelAlias {
+    pub fn parse(target: &str) -> Self {
+        let non_empty = |s: &str| {
+            let s = s.trim();
+            (!s.is_empty()).then(|| s.to_string())
+        };
+        match target.split_once('/') {
+            Some((provider, model)) => Self {
+                provider: non_empty(provider),
+                model: non_empty(model),
+            },
+            None => Self {
+                provider: None,
+                model: non_empty(target),
+            },
+        }
+    }
 }
 
 impl ProvidersConfig {
@@ -144,6 +188,14 @@ impl ProvidersConfig {
         }
     }
 
+    pub fn set_alias(&mut self, name: impl Into<String>, target: impl Into<String>) {
+        self.aliases.insert(name.into(), target.into());
+    }
+
+    pub fn remove_alias(&mut self, name: &str) -> Option<String> {
+        self.aliases.remove(name)
+    }
+
     pub fn set_default_provider(&mut self, provider_id: &str) -> bool {
         if self.providers.contains_key(provider_id) {
             self.default_provider = Some(provider_id.to_string());
@@ -1,45 +1,223 @@
+use std::fmt;
+
+use serde::ser::{Serialize, SerializeStruct, Serializer};
 use thiserror::Error;
 
-#[derive(Error, Debug)]
+use crate::redact::redact;
+
+/// Errors from providers and the router. Their `Display`, `Debug` and
+/// `Serialize` forms are passed through [`redact`](crate::redact::redact),
+/// so API keys and bearer tokens echoed back in a message never reach logs
+/// or the UI.
+#[derive(Error, Clone)]
 pub enum ProviderError {
-    #[error("Provider not configured: {0}")]
     NotConfigured(String),
-
-    #[error("Authentication failed: {0}")]
     AuthenticationFailed(String),
-
-    #[error("API request failed: {0}")]
     RequestFailed(String),
+    RateLimited { retry_after_secs: Option<u64> },
+    InvalidResponse(String),
+    ModelNotFound(String),
+    ContextLengthExceeded { used: usize, max: usize },
+    Unavailable(String),
+    StreamingError(String),
+    Timeout(u64),
+    NetworkError(String),
+    SerializationError(String),
+    BadRequest(String),
+    Unsupported(String),
+    Server { status: u16, message: String },
+    ContentBlocked(String),
+    Middleware { name: String, message: String },
+    Unknown(String),
+}
 
-    #[error("Rate limited: retry after {retry_after_secs} seconds")]
-    RateLimited { retry_after_secs: u64 },
+impl fmt::Display for ProviderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&redact(&self.raw_message()))
+    }
+}
 
-    #[error("Invalid response: {0}")]
-    InvalidResponse(String),
+/// `ProviderError { kind, message }` with the redacted message, so `{:?}`
+/// and `unwrap` leak no more than `Display`
+impl fmt::Debug for ProviderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProviderError")
+            .field("kind", &self.kind())
+            .field("message", &self.to_string())
+            .finish()
+    }
+}
 
-    #[error("Model not found: {0}")]
-    ModelNotFound(String),
+/// Serialized as `{"kind": ..., "message": ...}` with the redacted message
+impl Serialize for ProviderError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("ProviderError", 2)?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
 
-    #[error("Context length exceeded: {used} tokens used, {max} max")]
-    ContextLengthExceeded { used: usize, max: usize },
+impl ProviderError {
+    /// Whether the same request may succeed if retried later or on another provider
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            ProviderError::RateLimited { .. }
+                | ProviderError::Unavailable(_)
+                | ProviderError::Timeout(_)
+                | ProviderError::NetworkError(_)
+                | ProviderError::Server { .. }
+        )
+    }
 
-    #[error("Provider unavailable: {0}")]
-    Unavailable(String),
+    /// Wait requested by the provider, e.g. from a `Retry-After` header
+    pub fn retry_after(&self) -> Option<std::time::Duration> {
+        match self {
+            ProviderError::RateLimited { retry_after_secs } => {
+                retry_after_secs.map(std::time::Duration::from_secs)
+            }
+            _ => None,
+        }
+    }
 
-    #[error("Streaming error: {0}")]
-    StreamingError(String),
+    /// Short category name, stable for display and logging
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ProviderError::NotConfigured(_) => "not_configured",
+            ProviderError::AuthenticationFailed(_) => "auth",
+            ProviderError::RequestFailed(_) => "request_failed",
+            ProviderError::RateLimited { .. } => "rate_limited",
+            ProviderError::InvalidResponse(_) => "invalid_response",
+            ProviderError::ModelNotFound(_) => "model_not_found",
+            ProviderError::ContextLengthExceeded { .. } => "context_length",
+            ProviderError::Unavailable(_) => "unavailable",
+            ProviderError::StreamingError(_) => "streaming",
+            ProviderError::Timeout(_) => "timeout",
+            ProviderError::NetworkError(_) => "network",
+            ProviderError::SerializationError(_) => "serialization",
+            ProviderError::BadRequest(_) => "bad_request",
+            ProviderError::Unsupported(_) => "unsupported",
+            ProviderError::Server { .. } => "server",
+            ProviderError::ContentBlocked(_) => "content_blocked",
+            ProviderError::Middleware { .. } => "middleware",
+            ProviderError::Unknown(_) => "unknown",
+        }
+    }
 
-    #[error("Timeout after {0} seconds")]
-    Timeout(u64),
+    /// Classify a non-success API response from its status code and error body.
+    /// Understands the OpenAI/Mistral, Anthropic, Gemini and Ollama error shapes.
+    pub fn from_response(status: u16, body: &str, retry_after_secs: Option<u64>) -> Self {
+        let json: Option<serde_json::Value> = serde_json::from_str(body).ok();
+        let field = |path: &[&str]| {
+            let mut value = json.as_ref()?;
+            for key in path {
+                value = value.get(key)?;
+            }
+            value.as_str().map(str::to_string)
+        };
 
-    #[error("Network error: {0}")]
-    NetworkError(String),
+        let message = field(&["error", "message"])
+            .or_else(|| field(&["message"]))
+            .or_else(|| field(&["error"]))
+            .or_else(|| field(&["detail"]))
+            .unwrap_or_else(|| body.trim().to_string());
+        let kind = field(&["error", "type"])
+            .or_else(|| field(&["error", "status"]))
+            .or_else(|| field(&["error", "code"]))
+            .or_else(|| field(&["type"]))
+            .unwrap_or_default()
+            .to_lowercase();
+        let lower = message.to_lowercase();
 
-    #[error("Serialization error: {0}")]
-    SerializationError(String),
+        let auth_failure = matches!(status, 401 | 403)
+            || matches!(
+                kind.as_str(),
+                "authentication_error"
+                    | "permission_error"
+                    | "unauthenticated"
+                    | "permission_denied"
+                    | "invalid_api_key"
+            )
+            || lower.contains("api key not valid")
+            || lower.contains("invalid api key");
 
-    #[error("Unknown error: {0}")]
-    Unknown(String),
+        if auth_failure {
+            return ProviderError::AuthenticationFailed(message);
+        }
+
+        if status == 429 || matches!(kind.as_str(), "rate_limit_error" | "resource_exhausted") {
+            return ProviderError::RateLimited { retry_after_secs };
+        }
+
+        if matches!(
+            kind.as_str(),
+            "overloaded_error" | "unavailable" | "internal"
+        ) {
+            return ProviderError::Server { status, message };
+        }
+
+        match status {
+            404 if lower.contains("model") || kind.contains("not_found") => {
+                ProviderError::ModelNotFound(message)
+            }
+            405 | 501 => ProviderError::Unsupported(message),
+            400 | 413 | 422 => ProviderError::BadRequest(message),
+            408 | 500..=599 => ProviderError::Server { status, message },
+            _ => ProviderError::RequestFailed(format!("{}: {}", status, message)),
+        }
+    }
+
+    /// Read and classify a failed HTTP response
+    pub async fn from_http_response(response: reqwest::Response) -> Self {
+        let status = response.status().as_u16();
+        let retry_after = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse().ok());
+        let body = response.text().await.unwrap_or_default();
+        Self::from_response(status, &body, retry_after)
+    }
+
+    /// The message before redaction
+    fn raw_message(&self) -> String {
+        match self {
+            ProviderError::NotConfigured(value) => format!("Provider not configured: {}", value),
+            ProviderError::AuthenticationFailed(value) => {
+                format!("Authentication failed: {}", value)
+            }
+            ProviderError::RequestFailed(value) => format!("API request failed: {}", value),
+            ProviderError::RateLimited {
+                retry_after_secs: Some(secs),
+            } => format!("Rate limited: retry after {} seconds", secs),
+            ProviderError::RateLimited {
+                retry_after_secs: None,
+            } => "Rate limited".to_string(),
+            ProviderError::InvalidResponse(value) => format!("Invalid response: {}", value),
+            ProviderError::ModelNotFound(value) => format!("Model not found: {}", value),
+            ProviderError::ContextLengthExceeded { used, max } => {
+                format!("Context length exceeded: {} tokens used, {} max", used, max)
+            }
+            ProviderError::Unavailable(value) => format!("Provider unavailable: {}", value),
+            ProviderError::StreamingError(value) => format!("Streaming error: {}", value),
+            ProviderError::Timeout(value) => format!("Timeout after {} seconds", value),
+            ProviderError::NetworkError(value) => format!("Network error: {}", value),
+            ProviderError::SerializationError(value) => format!("Serialization error: {}", value),
+            ProviderError::BadRequest(value) => format!("Bad request: {}", value),
+            ProviderError::Unsupported(value) => format!("Unsupported: {}", value),
+            ProviderError::Server { status, message } => {
+                format!("Server error ({}): {}", status, message)
+            }
+            ProviderError::ContentBlocked(value) => {
+                format!("Content blocked by provider safety filter: {}", value)
+            }
+            ProviderError::Middleware { name, message } => {
+                format!("Middleware '{}' failed: {}", name, message)
+            }
+            ProviderError::Unknown(value) => format!("Unknown error: {}", value),
+        }
+    }
 }
 
 impl From<reqwest::Error> for ProviderError {
@@ -61,3 +239,135 @@ impl From<serde_json::Error> for ProviderError {
 }
 
 pub type Result<T> = std::result::Result<T, ProviderError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_openai_and_mistral_errors() {
+        let unauthorized = r#"{"error":{"message":"Incorrect API key provided","type":"invalid_request_error","code":"invalid_api_key"}}"#;
+        assert!(matches!(
+            ProviderError::from_response(401, unauthorized, None),
+            ProviderError::AuthenticationFailed(ref m) if m == "Incorrect API key provided"
+        ));
+
+        let limited = r#"{"error":{"message":"Rate limit reached","type":"requests","code":"rate_limit_exceeded"}}"#;
+        assert!(matches!(
+            ProviderError::from_response(429, limited, Some(12)),
+            ProviderError::RateLimited {
+                retry_after_secs: Some(12)
+            }
+        ));
+
+        let mistral = r#"{"object":"error","message":"Internal server error","type":"internal_error","code":null}"#;
+        assert!(matches!(
+            ProviderError::from_response(500, mistral, None),
+            ProviderError::Server { status: 500, ref message } if message == "Internal server error"
+        ));
+    }
+
+    #[test]
+    fn test_anthropic_errors() {
+        let unauthorized = r#"{"type":"error","error":{"type":"authentication_error","message":"invalid x-api-key"}}"#;
+        assert!(matches!(
+            ProviderError::from_response(401, unauthorized, None),
+            ProviderError::AuthenticationFailed(_)
+        ));
+
+        let limited = r#"{"type":"error","error":{"type":"rate_limit_error","message":"Number of requests has exceeded your rate limit"}}"#;
+        assert!(matches!(
+            ProviderError::from_response(429, limited, None),
+            ProviderError::RateLimited {
+                retry_after_secs: None
+            }
+        ));
+
+        let overloaded =
+            r#"{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#;
+        let error = ProviderError::from_response(529, overloaded, None);
+        assert!(matches!(error, ProviderError::Server { status: 529, .. }));
+        assert!(error.is_retryable());
+    }
+
+    #[test]
+    fn test_gemini_errors() {
+        let bad_key = r#"{"error":{"code":400,"message":"API key not valid. Please pass a valid API key.","status":"INVALID_ARGUMENT"}}"#;
+        assert!(matches!(
+            ProviderError::from_response(400, bad_key, None),
+            ProviderError::AuthenticationFailed(_)
+        ));
+
+        let exhausted = r#"{"error":{"code":429,"message":"Resource has been exhausted","status":"RESOURCE_EXHAUSTED"}}"#;
+        assert!(matches!(
+            ProviderError::from_response(429, exhausted, None),
+            ProviderError::RateLimited { .. }
+        ));
+
+        let internal = r#"{"error":{"code":500,"message":"An internal error has occurred","status":"INTERNAL"}}"#;
+        assert!(matches!(
+            ProviderError::from_response(500, internal, None),
+            ProviderError::Server { status: 500, .. }
+        ));
+    }
+
+    #[test]
+    fn test_ollama_errors() {
+        let missing = r#"{"error":"model 'llama9' not found, try pulling it first"}"#;
+        assert!(matches!(
+            ProviderError::from_response(404, missing, None),
+            ProviderError::ModelNotFound(_)
+        ));
+
+        let crashed = r#"{"error":"llama runner process has terminated"}"#;
+        assert!(ProviderError::from_response(500, crashed, None).is_retryable());
+
+        assert!(matches!(
+            ProviderError::from_response(400, "not json", None),
+            ProviderError::BadRequest(ref m) if m == "not json"
+        ));
+    }
+
+    #[test]
+    fn test_retryable_classification() {
+        assert!(ProviderError::Timeout(30).is_retryable());
+        assert!(!ProviderError::AuthenticationFailed("x".into()).is_retryable());
+        assert!(!ProviderError::BadRequest("x".into()).is_retryable());
+        assert!(!ProviderError::Unsupported("x".into()).is_retryable());
+        assert_eq!(
+            ProviderError::RateLimited {
+                retry_after_secs: Some(1)
+            }
+            .kind(),
+            "rate_limited"
+        );
+    }
+
+    #[test]
+    fn test_configured_key_is_masked_in_display_debug_and_json() {
+        let config = crate::ProviderConfig::new("test").with_api_key("t0p-secret-key-9876");
+        assert_eq!(config.redacted_api_key().as_deref(), Some("t0p...9876"));
+        // Constructing the provider registers its key for masking
+        crate::claude::ClaudeProvider::new(config).unwrap();
+
+        let err = ProviderError::RequestFailed(
+            "400: request {\"api_key\": \"t0p-secret-key-9876\"} rejected".into(),
+        );
+        let display = err.to_string();
+        assert!(!display.contains("t0p-secret-key-9876"));
+        assert!(display.contains("[REDACTED]"));
+        let debug = format!("{:?}", err);
+        assert!(!debug.contains("t0p-secret-key-9876"));
+        assert!(debug.contains("request_failed"));
+
+        let json = serde_json::to_value(&err).unwrap();
+        assert_eq!(json["kind"], "request_failed");
+        assert!(!json["message"].as_str().unwrap().contains("secret"));
+
+        let err = ProviderError::NetworkError(
+            "error sending request for url (https://host/v1beta/models?key=AIzaSyA0123456789abcdefghijklmnopqrstu)"
+                .into(),
+        );
+        assert!(!err.to_string().contains("AIzaSyA"));
+    }
+}
@@ -0,0 +1,229 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::{ProviderConfig, ProvidersConfig},
+    router::ProviderRouter,
+    ProviderCapabilities,
+};
+
+/// Provider cargo features, in report order
+pub const PROVIDER_FEATURES: &[&str] = &["claude", "openai", "gemini", "ollama", "mistral"];
+
+/// Provider features compiled into this build
+pub fn compiled_providers() -> Vec<&'static str> {
+    PROVIDER_FEATURES
+        .iter()
+        .copied()
+        .filter(|feature| match *feature {
+            "claude" => cfg!(feature = "claude"),
+            "openai" => cfg!(feature = "openai"),
+            "gemini" => cfg!(feature = "gemini"),
+            "ollama" => cfg!(feature = "ollama"),
+            "mistral" => cfg!(feature = "mistral"),
+            _ => false,
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapabilitySet {
+    pub streaming: bool,
+    pub tool_use: bool,
+    pub vision: bool,
+}
+
+impl CapabilitySet {
+    pub fn union(self, other: Self) -> Self {
+        Self {
+            streaming: self.streaming || other.streaming,
+            tool_use: self.tool_use || other.tool_use,
+            vision: self.vision || other.vision,
+        }
+    }
+
+    /// `(name, available)` pairs in display order
+    pub fn entries(&self) -> [(&'static str, bool); 3] {
+        [
+            ("streaming", self.streaming),
+            ("tools", self.tool_use),
+            ("vision", self.vision),
+        ]
+    }
+}
+
+impl From<&ProviderCapabilities> for CapabilitySet {
+    fn from(capabilities: &ProviderCapabilities) -> Self {
+        Self {
+            streaming: capabilities.streaming,
+            tool_use: capabilities.tool_use,
+            vision: capabilities.vision,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderFeature {
+    pub id: String,
+    /// The provider's cargo feature is enabled in this build
+    pub compiled: bool,
+    /// Present and enabled in `providers.toml`
+    pub enabled: bool,
+    /// Compiled, enabled and has the credentials it needs
+    pub configured: bool,
+    /// What the provider supports; `None` when it is not compiled in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capabilities: Option<CapabilitySet>,
+}
+
+/// Which providers this build includes, which are usable, and what the
+/// usable set can do
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureReport {
+    pub providers: Vec<ProviderFeature>,
+    /// Union of the configured providers' capabilities
+    pub capabilities: CapabilitySet,
+}
+
+impl FeatureReport {
+    pub fn new(config: &ProvidersConfig) -> Self {
+        Self::for_features(&compiled_providers(), config)
+    }
+
+    /// Build the report as if only `compiled` provider features were enabled
+    pub fn for_features(compiled: &[&str], config: &ProvidersConfig) -> Self {
+        let configured = ProviderRouter::from_config(config).ok();
+
+        // Capabilities are static per provider, so a placeholder key is
+        // enough to read them for providers without credentials
+        let mut placeholder = ProvidersConfig::new();
+        for id in compiled {
+            placeholder.add_provider(ProviderConfig::new(*id).with_api_key("unused"));
+        }
+        let known = ProviderRouter::from_config(&placeholder).ok();
+
+        let providers: Vec<ProviderFeature> = PROVIDER_FEATURES
+            .iter()
+            .map(|id| {
+                let is_compiled = compiled.contains(id);
+                let enabled = config.get_provider(id).is_some_and(|p| p.enabled);
+                let is_configured = is_compiled
+                    && configured
+                        .as_ref()
+                        .is_some_and(|router| router.get_provider(id).is_some());
+                let capabilities = known
+                    .as_ref()
+                    .filter(|_| is_compiled)
+                    .and_then(|router| router.get_provider(id))
+                    .map(|provider| CapabilitySet::from(provider.capabilities()));
+                ProviderFeature {
+                    id: id.to_string(),
+                    compiled: is_compiled,
+                    enabled,
+                    configured: is_configured,
+                    capabilities,
+                }
+            })
+            .collect();
+
+        let capabilities = providers
+            .iter()
+            .filter(|p| p.configured)
+            .filter_map(|p| p.capabilities)
+            .fold(CapabilitySet::default(), CapabilitySet::union);
+
+        Self {
+            providers,
+            capabilities,
+        }
+    }
+
+    pub fn compiled(&self) -> impl Iterator<Item = &ProviderFeature> {
+        self.providers.iter().filter(|p| p.compiled)
+    }
+
+    pub fn configured(&self) -> impl Iterator<Item = &ProviderFeature> {
+        self.providers.iter().filter(|p| p.configured)
+    }
+
+    /// Why each unavailable capability is missing and how to get it
+    pub fn hints(&self) -> Vec<String> {
+        let mut hints = Vec::new();
+        for (index, (name, available)) in self.capabilities.entries().into_iter().enumerate() {
+            if available {
+                continue;
+            }
+            let supports =
+                |p: &&ProviderFeature| p.capabilities.is_some_and(|caps| caps.entries()[index].1);
+            let unconfigured: Vec<&str> = self
+                .compiled()
+                .filter(|p| !p.configured)
+                .filter(supports)
+                .map(|p| p.id.as_str())
+                .collect();
+            let hint = if unconfigured.is_empty() {
+                let missing: Vec<&str> = self
+                    .providers
+                    .iter()
+                    .filter(|p| !p.compiled)
+                    .map(|p| p.id.as_str())
+                    .collect();
+                if missing.is_empty() {
+                    format!("{}: no compiled provider supports it", name)
+                } else {
+                    format!(
+                        "{}: no configured provider supports it; rebuild with one of the features: {}",
+                        name,
+                        missing.join(", ")
+                    )
+                }
+            } else {
+                format!(
+                    "{}: configure credentials for {}",
+                    name,
+                    unconfigured.join(", ")
+                )
+            };
+            hints.push(hint);
+        }
+        hints
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_lists_compiled_and_marks_configured() {
+        let mut config = ProvidersConfig::new();
+        config.add_provider(ProviderConfig::new("claude").with_api_key("test-key"));
+        config.add_provider(ProviderConfig::new("openai").with_api_key_env("SENA_TEST_UNSET_KEY"));
+        config.add_provider(ProviderConfig::new("gemini").with_api_key("test-key"));
+
+        let report = FeatureReport::for_features(&["claude", "openai"], &config);
+        let compiled: Vec<&str> = report.compiled().map(|p| p.id.as_str()).collect();
+        assert_eq!(compiled, vec!["claude", "openai"]);
+        assert_eq!(report.providers.len(), PROVIDER_FEATURES.len());
+
+        let configured: Vec<&str> = report.configured().map(|p| p.id.as_str()).collect();
+        assert_eq!(configured, vec!["claude"]);
+
+        let gemini = report.providers.iter().find(|p| p.id == "gemini").unwrap();
+        assert!(gemini.enabled && !gemini.compiled && !gemini.configured);
+        assert!(gemini.capabilities.is_none());
+
+        let openai = report.providers.iter().find(|p| p.id == "openai").unwrap();
+        assert!(openai.enabled && !openai.configured);
+        assert!(openai.capabilities.is_some());
+
+        assert!(report.capabilities.streaming);
+        assert!(report.capabilities.vision);
+
+        let empty = FeatureReport::for_features(&["openai"], &config);
+        assert_eq!(empty.capabilities, CapabilitySet::default());
+        assert!(empty
+            .hints()
+            .iter()
+            .any(|hint| hint.starts_with("vision: configure credentials for openai")));
+    }
+}
@@ -4,21 +4,55 @@ use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    health::{check_endpoint, HealthInfo},
     metadata::{gemini_metadata, ProviderMetadata},
-    provider::{AIProvider, ChatStream},
-    ChatRequest, ChatResponse, FinishReason, Message, MessageContent, ModelInfo,
+    provider::{ensure_vision, AIProvider, ChatStream},
+    timeout::{idle_timeout, with_timeout, RequestTimeout},
+    warmup::{open_connection, shared_client},
+    ChatRequest, ChatResponse, FinishReason, ImageData, Message, MessageContent, ModelInfo,
     ProviderCapabilities, ProviderConfig, ProviderError, ProviderStatus, Result, Role, StreamChunk,
-    Usage,
+    Tool, ToolCall, Usage,
 };
 
 const GEMINI_API_BASE: &str = "https://generativelanguage.googleapis.com/v1beta/models";
 
+/// `extra` key setting the block threshold for every harm category
+pub const SAFETY_THRESHOLD_KEY: &str = "safety_threshold";
+
+/// Prefix of `extra` keys overriding one category, e.g. `safety_dangerous_content`
+pub const SAFETY_CATEGORY_PREFIX: &str = "safety_";
+
+/// Harm categories accepted in `safetySettings`
+pub const SAFETY_CATEGORIES: &[&str] = &[
+    "HARM_CATEGORY_HARASSMENT",
+    "HARM_CATEGORY_HATE_SPEECH",
+    "HARM_CATEGORY_SEXUALLY_EXPLICIT",
+    "HARM_CATEGORY_DANGEROUS_CONTENT",
+];
+
+/// Block thresholds accepted in `safetySettings`
+pub const SAFETY_THRESHOLDS: &[&str] = &[
+    "BLOCK_NONE",
+    "BLOCK_ONLY_HIGH",
+    "BLOCK_MEDIUM_AND_ABOVE",
+    "BLOCK_LOW_AND_ABOVE",
+    "OFF",
+];
+
+const BLOCKED_FINISH_REASONS: &[&str] = &[
+    "SAFETY",
+    "RECITATION",
+    "BLOCKLIST",
+    "PROHIBITED_CONTENT",
+    "SPII",
+];
+
 pub struct GeminiProvider {
     client: Client,
     config: ProviderConfig,
     capabilities: ProviderCapabilities,
     status: ProviderStatus,
-    api_key: String,
+    safety_settings: Vec<GeminiSafetySetting>,
 }
 
 impl GeminiProvider {
@@ -26,25 +60,49 @@ impl GeminiProvider {
         let api_key = config
             .get_api_key()
             .ok_or_else(|| ProviderError::NotConfigured("GOOGLE_API_KEY not set".into()))?;
+        // Masked in every error from here on, wherever the API echoes it
+        crate::redact::register_secret(&api_key);
 
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(
-                config.timeout_secs.unwrap_or(120),
-            ))
-            .build()
-            .map_err(|e| ProviderError::NetworkError(e.to_string()))?;
+        let client = shared_client(
+            config.timeout_secs.unwrap_or(120),
+            Self::build_headers(&api_key)?,
+        )?;
 
         let capabilities = Self::build_capabilities();
+        let safety_settings = safety_settings_from_extra(&config.extra)?;
 
         Ok(Self {
             client,
             config,
             capabilities,
             status: ProviderStatus::Connected,
-            api_key,
+            safety_settings,
         })
     }
 
+    /// The key goes in a header rather than the `?key=` query parameter,
+    /// so it never shows up in URLs that reach logs or error messages
+    fn build_headers(api_key: &str) -> Result<reqwest::header::HeaderMap> {
+        let mut headers = reqwest::header::HeaderMap::new();
+        let mut value: reqwest::header::HeaderValue = api_key
+            .parse()
+            .map_err(|_| ProviderError::AuthenticationFailed("Invalid API key format".into()))?;
+        value.set_sensitive(true);
+        headers.insert("x-goog-api-key", value);
+        Ok(headers)
+    }
+
+    /// Set the block threshold for one harm category, e.g.
+    /// `("dangerous_content", "BLOCK_ONLY_HIGH")`. Defaults to the
+    /// `safety_threshold` and `safety_<category>` entries of `extra`.
+    pub fn with_safety_setting(mut self, category: &str, threshold: &str) -> Result<Self> {
+        let setting = GeminiSafetySetting::parse(category, threshold)?;
+        self.safety_settings
+            .retain(|existing| existing.category != setting.category);
+        self.safety_settings.push(setting);
+        Ok(self)
+    }
+
     fn build_capabilities() -> ProviderCapabilities {
         ProviderCapabilities {
             streaming: true,
@@ -61,6 +119,8 @@ impl GeminiProvider {
                     supports_vision: true,
                     supports_tools: true,
                     supports_streaming: true,
+                    price_per_1k_input: None,
+                    price_per_1k_output: None,
                 },
                 ModelInfo {
                     id: "gemini-2.5-pro".into(),
@@ -70,6 +130,8 @@ impl GeminiProvider {
                     supports_vision: true,
                     supports_tools: true,
                     supports_streaming: true,
+                    price_per_1k_input: None,
+                    price_per_1k_output: None,
                 },
                 ModelInfo {
                     id: "gemini-2.5-flash-lite".into(),
@@ -79,6 +141,8 @@ impl GeminiProvider {
                     supports_vision: true,
                     supports_tools: true,
                     supports_streaming: true,
+                    price_per_1k_input: None,
+                    price_per_1k_output: None,
                 },
                 ModelInfo {
                     id: "gemini-2.0-flash".into(),
@@ -88,8 +152,13 @@ impl GeminiProvider {
                     supports_vision: true,
                     supports_tools: true,
                     supports_streaming: true,
+                    price_per_1k_input: None,
+                    price_per_1k_output: None,
                 },
-            ],
+            ]
+            .into_iter()
+            .map(ModelInfo::with_pricing)
+            .collect(),
         }
     }
 
@@ -99,10 +168,7 @@ impl GeminiProvider {
         } else {
             "generateContent"
         };
-        format!(
-            "{}/{}:{}?key={}",
-            GEMINI_API_BASE, model, action, self.api_key
-        )
+        format!("{}/{}:{}", GEMINI_API_BASE, model, action)
     }
 
     fn convert_messages(
@@ -123,6 +189,29 @@ impl GeminiProvider {
                         });
                     }
                 }
+                Role::Assistant if !message.tool_calls.is_empty() => {
+                    let mut parts = Vec::with_capacity(message.tool_calls.len() + 1);
+                    if let Some(text) = message.content.as_text().filter(|t| !t.is_empty()) {
+                        parts.push(GeminiPart::Text { text: text.into() });
+                    }
+                    parts.extend(
+                        message
+                            .tool_calls
+                            .iter()
+                            .map(|call| GeminiPart::FunctionCall {
+                                function_call: GeminiFunctionCall {
+                                    name: call.function.name.clone(),
+                                    args: call
+                                        .arguments()
+                                        .unwrap_or_else(|_| serde_json::json!({})),
+                                },
+                            }),
+                    );
+                    contents.push(GeminiContent {
+                        role: "model".into(),
+                        parts,
+                    });
+                }
                 Role::User | Role::Assistant => {
                     contents.push(GeminiContent {
                         role: match message.role {
@@ -133,7 +222,34 @@ impl GeminiProvider {
                         parts: self.convert_content(&message.content),
                     });
                 }
-                Role::Tool => {}
+                Role::Tool => {
+                    let output = message.content.as_text().unwrap_or_default();
+                    let response = GeminiPart::FunctionResponse {
+                        function_response: GeminiFunctionResponse {
+                            name: message.name.clone().unwrap_or_default(),
+                            response: serde_json::from_str(output)
+                                .ok()
+                                .filter(serde_json::Value::is_object)
+                                .unwrap_or_else(|| serde_json::json!({ "output": output })),
+                        },
+                    };
+                    // Results of parallel calls share one user turn
+                    match contents.last_mut() {
+                        Some(last)
+                            if last.role == "user"
+                                && last
+                                    .parts
+                                    .iter()
+                                    .all(|p| matches!(p, GeminiPart::FunctionResponse { .. })) =>
+                        {
+                            last.parts.push(response)
+                        }
+                        _ => contents.push(GeminiContent {
+                            role: "user".into(),
+                            parts: vec![response],
+                        }),
+                    }
+                }
             }
         }
 
@@ -145,27 +261,146 @@ impl GeminiProvider {
             MessageContent::Text(text) => vec![GeminiPart::Text { text: text.clone() }],
             MessageContent::Parts(parts) => parts
                 .iter()
-                .map(|part| match part {
-                    crate::ContentPart::Text { text } => GeminiPart::Text { text: text.clone() },
-                    crate::ContentPart::ImageUrl { image_url } => GeminiPart::InlineData {
-                        inline_data: GeminiInlineData {
-                            mime_type: "image/jpeg".into(),
-                            data: image_url.url.clone(),
-                        },
-                    },
+                .filter_map(|part| match part {
+                    crate::ContentPart::Text { text } => {
+                        Some(GeminiPart::Text { text: text.clone() })
+                    }
+                    _ => part.image_data().map(Self::convert_image),
                 })
                 .collect(),
         }
     }
 
+    fn convert_image(image: ImageData<'_>) -> GeminiPart {
+        match image {
+            ImageData::Url { url, mime } => GeminiPart::FileData {
+                file_data: GeminiFileData {
+                    mime_type: mime.unwrap_or(ImageData::DEFAULT_MIME).into(),
+                    file_uri: url.into(),
+                },
+            },
+            ImageData::Base64 { mime, data } => GeminiPart::InlineData {
+                inline_data: GeminiInlineData {
+                    mime_type: mime.into(),
+                    data: data.into(),
+                },
+            },
+        }
+    }
+
     fn parse_finish_reason(reason: &str) -> FinishReason {
         match reason {
             "STOP" => FinishReason::Stop,
             "MAX_TOKENS" => FinishReason::Length,
-            "SAFETY" => FinishReason::ContentFilter,
+            reason if BLOCKED_FINISH_REASONS.contains(&reason) => FinishReason::ContentFilter,
             _ => FinishReason::Stop,
         }
     }
+
+    fn build_request(
+        &self,
+        request: ChatRequest,
+        system_instruction: Option<GeminiSystemInstruction>,
+        contents: Vec<GeminiContent>,
+    ) -> GeminiRequest {
+        GeminiRequest {
+            contents,
+            system_instruction,
+            generation_config: Some(GeminiGenerationConfig {
+                max_output_tokens: request.max_tokens,
+                temperature: request.temperature,
+                top_p: request.top_p,
+                stop_sequences: request.stop,
+            }),
+            safety_settings: self.safety_settings.clone(),
+            tools: convert_tools(request.tools.as_deref()),
+        }
+    }
+
+    fn into_chat_response(response: GeminiResponse, model: &str) -> Result<ChatResponse> {
+        if let Some(reason) = response
+            .prompt_feedback
+            .as_ref()
+            .and_then(|feedback| feedback.block_reason.as_deref())
+        {
+            return Err(ProviderError::ContentBlocked(format!(
+                "prompt ({})",
+                reason
+            )));
+        }
+
+        let candidate = response
+            .candidates
+            .first()
+            .ok_or_else(|| ProviderError::InvalidResponse("No candidates in response".into()))?;
+        if let Some(reason) = candidate.blocked_reason() {
+            return Err(ProviderError::ContentBlocked(reason));
+        }
+
+        let usage = response
+            .usage_metadata
+            .map(|u| Usage {
+                prompt_tokens: u.prompt_token_count,
+                completion_tokens: u.candidates_token_count,
+                total_tokens: u.total_token_count,
+            })
+            .unwrap_or_default();
+
+        Ok(ChatResponse {
+            id: uuid::Uuid::new_v4().to_string(),
+            provider: "gemini".into(),
+            model: model.into(),
+            content: candidate.text(),
+            role: Role::Assistant,
+            tool_calls: candidate.tool_calls(),
+            usage,
+            created_at: chrono::Utc::now(),
+            finish_reason: candidate
+                .finish_reason
+                .as_ref()
+                .map(|r| Self::parse_finish_reason(r)),
+            skipped_providers: Vec::new(),
+        })
+    }
+}
+
+fn convert_tools(tools: Option<&[Tool]>) -> Vec<GeminiTool> {
+    match tools {
+        Some(tools) if !tools.is_empty() => vec![GeminiTool {
+            function_declarations: tools
+                .iter()
+                .map(|tool| GeminiFunctionDeclaration {
+                    name: tool.function.name.clone(),
+                    description: tool.function.description.clone(),
+                    parameters: tool.parameters_schema(),
+                })
+                .collect(),
+        }],
+        _ => Vec::new(),
+    }
+}
+
+fn safety_settings_from_extra(
+    extra: &std::collections::HashMap<String, String>,
+) -> Result<Vec<GeminiSafetySetting>> {
+    let mut settings = Vec::new();
+    if let Some(threshold) = extra.get(SAFETY_THRESHOLD_KEY) {
+        for category in SAFETY_CATEGORIES {
+            settings.push(GeminiSafetySetting::parse(category, threshold)?);
+        }
+    }
+
+    let mut overrides: Vec<(&String, &String)> = extra
+        .iter()
+        .filter(|(key, _)| key.starts_with(SAFETY_CATEGORY_PREFIX) && *key != SAFETY_THRESHOLD_KEY)
+        .collect();
+    overrides.sort();
+    for (key, threshold) in overrides {
+        let setting = GeminiSafetySetting::parse(&key[SAFETY_CATEGORY_PREFIX.len()..], threshold)?;
+        settings.retain(|existing| existing.category != setting.category);
+        settings.push(setting);
+    }
+    Ok(settings)
 }
 
 #[async_trait]
@@ -201,134 +436,108 @@ impl AIProvider for GeminiProvider {
         gemini_metadata()
     }
 
-    async fn chat(&self, request: ChatRequest) -> Result<ChatResponse> {
-        let model = request
-            .model
-            .as_deref()
-            .unwrap_or_else(|| self.default_model());
-        let (system_instruction, contents) = self.convert_messages(&request.messages);
-
-        let gemini_request = GeminiRequest {
-            contents,
-            system_instruction,
-            generation_config: Some(GeminiGenerationConfig {
-                max_output_tokens: request.max_tokens,
-                temperature: request.temperature,
-                top_p: request.top_p,
-                stop_sequences: request.stop,
-            }),
-        };
-
-        let url = self.build_url(model, false);
-        let response = self.client.post(&url).json(&gemini_request).send().await?;
-
+    async fn list_models_remote(&self) -> Result<Vec<ModelInfo>> {
+        let url = format!("{}?pageSize=1000", GEMINI_API_BASE);
+        let response = self.client.get(&url).send().await?;
         if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_default();
-
-            return match status.as_u16() {
-                401 | 403 => Err(ProviderError::AuthenticationFailed(error_text)),
-                429 => Err(ProviderError::RateLimited {
-                    retry_after_secs: 60,
-                }),
-                _ => Err(ProviderError::RequestFailed(format!(
-                    "{}: {}",
-                    status, error_text
-                ))),
-            };
+            return Err(ProviderError::from_http_response(response).await);
         }
 
-        let gemini_response: GeminiResponse = response.json().await?;
-        let candidate = gemini_response
-            .candidates
-            .first()
-            .ok_or_else(|| ProviderError::InvalidResponse("No candidates in response".into()))?;
-
-        let content = candidate
-            .content
-            .parts
-            .iter()
-            .filter_map(|part| {
-                if let GeminiPart::Text { text } = part {
-                    Some(text.clone())
-                } else {
-                    None
-                }
+        let list: GeminiModelList = response.json().await?;
+        Ok(list
+            .models
+            .into_iter()
+            .filter(|entry| {
+                entry
+                    .supported_generation_methods
+                    .iter()
+                    .any(|method| method == "generateContent")
             })
-            .collect::<Vec<_>>()
-            .join("");
-
-        let usage = gemini_response
-            .usage_metadata
-            .map(|u| Usage {
-                prompt_tokens: u.prompt_token_count,
-                completion_tokens: u.candidates_token_count,
-                total_tokens: u.total_token_count,
+            .filter_map(|entry| {
+                let id = entry.name.strip_prefix("models/")?;
+                let mut model = self.capabilities.model_info("gemini", id);
+                if let Some(name) = entry.display_name {
+                    model.name = name;
+                }
+                if let Some(limit) = entry.input_token_limit.filter(|n| *n > 0) {
+                    model.context_length = limit;
+                }
+                Some(model)
             })
-            .unwrap_or_default();
+            .collect())
+    }
 
-        Ok(ChatResponse {
-            id: uuid::Uuid::new_v4().to_string(),
-            provider: "gemini".into(),
-            model: model.into(),
-            content,
-            role: Role::Assistant,
-            tool_calls: None,
-            usage,
-            created_at: chrono::Utc::now(),
-            finish_reason: candidate
-                .finish_reason
-                .as_ref()
-                .map(|r| Self::parse_finish_reason(r)),
-        })
+    async fn warm_up(&self) -> Result<()> {
+        open_connection(&self.client, GEMINI_API_BASE).await
     }
 
-    async fn chat_stream(&self, request: ChatRequest) -> Result<ChatStream> {
-        let model = request
-            .model
-            .as_deref()
-            .unwrap_or_else(|| self.default_model());
-        let (system_instruction, contents) = self.convert_messages(&request.messages);
+    async fn health_check(&self) -> Result<HealthInfo> {
+        let url = format!("{}?pageSize=1", GEMINI_API_BASE);
+        check_endpoint(self.provider_id(), self.client.get(&url)).await
+    }
 
-        let gemini_request = GeminiRequest {
-            contents,
-            system_instruction,
-            generation_config: Some(GeminiGenerationConfig {
-                max_output_tokens: request.max_tokens,
-                temperature: request.temperature,
-                top_p: request.top_p,
-                stop_sequences: request.stop,
-            }),
-        };
+    async fn chat(&self, request: ChatRequest) -> Result<ChatResponse> {
+        let timeout = request.timeout;
+        with_timeout(timeout, async move {
+            let model = request
+                .model
+                .as_deref()
+                .unwrap_or_else(|| self.default_model());
+            ensure_vision(self, &request, model)?;
+            let (system_instruction, contents) = self.convert_messages(&request.messages);
+            let model = model.to_string();
+            let gemini_request = self.build_request(request, system_instruction, contents);
+
+            let url = self.build_url(&model, false);
+            let response = self
+                .client
+                .post(&url)
+                .request_timeout(timeout)
+                .json(&gemini_request)
+                .send()
+                .await?;
+
+            if !response.status().is_success() {
+                return Err(ProviderError::from_http_response(response).await);
+            }
 
-        let url = self.build_url(model, true);
-        let response = self.client.post(&url).json(&gemini_request).send().await?;
+            let gemini_response: GeminiResponse = response.json().await?;
+            Self::into_chat_response(gemini_response, &model)
+        })
+        .await
+    }
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_default();
-
-            return match status.as_u16() {
-                401 | 403 => Err(ProviderError::AuthenticationFailed(error_text)),
-                429 => Err(ProviderError::RateLimited {
-                    retry_after_secs: 60,
-                }),
-                _ => Err(ProviderError::RequestFailed(format!(
-                    "{}: {}",
-                    status, error_text
-                ))),
-            };
-        }
+    async fn chat_stream(&self, request: ChatRequest) -> Result<ChatStream> {
+        let timeout = request.timeout;
+        let stream = with_timeout(timeout, async move {
+            let model = request
+                .model
+                .as_deref()
+                .unwrap_or_else(|| self.default_model());
+            ensure_vision(self, &request, model)?;
+            let (system_instruction, contents) = self.convert_messages(&request.messages);
+            let model = model.to_string();
+            let gemini_request = self.build_request(request, system_instruction, contents);
+
+            let url = self.build_url(&model, true);
+            let response = self.client.post(&url).json(&gemini_request).send().await?;
+
+            if !response.status().is_success() {
+                return Err(ProviderError::from_http_response(response).await);
+            }
 
-        let model_clone = model.to_string();
-        let stream = response.bytes_stream().map(move |chunk| {
-            let chunk = chunk.map_err(|e| ProviderError::StreamingError(e.to_string()))?;
-            let text = String::from_utf8_lossy(&chunk);
+            let model_clone = model;
+            let stream = response.bytes_stream().map(move |chunk| {
+                let chunk = chunk.map_err(|e| ProviderError::StreamingError(e.to_string()))?;
+                let text = String::from_utf8_lossy(&chunk);
 
-            parse_stream_chunk(&text, &model_clone)
-        });
+                parse_stream_chunk(&text, &model_clone)
+            });
 
-        Ok(Box::pin(stream))
+            Ok(Box::pin(stream))
+        })
+        .await?;
+        Ok(idle_timeout(stream, timeout))
     }
 }
 
@@ -349,20 +558,21 @@ fn parse_stream_chunk(text: &str, model: &str) -> Result<StreamChunk> {
     let json_text = trimmed.trim_start_matches('[').trim_start_matches(',');
 
     if let Ok(response) = serde_json::from_str::<GeminiStreamResponse>(json_text) {
+        if let Some(reason) = response
+            .prompt_feedback
+            .as_ref()
+            .and_then(|feedback| feedback.block_reason.as_deref())
+        {
+            return Err(ProviderError::ContentBlocked(format!(
+                "prompt ({})",
+                reason
+            )));
+        }
         if let Some(candidate) = response.candidates.first() {
-            let delta = candidate
-                .content
-                .parts
-                .iter()
-                .filter_map(|part| {
-                    if let GeminiPart::Text { text } = part {
-                        Some(text.clone())
-                    } else {
-                        None
-                    }
-                })
-                .collect::<Vec<_>>()
-                .join("");
+            if let Some(reason) = candidate.blocked_reason() {
+                return Err(ProviderError::ContentBlocked(reason));
+            }
+            let delta = candidate.text();
 
             let is_final = candidate.finish_reason.is_some();
             let finish_reason = candidate
@@ -400,6 +610,63 @@ struct GeminiRequest {
     system_instruction: Option<GeminiSystemInstruction>,
     #[serde(skip_serializing_if = "Option::is_none")]
     generation_config: Option<GeminiGenerationConfig>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    safety_settings: Vec<GeminiSafetySetting>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<GeminiTool>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiTool {
+    function_declarations: Vec<GeminiFunctionDeclaration>,
+}
+
+#[derive(Debug, Serialize)]
+struct GeminiFunctionDeclaration {
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    parameters: serde_json::Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct GeminiSafetySetting {
+    category: String,
+    threshold: String,
+}
+
+impl GeminiSafetySetting {
+    /// Accepts short (`dangerous_content`) or full (`HARM_CATEGORY_DANGEROUS_CONTENT`)
+    /// category names and case-insensitive thresholds
+    fn parse(category: &str, threshold: &str) -> Result<Self> {
+        let category = category.trim().to_uppercase();
+        let category = if category.starts_with("HARM_CATEGORY_") {
+            category
+        } else {
+            format!("HARM_CATEGORY_{}", category)
+        };
+        if !SAFETY_CATEGORIES.contains(&category.as_str()) {
+            return Err(ProviderError::NotConfigured(format!(
+                "Unknown Gemini safety category '{}' (expected one of: {})",
+                category,
+                SAFETY_CATEGORIES.join(", ")
+            )));
+        }
+
+        let threshold = threshold.trim().to_uppercase();
+        if !SAFETY_THRESHOLDS.contains(&threshold.as_str()) {
+            return Err(ProviderError::NotConfigured(format!(
+                "Unknown Gemini safety threshold '{}' (expected one of: {})",
+                threshold,
+                SAFETY_THRESHOLDS.join(", ")
+            )));
+        }
+        Ok(Self {
+            category,
+            threshold,
+        })
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -416,8 +683,36 @@ struct GeminiContent {
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(untagged)]
 enum GeminiPart {
-    Text { text: String },
-    InlineData { inline_data: GeminiInlineData },
+    Text {
+        text: String,
+    },
+    InlineData {
+        inline_data: GeminiInlineData,
+    },
+    FileData {
+        file_data: GeminiFileData,
+    },
+    FunctionCall {
+        #[serde(rename = "functionCall")]
+        function_call: GeminiFunctionCall,
+    },
+    FunctionResponse {
+        #[serde(rename = "functionResponse")]
+        function_response: GeminiFunctionResponse,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct GeminiFunctionCall {
+    name: String,
+    #[serde(default)]
+    args: serde_json::Value,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct GeminiFunctionResponse {
+    name: String,
+    response: serde_json::Value,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -426,6 +721,12 @@ struct GeminiInlineData {
     data: String,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+struct GeminiFileData {
+    mime_type: String,
+    file_uri: String,
+}
+
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 struct GeminiGenerationConfig {
@@ -439,22 +740,110 @@ struct GeminiGenerationConfig {
     stop_sequences: Option<Vec<String>>,
 }
 
+#[derive(Debug, Deserialize)]
+struct GeminiModelList {
+    #[serde(default)]
+    models: Vec<GeminiModelEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiModelEntry {
+    name: String,
+    display_name: Option<String>,
+    input_token_limit: Option<usize>,
+    #[serde(default)]
+    supported_generation_methods: Vec<String>,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct GeminiResponse {
+    #[serde(default)]
     candidates: Vec<GeminiCandidate>,
     usage_metadata: Option<GeminiUsageMetadata>,
+    prompt_feedback: Option<GeminiPromptFeedback>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiPromptFeedback {
+    block_reason: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct GeminiCandidate {
+    #[serde(default)]
     content: GeminiResponseContent,
     finish_reason: Option<String>,
+    #[serde(default)]
+    safety_ratings: Vec<GeminiSafetyRating>,
+}
+
+impl GeminiCandidate {
+    fn text(&self) -> String {
+        self.content
+            .parts
+            .iter()
+            .filter_map(|part| {
+                if let GeminiPart::Text { text } = part {
+                    Some(text.clone())
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("")
+    }
+
+    /// Gemini does not id its calls, so each gets a fresh one
+    fn tool_calls(&self) -> Vec<ToolCall> {
+        self.content
+            .parts
+            .iter()
+            .filter_map(|part| match part {
+                GeminiPart::FunctionCall { function_call } => Some(ToolCall::function(
+                    format!("call_{}", uuid::Uuid::new_v4().simple()),
+                    function_call.name.clone(),
+                    function_call.args.to_string(),
+                )),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The finish reason plus the categories that tripped it, when the
+    /// response was withheld by a safety or recitation filter
+    fn blocked_reason(&self) -> Option<String> {
+        let reason = self.finish_reason.as_deref()?;
+        if !BLOCKED_FINISH_REASONS.contains(&reason) {
+            return None;
+        }
+        let categories: Vec<&str> = self
+            .safety_ratings
+            .iter()
+            .filter(|rating| rating.blocked)
+            .map(|rating| rating.category.as_str())
+            .collect();
+        Some(if categories.is_empty() {
+            reason.to_string()
+        } else {
+            format!("{} ({})", reason, categories.join(", "))
+        })
+    }
 }
 
 #[derive(Debug, Deserialize)]
+struct GeminiSafetyRating {
+    category: String,
+    #[serde(default)]
+    blocked: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
 struct GeminiResponseContent {
+    #[serde(default)]
     parts: Vec<GeminiPart>,
 }
 
@@ -467,13 +856,54 @@ struct GeminiUsageMetadata {
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
 struct GeminiStreamResponse {
+    #[serde(default)]
     candidates: Vec<GeminiCandidate>,
+    prompt_feedback: Option<GeminiPromptFeedback>,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ContentPart;
+
+    #[test]
+    fn test_image_parts_serialize_as_gemini_inline_and_file_data() {
+        let provider =
+            GeminiProvider::new(ProviderConfig::new("gemini").with_api_key("test-key")).unwrap();
+        let message = Message::user_with_images(
+            "Describe these",
+            vec![
+                ContentPart::image("data:image/webp;base64,UklGRg==", "image/png"),
+                ContentPart::image("https://example.com/cat.png", "image/png"),
+            ],
+        );
+        let (_, contents) = provider.convert_messages(&[message]);
+
+        assert_eq!(
+            serde_json::to_value(&contents[0].parts).unwrap(),
+            serde_json::json!([
+                {"inline_data": {"mime_type": "image/webp", "data": "UklGRg=="}},
+                {"file_data": {"mime_type": "image/png", "file_uri": "https://example.com/cat.png"}},
+                {"text": "Describe these"}
+            ])
+        );
+    }
+
+    #[test]
+    fn test_api_key_is_sent_as_a_header_not_in_the_url() {
+        let provider =
+            GeminiProvider::new(ProviderConfig::new("gemini").with_api_key("AIza-test-key"))
+                .unwrap();
+        assert!(!provider
+            .build_url("gemini-2.0-flash", false)
+            .contains("AIza-test-key"));
+
+        let headers = GeminiProvider::build_headers("AIza-test-key").unwrap();
+        assert_eq!(headers["x-goog-api-key"], "AIza-test-key");
+        assert!(headers["x-goog-api-key"].is_sensitive());
+    }
 
     #[test]
     fn test_build_capabilities() {
@@ -501,4 +931,72 @@ mod tests {
             FinishReason::ContentFilter
         );
     }
+
+    #[test]
+    fn test_safety_block_is_content_blocked_error() {
+        let body = r#"{
+            "candidates": [{
+                "finishReason": "SAFETY",
+                "index": 0,
+                "safetyRatings": [
+                    {"category": "HARM_CATEGORY_HARASSMENT", "probability": "NEGLIGIBLE"},
+                    {"category": "HARM_CATEGORY_DANGEROUS_CONTENT", "probability": "HIGH", "blocked": true}
+                ]
+            }],
+            "usageMetadata": {"promptTokenCount": 12, "candidatesTokenCount": 0, "totalTokenCount": 12}
+        }"#;
+        let response: GeminiResponse = serde_json::from_str(body).unwrap();
+        let error = GeminiProvider::into_chat_response(response, "gemini-2.5-flash").unwrap_err();
+        assert!(matches!(
+            error,
+            ProviderError::ContentBlocked(ref reason)
+                if reason == "SAFETY (HARM_CATEGORY_DANGEROUS_CONTENT)"
+        ));
+        assert_eq!(error.kind(), "content_blocked");
+        assert!(!error.is_retryable());
+
+        let recitation =
+            r#"{"candidates": [{"content": {"parts": []}, "finishReason": "RECITATION"}]}"#;
+        let response: GeminiResponse = serde_json::from_str(recitation).unwrap();
+        assert!(matches!(
+            GeminiProvider::into_chat_response(response, "gemini-2.5-flash"),
+            Err(ProviderError::ContentBlocked(ref reason)) if reason == "RECITATION"
+        ));
+
+        let prompt_blocked = r#"{"promptFeedback": {"blockReason": "SAFETY"}}"#;
+        assert!(matches!(
+            parse_stream_chunk(prompt_blocked, "gemini-2.5-flash"),
+            Err(ProviderError::ContentBlocked(_))
+        ));
+    }
+
+    #[test]
+    fn test_safety_settings_from_extra() {
+        let mut extra = std::collections::HashMap::new();
+        extra.insert(
+            SAFETY_THRESHOLD_KEY.to_string(),
+            "block_only_high".to_string(),
+        );
+        extra.insert(
+            "safety_dangerous_content".to_string(),
+            "BLOCK_NONE".to_string(),
+        );
+        let settings = safety_settings_from_extra(&extra).unwrap();
+        assert_eq!(settings.len(), SAFETY_CATEGORIES.len());
+        let dangerous = settings
+            .iter()
+            .find(|s| s.category == "HARM_CATEGORY_DANGEROUS_CONTENT")
+            .unwrap();
+        assert_eq!(dangerous.threshold, "BLOCK_NONE");
+        assert!(settings
+            .iter()
+            .filter(|s| s.category != dangerous.category)
+            .all(|s| s.threshold == "BLOCK_ONLY_HIGH"));
+
+        extra.insert("safety_violence".to_string(), "BLOCK_NONE".to_string());
+        assert!(matches!(
+            safety_settings_from_extra(&extra),
+            Err(ProviderError::NotConfigured(_))
+        ));
+    }
 }
@@ -0,0 +1,168 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+const BASE_BACKOFF_SECS: i64 = 30;
+const MAX_BACKOFF_SECS: i64 = 600;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderHealth {
+    pub provider_id: String,
+    pub consecutive_failures: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_failure: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_success: Option<DateTime<Utc>>,
+}
+
+impl ProviderHealth {
+    pub fn new(provider_id: &str) -> Self {
+        Self {
+            provider_id: provider_id.to_string(),
+            consecutive_failures: 0,
+            last_error: None,
+            last_failure: None,
+            last_success: None,
+        }
+    }
+
+    pub fn record_success(&mut self) {
+        self.consecutive_failures = 0;
+        self.last_error = None;
+        self.last_success = Some(Utc::now());
+    }
+
+    pub fn record_failure(&mut self, error: &str) {
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+        self.last_error = Some(error.to_string());
+        self.last_failure = Some(Utc::now());
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        self.consecutive_failures == 0
+    }
+
+    pub fn backoff(&self) -> Duration {
+        if self.consecutive_failures == 0 {
+            return Duration::zero();
+        }
+        let exponent = (self.consecutive_failures - 1).min(10);
+        let secs = BASE_BACKOFF_SECS.saturating_mul(1 << exponent);
+        Duration::seconds(secs.min(MAX_BACKOFF_SECS))
+    }
+
+    pub fn retry_at(&self) -> Option<DateTime<Utc>> {
+        self.last_failure
+            .filter(|_| !self.is_healthy())
+            .map(|failed_at| failed_at + self.backoff())
+    }
+
+    pub fn in_backoff_at(&self, now: DateTime<Utc>) -> bool {
+        self.retry_at().is_some_and(|retry_at| now < retry_at)
+    }
+
+    pub fn in_backoff(&self) -> bool {
+        self.in_backoff_at(Utc::now())
+    }
+
+    pub fn skip_reason(&self) -> String {
+        format!(
+            "{} consecutive failure(s), retrying after {}s backoff: {}",
+            self.consecutive_failures,
+            self.backoff().num_seconds(),
+            self.last_error.as_deref().unwrap_or("unknown error")
+        )
+    }
+}
+
+/// How a health check reached the provider
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthCheckMethod {
+    /// A free endpoint such as the models list, which spends no tokens
+    Endpoint,
+    /// A one-token chat, for providers without a free endpoint
+    Chat,
+}
+
+/// Result of a successful `AIProvider::health_check`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthInfo {
+    pub provider_id: String,
+    pub latency_ms: u64,
+    pub method: HealthCheckMethod,
+    pub checked_at: DateTime<Utc>,
+}
+
+impl HealthInfo {
+    pub fn new(provider_id: &str, latency: std::time::Duration, method: HealthCheckMethod) -> Self {
+        Self {
+            provider_id: provider_id.to_string(),
+            latency_ms: latency.as_millis() as u64,
+            method,
+            checked_at: Utc::now(),
+        }
+    }
+}
+
+/// Time a request to a free endpoint; any non-success status is an error
+pub(crate) async fn check_endpoint(
+    provider_id: &str,
+    request: reqwest::RequestBuilder,
+) -> crate::Result<HealthInfo> {
+    let started = std::time::Instant::now();
+    let response = request.send().await?;
+    if !response.status().is_success() {
+        return Err(crate::ProviderError::from_http_response(response).await);
+    }
+    Ok(HealthInfo::new(
+        provider_id,
+        started.elapsed(),
+        HealthCheckMethod::Endpoint,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_provider_is_healthy() {
+        let health = ProviderHealth::new("claude");
+        assert!(health.is_healthy());
+        assert!(!health.in_backoff());
+        assert_eq!(health.backoff(), Duration::zero());
+    }
+
+    #[test]
+    fn test_backoff_grows_and_caps() {
+        let mut health = ProviderHealth::new("openai");
+        health.record_failure("timeout");
+        assert_eq!(health.backoff(), Duration::seconds(30));
+        health.record_failure("timeout");
+        assert_eq!(health.backoff(), Duration::seconds(60));
+        for _ in 0..20 {
+            health.record_failure("timeout");
+        }
+        assert_eq!(health.backoff(), Duration::seconds(MAX_BACKOFF_SECS));
+    }
+
+    #[test]
+    fn test_backoff_expires() {
+        let mut health = ProviderHealth::new("gemini");
+        health.record_failure("unavailable");
+        assert!(health.in_backoff());
+        assert!(!health.in_backoff_at(Utc::now() + Duration::seconds(31)));
+    }
+
+    #[test]
+    fn test_success_resets_health() {
+        let mut health = ProviderHealth::new("ollama");
+        health.record_failure("connection refused");
+        health.record_success();
+        assert!(health.is_healthy());
+        assert!(health.last_error.is_none());
+        assert!(!health.in_backoff());
+    }
+}
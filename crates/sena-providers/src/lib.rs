@@ -1,9 +1,20 @@
+pub mod coalesce;
 pub mod config;
 pub mod error;
+pub mod features;
+pub mod health;
 pub mod metadata;
+pub mod middleware;
+pub mod pricing;
+pub mod probe;
 pub mod provider;
+pub mod redact;
+pub mod retry;
 pub mod router;
+pub(crate) mod timeout;
 pub mod types;
+pub mod usage;
+pub mod warmup;
 
 #[cfg(feature = "claude")]
 pub mod claude;
@@ -20,14 +31,25 @@ pub mod ollama;
 #[cfg(feature = "mistral")]
 pub mod mistral;
 
-pub use config::{ProviderConfig, ProvidersConfig};
+pub use coalesce::RequestCoalescer;
+pub use config::{ModelAlias, ProviderConfig, ProvidersConfig};
 pub use error::{ProviderError, Result};
+pub use features::{compiled_providers, CapabilitySet, FeatureReport, ProviderFeature};
+pub use health::{HealthCheckMethod, HealthInfo, ProviderHealth};
 pub use metadata::{
-    get_all_provider_metadata, AuthField, AuthSchema, AuthType, FieldType, ProviderMetadata,
+    get_all_provider_metadata, AuthField, AuthSchema, AuthType, FieldType, FormatError,
+    ProviderMetadata,
 };
+pub use middleware::{ProviderMiddleware, SystemPromptMiddleware, TimeoutMiddleware};
+pub use pricing::ModelPrice;
+pub use probe::{CapabilityCache, ModelCapabilities};
 pub use provider::AIProvider;
+pub use redact::redact;
+pub use retry::RetryPolicy;
 pub use router::ProviderRouter;
 pub use types::*;
+pub use usage::{CostSummary, ProviderCost, ProviderUsage, UsageLedger, UsageTotals, UsageTracker};
+pub use warmup::WarmUpResult;
 
 #[cfg(feature = "claude")]
 pub use claude::ClaudeProvider;
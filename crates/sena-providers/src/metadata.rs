@@ -1,4 +1,6 @@
+use regex::Regex;
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProviderMetadata {
@@ -39,6 +41,33 @@ pub struct AuthField {
     pub default_value: Option<String>,
     pub env_var_name: Option<String>,
     pub validation_pattern: Option<String>,
+    /// Shortest value accepted, checked before `validation_pattern` so a
+    /// truncated key gets a clearer error
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_length: Option<usize>,
+}
+
+/// Why a credential value doesn't have the shape its field expects
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum FormatError {
+    #[error("{field} is empty")]
+    Empty { field: String },
+
+    #[error("{field} contains whitespace; check it was copied without line breaks")]
+    Whitespace { field: String },
+
+    #[error("{field} should start with '{prefix}'")]
+    WrongPrefix { field: String, prefix: String },
+
+    #[error("{field} is too short: {len} characters, expected at least {min}")]
+    TooShort {
+        field: String,
+        len: usize,
+        min: usize,
+    },
+
+    #[error("{field} does not match the expected format ({pattern})")]
+    Mismatch { field: String, pattern: String },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -129,6 +158,7 @@ impl AuthField {
             default_value: None,
             env_var_name: Some(env_var.to_string()),
             validation_pattern: None,
+            min_length: None,
         }
     }
 
@@ -144,6 +174,7 @@ impl AuthField {
             default_value: default.map(|s| s.to_string()),
             env_var_name: None,
             validation_pattern: Some(r"^https?://".to_string()),
+            min_length: None,
         }
     }
 
@@ -161,6 +192,64 @@ impl AuthField {
         self.validation_pattern = Some(pattern.to_string());
         self
     }
+
+    pub fn with_min_length(mut self, min_length: usize) -> Self {
+        self.min_length = Some(min_length);
+        self
+    }
+
+    /// Check `value` against this field's shape without contacting the
+    /// provider. The most specific problem found is reported: whitespace,
+    /// then the literal prefix of `validation_pattern`, then `min_length`,
+    /// then the full pattern. An unparsable pattern accepts everything.
+    pub fn validate_format(&self, value: &str) -> Result<(), FormatError> {
+        let field = self.display_name.clone();
+        if value.trim().is_empty() {
+            return Err(FormatError::Empty { field });
+        }
+        if value.chars().any(char::is_whitespace) {
+            return Err(FormatError::Whitespace { field });
+        }
+
+        let pattern = self.validation_pattern.as_deref().unwrap_or("");
+        let prefix = literal_prefix(pattern);
+        if !value.starts_with(&prefix) {
+            return Err(FormatError::WrongPrefix { field, prefix });
+        }
+
+        let len = value.chars().count();
+        if let Some(min) = self.min_length.filter(|&min| len < min) {
+            return Err(FormatError::TooShort { field, len, min });
+        }
+
+        match Regex::new(pattern) {
+            Ok(regex) if !regex.is_match(value) => Err(FormatError::Mismatch {
+                field,
+                pattern: pattern.to_string(),
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Text an anchored pattern requires at the start, e.g. `sk-` for
+/// `^sk-[A-Za-z0-9]+$`
+fn literal_prefix(pattern: &str) -> String {
+    let Some(rest) = pattern.strip_prefix('^') else {
+        return String::new();
+    };
+    let mut prefix: String = rest
+        .chars()
+        .take_while(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | ':' | '/'))
+        .collect();
+    // A quantifier right after the prefix applies to its last character
+    if matches!(
+        rest[prefix.len()..].chars().next(),
+        Some('?' | '*' | '{' | '+')
+    ) {
+        prefix.pop();
+    }
+    prefix
 }
 
 pub fn claude_metadata() -> ProviderMetadata {
@@ -185,7 +274,8 @@ pub fn openai_metadata() -> ProviderMetadata {
         )
         .with_placeholder("sk-...")
         .with_help_text("Get your API key from platform.openai.com/api-keys")
-        .with_validation(r"^sk-")]))
+        .with_validation(r"^sk-[A-Za-z0-9_-]+$")
+        .with_min_length(40)]))
 }
 
 pub fn gemini_metadata() -> ProviderMetadata {
@@ -197,9 +287,10 @@ pub fn gemini_metadata() -> ProviderMetadata {
         .with_auth_schema(AuthSchema::api_key(vec![AuthField::api_key(
             "GOOGLE_API_KEY",
         )
-        .with_placeholder("AI...")
+        .with_placeholder("AIza...")
         .with_help_text("Get your API key from aistudio.google.com/apikey")
-        .with_validation(r"^AI")]))
+        .with_validation(r"^AIza[A-Za-z0-9_-]+$")
+        .with_min_length(39)]))
 }
 
 pub fn ollama_metadata() -> ProviderMetadata {
@@ -269,4 +360,52 @@ mod tests {
         let all = get_all_provider_metadata();
         assert_eq!(all.len(), 5);
     }
+
+    #[test]
+    fn test_openai_key_format() {
+        let meta = openai_metadata();
+        let field = &meta.auth_schema.fields[0];
+
+        let key = format!("sk-proj-{}", "aB3_dE6-gH9".repeat(5));
+        assert_eq!(field.validate_format(&key), Ok(()));
+
+        assert!(matches!(
+            field.validate_format("pk-0123456789abcdef0123456789abcdef01234567"),
+            Err(FormatError::WrongPrefix { ref prefix, .. }) if prefix == "sk-"
+        ));
+        assert!(matches!(
+            field.validate_format("sk-proj-abc123"),
+            Err(FormatError::TooShort {
+                len: 14,
+                min: 40,
+                ..
+            })
+        ));
+        assert!(matches!(
+            field.validate_format(&format!("{}\n{}", &key[..20], &key[20..])),
+            Err(FormatError::Whitespace { .. })
+        ));
+        assert!(matches!(
+            field.validate_format(&format!("{}!", key)),
+            Err(FormatError::Mismatch { .. })
+        ));
+        assert_eq!(
+            field
+                .validate_format("sk-proj-abc123")
+                .unwrap_err()
+                .to_string(),
+            "API Key is too short: 14 characters, expected at least 40"
+        );
+    }
+
+    #[test]
+    fn test_url_field_format() {
+        let meta = ollama_metadata();
+        let field = &meta.auth_schema.fields[0];
+        assert_eq!(field.validate_format("http://localhost:11434"), Ok(()));
+        assert!(matches!(
+            field.validate_format("localhost:11434"),
+            Err(FormatError::WrongPrefix { ref prefix, .. }) if prefix == "http"
+        ));
+    }
 }
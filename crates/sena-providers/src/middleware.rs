@@ -0,0 +1,132 @@
+use std::time::Duration;
+
+use crate::{config::ProvidersConfig, ChatRequest, ChatResponse, Message, Result, Role};
+
+pub trait ProviderMiddleware: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn on_request(&self, _request: &mut ChatRequest) -> Result<()> {
+        Ok(())
+    }
+
+    fn on_response(&self, _response: &mut ChatResponse) -> Result<()> {
+        Ok(())
+    }
+}
+
+pub struct SystemPromptMiddleware {
+    prompt: String,
+}
+
+impl SystemPromptMiddleware {
+    pub fn new(prompt: impl Into<String>) -> Self {
+        Self {
+            prompt: prompt.into(),
+        }
+    }
+
+    pub fn from_config(config: &ProvidersConfig) -> Option<Self> {
+        config
+            .system_prompt
+            .as_deref()
+            .map(str::trim)
+            .filter(|prompt| !prompt.is_empty())
+            .map(Self::new)
+    }
+}
+
+impl ProviderMiddleware for SystemPromptMiddleware {
+    fn name(&self) -> &str {
+        "system_prompt"
+    }
+
+    fn on_request(&self, request: &mut ChatRequest) -> Result<()> {
+        let already_present = request
+            .messages
+            .iter()
+            .any(|m| m.role == Role::System && m.content.as_text() == Some(self.prompt.as_str()));
+        if !already_present {
+            request
+                .messages
+                .insert(0, Message::system(self.prompt.clone()));
+        }
+        Ok(())
+    }
+}
+
+/// Gives requests without a timeout of their own the configured default
+pub struct TimeoutMiddleware {
+    timeout: Duration,
+}
+
+impl TimeoutMiddleware {
+    pub fn new(timeout: Duration) -> Self {
+        Self { timeout }
+    }
+
+    pub fn from_config(config: &ProvidersConfig) -> Option<Self> {
+        config
+            .request_timeout_secs
+            .filter(|&secs| secs > 0)
+            .map(|secs| Self::new(Duration::from_secs(secs)))
+    }
+}
+
+impl ProviderMiddleware for TimeoutMiddleware {
+    fn name(&self) -> &str {
+        "timeout"
+    }
+
+    fn on_request(&self, request: &mut ChatRequest) -> Result<()> {
+        request.timeout.get_or_insert(self.timeout);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_system_prompt_prepended_once() {
+        let middleware = SystemPromptMiddleware::new("Be concise.");
+        let mut request = ChatRequest::new(vec![Message::user("hi")]);
+
+        middleware.on_request(&mut request).unwrap();
+        middleware.on_request(&mut request).unwrap();
+
+        assert_eq!(request.messages.len(), 2);
+        assert_eq!(request.messages[0].role, Role::System);
+        assert_eq!(request.messages[0].content.as_text(), Some("Be concise."));
+    }
+
+    #[test]
+    fn test_from_config_ignores_blank_prompt() {
+        let mut config = ProvidersConfig::new();
+        assert!(SystemPromptMiddleware::from_config(&config).is_none());
+
+        config.system_prompt = Some("   ".into());
+        assert!(SystemPromptMiddleware::from_config(&config).is_none());
+
+        config.system_prompt = Some("You are SENA.".into());
+        assert!(SystemPromptMiddleware::from_config(&config).is_some());
+    }
+
+    #[test]
+    fn test_timeout_default_keeps_request_timeout() {
+        let mut config = ProvidersConfig::new();
+        assert!(TimeoutMiddleware::from_config(&config).is_none());
+
+        config.request_timeout_secs = Some(30);
+        let middleware = TimeoutMiddleware::from_config(&config).unwrap();
+
+        let mut request = ChatRequest::new(vec![Message::user("hi")]);
+        middleware.on_request(&mut request).unwrap();
+        assert_eq!(request.timeout, Some(Duration::from_secs(30)));
+
+        let mut request =
+            ChatRequest::new(vec![Message::user("hi")]).with_timeout(Duration::from_secs(5));
+        middleware.on_request(&mut request).unwrap();
+        assert_eq!(request.timeout, Some(Duration::from_secs(5)));
+    }
+}
@@ -4,14 +4,18 @@ use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    health::{check_endpoint, HealthInfo},
     metadata::{mistral_metadata, ProviderMetadata},
-    provider::{AIProvider, ChatStream},
+    provider::{ensure_vision, AIProvider, ChatStream},
+    timeout::{idle_timeout, with_timeout, RequestTimeout},
+    warmup::{open_connection, shared_client},
     ChatRequest, ChatResponse, FinishReason, Message, MessageContent, ModelInfo,
     ProviderCapabilities, ProviderConfig, ProviderError, ProviderStatus, Result, Role, StreamChunk,
-    ToolCall, ToolCallFunction, Usage,
+    Tool, ToolCall, ToolCallFunction, Usage,
 };
 
 const MISTRAL_API_URL: &str = "https://api.mistral.ai/v1/chat/completions";
+const MISTRAL_MODELS_URL: &str = "https://api.mistral.ai/v1/models";
 
 pub struct MistralProvider {
     client: Client,
@@ -25,14 +29,13 @@ impl MistralProvider {
         let api_key = config
             .get_api_key()
             .ok_or_else(|| ProviderError::NotConfigured("MISTRAL_API_KEY not set".into()))?;
+        // Masked in every error from here on, wherever the API echoes it
+        crate::redact::register_secret(&api_key);
 
-        let client = Client::builder()
-            .default_headers(Self::build_headers(&api_key)?)
-            .timeout(std::time::Duration::from_secs(
-                config.timeout_secs.unwrap_or(120),
-            ))
-            .build()
-            .map_err(|e| ProviderError::NetworkError(e.to_string()))?;
+        let client = shared_client(
+            config.timeout_secs.unwrap_or(120),
+            Self::build_headers(&api_key)?,
+        )?;
 
         let capabilities = Self::build_capabilities();
 
@@ -78,6 +81,8 @@ impl MistralProvider {
                     supports_vision: true,
                     supports_tools: true,
                     supports_streaming: true,
+                    price_per_1k_input: None,
+                    price_per_1k_output: None,
                 },
                 ModelInfo {
                     id: "mistral-medium-latest".into(),
@@ -87,6 +92,8 @@ impl MistralProvider {
                     supports_vision: false,
                     supports_tools: true,
                     supports_streaming: true,
+                    price_per_1k_input: None,
+                    price_per_1k_output: None,
                 },
                 ModelInfo {
                     id: "mistral-small-latest".into(),
@@ -96,6 +103,8 @@ impl MistralProvider {
                     supports_vision: false,
                     supports_tools: true,
                     supports_streaming: true,
+                    price_per_1k_input: None,
+                    price_per_1k_output: None,
                 },
                 ModelInfo {
                     id: "codestral-latest".into(),
@@ -105,6 +114,8 @@ impl MistralProvider {
                     supports_vision: false,
                     supports_tools: true,
                     supports_streaming: true,
+                    price_per_1k_input: None,
+                    price_per_1k_output: None,
                 },
                 ModelInfo {
                     id: "open-mistral-nemo".into(),
@@ -114,8 +125,13 @@ impl MistralProvider {
                     supports_vision: false,
                     supports_tools: true,
                     supports_streaming: true,
+                    price_per_1k_input: None,
+                    price_per_1k_output: None,
                 },
-            ],
+            ]
+            .into_iter()
+            .map(ModelInfo::with_pricing)
+            .collect(),
         }
     }
 
@@ -125,6 +141,9 @@ impl MistralProvider {
             .map(|msg| MistralMessage {
                 role: Self::convert_role(&msg.role),
                 content: Self::convert_content(&msg.content),
+                name: msg.name.clone().filter(|_| msg.role == Role::Tool),
+                tool_calls: msg.tool_calls.clone(),
+                tool_call_id: msg.tool_call_id.clone(),
             })
             .collect()
     }
@@ -148,10 +167,10 @@ impl MistralProvider {
                         crate::ContentPart::Text { text } => {
                             MistralContentPart::Text { text: text.clone() }
                         }
-                        crate::ContentPart::ImageUrl { image_url } => {
+                        crate::ContentPart::ImageUrl { .. } | crate::ContentPart::Image { .. } => {
                             MistralContentPart::ImageUrl {
                                 image_url: MistralImageUrl {
-                                    url: image_url.url.clone(),
+                                    url: part.image_data().map(|i| i.to_url()).unwrap_or_default(),
                                 },
                             }
                         }
@@ -206,54 +225,82 @@ impl AIProvider for MistralProvider {
         mistral_metadata()
     }
 
-    async fn chat(&self, request: ChatRequest) -> Result<ChatResponse> {
-        let model = request
-            .model
-            .as_deref()
-            .unwrap_or_else(|| self.default_model());
-        let messages = self.convert_messages(&request.messages);
-
-        let mistral_request = MistralRequest {
-            model: model.into(),
-            messages,
-            max_tokens: request.max_tokens,
-            temperature: request.temperature,
-            top_p: request.top_p,
-            stream: false,
-        };
-
-        let response = self
-            .client
-            .post(MISTRAL_API_URL)
-            .json(&mistral_request)
-            .send()
-            .await?;
-
+    async fn list_models_remote(&self) -> Result<Vec<ModelInfo>> {
+        let response = self.client.get(MISTRAL_MODELS_URL).send().await?;
         if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_default();
-
-            return match status.as_u16() {
-                401 => Err(ProviderError::AuthenticationFailed(error_text)),
-                429 => Err(ProviderError::RateLimited {
-                    retry_after_secs: 60,
-                }),
-                _ => Err(ProviderError::RequestFailed(format!(
-                    "{}: {}",
-                    status, error_text
-                ))),
-            };
+            return Err(ProviderError::from_http_response(response).await);
         }
 
-        let mistral_response: MistralResponse = response.json().await?;
-        let choice = mistral_response
-            .choices
-            .first()
-            .ok_or_else(|| ProviderError::InvalidResponse("No choices in response".into()))?;
+        let list: MistralModelList = response.json().await?;
+        let mut models: Vec<ModelInfo> = list
+            .data
+            .into_iter()
+            .filter(|entry| entry.capabilities.completion_chat)
+            .map(|entry| {
+                let mut model = self.capabilities.model_info("mistral", &entry.id);
+                if let Some(context_length) = entry.max_context_length.filter(|n| *n > 0) {
+                    model.context_length = context_length;
+                }
+                model.supports_tools = entry.capabilities.function_calling;
+                model.supports_vision = entry.capabilities.vision;
+                model
+            })
+            .collect();
+        models.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(models)
+    }
+
+    async fn warm_up(&self) -> Result<()> {
+        open_connection(&self.client, MISTRAL_API_URL).await
+    }
+
+    async fn health_check(&self) -> Result<HealthInfo> {
+        check_endpoint(self.provider_id(), self.client.get(MISTRAL_MODELS_URL)).await
+    }
+
+    async fn chat(&self, request: ChatRequest) -> Result<ChatResponse> {
+        let timeout = request.timeout;
+        with_timeout(timeout, async move {
+            let model = request
+                .model
+                .as_deref()
+                .unwrap_or_else(|| self.default_model());
+            ensure_vision(self, &request, model)?;
+            let messages = self.convert_messages(&request.messages);
+
+            let mistral_request = MistralRequest {
+                model: model.into(),
+                messages,
+                max_tokens: request.max_tokens,
+                temperature: request.temperature,
+                top_p: request.top_p,
+                tools: request.tools.filter(|t| !t.is_empty()),
+                stream: false,
+            };
+
+            let response = self
+                .client
+                .post(MISTRAL_API_URL)
+                .request_timeout(timeout)
+                .json(&mistral_request)
+                .send()
+                .await?;
+
+            if !response.status().is_success() {
+                return Err(ProviderError::from_http_response(response).await);
+            }
+
+            let mistral_response: MistralResponse = response.json().await?;
+            let choice = mistral_response
+                .choices
+                .first()
+                .ok_or_else(|| ProviderError::InvalidResponse("No choices in response".into()))?;
 
-        let tool_calls = choice.message.tool_calls.as_ref().map(|calls| {
-            calls
+            let tool_calls = choice
+                .message
+                .tool_calls
                 .iter()
+                .flatten()
                 .map(|tc| ToolCall {
                     id: tc.id.clone(),
                     call_type: tc.call_type.clone(),
@@ -262,80 +309,113 @@ impl AIProvider for MistralProvider {
                         arguments: tc.function.arguments.clone(),
                     },
                 })
-                .collect()
-        });
-
-        Ok(ChatResponse {
-            id: mistral_response.id,
-            provider: "mistral".into(),
-            model: mistral_response.model,
-            content: choice.message.content.clone().unwrap_or_default(),
-            role: Role::Assistant,
-            tool_calls,
-            usage: Usage {
-                prompt_tokens: mistral_response.usage.prompt_tokens,
-                completion_tokens: mistral_response.usage.completion_tokens,
-                total_tokens: mistral_response.usage.total_tokens,
-            },
-            created_at: chrono::Utc::now(),
-            finish_reason: choice
-                .finish_reason
-                .as_ref()
-                .map(|r| Self::parse_finish_reason(r)),
+                .collect();
+
+            Ok(ChatResponse {
+                id: mistral_response.id,
+                provider: "mistral".into(),
+                model: mistral_response.model,
+                content: choice.message.content.clone().unwrap_or_default(),
+                role: Role::Assistant,
+                tool_calls,
+                usage: Usage {
+                    prompt_tokens: mistral_response.usage.prompt_tokens,
+                    completion_tokens: mistral_response.usage.completion_tokens,
+                    total_tokens: mistral_response.usage.total_tokens,
+                },
+                created_at: chrono::Utc::now(),
+                finish_reason: choice
+                    .finish_reason
+                    .as_ref()
+                    .map(|r| Self::parse_finish_reason(r)),
+                skipped_providers: Vec::new(),
+            })
         })
+        .await
     }
 
     async fn chat_stream(&self, request: ChatRequest) -> Result<ChatStream> {
-        let model = request
-            .model
-            .as_deref()
-            .unwrap_or_else(|| self.default_model());
-        let messages = self.convert_messages(&request.messages);
-
-        let mistral_request = MistralRequest {
-            model: model.into(),
-            messages,
-            max_tokens: request.max_tokens,
-            temperature: request.temperature,
-            top_p: request.top_p,
-            stream: true,
-        };
-
-        let response = self
-            .client
-            .post(MISTRAL_API_URL)
-            .json(&mistral_request)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_default();
-
-            return match status.as_u16() {
-                401 => Err(ProviderError::AuthenticationFailed(error_text)),
-                429 => Err(ProviderError::RateLimited {
-                    retry_after_secs: 60,
-                }),
-                _ => Err(ProviderError::RequestFailed(format!(
-                    "{}: {}",
-                    status, error_text
-                ))),
+        let timeout = request.timeout;
+        let stream = with_timeout(timeout, async move {
+            let model = request
+                .model
+                .as_deref()
+                .unwrap_or_else(|| self.default_model());
+            ensure_vision(self, &request, model)?;
+            let messages = self.convert_messages(&request.messages);
+
+            let mistral_request = MistralRequest {
+                model: model.into(),
+                messages,
+                max_tokens: request.max_tokens,
+                temperature: request.temperature,
+                top_p: request.top_p,
+                tools: request.tools.filter(|t| !t.is_empty()),
+                stream: true,
             };
-        }
 
-        let model_clone = model.to_string();
-        let stream = response.bytes_stream().map(move |chunk| {
-            let chunk = chunk.map_err(|e| ProviderError::StreamingError(e.to_string()))?;
-            let text = String::from_utf8_lossy(&chunk);
+            let response = self
+                .client
+                .post(MISTRAL_API_URL)
+                .json(&mistral_request)
+                .send()
+                .await?;
 
-            parse_sse_chunk(&text, &model_clone)
-        });
+            if !response.status().is_success() {
+                return Err(ProviderError::from_http_response(response).await);
+            }
+
+            let model_clone = model.to_string();
+            let stream = response.bytes_stream().map(move |chunk| {
+                let chunk = chunk.map_err(|e| ProviderError::StreamingError(e.to_string()))?;
+                let text = String::from_utf8_lossy(&chunk);
+
+                parse_sse_chunk(&text, &model_clone)
+            });
 
-        Ok(Box::pin(stream))
+            Ok(Box::pin(stream))
+        })
+        .await?;
+        Ok(idle_timeout(stream, timeout))
     }
 }
 
+#[derive(Debug, Deserialize)]
+struct MistralModelList {
+    data: Vec<MistralModelEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MistralModelEntry {
+    id: String,
+    #[serde(default)]
+    max_context_length: Option<usize>,
+    #[serde(default)]
+    capabilities: MistralModelCapabilities,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+struct MistralModelCapabilities {
+    completion_chat: bool,
+    function_calling: bool,
+    vision: bool,
+}
+
+impl Default for MistralModelCapabilities {
+    fn default() -> Self {
+        Self {
+            completion_chat: true,
+            function_calling: false,
+            vision: false,
+        }
+    }
+}
+
+fn function_call_type() -> String {
+    "function".into()
+}
+
 fn parse_sse_chunk(text: &str, model: &str) -> Result<StreamChunk> {
     for line in text.lines() {
         if let Some(data) = line.strip_prefix("data: ") {
@@ -395,6 +475,8 @@ struct MistralRequest {
     temperature: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tools: Option<Vec<Tool>>,
     stream: bool,
 }
 
@@ -402,6 +484,12 @@ struct MistralRequest {
 struct MistralMessage {
     role: String,
     content: MistralContent,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tool_calls: Vec<ToolCall>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_call_id: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -452,7 +540,7 @@ struct MistralResponseMessage {
 #[derive(Debug, Deserialize)]
 struct MistralToolCall {
     id: String,
-    #[serde(rename = "type")]
+    #[serde(rename = "type", default = "function_call_type")]
     call_type: String,
     function: MistralFunctionCall,
 }
@@ -1,17 +1,48 @@
+use std::pin::Pin;
+use std::time::Duration;
+
 use async_trait::async_trait;
-use futures::StreamExt;
+use futures::{future, stream, Stream, StreamExt};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    health::{check_endpoint, HealthInfo},
     metadata::{ollama_metadata, ProviderMetadata},
-    provider::{AIProvider, ChatStream},
-    ChatRequest, ChatResponse, FinishReason, Message, MessageContent, ModelInfo,
+    provider::{ensure_vision, AIProvider, ChatStream},
+    timeout::{idle_timeout, with_timeout, RequestTimeout},
+    warmup::shared_client,
+    ChatRequest, ChatResponse, FinishReason, ImageData, Message, MessageContent, ModelInfo,
     ProviderCapabilities, ProviderConfig, ProviderError, ProviderStatus, Result, Role, StreamChunk,
-    Usage,
+    Tool, ToolCall, Usage,
 };
 
 const DEFAULT_OLLAMA_URL: &str = "http://localhost:11434";
+const KEEP_ALIVE_KEY: &str = "keep_alive";
+/// `extra` key giving the keep-alive in seconds; `keep_alive` wins if both are set
+const KEEP_ALIVE_SECS_KEY: &str = "keep_alive_secs";
+/// Upper bound for a whole model download
+const PULL_TIMEOUT: Duration = Duration::from_secs(6 * 60 * 60);
+/// A pull that sends nothing for this long is treated as interrupted
+const PULL_IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Progress lines of a model pull, ending with status `success`
+pub type PullStream = Pin<Box<dyn Stream<Item = Result<PullProgress>> + Send>>;
+
+/// One status line of `/api/pull`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PullProgress {
+    pub status: String,
+    /// Share of the current layer downloaded, when Ollama reports sizes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub percent: Option<f64>,
+}
+
+impl PullProgress {
+    pub fn is_done(&self) -> bool {
+        self.status == "success"
+    }
+}
 
 pub struct OllamaProvider {
     client: Client,
@@ -19,16 +50,15 @@ pub struct OllamaProvider {
     capabilities: ProviderCapabilities,
     status: ProviderStatus,
     base_url: String,
+    keep_alive: Option<String>,
 }
 
 impl OllamaProvider {
     pub fn new(config: ProviderConfig) -> Result<Self> {
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(
-                config.timeout_secs.unwrap_or(300),
-            ))
-            .build()
-            .map_err(|e| ProviderError::NetworkError(e.to_string()))?;
+        let client = shared_client(
+            config.timeout_secs.unwrap_or(300),
+            reqwest::header::HeaderMap::new(),
+        )?;
 
         let base_url = config
             .base_url
@@ -36,6 +66,11 @@ impl OllamaProvider {
             .unwrap_or_else(|| DEFAULT_OLLAMA_URL.into());
 
         let capabilities = Self::build_capabilities();
+        let keep_alive = config
+            .extra
+            .get(KEEP_ALIVE_KEY)
+            .or_else(|| config.extra.get(KEEP_ALIVE_SECS_KEY))
+            .cloned();
 
         Ok(Self {
             client,
@@ -43,9 +78,100 @@ impl OllamaProvider {
             capabilities,
             status: ProviderStatus::Connected,
             base_url,
+            keep_alive,
         })
     }
 
+    /// How long Ollama keeps the model loaded after a request, e.g. `"30m"`,
+    /// `"3600"` (seconds) or `"-1"` (forever). Defaults to `extra.keep_alive`,
+    /// then `extra.keep_alive_secs`.
+    pub fn with_keep_alive(mut self, keep_alive: impl Into<String>) -> Self {
+        self.keep_alive = Some(keep_alive.into());
+        self
+    }
+
+    pub fn with_keep_alive_secs(self, secs: u64) -> Self {
+        self.with_keep_alive(secs.to_string())
+    }
+
+    /// Load `model` into memory ahead of the first chat with an empty chat
+    /// request, keeping it resident for the configured keep-alive. This is
+    /// [`warm_up`](AIProvider::warm_up) for a model other than the default.
+    /// A model that hasn't been pulled yet fails with `ModelNotFound`.
+    pub async fn preload(&self, model: &str) -> Result<()> {
+        let load_request = OllamaRequest {
+            model: model.into(),
+            messages: Vec::new(),
+            stream: false,
+            keep_alive: self.keep_alive_value(),
+            options: None,
+            tools: None,
+        };
+
+        let response = self
+            .client
+            .post(self.chat_url())
+            .json(&load_request)
+            .send()
+            .await
+            .map_err(|e| {
+                if e.is_connect() {
+                    ProviderError::Unavailable(format!("Ollama not running at {}", self.base_url))
+                } else {
+                    ProviderError::NetworkError(e.to_string())
+                }
+            })?;
+
+        if !response.status().is_success() {
+            return Err(ProviderError::from_http_response(response).await);
+        }
+        Ok(())
+    }
+
+    fn keep_alive_value(&self) -> Option<serde_json::Value> {
+        let keep_alive = self.keep_alive.as_deref()?.trim();
+        if keep_alive.is_empty() {
+            return None;
+        }
+        Some(
+            keep_alive
+                .parse::<i64>()
+                .map(serde_json::Value::from)
+                .unwrap_or_else(|_| serde_json::Value::from(keep_alive)),
+        )
+    }
+
+    /// Download `name` from the Ollama library, streaming its progress. The
+    /// stream ends after the `success` line; a dropped or stalled connection
+    /// ends it with an error instead.
+    pub async fn pull_model(&self, name: &str) -> Result<PullStream> {
+        let response = self
+            .client
+            .post(format!("{}/api/pull", self.base_url))
+            .timeout(PULL_TIMEOUT)
+            .json(&serde_json::json!({ "model": name, "stream": true }))
+            .send()
+            .await
+            .map_err(|e| {
+                if e.is_connect() {
+                    ProviderError::Unavailable(format!("Ollama not running at {}", self.base_url))
+                } else {
+                    ProviderError::NetworkError(e.to_string())
+                }
+            })?;
+
+        if !response.status().is_success() {
+            return Err(ProviderError::from_http_response(response).await);
+        }
+
+        let lines = ndjson_lines(
+            Box::pin(response.bytes_stream()),
+            Some(PULL_IDLE_TIMEOUT),
+            parse_pull_line,
+        );
+        Ok(Box::pin(until_pulled(lines)))
+    }
+
     fn build_capabilities() -> ProviderCapabilities {
         ProviderCapabilities {
             streaming: true,
@@ -62,6 +188,8 @@ impl OllamaProvider {
                     supports_vision: true,
                     supports_tools: true,
                     supports_streaming: true,
+                    price_per_1k_input: None,
+                    price_per_1k_output: None,
                 },
                 ModelInfo {
                     id: "llama3.2:70b".into(),
@@ -71,6 +199,8 @@ impl OllamaProvider {
                     supports_vision: true,
                     supports_tools: true,
                     supports_streaming: true,
+                    price_per_1k_input: None,
+                    price_per_1k_output: None,
                 },
                 ModelInfo {
                     id: "mistral".into(),
@@ -80,6 +210,8 @@ impl OllamaProvider {
                     supports_vision: false,
                     supports_tools: true,
                     supports_streaming: true,
+                    price_per_1k_input: None,
+                    price_per_1k_output: None,
                 },
                 ModelInfo {
                     id: "codellama".into(),
@@ -89,6 +221,8 @@ impl OllamaProvider {
                     supports_vision: false,
                     supports_tools: false,
                     supports_streaming: true,
+                    price_per_1k_input: None,
+                    price_per_1k_output: None,
                 },
                 ModelInfo {
                     id: "deepseek-r1".into(),
@@ -98,6 +232,8 @@ impl OllamaProvider {
                     supports_vision: false,
                     supports_tools: true,
                     supports_streaming: true,
+                    price_per_1k_input: None,
+                    price_per_1k_output: None,
                 },
                 ModelInfo {
                     id: "qwen2.5".into(),
@@ -107,8 +243,13 @@ impl OllamaProvider {
                     supports_vision: true,
                     supports_tools: true,
                     supports_streaming: true,
+                    price_per_1k_input: None,
+                    price_per_1k_output: None,
                 },
-            ],
+            ]
+            .into_iter()
+            .map(ModelInfo::with_pricing)
+            .collect(),
         }
     }
 
@@ -116,13 +257,60 @@ impl OllamaProvider {
         format!("{}/api/chat", self.base_url)
     }
 
-    fn convert_messages(&self, messages: &[Message]) -> Vec<OllamaMessage> {
+    /// Context length and capabilities from `/api/show`, which newer Ollama
+    /// versions report per model. Missing details keep the static defaults.
+    async fn describe_model(&self, name: &str) -> ModelInfo {
+        let id = name.strip_suffix(":latest").unwrap_or(name);
+        let mut model = self.capabilities.model_info("ollama", id);
+
+        let show = self
+            .client
+            .post(format!("{}/api/show", self.base_url))
+            .json(&serde_json::json!({ "model": name }))
+            .send()
+            .await;
+        let details = match show {
+            Ok(response) if response.status().is_success() => {
+                response.json::<OllamaShowResponse>().await.ok()
+            }
+            _ => None,
+        };
+        let Some(details) = details else {
+            return model;
+        };
+
+        if let Some(context_length) = details.context_length() {
+            model.context_length = context_length;
+        }
+        if let Some(capabilities) = details.capabilities {
+            model.supports_tools = capabilities.iter().any(|c| c == "tools");
+            model.supports_vision = capabilities.iter().any(|c| c == "vision");
+        }
+        model
+    }
+
+    fn convert_messages(&self, messages: &[Message]) -> Result<Vec<OllamaMessage>> {
         messages
             .iter()
-            .map(|msg| OllamaMessage {
-                role: Self::convert_role(&msg.role),
-                content: Self::extract_text(&msg.content),
-                images: Self::extract_images(&msg.content),
+            .map(|msg| {
+                Ok(OllamaMessage {
+                    role: Self::convert_role(&msg.role),
+                    content: Self::extract_text(&msg.content),
+                    images: Self::extract_images(&msg.content)?,
+                    tool_calls: msg
+                        .tool_calls
+                        .iter()
+                        .map(|call| OllamaToolCall {
+                            function: OllamaFunctionCall {
+                                name: call.function.name.clone(),
+                                arguments: call
+                                    .arguments()
+                                    .unwrap_or_else(|_| serde_json::json!({})),
+                            },
+                        })
+                        .collect(),
+                    tool_name: msg.name.clone().filter(|_| msg.role == Role::Tool),
+                })
             })
             .collect()
     }
@@ -153,26 +341,29 @@ impl OllamaProvider {
         }
     }
 
-    fn extract_images(content: &MessageContent) -> Option<Vec<String>> {
+    /// Ollama only takes inline base64 images, so URL images are rejected
+    /// rather than sent where bytes are expected.
+    fn extract_images(content: &MessageContent) -> Result<Option<Vec<String>>> {
         match content {
-            MessageContent::Text(_) => None,
+            MessageContent::Text(_) => Ok(None),
             MessageContent::Parts(parts) => {
-                let images: Vec<String> = parts
+                let images = parts
                     .iter()
-                    .filter_map(|part| {
-                        if let crate::ContentPart::ImageUrl { image_url } = part {
-                            Some(image_url.url.clone())
-                        } else {
-                            None
-                        }
+                    .filter_map(|part| part.image_data())
+                    .map(|image| match image {
+                        ImageData::Base64 { data, .. } => Ok(data.to_string()),
+                        ImageData::Url { url, .. } => Err(ProviderError::Unsupported(format!(
+                            "Ollama cannot fetch image URLs ({}); attach the image as base64",
+                            url
+                        ))),
                     })
-                    .collect();
+                    .collect::<Result<Vec<String>>>()?;
 
-                if images.is_empty() {
+                Ok(if images.is_empty() {
                     None
                 } else {
                     Some(images)
-                }
+                })
             }
         }
     }
@@ -208,29 +399,10 @@ impl AIProvider for OllamaProvider {
         ollama_metadata()
     }
 
-    async fn chat(&self, request: ChatRequest) -> Result<ChatResponse> {
-        let model = request
-            .model
-            .as_deref()
-            .unwrap_or_else(|| self.default_model());
-        let messages = self.convert_messages(&request.messages);
-
-        let ollama_request = OllamaRequest {
-            model: model.into(),
-            messages,
-            stream: false,
-            options: Some(OllamaOptions {
-                temperature: request.temperature,
-                top_p: request.top_p,
-                num_predict: request.max_tokens.map(|t| t as i32),
-                stop: request.stop,
-            }),
-        };
-
+    async fn list_models_remote(&self) -> Result<Vec<ModelInfo>> {
         let response = self
             .client
-            .post(self.chat_url())
-            .json(&ollama_request)
+            .get(format!("{}/api/tags", self.base_url))
             .send()
             .await
             .map_err(|e| {
@@ -242,136 +414,342 @@ impl AIProvider for OllamaProvider {
             })?;
 
         if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_default();
-
-            return Err(ProviderError::RequestFailed(format!(
-                "{}: {}",
-                status, error_text
-            )));
+            return Err(ProviderError::from_http_response(response).await);
         }
 
-        let ollama_response: OllamaResponse = response.json().await?;
-
-        Ok(ChatResponse {
-            id: uuid::Uuid::new_v4().to_string(),
-            provider: "ollama".into(),
-            model: ollama_response.model,
-            content: ollama_response.message.content,
-            role: Role::Assistant,
-            tool_calls: None,
-            usage: Usage {
-                prompt_tokens: ollama_response.prompt_eval_count.unwrap_or(0),
-                completion_tokens: ollama_response.eval_count.unwrap_or(0),
-                total_tokens: ollama_response.prompt_eval_count.unwrap_or(0)
-                    + ollama_response.eval_count.unwrap_or(0),
-            },
-            created_at: chrono::Utc::now(),
-            finish_reason: if ollama_response.done {
-                Some(FinishReason::Stop)
-            } else {
-                None
-            },
+        let tags: OllamaTagsResponse = response.json().await?;
+        let models = tags
+            .models
+            .iter()
+            .map(|entry| self.describe_model(&entry.name));
+        Ok(future::join_all(models).await)
+    }
+
+    async fn health_check(&self) -> Result<HealthInfo> {
+        let tags = self.client.get(format!("{}/api/tags", self.base_url));
+        check_endpoint(self.provider_id(), tags).await
+    }
+
+    async fn warm_up(&self) -> Result<()> {
+        self.preload(self.default_model()).await
+    }
+
+    async fn chat(&self, request: ChatRequest) -> Result<ChatResponse> {
+        let timeout = request.timeout;
+        with_timeout(timeout, async move {
+            let model = request
+                .model
+                .as_deref()
+                .unwrap_or_else(|| self.default_model());
+            ensure_vision(self, &request, model)?;
+            let messages = self.convert_messages(&request.messages)?;
+
+            let ollama_request = OllamaRequest {
+                model: model.into(),
+                messages,
+                stream: false,
+                keep_alive: self.keep_alive_value(),
+                options: Some(OllamaOptions {
+                    temperature: request.temperature,
+                    top_p: request.top_p,
+                    num_predict: request.max_tokens.map(|t| t as i32),
+                    stop: request.stop,
+                }),
+                tools: request.tools.filter(|t| !t.is_empty()),
+            };
+
+            let response = self
+                .client
+                .post(self.chat_url())
+                .request_timeout(timeout)
+                .json(&ollama_request)
+                .send()
+                .await
+                .map_err(|e| {
+                    if e.is_connect() {
+                        ProviderError::Unavailable(format!(
+                            "Ollama not running at {}",
+                            self.base_url
+                        ))
+                    } else if e.is_timeout() {
+                        e.into()
+                    } else {
+                        ProviderError::NetworkError(e.to_string())
+                    }
+                })?;
+
+            if !response.status().is_success() {
+                return Err(ProviderError::from_http_response(response).await);
+            }
+
+            let ollama_response: OllamaResponse = response.json().await?;
+            let message = ollama_response.message.unwrap_or_default();
+            let tool_calls = message
+                .tool_calls
+                .into_iter()
+                .map(|call| {
+                    ToolCall::function(
+                        format!("call_{}", uuid::Uuid::new_v4().simple()),
+                        call.function.name,
+                        call.function.arguments.to_string(),
+                    )
+                })
+                .collect();
+
+            Ok(ChatResponse {
+                id: uuid::Uuid::new_v4().to_string(),
+                provider: "ollama".into(),
+                model: ollama_response.model,
+                content: message.content,
+                role: Role::Assistant,
+                tool_calls,
+                usage: Usage {
+                    prompt_tokens: ollama_response.prompt_eval_count.unwrap_or(0),
+                    completion_tokens: ollama_response.eval_count.unwrap_or(0),
+                    total_tokens: ollama_response.prompt_eval_count.unwrap_or(0)
+                        + ollama_response.eval_count.unwrap_or(0),
+                },
+                created_at: chrono::Utc::now(),
+                finish_reason: finish_reason(
+                    ollama_response.done,
+                    ollama_response.done_reason.as_deref(),
+                ),
+                skipped_providers: Vec::new(),
+            })
         })
+        .await
     }
 
     async fn chat_stream(&self, request: ChatRequest) -> Result<ChatStream> {
-        let model = request
-            .model
-            .as_deref()
-            .unwrap_or_else(|| self.default_model());
-        let messages = self.convert_messages(&request.messages);
+        let timeout = request.timeout;
+        let stream = with_timeout(timeout, async move {
+            let model = request
+                .model
+                .as_deref()
+                .unwrap_or_else(|| self.default_model());
+            ensure_vision(self, &request, model)?;
+            let messages = self.convert_messages(&request.messages)?;
 
-        let ollama_request = OllamaRequest {
-            model: model.into(),
-            messages,
-            stream: true,
-            options: Some(OllamaOptions {
-                temperature: request.temperature,
-                top_p: request.top_p,
-                num_predict: request.max_tokens.map(|t| t as i32),
-                stop: request.stop,
-            }),
-        };
+            let ollama_request = OllamaRequest {
+                model: model.into(),
+                messages,
+                stream: true,
+                keep_alive: self.keep_alive_value(),
+                options: Some(OllamaOptions {
+                    temperature: request.temperature,
+                    top_p: request.top_p,
+                    num_predict: request.max_tokens.map(|t| t as i32),
+                    stop: request.stop,
+                }),
+                tools: request.tools.filter(|t| !t.is_empty()),
+            };
 
-        let response = self
-            .client
-            .post(self.chat_url())
-            .json(&ollama_request)
-            .send()
-            .await
-            .map_err(|e| {
-                if e.is_connect() {
-                    ProviderError::Unavailable(format!("Ollama not running at {}", self.base_url))
-                } else {
-                    ProviderError::NetworkError(e.to_string())
+            let response = self
+                .client
+                .post(self.chat_url())
+                .json(&ollama_request)
+                .send()
+                .await
+                .map_err(|e| {
+                    if e.is_connect() {
+                        ProviderError::Unavailable(format!(
+                            "Ollama not running at {}",
+                            self.base_url
+                        ))
+                    } else if e.is_timeout() {
+                        e.into()
+                    } else {
+                        ProviderError::NetworkError(e.to_string())
+                    }
+                })?;
+
+            if !response.status().is_success() {
+                return Err(ProviderError::from_http_response(response).await);
+            }
+
+            Ok(Box::pin(ndjson_stream(
+                Box::pin(response.bytes_stream()),
+                model.to_string(),
+            )))
+        })
+        .await?;
+        Ok(idle_timeout(stream, timeout))
+    }
+}
+
+/// Turn Ollama's line-delimited JSON body into stream chunks, buffering
+/// partial lines across network reads
+fn ndjson_stream<S, B, E>(bytes: S, model: String) -> impl Stream<Item = Result<StreamChunk>>
+where
+    S: Stream<Item = std::result::Result<B, E>> + Unpin,
+    B: AsRef<[u8]>,
+    E: std::fmt::Display,
+{
+    ndjson_lines(bytes, None, move |line| parse_stream_line(line, &model))
+}
+
+/// Parse each line of a line-delimited JSON body with `parse`. With an
+/// `idle_timeout`, a body that goes quiet for that long ends with an error.
+fn ndjson_lines<S, B, E, T, F>(
+    bytes: S,
+    idle_timeout: Option<Duration>,
+    parse: F,
+) -> impl Stream<Item = Result<T>>
+where
+    S: Stream<Item = std::result::Result<B, E>> + Unpin,
+    B: AsRef<[u8]>,
+    E: std::fmt::Display,
+    F: Fn(&[u8]) -> Option<Result<T>>,
+{
+    stream::unfold(
+        (bytes, Vec::new(), false, parse),
+        move |(mut bytes, mut buffer, mut done, parse)| async move {
+            loop {
+                if let Some(pos) = buffer.iter().position(|b| *b == b'\n') {
+                    let line: Vec<u8> = buffer.drain(..=pos).collect();
+                    if let Some(item) = parse(&line) {
+                        return Some((item, (bytes, buffer, done, parse)));
+                    }
+                    continue;
                 }
-            })?;
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_default();
+                if done {
+                    let line = std::mem::take(&mut buffer);
+                    return parse(&line).map(|item| (item, (bytes, buffer, done, parse)));
+                }
 
-            return Err(ProviderError::RequestFailed(format!(
-                "{}: {}",
-                status, error_text
-            )));
-        }
+                let next = match idle_timeout {
+                    Some(idle) => match tokio::time::timeout(idle, bytes.next()).await {
+                        Ok(next) => next,
+                        Err(_) => {
+                            buffer.clear();
+                            let error = ProviderError::StreamingError(format!(
+                                "No data for {} seconds",
+                                idle.as_secs()
+                            ));
+                            return Some((Err(error), (bytes, buffer, true, parse)));
+                        }
+                    },
+                    None => bytes.next().await,
+                };
+                match next {
+                    Some(Ok(data)) => buffer.extend_from_slice(data.as_ref()),
+                    Some(Err(e)) => {
+                        buffer.clear();
+                        let error = ProviderError::StreamingError(e.to_string());
+                        return Some((Err(error), (bytes, buffer, true, parse)));
+                    }
+                    None => done = true,
+                }
+            }
+        },
+    )
+}
 
-        let model_clone = model.to_string();
-        let stream = response.bytes_stream().map(move |chunk| {
-            let chunk = chunk.map_err(|e| ProviderError::StreamingError(e.to_string()))?;
-            let text = String::from_utf8_lossy(&chunk);
+/// Stop after the first error or the `success` line, and turn a body that
+/// ends before `success` into an error
+fn until_pulled<S>(lines: S) -> impl Stream<Item = Result<PullProgress>>
+where
+    S: Stream<Item = Result<PullProgress>>,
+{
+    stream::unfold(
+        (Box::pin(lines), false),
+        |(mut lines, finished)| async move {
+            if finished {
+                return None;
+            }
+            match lines.next().await {
+                Some(Ok(progress)) => {
+                    let finished = progress.is_done();
+                    Some((Ok(progress), (lines, finished)))
+                }
+                Some(Err(e)) => Some((Err(e), (lines, true))),
+                None => Some((
+                    Err(ProviderError::StreamingError(
+                        "Pull ended before the model was downloaded".into(),
+                    )),
+                    (lines, true),
+                )),
+            }
+        },
+    )
+}
 
-            parse_stream_chunk(&text, &model_clone)
-        });
+fn parse_pull_line(line: &[u8]) -> Option<Result<PullProgress>> {
+    let line = String::from_utf8_lossy(line);
+    let trimmed = line.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
 
-        Ok(Box::pin(stream))
+    let status = match serde_json::from_str::<OllamaPullStatus>(trimmed) {
+        Ok(status) => status,
+        Err(e) => return Some(Err(ProviderError::InvalidResponse(e.to_string()))),
+    };
+    if let Some(error) = status.error {
+        return Some(Err(ProviderError::RequestFailed(error)));
     }
+
+    // A layer's first line has its size but no `completed` yet
+    let percent = status
+        .total
+        .filter(|&total| total > 0)
+        .map(|total| (status.completed.unwrap_or(0) as f64 / total as f64 * 100.0).min(100.0));
+    Some(Ok(PullProgress {
+        status: status.status.unwrap_or_default(),
+        percent,
+    }))
 }
 
-fn parse_stream_chunk(text: &str, model: &str) -> Result<StreamChunk> {
-    for line in text.lines() {
-        let trimmed = line.trim();
-        if trimmed.is_empty() {
-            continue;
-        }
+fn parse_stream_line(line: &[u8], model: &str) -> Option<Result<StreamChunk>> {
+    let line = String::from_utf8_lossy(line);
+    let trimmed = line.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
 
-        if let Ok(response) = serde_json::from_str::<OllamaStreamResponse>(trimmed) {
-            return Ok(StreamChunk {
-                id: String::new(),
-                provider: "ollama".into(),
-                model: model.into(),
-                delta: response.message.content,
-                is_final: response.done,
-                usage: if response.done {
-                    Some(Usage {
-                        prompt_tokens: response.prompt_eval_count.unwrap_or(0),
-                        completion_tokens: response.eval_count.unwrap_or(0),
-                        total_tokens: response.prompt_eval_count.unwrap_or(0)
-                            + response.eval_count.unwrap_or(0),
-                    })
-                } else {
-                    None
-                },
-                finish_reason: if response.done {
-                    Some(FinishReason::Stop)
-                } else {
-                    None
-                },
-            });
-        }
+    let response = match serde_json::from_str::<OllamaStreamResponse>(trimmed) {
+        Ok(response) => response,
+        Err(e) => return Some(Err(ProviderError::InvalidResponse(e.to_string()))),
+    };
+
+    if let Some(error) = response.error {
+        return Some(Err(ProviderError::StreamingError(error)));
     }
 
-    Ok(StreamChunk {
+    let usage = response.done.then(|| {
+        let prompt_tokens = response.prompt_eval_count.unwrap_or(0);
+        let completion_tokens = response.eval_count.unwrap_or(0);
+        Usage {
+            prompt_tokens,
+            completion_tokens,
+            total_tokens: prompt_tokens + completion_tokens,
+        }
+    });
+
+    let finish_reason = finish_reason(response.done, response.done_reason.as_deref());
+
+    Some(Ok(StreamChunk {
         id: String::new(),
         provider: "ollama".into(),
-        model: model.into(),
-        delta: String::new(),
-        is_final: false,
-        usage: None,
-        finish_reason: None,
-    })
+        model: response.model.unwrap_or_else(|| model.into()),
+        delta: response.message.map(|m| m.content).unwrap_or_default(),
+        is_final: response.done,
+        usage,
+        finish_reason,
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaPullStatus {
+    #[serde(default)]
+    status: Option<String>,
+    #[serde(default)]
+    completed: Option<u64>,
+    #[serde(default)]
+    total: Option<u64>,
+    #[serde(default)]
+    error: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -380,7 +758,11 @@ struct OllamaRequest {
     messages: Vec<OllamaMessage>,
     stream: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
+    keep_alive: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     options: Option<OllamaOptions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tools: Option<Vec<Tool>>,
 }
 
 #[derive(Debug, Serialize)]
@@ -389,6 +771,23 @@ struct OllamaMessage {
     content: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     images: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tool_calls: Vec<OllamaToolCall>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_name: Option<String>,
+}
+
+/// Ollama neither ids calls nor JSON-encodes their arguments
+#[derive(Debug, Serialize, Deserialize)]
+struct OllamaToolCall {
+    function: OllamaFunctionCall,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct OllamaFunctionCall {
+    name: String,
+    #[serde(default)]
+    arguments: serde_json::Value,
 }
 
 #[derive(Debug, Serialize)]
@@ -406,27 +805,72 @@ struct OllamaOptions {
 #[derive(Debug, Deserialize)]
 struct OllamaResponse {
     model: String,
-    message: OllamaResponseMessage,
+    message: Option<OllamaResponseMessage>,
     done: bool,
+    #[serde(default)]
+    done_reason: Option<String>,
     prompt_eval_count: Option<u32>,
     eval_count: Option<u32>,
 }
 
 #[allow(dead_code)]
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 struct OllamaResponseMessage {
+    #[serde(default)]
     role: String,
+    #[serde(default)]
     content: String,
+    #[serde(default)]
+    tool_calls: Vec<OllamaToolCall>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaTagsResponse {
+    #[serde(default)]
+    models: Vec<OllamaTagEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaTagEntry {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaShowResponse {
+    #[serde(default)]
+    model_info: serde_json::Map<String, serde_json::Value>,
+    capabilities: Option<Vec<String>>,
+}
+
+impl OllamaShowResponse {
+    /// `model_info` keys are prefixed by architecture, e.g.
+    /// `llama.context_length`
+    fn context_length(&self) -> Option<usize> {
+        self.model_info
+            .iter()
+            .find(|(key, _)| key.ends_with(".context_length"))
+            .and_then(|(_, value)| value.as_u64())
+            .map(|n| n as usize)
+    }
+}
+
+fn finish_reason(done: bool, done_reason: Option<&str>) -> Option<FinishReason> {
+    done.then_some(match done_reason {
+        Some("length") => FinishReason::Length,
+        _ => FinishReason::Stop,
+    })
 }
 
-#[allow(dead_code)]
 #[derive(Debug, Deserialize)]
 struct OllamaStreamResponse {
-    model: String,
-    message: OllamaResponseMessage,
+    model: Option<String>,
+    message: Option<OllamaResponseMessage>,
+    #[serde(default)]
     done: bool,
+    done_reason: Option<String>,
     prompt_eval_count: Option<u32>,
     eval_count: Option<u32>,
+    error: Option<String>,
 }
 
 #[cfg(test)]
@@ -456,4 +900,293 @@ mod tests {
         let content = MessageContent::Text("Hello".into());
         assert_eq!(OllamaProvider::extract_text(&content), "Hello");
     }
+
+    #[tokio::test]
+    async fn test_images_for_non_vision_model_are_unsupported() {
+        let provider = OllamaProvider::new(ProviderConfig::new("ollama")).unwrap();
+        let request = ChatRequest::new(vec![Message::user_with_images(
+            "What is this?",
+            vec![crate::ContentPart::image("iVBORw0KGgo=", "image/png")],
+        )])
+        .with_model("mistral");
+
+        let err = provider.chat(request).await.unwrap_err();
+        assert!(matches!(err, ProviderError::Unsupported(_)));
+        assert!(err.to_string().contains("mistral"));
+    }
+
+    #[test]
+    fn test_url_images_are_rejected() {
+        let provider = OllamaProvider::new(ProviderConfig::new("ollama")).unwrap();
+        let inline = Message::user_with_images(
+            "What is this?",
+            vec![crate::ContentPart::image(
+                "data:image/png;base64,iVBORw0KGgo=",
+                "image/png",
+            )],
+        );
+        let messages = provider.convert_messages(&[inline]).unwrap();
+        assert_eq!(messages[0].images, Some(vec!["iVBORw0KGgo=".to_string()]));
+
+        let remote = Message::user_with_images(
+            "What is this?",
+            vec![crate::ContentPart::image(
+                "https://example.com/cat.png",
+                "image/png",
+            )],
+        );
+        let err = provider.convert_messages(&[remote]).unwrap_err();
+        assert!(matches!(err, ProviderError::Unsupported(_)));
+        assert!(err.to_string().contains("https://example.com/cat.png"));
+    }
+
+    #[test]
+    fn test_keep_alive_from_config() {
+        let mut config = ProviderConfig::new("ollama");
+        config.extra.insert("keep_alive".into(), "-1".into());
+        let provider = OllamaProvider::new(config).unwrap();
+        assert_eq!(provider.keep_alive_value(), Some(serde_json::json!(-1)));
+
+        let provider = provider.with_keep_alive("30m");
+        assert_eq!(provider.keep_alive_value(), Some(serde_json::json!("30m")));
+    }
+
+    #[tokio::test]
+    async fn test_chat_stream_emits_chunks_and_final_usage() {
+        use wiremock::matchers::{body_partial_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        let body = concat!(
+            "{\"model\":\"llama3.2\",\"message\":{\"role\":\"assistant\",\"content\":\"Hel\"},\"done\":false}\n",
+            "{\"model\":\"llama3.2\",\"message\":{\"role\":\"assistant\",\"content\":\"lo\"},\"done\":false}\n",
+            "{\"model\":\"llama3.2\",\"message\":{\"role\":\"assistant\",\"content\":\"\"},\"done\":true,",
+            "\"done_reason\":\"stop\",\"prompt_eval_count\":12,\"eval_count\":5}\n",
+        );
+        Mock::given(method("POST"))
+            .and(path("/api/chat"))
+            .and(body_partial_json(
+                serde_json::json!({"stream": true, "keep_alive": "10m"}),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(body, "application/x-ndjson"))
+            .mount(&server)
+            .await;
+
+        let provider =
+            OllamaProvider::new(ProviderConfig::new("ollama").with_base_url(server.uri()))
+                .unwrap()
+                .with_keep_alive("10m");
+        let stream = provider
+            .chat_stream(ChatRequest::new(vec![Message::user("hi")]))
+            .await
+            .unwrap();
+        let chunks: Vec<StreamChunk> = stream.map(|c| c.unwrap()).collect().await;
+
+        assert_eq!(chunks.len(), 3);
+        let text: String = chunks.iter().map(|c| c.delta.as_str()).collect();
+        assert_eq!(text, "Hello");
+        assert!(chunks[..2].iter().all(|c| !c.is_final && c.usage.is_none()));
+
+        let last = chunks.last().unwrap();
+        assert!(last.is_final);
+        assert_eq!(last.finish_reason, Some(FinishReason::Stop));
+        let usage = last.usage.as_ref().unwrap();
+        assert_eq!(usage.prompt_tokens, 12);
+        assert_eq!(usage.completion_tokens, 5);
+        assert_eq!(usage.total_tokens, 17);
+    }
+
+    #[tokio::test]
+    async fn test_slow_chat_fails_with_request_timeout() {
+        use std::time::Duration;
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/chat"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_delay(Duration::from_secs(3))
+                    .set_body_json(serde_json::json!({
+                        "model": "llama3.2",
+                        "message": {"role": "assistant", "content": "late"},
+                        "done": true
+                    })),
+            )
+            .mount(&server)
+            .await;
+
+        let provider =
+            OllamaProvider::new(ProviderConfig::new("ollama").with_base_url(server.uri())).unwrap();
+        let request =
+            ChatRequest::new(vec![Message::user("hi")]).with_timeout(Duration::from_secs(1));
+
+        let err = provider.chat(request).await.unwrap_err();
+        assert!(matches!(err, ProviderError::Timeout(1)));
+    }
+
+    #[tokio::test]
+    async fn test_warm_up_loads_default_model_with_keep_alive() {
+        use wiremock::matchers::{body_partial_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/chat"))
+            .and(body_partial_json(serde_json::json!({
+                "model": "llama3.2",
+                "messages": [],
+                "keep_alive": "30m"
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "model": "llama3.2",
+                "done": true
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let provider =
+            OllamaProvider::new(ProviderConfig::new("ollama").with_base_url(server.uri()))
+                .unwrap()
+                .with_keep_alive("30m");
+        provider.warm_up().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_preload_sends_keep_alive_and_reports_missing_model() {
+        use wiremock::matchers::{body_partial_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/chat"))
+            .and(body_partial_json(serde_json::json!({
+                "model": "qwen2.5",
+                "keep_alive": 900
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "model": "qwen2.5",
+                "done": true
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/chat"))
+            .and(body_partial_json(serde_json::json!({ "model": "phi4" })))
+            .respond_with(ResponseTemplate::new(404).set_body_json(serde_json::json!({
+                "error": "model \"phi4\" not found, try pulling it first"
+            })))
+            .mount(&server)
+            .await;
+
+        let mut config = ProviderConfig::new("ollama").with_base_url(server.uri());
+        config.extra.insert("keep_alive_secs".into(), "900".into());
+        let provider = OllamaProvider::new(config).unwrap();
+
+        provider.preload("qwen2.5").await.unwrap();
+        assert!(matches!(
+            provider.preload("phi4").await,
+            Err(ProviderError::ModelNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_list_models_remote_reads_tags_and_context_length() {
+        use wiremock::matchers::{body_partial_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/tags"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "models": [
+                    {"name": "llama3.2:latest", "details": {"family": "llama"}},
+                    {"name": "gemma3:4b", "details": {"family": "gemma3"}}
+                ]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/show"))
+            .and(body_partial_json(serde_json::json!({"model": "gemma3:4b"})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "model_info": {"gemma3.context_length": 131072, "general.architecture": "gemma3"},
+                "capabilities": ["completion", "vision"]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/show"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+
+        let provider =
+            OllamaProvider::new(ProviderConfig::new("ollama").with_base_url(server.uri())).unwrap();
+        let models = provider.list_models_remote().await.unwrap();
+
+        assert_eq!(models.len(), 2);
+        assert_eq!(models[0].id, "llama3.2");
+        assert_eq!(models[0].name, "Llama 3.2");
+        assert_eq!(models[0].context_length, 128000);
+        assert_eq!(models[1].id, "gemma3:4b");
+        assert_eq!(models[1].context_length, 131072);
+        assert!(models[1].supports_vision);
+        assert!(!models[1].supports_tools);
+        assert_eq!(models[1].price_per_1k_input, Some(0.0));
+    }
+
+    #[tokio::test]
+    async fn test_ndjson_stream_joins_split_lines() {
+        let parts: Vec<std::result::Result<&[u8], String>> = vec![
+            Ok(b"{\"message\":{\"content\":\"a\"},\"do"),
+            Ok(b"ne\":false}\n{\"error\":\"model not found\"}"),
+        ];
+        let chunks: Vec<_> = ndjson_stream(stream::iter(parts), "llama3.2".into())
+            .collect()
+            .await;
+
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].as_ref().unwrap().delta, "a");
+        assert!(matches!(chunks[1], Err(ProviderError::StreamingError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_pull_progress_from_captured_status_lines() {
+        let captured = concat!(
+            "{\"status\":\"pulling manifest\"}\n",
+            "{\"status\":\"pulling 6a0746a1ec1a\",\"digest\":\"sha256:6a07\",\"total\":4000}\n",
+            "{\"status\":\"pulling 6a0746a1ec1a\",\"digest\":\"sha256:6a07\",\"total\":4000,\"completed\":1000}\n",
+            "{\"status\":\"pulling 6a0746a1ec1a\",\"digest\":\"sha256:6a07\",\"total\":4000,\"completed\":4000}\n",
+            "{\"status\":\"verifying sha256 digest\"}\n",
+            "{\"status\":\"success\"}\n",
+        );
+        let parts: Vec<std::result::Result<&[u8], String>> =
+            captured.as_bytes().chunks(37).map(Ok).collect();
+
+        let progress: Vec<PullProgress> =
+            until_pulled(ndjson_lines(stream::iter(parts), None, parse_pull_line))
+                .map(|p| p.unwrap())
+                .collect()
+                .await;
+        let percents: Vec<Option<f64>> = progress.iter().map(|p| p.percent).collect();
+        assert_eq!(
+            percents,
+            vec![None, Some(0.0), Some(25.0), Some(100.0), None, None]
+        );
+        assert_eq!(progress[0].status, "pulling manifest");
+        assert!(progress[5].is_done());
+
+        // A body cut off mid-pull ends with an error instead of hanging
+        let cut: Vec<std::result::Result<&[u8], String>> =
+            vec![Ok(captured.lines().next().unwrap().as_bytes())];
+        let items: Vec<_> = until_pulled(ndjson_lines(stream::iter(cut), None, parse_pull_line))
+            .collect()
+            .await;
+        assert_eq!(items.len(), 2);
+        assert!(matches!(items[1], Err(ProviderError::StreamingError(_))));
+    }
 }
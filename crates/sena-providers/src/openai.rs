@@ -4,20 +4,40 @@ use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    health::{check_endpoint, HealthInfo},
     metadata::{openai_metadata, ProviderMetadata},
-    provider::{AIProvider, ChatStream},
+    provider::{ensure_vision, AIProvider, ChatStream},
+    timeout::{idle_timeout, with_timeout, RequestTimeout},
+    warmup::{open_connection, shared_client},
     ChatRequest, ChatResponse, FinishReason, Message, MessageContent, ModelInfo,
     ProviderCapabilities, ProviderConfig, ProviderError, ProviderStatus, Result, Role, StreamChunk,
-    ToolCall, ToolCallFunction, Usage,
+    Tool, ToolCall, ToolCallFunction, Usage,
 };
 
-const OPENAI_API_URL: &str = "https://api.openai.com/v1/chat/completions";
+const DEFAULT_OPENAI_URL: &str = "https://api.openai.com/v1";
+
+/// Id fragments of listed models that do not serve chat completions
+const NON_CHAT_MODELS: &[&str] = &[
+    "embedding",
+    "whisper",
+    "tts",
+    "dall-e",
+    "moderation",
+    "davinci",
+    "babbage",
+    "transcribe",
+    "realtime",
+    "audio",
+    "image",
+    "search",
+];
 
 pub struct OpenAIProvider {
     client: Client,
     config: ProviderConfig,
     capabilities: ProviderCapabilities,
     status: ProviderStatus,
+    base_url: String,
 }
 
 impl OpenAIProvider {
@@ -25,25 +45,39 @@ impl OpenAIProvider {
         let api_key = config
             .get_api_key()
             .ok_or_else(|| ProviderError::NotConfigured("OPENAI_API_KEY not set".into()))?;
+        // Masked in every error from here on, wherever the API echoes it
+        crate::redact::register_secret(&api_key);
 
-        let client = Client::builder()
-            .default_headers(Self::build_headers(&api_key)?)
-            .timeout(std::time::Duration::from_secs(
-                config.timeout_secs.unwrap_or(120),
-            ))
-            .build()
-            .map_err(|e| ProviderError::NetworkError(e.to_string()))?;
+        let client = shared_client(
+            config.timeout_secs.unwrap_or(120),
+            Self::build_headers(&api_key)?,
+        )?;
 
         let capabilities = Self::build_capabilities();
+        let base_url = config
+            .base_url
+            .clone()
+            .unwrap_or_else(|| DEFAULT_OPENAI_URL.into())
+            .trim_end_matches('/')
+            .to_string();
 
         Ok(Self {
             client,
             config,
             capabilities,
             status: ProviderStatus::Connected,
+            base_url,
         })
     }
 
+    fn chat_url(&self) -> String {
+        format!("{}/chat/completions", self.base_url)
+    }
+
+    fn models_url(&self) -> String {
+        format!("{}/models", self.base_url)
+    }
+
     fn build_headers(api_key: &str) -> Result<reqwest::header::HeaderMap> {
         let mut headers = reqwest::header::HeaderMap::new();
         let auth_value = format!("Bearer {}", api_key);
@@ -78,6 +112,8 @@ impl OpenAIProvider {
                     supports_vision: true,
                     supports_tools: true,
                     supports_streaming: true,
+                    price_per_1k_input: None,
+                    price_per_1k_output: None,
                 },
                 ModelInfo {
                     id: "gpt-4.1-mini".into(),
@@ -87,6 +123,8 @@ impl OpenAIProvider {
                     supports_vision: true,
                     supports_tools: true,
                     supports_streaming: true,
+                    price_per_1k_input: None,
+                    price_per_1k_output: None,
                 },
                 ModelInfo {
                     id: "gpt-4.1-nano".into(),
@@ -96,6 +134,8 @@ impl OpenAIProvider {
                     supports_vision: true,
                     supports_tools: true,
                     supports_streaming: true,
+                    price_per_1k_input: None,
+                    price_per_1k_output: None,
                 },
                 ModelInfo {
                     id: "o4-mini".into(),
@@ -105,6 +145,8 @@ impl OpenAIProvider {
                     supports_vision: true,
                     supports_tools: true,
                     supports_streaming: true,
+                    price_per_1k_input: None,
+                    price_per_1k_output: None,
                 },
                 ModelInfo {
                     id: "gpt-4o".into(),
@@ -114,8 +156,13 @@ impl OpenAIProvider {
                     supports_vision: true,
                     supports_tools: true,
                     supports_streaming: true,
+                    price_per_1k_input: None,
+                    price_per_1k_output: None,
                 },
-            ],
+            ]
+            .into_iter()
+            .map(ModelInfo::with_pricing)
+            .collect(),
         }
     }
 
@@ -125,7 +172,9 @@ impl OpenAIProvider {
             .map(|msg| OpenAIMessage {
                 role: Self::convert_role(&msg.role),
                 content: Self::convert_content(&msg.content),
-                name: msg.name.clone(),
+                name: msg.name.clone().filter(|_| msg.role != Role::Tool),
+                tool_calls: msg.tool_calls.clone(),
+                tool_call_id: msg.tool_call_id.clone(),
             })
             .collect()
     }
@@ -155,6 +204,12 @@ impl OpenAIProvider {
                                 detail: image_url.detail.clone(),
                             },
                         },
+                        crate::ContentPart::Image { .. } => OpenAIContentPart::ImageUrl {
+                            image_url: OpenAIImageUrl {
+                                url: part.image_data().map(|i| i.to_url()).unwrap_or_default(),
+                                detail: None,
+                            },
+                        },
                     })
                     .collect();
                 OpenAIContent::Parts(openai_parts)
@@ -203,138 +258,133 @@ impl AIProvider for OpenAIProvider {
         openai_metadata()
     }
 
-    async fn chat(&self, request: ChatRequest) -> Result<ChatResponse> {
-        let model = request
-            .model
-            .as_deref()
-            .unwrap_or_else(|| self.default_model());
-        let messages = self.convert_messages(&request.messages);
-
-        let openai_request = OpenAIRequest {
-            model: model.into(),
-            messages,
-            max_tokens: request.max_tokens,
-            temperature: request.temperature,
-            top_p: request.top_p,
-            stop: request.stop,
-            stream: false,
-        };
-
-        let response = self
-            .client
-            .post(OPENAI_API_URL)
-            .json(&openai_request)
-            .send()
-            .await?;
-
+    async fn list_models_remote(&self) -> Result<Vec<ModelInfo>> {
+        let response = self.client.get(self.models_url()).send().await?;
         if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_default();
-
-            return match status.as_u16() {
-                401 => Err(ProviderError::AuthenticationFailed(error_text)),
-                429 => Err(ProviderError::RateLimited {
-                    retry_after_secs: 60,
-                }),
-                _ => Err(ProviderError::RequestFailed(format!(
-                    "{}: {}",
-                    status, error_text
-                ))),
-            };
+            return Err(ProviderError::from_http_response(response).await);
         }
 
-        let openai_response: OpenAIResponse = response.json().await?;
-        let choice = openai_response
-            .choices
-            .first()
-            .ok_or_else(|| ProviderError::InvalidResponse("No choices in response".into()))?;
-
-        let tool_calls = choice.message.tool_calls.as_ref().map(|calls| {
-            calls
-                .iter()
-                .map(|tc| ToolCall {
-                    id: tc.id.clone(),
-                    call_type: tc.call_type.clone(),
-                    function: ToolCallFunction {
-                        name: tc.function.name.clone(),
-                        arguments: tc.function.arguments.clone(),
-                    },
-                })
-                .collect()
-        });
-
-        Ok(ChatResponse {
-            id: openai_response.id,
-            provider: "openai".into(),
-            model: openai_response.model,
-            content: choice.message.content.clone().unwrap_or_default(),
-            role: Role::Assistant,
-            tool_calls,
-            usage: Usage {
-                prompt_tokens: openai_response.usage.prompt_tokens,
-                completion_tokens: openai_response.usage.completion_tokens,
-                total_tokens: openai_response.usage.total_tokens,
-            },
-            created_at: chrono::Utc::now(),
-            finish_reason: choice
-                .finish_reason
-                .as_ref()
-                .map(|r| Self::parse_finish_reason(r)),
+        let list: OpenAIModelList = response.json().await?;
+        Ok(chat_models(&self.capabilities, list))
+    }
+
+    async fn warm_up(&self) -> Result<()> {
+        open_connection(&self.client, &self.chat_url()).await
+    }
+
+    async fn health_check(&self) -> Result<HealthInfo> {
+        check_endpoint(self.provider_id(), self.client.get(self.models_url())).await
+    }
+
+    async fn chat(&self, request: ChatRequest) -> Result<ChatResponse> {
+        let timeout = request.timeout;
+        with_timeout(timeout, async move {
+            let model = request
+                .model
+                .as_deref()
+                .unwrap_or_else(|| self.default_model());
+            ensure_vision(self, &request, model)?;
+            let messages = self.convert_messages(&request.messages);
+
+            let openai_request = OpenAIRequest {
+                model: model.into(),
+                messages,
+                max_tokens: request.max_tokens,
+                temperature: request.temperature,
+                top_p: request.top_p,
+                stop: request.stop,
+                tools: request.tools.filter(|t| !t.is_empty()),
+                stream: false,
+            };
+
+            let response = self
+                .client
+                .post(self.chat_url())
+                .request_timeout(timeout)
+                .json(&openai_request)
+                .send()
+                .await?;
+
+            if !response.status().is_success() {
+                return Err(ProviderError::from_http_response(response).await);
+            }
+
+            let openai_response: OpenAIResponse = response.json().await?;
+            into_chat_response(openai_response)
         })
+        .await
     }
 
     async fn chat_stream(&self, request: ChatRequest) -> Result<ChatStream> {
-        let model = request
-            .model
-            .as_deref()
-            .unwrap_or_else(|| self.default_model());
-        let messages = self.convert_messages(&request.messages);
-
-        let openai_request = OpenAIRequest {
-            model: model.into(),
-            messages,
-            max_tokens: request.max_tokens,
-            temperature: request.temperature,
-            top_p: request.top_p,
-            stop: request.stop,
-            stream: true,
-        };
-
-        let response = self
-            .client
-            .post(OPENAI_API_URL)
-            .json(&openai_request)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_default();
-
-            return match status.as_u16() {
-                401 => Err(ProviderError::AuthenticationFailed(error_text)),
-                429 => Err(ProviderError::RateLimited {
-                    retry_after_secs: 60,
-                }),
-                _ => Err(ProviderError::RequestFailed(format!(
-                    "{}: {}",
-                    status, error_text
-                ))),
+        let timeout = request.timeout;
+        let stream = with_timeout(timeout, async move {
+            let model = request
+                .model
+                .as_deref()
+                .unwrap_or_else(|| self.default_model());
+            ensure_vision(self, &request, model)?;
+            let messages = self.convert_messages(&request.messages);
+
+            let openai_request = OpenAIRequest {
+                model: model.into(),
+                messages,
+                max_tokens: request.max_tokens,
+                temperature: request.temperature,
+                top_p: request.top_p,
+                stop: request.stop,
+                tools: request.tools.filter(|t| !t.is_empty()),
+                stream: true,
             };
-        }
 
-        let model_clone = model.to_string();
-        let stream = response.bytes_stream().map(move |chunk| {
-            let chunk = chunk.map_err(|e| ProviderError::StreamingError(e.to_string()))?;
-            let text = String::from_utf8_lossy(&chunk);
+            let response = self
+                .client
+                .post(self.chat_url())
+                .json(&openai_request)
+                .send()
+                .await?;
+
+            if !response.status().is_success() {
+                return Err(ProviderError::from_http_response(response).await);
+            }
+
+            let model_clone = model.to_string();
+            let stream = response.bytes_stream().map(move |chunk| {
+                let chunk = chunk.map_err(|e| ProviderError::StreamingError(e.to_string()))?;
+                let text = String::from_utf8_lossy(&chunk);
 
-            parse_sse_chunk(&text, &model_clone)
-        });
+                parse_sse_chunk(&text, &model_clone)
+            });
 
-        Ok(Box::pin(stream))
+            Ok(Box::pin(stream))
+        })
+        .await?;
+        Ok(idle_timeout(stream, timeout))
     }
 }
 
+fn chat_models(capabilities: &ProviderCapabilities, list: OpenAIModelList) -> Vec<ModelInfo> {
+    let mut ids: Vec<String> = list
+        .data
+        .into_iter()
+        .map(|m| m.id)
+        .filter(|id| !NON_CHAT_MODELS.iter().any(|marker| id.contains(marker)))
+        .collect();
+    ids.sort();
+    ids.into_iter()
+        .map(|id| capabilities.model_info("openai", &id))
+        .collect()
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAIModelList {
+    data: Vec<OpenAIModelEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAIModelEntry {
+    id: String,
+}
+
 fn parse_sse_chunk(text: &str, model: &str) -> Result<StreamChunk> {
     for line in text.lines() {
         if let Some(data) = line.strip_prefix("data: ") {
@@ -396,6 +446,8 @@ struct OpenAIRequest {
     top_p: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     stop: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tools: Option<Vec<Tool>>,
     stream: bool,
 }
 
@@ -405,6 +457,10 @@ struct OpenAIMessage {
     content: OpenAIContent,
     #[serde(skip_serializing_if = "Option::is_none")]
     name: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tool_calls: Vec<ToolCall>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_call_id: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -430,6 +486,48 @@ struct OpenAIImageUrl {
     detail: Option<String>,
 }
 
+fn into_chat_response(openai_response: OpenAIResponse) -> Result<ChatResponse> {
+    let choice = openai_response
+        .choices
+        .first()
+        .ok_or_else(|| ProviderError::InvalidResponse("No choices in response".into()))?;
+
+    let tool_calls = choice
+        .message
+        .tool_calls
+        .iter()
+        .flatten()
+        .map(|tc| ToolCall {
+            id: tc.id.clone(),
+            call_type: tc.call_type.clone(),
+            function: ToolCallFunction {
+                name: tc.function.name.clone(),
+                arguments: tc.function.arguments.clone(),
+            },
+        })
+        .collect();
+
+    Ok(ChatResponse {
+        id: openai_response.id,
+        provider: "openai".into(),
+        model: openai_response.model,
+        content: choice.message.content.clone().unwrap_or_default(),
+        role: Role::Assistant,
+        tool_calls,
+        usage: Usage {
+            prompt_tokens: openai_response.usage.prompt_tokens,
+            completion_tokens: openai_response.usage.completion_tokens,
+            total_tokens: openai_response.usage.total_tokens,
+        },
+        created_at: chrono::Utc::now(),
+        finish_reason: choice
+            .finish_reason
+            .as_ref()
+            .map(|r| OpenAIProvider::parse_finish_reason(r)),
+        skipped_providers: Vec::new(),
+    })
+}
+
 #[derive(Debug, Deserialize)]
 struct OpenAIResponse {
     id: String,
@@ -491,6 +589,61 @@ struct OpenAIStreamDelta {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ContentPart;
+
+    #[test]
+    fn test_image_parts_serialize_as_openai_image_urls() {
+        let message = Message::user_with_images(
+            "Describe these",
+            vec![
+                ContentPart::image("iVBORw0KGgo=", "image/png"),
+                ContentPart::image("https://example.com/cat.jpg", "image/jpeg"),
+            ],
+        );
+
+        assert_eq!(
+            serde_json::to_value(OpenAIProvider::convert_content(&message.content)).unwrap(),
+            serde_json::json!([
+                {"type": "image_url", "image_url": {"url": "data:image/png;base64,iVBORw0KGgo="}},
+                {"type": "image_url", "image_url": {"url": "https://example.com/cat.jpg"}},
+                {"type": "text", "text": "Describe these"}
+            ])
+        );
+    }
+
+    #[test]
+    fn test_chat_models_skips_non_chat_and_keeps_curated_metadata() {
+        let list: OpenAIModelList = serde_json::from_value(serde_json::json!({
+            "object": "list",
+            "data": [
+                {"id": "text-embedding-3-small", "object": "model"},
+                {"id": "gpt-4.1", "object": "model"},
+                {"id": "whisper-1", "object": "model"},
+                {"id": "gpt-5-preview", "object": "model"}
+            ]
+        }))
+        .unwrap();
+        let models = chat_models(&OpenAIProvider::build_capabilities(), list);
+
+        let ids: Vec<&str> = models.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, vec!["gpt-4.1", "gpt-5-preview"]);
+        assert_eq!(models[0].name, "GPT-4.1");
+        assert_eq!(models[0].context_length, 1047576);
+        assert_eq!(models[1].name, "gpt-5-preview");
+        assert_eq!(models[1].context_length, 128000);
+    }
+
+    #[test]
+    fn test_length_finish_reason_marks_response_truncated() {
+        let response: OpenAIResponse = serde_json::from_str(
+            r#"{"id":"chatcmpl-1","model":"gpt-4.1","choices":[{"message":{"content":"Once upon"},"finish_reason":"length"}],"usage":{"prompt_tokens":5,"completion_tokens":2,"total_tokens":7}}"#,
+        )
+        .unwrap();
+
+        let response = into_chat_response(response).unwrap();
+        assert_eq!(response.finish_reason, Some(FinishReason::Length));
+        assert!(response.is_truncated());
+    }
 
     #[test]
     fn test_build_capabilities() {
@@ -529,4 +682,39 @@ mod tests {
         assert_eq!(OpenAIProvider::convert_role(&Role::Assistant), "assistant");
         assert_eq!(OpenAIProvider::convert_role(&Role::Tool), "tool");
     }
+
+    #[tokio::test]
+    async fn test_health_check_lists_models_instead_of_chatting() {
+        use crate::health::HealthCheckMethod;
+        use wiremock::matchers::{header, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/models"))
+            .and(header("authorization", "Bearer sk-test"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({ "data": [] })),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let config = ProviderConfig::new("openai")
+            .with_api_key("sk-test")
+            .with_base_url(format!("{}/v1", server.uri()));
+        let info = OpenAIProvider::new(config)
+            .unwrap()
+            .health_check()
+            .await
+            .unwrap();
+        assert_eq!(info.provider_id, "openai");
+        assert_eq!(info.method, HealthCheckMethod::Endpoint);
+    }
 }
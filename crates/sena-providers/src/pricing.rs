@@ -0,0 +1,96 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{ModelInfo, Usage};
+
+/// List prices in USD per 1K tokens as `(model id prefix, input, output)`.
+/// Dated or suffixed ids match the longest listed prefix.
+const PRICING: &[(&str, f64, f64)] = &[
+    ("claude-sonnet-4-5", 0.003, 0.015),
+    ("claude-3-5-haiku", 0.0008, 0.004),
+    ("gpt-4.1", 0.002, 0.008),
+    ("gpt-4.1-mini", 0.0004, 0.0016),
+    ("gpt-4.1-nano", 0.0001, 0.0004),
+    ("o4-mini", 0.0011, 0.0044),
+    ("gpt-4o", 0.0025, 0.01),
+    ("gemini-2.5-pro", 0.00125, 0.01),
+    ("gemini-2.5-flash", 0.0003, 0.0025),
+    ("gemini-2.5-flash-lite", 0.0001, 0.0004),
+    ("gemini-2.0-flash", 0.0001, 0.0004),
+    ("mistral-large", 0.002, 0.006),
+    ("mistral-medium", 0.0004, 0.002),
+    ("mistral-small", 0.0001, 0.0003),
+    ("codestral", 0.0003, 0.0009),
+    ("open-mistral-nemo", 0.00015, 0.00015),
+];
+
+/// Providers that run locally and cost nothing per token
+const FREE_PROVIDERS: &[&str] = &["ollama"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModelPrice {
+    pub per_1k_input: f64,
+    pub per_1k_output: f64,
+}
+
+impl ModelPrice {
+    pub fn lookup(provider: &str, model: &str) -> Option<Self> {
+        if FREE_PROVIDERS.contains(&provider) {
+            return Some(Self {
+                per_1k_input: 0.0,
+                per_1k_output: 0.0,
+            });
+        }
+        PRICING
+            .iter()
+            .filter(|(prefix, _, _)| model.starts_with(prefix))
+            .max_by_key(|(prefix, _, _)| prefix.len())
+            .map(|(_, input, output)| Self {
+                per_1k_input: *input,
+                per_1k_output: *output,
+            })
+    }
+
+    /// Estimated USD cost of one response's token usage
+    pub fn cost(&self, usage: &Usage) -> f64 {
+        (usage.prompt_tokens as f64 * self.per_1k_input
+            + usage.completion_tokens as f64 * self.per_1k_output)
+            / 1000.0
+    }
+}
+
+impl ModelInfo {
+    /// Fill the per-1K token prices from the static pricing table
+    pub fn with_pricing(mut self) -> Self {
+        let price = ModelPrice::lookup(&self.provider, &self.id);
+        self.price_per_1k_input = price.map(|p| p.per_1k_input);
+        self.price_per_1k_output = price.map(|p| p.per_1k_output);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_prefers_longest_prefix() {
+        let mini = ModelPrice::lookup("openai", "gpt-4.1-mini-2025-04-14").unwrap();
+        assert_eq!(mini.per_1k_input, 0.0004);
+        let full = ModelPrice::lookup("openai", "gpt-4.1").unwrap();
+        assert_eq!(full.per_1k_output, 0.008);
+        assert_eq!(
+            ModelPrice::lookup("ollama", "llama3.2")
+                .unwrap()
+                .per_1k_input,
+            0.0
+        );
+        assert!(ModelPrice::lookup("openai", "unknown-model").is_none());
+
+        let usage = Usage {
+            prompt_tokens: 2000,
+            completion_tokens: 500,
+            total_tokens: 2500,
+        };
+        assert!((full.cost(&usage) - 0.008).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,239 @@
+use chrono::{DateTime, Duration, Utc};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::{
+    provider::AIProvider, ChatRequest, FinishReason, Message, ModelInfo, Tool, ToolFunction,
+};
+
+pub const DEFAULT_PROBE_TTL_HOURS: i64 = 24;
+
+const PROBE_MAX_TOKENS: u32 = 16;
+const PROBE_TOOL_NAME: &str = "sena_probe";
+
+/// Capabilities of a model as observed by test calls, with vision as declared
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ModelCapabilities {
+    pub provider: String,
+    pub model: String,
+    pub supports_tools: bool,
+    pub supports_vision: bool,
+    pub supports_streaming: bool,
+    pub probed_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<String>,
+}
+
+impl ModelCapabilities {
+    pub fn is_fresh(&self, ttl: Duration) -> bool {
+        Utc::now() - self.probed_at < ttl
+    }
+
+    /// Overwrite the declared flags of `info` with the probed ones
+    pub fn apply(&self, info: &mut ModelInfo) {
+        info.supports_tools = self.supports_tools;
+        info.supports_vision = self.supports_vision;
+        info.supports_streaming = self.supports_streaming;
+    }
+}
+
+/// Make minimal test calls (tiny prompts, `max_tokens` capped) to see whether
+/// `model` actually supports tools and streaming; vision is taken from the
+/// model metadata. A failed probe marks the feature as unsupported and
+/// records the reason in `notes`.
+pub async fn probe_model<P: AIProvider + ?Sized>(provider: &P, model: &str) -> ModelCapabilities {
+    let mut notes = Vec::new();
+
+    let tool_request =
+        probe_request(model, Message::user("Call the sena_probe tool.")).with_tools(vec![Tool {
+            tool_type: "function".to_string(),
+            function: ToolFunction {
+                name: PROBE_TOOL_NAME.to_string(),
+                description: Some("Capability probe; takes no arguments.".to_string()),
+                parameters: Some(serde_json::json!({"type": "object", "properties": {}})),
+            },
+        }]);
+    let supports_tools = match provider.chat(tool_request).await {
+        Ok(response) => {
+            let called = !response.tool_calls.is_empty()
+                || response.finish_reason == Some(FinishReason::ToolCalls);
+            if !called {
+                notes.push("tools: model answered without calling the probe tool".to_string());
+            }
+            called
+        }
+        Err(e) => {
+            notes.push(format!("tools: {}", e));
+            false
+        }
+    };
+
+    // A tiny test image says little about real image input, so vision
+    // comes from the model's declared metadata
+    let supports_vision = provider
+        .capabilities()
+        .model_info(provider.provider_id(), model)
+        .supports_vision;
+
+    let supports_streaming = match provider
+        .chat_stream(probe_request(model, Message::user("Reply OK.")))
+        .await
+    {
+        Ok(mut stream) => match stream.next().await {
+            Some(Ok(_)) => true,
+            Some(Err(e)) => {
+                notes.push(format!("streaming: {}", e));
+                false
+            }
+            None => {
+                notes.push("streaming: stream ended without a chunk".to_string());
+                false
+            }
+        },
+        Err(e) => {
+            notes.push(format!("streaming: {}", e));
+            false
+        }
+    };
+
+    ModelCapabilities {
+        provider: provider.provider_id().to_string(),
+        model: model.to_string(),
+        supports_tools,
+        supports_vision,
+        supports_streaming,
+        probed_at: Utc::now(),
+        notes,
+    }
+}
+
+fn probe_request(model: &str, message: Message) -> ChatRequest {
+    ChatRequest::new(vec![message])
+        .with_model(model)
+        .with_max_tokens(PROBE_MAX_TOKENS)
+        .with_temperature(0.0)
+}
+
+/// Probe results keyed by provider and model, valid for a TTL
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapabilityCache {
+    #[serde(default)]
+    entries: HashMap<String, ModelCapabilities>,
+    #[serde(skip, default = "default_ttl")]
+    ttl: Duration,
+}
+
+fn default_ttl() -> Duration {
+    Duration::hours(DEFAULT_PROBE_TTL_HOURS)
+}
+
+fn cache_key(provider: &str, model: &str) -> String {
+    format!("{}/{}", provider, model)
+}
+
+impl CapabilityCache {
+    pub fn new() -> Self {
+        Self::with_ttl(default_ttl())
+    }
+
+    pub fn with_ttl(ttl: Duration) -> Self {
+        Self {
+            entries: HashMap::new(),
+            ttl,
+        }
+    }
+
+    pub fn cache_path() -> PathBuf {
+        dirs_next::home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(".sena")
+            .join("provider_probes.json")
+    }
+
+    /// Load a cache file, starting empty when it is missing or unreadable
+    pub fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        std::fs::write(path, content)
+    }
+
+    /// Fresh probe result for a model, if any
+    pub fn get(&self, provider: &str, model: &str) -> Option<&ModelCapabilities> {
+        self.entries
+            .get(&cache_key(provider, model))
+            .filter(|caps| caps.is_fresh(self.ttl))
+    }
+
+    pub fn insert(&mut self, capabilities: ModelCapabilities) {
+        self.entries.insert(
+            cache_key(&capabilities.provider, &capabilities.model),
+            capabilities,
+        );
+    }
+
+    /// Apply a fresh probe result, if any, to a declared model
+    pub fn apply(&self, info: &mut ModelInfo) {
+        if let Some(caps) = self.get(&info.provider, &info.id) {
+            caps.apply(info);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl Default for CapabilityCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn capabilities(probed_at: DateTime<Utc>) -> ModelCapabilities {
+        ModelCapabilities {
+            provider: "mock".to_string(),
+            model: "m1".to_string(),
+            supports_tools: false,
+            supports_vision: false,
+            supports_streaming: true,
+            probed_at,
+            notes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_cache_expires_after_ttl_and_round_trips() {
+        let mut cache = CapabilityCache::with_ttl(Duration::hours(1));
+        cache.insert(capabilities(Utc::now() - Duration::hours(2)));
+        assert!(cache.get("mock", "m1").is_none());
+
+        cache.insert(capabilities(Utc::now()));
+        assert!(cache.get("mock", "m1").is_some());
+
+        let path = std::env::temp_dir().join(format!("sena-probes-{}.json", uuid::Uuid::new_v4()));
+        cache.save(&path).unwrap();
+        let loaded = CapabilityCache::load(&path);
+        assert_eq!(loaded.get("mock", "m1"), cache.get("mock", "m1"));
+        let _ = std::fs::remove_file(path);
+    }
+}
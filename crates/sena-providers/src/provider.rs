@@ -3,7 +3,10 @@ use futures::Stream;
 use std::pin::Pin;
 
 use crate::{
-    metadata::ProviderMetadata, ChatRequest, ChatResponse, ModelInfo, ProviderCapabilities,
+    health::{HealthCheckMethod, HealthInfo},
+    metadata::ProviderMetadata,
+    probe::ModelCapabilities,
+    ChatRequest, ChatResponse, Message, ModelInfo, ProviderCapabilities, ProviderError,
     ProviderStatus, Result, StreamChunk,
 };
 
@@ -21,6 +24,13 @@ pub trait AIProvider: Send + Sync {
 
     fn available_models(&self) -> &[ModelInfo];
 
+    /// Models reported by the provider's models endpoint, so newly released
+    /// or locally pulled models show up. Providers without such an endpoint
+    /// return the static list.
+    async fn list_models_remote(&self) -> Result<Vec<ModelInfo>> {
+        Ok(self.available_models().to_vec())
+    }
+
     fn status(&self) -> ProviderStatus;
 
     fn provider_metadata(&self) -> ProviderMetadata;
@@ -44,4 +54,55 @@ pub trait AIProvider: Send + Sync {
     fn max_context_tokens(&self) -> usize {
         self.capabilities().max_context_tokens
     }
+
+    /// Open a pooled connection (and load the default model for local
+    /// providers) so the first real request skips the handshake
+    async fn warm_up(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Confirm the provider is reachable and accepts the credentials. The
+    /// default sends a one-token chat; providers with a free endpoint
+    /// override it so the check spends no tokens.
+    async fn health_check(&self) -> Result<HealthInfo> {
+        let started = std::time::Instant::now();
+        self.chat(ChatRequest::new(vec![Message::user("ping")]).with_max_tokens(1))
+            .await?;
+        Ok(HealthInfo::new(
+            self.provider_id(),
+            started.elapsed(),
+            HealthCheckMethod::Chat,
+        ))
+    }
+
+    /// Verify tool and streaming support for `model` with minimal test calls
+    async fn probe_model(&self, model: &str) -> ModelCapabilities {
+        crate::probe::probe_model(self, model).await
+    }
+}
+
+/// Reject image input for `model` when it is known not to read images.
+/// Models outside the static list fall back to the provider capability.
+pub(crate) fn ensure_vision<P: AIProvider + ?Sized>(
+    provider: &P,
+    request: &ChatRequest,
+    model: &str,
+) -> Result<()> {
+    if !request.has_images() {
+        return Ok(());
+    }
+    let supported = provider
+        .available_models()
+        .iter()
+        .find(|m| m.id == model)
+        .map_or_else(|| provider.supports_vision(), |m| m.supports_vision);
+    if supported {
+        Ok(())
+    } else {
+        Err(ProviderError::Unsupported(format!(
+            "{} model {} does not accept image input",
+            provider.display_name(),
+            model
+        )))
+    }
 }
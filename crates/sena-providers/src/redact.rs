@@ -0,0 +1,196 @@
+//! Secret redaction for error messages
+//!
+//! Provider errors can carry request URLs, headers or bodies, so every
+//! `ProviderError` goes through [`redact`] before it is displayed or
+//! serialized. Besides well-known key shapes and the values of secret-named
+//! keys, the API key of every provider constructed is masked wherever it
+//! appears. Collab transcripts are exported through the same function.
+
+use std::sync::{OnceLock, RwLock};
+
+/// Replacement for a masked secret
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Keys shorter than this are too likely to match ordinary text
+const MIN_SECRET_LEN: usize = 8;
+
+/// Prefixes of API keys that are masked wherever they appear, with the
+/// minimum number of key characters that must follow
+const KEY_PREFIXES: &[(&str, usize)] = &[
+    ("sk-", 16),
+    ("sk_live_", 8),
+    ("sk_test_", 8),
+    ("AIza", 30),
+    ("AKIA", 16),
+    ("ghp_", 8),
+    ("gho_", 8),
+    ("ghs_", 8),
+    ("github_pat_", 8),
+    ("xoxb-", 8),
+    ("xoxp-", 8),
+];
+
+/// Markers after which the rest of the token is a credential
+const CREDENTIAL_MARKERS: &[&str] = &["Bearer ", "bearer ", "key=", "x-api-key: "];
+
+/// Key names (case-insensitive suffixes) whose `name=value` / `name: value`
+/// values are credentials
+const SECRET_NAMES: &[&str] = &["api_key", "apikey", "token", "password", "secret"];
+
+fn secrets() -> &'static RwLock<Vec<String>> {
+    static SECRETS: OnceLock<RwLock<Vec<String>>> = OnceLock::new();
+    SECRETS.get_or_init(|| RwLock::new(Vec::new()))
+}
+
+/// Mask `secret` in everything redacted from now on
+pub fn register_secret(secret: &str) {
+    let secret = secret.trim();
+    if secret.len() < MIN_SECRET_LEN {
+        return;
+    }
+    if let Ok(mut secrets) = secrets().write() {
+        if !secrets.iter().any(|known| known == secret) {
+            secrets.push(secret.to_string());
+        }
+    }
+}
+
+/// `text` with registered secrets, known key shapes, credentials after
+/// `Bearer`/`key=` markers and values of secret-named keys replaced by
+/// [`REDACTED`]
+pub fn redact(text: &str) -> String {
+    let mut text = text.to_string();
+    if let Ok(secrets) = secrets().read() {
+        for secret in secrets.iter() {
+            if text.contains(secret.as_str()) {
+                text = text.replace(secret.as_str(), REDACTED);
+            }
+        }
+    }
+
+    for (prefix, min_len) in KEY_PREFIXES {
+        text = mask_after(&text, prefix, *min_len, false);
+    }
+    for marker in CREDENTIAL_MARKERS {
+        text = mask_after(&text, marker, 1, true);
+    }
+    mask_named_values(&text)
+}
+
+/// Short preview of a key for display, e.g. `sk-...abcd`
+pub fn preview(key: &str) -> String {
+    let chars: Vec<char> = key.trim().chars().collect();
+    if chars.len() < 12 {
+        return "*".repeat(chars.len().min(8));
+    }
+    let head: String = chars[..3].iter().collect();
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("{}...{}", head, tail)
+}
+
+fn is_key_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '~' | '+' | '/' | '=')
+}
+
+/// Mask the run of key characters following each `marker` when it is at
+/// least `min_len` long. With `keep_marker` the marker itself stays visible.
+fn mask_after(text: &str, marker: &str, min_len: usize, keep_marker: bool) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(pos) = rest.find(marker) {
+        let at_boundary = rest[..pos]
+            .chars()
+            .next_back()
+            .map_or(true, |c| !c.is_ascii_alphanumeric());
+        let after = &rest[pos + marker.len()..];
+        let len = after.find(|c: char| !is_key_char(c)).unwrap_or(after.len());
+
+        out.push_str(&rest[..pos]);
+        if at_boundary && len >= min_len && !after[..len].starts_with(REDACTED) {
+            if keep_marker {
+                out.push_str(marker);
+            }
+            out.push_str(REDACTED);
+        } else {
+            out.push_str(&rest[pos..pos + marker.len() + len]);
+        }
+        rest = &after[len..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Mask the value following each secret-named key, as in
+/// `OPENAI_API_KEY=...`, `password: ...` or `{"token":"..."}`
+fn mask_named_values(text: &str) -> String {
+    let is_token_char = |c: char| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.');
+    let mut out = String::with_capacity(text.len());
+    let mut mask_next = false;
+    let mut rest = text;
+    while let Some(start) = rest.find(is_token_char) {
+        out.push_str(&rest[..start]);
+        let len = rest[start..]
+            .find(|c: char| !is_token_char(c))
+            .unwrap_or(rest.len() - start);
+        let token = &rest[start..start + len];
+        let after = &rest[start + len..];
+
+        let already_masked = token == "REDACTED" && out.ends_with('[');
+        if mask_next && !already_masked {
+            out.push_str(REDACTED);
+        } else {
+            out.push_str(token);
+        }
+        let name = token.to_lowercase();
+        // JSON keys are followed by a closing quote before the colon
+        let separator = after.trim_start_matches(['"', '\'']);
+        mask_next = SECRET_NAMES.iter().any(|secret| name.ends_with(secret))
+            && (separator.starts_with('=') || separator.starts_with(':'));
+        rest = after;
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_key_shapes_are_masked() {
+        let text = "POST https://example.com/v1?key=AIzaSyD-abcdefghijklmnopqrstuvwxyz012 \
+                    with Authorization: Bearer abc.def-ghi and sk-proj-ABCDEFGHIJKLMNOP1234";
+        let redacted = redact(text);
+        assert!(!redacted.contains("AIzaSy"));
+        assert!(!redacted.contains("abc.def-ghi"));
+        assert!(!redacted.contains("ABCDEFGHIJKLMNOP"));
+        assert!(redacted.contains("key=[REDACTED]"));
+        assert!(redacted.contains("Bearer [REDACTED]"));
+        assert_eq!(redact("task-sk-1 monkey=3"), "task-sk-1 monkey=3");
+    }
+
+    #[test]
+    fn test_secret_named_values_are_masked() {
+        assert_eq!(
+            redact("OPENAI_API_KEY=plain-value, password: hunter2, ghp_0123456789abcdef"),
+            "OPENAI_API_KEY=[REDACTED], password: [REDACTED], [REDACTED]"
+        );
+        assert_eq!(redact(r#"{"token":"abc123"}"#), r#"{"token":"[REDACTED]"}"#);
+        assert_eq!(redact("skip this task"), "skip this task");
+    }
+
+    #[test]
+    fn test_registered_secret_is_masked() {
+        register_secret("plain-secret-value-42");
+        assert_eq!(
+            redact("upstream said: plain-secret-value-42 is invalid"),
+            "upstream said: [REDACTED] is invalid"
+        );
+    }
+
+    #[test]
+    fn test_preview() {
+        assert_eq!(preview("sk-ant-REDACTED"), "sk-...abcd");
+        assert_eq!(preview("short"), "*****");
+    }
+}
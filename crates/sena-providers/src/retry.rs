@@ -0,0 +1,158 @@
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::time::Duration;
+
+use crate::{ProviderError, Result};
+
+/// How often and how patiently a provider call is retried on transient
+/// errors. Read from the `[retry]` table of `providers.toml`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt; 0 disables retrying
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled for each one after it
+    pub base_delay_ms: u64,
+    /// Ceiling for any single delay, including a server's `Retry-After`
+    pub max_delay_ms: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 2,
+            base_delay_ms: 500,
+            max_delay_ms: 10_000,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn new(max_retries: u32, base_delay_ms: u64, max_delay_ms: u64) -> Self {
+        Self {
+            max_retries,
+            base_delay_ms,
+            max_delay_ms,
+        }
+    }
+
+    /// Every call gets exactly one attempt
+    pub fn disabled() -> Self {
+        Self::new(0, 0, 0)
+    }
+
+    /// Delay before retry number `retry` (0-based) after `error`. A
+    /// `Retry-After` from the provider replaces the computed backoff.
+    pub fn delay_for(&self, retry: u32, error: &ProviderError) -> Duration {
+        let delay_ms = match error.retry_after() {
+            Some(retry_after) => retry_after.as_millis().try_into().unwrap_or(u64::MAX),
+            None => self.base_delay_ms.saturating_mul(1u64 << retry.min(32)),
+        };
+        Duration::from_millis(delay_ms.min(self.max_delay_ms))
+    }
+
+    /// Run `call` until it succeeds, fails with a non-retryable error, or
+    /// the retry budget is spent
+    pub async fn run<T, F, Fut>(&self, mut call: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut retry = 0;
+        loop {
+            match call().await {
+                Err(e) if e.is_retryable() && retry < self.max_retries => {
+                    let delay = self.delay_for(retry, &e);
+                    tracing::debug!(
+                        "Retrying in {}ms ({}/{}) after: {}",
+                        delay.as_millis(),
+                        retry + 1,
+                        self.max_retries,
+                        e
+                    );
+                    tokio::time::sleep(delay).await;
+                    retry += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[test]
+    fn test_backoff_doubles_and_honors_retry_after() {
+        let policy = RetryPolicy::new(5, 100, 1_000);
+        let server = ProviderError::Server {
+            status: 503,
+            message: "busy".into(),
+        };
+        assert_eq!(policy.delay_for(0, &server), Duration::from_millis(100));
+        assert_eq!(policy.delay_for(2, &server), Duration::from_millis(400));
+        assert_eq!(policy.delay_for(8, &server), Duration::from_millis(1_000));
+
+        let limited = ProviderError::RateLimited {
+            retry_after_secs: Some(0),
+        };
+        assert_eq!(policy.delay_for(3, &limited), Duration::ZERO);
+        let limited = ProviderError::RateLimited {
+            retry_after_secs: Some(30),
+        };
+        assert_eq!(policy.delay_for(0, &limited), Duration::from_millis(1_000));
+        // Without a Retry-After header the policy's own backoff applies
+        let limited = ProviderError::RateLimited {
+            retry_after_secs: None,
+        };
+        assert_eq!(policy.delay_for(2, &limited), Duration::from_millis(400));
+    }
+
+    #[tokio::test]
+    async fn test_retries_transient_errors_but_not_auth_failures() {
+        let policy = RetryPolicy::new(3, 1, 1);
+
+        let attempts = AtomicU32::new(0);
+        let result = policy
+            .run(|| async {
+                match attempts.fetch_add(1, Ordering::SeqCst) {
+                    0 => Err(ProviderError::RateLimited {
+                        retry_after_secs: Some(0),
+                    }),
+                    1 => Err(ProviderError::Server {
+                        status: 503,
+                        message: "unavailable".into(),
+                    }),
+                    _ => Ok("answer"),
+                }
+            })
+            .await;
+        assert_eq!(result.unwrap(), "answer");
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+
+        let attempts = AtomicU32::new(0);
+        let result: Result<()> = policy
+            .run(|| async {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err(ProviderError::AuthenticationFailed("bad key".into()))
+            })
+            .await;
+        assert!(matches!(
+            result,
+            Err(ProviderError::AuthenticationFailed(_))
+        ));
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+
+        let attempts = AtomicU32::new(0);
+        let result: Result<()> = policy
+            .run(|| async {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err(ProviderError::Timeout(30))
+            })
+            .await;
+        assert!(matches!(result, Err(ProviderError::Timeout(_))));
+        assert_eq!(attempts.load(Ordering::SeqCst), 4);
+    }
+}
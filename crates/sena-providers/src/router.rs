@@ -1,10 +1,18 @@
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use crate::{
-    config::ProvidersConfig,
+    coalesce::RequestCoalescer,
+    config::{ModelAlias, ProvidersConfig},
+    health::{HealthInfo, ProviderHealth},
+    middleware::{ProviderMiddleware, SystemPromptMiddleware, TimeoutMiddleware},
+    probe::{CapabilityCache, ModelCapabilities},
     provider::{AIProvider, ChatStream},
-    ChatRequest, ChatResponse, ModelInfo, ProviderError, ProviderStatus, Result,
+    retry::RetryPolicy,
+    usage::UsageTracker,
+    warmup::WarmUpResult,
+    ChatRequest, ChatResponse, FinishReason, ModelInfo, ProviderError, ProviderStatus, Result,
+    SkippedProvider,
 };
 
 #[cfg(feature = "claude")]
@@ -26,6 +34,15 @@ pub struct ProviderRouter {
     providers: HashMap<String, Arc<dyn AIProvider>>,
     default_provider: Option<String>,
     fallback_chain: Vec<String>,
+    health: Mutex<HashMap<String, ProviderHealth>>,
+    middleware: Vec<Arc<dyn ProviderMiddleware>>,
+    aliases: HashMap<String, ModelAlias>,
+    probes: Mutex<CapabilityCache>,
+    coalescing: bool,
+    coalescer: Arc<RequestCoalescer>,
+    fallback_on_block: bool,
+    retry: RetryPolicy,
+    usage: Option<Arc<UsageTracker>>,
 }
 
 impl ProviderRouter {
@@ -34,6 +51,15 @@ impl ProviderRouter {
             providers: HashMap::new(),
             default_provider: None,
             fallback_chain: Vec::new(),
+            health: Mutex::new(HashMap::new()),
+            middleware: Vec::new(),
+            aliases: HashMap::new(),
+            probes: Mutex::new(CapabilityCache::new()),
+            coalescing: true,
+            coalescer: Arc::new(RequestCoalescer::new()),
+            fallback_on_block: false,
+            retry: RetryPolicy::disabled(),
+            usage: None,
         }
     }
 
@@ -79,6 +105,19 @@ impl ProviderRouter {
         }
 
         router.fallback_chain = config.fallback_chain.clone();
+        router.fallback_on_block = config.fallback_on_block;
+        router.retry = config.retry;
+
+        for (name, target) in &config.aliases {
+            router.set_alias(name, target);
+        }
+
+        if let Some(system_prompt) = SystemPromptMiddleware::from_config(config) {
+            router.add_middleware(Arc::new(system_prompt));
+        }
+        if let Some(timeout) = TimeoutMiddleware::from_config(config) {
+            router.add_middleware(Arc::new(timeout));
+        }
 
         Ok(router)
     }
@@ -100,6 +139,108 @@ impl ProviderRouter {
         self.fallback_chain = chain;
     }
 
+    /// Map a friendly model name to `provider/model` or a bare model id
+    pub fn set_alias(&mut self, name: &str, target: &str) {
+        self.aliases
+            .insert(name.to_string(), ModelAlias::parse(target));
+    }
+
+    pub fn is_alias(&self, name: &str) -> bool {
+        self.aliases.contains_key(name)
+    }
+
+    /// Resolve an alias to the provider that serves it and the model to request
+    /// (`None` means the provider's default model)
+    pub fn resolve_alias(&self, name: &str) -> Result<(&Arc<dyn AIProvider>, Option<String>)> {
+        let alias = self.aliases.get(name).ok_or_else(|| {
+            ProviderError::NotConfigured(format!("Unknown model alias '{}'", name))
+        })?;
+
+        let provider = match (&alias.provider, &alias.model) {
+            (Some(provider_id), _) => self.providers.get(provider_id).ok_or_else(|| {
+                ProviderError::NotConfigured(format!(
+                    "Alias '{}' points to provider '{}', which is not configured",
+                    name, provider_id
+                ))
+            })?,
+            (None, Some(model)) => match self.find_model(model) {
+                Some((provider, _)) => provider,
+                None => self.default_provider().ok_or_else(|| {
+                    ProviderError::NotConfigured(format!(
+                        "Alias '{}' model '{}' has no provider",
+                        name, model
+                    ))
+                })?,
+            },
+            (None, None) => {
+                return Err(ProviderError::NotConfigured(format!(
+                    "Alias '{}' has an empty target",
+                    name
+                )))
+            }
+        };
+
+        Ok((provider, alias.model.clone()))
+    }
+
+    /// Replace an alias in `request` with the model it names, returning the
+    /// provider that serves it
+    fn expand_alias(&self, request: &mut ChatRequest) -> Result<Option<String>> {
+        let Some(name) = request.model.clone().filter(|m| self.is_alias(m)) else {
+            return Ok(None);
+        };
+        let (provider, model) = self.resolve_alias(&name)?;
+        request.model = model;
+        Ok(Some(provider.provider_id().to_string()))
+    }
+
+    /// Share one provider call between concurrent identical chat requests
+    /// (on by default). Turn off when identical prompts should be sampled
+    /// independently.
+    pub fn set_coalescing(&mut self, enabled: bool) {
+        self.coalescing = enabled;
+    }
+
+    /// Treat a content block or refusal as a reason to try the next provider
+    /// in `chat_with_fallback`. Off by default so blocks reach the caller.
+    pub fn set_fallback_on_block(&mut self, enabled: bool) {
+        self.fallback_on_block = enabled;
+    }
+
+    /// Retry each provider call on transient errors before giving up or
+    /// falling back. Routers built with `new` make a single attempt.
+    pub fn set_retry_policy(&mut self, policy: RetryPolicy) {
+        self.retry = policy;
+    }
+
+    pub fn retry_policy(&self) -> RetryPolicy {
+        self.retry
+    }
+
+    /// Record the token usage of every successful chat call
+    pub fn set_usage_tracker(&mut self, tracker: Arc<UsageTracker>) {
+        self.usage = Some(tracker);
+    }
+
+    /// Share in-flight calls with other routers using the same coalescer,
+    /// for callers that build a router per request
+    pub fn set_coalescer(&mut self, coalescer: Arc<RequestCoalescer>) {
+        self.coalescer = coalescer;
+    }
+
+    /// Number of chat calls currently shared between identical requests
+    pub fn coalesced_in_flight(&self) -> usize {
+        self.coalescer.in_flight()
+    }
+
+    pub fn add_middleware(&mut self, middleware: Arc<dyn ProviderMiddleware>) {
+        self.middleware.push(middleware);
+    }
+
+    pub fn middleware_names(&self) -> Vec<&str> {
+        self.middleware.iter().map(|m| m.name()).collect()
+    }
+
     pub fn get_provider(&self, provider_id: &str) -> Option<&Arc<dyn AIProvider>> {
         self.providers.get(provider_id)
     }
@@ -114,6 +255,21 @@ impl ProviderRouter {
         self.providers.values().collect()
     }
 
+    /// Health-check every provider at once, without spending tokens where
+    /// the provider has a free endpoint
+    pub async fn health_checks(&self) -> HashMap<String, Result<HealthInfo>> {
+        let checks = self
+            .providers
+            .iter()
+            .map(|(id, provider)| async move { (id.clone(), provider.health_check().await) });
+        futures::future::join_all(checks)
+            .await
+            .into_iter()
+            .collect()
+    }
+
+    /// Status each provider last reported, without a network call; use
+    /// [`health_checks`](Self::health_checks) for a live check
     pub fn provider_status(&self) -> HashMap<String, ProviderStatus> {
         self.providers
             .iter()
@@ -121,11 +277,83 @@ impl ProviderRouter {
             .collect()
     }
 
+    /// Declared models, with flags corrected by any fresh probe results
     pub fn all_models(&self) -> Vec<ModelInfo> {
-        self.providers
+        let mut models: Vec<ModelInfo> = self
+            .providers
             .values()
             .flat_map(|provider| provider.available_models().to_vec())
-            .collect()
+            .collect();
+        if let Ok(probes) = self.probes.lock() {
+            for model in &mut models {
+                probes.apply(model);
+            }
+        }
+        models
+    }
+
+    pub fn set_capability_cache(&mut self, cache: CapabilityCache) {
+        self.probes = Mutex::new(cache);
+    }
+
+    pub fn capability_cache(&self) -> CapabilityCache {
+        self.probes
+            .lock()
+            .map(|probes| probes.clone())
+            .unwrap_or_default()
+    }
+
+    /// Probe a model (the provider's default when `model` is `None`), reusing
+    /// a fresh cached result unless `force` is set
+    pub async fn probe_model(
+        &self,
+        provider_id: &str,
+        model: Option<&str>,
+        force: bool,
+    ) -> Result<ModelCapabilities> {
+        let provider = self
+            .providers
+            .get(provider_id)
+            .ok_or_else(|| ProviderError::NotConfigured(provider_id.into()))?;
+        let model = model
+            .unwrap_or_else(|| provider.default_model())
+            .to_string();
+
+        if !force {
+            let cached = self
+                .probes
+                .lock()
+                .ok()
+                .and_then(|probes| probes.get(provider_id, &model).cloned());
+            if let Some(cached) = cached {
+                return Ok(cached);
+            }
+        }
+
+        let capabilities = provider.probe_model(&model).await;
+        if let Ok(mut probes) = self.probes.lock() {
+            probes.insert(capabilities.clone());
+        }
+        Ok(capabilities)
+    }
+
+    /// Warm every configured provider concurrently, returning one result per
+    /// provider sorted by ID. Failures are reported, never raised.
+    pub async fn warm_up(&self) -> Vec<WarmUpResult> {
+        let warming = self.providers.values().map(|provider| async move {
+            let start = std::time::Instant::now();
+            let outcome = provider.warm_up().await;
+            WarmUpResult {
+                provider_id: provider.provider_id().to_string(),
+                success: outcome.is_ok(),
+                latency_ms: start.elapsed().as_millis() as u64,
+                error: outcome.err().map(|e| e.to_string()),
+            }
+        });
+
+        let mut results = futures::future::join_all(warming).await;
+        results.sort_by(|a, b| a.provider_id.cmp(&b.provider_id));
+        results
     }
 
     pub fn find_model(&self, model_id: &str) -> Option<(&Arc<dyn AIProvider>, &ModelInfo)> {
@@ -141,54 +369,255 @@ impl ProviderRouter {
         None
     }
 
-    pub async fn chat(&self, request: ChatRequest) -> Result<ChatResponse> {
+    pub fn provider_health(&self, provider_id: &str) -> Option<ProviderHealth> {
+        self.health
+            .lock()
+            .ok()
+            .and_then(|health| health.get(provider_id).cloned())
+    }
+
+    pub fn record_success(&self, provider_id: &str) {
+        if let Ok(mut health) = self.health.lock() {
+            health
+                .entry(provider_id.to_string())
+                .or_insert_with(|| ProviderHealth::new(provider_id))
+                .record_success();
+        }
+    }
+
+    pub fn record_failure(&self, provider_id: &str, error: &ProviderError) {
+        if let Ok(mut health) = self.health.lock() {
+            health
+                .entry(provider_id.to_string())
+                .or_insert_with(|| ProviderHealth::new(provider_id))
+                .record_failure(&error.to_string());
+        }
+    }
+
+    pub async fn chat(&self, mut request: ChatRequest) -> Result<ChatResponse> {
         let provider = self.resolve_provider(&request)?;
-        provider.chat(request).await
+        self.expand_alias(&mut request)?;
+        self.apply_request_middleware(&mut request)?;
+        let mut response = self.chat_tracked(provider, request).await?;
+        self.apply_response_middleware(&mut response)?;
+        Ok(response)
     }
 
-    pub async fn chat_with_fallback(&self, request: ChatRequest) -> Result<ChatResponse> {
-        let primary_provider = self.resolve_provider(&request);
+    /// Send a request to one provider, bypassing model-based routing
+    pub async fn chat_with_provider(
+        &self,
+        provider_id: &str,
+        mut request: ChatRequest,
+    ) -> Result<ChatResponse> {
+        let provider = self
+            .providers
+            .get(provider_id)
+            .ok_or_else(|| ProviderError::NotConfigured(provider_id.into()))?;
+        self.expand_alias(&mut request)?;
+        self.apply_request_middleware(&mut request)?;
+        let mut response = self.chat_tracked(provider, request).await?;
+        self.apply_response_middleware(&mut response)?;
+        Ok(response)
+    }
+
+    pub async fn chat_with_fallback(&self, mut request: ChatRequest) -> Result<ChatResponse> {
+        let (attempts, mut skipped) = self.plan_fallback(&request);
+        let alias_provider = self.expand_alias(&mut request)?;
+        self.apply_request_middleware(&mut request)?;
 
-        if let Ok(provider) = primary_provider {
-            match provider.chat(request.clone()).await {
-                Ok(response) => return Ok(response),
+        // The first block is returned if no other provider answers
+        let mut blocked: Option<Result<ChatResponse>> = None;
+
+        for provider in attempts {
+            // An alias's model belongs to its provider; the others use their default
+            let mut attempt = request.clone();
+            if alias_provider
+                .as_deref()
+                .is_some_and(|id| id != provider.provider_id())
+            {
+                attempt.model = None;
+            }
+            match self.chat_tracked(provider, attempt).await {
+                Ok(response)
+                    if self.fallback_on_block
+                        && response.finish_reason == Some(FinishReason::ContentFilter) =>
+                {
+                    skipped.push(SkippedProvider::blocked(
+                        provider.provider_id(),
+                        "response refused by content filter",
+                    ));
+                    blocked.get_or_insert(Ok(response));
+                }
+                Ok(mut response) => {
+                    response.skipped_providers = skipped;
+                    self.apply_response_middleware(&mut response)?;
+                    return Ok(response);
+                }
+                Err(e @ ProviderError::ContentBlocked(_)) if self.fallback_on_block => {
+                    skipped.push(SkippedProvider::blocked(
+                        provider.provider_id(),
+                        e.to_string(),
+                    ));
+                    blocked.get_or_insert(Err(e));
+                }
                 Err(e) => {
                     if !self.should_fallback(&e) {
                         return Err(e);
                     }
+                    skipped.push(SkippedProvider::new(provider.provider_id(), e.to_string()));
                 }
             }
         }
 
-        for provider_id in &self.fallback_chain {
-            if let Some(provider) = self.providers.get(provider_id) {
-                if provider.status() != ProviderStatus::Connected {
-                    continue;
-                }
+        match blocked {
+            Some(Ok(mut response)) => {
+                response.skipped_providers = skipped;
+                self.apply_response_middleware(&mut response)?;
+                Ok(response)
+            }
+            Some(Err(e)) => Err(e),
+            None => Err(ProviderError::Unavailable(
+                "All providers failed or unavailable".into(),
+            )),
+        }
+    }
 
-                match provider.chat(request.clone()).await {
-                    Ok(response) => return Ok(response),
-                    Err(e) => {
-                        if !self.should_fallback(&e) {
-                            return Err(e);
+    async fn chat_tracked(
+        &self,
+        provider: &Arc<dyn AIProvider>,
+        request: ChatRequest,
+    ) -> Result<ChatResponse> {
+        let key = self
+            .coalescing
+            .then(|| RequestCoalescer::key(provider.provider_id(), &request))
+            .flatten();
+        let retry = self.retry;
+        let target = Arc::clone(provider);
+        let call = async move {
+            retry
+                .run(|| {
+                    let target = Arc::clone(&target);
+                    let request = request.clone();
+                    async move { target.chat(request).await }
+                })
+                .await
+        };
+        let sent = std::time::Instant::now();
+        let (result, started) = match key {
+            Some(key) => self.coalescer.run(key, call).await,
+            None => (call.await, true),
+        };
+        if started {
+            match &result {
+                Ok(response) => {
+                    self.record_success(provider.provider_id());
+                    if let Some(tracker) = &self.usage {
+                        if let Err(e) = tracker.record_timed(
+                            provider.provider_id(),
+                            &response.model,
+                            &response.usage,
+                            sent.elapsed(),
+                        ) {
+                            tracing::warn!("Failed to record token usage: {}", e);
+                        }
+                    }
+                }
+                // A block says nothing about the provider's health
+                Err(ProviderError::ContentBlocked(_)) => {}
+                Err(e) => {
+                    // Only outages put the provider in backoff; a bad or
+                    // unauthorized request would fail the same way again
+                    if e.is_retryable() {
+                        self.record_failure(provider.provider_id(), e);
+                    }
+                    if let Some(tracker) = &self.usage {
+                        if let Err(e) = tracker.record_failure(provider.provider_id()) {
+                            tracing::warn!("Failed to record failed request: {}", e);
                         }
                     }
                 }
             }
         }
+        result
+    }
+
+    fn plan_fallback(
+        &self,
+        request: &ChatRequest,
+    ) -> (Vec<&Arc<dyn AIProvider>>, Vec<SkippedProvider>) {
+        let mut candidates: Vec<&Arc<dyn AIProvider>> = Vec::new();
+        let mut skipped = Vec::new();
+
+        if let Ok(primary) = self.resolve_provider(request) {
+            candidates.push(primary);
+        }
 
-        Err(ProviderError::Unavailable(
-            "All providers failed or unavailable".into(),
-        ))
+        for provider_id in &self.fallback_chain {
+            let Some(provider) = self.providers.get(provider_id) else {
+                continue;
+            };
+            if candidates.iter().any(|c| c.provider_id() == provider_id) {
+                continue;
+            }
+            if provider.status() != ProviderStatus::Connected {
+                skipped.push(SkippedProvider::new(
+                    provider_id,
+                    format!("status is {:?}", provider.status()),
+                ));
+                continue;
+            }
+            candidates.push(provider);
+        }
+
+        let (healthy, backing_off): (Vec<_>, Vec<_>) = candidates
+            .into_iter()
+            .partition(|provider| !self.in_backoff(provider.provider_id()));
+
+        if healthy.is_empty() {
+            return (backing_off, skipped);
+        }
+
+        skipped.extend(backing_off.iter().filter_map(|provider| {
+            self.provider_health(provider.provider_id())
+                .map(|health| SkippedProvider::new(provider.provider_id(), health.skip_reason()))
+        }));
+
+        (healthy, skipped)
+    }
+
+    fn in_backoff(&self, provider_id: &str) -> bool {
+        self.provider_health(provider_id)
+            .is_some_and(|health| health.in_backoff())
     }
 
-    pub async fn chat_stream(&self, request: ChatRequest) -> Result<ChatStream> {
+    pub async fn chat_stream(&self, mut request: ChatRequest) -> Result<ChatStream> {
         let provider = self.resolve_provider(&request)?;
+        self.expand_alias(&mut request)?;
+        self.apply_request_middleware(&mut request)?;
         provider.chat_stream(request).await
     }
 
+    fn apply_request_middleware(&self, request: &mut ChatRequest) -> Result<()> {
+        self.middleware.iter().try_for_each(|middleware| {
+            middleware
+                .on_request(request)
+                .map_err(|e| middleware_error(middleware.as_ref(), e))
+        })
+    }
+
+    fn apply_response_middleware(&self, response: &mut ChatResponse) -> Result<()> {
+        self.middleware.iter().rev().try_for_each(|middleware| {
+            middleware
+                .on_response(response)
+                .map_err(|e| middleware_error(middleware.as_ref(), e))
+        })
+    }
+
     fn resolve_provider(&self, request: &ChatRequest) -> Result<&Arc<dyn AIProvider>> {
         if let Some(model) = &request.model {
+            if self.is_alias(model) {
+                return self.resolve_alias(model).map(|(provider, _)| provider);
+            }
             if let Some((provider, _)) = self.find_model(model) {
                 return Ok(provider);
             }
@@ -199,13 +628,17 @@ impl ProviderRouter {
     }
 
     fn should_fallback(&self, error: &ProviderError) -> bool {
-        matches!(
-            error,
-            ProviderError::RateLimited { .. }
-                | ProviderError::Unavailable(_)
-                | ProviderError::Timeout(_)
-                | ProviderError::NetworkError(_)
-        )
+        error.is_retryable()
+    }
+}
+
+fn middleware_error(middleware: &dyn ProviderMiddleware, error: ProviderError) -> ProviderError {
+    match error {
+        ProviderError::Middleware { .. } => error,
+        other => ProviderError::Middleware {
+            name: middleware.name().to_string(),
+            message: other.to_string(),
+        },
     }
 }
 
@@ -241,6 +674,46 @@ impl RouterBuilder {
         self
     }
 
+    pub fn with_middleware(mut self, middleware: Arc<dyn ProviderMiddleware>) -> Self {
+        self.router.add_middleware(middleware);
+        self
+    }
+
+    pub fn with_alias(mut self, name: &str, target: &str) -> Self {
+        self.router.set_alias(name, target);
+        self
+    }
+
+    pub fn with_capability_cache(mut self, cache: CapabilityCache) -> Self {
+        self.router.set_capability_cache(cache);
+        self
+    }
+
+    pub fn with_coalescing(mut self, enabled: bool) -> Self {
+        self.router.set_coalescing(enabled);
+        self
+    }
+
+    pub fn with_coalescer(mut self, coalescer: Arc<RequestCoalescer>) -> Self {
+        self.router.set_coalescer(coalescer);
+        self
+    }
+
+    pub fn with_fallback_on_block(mut self, enabled: bool) -> Self {
+        self.router.set_fallback_on_block(enabled);
+        self
+    }
+
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.router.set_retry_policy(policy);
+        self
+    }
+
+    pub fn with_usage_tracker(mut self, tracker: Arc<UsageTracker>) -> Self {
+        self.router.set_usage_tracker(tracker);
+        self
+    }
+
     pub fn build(self) -> ProviderRouter {
         self.router
     }
@@ -256,6 +729,235 @@ impl Default for RouterBuilder {
 mod tests {
     use super::*;
     use crate::config::ProvidersConfig;
+    use crate::metadata::ProviderMetadata;
+    use crate::{Message, ProviderCapabilities};
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct MockProvider {
+        id: String,
+        fail: bool,
+        calls: AtomicUsize,
+        capabilities: ProviderCapabilities,
+        last_request: Mutex<Option<ChatRequest>>,
+        delay: std::time::Duration,
+        error: Option<ProviderError>,
+    }
+
+    impl MockProvider {
+        fn new(id: &str, fail: bool) -> Arc<Self> {
+            Arc::new(Self {
+                id: id.to_string(),
+                fail,
+                calls: AtomicUsize::new(0),
+                capabilities: ProviderCapabilities::default(),
+                last_request: Mutex::new(None),
+                delay: std::time::Duration::ZERO,
+                error: None,
+            })
+        }
+
+        fn with_models(id: &str, models: Vec<ModelInfo>) -> Arc<Self> {
+            Arc::new(Self {
+                id: id.to_string(),
+                fail: false,
+                calls: AtomicUsize::new(0),
+                capabilities: ProviderCapabilities {
+                    models,
+                    ..ProviderCapabilities::default()
+                },
+                last_request: Mutex::new(None),
+                delay: std::time::Duration::ZERO,
+                error: None,
+            })
+        }
+
+        fn slow(id: &str, delay: std::time::Duration) -> Arc<Self> {
+            Arc::new(Self {
+                id: id.to_string(),
+                fail: false,
+                calls: AtomicUsize::new(0),
+                capabilities: ProviderCapabilities::default(),
+                last_request: Mutex::new(None),
+                delay,
+                error: None,
+            })
+        }
+
+        fn failing_with(id: &str, error: ProviderError) -> Arc<Self> {
+            Arc::new(Self {
+                id: id.to_string(),
+                fail: false,
+                calls: AtomicUsize::new(0),
+                capabilities: ProviderCapabilities::default(),
+                last_request: Mutex::new(None),
+                delay: std::time::Duration::ZERO,
+                error: Some(error),
+            })
+        }
+
+        fn blocking(id: &str) -> Arc<Self> {
+            Self::failing_with(id, ProviderError::ContentBlocked("SAFETY".into()))
+        }
+
+        fn calls(&self) -> usize {
+            self.calls.load(Ordering::SeqCst)
+        }
+
+        fn last_user_text(&self) -> Option<String> {
+            let request = self.last_request.lock().unwrap().clone()?;
+            request
+                .messages
+                .iter()
+                .rev()
+                .find(|m| m.role == crate::Role::User)
+                .and_then(|m| m.content.as_text().map(str::to_string))
+        }
+    }
+
+    #[async_trait]
+    impl AIProvider for MockProvider {
+        fn provider_id(&self) -> &str {
+            &self.id
+        }
+
+        fn display_name(&self) -> &str {
+            &self.id
+        }
+
+        fn capabilities(&self) -> &ProviderCapabilities {
+            &self.capabilities
+        }
+
+        fn default_model(&self) -> &str {
+            "mock-model"
+        }
+
+        fn available_models(&self) -> &[ModelInfo] {
+            &self.capabilities.models
+        }
+
+        fn status(&self) -> ProviderStatus {
+            ProviderStatus::Connected
+        }
+
+        fn provider_metadata(&self) -> ProviderMetadata {
+            ProviderMetadata::new(&self.id, &self.id)
+        }
+
+        async fn chat(&self, request: ChatRequest) -> Result<ChatResponse> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            *self.last_request.lock().unwrap() = Some(request);
+            if !self.delay.is_zero() {
+                tokio::time::sleep(self.delay).await;
+            }
+            if self.fail {
+                return Err(ProviderError::Unavailable("mock outage".into()));
+            }
+            if let Some(error) = &self.error {
+                return Err(error.clone());
+            }
+            Ok(ChatResponse::new(&self.id, "mock-model", "ok".into()))
+        }
+
+        async fn chat_stream(&self, _request: ChatRequest) -> Result<ChatStream> {
+            Err(ProviderError::StreamingError("not supported".into()))
+        }
+
+        async fn warm_up(&self) -> Result<()> {
+            if self.fail {
+                return Err(ProviderError::Unavailable("mock unreachable".into()));
+            }
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_identical_requests_share_one_call() {
+        let provider = MockProvider::slow("primary", std::time::Duration::from_millis(50));
+        let router = RouterBuilder::new()
+            .with_provider(provider.clone())
+            .with_default("primary")
+            .build();
+        let request = ChatRequest::new(vec![Message::user("list models")]);
+
+        let (first, second) =
+            tokio::join!(router.chat(request.clone()), router.chat(request.clone()));
+        let (first, second) = (first.unwrap(), second.unwrap());
+        assert_eq!(provider.calls(), 1);
+        assert_eq!(first.id, second.id);
+        assert_eq!(router.coalesced_in_flight(), 0);
+
+        let (_, other) = tokio::join!(
+            router.chat(request.clone()),
+            router.chat(ChatRequest::new(vec![Message::user("something else")]))
+        );
+        other.unwrap();
+        assert_eq!(provider.calls(), 3);
+
+        let coalescer = Arc::new(RequestCoalescer::new());
+        let build = || {
+            RouterBuilder::new()
+                .with_provider(provider.clone())
+                .with_default("primary")
+                .with_coalescer(coalescer.clone())
+                .build()
+        };
+        let (left, right) = (build(), build());
+        let (a, b) = tokio::join!(
+            left.chat_with_provider("primary", request.clone()),
+            right.chat_with_provider("primary", request.clone())
+        );
+        assert_eq!(a.unwrap().id, b.unwrap().id);
+        assert_eq!(provider.calls(), 4);
+
+        let mut router = router;
+        router.set_coalescing(false);
+        let (a, b) = tokio::join!(router.chat(request.clone()), router.chat(request));
+        a.unwrap();
+        b.unwrap();
+        assert_eq!(provider.calls(), 6);
+    }
+
+    #[tokio::test]
+    async fn test_alias_resolves_provider_and_model() {
+        let primary = MockProvider::new("primary", false);
+        let secondary = MockProvider::new("secondary", false);
+        let mut config = ProvidersConfig::new();
+        config.set_alias("smart", "secondary/big-model-2024");
+        config.set_alias("fast", "groq/llama-3.3-70b");
+
+        let mut router = RouterBuilder::new()
+            .with_provider(primary.clone())
+            .with_provider(secondary.clone())
+            .with_default("primary")
+            .build();
+        for (name, target) in &config.aliases {
+            router.set_alias(name, target);
+        }
+
+        let (provider, model) = router.resolve_alias("smart").unwrap();
+        assert_eq!(provider.provider_id(), "secondary");
+        assert_eq!(model.as_deref(), Some("big-model-2024"));
+
+        router
+            .chat(ChatRequest::new(vec![Message::user("hi")]).with_model("smart"))
+            .await
+            .unwrap();
+        assert_eq!(primary.calls(), 0);
+        assert_eq!(secondary.calls(), 1);
+        let sent = secondary.last_request.lock().unwrap().clone().unwrap();
+        assert_eq!(sent.model.as_deref(), Some("big-model-2024"));
+
+        let err = router
+            .chat(ChatRequest::new(vec![Message::user("hi")]).with_model("fast"))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("provider 'groq'"));
+
+        let err = router.resolve_alias("missing").err().unwrap();
+        assert!(err.to_string().contains("Unknown model alias 'missing'"));
+    }
 
     #[test]
     fn test_router_new() {
@@ -276,12 +978,35 @@ mod tests {
         assert_eq!(router.fallback_chain.len(), 2);
     }
 
+    #[tokio::test]
+    async fn test_alias_fallback_uses_the_serving_providers_model() {
+        let aliased = MockProvider::new("aliased", true);
+        let backup = MockProvider::new("backup", false);
+        let router = RouterBuilder::new()
+            .with_provider(aliased.clone())
+            .with_provider(backup.clone())
+            .with_default("backup")
+            .with_fallback_chain(vec!["backup".into()])
+            .with_alias("smart", "aliased/big-model-2024")
+            .build();
+
+        let response = router
+            .chat_with_fallback(ChatRequest::new(vec![Message::user("hi")]).with_model("smart"))
+            .await
+            .unwrap();
+        assert_eq!(response.provider, "backup");
+        let tried = aliased.last_request.lock().unwrap().clone().unwrap();
+        assert_eq!(tried.model.as_deref(), Some("big-model-2024"));
+        let served = backup.last_request.lock().unwrap().clone().unwrap();
+        assert_eq!(served.model, None);
+    }
+
     #[test]
     fn test_should_fallback() {
         let router = ProviderRouter::new();
 
         assert!(router.should_fallback(&ProviderError::RateLimited {
-            retry_after_secs: 60
+            retry_after_secs: None
         }));
         assert!(router.should_fallback(&ProviderError::Unavailable("test".into())));
         assert!(router.should_fallback(&ProviderError::Timeout(30)));
@@ -289,6 +1014,11 @@ mod tests {
 
         assert!(!router.should_fallback(&ProviderError::AuthenticationFailed("test".into())));
         assert!(!router.should_fallback(&ProviderError::InvalidResponse("test".into())));
+        assert!(router.should_fallback(&ProviderError::Server {
+            status: 503,
+            message: "test".into()
+        }));
+        assert!(!router.should_fallback(&ProviderError::BadRequest("test".into())));
     }
 
     #[test]
@@ -300,4 +1030,291 @@ mod tests {
         assert!(config.providers.contains_key("ollama"));
         assert!(config.providers.contains_key("mistral"));
     }
+
+    #[tokio::test]
+    async fn test_retry_policy_from_config_wraps_provider_calls() {
+        let config: ProvidersConfig =
+            toml::from_str("[retry]\nmax_retries = 2\nbase_delay_ms = 1\nmax_delay_ms = 5\n")
+                .unwrap();
+        let router = ProviderRouter::from_config(&config).unwrap();
+        assert_eq!(router.retry_policy(), RetryPolicy::new(2, 1, 5));
+        assert_eq!(
+            ProviderRouter::from_config(&ProvidersConfig::new())
+                .unwrap()
+                .retry_policy(),
+            RetryPolicy::default()
+        );
+
+        let down = MockProvider::new("down", true);
+        let router = RouterBuilder::new()
+            .with_provider(down.clone())
+            .with_default("down")
+            .with_retry_policy(config.retry)
+            .build();
+        let result = router
+            .chat(ChatRequest::new(vec![Message::user("hi")]))
+            .await;
+        assert!(matches!(result, Err(ProviderError::Unavailable(_))));
+        assert_eq!(down.calls(), 3);
+        assert_eq!(
+            router.provider_health("down").unwrap().consecutive_failures,
+            1
+        );
+
+        let blocked = MockProvider::blocking("blocked");
+        let router = RouterBuilder::new()
+            .with_provider(blocked.clone())
+            .with_default("blocked")
+            .with_retry_policy(config.retry)
+            .build();
+        assert!(router
+            .chat(ChatRequest::new(vec![Message::user("hi")]))
+            .await
+            .is_err());
+        assert_eq!(blocked.calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_fallback_skips_recently_failed_provider() {
+        let flaky = MockProvider::new("flaky", true);
+        let healthy = MockProvider::new("healthy", false);
+        let router = RouterBuilder::new()
+            .with_provider(flaky.clone())
+            .with_provider(healthy.clone())
+            .with_default("flaky")
+            .with_fallback_chain(vec!["healthy".into()])
+            .build();
+        let request = ChatRequest::new(vec![Message::user("hi")]);
+
+        let first = router.chat_with_fallback(request.clone()).await.unwrap();
+        assert_eq!(first.provider, "healthy");
+        assert_eq!(flaky.calls(), 1);
+
+        let second = router.chat_with_fallback(request).await.unwrap();
+        assert_eq!(second.provider, "healthy");
+        assert_eq!(flaky.calls(), 1);
+        assert_eq!(healthy.calls(), 2);
+        assert_eq!(second.skipped_providers.len(), 1);
+        assert_eq!(second.skipped_providers[0].provider, "flaky");
+        assert!(second.skipped_providers[0].reason.contains("mock outage"));
+    }
+
+    #[tokio::test]
+    async fn test_content_block_falls_through_when_enabled() {
+        let strict = MockProvider::blocking("strict");
+        let lenient = MockProvider::new("lenient", false);
+        let router = |fallback_on_block| {
+            RouterBuilder::new()
+                .with_provider(strict.clone())
+                .with_provider(lenient.clone())
+                .with_default("strict")
+                .with_fallback_chain(vec!["lenient".into()])
+                .with_fallback_on_block(fallback_on_block)
+                .build()
+        };
+        let request = ChatRequest::new(vec![Message::user("hi")]);
+
+        let blocked = router(false).chat_with_fallback(request.clone()).await;
+        assert!(matches!(blocked, Err(ProviderError::ContentBlocked(_))));
+        assert_eq!(lenient.calls(), 0);
+
+        let router = router(true);
+        let response = router.chat_with_fallback(request.clone()).await.unwrap();
+        assert_eq!(response.provider, "lenient");
+        assert_eq!(response.skipped_providers.len(), 1);
+        assert_eq!(response.skipped_providers[0].provider, "strict");
+        assert!(response.skipped_providers[0].content_blocked);
+
+        // A block is not an outage, so the provider is tried again next time
+        router.chat_with_fallback(request).await.unwrap();
+        assert_eq!(strict.calls(), 3);
+        assert!(!router.in_backoff("strict"));
+    }
+
+    #[tokio::test]
+    async fn test_bad_request_leaves_provider_healthy() {
+        let picky =
+            MockProvider::failing_with("picky", ProviderError::BadRequest("too long".into()));
+        let router = RouterBuilder::new()
+            .with_provider(picky.clone())
+            .with_default("picky")
+            .build();
+
+        let result = router
+            .chat(ChatRequest::new(vec![Message::user("hi")]))
+            .await;
+        assert!(matches!(result, Err(ProviderError::BadRequest(_))));
+        assert!(!router.in_backoff("picky"));
+        assert!(router
+            .provider_health("picky")
+            .is_none_or(|health| health.is_healthy()));
+    }
+
+    #[tokio::test]
+    async fn test_fallback_attempts_all_when_every_provider_unhealthy() {
+        let only = MockProvider::new("only", false);
+        let router = RouterBuilder::new()
+            .with_provider(only.clone())
+            .with_default("only")
+            .build();
+        router.record_failure("only", &ProviderError::Timeout(30));
+
+        let response = router
+            .chat_with_fallback(ChatRequest::new(vec![Message::user("hi")]))
+            .await
+            .unwrap();
+
+        assert_eq!(response.provider, "only");
+        assert_eq!(only.calls(), 1);
+        assert!(router.provider_health("only").unwrap().is_healthy());
+    }
+
+    struct MarkerMiddleware {
+        marker: &'static str,
+    }
+
+    impl ProviderMiddleware for MarkerMiddleware {
+        fn name(&self) -> &str {
+            "marker"
+        }
+
+        fn on_request(&self, request: &mut ChatRequest) -> Result<()> {
+            let last = request
+                .messages
+                .last_mut()
+                .ok_or_else(|| ProviderError::Unknown("empty request".into()))?;
+            let text = last.content.as_text().unwrap_or_default().to_string();
+            *last = Message::user(format!("{}{}", text, self.marker));
+            Ok(())
+        }
+
+        fn on_response(&self, response: &mut ChatResponse) -> Result<()> {
+            response.content.push_str(self.marker);
+            Ok(())
+        }
+    }
+
+    struct FailingMiddleware;
+
+    impl ProviderMiddleware for FailingMiddleware {
+        fn name(&self) -> &str {
+            "failing"
+        }
+
+        fn on_request(&self, _request: &mut ChatRequest) -> Result<()> {
+            Err(ProviderError::Unknown("refused".into()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_middleware_chain_reaches_provider_in_order() {
+        let provider = MockProvider::new("mock", false);
+        let router = RouterBuilder::new()
+            .with_provider(provider.clone())
+            .with_default("mock")
+            .with_middleware(Arc::new(MarkerMiddleware { marker: " [a]" }))
+            .with_middleware(Arc::new(MarkerMiddleware { marker: " [b]" }))
+            .build();
+
+        let response = router
+            .chat(ChatRequest::new(vec![Message::user("hello")]))
+            .await
+            .unwrap();
+
+        assert_eq!(provider.last_user_text().as_deref(), Some("hello [a] [b]"));
+        assert_eq!(response.content, "ok [b] [a]");
+    }
+
+    #[tokio::test]
+    async fn test_middleware_error_names_middleware() {
+        let provider = MockProvider::new("mock", false);
+        let router = RouterBuilder::new()
+            .with_provider(provider.clone())
+            .with_default("mock")
+            .with_middleware(Arc::new(FailingMiddleware))
+            .build();
+
+        let error = router
+            .chat(ChatRequest::new(vec![Message::user("hello")]))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            error,
+            ProviderError::Middleware { ref name, .. } if name == "failing"
+        ));
+        assert_eq!(provider.calls(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_probe_corrects_over_optimistic_tool_flag() {
+        let provider = MockProvider::with_models(
+            "mock",
+            vec![ModelInfo {
+                id: "mock-model".to_string(),
+                name: "Mock".to_string(),
+                provider: "mock".to_string(),
+                context_length: 4096,
+                supports_vision: false,
+                supports_tools: true,
+                supports_streaming: false,
+                price_per_1k_input: None,
+                price_per_1k_output: None,
+            }],
+        );
+        let router = RouterBuilder::new()
+            .with_provider(provider.clone())
+            .with_default("mock")
+            .build();
+        assert!(router.all_models()[0].supports_tools);
+
+        let probed = router.probe_model("mock", None, false).await.unwrap();
+        assert_eq!(probed.model, "mock-model");
+        assert!(!probed.supports_tools);
+        assert!(!router.all_models()[0].supports_tools);
+
+        let probe_calls = provider.calls();
+        let request = provider.last_request.lock().unwrap().clone().unwrap();
+        assert!(request.max_tokens.unwrap() <= 16);
+
+        router.probe_model("mock", None, false).await.unwrap();
+        assert_eq!(provider.calls(), probe_calls);
+
+        router.probe_model("mock", None, true).await.unwrap();
+        assert!(provider.calls() > probe_calls);
+    }
+
+    #[tokio::test]
+    async fn test_warm_up_reports_each_provider() {
+        let router = RouterBuilder::new()
+            .with_provider(MockProvider::new("reachable", false))
+            .with_provider(MockProvider::new("unreachable", true))
+            .build();
+
+        let results = router.warm_up().await;
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].provider_id, "reachable");
+        assert!(results[0].success);
+        assert!(results[0].error.is_none());
+        assert_eq!(results[1].provider_id, "unreachable");
+        assert!(!results[1].success);
+        assert!(results[1]
+            .error
+            .as_deref()
+            .unwrap()
+            .contains("mock unreachable"));
+    }
+
+    #[cfg(feature = "ollama")]
+    #[tokio::test]
+    async fn test_warm_up_unreachable_server_fails_gracefully() {
+        let config = crate::ProviderConfig::new("ollama").with_base_url("http://127.0.0.1:1");
+        let provider = Arc::new(OllamaProvider::new(config).unwrap());
+        let router = RouterBuilder::new().with_provider(provider).build();
+
+        let results = router.warm_up().await;
+        assert_eq!(results.len(), 1);
+        assert!(!results[0].success);
+        assert!(results[0].error.is_some());
+    }
 }
@@ -0,0 +1,92 @@
+//! Per-request timeouts shared by every provider
+//!
+//! `ChatRequest::timeout` bounds a whole `chat` call, and for `chat_stream`
+//! both the wait for the response and every gap between chunks.
+
+use std::future::Future;
+use std::time::Duration;
+
+use futures::{stream, StreamExt};
+
+use crate::{provider::ChatStream, ProviderError, Result};
+
+/// `Timeout` error for `limit`, rounded up to whole seconds
+pub(crate) fn timeout_error(limit: Duration) -> ProviderError {
+    ProviderError::Timeout(limit.as_secs_f64().ceil() as u64)
+}
+
+/// Run `future` within `timeout`. A timeout reported by the HTTP client is
+/// given the request's limit as well, so both report the same error.
+pub(crate) async fn with_timeout<T>(
+    timeout: Option<Duration>,
+    future: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    let Some(limit) = timeout else {
+        return future.await;
+    };
+    match tokio::time::timeout(limit, future).await {
+        Ok(Err(ProviderError::Timeout(_))) | Err(_) => Err(timeout_error(limit)),
+        Ok(result) => result,
+    }
+}
+
+/// End `stream` with a `Timeout` error when no chunk arrives within
+/// `timeout` of the previous one
+pub(crate) fn idle_timeout(stream: ChatStream, timeout: Option<Duration>) -> ChatStream {
+    let Some(limit) = timeout else {
+        return stream;
+    };
+    Box::pin(stream::unfold(
+        (stream, false),
+        move |(mut stream, timed_out)| async move {
+            if timed_out {
+                return None;
+            }
+            match tokio::time::timeout(limit, stream.next()).await {
+                Ok(Some(chunk)) => Some((chunk, (stream, false))),
+                Ok(None) => None,
+                Err(_) => Some((Err(timeout_error(limit)), (stream, true))),
+            }
+        },
+    ))
+}
+
+/// Per-request timeout on an HTTP request, when one is set
+pub(crate) trait RequestTimeout {
+    fn request_timeout(self, timeout: Option<Duration>) -> Self;
+}
+
+impl RequestTimeout for reqwest::RequestBuilder {
+    fn request_timeout(self, timeout: Option<Duration>) -> Self {
+        match timeout {
+            Some(limit) => self.timeout(limit),
+            None => self,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::StreamChunk;
+
+    #[tokio::test]
+    async fn test_stalled_stream_ends_with_timeout() {
+        let chunk = StreamChunk {
+            id: "1".into(),
+            provider: "test".into(),
+            model: "test".into(),
+            delta: "Hel".into(),
+            is_final: false,
+            usage: None,
+            finish_reason: None,
+        };
+        let chunks = stream::iter(vec![Ok(chunk)]).chain(stream::pending());
+        let stream = idle_timeout(Box::pin(chunks), Some(Duration::from_millis(50)));
+
+        let items: Vec<Result<StreamChunk>> = stream.collect().await;
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].as_ref().unwrap().delta, "Hel");
+        assert!(matches!(items[1], Err(ProviderError::Timeout(1))));
+    }
+}
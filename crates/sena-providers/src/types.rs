@@ -9,6 +9,13 @@ pub struct Message {
     pub content: MessageContent,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
+    /// Calls the assistant made in this turn, replayed so the provider can
+    /// pair them with the tool results that follow
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCall>,
+    /// For `Role::Tool` messages, the id of the call this result answers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
 }
 
 impl Message {
@@ -17,6 +24,8 @@ impl Message {
             role: Role::User,
             content: MessageContent::Text(content.into()),
             name: None,
+            tool_calls: Vec::new(),
+            tool_call_id: None,
         }
     }
 
@@ -25,6 +34,8 @@ impl Message {
             role: Role::Assistant,
             content: MessageContent::Text(content.into()),
             name: None,
+            tool_calls: Vec::new(),
+            tool_call_id: None,
         }
     }
 
@@ -33,6 +44,50 @@ impl Message {
             role: Role::System,
             content: MessageContent::Text(content.into()),
             name: None,
+            tool_calls: Vec::new(),
+            tool_call_id: None,
+        }
+    }
+
+    /// User message with image attachments placed ahead of the text, built
+    /// with [`ContentPart::image`]
+    pub fn user_with_images(content: impl Into<String>, images: Vec<ContentPart>) -> Self {
+        let mut parts = images;
+        parts.push(ContentPart::Text {
+            text: content.into(),
+        });
+        Self {
+            role: Role::User,
+            content: MessageContent::Parts(parts),
+            name: None,
+            tool_calls: Vec::new(),
+            tool_call_id: None,
+        }
+    }
+
+    /// Assistant turn that asked for `tool_calls`, to precede their results
+    pub fn assistant_with_tool_calls(
+        content: impl Into<String>,
+        tool_calls: Vec<ToolCall>,
+    ) -> Self {
+        Self {
+            tool_calls,
+            ..Self::assistant(content)
+        }
+    }
+
+    /// Output of the tool `name` for the call with id `call_id`
+    pub fn tool_result(
+        call_id: impl Into<String>,
+        name: impl Into<String>,
+        content: impl Into<String>,
+    ) -> Self {
+        Self {
+            role: Role::Tool,
+            content: MessageContent::Text(content.into()),
+            name: Some(name.into()),
+            tool_calls: Vec::new(),
+            tool_call_id: Some(call_id.into()),
         }
     }
 }
@@ -66,6 +121,13 @@ impl MessageContent {
             }),
         }
     }
+
+    pub fn has_images(&self) -> bool {
+        match self {
+            MessageContent::Text(_) => false,
+            MessageContent::Parts(parts) => parts.iter().any(|p| p.image_data().is_some()),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -75,6 +137,72 @@ pub enum ContentPart {
     Text { text: String },
     #[serde(rename = "image_url")]
     ImageUrl { image_url: ImageUrl },
+    /// An `http(s)` or `data:` URL, or bare base64 bytes of type `mime`
+    #[serde(rename = "image")]
+    Image { url_or_base64: String, mime: String },
+}
+
+impl ContentPart {
+    pub fn image(url_or_base64: impl Into<String>, mime: impl Into<String>) -> Self {
+        ContentPart::Image {
+            url_or_base64: url_or_base64.into(),
+            mime: mime.into(),
+        }
+    }
+
+    /// Where an image part's bytes come from, or `None` for text
+    pub fn image_data(&self) -> Option<ImageData<'_>> {
+        match self {
+            ContentPart::Text { .. } => None,
+            ContentPart::ImageUrl { image_url } => Some(ImageData::parse(&image_url.url, None)),
+            ContentPart::Image {
+                url_or_base64,
+                mime,
+            } => Some(ImageData::parse(url_or_base64, Some(mime))),
+        }
+    }
+}
+
+/// An image part normalised for the provider request formats
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImageData<'a> {
+    Url { url: &'a str, mime: Option<&'a str> },
+    Base64 { mime: &'a str, data: &'a str },
+}
+
+impl<'a> ImageData<'a> {
+    /// Mime type assumed when neither the part nor a `data:` URL names one
+    pub const DEFAULT_MIME: &'static str = "image/jpeg";
+
+    fn parse(url_or_base64: &'a str, mime: Option<&'a str>) -> Self {
+        if let Some((media_type, data)) = url_or_base64
+            .strip_prefix("data:")
+            .and_then(|rest| rest.split_once(";base64,"))
+        {
+            return ImageData::Base64 {
+                mime: media_type,
+                data,
+            };
+        }
+        if url_or_base64.starts_with("http://") || url_or_base64.starts_with("https://") {
+            return ImageData::Url {
+                url: url_or_base64,
+                mime,
+            };
+        }
+        ImageData::Base64 {
+            mime: mime.unwrap_or(Self::DEFAULT_MIME),
+            data: url_or_base64,
+        }
+    }
+
+    /// A fetchable URL, inlining base64 bytes as a `data:` URL
+    pub fn to_url(&self) -> String {
+        match self {
+            ImageData::Url { url, .. } => url.to_string(),
+            ImageData::Base64 { mime, data } => format!("data:{};base64,{}", mime, data),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -101,6 +229,10 @@ pub struct ChatRequest {
     pub tools: Option<Vec<Tool>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub metadata: Option<HashMap<String, String>>,
+    /// Limit for the whole call, or for each gap between chunks when
+    /// streaming. Exceeding it fails with `ProviderError::Timeout`.
+    #[serde(skip)]
+    pub timeout: Option<std::time::Duration>,
 }
 
 impl ChatRequest {
@@ -114,9 +246,15 @@ impl ChatRequest {
             stop: None,
             tools: None,
             metadata: None,
+            timeout: None,
         }
     }
 
+    pub fn with_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     pub fn with_model(mut self, model: impl Into<String>) -> Self {
         self.model = Some(model.into());
         self
@@ -131,6 +269,17 @@ impl ChatRequest {
         self.temperature = Some(temperature);
         self
     }
+
+    /// Offer `tools` to the model; anything convertible into a [`Tool`]
+    /// (such as an app's own tool definitions) is accepted
+    pub fn with_tools<T: Into<Tool>>(mut self, tools: impl IntoIterator<Item = T>) -> Self {
+        self.tools = Some(tools.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn has_images(&self) -> bool {
+        self.messages.iter().any(|m| m.content.has_images())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -140,12 +289,14 @@ pub struct ChatResponse {
     pub model: String,
     pub content: String,
     pub role: Role,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub tool_calls: Option<Vec<ToolCall>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCall>,
     pub usage: Usage,
     pub created_at: DateTime<Utc>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub finish_reason: Option<FinishReason>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skipped_providers: Vec<SkippedProvider>,
 }
 
 impl ChatResponse {
@@ -156,10 +307,45 @@ impl ChatResponse {
             model: model.to_string(),
             content,
             role: Role::Assistant,
-            tool_calls: None,
+            tool_calls: Vec::new(),
             usage: Usage::default(),
             created_at: Utc::now(),
             finish_reason: Some(FinishReason::Stop),
+            skipped_providers: Vec::new(),
+        }
+    }
+
+    /// Whether the provider stopped because it hit the token limit
+    pub fn is_truncated(&self) -> bool {
+        self.finish_reason
+            .as_ref()
+            .is_some_and(FinishReason::is_truncated)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SkippedProvider {
+    pub provider: String,
+    pub reason: String,
+    /// Skipped because the provider blocked or refused the content, not
+    /// because it was unreachable
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub content_blocked: bool,
+}
+
+impl SkippedProvider {
+    pub fn new(provider: &str, reason: impl Into<String>) -> Self {
+        Self {
+            provider: provider.to_string(),
+            reason: reason.into(),
+            content_blocked: false,
+        }
+    }
+
+    pub fn blocked(provider: &str, reason: impl Into<String>) -> Self {
+        Self {
+            content_blocked: true,
+            ..Self::new(provider, reason)
         }
     }
 }
@@ -194,6 +380,28 @@ pub enum FinishReason {
     Error,
 }
 
+impl FinishReason {
+    pub fn is_truncated(&self) -> bool {
+        *self == FinishReason::Length
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FinishReason::Stop => "stop",
+            FinishReason::Length => "length",
+            FinishReason::ToolCalls => "tool_calls",
+            FinishReason::ContentFilter => "content_filter",
+            FinishReason::Error => "error",
+        }
+    }
+}
+
+impl std::fmt::Display for FinishReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Tool {
     #[serde(rename = "type")]
@@ -201,6 +409,32 @@ pub struct Tool {
     pub function: ToolFunction,
 }
 
+impl Tool {
+    /// Function tool taking arguments described by the JSON schema `parameters`
+    pub fn function(
+        name: impl Into<String>,
+        description: impl Into<String>,
+        parameters: serde_json::Value,
+    ) -> Self {
+        Self {
+            tool_type: "function".into(),
+            function: ToolFunction {
+                name: name.into(),
+                description: Some(description.into()),
+                parameters: Some(parameters),
+            },
+        }
+    }
+
+    /// The parameter schema, or an empty object schema when none is given
+    pub fn parameters_schema(&self) -> serde_json::Value {
+        self.function
+            .parameters
+            .clone()
+            .unwrap_or_else(|| serde_json::json!({"type": "object", "properties": {}}))
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolFunction {
     pub name: String,
@@ -218,9 +452,35 @@ pub struct ToolCall {
     pub function: ToolCallFunction,
 }
 
+impl ToolCall {
+    pub fn function(
+        id: impl Into<String>,
+        name: impl Into<String>,
+        arguments: impl Into<String>,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            call_type: "function".into(),
+            function: ToolCallFunction {
+                name: name.into(),
+                arguments: arguments.into(),
+            },
+        }
+    }
+
+    /// Arguments parsed as a JSON object; empty arguments read as `{}`
+    pub fn arguments(&self) -> serde_json::Result<serde_json::Value> {
+        if self.function.arguments.trim().is_empty() {
+            return Ok(serde_json::json!({}));
+        }
+        serde_json::from_str(&self.function.arguments)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolCallFunction {
     pub name: String,
+    /// JSON-encoded arguments, as OpenAI sends them
     pub arguments: String,
 }
 
@@ -233,6 +493,12 @@ pub struct ModelInfo {
     pub supports_vision: bool,
     pub supports_tools: bool,
     pub supports_streaming: bool,
+    /// USD per 1K prompt tokens, when the model is in the pricing table
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price_per_1k_input: Option<f64>,
+    /// USD per 1K completion tokens, when the model is in the pricing table
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price_per_1k_output: Option<f64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -245,6 +511,31 @@ pub struct ProviderCapabilities {
     pub models: Vec<ModelInfo>,
 }
 
+impl ProviderCapabilities {
+    /// The curated entry for `id`, or one built from the provider-wide
+    /// capabilities for a model the static list does not know
+    pub fn model_info(&self, provider: &str, id: &str) -> ModelInfo {
+        self.models
+            .iter()
+            .find(|m| m.id == id)
+            .cloned()
+            .unwrap_or_else(|| {
+                ModelInfo {
+                    id: id.to_string(),
+                    name: id.to_string(),
+                    provider: provider.to_string(),
+                    context_length: self.max_context_tokens,
+                    supports_vision: self.vision,
+                    supports_tools: self.tool_use,
+                    supports_streaming: self.streaming,
+                    price_per_1k_input: None,
+                    price_per_1k_output: None,
+                }
+                .with_pricing()
+            })
+    }
+}
+
 impl Default for ProviderCapabilities {
     fn default() -> Self {
         Self {
@@ -265,3 +556,19 @@ pub enum ProviderStatus {
     RateLimited,
     Error,
 }
+
+impl ProviderStatus {
+    /// Status implied by the outcome of a health check
+    pub fn from_health_check(check: &crate::Result<crate::HealthInfo>) -> Self {
+        match check {
+            Ok(_) => ProviderStatus::Connected,
+            Err(crate::ProviderError::RateLimited { .. }) => ProviderStatus::RateLimited,
+            Err(
+                crate::ProviderError::Unavailable(_)
+                | crate::ProviderError::NetworkError(_)
+                | crate::ProviderError::NotConfigured(_),
+            ) => ProviderStatus::Disconnected,
+            Err(_) => ProviderStatus::Error,
+        }
+    }
+}
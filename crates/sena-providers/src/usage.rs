@@ -0,0 +1,507 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::{pricing::ModelPrice, Usage};
+
+/// Token counts and estimated spend for one provider or model
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UsageTotals {
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    /// Estimated USD cost at the prices in effect when each call was made
+    pub cost_usd: f64,
+    /// Requests for models missing from the pricing table, counted at no cost
+    #[serde(default)]
+    pub unpriced_requests: u64,
+    /// Requests that failed; `requests` only counts successful ones
+    #[serde(default)]
+    pub failed_requests: u64,
+    /// Successful requests whose latency was measured, and their summed
+    /// latency
+    #[serde(default)]
+    pub timed_requests: u64,
+    #[serde(default)]
+    pub latency_ms_total: u64,
+}
+
+impl UsageTotals {
+    fn add(&mut self, usage: &Usage, price: Option<ModelPrice>, latency: Option<Duration>) {
+        self.requests += 1;
+        self.prompt_tokens += u64::from(usage.prompt_tokens);
+        self.completion_tokens += u64::from(usage.completion_tokens);
+        match price {
+            Some(price) => self.cost_usd += price.cost(usage),
+            None => self.unpriced_requests += 1,
+        }
+        if let Some(latency) = latency {
+            self.timed_requests += 1;
+            self.latency_ms_total += latency.as_millis() as u64;
+        }
+    }
+
+    fn merge(&mut self, other: &UsageTotals) {
+        self.requests += other.requests;
+        self.prompt_tokens += other.prompt_tokens;
+        self.completion_tokens += other.completion_tokens;
+        self.cost_usd += other.cost_usd;
+        self.unpriced_requests += other.unpriced_requests;
+        self.failed_requests += other.failed_requests;
+        self.timed_requests += other.timed_requests;
+        self.latency_ms_total += other.latency_ms_total;
+    }
+
+    pub fn total_tokens(&self) -> u64 {
+        self.prompt_tokens + self.completion_tokens
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProviderUsage {
+    #[serde(flatten)]
+    pub totals: UsageTotals,
+    #[serde(default)]
+    pub models: BTreeMap<String, UsageTotals>,
+}
+
+impl ProviderUsage {
+    fn merge(&mut self, other: &ProviderUsage) {
+        self.totals.merge(&other.totals);
+        for (model, totals) in &other.models {
+            self.models.entry(model.clone()).or_default().merge(totals);
+        }
+    }
+}
+
+/// One month of usage, as stored in `usage.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageLedger {
+    /// `YYYY-MM` the ledger covers
+    pub month: String,
+    pub updated_at: DateTime<Utc>,
+    #[serde(default)]
+    pub providers: BTreeMap<String, ProviderUsage>,
+}
+
+impl UsageLedger {
+    fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            month: month_of(now),
+            updated_at: now,
+            providers: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderCost {
+    pub provider: String,
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub cost_usd: f64,
+    pub unpriced_requests: u64,
+}
+
+/// Spend per provider for the current month
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostSummary {
+    pub month: String,
+    pub providers: Vec<ProviderCost>,
+    pub total_cost_usd: f64,
+    pub total_tokens: u64,
+}
+
+fn month_of(time: DateTime<Utc>) -> String {
+    time.format("%Y-%m").to_string()
+}
+
+static NEXT_TMP_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Write through a uniquely named temp file and rename, so readers never see
+/// a partial file and concurrent writers never share a temp file. Same
+/// `{file}.{pid}-{n}.tmp` naming as the core `sync::write_atomic`, whose
+/// startup scan removes temps orphaned by a killed process.
+fn write_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, "path has no file name")
+        })?
+        .to_string_lossy();
+    let temp = path.with_file_name(format!(
+        "{}.{}-{}.tmp",
+        file_name,
+        std::process::id(),
+        NEXT_TMP_ID.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let result = std::fs::File::create(&temp)
+        .and_then(|mut file| {
+            file.write_all(contents)?;
+            file.sync_all()
+        })
+        .and_then(|()| std::fs::rename(&temp, path));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    result
+}
+
+fn write_ledger(path: &Path, ledger: &UsageLedger) -> std::io::Result<()> {
+    let content = serde_json::to_string_pretty(ledger)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    write_atomic(path, content.as_bytes())
+}
+
+/// The in-memory ledger and how many updates have been applied to it
+struct Revisioned {
+    ledger: UsageLedger,
+    revision: u64,
+}
+
+/// Accumulates token usage per provider and model into `~/.sena/usage.json`.
+/// When a new month starts the previous ledger is merged into
+/// `usage-YYYY-MM.json` next to it and counting starts again.
+pub struct UsageTracker {
+    path: PathBuf,
+    ledger: Mutex<Revisioned>,
+    /// Serialises file writes and holds the newest revision on disk, so a
+    /// slower writer never replaces a newer snapshot with an older one
+    written: Mutex<u64>,
+}
+
+impl UsageTracker {
+    pub fn usage_path() -> PathBuf {
+        dirs_next::home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(".sena")
+            .join("usage.json")
+    }
+
+    /// Tracker backed by `path`, starting empty when the file is missing or
+    /// unreadable
+    pub fn open(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let ledger = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_else(|| UsageLedger::new(Utc::now()));
+        Self {
+            path,
+            ledger: Mutex::new(Revisioned {
+                ledger,
+                revision: 0,
+            }),
+            written: Mutex::new(0),
+        }
+    }
+
+    pub fn open_default() -> Self {
+        Self::open(Self::usage_path())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Add one response's usage. Updates are applied under the ledger lock
+    /// and the file is written after it is released, newest snapshot wins.
+    pub fn record(&self, provider: &str, model: &str, usage: &Usage) -> std::io::Result<()> {
+        self.record_at(provider, model, usage, None, Utc::now())
+    }
+
+    /// Add one response's usage along with how long it took
+    pub fn record_timed(
+        &self,
+        provider: &str,
+        model: &str,
+        usage: &Usage,
+        latency: Duration,
+    ) -> std::io::Result<()> {
+        self.record_at(provider, model, usage, Some(latency), Utc::now())
+    }
+
+    /// Count a request to `provider` that failed
+    pub fn record_failure(&self, provider: &str) -> std::io::Result<()> {
+        self.update(Utc::now(), |ledger| {
+            ledger
+                .providers
+                .entry(provider.to_string())
+                .or_default()
+                .totals
+                .failed_requests += 1;
+        })
+    }
+
+    fn record_at(
+        &self,
+        provider: &str,
+        model: &str,
+        usage: &Usage,
+        latency: Option<Duration>,
+        now: DateTime<Utc>,
+    ) -> std::io::Result<()> {
+        self.update(now, |ledger| {
+            let price = ModelPrice::lookup(provider, model);
+            let entry = ledger.providers.entry(provider.to_string()).or_default();
+            entry.totals.add(usage, price, latency);
+            entry
+                .models
+                .entry(model.to_string())
+                .or_default()
+                .add(usage, price, latency);
+        })
+    }
+
+    /// Apply `change` to this month's ledger and save it, rolling over to a
+    /// new ledger first when the month has changed
+    fn update(
+        &self,
+        now: DateTime<Utc>,
+        change: impl FnOnce(&mut UsageLedger),
+    ) -> std::io::Result<()> {
+        let (finished, snapshot, revision) = {
+            let mut state = self
+                .ledger
+                .lock()
+                .map_err(|_| std::io::Error::other("usage ledger lock poisoned"))?;
+
+            let finished = (state.ledger.month != month_of(now))
+                .then(|| std::mem::replace(&mut state.ledger, UsageLedger::new(now)));
+
+            change(&mut state.ledger);
+            state.ledger.updated_at = now;
+            state.revision += 1;
+            (finished, state.ledger.clone(), state.revision)
+        };
+
+        let mut written = self
+            .written
+            .lock()
+            .map_err(|_| std::io::Error::other("usage write lock poisoned"))?;
+        if let Some(finished) = finished {
+            self.archive(finished)?;
+        }
+        if revision > *written {
+            write_ledger(&self.path, &snapshot)?;
+            *written = revision;
+        }
+        Ok(())
+    }
+
+    /// Merge a finished month into its archive, keeping whatever the archive
+    /// already holds
+    fn archive(&self, ledger: UsageLedger) -> std::io::Result<()> {
+        if ledger.providers.is_empty() {
+            return Ok(());
+        }
+        let stem = self
+            .path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("usage");
+        let archive = self
+            .path
+            .with_file_name(format!("{}-{}.json", stem, ledger.month));
+
+        let merged = match std::fs::read_to_string(&archive) {
+            Ok(content) => {
+                let mut existing: UsageLedger = serde_json::from_str(&content)
+                    .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+                for (provider, usage) in &ledger.providers {
+                    existing
+                        .providers
+                        .entry(provider.clone())
+                        .or_default()
+                        .merge(usage);
+                }
+                existing.updated_at = existing.updated_at.max(ledger.updated_at);
+                existing
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => ledger,
+            Err(e) => return Err(e),
+        };
+        write_ledger(&archive, &merged)
+    }
+
+    pub fn ledger(&self) -> UsageLedger {
+        self.ledger
+            .lock()
+            .map(|state| state.ledger.clone())
+            .unwrap_or_else(|poisoned| poisoned.into_inner().ledger.clone())
+    }
+
+    /// Spend per provider this month, most expensive first
+    pub fn cost_summary(&self) -> CostSummary {
+        let ledger = self.ledger();
+        let mut providers: Vec<ProviderCost> = ledger
+            .providers
+            .iter()
+            .map(|(provider, usage)| ProviderCost {
+                provider: provider.clone(),
+                requests: usage.totals.requests,
+                prompt_tokens: usage.totals.prompt_tokens,
+                completion_tokens: usage.totals.completion_tokens,
+                cost_usd: usage.totals.cost_usd,
+                unpriced_requests: usage.totals.unpriced_requests,
+            })
+            .collect();
+        providers.sort_by(|a, b| b.cost_usd.total_cmp(&a.cost_usd));
+
+        CostSummary {
+            month: ledger.month,
+            total_cost_usd: providers.iter().map(|p| p.cost_usd).sum(),
+            total_tokens: providers
+                .iter()
+                .map(|p| p.prompt_tokens + p.completion_tokens)
+                .sum(),
+            providers,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::sync::Arc;
+
+    fn usage(prompt: u32, completion: u32) -> Usage {
+        Usage {
+            prompt_tokens: prompt,
+            completion_tokens: completion,
+            total_tokens: prompt + completion,
+        }
+    }
+
+    #[test]
+    fn test_concurrent_records_and_monthly_rollover() {
+        let dir = std::env::temp_dir().join(format!("sena-usage-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("usage.json");
+        let tracker = Arc::new(UsageTracker::open(&path));
+
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let tracker = Arc::clone(&tracker);
+                std::thread::spawn(move || {
+                    tracker
+                        .record("openai", "gpt-4.1", &usage(1000, 1000))
+                        .unwrap();
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        tracker
+            .record("custom", "mystery-model", &usage(10, 10))
+            .unwrap();
+
+        let summary = tracker.cost_summary();
+        assert_eq!(summary.providers[0].provider, "openai");
+        assert_eq!(summary.providers[0].requests, 8);
+        assert!((summary.total_cost_usd - 8.0 * 0.010).abs() < 1e-9);
+        assert_eq!(summary.providers[1].unpriced_requests, 1);
+        assert_eq!(summary.total_tokens, 16_020);
+
+        let reopened = UsageTracker::open(&path);
+        let ledger = reopened.ledger();
+        assert_eq!(ledger.providers["openai"].models["gpt-4.1"].requests, 8);
+
+        let next_month = Utc.with_ymd_and_hms(2099, 1, 5, 0, 0, 0).unwrap();
+        reopened
+            .record_at("openai", "gpt-4.1", &usage(1, 1), None, next_month)
+            .unwrap();
+        assert_eq!(reopened.ledger().month, "2099-01");
+        assert_eq!(reopened.cost_summary().providers[0].requests, 1);
+        let archived = dir.join(format!("usage-{}.json", ledger.month));
+        let archived: UsageLedger =
+            serde_json::from_str(&std::fs::read_to_string(archived).unwrap()).unwrap();
+        assert_eq!(archived.providers["openai"].totals.requests, 8);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_rollover_merges_into_an_existing_archive() {
+        let dir = std::env::temp_dir().join(format!("sena-usage-{}", uuid::Uuid::new_v4()));
+        let tracker = UsageTracker::open(dir.join("usage.json"));
+        let march = Utc.with_ymd_and_hms(2098, 3, 5, 0, 0, 0).unwrap();
+        let april = Utc.with_ymd_and_hms(2098, 4, 5, 0, 0, 0).unwrap();
+
+        tracker
+            .record_at("openai", "gpt-4.1", &usage(10, 10), None, march)
+            .unwrap();
+        tracker
+            .record_at("openai", "gpt-4.1", &usage(10, 10), None, april)
+            .unwrap();
+        // The clock steps back into March and then forward again
+        tracker
+            .record_at("openai", "gpt-4.1", &usage(5, 5), None, march)
+            .unwrap();
+        tracker
+            .record_at("openai", "gpt-4.1", &usage(1, 1), None, april)
+            .unwrap();
+
+        let archived: UsageLedger =
+            serde_json::from_str(&std::fs::read_to_string(dir.join("usage-2098-03.json")).unwrap())
+                .unwrap();
+        let openai = &archived.providers["openai"];
+        assert_eq!(openai.totals.requests, 2);
+        assert_eq!(openai.totals.prompt_tokens, 15);
+        assert_eq!(openai.models["gpt-4.1"].completion_tokens, 15);
+
+        let leftovers: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .flatten()
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "tmp"))
+            .collect();
+        assert!(leftovers.is_empty());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_failures_and_latency_are_counted() {
+        let dir = std::env::temp_dir().join(format!("sena-usage-{}", uuid::Uuid::new_v4()));
+        let tracker = UsageTracker::open(dir.join("usage.json"));
+
+        tracker
+            .record_timed(
+                "openai",
+                "gpt-4.1",
+                &usage(10, 10),
+                Duration::from_millis(300),
+            )
+            .unwrap();
+        tracker
+            .record_timed(
+                "openai",
+                "gpt-4.1",
+                &usage(10, 10),
+                Duration::from_millis(100),
+            )
+            .unwrap();
+        tracker.record("openai", "gpt-4.1", &usage(10, 10)).unwrap();
+        tracker.record_failure("openai").unwrap();
+
+        let totals = &tracker.ledger().providers["openai"].totals;
+        assert_eq!(totals.requests, 3);
+        assert_eq!(totals.failed_requests, 1);
+        assert_eq!(totals.timed_requests, 2);
+        assert_eq!(totals.latency_ms_total, 400);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
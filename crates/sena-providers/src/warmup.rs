@@ -0,0 +1,111 @@
+use reqwest::{header::HeaderMap, Client};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use crate::{ProviderError, Result};
+
+/// How long warmed connections stay pooled without traffic
+pub const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Idle pooled connections kept per host
+pub const POOL_MAX_IDLE_PER_HOST: usize = 4;
+
+/// Outcome of warming one provider
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WarmUpResult {
+    pub provider_id: String,
+    pub success: bool,
+    pub latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Clients built in this process, keyed by their timeout and default
+/// headers. Every router built afterwards reuses them, so connections opened
+/// by a warm-up stay pooled for later requests instead of being dropped with
+/// the router that warmed them.
+static CLIENTS: OnceLock<Mutex<HashMap<u64, Client>>> = OnceLock::new();
+
+/// The process-wide pooled client for `timeout_secs` and `headers`, built on
+/// first use
+pub(crate) fn shared_client(timeout_secs: u64, headers: HeaderMap) -> Result<Client> {
+    let mut hasher = DefaultHasher::new();
+    timeout_secs.hash(&mut hasher);
+    for (name, value) in &headers {
+        name.as_str().hash(&mut hasher);
+        value.as_bytes().hash(&mut hasher);
+    }
+    let key = hasher.finish();
+
+    let mut clients = CLIENTS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(client) = clients.get(&key) {
+        return Ok(client.clone());
+    }
+    let client = Client::builder()
+        .timeout(Duration::from_secs(timeout_secs))
+        .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+        .pool_max_idle_per_host(POOL_MAX_IDLE_PER_HOST)
+        .default_headers(headers)
+        .build()
+        .map_err(|e| ProviderError::NetworkError(e.to_string()))?;
+    clients.insert(key, client.clone());
+    Ok(client)
+}
+
+/// Complete the TCP/TLS handshake with `url` so the pooled connection is
+/// reused by the next request. Any HTTP status counts as success, since an
+/// unauthenticated `HEAD` is typically rejected once the connection is up.
+pub(crate) async fn open_connection(client: &Client, url: &str) -> Result<()> {
+    client
+        .head(url)
+        .send()
+        .await
+        .map(|_| ())
+        .map_err(|e| ProviderError::NetworkError(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[tokio::test]
+    async fn test_connection_warmed_by_one_client_serves_the_next() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let accepted = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&accepted);
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                counter.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(async move {
+                    let mut buf = [0u8; 1024];
+                    while matches!(stream.read(&mut buf).await, Ok(n) if n > 0) {
+                        let reply = b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n";
+                        if stream.write_all(reply).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+
+        let timeout = 4_242;
+        let warmed = shared_client(timeout, HeaderMap::new()).unwrap();
+        open_connection(&warmed, &url).await.unwrap();
+        drop(warmed);
+
+        let later = shared_client(timeout, HeaderMap::new()).unwrap();
+        later.get(&url).send().await.unwrap();
+        assert_eq!(accepted.load(Ordering::SeqCst), 1);
+    }
+}
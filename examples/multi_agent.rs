@@ -13,7 +13,7 @@ async fn main() {
         println!("{}", "-".repeat(40));
 
         let request = ProcessingRequest::new(
-            &format!("Analyze typical {} project structure", agent),
+            format!("Analyze typical {} project structure", agent),
             "analysis",
         );
 
@@ -23,6 +23,7 @@ pub enum StorageType {
 #[derive(Debug, Clone, Serialize)]
 pub struct CredentialStatus {
     pub provider_id: String,
+    pub field_id: String,
     pub has_credential: bool,
     pub source: CredentialSource,
     pub is_valid: Option<bool>,
@@ -35,6 +36,126 @@ pub struct StorageOptions {
     pub config_file_path: String,
 }
 
+/// Outcome of [`CredentialManager::migrate_all`]
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct MigrationReport {
+    /// `provider/field` of every credential that was moved
+    pub migrated: Vec<String>,
+    pub failed: Vec<MigrationFailure>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationFailure {
+    pub provider_id: String,
+    pub field_id: String,
+    pub error: String,
+}
+
+/// One place credentials can be kept
+pub trait CredentialBackend {
+    fn read(&self, provider_id: &str, field_id: &str) -> Result<Option<String>, String>;
+    fn write(&self, provider_id: &str, field_id: &str, value: &str) -> Result<(), String>;
+    fn remove(&self, provider_id: &str, field_id: &str) -> Result<(), String>;
+}
+
+/// The keychain or config file of a [`CredentialManager`]
+struct StorageBackend<'a> {
+    manager: &'a CredentialManager,
+    storage: StorageType,
+}
+
+impl CredentialBackend for StorageBackend<'_> {
+    fn read(&self, provider_id: &str, field_id: &str) -> Result<Option<String>, String> {
+        match self.storage {
+            StorageType::Keychain => Ok(self.manager.get_from_keychain(provider_id, field_id)),
+            StorageType::ConfigFile => Ok(self
+                .manager
+                .load_config()?
+                .credentials
+                .get(provider_id)
+                .and_then(|provider| provider.fields.get(field_id))
+                .cloned()),
+        }
+    }
+
+    fn write(&self, provider_id: &str, field_id: &str, value: &str) -> Result<(), String> {
+        self.manager
+            .store(provider_id, field_id, value, self.storage.clone())
+    }
+
+    fn remove(&self, provider_id: &str, field_id: &str) -> Result<(), String> {
+        match self.storage {
+            StorageType::Keychain => self.manager.remove_from_keychain(provider_id, field_id),
+            StorageType::ConfigFile => self.manager.delete_from_config(provider_id, field_id),
+        }
+    }
+}
+
+/// Copy one credential from `from` to `to`, check the copy reads back
+/// unchanged, and only then remove the source. If the copy can't be
+/// verified, whatever `to` held before is put back and the source is left
+/// alone. Returns whether there was anything to move.
+pub fn migrate_credential(
+    from: &dyn CredentialBackend,
+    to: &dyn CredentialBackend,
+    provider_id: &str,
+    field_id: &str,
+) -> Result<bool, String> {
+    let Some(value) = from.read(provider_id, field_id)? else {
+        return Ok(false);
+    };
+    let previous = to.read(provider_id, field_id)?;
+
+    let verified = to
+        .write(provider_id, field_id, &value)
+        .and_then(|_| to.read(provider_id, field_id))
+        .and_then(|stored| match stored {
+            Some(stored) if stored == value => Ok(()),
+            _ => Err("stored value did not read back".to_string()),
+        });
+    if let Err(e) = verified {
+        let restored = match &previous {
+            Some(previous) => to.write(provider_id, field_id, previous),
+            None => to.remove(provider_id, field_id),
+        };
+        return Err(match restored {
+            Ok(()) => format!("Write not verified, source kept: {}", e),
+            Err(restore) => format!(
+                "Write not verified, source kept: {} (restoring the destination failed: {})",
+                e, restore
+            ),
+        });
+    }
+
+    from.remove(provider_id, field_id)
+        .map_err(|e| format!("Copied, but the source could not be removed: {}", e))?;
+    Ok(true)
+}
+
+/// Migrate each `(provider, field)` independently, so one failure leaves
+/// the others moved and its own secret in the source
+fn migrate_fields(
+    from: &dyn CredentialBackend,
+    to: &dyn CredentialBackend,
+    fields: &[(String, String)],
+) -> MigrationReport {
+    let mut report = MigrationReport::default();
+    for (provider_id, field_id) in fields {
+        match migrate_credential(from, to, provider_id, field_id) {
+            Ok(true) => report
+                .migrated
+                .push(format!("{}/{}", provider_id, field_id)),
+            Ok(false) => {}
+            Err(error) => report.failed.push(MigrationFailure {
+                provider_id: provider_id.clone(),
+                field_id: field_id.clone(),
+                error,
+            }),
+        }
+    }
+    report
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 struct CredentialsConfig {
     credentials: HashMap<String, ProviderCredentials>,
@@ -203,6 +324,16 @@ impl CredentialManager {
         }
     }
 
+    fn remove_from_keychain(&self, provider_id: &str, field_id: &str) -> Result<(), String> {
+        let key = format!("{}_{}", provider_id, field_id);
+        let entry = keyring::Entry::new(KEYRING_SERVICE, &key)
+            .map_err(|e| format!("Keychain entry error: {}", e))?;
+        match entry.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(format!("Keychain delete error: {}", e)),
+        }
+    }
+
     fn delete_from_config(&self, provider_id: &str, field_id: &str) -> Result<(), String> {
         let mut config = self.load_config()?;
 
@@ -231,6 +362,7 @@ impl CredentialManager {
         match credential {
             Some((_, source)) => CredentialStatus {
                 provider_id: provider_id.to_string(),
+                field_id: field_id.to_string(),
                 has_credential: true,
                 source,
                 is_valid: None,
@@ -238,6 +370,7 @@ impl CredentialManager {
             },
             None => CredentialStatus {
                 provider_id: provider_id.to_string(),
+                field_id: field_id.to_string(),
                 has_credential: false,
                 source: CredentialSource::NotSet,
                 is_valid: None,
@@ -263,6 +396,55 @@ impl CredentialManager {
         self.store(provider_id, field_id, &value, storage)
     }
 
+    /// Status of every credential field of every known provider
+    pub fn audit(&self) -> Vec<CredentialStatus> {
+        known_fields()
+            .into_iter()
+            .map(|(provider_id, field_id, env_var)| {
+                self.get_credential_status(&provider_id, &field_id, env_var.as_deref())
+            })
+            .collect()
+    }
+
+    /// Move every stored credential from one backend to the other. Each
+    /// credential is moved on its own; see [`migrate_credential`].
+    pub fn migrate_all(
+        &self,
+        from: StorageType,
+        to: StorageType,
+    ) -> Result<MigrationReport, String> {
+        if from == to {
+            return Err("Source and destination storage are the same".to_string());
+        }
+        if !self.keychain_available {
+            return Err("Keychain is not available on this system".to_string());
+        }
+
+        let mut fields: Vec<(String, String)> = known_fields()
+            .into_iter()
+            .map(|(provider_id, field_id, _)| (provider_id, field_id))
+            .collect();
+        // The config file may also hold fields no provider declares any more
+        for (provider_id, provider) in self.load_config()?.credentials {
+            for field_id in provider.fields.into_keys() {
+                let key = (provider_id.clone(), field_id);
+                if !fields.contains(&key) {
+                    fields.push(key);
+                }
+            }
+        }
+
+        let source = StorageBackend {
+            manager: self,
+            storage: from,
+        };
+        let destination = StorageBackend {
+            manager: self,
+            storage: to,
+        };
+        Ok(migrate_fields(&source, &destination, &fields))
+    }
+
     fn load_config(&self) -> Result<CredentialsConfig, String> {
         if !self.config_path.exists() {
             return Ok(CredentialsConfig::default());
@@ -283,7 +465,7 @@ impl CredentialManager {
         let content =
             toml::to_string_pretty(config).map_err(|e| format!("Cannot serialize config: {}", e))?;
 
-        std::fs::write(&self.config_path, content)
+        sena1996_ai::sync::write_atomic(&self.config_path, content)
             .map_err(|e| format!("Cannot write config: {}", e))
     }
 }
@@ -294,6 +476,37 @@ impl Default for CredentialManager {
     }
 }
 
+/// `(provider, field, env var)` for every auth field of every known provider
+fn known_fields() -> Vec<(String, String, Option<String>)> {
+    sena_providers::get_all_provider_metadata()
+        .into_iter()
+        .flat_map(|meta| {
+            let provider_id = meta.id;
+            meta.auth_schema
+                .fields
+                .into_iter()
+                .map(move |field| (provider_id.clone(), field.id, field.env_var_name))
+        })
+        .collect()
+}
+
+/// Reject a credential whose shape doesn't match the provider's field, so
+/// a truncated or mistyped key is caught before it is stored. Offline; see
+/// [`validate_api_key`] for asking the provider.
+pub fn validate_format(provider_id: &str, field_id: &str, value: &str) -> Result<(), String> {
+    let field = sena_providers::get_all_provider_metadata()
+        .into_iter()
+        .find(|m| m.id == provider_id)
+        .and_then(|m| m.auth_schema.fields.into_iter().find(|f| f.id == field_id));
+
+    match field {
+        Some(field) => field
+            .validate_format(value)
+            .map_err(|e| format!("Invalid credential for {}: {}", provider_id, e)),
+        None => Ok(()),
+    }
+}
+
 pub async fn validate_api_key(provider_id: &str, api_key: &str) -> Result<bool, String> {
     let client = reqwest::Client::builder()
         .timeout(std::time::Duration::from_secs(10))
@@ -387,4 +600,99 @@ mod tests {
         let options = manager.storage_options();
         assert!(options.config_file_path.contains("credentials.toml"));
     }
+
+    /// In-memory backend whose writes can be made to fail or to store a
+    /// different value than asked
+    #[derive(Default)]
+    struct MockBackend {
+        entries: std::cell::RefCell<HashMap<(String, String), String>>,
+        fail_writes: bool,
+        corrupt_writes: bool,
+    }
+
+    impl MockBackend {
+        fn with(provider_id: &str, field_id: &str, value: &str) -> Self {
+            let backend = Self::default();
+            backend.entries.borrow_mut().insert(
+                (provider_id.to_string(), field_id.to_string()),
+                value.to_string(),
+            );
+            backend
+        }
+
+        fn value(&self, provider_id: &str, field_id: &str) -> Option<String> {
+            self.read(provider_id, field_id).unwrap()
+        }
+    }
+
+    impl CredentialBackend for MockBackend {
+        fn read(&self, provider_id: &str, field_id: &str) -> Result<Option<String>, String> {
+            let key = (provider_id.to_string(), field_id.to_string());
+            Ok(self.entries.borrow().get(&key).cloned())
+        }
+
+        fn write(&self, provider_id: &str, field_id: &str, value: &str) -> Result<(), String> {
+            if self.fail_writes {
+                return Err("backend locked".to_string());
+            }
+            let value = if self.corrupt_writes { "" } else { value };
+            self.entries.borrow_mut().insert(
+                (provider_id.to_string(), field_id.to_string()),
+                value.to_string(),
+            );
+            Ok(())
+        }
+
+        fn remove(&self, provider_id: &str, field_id: &str) -> Result<(), String> {
+            let key = (provider_id.to_string(), field_id.to_string());
+            self.entries.borrow_mut().remove(&key);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_migrate_moves_verified_credential() {
+        let from = MockBackend::with("openai", "api_key", "sk-test-123");
+        let to = MockBackend::default();
+
+        assert!(migrate_credential(&from, &to, "openai", "api_key").unwrap());
+        assert_eq!(
+            to.value("openai", "api_key").as_deref(),
+            Some("sk-test-123")
+        );
+        assert_eq!(from.value("openai", "api_key"), None);
+
+        assert!(!migrate_credential(&from, &to, "openai", "api_key").unwrap());
+    }
+
+    #[test]
+    fn test_unverified_migration_keeps_source() {
+        let from = MockBackend::with("claude", "api_key", "sk-ant-new");
+        let to = MockBackend {
+            corrupt_writes: true,
+            ..MockBackend::with("claude", "api_key", "sk-ant-old")
+        };
+        assert!(migrate_credential(&from, &to, "claude", "api_key").is_err());
+        assert_eq!(
+            from.value("claude", "api_key").as_deref(),
+            Some("sk-ant-new")
+        );
+
+        let locked = MockBackend {
+            fail_writes: true,
+            ..MockBackend::default()
+        };
+        let fields = vec![
+            ("claude".to_string(), "api_key".to_string()),
+            ("gemini".to_string(), "api_key".to_string()),
+        ];
+        let report = migrate_fields(&from, &locked, &fields);
+        assert!(report.migrated.is_empty());
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].provider_id, "claude");
+        assert_eq!(
+            from.value("claude", "api_key").as_deref(),
+            Some("sk-ant-new")
+        );
+    }
 }
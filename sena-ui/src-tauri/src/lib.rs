@@ -1,15 +1,26 @@
 mod credentials;
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
-use std::time::Instant;
-use tauri::State;
+use std::time::{Duration, Instant};
+use futures::StreamExt;
+use tauri::{AppHandle, Emitter, State};
 use tokio::sync::RwLock;
 
-use sena_collab::CollabOrchestrator;
+use sena1996_ai::devil::{
+    ConsensusEngine, DevilCancelToken, DevilComparison, DevilConfig, DevilExecutor,
+    ProviderResponseData, ResponseAggregator, WaitMode,
+};
+use sena1996_ai::hub::IdempotencyStore;
+use sena1996_ai::memory::{auto_importance, MemoryType};
+use sena1996_ai::sync::write_atomic;
+use sena_collab::{CollabOrchestrator, TranscriptFormat};
 use sena_providers::{
-    config::ProvidersConfig, get_all_provider_metadata, AuthField, AuthSchema, AuthType,
-    ChatRequest, FieldType, Message, ProviderMetadata, ProviderRouter,
+    config::{ProviderConfig, ProvidersConfig}, get_all_provider_metadata,
+    ollama::OllamaProvider, AuthField, AuthSchema, AuthType,
+    AIProvider, ChatRequest, ContentPart, CostSummary, FieldType, Message, ProviderError,
+    ProviderMetadata, ProviderRouter, RequestCoalescer, Usage, UsageTracker,
 };
 
 use credentials::{CredentialManager, CredentialSource, CredentialStatus, StorageType};
@@ -21,6 +32,9 @@ pub struct ProviderInfo {
     pub status: String,
     pub default_model: String,
     pub has_api_key: bool,
+    /// Masked key, e.g. `sk-...abcd`; the key itself never leaves the backend
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_key_preview: Option<String>,
     pub capabilities: Capabilities,
 }
 
@@ -40,6 +54,8 @@ pub struct ModelInfo {
     pub supports_vision: bool,
     pub supports_tools: bool,
     pub supports_streaming: bool,
+    pub price_per_1k_input: Option<f64>,
+    pub price_per_1k_output: Option<f64>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -49,6 +65,8 @@ pub struct ChatResponseDto {
     pub model: String,
     pub content: String,
     pub usage: UsageDto,
+    pub finish_reason: Option<String>,
+    pub truncated: bool,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -58,6 +76,29 @@ pub struct UsageDto {
     pub total_tokens: u32,
 }
 
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatStreamEventDto {
+    pub stream_id: String,
+    pub delta: String,
+    pub done: bool,
+    pub usage: Option<UsageDto>,
+    pub finish_reason: Option<String>,
+}
+
+const CHAT_STREAM_EVENT: &str = "chat-stream";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PullProgressEventDto {
+    pub pull_id: String,
+    pub model: String,
+    pub status: String,
+    pub percent: Option<f64>,
+    pub done: bool,
+    pub error: Option<String>,
+}
+
+const PULL_PROGRESS_EVENT: &str = "ollama-pull-progress";
+
 #[derive(Debug, Clone, Serialize)]
 pub struct SessionDto {
     pub id: String,
@@ -140,21 +181,40 @@ pub struct HubMessageDto {
     pub message_type: String,
     pub timestamp: u64,
     pub read: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reply_to: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SendMessageResult {
     pub success: bool,
     pub message_id: String,
     pub target: String,
 }
 
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RemovalSummary {
+    pub target: String,
+    pub dry_run: bool,
+    pub items: Vec<String>,
+    pub count: usize,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct TestResultDto {
     pub success: bool,
     pub message: String,
 }
 
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreloadResultDto {
+    pub success: bool,
+    /// The model hasn't been pulled yet, so the UI can offer to pull it
+    pub model_missing: bool,
+    pub message: String,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct HubIdentityDto {
     pub hub_id: String,
@@ -173,7 +233,7 @@ pub struct DiscoveredPeerDto {
     pub version: String,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConnectedPeerDto {
     pub hub_id: String,
     pub name: String,
@@ -222,6 +282,8 @@ struct PeersFile {
     version: String,
     connected_hubs: std::collections::HashMap<String, ConnectedHubData>,
     pending_requests: Vec<PendingRequestData>,
+    #[serde(default)]
+    remote_sessions: std::collections::HashMap<String, Vec<RemoteSessionData>>,
     #[allow(dead_code)]
     last_updated: u64,
 }
@@ -237,6 +299,22 @@ struct ConnectedHubData {
     connected_at: u64,
     last_seen: u64,
     session_count: usize,
+    #[serde(default)]
+    missed_heartbeats: u32,
+    #[serde(default)]
+    offline: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RemoteSessionData {
+    hub_id: String,
+    hub_name: String,
+    session_id: String,
+    session_name: String,
+    role: String,
+    status: String,
+    working_on: Option<String>,
+    working_directory: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -252,10 +330,57 @@ struct PendingRequestData {
     expires_at: u64,
 }
 
+/// Who the app's own commands come from, scoping their idempotency keys
+const UI_SENDER: &str = "hub";
+const MODEL_LIST_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Model lists fetched from provider APIs, by provider id
+pub struct ModelListCache {
+    entries: RwLock<HashMap<String, (Instant, Vec<sena_providers::ModelInfo>)>>,
+    ttl: Duration,
+}
+
+impl ModelListCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            entries: RwLock::new(HashMap::new()),
+            ttl,
+        }
+    }
+
+    /// The provider's remote model list, fetched at most once per `ttl`.
+    /// Failed fetches are not cached and fall back to the static list.
+    async fn models(&self, provider: &Arc<dyn AIProvider>) -> Vec<sena_providers::ModelInfo> {
+        let provider_id = provider.provider_id().to_string();
+        if let Some((fetched_at, models)) = self.entries.read().await.get(&provider_id) {
+            if fetched_at.elapsed() < self.ttl {
+                return models.clone();
+            }
+        }
+
+        match provider.list_models_remote().await {
+            Ok(models) if !models.is_empty() => {
+                self.entries
+                    .write()
+                    .await
+                    .insert(provider_id, (Instant::now(), models.clone()));
+                models
+            }
+            _ => provider.available_models().to_vec(),
+        }
+    }
+}
+
 pub struct AppState {
     pub config: RwLock<ProvidersConfig>,
     pub orchestrator: Arc<RwLock<CollabOrchestrator>>,
     pub start_time: Instant,
+    pub idempotency: IdempotencyStore,
+    pub coalescer: Arc<RequestCoalescer>,
+    pub usage: Arc<UsageTracker>,
+    pub model_lists: ModelListCache,
+    /// Cancel switches for running `devil_execute` calls, by cancel token
+    pub devil_cancels: std::sync::Mutex<HashMap<String, DevilCancelToken>>,
 }
 
 impl AppState {
@@ -267,6 +392,11 @@ impl AppState {
             config: RwLock::new(config),
             orchestrator,
             start_time: Instant::now(),
+            idempotency: IdempotencyStore::new(),
+            coalescer: Arc::new(RequestCoalescer::new()),
+            usage: Arc::new(UsageTracker::open_default()),
+            model_lists: ModelListCache::new(MODEL_LIST_TTL),
+            devil_cancels: std::sync::Mutex::new(HashMap::new()),
         }
     }
 
@@ -328,6 +458,7 @@ async fn get_providers(state: State<'_, AppState>) -> Result<Vec<ProviderInfo>,
                 },
                 default_model: cfg.default_model.clone().unwrap_or_default(),
                 has_api_key: is_configured,
+                api_key_preview: cfg.redacted_api_key(),
                 capabilities: Capabilities {
                     streaming: true,
                     tools: true,
@@ -350,44 +481,36 @@ async fn get_models(
     state: State<'_, AppState>,
     provider_id: Option<String>,
 ) -> Result<Vec<ModelInfo>, String> {
-    let config = state.config.read().await;
-    let router =
-        ProviderRouter::from_config(&config).map_err(|e| format!("Router error: {}", e))?;
+    let router = {
+        let config = state.config.read().await;
+        ProviderRouter::from_config(&config).map_err(|e| format!("Router error: {}", e))?
+    };
 
-    let models: Vec<ModelInfo> = if let Some(pid) = provider_id {
-        router
-            .get_provider(&pid)
-            .map(|p| {
-                p.available_models()
-                    .iter()
-                    .map(|m| ModelInfo {
-                        id: m.id.clone(),
-                        name: m.name.clone(),
-                        provider: m.provider.clone(),
-                        context_length: m.context_length,
-                        supports_vision: m.supports_vision,
-                        supports_tools: m.supports_tools,
-                        supports_streaming: m.supports_streaming,
-                    })
-                    .collect()
-            })
-            .unwrap_or_default()
-    } else {
-        router
-            .all_models()
-            .iter()
-            .map(|m| ModelInfo {
-                id: m.id.clone(),
-                name: m.name.clone(),
-                provider: m.provider.clone(),
-                context_length: m.context_length,
-                supports_vision: m.supports_vision,
-                supports_tools: m.supports_tools,
-                supports_streaming: m.supports_streaming,
-            })
-            .collect()
+    let providers: Vec<Arc<dyn AIProvider>> = match provider_id {
+        Some(pid) => router.get_provider(&pid).cloned().into_iter().collect(),
+        None => router.available_providers().into_iter().cloned().collect(),
     };
 
+    let listings = providers
+        .iter()
+        .map(|provider| state.model_lists.models(provider));
+    let models = futures::future::join_all(listings)
+        .await
+        .into_iter()
+        .flatten()
+        .map(|m| ModelInfo {
+            id: m.id,
+            name: m.name,
+            provider: m.provider,
+            context_length: m.context_length,
+            supports_vision: m.supports_vision,
+            supports_tools: m.supports_tools,
+            supports_streaming: m.supports_streaming,
+            price_per_1k_input: m.price_per_1k_input,
+            price_per_1k_output: m.price_per_1k_output,
+        })
+        .collect();
+
     Ok(models)
 }
 
@@ -402,21 +525,64 @@ async fn send_chat(
         return Err("Message cannot be empty".to_string());
     }
 
+    dispatch_chat(&state, Message::user(&message), provider, model).await
+}
+
+/// Send `message` with the image at `image_path` attached as base64
+#[tauri::command]
+async fn send_chat_with_image(
+    state: State<'_, AppState>,
+    message: String,
+    image_path: String,
+    provider: Option<String>,
+    model: Option<String>,
+) -> Result<ChatResponseDto, String> {
+    let path = std::path::Path::new(&image_path);
+    let mime = image_mime_type(path)
+        .ok_or_else(|| format!("Unsupported image type: {}", image_path))?;
+    let bytes = tokio::fs::read(path)
+        .await
+        .map_err(|e| format!("Cannot read image {}: {}", image_path, e))?;
+    let data = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, bytes);
+
+    let message = Message::user_with_images(message, vec![ContentPart::image(data, mime)]);
+    dispatch_chat(&state, message, provider, model).await
+}
+
+fn image_mime_type(path: &std::path::Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    match extension.as_str() {
+        "png" => Some("image/png"),
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "gif" => Some("image/gif"),
+        "webp" => Some("image/webp"),
+        _ => None,
+    }
+}
+
+async fn dispatch_chat(
+    state: &AppState,
+    message: Message,
+    provider: Option<String>,
+    model: Option<String>,
+) -> Result<ChatResponseDto, String> {
     let config = state.config.read().await;
-    let router =
+    let mut router =
         ProviderRouter::from_config(&config).map_err(|e| format!("Router error: {}", e))?;
+    router.set_coalescer(state.coalescer.clone());
+    router.set_usage_tracker(state.usage.clone());
 
-    let mut request = ChatRequest::new(vec![Message::user(&message)]);
+    let mut request = ChatRequest::new(vec![message]);
 
     if let Some(m) = &model {
         request = request.with_model(m.clone());
     }
 
     let response = if let Some(provider_id) = provider {
-        let target_provider = router
-            .get_provider(&provider_id)
-            .ok_or_else(|| format!("Provider not found: {}", provider_id))?;
-        target_provider.chat(request).await
+        if router.get_provider(&provider_id).is_none() {
+            return Err(format!("Provider not found: {}", provider_id));
+        }
+        router.chat_with_provider(&provider_id, request).await
     } else {
         router.chat_with_fallback(request).await
     };
@@ -432,9 +598,109 @@ async fn send_chat(
                 completion_tokens: resp.usage.completion_tokens,
                 total_tokens: resp.usage.total_tokens,
             },
+            truncated: resp.is_truncated(),
+            finish_reason: resp.finish_reason.map(|r| r.to_string()),
         }),
-        Err(e) => Err(format!("Chat error: {}", e)),
+        Err(e) => Err(chat_error_message(&e)),
+    }
+}
+
+#[tauri::command]
+async fn send_chat_stream(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    stream_id: String,
+    message: String,
+    provider: Option<String>,
+    model: Option<String>,
+) -> Result<ChatResponseDto, String> {
+    if message.trim().is_empty() {
+        return Err("Message cannot be empty".to_string());
     }
+
+    let config = state.config.read().await;
+    let router =
+        ProviderRouter::from_config(&config).map_err(|e| format!("Router error: {}", e))?;
+
+    let mut request = ChatRequest::new(vec![Message::user(&message)]);
+    if let Some(m) = &model {
+        request = request.with_model(m.clone());
+    }
+
+    let stream = if let Some(provider_id) = &provider {
+        let target_provider = router
+            .get_provider(provider_id)
+            .ok_or_else(|| format!("Provider not found: {}", provider_id))?;
+        target_provider.chat_stream(request).await
+    } else {
+        router.chat_stream(request).await
+    };
+    let mut stream = stream.map_err(|e| chat_error_message(&e))?;
+
+    let mut response = ChatResponseDto {
+        id: stream_id.clone(),
+        provider: provider.unwrap_or_default(),
+        model: model.unwrap_or_default(),
+        content: String::new(),
+        usage: UsageDto {
+            prompt_tokens: 0,
+            completion_tokens: 0,
+            total_tokens: 0,
+        },
+        finish_reason: None,
+        truncated: false,
+    };
+
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| chat_error_message(&e))?;
+        let usage = chunk.usage.map(|u| UsageDto {
+            prompt_tokens: u.prompt_tokens,
+            completion_tokens: u.completion_tokens,
+            total_tokens: u.total_tokens,
+        });
+
+        response.content.push_str(&chunk.delta);
+        response.provider = chunk.provider;
+        response.model = chunk.model;
+        if let Some(usage) = &usage {
+            response.usage = usage.clone();
+        }
+        if let Some(reason) = &chunk.finish_reason {
+            response.truncated = reason.is_truncated();
+            response.finish_reason = Some(reason.to_string());
+        }
+
+        let _ = app.emit(
+            CHAT_STREAM_EVENT,
+            ChatStreamEventDto {
+                stream_id: stream_id.clone(),
+                delta: chunk.delta,
+                done: chunk.is_final,
+                usage,
+                finish_reason: chunk.finish_reason.map(|r| r.to_string()),
+            },
+        );
+
+        if chunk.is_final {
+            break;
+        }
+    }
+
+    let usage = Usage {
+        prompt_tokens: response.usage.prompt_tokens,
+        completion_tokens: response.usage.completion_tokens,
+        total_tokens: response.usage.total_tokens,
+    };
+    let _ = state
+        .usage
+        .record(&response.provider, &response.model, &usage);
+
+    Ok(response)
+}
+
+#[tauri::command]
+async fn get_usage_summary(state: State<'_, AppState>) -> Result<CostSummary, String> {
+    Ok(state.usage.cost_summary())
 }
 
 #[tauri::command]
@@ -484,13 +750,10 @@ async fn test_provider(state: State<'_, AppState>, provider_id: String) -> Resul
         }
     };
 
-    let test_request =
-        ChatRequest::new(vec![Message::user("Say 'OK' if you can hear me.")]).with_max_tokens(10);
-
-    match provider.chat(test_request).await {
-        Ok(response) => Ok(TestResultDto {
+    match provider.health_check().await {
+        Ok(info) => Ok(TestResultDto {
             success: true,
-            message: format!("Connected successfully. Response: {}", response.content.chars().take(50).collect::<String>()),
+            message: format!("Connected successfully in {} ms", info.latency_ms),
         }),
         Err(e) => Ok(TestResultDto {
             success: false,
@@ -499,6 +762,88 @@ async fn test_provider(state: State<'_, AppState>, provider_id: String) -> Resul
     }
 }
 
+/// Load an Ollama model as soon as the user selects it, so the first chat
+/// doesn't wait for a cold start
+#[tauri::command]
+async fn preload_model(state: State<'_, AppState>, model: String) -> Result<PreloadResultDto, String> {
+    let config = state
+        .config
+        .read()
+        .await
+        .get_provider("ollama")
+        .cloned()
+        .unwrap_or_else(|| ProviderConfig::new("ollama"));
+    let provider = OllamaProvider::new(config).map_err(|e| e.to_string())?;
+
+    match provider.preload(&model).await {
+        Ok(()) => Ok(PreloadResultDto {
+            success: true,
+            model_missing: false,
+            message: format!("{} is loaded", model),
+        }),
+        Err(ProviderError::ModelNotFound(_)) => Ok(PreloadResultDto {
+            success: false,
+            model_missing: true,
+            message: format!("{} is not pulled yet. Run `ollama pull {}` first.", model, model),
+        }),
+        Err(e) => Ok(PreloadResultDto {
+            success: false,
+            model_missing: false,
+            message: format!("Preload failed: {}", e),
+        }),
+    }
+}
+
+/// Download an Ollama model, forwarding each progress line as an
+/// `ollama-pull-progress` event. A failed or interrupted pull ends with an
+/// event carrying `error` as well as the returned error.
+#[tauri::command]
+async fn pull_ollama_model(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    pull_id: String,
+    model: String,
+) -> Result<(), String> {
+    let config = state
+        .config
+        .read()
+        .await
+        .get_provider("ollama")
+        .cloned()
+        .unwrap_or_else(|| ProviderConfig::new("ollama"));
+    let provider = OllamaProvider::new(config).map_err(|e| e.to_string())?;
+
+    let event = |status: String, percent: Option<f64>, done: bool, error: Option<String>| {
+        PullProgressEventDto {
+            pull_id: pull_id.clone(),
+            model: model.clone(),
+            status,
+            percent,
+            done,
+            error,
+        }
+    };
+    let fail = |error: ProviderError| {
+        let message = format!("Pull failed: {}", error);
+        let _ = app.emit(
+            PULL_PROGRESS_EVENT,
+            event("error".to_string(), None, true, Some(message.clone())),
+        );
+        message
+    };
+
+    let mut progress = provider.pull_model(&model).await.map_err(fail)?;
+    while let Some(update) = progress.next().await {
+        let update = update.map_err(fail)?;
+        let done = update.is_done();
+        let _ = app.emit(
+            PULL_PROGRESS_EVENT,
+            event(update.status, update.percent, done, None),
+        );
+    }
+    Ok(())
+}
+
 #[tauri::command]
 async fn create_session(
     state: State<'_, AppState>,
@@ -585,6 +930,32 @@ async fn list_sessions(state: State<'_, AppState>) -> Result<Vec<SessionDto>, St
     Ok(sessions)
 }
 
+#[tauri::command]
+async fn export_transcript(
+    state: State<'_, AppState>,
+    session_id: String,
+    format: Option<String>,
+) -> Result<String, String> {
+    let format = match format.as_deref().unwrap_or("markdown") {
+        "markdown" | "md" => TranscriptFormat::Markdown,
+        "json" => TranscriptFormat::Json,
+        other => return Err(format!("Unknown transcript format: {}", other)),
+    };
+
+    let config = state.config.read().await;
+    let mut orchestrator = state.orchestrator.write().await;
+    for provider_config in config.providers.values() {
+        if let Some(key) = provider_config.get_api_key() {
+            orchestrator.add_secret(key);
+        }
+    }
+
+    orchestrator
+        .export_transcript(&session_id, format)
+        .await
+        .map_err(|e| format!("Export transcript error: {}", e))
+}
+
 #[tauri::command]
 async fn get_health(state: State<'_, AppState>) -> Result<HealthDto, String> {
     let config = state.config.read().await;
@@ -625,6 +996,18 @@ async fn get_health(state: State<'_, AppState>) -> Result<HealthDto, String> {
     })
 }
 
+/// Error text shown in the chat view. Safety blocks get a plain explanation
+/// instead of looking like a failed request.
+fn chat_error_message(error: &ProviderError) -> String {
+    match error {
+        ProviderError::ContentBlocked(reason) => format!(
+            "Response blocked by the provider's safety filter ({}). Rephrase the message or adjust the provider's safety settings.",
+            reason
+        ),
+        _ => format!("Chat error [{}]: {}", error.kind(), error),
+    }
+}
+
 fn calculate_health_score(connected: usize, total: usize) -> u32 {
     if total == 0 {
         return 50;
@@ -687,6 +1070,23 @@ async fn list_cli_sessions() -> Result<Vec<CliSessionDto>, String> {
 
 #[tauri::command]
 async fn send_message_to_session(
+    state: State<'_, AppState>,
+    target_session: String,
+    message: String,
+    idempotency_key: Option<String>,
+) -> Result<SendMessageResult, String> {
+    state
+        .idempotency
+        .run_async(
+            UI_SENDER,
+            "send_message_to_session",
+            idempotency_key.as_deref(),
+            || deliver_session_message(target_session, message),
+        )
+        .await
+}
+
+async fn deliver_session_message(
     target_session: String,
     message: String,
 ) -> Result<SendMessageResult, String> {
@@ -745,6 +1145,7 @@ async fn send_message_to_session(
         message_type: "Direct".to_string(),
         timestamp,
         read: false,
+        reply_to: None,
     };
 
     messages.push(new_message);
@@ -752,7 +1153,7 @@ async fn send_message_to_session(
     let json = serde_json::to_string_pretty(&messages)
         .map_err(|e| format!("Cannot serialize: {}", e))?;
 
-    std::fs::write(&message_file, json)
+    write_atomic(&message_file, json)
         .map_err(|e| format!("Cannot write messages: {}", e))?;
 
     Ok(SendMessageResult {
@@ -762,18 +1163,40 @@ async fn send_message_to_session(
     })
 }
 
-#[tauri::command]
-async fn get_all_messages() -> Result<Vec<HubMessageDto>, String> {
-    let messages_dir = dirs::home_dir()
+/// Read receipts written by the hub's message queue: message id to the
+/// sessions that have read it
+const READ_RECEIPTS_FILE: &str = "read_receipts.json";
+
+/// Reader recorded for messages marked read from the desktop app
+const UI_READER: &str = "hub";
+
+type ReadReceipts = std::collections::BTreeMap<String, std::collections::BTreeSet<String>>;
+
+fn hub_messages_dir() -> Result<std::path::PathBuf, String> {
+    Ok(dirs::home_dir()
         .ok_or("Cannot find home directory")?
         .join(".claude")
         .join("hub")
-        .join("messages");
+        .join("messages"))
+}
+
+fn load_read_receipts(messages_dir: &std::path::Path) -> ReadReceipts {
+    std::fs::read_to_string(messages_dir.join(READ_RECEIPTS_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+#[tauri::command]
+async fn get_all_messages() -> Result<Vec<HubMessageDto>, String> {
+    let messages_dir = hub_messages_dir()?;
 
     if !messages_dir.exists() {
         return Ok(Vec::new());
     }
 
+    let receipts = load_read_receipts(&messages_dir);
+
     let mut all_messages: Vec<HubMessageDto> = Vec::new();
 
     let entries = std::fs::read_dir(&messages_dir)
@@ -781,7 +1204,9 @@ async fn get_all_messages() -> Result<Vec<HubMessageDto>, String> {
 
     for entry in entries.flatten() {
         let path = entry.path();
-        if path.extension().is_some_and(|ext| ext == "json") {
+        if path.extension().is_some_and(|ext| ext == "json")
+            && path.file_name().is_some_and(|name| name != READ_RECEIPTS_FILE)
+        {
             if let Ok(content) = std::fs::read_to_string(&path) {
                 if let Ok(messages) = serde_json::from_str::<Vec<HubMessageDto>>(&content) {
                     all_messages.extend(messages);
@@ -790,11 +1215,39 @@ async fn get_all_messages() -> Result<Vec<HubMessageDto>, String> {
         }
     }
 
+    // A direct message is read once its recipient read it; a broadcast
+    // once the app has marked it read
+    for message in &mut all_messages {
+        if let Some(readers) = receipts.get(&message.id) {
+            message.read |= readers.contains(UI_READER)
+                || (message.to != "all" && readers.contains(&message.to));
+        }
+    }
+
     all_messages.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
 
     Ok(all_messages)
 }
 
+/// Record that `session_id` (the app itself when omitted) read a hub message
+#[tauri::command]
+async fn mark_message_read(message_id: String, session_id: Option<String>) -> Result<(), String> {
+    let messages_dir = hub_messages_dir()?;
+    std::fs::create_dir_all(&messages_dir)
+        .map_err(|e| format!("Cannot create messages dir: {}", e))?;
+
+    let mut receipts = load_read_receipts(&messages_dir);
+    receipts
+        .entry(message_id)
+        .or_default()
+        .insert(session_id.unwrap_or_else(|| UI_READER.to_string()));
+
+    let json = serde_json::to_string_pretty(&receipts)
+        .map_err(|e| format!("Cannot serialize read receipts: {}", e))?;
+    write_atomic(messages_dir.join(READ_RECEIPTS_FILE), json)
+        .map_err(|e| format!("Cannot write read receipts: {}", e))
+}
+
 #[tauri::command]
 async fn broadcast_message(message: String) -> Result<SendMessageResult, String> {
     let messages_dir = dirs::home_dir()
@@ -831,6 +1284,7 @@ async fn broadcast_message(message: String) -> Result<SendMessageResult, String>
         message_type: "Broadcast".to_string(),
         timestamp,
         read: false,
+        reply_to: None,
     };
 
     messages.push(new_message);
@@ -838,7 +1292,7 @@ async fn broadcast_message(message: String) -> Result<SendMessageResult, String>
     let json = serde_json::to_string_pretty(&messages)
         .map_err(|e| format!("Cannot serialize: {}", e))?;
 
-    std::fs::write(&broadcast_file, json)
+    write_atomic(&broadcast_file, json)
         .map_err(|e| format!("Cannot write broadcast: {}", e))?;
 
     Ok(SendMessageResult {
@@ -849,27 +1303,39 @@ async fn broadcast_message(message: String) -> Result<SendMessageResult, String>
 }
 
 #[tauri::command]
-async fn clear_message_history() -> Result<(), String> {
-    let messages_dir = dirs::home_dir()
-        .ok_or("Cannot find home directory")?
-        .join(".claude")
-        .join("hub")
-        .join("messages");
+async fn clear_message_history(dry_run: Option<bool>) -> Result<RemovalSummary, String> {
+    clear_messages_in(&get_hub_dir()?.join("messages"), dry_run.unwrap_or(false))
+}
+
+fn clear_messages_in(
+    messages_dir: &std::path::Path,
+    dry_run: bool,
+) -> Result<RemovalSummary, String> {
+    let mut summary = RemovalSummary {
+        target: "message history".to_string(),
+        dry_run,
+        ..Default::default()
+    };
 
     if messages_dir.exists() {
-        let entries = std::fs::read_dir(&messages_dir)
+        let entries = std::fs::read_dir(messages_dir)
             .map_err(|e| format!("Cannot read messages dir: {}", e))?;
 
         for entry in entries.flatten() {
             let path = entry.path();
             if path.is_file() {
-                std::fs::remove_file(&path)
-                    .map_err(|e| format!("Cannot remove file: {}", e))?;
+                if !dry_run {
+                    std::fs::remove_file(&path)
+                        .map_err(|e| format!("Cannot remove file: {}", e))?;
+                }
+                summary.items.push(entry.file_name().to_string_lossy().to_string());
             }
         }
     }
 
-    Ok(())
+    summary.items.sort();
+    summary.count = summary.items.len();
+    Ok(summary)
 }
 
 fn get_hub_dir() -> Result<std::path::PathBuf, String> {
@@ -912,7 +1378,7 @@ fn ensure_hub_identity() -> Result<HubIdentityFile, String> {
         let json = serde_json::to_string_pretty(&identity)
             .map_err(|e| format!("Cannot serialize identity: {}", e))?;
 
-        std::fs::write(&identity_file, json)
+        write_atomic(&identity_file, json)
             .map_err(|e| format!("Cannot write identity: {}", e))?;
 
         Ok(identity)
@@ -951,7 +1417,7 @@ async fn set_hub_name(name: String) -> Result<(), String> {
     let json = serde_json::to_string_pretty(&identity)
         .map_err(|e| format!("Cannot serialize identity: {}", e))?;
 
-    std::fs::write(&identity_file, json)
+    write_atomic(&identity_file, json)
         .map_err(|e| format!("Cannot write identity: {}", e))
 }
 
@@ -993,7 +1459,7 @@ async fn get_connected_peers() -> Result<Vec<ConnectedPeerDto>, String> {
                 name: h.name,
                 address: h.address,
                 port: h.port,
-                is_online: now.saturating_sub(h.last_seen) < 60,
+                is_online: !h.offline,
                 session_count: h.session_count,
                 connected_since,
             }
@@ -1040,7 +1506,23 @@ async fn get_pending_requests() -> Result<Vec<ConnectionRequestDto>, String> {
 }
 
 #[tauri::command]
-async fn approve_peer_request(request_id: String) -> Result<ConnectedPeerDto, String> {
+async fn approve_peer_request(
+    state: State<'_, AppState>,
+    request_id: String,
+    idempotency_key: Option<String>,
+) -> Result<ConnectedPeerDto, String> {
+    state
+        .idempotency
+        .run_async(
+            UI_SENDER,
+            "approve_peer_request",
+            idempotency_key.as_deref(),
+            || approve_pending_peer(request_id),
+        )
+        .await
+}
+
+async fn approve_pending_peer(request_id: String) -> Result<ConnectedPeerDto, String> {
     let hub_dir = get_hub_dir()?;
     let peers_file = hub_dir.join("peers.json");
 
@@ -1068,7 +1550,7 @@ async fn approve_peer_request(request_id: String) -> Result<ConnectedPeerDto, St
         data.pending_requests.retain(|r| r.request_id != request_id);
         let json = serde_json::to_string_pretty(&data)
             .map_err(|e| format!("Cannot serialize: {}", e))?;
-        std::fs::write(&peers_file, json)
+        write_atomic(&peers_file, json)
             .map_err(|e| format!("Cannot write: {}", e))?;
         return Err("Request has expired".to_string());
     }
@@ -1090,6 +1572,8 @@ async fn approve_peer_request(request_id: String) -> Result<ConnectedPeerDto, St
         connected_at: now,
         last_seen: now,
         session_count: 0,
+        missed_heartbeats: 0,
+        offline: false,
     };
 
     data.connected_hubs.insert(request.from_hub_id.clone(), connected_hub.clone());
@@ -1099,7 +1583,7 @@ async fn approve_peer_request(request_id: String) -> Result<ConnectedPeerDto, St
     let json = serde_json::to_string_pretty(&data)
         .map_err(|e| format!("Cannot serialize: {}", e))?;
 
-    std::fs::write(&peers_file, json)
+    write_atomic(&peers_file, json)
         .map_err(|e| format!("Cannot write: {}", e))?;
 
     Ok(ConnectedPeerDto {
@@ -1144,30 +1628,49 @@ async fn reject_peer_request(request_id: String) -> Result<(), String> {
     let json = serde_json::to_string_pretty(&data)
         .map_err(|e| format!("Cannot serialize: {}", e))?;
 
-    std::fs::write(&peers_file, json)
+    write_atomic(&peers_file, json)
         .map_err(|e| format!("Cannot write: {}", e))
 }
 
 #[tauri::command]
-async fn disconnect_peer(hub_id: String) -> Result<(), String> {
-    let hub_dir = get_hub_dir()?;
-    let peers_file = hub_dir.join("peers.json");
-
+async fn disconnect_peer(hub_id: String, dry_run: Option<bool>) -> Result<RemovalSummary, String> {
+    disconnect_peer_in(
+        &get_hub_dir()?.join("peers.json"),
+        &hub_id,
+        dry_run.unwrap_or(false),
+    )
+}
+
+fn disconnect_peer_in(
+    peers_file: &std::path::Path,
+    hub_id: &str,
+    dry_run: bool,
+) -> Result<RemovalSummary, String> {
     if !peers_file.exists() {
         return Err("No peers file found".to_string());
     }
 
-    let content = std::fs::read_to_string(&peers_file)
+    let content = std::fs::read_to_string(peers_file)
         .map_err(|e| format!("Cannot read peers file: {}", e))?;
 
     let mut data: PeersFile = serde_json::from_str(&content)
         .map_err(|e| format!("Cannot parse peers file: {}", e))?;
 
-    if !data.connected_hubs.contains_key(&hub_id) {
+    let Some(hub) = data.connected_hubs.get(hub_id) else {
         return Err(format!("Hub {} not connected", hub_id));
+    };
+
+    let summary = RemovalSummary {
+        target: "connected hubs".to_string(),
+        dry_run,
+        items: vec![format!("{} ({})", hub.hub_id, hub.name)],
+        count: 1,
+    };
+    if dry_run {
+        return Ok(summary);
     }
 
-    data.connected_hubs.remove(&hub_id);
+    data.connected_hubs.remove(hub_id);
 
     let now = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
@@ -1178,8 +1681,10 @@ async fn disconnect_peer(hub_id: String) -> Result<(), String> {
     let json = serde_json::to_string_pretty(&data)
         .map_err(|e| format!("Cannot serialize: {}", e))?;
 
-    std::fs::write(&peers_file, json)
-        .map_err(|e| format!("Cannot write: {}", e))
+    write_atomic(peers_file, json)
+        .map_err(|e| format!("Cannot write: {}", e))?;
+
+    Ok(summary)
 }
 
 #[tauri::command]
@@ -1211,20 +1716,44 @@ async fn get_federated_sessions() -> Result<Vec<FederatedSessionDto>, String> {
         }
     }
 
+    // Sessions of hubs that stopped answering heartbeats are left out
+    let peers_path = hub_dir.join("peers.json");
+    if let Some(peers) = std::fs::read_to_string(&peers_path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<PeersFile>(&content).ok())
+    {
+        for (hub_id, sessions) in peers.remote_sessions {
+            let online = peers
+                .connected_hubs
+                .get(&hub_id)
+                .is_some_and(|hub| !hub.offline);
+            if !online {
+                continue;
+            }
+            for session in sessions {
+                all_sessions.push(FederatedSessionDto {
+                    hub_id: session.hub_id,
+                    hub_name: session.hub_name,
+                    session_id: session.session_id,
+                    session_name: session.session_name,
+                    role: session.role,
+                    status: session.status,
+                    working_on: session.working_on,
+                    working_directory: session.working_directory,
+                    is_local: false,
+                });
+            }
+        }
+    }
+
     Ok(all_sessions)
 }
 
+/// Only the passkey's hash is kept on disk, so it can be shown once, when
+/// generated
 #[tauri::command]
 async fn get_hub_passkey() -> Result<String, String> {
-    let hub_dir = get_hub_dir()?;
-    let passkey_file = hub_dir.join("passkey.txt");
-
-    if !passkey_file.exists() {
-        return Err("No passkey generated".to_string());
-    }
-
-    std::fs::read_to_string(&passkey_file)
-        .map_err(|e| format!("Cannot read passkey: {}", e))
+    Err("The passkey is only shown when generated".to_string())
 }
 
 #[tauri::command]
@@ -1241,7 +1770,11 @@ async fn generate_hub_passkey() -> Result<String, String> {
         &passkey_bytes,
     );
 
-    std::fs::write(&passkey_file, &passkey)
+    let hash = {
+        use sha2::{Digest, Sha256};
+        hex::encode(Sha256::digest(passkey.as_bytes()))
+    };
+    write_atomic(&passkey_file, &hash)
         .map_err(|e| format!("Cannot write passkey: {}", e))?;
 
     Ok(passkey)
@@ -1375,6 +1908,10 @@ pub struct MemoryEntryDto {
     pub created_at: String,
     pub updated_at: String,
     pub access_count: u64,
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decayed_at: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -1440,8 +1977,10 @@ async fn add_memory(
     content: String,
     memory_type: String,
     tags: Vec<String>,
-    importance: f64,
+    importance: Option<f64>,
 ) -> Result<MemoryEntryDto, String> {
+    let importance = importance
+        .unwrap_or_else(|| auto_importance(&content, &MemoryType::parse(&memory_type)));
     let memory_dir = get_memory_dir()?;
     std::fs::create_dir_all(&memory_dir)
         .map_err(|e| format!("Cannot create memory dir: {}", e))?;
@@ -1466,6 +2005,8 @@ async fn add_memory(
         created_at: now.to_rfc3339(),
         updated_at: now.to_rfc3339(),
         access_count: 0,
+        pinned: false,
+        decayed_at: None,
     };
 
     memories.push(new_memory.clone());
@@ -1473,7 +2014,7 @@ async fn add_memory(
     let json = serde_json::to_string_pretty(&memories)
         .map_err(|e| format!("Cannot serialize: {}", e))?;
 
-    std::fs::write(&memory_file, json)
+    write_atomic(&memory_file, json)
         .map_err(|e| format!("Cannot write memories: {}", e))?;
 
     Ok(new_memory)
@@ -1504,10 +2045,57 @@ async fn delete_memory(id: String) -> Result<(), String> {
     let json = serde_json::to_string_pretty(&memories)
         .map_err(|e| format!("Cannot serialize: {}", e))?;
 
-    std::fs::write(&memory_file, json)
+    write_atomic(&memory_file, json)
         .map_err(|e| format!("Cannot write memories: {}", e))
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryImportErrorDto {
+    pub line: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryImportSummaryDto {
+    pub imported: usize,
+    pub skipped: usize,
+    pub errors: Vec<MemoryImportErrorDto>,
+}
+
+/// Run `sena --format json memory <args>`, which validates and merges
+/// entries the same way as the CLI
+fn run_memory_cli(args: &[&str]) -> Result<Vec<u8>, String> {
+    let output = std::process::Command::new("./target/release/sena")
+        .args(["--format", "json", "memory"])
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run sena: {}", e))?;
+    if output.status.success() {
+        Ok(output.stdout)
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+#[tauri::command]
+async fn export_memories(path: String) -> Result<usize, String> {
+    let stdout = run_memory_cli(&["export", &path])?;
+    let result: serde_json::Value =
+        serde_json::from_slice(&stdout).map_err(|e| format!("Invalid export result: {}", e))?;
+    Ok(result["exported"].as_u64().unwrap_or(0) as usize)
+}
+
+/// `strategy` is `skip` (default), `overwrite` or `keep-newer`
+#[tauri::command]
+async fn import_memories(
+    path: String,
+    strategy: Option<String>,
+) -> Result<MemoryImportSummaryDto, String> {
+    let strategy = strategy.unwrap_or_else(|| "skip".to_string());
+    let stdout = run_memory_cli(&["import", &path, "--strategy", &strategy])?;
+    serde_json::from_slice(&stdout).map_err(|e| format!("Invalid import result: {}", e))
+}
+
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ProviderMetadataDto {
@@ -1546,6 +2134,7 @@ pub struct AuthFieldDto {
 #[serde(rename_all = "camelCase")]
 pub struct CredentialStatusDto {
     pub provider_id: String,
+    pub field_id: String,
     pub has_credential: bool,
     pub source: String,
     pub is_valid: Option<bool>,
@@ -1625,6 +2214,7 @@ fn convert_credential_status_to_dto(status: &CredentialStatus) -> CredentialStat
 
     CredentialStatusDto {
         provider_id: status.provider_id.clone(),
+        field_id: status.field_id.clone(),
         has_credential: status.has_credential,
         source: source.to_string(),
         is_valid: status.is_valid,
@@ -1672,6 +2262,7 @@ async fn save_credential(
         _ => return Err(format!("Invalid storage type: {}", storage_type)),
     };
 
+    credentials::validate_format(&provider_id, &field_id, &value)?;
     manager.store(&provider_id, &field_id, &value, storage.clone())?;
 
     let mut config = state.config.write().await;
@@ -1743,6 +2334,10 @@ async fn import_env_to_storage(
         _ => return Err(format!("Invalid storage type: {}", storage_type)),
     };
 
+    if let Ok(value) = std::env::var(&env_var) {
+        credentials::validate_format(&provider_id, field_id, &value)
+            .map_err(|e| format!("{} from {}", e, env_var))?;
+    }
     manager.import_from_env(&provider_id, field_id, &env_var, storage)?;
 
     if let Ok(value) = std::env::var(&env_var) {
@@ -1759,6 +2354,56 @@ async fn import_env_to_storage(
     Ok(())
 }
 
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationFailureDto {
+    pub provider_id: String,
+    pub field_id: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationReportDto {
+    pub migrated: Vec<String>,
+    pub failed: Vec<MigrationFailureDto>,
+}
+
+#[tauri::command]
+async fn audit_credentials() -> Result<Vec<CredentialStatusDto>, String> {
+    let manager = CredentialManager::new();
+    Ok(manager
+        .audit()
+        .iter()
+        .map(convert_credential_status_to_dto)
+        .collect())
+}
+
+#[tauri::command]
+async fn migrate_credentials(from: String, to: String) -> Result<MigrationReportDto, String> {
+    let storage = |name: &str| match name {
+        "keychain" => Ok(StorageType::Keychain),
+        "config" => Ok(StorageType::ConfigFile),
+        _ => Err(format!("Invalid storage type: {}", name)),
+    };
+
+    let manager = CredentialManager::new();
+    let report = manager.migrate_all(storage(&from)?, storage(&to)?)?;
+
+    Ok(MigrationReportDto {
+        migrated: report.migrated,
+        failed: report
+            .failed
+            .into_iter()
+            .map(|f| MigrationFailureDto {
+                provider_id: f.provider_id,
+                field_id: f.field_id,
+                error: f.error,
+            })
+            .collect(),
+    })
+}
+
 #[tauri::command]
 async fn get_storage_options_cmd() -> Result<StorageOptionsDto, String> {
     let manager = CredentialManager::new();
@@ -1907,6 +2552,66 @@ async fn guardian_check(content: String) -> Result<HallucinationCheckDto, String
     })
 }
 
+/// Read from `sena --format json guardian stats` (snake_case), sent to the UI
+/// in camelCase
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all(serialize = "camelCase"))]
+pub struct HallucinationStatsDto {
+    pub count: usize,
+    pub flagged: usize,
+    pub mean: f64,
+    pub min: f64,
+    pub max: f64,
+    pub percentiles: ScorePercentilesDto,
+    pub thresholds: Vec<ThresholdImpactDto>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScorePercentilesDto {
+    pub p50: f64,
+    pub p75: f64,
+    pub p90: f64,
+    pub p95: f64,
+    pub p99: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all(serialize = "camelCase"))]
+pub struct ThresholdImpactDto {
+    pub threshold: f64,
+    pub above: usize,
+    pub newly_flagged: usize,
+    pub newly_passed: usize,
+}
+
+#[tauri::command]
+async fn get_hallucination_stats(
+    thresholds: Option<Vec<f64>>,
+) -> Result<HallucinationStatsDto, String> {
+    use std::process::Command;
+
+    let mut args = vec![
+        "--format".to_string(),
+        "json".to_string(),
+        "guardian".to_string(),
+        "stats".to_string(),
+    ];
+    for threshold in thresholds.unwrap_or_default() {
+        args.push("--threshold".to_string());
+        args.push(threshold.to_string());
+    }
+
+    let output = Command::new("./target/release/sena")
+        .args(&args)
+        .output()
+        .map_err(|e| format!("Failed to run guardian: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+
+    serde_json::from_slice(&output.stdout).map_err(|e| format!("Invalid guardian stats: {}", e))
+}
+
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DevilStatusDto {
@@ -1931,6 +2636,12 @@ pub struct DevilProviderDto {
 pub struct DevilExecuteResultDto {
     pub content: String,
     pub consensus_score: f64,
+    pub responder_count: usize,
+    pub agreement_fraction: f64,
+    /// Providers that answered, in `agreement_matrix` order
+    pub agreement_providers: Vec<String>,
+    /// Pairwise similarity between the answers, 1.0 on the diagonal
+    pub agreement_matrix: Vec<Vec<f64>>,
     pub synthesis_method: String,
     pub total_latency_ms: u64,
     pub facts_verified: usize,
@@ -1938,6 +2649,15 @@ pub struct DevilExecuteResultDto {
     pub provider_responses: Vec<DevilProviderResponseDto>,
 }
 
+/// How a `devil_execute` call ended
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "outcome", rename_all = "camelCase")]
+pub enum DevilExecuteOutcome {
+    Completed(DevilExecuteResultDto),
+    /// Stopped through `cancel_devil` before it finished
+    Cancelled,
+}
+
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DevilProviderResponseDto {
@@ -1948,6 +2668,25 @@ pub struct DevilProviderResponseDto {
     pub content_preview: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DevilCompareResponseDto {
+    pub provider_id: String,
+    pub model: String,
+    pub status: String,
+    pub latency_ms: u64,
+    pub content: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DevilComparisonDto {
+    pub prompt: String,
+    pub responses: Vec<DevilCompareResponseDto>,
+    pub agreement_providers: Vec<String>,
+    pub agreement_matrix: Vec<Vec<f64>>,
+}
+
 #[tauri::command]
 async fn get_devil_status(state: State<'_, AppState>) -> Result<DevilStatusDto, String> {
     let config = state.config.read().await;
@@ -1979,69 +2718,87 @@ async fn devil_execute(
     state: State<'_, AppState>,
     prompt: String,
     timeout: Option<u64>,
-) -> Result<DevilExecuteResultDto, String> {
-    use std::time::{Duration, Instant};
-
+    first: Option<usize>,
+    quorum: Option<f64>,
+    cancel_token: Option<String>,
+) -> Result<DevilExecuteOutcome, String> {
     let config = state.config.read().await;
     let router = ProviderRouter::from_config(&config)
         .map_err(|e| format!("Failed to create router: {}", e))?;
 
-    let available_providers = router.available_providers();
+    let available_providers: Vec<Arc<dyn AIProvider>> =
+        router.available_providers().into_iter().cloned().collect();
     if available_providers.is_empty() {
         return Err("No providers available".to_string());
     }
 
-    let timeout_duration = Duration::from_secs(timeout.unwrap_or(30));
-    let request = ChatRequest::new(vec![Message::user(&prompt)]).with_max_tokens(1024);
+    let wait_mode = match (first, quorum) {
+        (Some(n), _) => WaitMode::FirstN(n),
+        (None, Some(fraction)) => WaitMode::Quorum(fraction),
+        (None, None) => WaitMode::WaitForAll,
+    };
+    let executor = DevilExecutor::new(
+        DevilConfig::default()
+            .with_timeout(timeout.unwrap_or(30))
+            .with_wait_mode(wait_mode),
+    );
+
+    let cancel = DevilCancelToken::new();
+    if let Some(token) = &cancel_token {
+        if let Ok(mut cancels) = state.devil_cancels.lock() {
+            cancels.insert(token.clone(), cancel.clone());
+        }
+    }
 
-    let mut provider_responses = Vec::new();
-    let mut contents = Vec::new();
     let start = Instant::now();
+    let responses = executor
+        .collect_responses(&prompt, available_providers, &cancel)
+        .await;
 
-    for provider in available_providers {
-        let provider_id = provider.provider_id().to_string();
-        let model = provider.default_model().to_string();
-        let req_start = Instant::now();
-
-        match tokio::time::timeout(timeout_duration, provider.chat(request.clone())).await {
-            Ok(Ok(response)) => {
-                contents.push(response.content.clone());
-                provider_responses.push(DevilProviderResponseDto {
-                    provider_id,
-                    model: response.model,
-                    status: "Success".to_string(),
-                    latency_ms: req_start.elapsed().as_millis() as u64,
-                    content_preview: Some(if response.content.len() > 100 {
-                        format!("{}...", &response.content[..100])
-                    } else {
-                        response.content
-                    }),
-                });
-            }
-            Ok(Err(e)) => {
-                provider_responses.push(DevilProviderResponseDto {
-                    provider_id,
-                    model,
-                    status: format!("Error: {}", e),
-                    latency_ms: req_start.elapsed().as_millis() as u64,
-                    content_preview: None,
-                });
-            }
-            Err(_) => {
-                provider_responses.push(DevilProviderResponseDto {
-                    provider_id,
-                    model,
-                    status: "Timeout".to_string(),
-                    latency_ms: timeout_duration.as_millis() as u64,
-                    content_preview: None,
-                });
-            }
+    if let Some(token) = &cancel_token {
+        if let Ok(mut cancels) = state.devil_cancels.lock() {
+            cancels.remove(token);
         }
     }
+    if cancel.is_cancelled() {
+        return Ok(DevilExecuteOutcome::Cancelled);
+    }
+
+    let aggregated = ResponseAggregator::new().aggregate(responses);
+    let provider_responses: Vec<DevilProviderResponseDto> = aggregated
+        .responses
+        .iter()
+        .map(|r| DevilProviderResponseDto {
+            provider_id: r.provider_id.clone(),
+            model: r.model.clone(),
+            status: devil_status(r),
+            latency_ms: r.latency_ms,
+            content_preview: r.content.as_ref().map(|content| {
+                if content.len() > 100 {
+                    format!("{}...", &content[..100])
+                } else {
+                    content.clone()
+                }
+            }),
+        })
+        .collect();
+    let contents: Vec<&str> = aggregated
+        .responses
+        .iter()
+        .filter_map(|r| r.content.as_deref())
+        .collect();
+
+    // Fails only without successful answers, which have nothing to compare
+    let consensus = ConsensusEngine::new().analyze(&aggregated).ok();
+    let (confidence, agreement_fraction) = consensus
+        .as_ref()
+        .map_or((0.0, 0.0), |c| (c.confidence, c.agreement_fraction));
+    let (agreement_providers, agreement_matrix) = consensus
+        .map(|c| (c.provider_ids, c.similarity_matrix))
+        .unwrap_or_default();
 
     let total_latency = start.elapsed().as_millis() as u64;
     let successful_count = contents.len();
-    let consensus_score = if successful_count > 1 { 0.7 } else if successful_count == 1 { 1.0 } else { 0.0 };
 
     let combined_content = if contents.is_empty() {
         "No successful responses from providers".to_string()
@@ -2049,15 +2806,88 @@ async fn devil_execute(
         contents.join("\n\n---\n\n")
     };
 
-    Ok(DevilExecuteResultDto {
+    Ok(DevilExecuteOutcome::Completed(DevilExecuteResultDto {
         content: combined_content,
-        consensus_score,
+        consensus_score: confidence,
+        responder_count: successful_count,
+        agreement_fraction,
+        agreement_providers,
+        agreement_matrix,
         synthesis_method: "CrossVerification".to_string(),
         total_latency_ms: total_latency,
         facts_verified: successful_count,
         facts_rejected: provider_responses.len() - successful_count,
         provider_responses,
-    })
+    }))
+}
+
+/// Abort a running `devil_execute` started with the same `cancel_token`
+#[tauri::command]
+async fn cancel_devil(state: State<'_, AppState>, cancel_token: String) -> Result<bool, String> {
+    let cancels = state
+        .devil_cancels
+        .lock()
+        .map_err(|_| "Devil cancel registry unavailable".to_string())?;
+    match cancels.get(&cancel_token) {
+        Some(cancel) => {
+            cancel.cancel();
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Status label the UI shows for one provider's answer
+fn devil_status(response: &ProviderResponseData) -> String {
+    match (&response.content, &response.error) {
+        (Some(_), _) => "Success".to_string(),
+        _ if response.cancelled => "Cancelled".to_string(),
+        (None, Some(e)) if e == "Timeout" => "Timeout".to_string(),
+        (None, e) => format!("Error: {}", e.as_deref().unwrap_or("Unknown error")),
+    }
+}
+
+#[tauri::command]
+async fn devil_compare(
+    state: State<'_, AppState>,
+    prompt: String,
+    timeout: Option<u64>,
+) -> Result<DevilComparisonDto, String> {
+    let config = state.config.read().await;
+    let router = ProviderRouter::from_config(&config)
+        .map_err(|e| format!("Failed to create router: {}", e))?;
+
+    let available_providers: Vec<Arc<dyn AIProvider>> =
+        router.available_providers().into_iter().cloned().collect();
+    if available_providers.is_empty() {
+        return Err("No providers available".to_string());
+    }
+
+    let executor = DevilExecutor::new(DevilConfig::default().with_timeout(timeout.unwrap_or(30)));
+    let responses = executor
+        .collect_responses(&prompt, available_providers, &DevilCancelToken::new())
+        .await;
+
+    Ok(devil_comparison_dto(DevilComparison::new(&prompt, responses)))
+}
+
+fn devil_comparison_dto(comparison: DevilComparison) -> DevilComparisonDto {
+    DevilComparisonDto {
+        prompt: comparison.prompt,
+        responses: comparison
+            .responses
+            .into_iter()
+            .map(|r| DevilCompareResponseDto {
+                status: devil_status(&r),
+                provider_id: r.provider_id,
+                model: r.model,
+                latency_ms: r.latency_ms,
+                content: r.content,
+            })
+            .collect(),
+        agreement_providers: comparison.agreement.providers,
+        agreement_matrix: comparison.agreement.scores,
+    }
 }
 
 #[tauri::command]
@@ -2089,6 +2919,10 @@ async fn devil_test(prompt: String) -> Result<DevilExecuteResultDto, String> {
     Ok(DevilExecuteResultDto {
         content: format!("Mock consensus response for: {}", prompt),
         consensus_score: 0.85,
+        responder_count: 3,
+        agreement_fraction: 1.0,
+        agreement_providers: mock_responses.iter().map(|r| r.provider_id.clone()).collect(),
+        agreement_matrix: vec![vec![1.0; 3]; 3],
         synthesis_method: "CrossVerification".to_string(),
         total_latency_ms: 600,
         facts_verified: 3,
@@ -2099,6 +2933,8 @@ async fn devil_test(prompt: String) -> Result<DevilExecuteResultDto, String> {
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    sena1996_ai::sync::atomic::cleanup_sena_dir();
+
     let app_state = AppState::new();
 
     tauri::Builder::default()
@@ -2109,13 +2945,20 @@ pub fn run() {
             get_provider_status,
             get_models,
             send_chat,
+            send_chat_with_image,
+            send_chat_stream,
             set_default_provider,
             test_provider,
+            preload_model,
+            pull_ollama_model,
             create_session,
             list_sessions,
+            export_transcript,
+            get_usage_summary,
             list_cli_sessions,
             send_message_to_session,
             get_all_messages,
+            mark_message_read,
             broadcast_message,
             clear_message_history,
             get_health,
@@ -2136,6 +2979,8 @@ pub fn run() {
             get_memory_stats,
             add_memory,
             delete_memory,
+            export_memories,
+            import_memories,
             get_all_provider_metadata_cmd,
             get_credential_status_cmd,
             get_credential,
@@ -2144,14 +2989,106 @@ pub fn run() {
             validate_api_key_cmd,
             import_env_to_storage,
             get_storage_options_cmd,
+            audit_credentials,
+            migrate_credentials,
             open_external_url,
             get_guardian_status,
             guardian_validate,
             guardian_check,
+            get_hallucination_stats,
             get_devil_status,
             devil_execute,
+            cancel_devil,
+            devil_compare,
             devil_test,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_devil_comparison_dto_keeps_core_scores() {
+        use sena1996_ai::devil::ProviderResponse;
+
+        let answer = |id: &str, content: &str| {
+            ProviderResponse::success(id.to_string(), "m".to_string(), content.to_string(), Duration::ZERO)
+        };
+        let comparison = DevilComparison::new(
+            "How far is the Moon?",
+            vec![
+                answer("claude", "The Moon is about 384,000 km from Earth"),
+                answer("openai", "The Moon is roughly 384,000 km from Earth"),
+                ProviderResponse::failure("gemini".to_string(), "m".to_string(), "Timeout".to_string(), Duration::ZERO),
+                ProviderResponse::cancelled("ollama".to_string(), "m".to_string(), Duration::ZERO),
+            ],
+        );
+        let scores = comparison.agreement.scores.clone();
+        let dto = devil_comparison_dto(comparison);
+
+        assert_eq!(dto.agreement_providers, vec!["claude", "openai"]);
+        assert_eq!(dto.agreement_matrix, scores);
+        let statuses: Vec<&str> = dto.responses.iter().map(|r| r.status.as_str()).collect();
+        assert_eq!(statuses, vec!["Success", "Success", "Timeout", "Cancelled"]);
+    }
+
+    #[test]
+    fn test_clear_messages_dry_run_keeps_files() {
+        let dir = std::env::temp_dir()
+            .join(format!("sena-ui-messages-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("web.json"), "[]").unwrap();
+        std::fs::write(dir.join("broadcast.json"), "[]").unwrap();
+
+        let preview = clear_messages_in(&dir, true).unwrap();
+        assert!(preview.dry_run);
+        assert_eq!(preview.count, 2);
+        assert!(dir.join("web.json").exists());
+
+        let cleared = clear_messages_in(&dir, false).unwrap();
+        assert_eq!(cleared.count, 2);
+        assert!(!dir.join("web.json").exists());
+        assert!(!dir.join("broadcast.json").exists());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_disconnect_peer_dry_run_keeps_peer() {
+        let file = std::env::temp_dir()
+            .join(format!("sena-ui-peers-{}.json", uuid::Uuid::new_v4()));
+        let peers = serde_json::json!({
+            "version": "1.0",
+            "connected_hubs": {
+                "hub-1": {
+                    "hub_id": "hub-1",
+                    "name": "Workstation",
+                    "address": "192.168.1.50",
+                    "port": 9876,
+                    "auth_token": "token",
+                    "connected_at": 0,
+                    "last_seen": 0,
+                    "session_count": 2
+                }
+            },
+            "pending_requests": [],
+            "last_updated": 0
+        });
+        std::fs::write(&file, peers.to_string()).unwrap();
+
+        let preview = disconnect_peer_in(&file, "hub-1", true).unwrap();
+        assert!(preview.dry_run);
+        assert_eq!(preview.count, 1);
+        assert!(std::fs::read_to_string(&file).unwrap().contains("hub-1"));
+
+        let removed = disconnect_peer_in(&file, "hub-1", false).unwrap();
+        assert_eq!(removed.count, 1);
+        assert!(!std::fs::read_to_string(&file).unwrap().contains("\"hub-1\""));
+        assert!(disconnect_peer_in(&file, "hub-1", true).is_err());
+
+        let _ = std::fs::remove_file(file);
+    }
+}
@@ -1,11 +1,24 @@
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import type {
   Provider,
   Session,
   Message,
   ChatResponse,
+  ChatStreamEvent,
+  DevilComparison,
+  DevilExecuteOutcome,
   SystemHealth,
   Model,
+  PreloadResult,
+  PullProgressEvent,
+  UsageSummary,
+  HallucinationStats,
+  MemoryImportSummary,
+  MemoryMergeStrategy,
+  CredentialStatus,
+  CredentialMigrationReport,
+  StorageType,
 } from '../types';
 
 export class TauriError extends Error {
@@ -39,6 +52,10 @@ export async function getModels(providerId?: string): Promise<Model[]> {
   return safeInvoke<Model[]>('get_models', { providerId });
 }
 
+export async function getUsageSummary(): Promise<UsageSummary> {
+  return safeInvoke<UsageSummary>('get_usage_summary');
+}
+
 export async function sendChat(
   message: string,
   provider?: string,
@@ -50,6 +67,47 @@ export async function sendChat(
   return safeInvoke<ChatResponse>('send_chat', { message, provider, model });
 }
 
+export async function sendChatWithImage(
+  message: string,
+  imagePath: string,
+  provider?: string,
+  model?: string
+): Promise<ChatResponse> {
+  return safeInvoke<ChatResponse>('send_chat_with_image', {
+    message,
+    imagePath,
+    provider,
+    model,
+  });
+}
+
+export async function sendChatStream(
+  message: string,
+  onChunk: (event: ChatStreamEvent) => void,
+  provider?: string,
+  model?: string
+): Promise<ChatResponse> {
+  if (!message.trim()) {
+    throw new TauriError('Message cannot be empty');
+  }
+  const streamId = crypto.randomUUID();
+  const unlisten = await listen<ChatStreamEvent>('chat-stream', (event) => {
+    if (event.payload.stream_id === streamId) {
+      onChunk(event.payload);
+    }
+  });
+  try {
+    return await safeInvoke<ChatResponse>('send_chat_stream', {
+      streamId,
+      message,
+      provider,
+      model,
+    });
+  } finally {
+    unlisten();
+  }
+}
+
 export async function setDefaultProvider(providerId: string): Promise<void> {
   if (!providerId.trim()) {
     throw new TauriError('Provider ID cannot be empty');
@@ -64,6 +122,33 @@ export async function testProvider(providerId: string): Promise<boolean> {
   return safeInvoke<boolean>('test_provider', { providerId });
 }
 
+export async function preloadModel(model: string): Promise<PreloadResult> {
+  if (!model.trim()) {
+    throw new TauriError('Model cannot be empty');
+  }
+  return safeInvoke<PreloadResult>('preload_model', { model });
+}
+
+export async function pullOllamaModel(
+  model: string,
+  onProgress: (event: PullProgressEvent) => void
+): Promise<void> {
+  if (!model.trim()) {
+    throw new TauriError('Model cannot be empty');
+  }
+  const pullId = crypto.randomUUID();
+  const unlisten = await listen<PullProgressEvent>('ollama-pull-progress', (event) => {
+    if (event.payload.pull_id === pullId) {
+      onProgress(event.payload);
+    }
+  });
+  try {
+    await safeInvoke<void>('pull_ollama_model', { pullId, model });
+  } finally {
+    unlisten();
+  }
+}
+
 export async function createSession(
   name: string,
   hostProvider: string
@@ -81,6 +166,20 @@ export async function listSessions(): Promise<Session[]> {
   return safeInvoke<Session[]>('list_sessions');
 }
 
+export async function exportTranscript(
+  sessionId: string,
+  format: 'markdown' | 'json' = 'markdown'
+): Promise<string> {
+  return safeInvoke<string>('export_transcript', { sessionId, format });
+}
+
+export async function markMessageRead(
+  messageId: string,
+  sessionId?: string
+): Promise<void> {
+  return safeInvoke<void>('mark_message_read', { messageId, sessionId });
+}
+
 export async function joinSession(
   sessionId: string,
   providerId: string
@@ -115,6 +214,58 @@ export async function endSession(sessionId: string): Promise<void> {
   return safeInvoke<void>('end_session', { sessionId });
 }
 
+export async function exportMemories(path: string): Promise<number> {
+  return safeInvoke<number>('export_memories', { path });
+}
+
+export async function importMemories(
+  path: string,
+  strategy: MemoryMergeStrategy = 'skip'
+): Promise<MemoryImportSummary> {
+  return safeInvoke<MemoryImportSummary>('import_memories', { path, strategy });
+}
+
+export interface DevilExecuteOptions {
+  timeout?: number;
+  /** Return once this many providers have answered; the rest are cancelled */
+  first?: number;
+  /** Return once this fraction (0-1) of providers has answered */
+  quorum?: number;
+  /** Pass the same token to `cancelDevil` to abort the run */
+  cancelToken?: string;
+}
+
+export async function devilExecute(
+  prompt: string,
+  options: DevilExecuteOptions = {}
+): Promise<DevilExecuteOutcome> {
+  return safeInvoke<DevilExecuteOutcome>('devil_execute', { prompt, ...options });
+}
+
+export async function cancelDevil(cancelToken: string): Promise<boolean> {
+  return safeInvoke<boolean>('cancel_devil', { cancelToken });
+}
+
+export async function devilCompare(prompt: string, timeout?: number): Promise<DevilComparison> {
+  return safeInvoke<DevilComparison>('devil_compare', { prompt, timeout });
+}
+
+export async function getHallucinationStats(thresholds?: number[]): Promise<HallucinationStats> {
+  return safeInvoke<HallucinationStats>('get_hallucination_stats', { thresholds });
+}
+
+export async function auditCredentials(): Promise<CredentialStatus[]> {
+  return safeInvoke<CredentialStatus[]>('audit_credentials');
+}
+
+/** Move every stored credential; each one is removed from `from` only after it verifiably reads back from `to` */
+export async function migrateCredentials(
+  from: StorageType,
+  to: StorageType
+): Promise<CredentialMigrationReport> {
+  return safeInvoke<CredentialMigrationReport>('migrate_credentials', { from, to });
+}
+
 export async function getHealth(): Promise<SystemHealth> {
   return safeInvoke<SystemHealth>('get_health');
 }
@@ -187,6 +187,7 @@ export default function Chat() {
         const result = await invoke<{ success: boolean; target: string }>('send_message_to_session', {
           targetSession: parsed.target,
           message: parsed.message,
+          idempotencyKey: crypto.randomUUID(),
         });
 
         if (result.success) {
@@ -208,6 +209,7 @@ export default function Chat() {
         const result = await invoke<{ success: boolean; target: string }>('send_message_to_session', {
           targetSession: selectedTarget,
           message: trimmedInput,
+          idempotencyKey: crypto.randomUUID(),
         });
 
         if (result.success) {
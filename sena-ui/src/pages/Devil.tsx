@@ -35,6 +35,8 @@ interface DevilProvider {
 interface DevilExecuteResult {
   content: string;
   consensusScore: number;
+  responderCount: number;
+  agreementFraction: number;
   synthesisMethod: string;
   totalLatencyMs: number;
   factsVerified: number;
@@ -93,10 +95,17 @@ export default function Devil() {
 
     setIsExecuting(true);
     try {
-      const result = await invoke<DevilExecuteResult>('devil_execute', {
+      const outcome = await invoke<
+        ({ outcome: 'completed' } & DevilExecuteResult) | { outcome: 'cancelled' }
+      >('devil_execute', {
         prompt,
         timeout,
       });
+      if (outcome.outcome === 'cancelled') {
+        toast.info('Devil Mode cancelled');
+        return;
+      }
+      const result = outcome;
       setExecuteResult(result);
       setExecutionHistory((prev) => [result, ...prev.slice(0, 4)]);
 
@@ -299,6 +308,9 @@ export default function Devil() {
                   <p className={clsx('text-2xl font-bold', getConsensusColor(executeResult.consensusScore))}>
                     {(executeResult.consensusScore * 100).toFixed(0)}%
                   </p>
+                  <p className="text-xs text-dark-500">
+                    {executeResult.responderCount} responder(s), {(executeResult.agreementFraction * 100).toFixed(0)}% agreeing
+                  </p>
                 </div>
                 <div className="p-3 rounded-lg bg-dark-800/50">
                   <p className="text-xs text-dark-500">Total Latency</p>
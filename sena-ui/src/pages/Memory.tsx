@@ -24,6 +24,7 @@ interface MemoryEntry {
   created_at: string;
   updated_at: string;
   access_count: number;
+  pinned?: boolean;
 }
 
 interface MemoryStats {
@@ -50,11 +51,16 @@ export default function Memory() {
   const [showAddForm, setShowAddForm] = useState(false);
   const toast = useToast();
 
-  const [newMemory, setNewMemory] = useState({
+  const [newMemory, setNewMemory] = useState<{
+    content: string;
+    memory_type: string;
+    tags: string;
+    importance: number | null;
+  }>({
     content: '',
     memory_type: 'Fact',
     tags: '',
-    importance: 0.5,
+    importance: null,
   });
 
   const loadMemories = useCallback(async () => {
@@ -127,18 +133,22 @@ export default function Memory() {
     }
 
     try {
-      await invoke('add_memory', {
+      await invoke<MemoryEntry>('add_memory', {
         content: newMemory.content,
         memoryType: newMemory.memory_type,
         tags: newMemory.tags.split(',').map((t) => t.trim()).filter(Boolean),
         importance: newMemory.importance,
+      }).then((added) => {
+        if (newMemory.importance === null) {
+          toast.info(`Importance scored at ${(added.importance * 100).toFixed(0)}%`);
+        }
       }).catch(() => {
         const newEntry: MemoryEntry = {
           id: `mem_${Date.now()}`,
           content: newMemory.content,
           memory_type: newMemory.memory_type,
           tags: newMemory.tags.split(',').map((t) => t.trim()).filter(Boolean),
-          importance: newMemory.importance,
+          importance: newMemory.importance ?? 0.5,
           created_at: new Date().toISOString(),
           updated_at: new Date().toISOString(),
           access_count: 0,
@@ -147,7 +157,7 @@ export default function Memory() {
       });
 
       toast.success('Memory added successfully');
-      setNewMemory({ content: '', memory_type: 'Fact', tags: '', importance: 0.5 });
+      setNewMemory({ content: '', memory_type: 'Fact', tags: '', importance: null });
       setShowAddForm(false);
       loadMemories();
     } catch (error) {
@@ -259,14 +269,17 @@ export default function Memory() {
               </div>
               <div>
                 <label className="block text-sm text-dark-400 mb-1">
-                  Importance: {(newMemory.importance * 100).toFixed(0)}%
+                  Importance:{' '}
+                  {newMemory.importance === null
+                    ? 'Auto'
+                    : `${(newMemory.importance * 100).toFixed(0)}%`}
                 </label>
                 <input
                   type="range"
                   min="0"
                   max="1"
                   step="0.1"
-                  value={newMemory.importance}
+                  value={newMemory.importance ?? 0.5}
                   onChange={(e) => setNewMemory({ ...newMemory, importance: parseFloat(e.target.value) })}
                   className="w-full"
                 />
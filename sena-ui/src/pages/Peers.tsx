@@ -93,7 +93,7 @@ export default function Peers() {
 
   const approveRequest = useCallback(async (requestId: string) => {
     try {
-      await invoke('approve_peer_request', { requestId });
+      await invoke('approve_peer_request', { requestId, idempotencyKey: requestId });
       await loadData();
       toast.success('Connection request approved');
     } catch (error) {
@@ -21,6 +21,7 @@ import {
 } from 'lucide-react';
 import clsx from 'clsx';
 import { useToast } from '../components/Toast';
+import type { RemovalSummary } from '../types';
 
 interface HubIdentity {
   hub_id: string;
@@ -81,8 +82,8 @@ export default function Settings() {
   const handleClearHistory = async () => {
     setIsClearing(true);
     try {
-      await invoke('clear_message_history');
-      toast.success('History cleared successfully');
+      const summary = await invoke<RemovalSummary>('clear_message_history', { dryRun: false });
+      toast.success(`History cleared (${summary.count} file${summary.count === 1 ? '' : 's'})`);
     } catch (error) {
       toast.error(`Failed to clear history: ${error}`);
     } finally {
@@ -4,6 +4,7 @@ export interface Provider {
   status: 'connected' | 'disconnected' | 'rate_limited' | 'error';
   defaultModel: string;
   hasApiKey: boolean;
+  apiKeyPreview?: string;
   capabilities: {
     streaming: boolean;
     tools: boolean;
@@ -19,6 +20,24 @@ export interface Model {
   supportsVision: boolean;
   supportsTools: boolean;
   supportsStreaming: boolean;
+  pricePer1kInput?: number | null;
+  pricePer1kOutput?: number | null;
+}
+
+export interface PreloadResult {
+  success: boolean;
+  /** The model hasn't been pulled yet */
+  modelMissing: boolean;
+  message: string;
+}
+
+export interface PullProgressEvent {
+  pull_id: string;
+  model: string;
+  status: string;
+  percent: number | null;
+  done: boolean;
+  error: string | null;
 }
 
 export interface Message {
@@ -67,6 +86,38 @@ export interface ChatResponse {
     completionTokens: number;
     totalTokens: number;
   };
+  finish_reason: FinishReason | null;
+  truncated: boolean;
+}
+
+export type FinishReason = 'stop' | 'length' | 'tool_calls' | 'content_filter' | 'error';
+
+export interface ChatStreamEvent {
+  stream_id: string;
+  delta: string;
+  done: boolean;
+  usage: {
+    prompt_tokens: number;
+    completion_tokens: number;
+    total_tokens: number;
+  } | null;
+  finish_reason: FinishReason | null;
+}
+
+export interface ProviderCost {
+  provider: string;
+  requests: number;
+  prompt_tokens: number;
+  completion_tokens: number;
+  cost_usd: number;
+  unpriced_requests: number;
+}
+
+export interface UsageSummary {
+  month: string;
+  providers: ProviderCost[];
+  total_cost_usd: number;
+  total_tokens: number;
 }
 
 export interface SystemHealth {
@@ -114,6 +165,7 @@ export interface AuthField {
 
 export interface CredentialStatus {
   providerId: string;
+  fieldId: string;
   hasCredential: boolean;
   source: 'keychain' | 'config' | 'environment' | 'none';
   isValid: boolean | null;
@@ -132,6 +184,12 @@ export interface StorageOptions {
 
 export type StorageType = 'keychain' | 'config';
 
+export interface CredentialMigrationReport {
+  /** `provider/field` of each credential moved */
+  migrated: string[];
+  failed: { providerId: string; fieldId: string; error: string }[];
+}
+
 export interface GuardianStatus {
   enabled: boolean;
   sandboxLevel: string;
@@ -161,6 +219,37 @@ export interface HallucinationCheck {
   };
 }
 
+export type MemoryMergeStrategy = 'skip' | 'overwrite' | 'keep-newer';
+
+export interface MemoryImportSummary {
+  imported: number;
+  skipped: number;
+  errors: { line: number; message: string }[];
+}
+
+export interface HallucinationThresholdImpact {
+  threshold: number;
+  above: number;
+  newlyFlagged: number;
+  newlyPassed: number;
+}
+
+export interface HallucinationStats {
+  count: number;
+  flagged: number;
+  mean: number;
+  min: number;
+  max: number;
+  percentiles: {
+    p50: number;
+    p75: number;
+    p90: number;
+    p95: number;
+    p99: number;
+  };
+  thresholds: HallucinationThresholdImpact[];
+}
+
 export interface DevilStatus {
   enabled: boolean;
   timeoutSecs: number;
@@ -179,6 +268,10 @@ export interface DevilProvider {
 export interface DevilExecuteResult {
   content: string;
   consensusScore: number;
+  responderCount: number;
+  agreementFraction: number;
+  agreementProviders: string[];
+  agreementMatrix: number[][];
   synthesisMethod: string;
   totalLatencyMs: number;
   factsVerified: number;
@@ -186,6 +279,10 @@ export interface DevilExecuteResult {
   providerResponses: DevilProviderResponse[];
 }
 
+export type DevilExecuteOutcome =
+  | ({ outcome: 'completed' } & DevilExecuteResult)
+  | { outcome: 'cancelled' };
+
 export interface DevilProviderResponse {
   providerId: string;
   model: string;
@@ -193,3 +290,25 @@ export interface DevilProviderResponse {
   latencyMs: number;
   contentPreview: string | null;
 }
+
+export interface DevilCompareResponse {
+  providerId: string;
+  model: string;
+  status: string;
+  latencyMs: number;
+  content: string | null;
+}
+
+export interface DevilComparison {
+  prompt: string;
+  responses: DevilCompareResponse[];
+  agreementProviders: string[];
+  agreementMatrix: number[][];
+}
+
+export interface RemovalSummary {
+  target: string;
+  dry_run: boolean;
+  items: string[];
+  count: number;
+}
@@ -1,4 +1,4 @@
-use super::{DomainAgentType, DomainAnalysis, Finding, Severity};
+use super::{AnalysisContext, DomainAgentType, DomainAnalysis, Finding, Severity};
 use once_cell::sync::Lazy;
 use regex::Regex;
 
@@ -20,6 +20,8 @@ static HARDCODED_SECRET_REGEX: Lazy<Regex> = Lazy::new(|| {
 static JWT_REGEX: Lazy<Regex> =
     Lazy::new(|| Regex::new(r#"(jwt|jsonwebtoken|jose)"#).expect("invalid jwt regex"));
 
+const PUBLIC_ROUTE_HINTS: &[&str] = &["login", "signin", "register", "signup", "health", "public"];
+
 static DB_CONNECTION_REGEX: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r#"(mongodb|postgres|mysql|redis|sqlite)://[^\s'"`]+"#)
         .expect("invalid db connection regex")
@@ -48,6 +50,66 @@ impl BackendAgent {
         }
     }
 
+    /// Analyze `input`, also checking routes against conventions the
+    /// project context establishes, such as a shared auth middleware
+    pub fn analyze_with_context(
+        &self,
+        command: &str,
+        input: &str,
+        context: &AnalysisContext,
+    ) -> DomainAnalysis {
+        let mut analysis = self.analyze(command, input);
+        if matches!(command, "flow" | "secrets") {
+            return analysis;
+        }
+
+        let deviations = self.check_auth_convention(input, context);
+        let penalty = (deviations.len() * 10).min(u8::MAX as usize) as u8;
+        analysis.score = analysis.score.saturating_sub(penalty);
+        analysis.findings.extend(deviations);
+        analysis
+    }
+
+    fn check_auth_convention(&self, input: &str, context: &AnalysisContext) -> Vec<Finding> {
+        let middleware = context.auth_middleware();
+        if middleware.is_empty() {
+            return Vec::new();
+        }
+        let middleware_lower: Vec<String> = middleware.iter().map(|m| m.to_lowercase()).collect();
+
+        let mut findings = Vec::new();
+        for (line_no, line) in input.lines().enumerate() {
+            let line_lower = line.to_lowercase();
+            let Some(path) = ENDPOINT_REGEX
+                .captures(&line_lower)
+                .and_then(|cap| cap.get(3))
+                .map(|m| m.as_str().to_string())
+            else {
+                continue;
+            };
+            let is_public = PUBLIC_ROUTE_HINTS.iter().any(|hint| path.contains(hint));
+            let is_guarded = middleware_lower.iter().any(|m| line_lower.contains(m));
+            if is_public || is_guarded {
+                continue;
+            }
+
+            findings.push(Finding {
+                severity: Severity::Warning,
+                title: format!("Endpoint {} skips project auth middleware", path),
+                description: format!(
+                    "This endpoint lacks the {} middleware used elsewhere in the project",
+                    middleware.join(" / ")
+                ),
+                location: Some(format!("line {}", line_no + 1)),
+                suggestion: Some(format!(
+                    "Add {} to the route, or document why it is public",
+                    middleware[0]
+                )),
+            });
+        }
+        findings
+    }
+
     fn map_endpoints(&self, input: &str) -> DomainAnalysis {
         let mut findings = Vec::new();
         let input_lower = input.to_lowercase();
@@ -606,4 +668,38 @@ mod tests {
             .iter()
             .any(|f| f.severity == Severity::Critical));
     }
+
+    #[test]
+    fn test_context_auth_convention_flags_unguarded_route() {
+        let agent = BackendAgent::new();
+        let code = r#"
+            app.get('/api/orders', requireAuth, listOrders);
+            app.post('/api/orders', createOrder);
+            app.post('/api/login', login);
+        "#;
+        let context = AnalysisContext::new()
+            .with_framework("Express")
+            .with_memories(vec![
+                "All API routes use the requireAuth middleware".to_string()
+            ]);
+
+        let plain = agent.analyze("map", code);
+        assert!(!plain
+            .findings
+            .iter()
+            .any(|f| f.title.contains("auth middleware")));
+
+        let result = agent.analyze_with_context("map", code, &context);
+        let deviations: Vec<&Finding> = result
+            .findings
+            .iter()
+            .filter(|f| f.title.contains("auth middleware"))
+            .collect();
+        assert_eq!(deviations.len(), 1);
+        assert_eq!(deviations[0].severity, Severity::Warning);
+        assert!(deviations[0].title.contains("/api/orders"));
+        assert_eq!(deviations[0].location.as_deref(), Some("line 3"));
+        assert!(deviations[0].description.contains("requireAuth"));
+        assert!(result.score < plain.score);
+    }
 }
@@ -0,0 +1,188 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use super::{DomainAgentType, DomainAnalysis};
+use crate::sync::write_atomic;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedAnalysis {
+    pub agent: DomainAgentType,
+    pub command: String,
+    pub content_hash: String,
+    pub created_at: DateTime<Utc>,
+    pub analysis: DomainAnalysis,
+}
+
+/// Entries kept before the least recently used are evicted
+pub const DEFAULT_MAX_ENTRIES: usize = 500;
+/// Age after which an entry is ignored and removed
+pub const DEFAULT_TTL_DAYS: i64 = 7;
+
+/// On-disk analysis cache, one JSON file per entry. A file's mtime records
+/// its last use, so eviction drops the least recently used entries.
+#[derive(Debug, Clone)]
+pub struct AgentCache {
+    dir: PathBuf,
+    max_entries: usize,
+    ttl: Duration,
+}
+
+impl AgentCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            max_entries: DEFAULT_MAX_ENTRIES,
+            ttl: Duration::days(DEFAULT_TTL_DAYS),
+        }
+    }
+
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries.max(1);
+        self
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    pub fn default_dir() -> PathBuf {
+        dirs::home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(".sena")
+            .join("cache")
+            .join("agents")
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn content_hash(input: &str) -> String {
+        hex::encode(Sha256::digest(input.as_bytes()))
+    }
+
+    pub fn key(agent: DomainAgentType, command: &str, input: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(format!("{:?}", agent).as_bytes());
+        hasher.update([0]);
+        hasher.update(command.as_bytes());
+        hasher.update([0]);
+        hasher.update(Self::content_hash(input).as_bytes());
+        hex::encode(hasher.finalize())
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+
+    pub fn get(
+        &self,
+        agent: DomainAgentType,
+        command: &str,
+        input: &str,
+    ) -> Option<DomainAnalysis> {
+        let path = self.entry_path(&Self::key(agent, command, input));
+        let content = std::fs::read_to_string(&path).ok()?;
+        let cached: CachedAnalysis = serde_json::from_str(&content).ok()?;
+
+        if Utc::now() - cached.created_at > self.ttl {
+            let _ = std::fs::remove_file(&path);
+            return None;
+        }
+
+        let matches = cached.agent == agent
+            && cached.command == command
+            && cached.content_hash == Self::content_hash(input);
+        if matches {
+            touch(&path);
+        }
+        matches.then_some(cached.analysis)
+    }
+
+    pub fn put(
+        &self,
+        agent: DomainAgentType,
+        command: &str,
+        input: &str,
+        analysis: &DomainAnalysis,
+    ) -> Result<(), String> {
+        std::fs::create_dir_all(&self.dir)
+            .map_err(|e| format!("Failed to create agent cache directory: {}", e))?;
+
+        let cached = CachedAnalysis {
+            agent,
+            command: command.to_string(),
+            content_hash: Self::content_hash(input),
+            created_at: Utc::now(),
+            analysis: analysis.clone(),
+        };
+        let content = serde_json::to_string(&cached)
+            .map_err(|e| format!("Failed to serialize cached analysis: {}", e))?;
+        write_atomic(self.entry_path(&Self::key(agent, command, input)), content)
+            .map_err(|e| format!("Failed to write cached analysis: {}", e))?;
+        self.evict();
+        Ok(())
+    }
+
+    /// Number of entries currently stored
+    pub fn len(&self) -> usize {
+        self.entries().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn entries(&self) -> Vec<(PathBuf, SystemTime)> {
+        let Ok(read_dir) = std::fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        read_dir
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|path| {
+                let used = std::fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+                Some((path, used))
+            })
+            .collect()
+    }
+
+    /// Drop the least recently used entries beyond `max_entries`
+    fn evict(&self) {
+        let mut entries = self.entries();
+        if entries.len() <= self.max_entries {
+            return;
+        }
+        entries.sort_by_key(|(_, used)| *used);
+        let excess = entries.len() - self.max_entries;
+        for (path, _) in entries.into_iter().take(excess) {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
+    pub fn clear(&self) -> Result<(), String> {
+        if !self.dir.exists() {
+            return Ok(());
+        }
+        std::fs::remove_dir_all(&self.dir)
+            .map_err(|e| format!("Failed to clear agent cache: {}", e))
+    }
+}
+
+/// Mark an entry as just used
+fn touch(path: &Path) {
+    if let Ok(file) = std::fs::File::options().write(true).open(path) {
+        let _ = file.set_modified(SystemTime::now());
+    }
+}
+
+impl Default for AgentCache {
+    fn default() -> Self {
+        Self::new(Self::default_dir())
+    }
+}
@@ -0,0 +1,191 @@
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::path::Path;
+
+use crate::git::GitRepo;
+
+const FRAMEWORK_MARKERS: &[(&str, &[(&str, &str)])] = &[
+    (
+        "package.json",
+        &[
+            ("\"@nestjs/core\"", "NestJS"),
+            ("\"next\"", "Next.js"),
+            ("\"express\"", "Express"),
+            ("\"fastify\"", "Fastify"),
+            ("\"koa\"", "Koa"),
+            ("\"vue\"", "Vue"),
+            ("\"react\"", "React"),
+        ],
+    ),
+    (
+        "Cargo.toml",
+        &[
+            ("axum", "Axum"),
+            ("actix-web", "Actix Web"),
+            ("rocket", "Rocket"),
+        ],
+    ),
+    (
+        "requirements.txt",
+        &[
+            ("fastapi", "FastAPI"),
+            ("django", "Django"),
+            ("flask", "Flask"),
+        ],
+    ),
+    (
+        "pyproject.toml",
+        &[
+            ("fastapi", "FastAPI"),
+            ("django", "Django"),
+            ("flask", "Flask"),
+        ],
+    ),
+    (
+        "go.mod",
+        &[("gin-gonic/gin", "Gin"), ("labstack/echo", "Echo")],
+    ),
+];
+
+const PLAIN_AUTH_WORDS: &[&str] = &[
+    "auth",
+    "authentication",
+    "authorization",
+    "authenticated",
+    "authorized",
+    "oauth",
+];
+
+/// Project knowledge passed alongside the analyzed input so findings can be
+/// judged against the rest of the codebase
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AnalysisContext {
+    pub memories: Vec<String>,
+    pub framework: Option<String>,
+    pub git_branch: Option<String>,
+}
+
+impl AnalysisContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Detect the framework and git branch of the project at `dir`
+    pub fn detect(dir: &Path) -> Self {
+        Self {
+            memories: Vec::new(),
+            framework: detect_framework(dir),
+            git_branch: GitRepo::open(dir)
+                .and_then(|repo| repo.current_branch())
+                .ok(),
+        }
+    }
+
+    pub fn with_memories(mut self, memories: Vec<String>) -> Self {
+        self.memories = memories;
+        self
+    }
+
+    pub fn with_framework(mut self, framework: impl Into<String>) -> Self {
+        self.framework = Some(framework.into());
+        self
+    }
+
+    pub fn with_git_branch(mut self, branch: impl Into<String>) -> Self {
+        self.git_branch = Some(branch.into());
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.memories.is_empty() && self.framework.is_none() && self.git_branch.is_none()
+    }
+
+    /// Input used for the analysis cache key, so results computed with
+    /// different context are cached separately
+    pub fn cache_input<'a>(&self, input: &'a str) -> Cow<'a, str> {
+        if self.is_empty() {
+            return Cow::Borrowed(input);
+        }
+        let context = serde_json::to_string(self).unwrap_or_default();
+        Cow::Owned(format!("{}\n\u{0}{}", input, context))
+    }
+
+    /// Auth middleware identifiers the project memories say are used, such
+    /// as `requireAuth` or `auth_required`
+    pub fn auth_middleware(&self) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        for memory in &self.memories {
+            let tokens = memory
+                .split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.'))
+                .map(|token| token.trim_matches('.'));
+            for token in tokens {
+                if is_auth_identifier(token) && !names.iter().any(|n| n == token) {
+                    names.push(token.to_string());
+                }
+            }
+        }
+        names
+    }
+}
+
+fn is_auth_identifier(token: &str) -> bool {
+    let lower = token.to_lowercase();
+    if !lower.contains("auth") || PLAIN_AUTH_WORDS.contains(&lower.as_str()) {
+        return false;
+    }
+    let has_inner_capital = token.chars().skip(1).any(char::is_uppercase);
+    has_inner_capital || token.contains('_') || token.contains('.')
+}
+
+/// Guess the application framework from the manifests in `dir`
+pub fn detect_framework(dir: &Path) -> Option<String> {
+    FRAMEWORK_MARKERS.iter().find_map(|(manifest, markers)| {
+        let content = std::fs::read_to_string(dir.join(manifest)).ok()?;
+        markers
+            .iter()
+            .find(|(needle, _)| content.contains(needle))
+            .map(|(_, name)| name.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_auth_middleware_from_memories() {
+        let context = AnalysisContext::new().with_memories(vec![
+            "All API routes use the requireAuth middleware.".to_string(),
+            "Admin views are wrapped in auth_required; OAuth is handled upstream".to_string(),
+        ]);
+        assert_eq!(
+            context.auth_middleware(),
+            vec!["requireAuth", "auth_required"]
+        );
+    }
+
+    #[test]
+    fn test_cache_input_unchanged_without_context() {
+        let input = "app.get('/users', handler);";
+        assert_eq!(AnalysisContext::new().cache_input(input), input);
+        let context = AnalysisContext::new().with_git_branch("main");
+        assert_ne!(context.cache_input(input), input);
+    }
+
+    #[test]
+    fn test_detect_framework_from_manifest() {
+        let dir = std::env::temp_dir().join(format!("sena-ctx-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("package.json"),
+            r#"{"dependencies": {"express": "^4.18.0"}}"#,
+        )
+        .unwrap();
+
+        let context = AnalysisContext::detect(&dir);
+        assert_eq!(context.framework.as_deref(), Some("Express"));
+        assert!(context.git_branch.is_none());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -1,5 +1,7 @@
 pub mod android;
 pub mod backend;
+pub mod cache;
+pub mod context;
 pub mod ios;
 pub mod iot;
 pub mod web;
@@ -8,6 +10,8 @@ use serde::{Deserialize, Serialize};
 
 pub use android::AndroidAgent;
 pub use backend::BackendAgent;
+pub use cache::{AgentCache, CachedAnalysis};
+pub use context::AnalysisContext;
 pub use ios::IOSAgent;
 pub use iot::IoTAgent;
 pub use web::WebAgent;
@@ -96,22 +100,18 @@ pub struct Finding {
     pub suggestion: Option<String>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+/// Ordered from least to most severe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum Severity {
-    Critical,
-    Warning,
-    Info,
     Success,
+    Info,
+    Warning,
+    Critical,
 }
 
 impl std::fmt::Display for Severity {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Severity::Critical => write!(f, "🔴"),
-            Severity::Warning => write!(f, "🟡"),
-            Severity::Info => write!(f, "🔵"),
-            Severity::Success => write!(f, "✅"),
-        }
+        write!(f, "{}", crate::output::Theme::current().severity(*self))
     }
 }
 
@@ -162,6 +162,7 @@ pub struct DomainAgentPool {
     ios: IOSAgent,
     android: AndroidAgent,
     web: WebAgent,
+    cache: Option<AgentCache>,
 }
 
 impl DomainAgentPool {
@@ -172,17 +173,60 @@ impl DomainAgentPool {
             ios: IOSAgent::new(),
             android: AndroidAgent::new(),
             web: WebAgent::new(),
+            cache: None,
         }
     }
 
+    pub fn with_cache(mut self, cache: AgentCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    pub fn cache(&self) -> Option<&AgentCache> {
+        self.cache.as_ref()
+    }
+
     pub fn analyze(
         &self,
         agent_type: DomainAgentType,
         command: &str,
         input: &str,
+    ) -> DomainAnalysis {
+        self.analyze_with_context(agent_type, command, input, &AnalysisContext::default())
+    }
+
+    /// Analyze `input` with project context (memories, framework, git
+    /// branch) so findings can reference the rest of the project
+    pub fn analyze_with_context(
+        &self,
+        agent_type: DomainAgentType,
+        command: &str,
+        input: &str,
+        context: &AnalysisContext,
+    ) -> DomainAnalysis {
+        let Some(cache) = &self.cache else {
+            return self.run_analysis(agent_type, command, input, context);
+        };
+
+        let cache_input = context.cache_input(input);
+        if let Some(cached) = cache.get(agent_type, command, &cache_input) {
+            return cached;
+        }
+
+        let analysis = self.run_analysis(agent_type, command, input, context);
+        let _ = cache.put(agent_type, command, &cache_input, &analysis);
+        analysis
+    }
+
+    fn run_analysis(
+        &self,
+        agent_type: DomainAgentType,
+        command: &str,
+        input: &str,
+        context: &AnalysisContext,
     ) -> DomainAnalysis {
         match agent_type {
-            DomainAgentType::Backend => self.backend.analyze(command, input),
+            DomainAgentType::Backend => self.backend.analyze_with_context(command, input, context),
             DomainAgentType::IoT => self.iot.analyze(command, input),
             DomainAgentType::IOS => self.ios.analyze(command, input),
             DomainAgentType::Android => self.android.analyze(command, input),
@@ -231,4 +275,104 @@ mod tests {
         let pool = DomainAgentPool::new();
         assert_eq!(pool.list_agents().len(), 5);
     }
+
+    fn test_cache(name: &str) -> AgentCache {
+        let cache = AgentCache::new(std::env::temp_dir().join(name));
+        cache.clear().unwrap();
+        cache
+    }
+
+    #[test]
+    fn test_cache_hit_on_identical_input() {
+        let cache = test_cache("sena_agent_cache_hit");
+        let pool = DomainAgentPool::new().with_cache(cache.clone());
+        let input = "app.get('/users', handler);";
+
+        let first = pool.analyze(DomainAgentType::Backend, "map", input);
+        let cached = cache
+            .get(DomainAgentType::Backend, "map", input)
+            .expect("analysis should be cached");
+
+        assert_eq!(cached.score, first.score);
+        assert_eq!(cached.findings.len(), first.findings.len());
+        assert_eq!(
+            pool.analyze(DomainAgentType::Backend, "map", input).score,
+            first.score
+        );
+    }
+
+    #[test]
+    fn test_cache_miss_after_content_change() {
+        let cache = test_cache("sena_agent_cache_miss");
+        let pool = DomainAgentPool::new().with_cache(cache.clone());
+
+        pool.analyze(DomainAgentType::Web, "seo", "<title>Home</title>");
+
+        assert!(cache
+            .get(DomainAgentType::Web, "seo", "<title>Home</title>")
+            .is_some());
+        assert!(cache
+            .get(DomainAgentType::Web, "seo", "<title>About</title>")
+            .is_none());
+        assert!(cache
+            .get(DomainAgentType::Web, "a11y", "<title>Home</title>")
+            .is_none());
+    }
+
+    #[test]
+    fn test_context_is_part_of_cache_key() {
+        let cache = test_cache("sena_agent_cache_context");
+        let pool = DomainAgentPool::new().with_cache(cache.clone());
+        let input = "app.post('/api/orders', createOrder);";
+        let context = AnalysisContext::new()
+            .with_memories(vec!["Routes are guarded by requireAuth".to_string()]);
+
+        let plain = pool.analyze(DomainAgentType::Backend, "map", input);
+        let with_context =
+            pool.analyze_with_context(DomainAgentType::Backend, "map", input, &context);
+
+        assert_eq!(with_context.findings.len(), plain.findings.len() + 1);
+        assert!(cache
+            .get(DomainAgentType::Backend, "map", &context.cache_input(input))
+            .is_some());
+    }
+
+    #[test]
+    fn test_cache_evicts_least_recently_used() {
+        let cache = test_cache("sena_agent_cache_lru").with_max_entries(2);
+        let pool = DomainAgentPool::new().with_cache(cache.clone());
+
+        pool.analyze(DomainAgentType::Web, "seo", "<title>One</title>");
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        pool.analyze(DomainAgentType::Web, "seo", "<title>Two</title>");
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        // Using the oldest entry makes "Two" the least recently used
+        assert!(cache
+            .get(DomainAgentType::Web, "seo", "<title>One</title>")
+            .is_some());
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        pool.analyze(DomainAgentType::Web, "seo", "<title>Three</title>");
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache
+            .get(DomainAgentType::Web, "seo", "<title>One</title>")
+            .is_some());
+        assert!(cache
+            .get(DomainAgentType::Web, "seo", "<title>Two</title>")
+            .is_none());
+    }
+
+    #[test]
+    fn test_cache_entries_expire() {
+        let cache = test_cache("sena_agent_cache_ttl");
+        let pool = DomainAgentPool::new().with_cache(cache.clone());
+        pool.analyze(DomainAgentType::Web, "seo", "<title>Home</title>");
+
+        let expired = cache.clone().with_ttl(chrono::Duration::zero());
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        assert!(expired
+            .get(DomainAgentType::Web, "seo", "<title>Home</title>")
+            .is_none());
+        assert!(cache.is_empty());
+    }
 }
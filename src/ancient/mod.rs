@@ -44,9 +44,9 @@ pub use constraint_feature::{
 };
 
 pub use negative_space::{
-    BoundaryType, BoundaryValue, NegativeSpaceArchitecture, NegativeSpaceCheckResult,
-    NegativeSpaceDefinition, Prohibition, ProhibitionCategory, ProhibitionLevel, SafeBoundary,
-    SafetyReport, ViolationAttempt, ViolationSeverity,
+    BoundaryType, BoundaryValue, HardeningSuggestion, NegativeSpaceArchitecture,
+    NegativeSpaceCheckResult, NegativeSpaceDefinition, Prohibition, ProhibitionCategory,
+    ProhibitionLevel, SafeBoundary, SafetyReport, ViolationAttempt, ViolationSeverity,
 };
 
 pub use relationship_model::{
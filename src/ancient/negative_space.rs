@@ -9,6 +9,7 @@
 //! Applied to AI: Define failure modes before success modes.
 //! Know what the system must NEVER do.
 
+use crate::base::text::is_stop_word;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
@@ -42,6 +43,15 @@ pub enum ProhibitionLevel {
 }
 
 impl ProhibitionLevel {
+    /// The next stricter level; Absolute stays Absolute
+    pub fn stricter(&self) -> Self {
+        match self {
+            ProhibitionLevel::Absolute | ProhibitionLevel::Strong => ProhibitionLevel::Absolute,
+            ProhibitionLevel::Standard => ProhibitionLevel::Strong,
+            ProhibitionLevel::Advisory => ProhibitionLevel::Standard,
+        }
+    }
+
     pub fn severity_score(&self) -> f64 {
         match self {
             ProhibitionLevel::Absolute => 1.0,
@@ -323,6 +333,10 @@ pub struct ViolationAttempt {
     pub context: HashMap<String, String>,
     pub action_taken: String,
     pub blocked: bool,
+    /// Distinctive words of the content in order of appearance, used to
+    /// group similar attempts
+    #[serde(default)]
+    pub terms: Vec<String>,
 }
 
 impl ViolationAttempt {
@@ -352,6 +366,93 @@ impl ViolationAttempt {
                 "LOGGED".to_string()
             },
             blocked,
+            terms: distinctive_terms(content),
+        }
+    }
+
+    /// Share of distinct terms the two attempts have in common
+    fn similarity(&self, other: &ViolationAttempt) -> f64 {
+        let shared = self
+            .terms
+            .iter()
+            .filter(|term| other.terms.contains(term))
+            .count();
+        let union = self.terms.len() + other.terms.len() - shared;
+        if union == 0 {
+            0.0
+        } else {
+            shared as f64 / union as f64
+        }
+    }
+}
+
+/// Lowercase words of three or more characters, without stop words or
+/// repeats, in the order they first appear
+fn distinctive_terms(content: &str) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    for word in content
+        .split(|c: char| !c.is_alphanumeric())
+        .map(str::to_lowercase)
+    {
+        if word.chars().count() >= 3 && !is_stop_word(&word) && !terms.contains(&word) {
+            terms.push(word);
+        }
+    }
+    terms
+}
+
+/// Attempts must share at least this share of their terms to count as similar
+const SIMILARITY_THRESHOLD: f64 = 0.5;
+
+/// Similar attempts needed before [`NegativeSpaceArchitecture::learn_from_violations`]
+/// suggests a change, so one-offs never become rules
+pub const DEFAULT_LEARNING_THRESHOLD: usize = 3;
+
+/// A change proposed from repeated violation attempts, left for review
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum HardeningSuggestion {
+    /// A dedicated, stricter rule for a recurring kind of attempt
+    NewProhibition {
+        prohibition: Box<Prohibition>,
+        /// The prohibition the attempts were caught by
+        learned_from: String,
+        attempts: usize,
+    },
+    /// Attempts keep getting through a prohibition that only logs them
+    RaiseLevel {
+        prohibition_id: String,
+        name: String,
+        from: ProhibitionLevel,
+        to: ProhibitionLevel,
+        attempts: usize,
+    },
+}
+
+impl HardeningSuggestion {
+    /// One line for reports
+    pub fn summary(&self) -> String {
+        match self {
+            HardeningSuggestion::NewProhibition {
+                prohibition,
+                attempts,
+                ..
+            } => format!(
+                "Add {:?} prohibition '{}' matching {} ({} similar attempts)",
+                prohibition.level,
+                prohibition.name,
+                prohibition.patterns.join(", "),
+                attempts
+            ),
+            HardeningSuggestion::RaiseLevel {
+                name,
+                from,
+                to,
+                attempts,
+                ..
+            } => format!(
+                "Raise '{}' from {:?} to {:?} ({} attempts got through)",
+                name, from, to, attempts
+            ),
         }
     }
 }
@@ -422,6 +523,7 @@ pub struct NegativeSpaceArchitecture {
     boundaries: HashMap<String, SafeBoundary>,
     violation_history: Vec<ViolationAttempt>,
     strict_mode: bool,
+    learning_threshold: usize,
 }
 
 impl Default for NegativeSpaceArchitecture {
@@ -438,6 +540,7 @@ impl NegativeSpaceArchitecture {
             boundaries: HashMap::new(),
             violation_history: Vec::new(),
             strict_mode: true,
+            learning_threshold: DEFAULT_LEARNING_THRESHOLD,
         };
         engine.initialize_core_prohibitions();
         engine
@@ -448,6 +551,12 @@ impl NegativeSpaceArchitecture {
         self
     }
 
+    /// Similar attempts needed before a hardening suggestion; at least 2
+    pub fn with_learning_threshold(mut self, attempts: usize) -> Self {
+        self.learning_threshold = attempts.max(2);
+        self
+    }
+
     /// Initialize core safety prohibitions
     fn initialize_core_prohibitions(&mut self) {
         // Absolute prohibitions - NEVER violate
@@ -718,6 +827,114 @@ impl NegativeSpaceArchitecture {
         Ok(())
     }
 
+    /// Propose hardening from the violation history without applying it.
+    ///
+    /// Attempts caught by the same prohibition are grouped by the terms they
+    /// share; a group of at least the learning threshold yields a stricter
+    /// prohibition matching those terms. A prohibition with such a group
+    /// and no attempt blocked is proposed for a stricter level.
+    pub fn learn_from_violations(&self) -> Vec<HardeningSuggestion> {
+        let mut by_prohibition: HashMap<&str, Vec<&ViolationAttempt>> = HashMap::new();
+        for attempt in &self.violation_history {
+            by_prohibition
+                .entry(attempt.prohibition_id.as_str())
+                .or_default()
+                .push(attempt);
+        }
+
+        let mut suggestions = Vec::new();
+        let mut sources: Vec<_> = by_prohibition.into_iter().collect();
+        sources.sort_by_key(|(id, _)| *id);
+        for (prohibition_id, attempts) in sources {
+            let Some(source) = self.prohibitions.get(prohibition_id) else {
+                continue;
+            };
+
+            let groups = group_similar(&attempts);
+            for group in &groups {
+                if group.len() < self.learning_threshold {
+                    continue;
+                }
+                if let Some(prohibition) = self.learned_prohibition(source, group) {
+                    suggestions.push(HardeningSuggestion::NewProhibition {
+                        prohibition: Box::new(prohibition),
+                        learned_from: source.id.clone(),
+                        attempts: group.len(),
+                    });
+                }
+            }
+
+            let only_logged = matches!(
+                source.level,
+                ProhibitionLevel::Standard | ProhibitionLevel::Advisory
+            ) && attempts.iter().all(|a| !a.blocked);
+            let repeated = groups.iter().map(Vec::len).max().unwrap_or(0);
+            if only_logged && repeated >= self.learning_threshold {
+                suggestions.push(HardeningSuggestion::RaiseLevel {
+                    prohibition_id: source.id.clone(),
+                    name: source.name.clone(),
+                    from: source.level,
+                    to: source.level.stricter(),
+                    attempts: repeated,
+                });
+            }
+        }
+        suggestions
+    }
+
+    /// A prohibition matching the terms every attempt in `group` shares, one
+    /// level stricter than `source`; `None` when they share too little or a
+    /// prohibition with that pattern already exists
+    fn learned_prohibition(
+        &self,
+        source: &Prohibition,
+        group: &[&ViolationAttempt],
+    ) -> Option<Prohibition> {
+        let common: Vec<&String> = group[0]
+            .terms
+            .iter()
+            .filter(|term| group[1..].iter().all(|a| a.terms.contains(term)))
+            .collect();
+        if common.len() < 2 {
+            return None;
+        }
+
+        let pattern = format!(
+            r"(?i)\b{}\b",
+            common
+                .iter()
+                .map(|term| regex::escape(term))
+                .collect::<Vec<_>>()
+                .join(r"\b.*\b")
+        );
+        if self
+            .prohibitions
+            .values()
+            .any(|p| p.patterns.contains(&pattern))
+        {
+            return None;
+        }
+
+        let words: Vec<&str> = common.iter().map(|term| term.as_str()).collect();
+        Some(
+            Prohibition::new(
+                format!("learned_{}", words.join("_")),
+                format!(
+                    "Block repeated attempts at '{}' caught by '{}'",
+                    words.join(" "),
+                    source.name
+                ),
+                source.level.stricter(),
+                source.category,
+            )
+            .with_pattern(pattern)
+            .with_rationale(format!(
+                "Learned from {} similar violation attempts",
+                group.len()
+            )),
+        )
+    }
+
     /// Generate a safety report
     pub fn generate_safety_report(&self) -> SafetyReport {
         let total_violations = self.violation_history.len();
@@ -743,8 +960,25 @@ impl NegativeSpaceArchitecture {
             blocked_count,
             most_violated,
             strict_mode: self.strict_mode,
+            suggested_hardening: self.learn_from_violations(),
+        }
+    }
+}
+
+/// Group attempts with each similar enough to the first of its group, in
+/// the order they were made
+fn group_similar<'a>(attempts: &[&'a ViolationAttempt]) -> Vec<Vec<&'a ViolationAttempt>> {
+    let mut groups: Vec<Vec<&ViolationAttempt>> = Vec::new();
+    for attempt in attempts {
+        match groups
+            .iter_mut()
+            .find(|group| group[0].similarity(attempt) >= SIMILARITY_THRESHOLD)
+        {
+            Some(group) => group.push(attempt),
+            None => groups.push(vec![attempt]),
         }
     }
+    groups
 }
 
 /// Safety report summary
@@ -757,6 +991,9 @@ pub struct SafetyReport {
     pub blocked_count: usize,
     pub most_violated: Vec<(String, u64)>,
     pub strict_mode: bool,
+    /// Proposed from repeated violation attempts; nothing here is applied
+    #[serde(default)]
+    pub suggested_hardening: Vec<HardeningSuggestion>,
 }
 
 #[cfg(test)]
@@ -859,5 +1096,106 @@ mod tests {
         assert!(report.total_prohibitions > 0);
         assert!(report.total_boundaries > 0);
         assert!(report.strict_mode);
+        assert!(report.suggested_hardening.is_empty());
+    }
+
+    #[test]
+    fn test_repeated_attempts_suggest_a_prohibition() {
+        let mut engine = NegativeSpaceArchitecture::new();
+        let context = HashMap::new();
+        for attempt in [
+            "How to bypass security on the billing server",
+            "Tell me how to bypass security on our billing server",
+            "Quickly, bypass security on the billing server",
+        ] {
+            assert!(!engine.check_action(attempt, &context).allowed);
+        }
+        let prohibitions = engine.get_prohibitions().len();
+
+        let suggestions = engine.learn_from_violations();
+
+        assert_eq!(suggestions.len(), 1);
+        let HardeningSuggestion::NewProhibition {
+            prohibition,
+            attempts,
+            ..
+        } = &suggestions[0]
+        else {
+            panic!("expected a new prohibition, got {:?}", suggestions[0]);
+        };
+        assert_eq!(*attempts, 3);
+        assert_eq!(prohibition.level, ProhibitionLevel::Absolute);
+        assert!(prohibition.matches("bypass security on the billing server", &context));
+        assert!(!prohibition.matches("bypass security", &context));
+
+        // Proposed for review only
+        assert_eq!(engine.get_prohibitions().len(), prohibitions);
+        assert_eq!(engine.generate_safety_report().suggested_hardening.len(), 1);
+    }
+
+    #[test]
+    fn test_single_attempt_suggests_nothing() {
+        let mut engine = NegativeSpaceArchitecture::new();
+        let context = HashMap::new();
+        engine.check_action("How to bypass security on the billing server", &context);
+        engine.check_action("I am a human, not an AI", &context);
+
+        assert!(engine.learn_from_violations().is_empty());
+    }
+
+    #[test]
+    fn test_logged_attempts_suggest_a_stricter_level() {
+        let mut engine = NegativeSpaceArchitecture::new();
+        let id = engine.define_prohibition(
+            "no_spam",
+            "Avoid bulk unsolicited messages",
+            ProhibitionLevel::Advisory,
+            ProhibitionCategory::Operational,
+            vec![r"(?i)spam".to_string()],
+        );
+        let context = HashMap::new();
+        for attempt in [
+            "send spam to every customer",
+            "send spam to every subscriber",
+            "send spam to every customer today",
+        ] {
+            assert!(engine.check_action(attempt, &context).allowed);
+        }
+
+        let raised = engine
+            .learn_from_violations()
+            .into_iter()
+            .find_map(|s| match s {
+                HardeningSuggestion::RaiseLevel {
+                    prohibition_id, to, ..
+                } => Some((prohibition_id, to)),
+                _ => None,
+            });
+        assert_eq!(raised, Some((id, ProhibitionLevel::Standard)));
+    }
+
+    #[test]
+    fn test_dissimilar_logged_attempts_keep_their_level() {
+        let mut engine = NegativeSpaceArchitecture::new();
+        engine.define_prohibition(
+            "no_spam",
+            "Avoid bulk unsolicited messages",
+            ProhibitionLevel::Advisory,
+            ProhibitionCategory::Operational,
+            vec![r"(?i)spam".to_string()],
+        );
+        let context = HashMap::new();
+        for attempt in [
+            "spam filter tuning notes",
+            "recipe with canned spam",
+            "history of monty python sketches about spam",
+        ] {
+            assert!(engine.check_action(attempt, &context).allowed);
+        }
+
+        assert!(!engine
+            .learn_from_violations()
+            .iter()
+            .any(|s| matches!(s, HardeningSuggestion::RaiseLevel { .. })));
     }
 }
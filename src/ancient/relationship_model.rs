@@ -755,6 +755,111 @@ impl RelationshipDataModel {
     pub fn get_all_relationships(&self) -> Vec<&Relationship> {
         self.relationships.values().collect()
     }
+
+    fn sorted_nodes(&self) -> Vec<&RelationshipNode> {
+        let mut nodes: Vec<&RelationshipNode> = self.nodes.values().collect();
+        nodes.sort_by(|a, b| a.name.cmp(&b.name));
+        nodes
+    }
+
+    fn sorted_edges(&self) -> Vec<(&str, &str, &Relationship)> {
+        let mut edges: Vec<(&str, &str, &Relationship)> = self
+            .relationships
+            .values()
+            .filter_map(|rel| {
+                let source = self.nodes.get(&rel.source_id)?;
+                let target = self.nodes.get(&rel.target_id)?;
+                Some((source.name.as_str(), target.name.as_str(), rel))
+            })
+            .collect();
+        edges.sort_by(|a, b| (a.0, a.1).cmp(&(b.0, b.1)));
+        edges
+    }
+
+    fn edge_label(rel: &Relationship) -> String {
+        rel.properties
+            .get("label")
+            .cloned()
+            .unwrap_or_else(|| format!("{:?}", rel.relation_type))
+    }
+
+    /// Export the graph in Graphviz DOT format, ordered by node name
+    pub fn to_dot(&self, graph_name: &str) -> String {
+        fn quote(value: &str) -> String {
+            format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+        }
+
+        let mut output = format!("digraph {} {{\n", quote(graph_name));
+        output.push_str("  rankdir=LR;\n");
+
+        for node in self.sorted_nodes() {
+            let label = node.properties.get("label").unwrap_or(&node.name);
+            let mut attrs = vec![format!("label={}", quote(label))];
+            if let Some(shape) = node.properties.get("shape") {
+                attrs.push(format!("shape={}", quote(shape)));
+            }
+            output.push_str(&format!(
+                "  {} [{}];\n",
+                quote(&node.name),
+                attrs.join(", ")
+            ));
+        }
+
+        for (source, target, rel) in self.sorted_edges() {
+            let mut attrs = vec![format!("label={}", quote(&Self::edge_label(rel)))];
+            for key in ["style", "color"] {
+                if let Some(value) = rel.properties.get(key) {
+                    attrs.push(format!("{}={}", key, quote(value)));
+                }
+            }
+            if rel.bidirectional {
+                attrs.push("dir=both".to_string());
+            }
+            output.push_str(&format!(
+                "  {} -> {} [{}];\n",
+                quote(source),
+                quote(target),
+                attrs.join(", ")
+            ));
+        }
+
+        output.push_str("}\n");
+        output
+    }
+
+    /// Export the graph as an indented ASCII listing of each node's outgoing edges
+    pub fn to_ascii(&self) -> String {
+        let edges = self.sorted_edges();
+        let mut output = String::new();
+
+        for node in self.sorted_nodes() {
+            let label = node.properties.get("label").unwrap_or(&node.name);
+            output.push_str(&format!("{}\n", label));
+
+            let outgoing: Vec<_> = edges.iter().filter(|(s, _, _)| *s == node.name).collect();
+            for (i, (_, target, rel)) in outgoing.iter().enumerate() {
+                let branch = if i + 1 == outgoing.len() {
+                    "└──"
+                } else {
+                    "├──"
+                };
+                let marker = rel
+                    .properties
+                    .get("marker")
+                    .map(|m| format!(" {}", m))
+                    .unwrap_or_default();
+                output.push_str(&format!(
+                    "  {} [{}] {}{}\n",
+                    branch,
+                    Self::edge_label(rel),
+                    target,
+                    marker
+                ));
+            }
+        }
+
+        output
+    }
 }
 
 /// Statistics about the relationship model
@@ -861,4 +966,20 @@ mod tests {
         assert_eq!(stats.node_count, 2);
         assert_eq!(stats.relationship_count, 1);
     }
+
+    #[test]
+    fn test_dot_export_is_sorted_and_styled() {
+        let mut model = RelationshipDataModel::new();
+        let b = model.create_node("B", NodeType::Entity);
+        let a = model.create_node("A", NodeType::Entity);
+        model.add_relationship(
+            Relationship::new(&a, &b, RelationType::DependsOn).with_property("style", "dashed"),
+        );
+
+        let dot = model.to_dot("g");
+        assert!(dot.starts_with("digraph \"g\" {"));
+        assert!(dot.find("\"A\" [").unwrap() < dot.find("\"B\" [").unwrap());
+        assert!(dot.contains("\"A\" -> \"B\" [label=\"DependsOn\", style=\"dashed\"];"));
+        assert!(model.to_ascii().contains("└── [DependsOn] B"));
+    }
 }
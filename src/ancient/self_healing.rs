@@ -541,7 +541,7 @@ impl EmbeddedSelfHealing {
         // Sort by strategy
         match self.strategy {
             HealingStrategy::Priority => {
-                applicable.sort_by(|a, b| b.priority.cmp(&a.priority));
+                applicable.sort_by_key(|b| std::cmp::Reverse(b.priority));
             }
             HealingStrategy::BestSuccess => {
                 applicable.sort_by(|a, b| {
@@ -551,7 +551,7 @@ impl EmbeddedSelfHealing {
                 });
             }
             HealingStrategy::Fastest => {
-                applicable.sort_by(|a, b| a.avg_healing_time_ms.cmp(&b.avg_healing_time_ms));
+                applicable.sort_by_key(|a| a.avg_healing_time_ms);
             }
             HealingStrategy::Exhaustive => {
                 // Keep original order, try all
@@ -4,10 +4,12 @@
 //! - Interface traits (IVerifier, IStorage, IExecutor, etc.)
 //! - Component registry for dependency injection
 //! - Base component trait for lifecycle management
+//! - Shared text helpers (stop words)
 
 pub mod component;
 pub mod interfaces;
 pub mod registry;
+pub mod text;
 
 pub use component::{BaseComponent, ComponentMetrics, ComponentState, ComponentStatus};
 pub use interfaces::*;
@@ -0,0 +1,18 @@
+//! Shared Text Helpers
+//!
+//! Word lists used wherever free text is reduced to its meaningful terms.
+
+/// Common English words that say nothing about a text's topic
+pub const STOP_WORDS: &[&str] = &[
+    "about", "after", "also", "and", "any", "are", "because", "been", "being", "but", "can",
+    "could", "did", "does", "doing", "for", "from", "give", "had", "has", "have", "here", "how",
+    "into", "it's", "its", "just", "like", "make", "more", "most", "not", "only", "onto", "our",
+    "over", "please", "should", "show", "some", "tell", "than", "that", "the", "their", "them",
+    "then", "there", "these", "they", "this", "those", "very", "was", "were", "what", "when",
+    "where", "which", "while", "who", "why", "will", "with", "would", "you", "your",
+];
+
+/// Whether `word` (lowercase) is in `STOP_WORDS`
+pub fn is_stop_word(word: &str) -> bool {
+    STOP_WORDS.contains(&word)
+}
@@ -24,6 +24,23 @@ pub enum OutputFormat {
     Text,
     Json,
     Pretty,
+    Yaml,
+}
+
+impl OutputFormat {
+    /// JSON and YAML carry data; text and pretty are for people
+    pub fn is_structured(self) -> bool {
+        matches!(self, OutputFormat::Json | OutputFormat::Yaml)
+    }
+
+    /// The format handed to command handlers. YAML is converted from their
+    /// JSON in `execute_command`, so handlers only deal with JSON.
+    pub fn for_handler(self) -> Self {
+        match self {
+            OutputFormat::Yaml => OutputFormat::Json,
+            other => other,
+        }
+    }
 }
 
 #[derive(Subcommand, Debug)]
@@ -50,6 +67,20 @@ pub enum Commands {
 
         #[arg(short = 't', long, default_value = "general", help = "Request type")]
         request_type: String,
+
+        #[arg(
+            long,
+            value_name = "PRIOR_RESULT_JSON",
+            help = "Compare against a result saved with --format json"
+        )]
+        diff: Option<String>,
+
+        #[arg(
+            long,
+            value_name = "FILE",
+            help = "Write a per-phase timing trace for speedscope or Perfetto"
+        )]
+        trace: Option<String>,
     },
 
     #[command(about = "Check system health")]
@@ -58,10 +89,34 @@ pub enum Commands {
         detailed: bool,
     },
 
+    #[command(about = "Show compiled provider features and available capabilities")]
+    Features,
+
+    #[command(about = "Run the millennium test against SENA's own subsystems")]
+    Selftest {
+        #[arg(
+            long,
+            default_value_t = crate::metrics::DEFAULT_SELFTEST_THRESHOLD,
+            help = "Minimum score (0-1) every subsystem must reach"
+        )]
+        threshold: f64,
+    },
+
     #[command(about = "Get system metrics")]
     Metrics {
         #[arg(value_enum, help = "Metric category")]
         category: Option<MetricCategory>,
+
+        #[arg(long, help = "Print metrics in the Prometheus text format")]
+        prometheus: bool,
+
+        #[cfg(feature = "metrics-server")]
+        #[arg(
+            long,
+            value_name = "ADDR",
+            help = "Serve Prometheus metrics at http://ADDR/metrics"
+        )]
+        serve: Option<String>,
     },
 
     #[command(about = "Detect format for input")]
@@ -74,6 +129,9 @@ pub enum Commands {
     Daemon {
         #[arg(value_enum, help = "Action")]
         action: DaemonAction,
+
+        #[arg(long, help = "Run in this terminal instead of detaching")]
+        foreground: bool,
     },
 
     #[command(about = "Session management")]
@@ -105,6 +163,15 @@ pub enum Commands {
         #[arg(short, long, help = "Title")]
         title: Option<String>,
 
+        #[arg(long, help = "Maximum table column width")]
+        max_width: Option<usize>,
+
+        #[arg(
+            long,
+            help = "Wrap table cells wider than --max-width instead of truncating"
+        )]
+        wrap: bool,
+
         #[arg(help = "Data (JSON)")]
         data: String,
     },
@@ -134,6 +201,9 @@ pub enum Commands {
 
         #[arg(help = "Message")]
         message: String,
+
+        #[arg(long, value_name = "MESSAGE_ID", help = "Reply to a message")]
+        reply: Option<String>,
     },
 
     #[command(about = "Check inbox")]
@@ -175,6 +245,12 @@ pub enum Commands {
         content: String,
     },
 
+    #[command(about = "Built-in and user-defined skills")]
+    Skills {
+        #[command(subcommand)]
+        action: SkillsAction,
+    },
+
     #[command(about = "Evolution system commands")]
     Evolve {
         #[command(subcommand)]
@@ -200,6 +276,15 @@ pub enum Commands {
 
         #[arg(help = "Code or file content to analyze")]
         input: String,
+
+        #[arg(long, default_value_t = false, help = "Bypass the analysis cache")]
+        no_cache: bool,
+
+        #[arg(
+            long,
+            help = "Check against project memories, framework and git branch"
+        )]
+        project_context: bool,
     },
 
     #[command(about = "IoT development agent")]
@@ -209,6 +294,9 @@ pub enum Commands {
 
         #[arg(help = "Code or file content to analyze")]
         input: String,
+
+        #[arg(long, default_value_t = false, help = "Bypass the analysis cache")]
+        no_cache: bool,
     },
 
     #[command(about = "iOS development agent")]
@@ -218,6 +306,9 @@ pub enum Commands {
 
         #[arg(help = "Code or file content to analyze")]
         input: String,
+
+        #[arg(long, default_value_t = false, help = "Bypass the analysis cache")]
+        no_cache: bool,
     },
 
     #[command(about = "Android development agent")]
@@ -227,6 +318,9 @@ pub enum Commands {
 
         #[arg(help = "Code or file content to analyze")]
         input: String,
+
+        #[arg(long, default_value_t = false, help = "Bypass the analysis cache")]
+        no_cache: bool,
     },
 
     #[command(about = "Web development agent")]
@@ -236,6 +330,9 @@ pub enum Commands {
 
         #[arg(help = "Code or file content to analyze")]
         input: String,
+
+        #[arg(long, default_value_t = false, help = "Bypass the analysis cache")]
+        no_cache: bool,
     },
 
     #[command(about = "Interactive setup wizard")]
@@ -297,10 +394,25 @@ pub enum Commands {
         action: MemoryAction,
     },
 
-    #[command(about = "Autonomous agent execution")]
+    #[command(about = "Show the memory and knowledge context assembled for a prompt")]
+    Context {
+        #[arg(help = "Prompt or query to gather context for")]
+        query: String,
+
+        #[arg(long, default_value_t = crate::knowledge::DEFAULT_CONTEXT_TOKENS, help = "Token budget for the assembled context")]
+        max_tokens: usize,
+    },
+
+    #[command(
+        about = "Autonomous agent execution",
+        args_conflicts_with_subcommands = true
+    )]
     Auto {
+        #[command(subcommand)]
+        action: Option<AutoAction>,
+
         #[arg(help = "Task description")]
-        task: String,
+        task: Option<String>,
 
         #[arg(short, long, default_value_t = 10, help = "Maximum steps")]
         max_steps: usize,
@@ -334,6 +446,12 @@ pub enum Commands {
         #[command(subcommand)]
         action: DevilAction,
     },
+
+    #[command(about = "SENA configuration")]
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
 }
 
 #[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
@@ -409,6 +527,8 @@ pub enum HubAction {
         target: String,
         #[arg(help = "Message content")]
         message: String,
+        #[arg(long, value_name = "MESSAGE_ID", help = "Reply to a message")]
+        reply: Option<String>,
     },
     #[command(about = "View all messages across sessions")]
     Messages {
@@ -423,7 +543,13 @@ pub enum HubAction {
     #[command(about = "Show conflicts")]
     Conflicts,
     #[command(about = "Clear hub data")]
-    Clear,
+    Clear {
+        #[arg(long, default_value_t = false, help = "Show what would be removed")]
+        dry_run: bool,
+
+        #[arg(short, long, default_value_t = false, help = "Confirm removal")]
+        yes: bool,
+    },
     #[command(about = "Show this hub's identity")]
     Identity,
     #[command(about = "Set hub display name")]
@@ -439,9 +565,16 @@ pub enum HubAction {
     Connect {
         #[arg(help = "Hub address (IP:port or hostname:port)")]
         address: String,
+        #[arg(short, long, help = "Passkey shown by the remote hub")]
+        passkey: String,
         #[arg(short, long, help = "Optional connection message")]
         message: Option<String>,
     },
+    #[command(about = "Accept connection requests from remote hubs")]
+    Listen {
+        #[arg(long, help = "Port to listen on (defaults to the hub identity port)")]
+        port: Option<u16>,
+    },
     #[command(about = "Approve pending connection request")]
     Approve {
         #[arg(help = "Request ID (first 8 characters)")]
@@ -471,6 +604,17 @@ pub enum HubAction {
         #[arg(short, long, help = "Also remove associated messages")]
         messages: bool,
     },
+    #[command(about = "Show sessions, tasks and dependencies as a graph")]
+    Graph {
+        #[arg(short, long, value_enum, default_value_t = GraphFormat::Ascii, help = "Graph format")]
+        format: GraphFormat,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
+pub enum GraphFormat {
+    Dot,
+    Ascii,
 }
 
 #[derive(Subcommand, Debug, Clone)]
@@ -496,6 +640,9 @@ pub enum TaskAction {
     #[command(about = "My tasks")]
     Mine,
 
+    #[command(about = "Pending tasks whose dependencies are all done")]
+    Ready,
+
     #[command(about = "Mark done")]
     Done {
         #[arg(help = "Task ID")]
@@ -525,6 +672,15 @@ pub enum TaskAction {
         #[arg(help = "Task ID")]
         id: u64,
     },
+
+    #[command(about = "Mark a task as depending on another")]
+    Depend {
+        #[arg(help = "Task ID")]
+        id: u64,
+
+        #[arg(help = "ID of the task it depends on")]
+        on: u64,
+    },
 }
 
 #[derive(Subcommand, Debug, Clone)]
@@ -546,6 +702,34 @@ pub enum KnowledgeAction {
 
     #[command(about = "Show knowledge statistics")]
     Stats,
+
+    #[command(about = "Import an OWASP/CWE dataset into security patterns")]
+    ImportOwasp {
+        #[arg(help = "Dataset JSON file (defaults to the bundled OWASP Top 10)")]
+        file: Option<String>,
+    },
+
+    #[command(
+        about = "Lint a file or directory with the security, performance and architecture detectors"
+    )]
+    Lint {
+        #[arg(help = "File or directory to lint")]
+        path: String,
+
+        #[arg(
+            long,
+            value_enum,
+            help = "Exit non-zero if any finding is at least this severe"
+        )]
+        fail_on: Option<LintSeverityArg>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
+pub enum LintSeverityArg {
+    Critical,
+    Warning,
+    Info,
 }
 
 #[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
@@ -566,8 +750,17 @@ pub enum ThinkingDepthArg {
     Maximum,
 }
 
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq, Default)]
+pub enum CommitStyleArg {
+    #[default]
+    Plain,
+    Conventional,
+}
+
 #[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
 pub enum AgentTypeArg {
+    /// Pick the agent from the task's keywords
+    Auto,
     Security,
     Performance,
     Architecture,
@@ -599,6 +792,15 @@ pub enum EvolveAction {
         #[arg(short, long, default_value_t = 10, help = "Limit")]
         limit: usize,
     },
+
+    #[command(about = "Recall learned patterns similar to a context")]
+    Recall {
+        #[arg(help = "Context")]
+        context: String,
+
+        #[arg(short, long, default_value_t = 3, help = "Number of patterns")]
+        top: usize,
+    },
 }
 
 #[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
@@ -712,6 +914,11 @@ pub enum ProviderAction {
     Models {
         #[arg(short, long, help = "Filter by provider")]
         provider: Option<String>,
+        #[arg(
+            long,
+            help = "Ask each provider's API for its current models instead of the built-in list"
+        )]
+        remote: bool,
     },
 
     #[command(about = "Chat with an AI provider")]
@@ -724,6 +931,14 @@ pub enum ProviderAction {
 
         #[arg(short, long, help = "Model to use")]
         model: Option<String>,
+
+        #[arg(
+            long,
+            default_value_t = false,
+            conflicts_with_all = ["provider", "model"],
+            help = "Pick the provider and model from the task type"
+        )]
+        auto: bool,
     },
 
     #[command(about = "Set default provider")]
@@ -737,6 +952,48 @@ pub enum ProviderAction {
         #[arg(help = "Provider to test (or 'all')")]
         provider: String,
     },
+
+    #[command(about = "Open pooled connections to all providers ahead of the first request")]
+    Warmup,
+    #[command(about = "Verify model tool/streaming support with minimal test calls")]
+    #[command(about = "Verify model tool/vision/streaming support with minimal test calls")]
+    Probe {
+        #[arg(help = "Provider to probe (all configured providers when omitted)")]
+        provider: Option<String>,
+
+        #[arg(short, long, help = "Model to probe (provider default when omitted)")]
+        model: Option<String>,
+
+        #[arg(long, help = "Ignore cached probe results")]
+        force: bool,
+    },
+
+    #[command(about = "Manage friendly model aliases")]
+    Alias {
+        #[command(subcommand)]
+        action: AliasAction,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum AliasAction {
+    #[command(about = "List model aliases")]
+    List,
+
+    #[command(about = "Set an alias (target is provider/model or a model id)")]
+    Set {
+        #[arg(help = "Alias name")]
+        name: String,
+
+        #[arg(help = "Target, e.g. claude/claude-sonnet-4-5-20250929")]
+        target: String,
+    },
+
+    #[command(about = "Remove an alias")]
+    Remove {
+        #[arg(help = "Alias name")]
+        name: String,
+    },
 }
 
 #[derive(Subcommand, Debug, Clone)]
@@ -763,6 +1020,12 @@ pub enum PeerAction {
     Remove {
         #[arg(help = "Peer ID")]
         peer_id: String,
+
+        #[arg(long, default_value_t = false, help = "Show what would be removed")]
+        dry_run: bool,
+
+        #[arg(short, long, default_value_t = false, help = "Confirm removal")]
+        yes: bool,
     },
 
     #[command(about = "Authorize peer (generate token)")]
@@ -770,7 +1033,12 @@ pub enum PeerAction {
         #[arg(help = "Peer ID")]
         peer_id: String,
 
-        #[arg(short, long, default_value_t = 300, help = "Token expiry in seconds")]
+        #[arg(
+            short,
+            long,
+            default_value_t = crate::network::DEFAULT_PEER_TOKEN_EXPIRY,
+            help = "Token expiry in seconds"
+        )]
         expires: i64,
     },
 
@@ -786,7 +1054,26 @@ pub enum PeerAction {
         token: String,
     },
 
-    #[command(about = "Revoke peer authorization")]
+    #[command(about = "Accept a peer's new TLS certificate after it was rotated")]
+    Reauthorize {
+        #[arg(help = "Peer ID")]
+        peer_id: String,
+
+        #[arg(
+            long,
+            help = "Fingerprint to pin (from `sena network info` on the peer); \
+                    without it the next `peer connect` pins the presented certificate"
+        )]
+        fingerprint: Option<String>,
+    },
+
+    #[command(about = "Issue a peer a new token; the old one works for a short grace period")]
+    Rotate {
+        #[arg(help = "Peer ID")]
+        peer_id: String,
+    },
+
+    #[command(about = "Revoke peer authorization and its tokens")]
     Revoke {
         #[arg(help = "Peer ID")]
         peer_id: String,
@@ -875,6 +1162,33 @@ pub enum CollabAction {
         #[arg(help = "Session ID")]
         session_id: String,
     },
+
+    #[command(about = "Export a session transcript (Markdown, or JSON with --format json)")]
+    Export {
+        #[arg(help = "Session ID")]
+        session_id: String,
+
+        #[arg(short, long, help = "Write the transcript to a file")]
+        output: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum SkillsAction {
+    #[command(about = "List built-in and user-defined skills")]
+    List,
+
+    #[command(about = "Run the skill whose triggers match the context")]
+    Run {
+        #[arg(help = "Context to run the skill on")]
+        context: String,
+
+        #[arg(short, long, help = "Run this skill instead of matching triggers")]
+        name: Option<String>,
+    },
+
+    #[command(about = "Reload user-defined skills from ~/.sena/skills")]
+    Reload,
 }
 
 #[derive(Subcommand, Debug, Clone)]
@@ -911,6 +1225,18 @@ pub enum ToolsAction {
         #[arg(short, long, help = "File pattern (e.g., *.rs)")]
         files: Option<String>,
     },
+
+    #[command(about = "Show persisted tool call history")]
+    History {
+        #[arg(short, long, help = "Only calls to this tool")]
+        tool: Option<String>,
+
+        #[arg(long, help = "Only successful calls")]
+        success_only: bool,
+
+        #[arg(short, long, default_value = "20", help = "Number of calls to show")]
+        limit: usize,
+    },
 }
 
 #[derive(Subcommand, Debug, Clone)]
@@ -931,7 +1257,11 @@ pub enum MemoryAction {
         #[arg(short, long, help = "Tags (comma-separated)")]
         tags: Option<String>,
 
-        #[arg(short, long, help = "Importance (0.0-1.0)")]
+        #[arg(
+            short,
+            long,
+            help = "Importance (0.0-1.0, scored automatically when omitted)"
+        )]
         importance: Option<f64>,
     },
 
@@ -959,14 +1289,129 @@ pub enum MemoryAction {
         id: String,
     },
 
+    #[command(about = "Pin a memory so it is never decayed or evicted")]
+    Pin {
+        #[arg(help = "Memory ID")]
+        id: String,
+
+        #[arg(long, help = "Unpin instead")]
+        unpin: bool,
+    },
+
     #[command(about = "Show memory statistics")]
     Stats,
 
+    #[command(about = "Capture new lines from a notes file or stdin as memories")]
+    Watch {
+        #[arg(short, long, help = "Notes file to tail (reads stdin when omitted)")]
+        file: Option<String>,
+
+        #[arg(
+            short = 't',
+            long = "type",
+            help = "Memory type for every note (inferred when omitted)"
+        )]
+        memory_type: Option<String>,
+
+        #[arg(long = "tag", help = "Tag added to every note (repeatable)")]
+        tags: Vec<String>,
+
+        #[arg(long, help = "Ingest the existing file contents before tailing")]
+        from_start: bool,
+
+        #[arg(
+            long,
+            default_value_t = 500,
+            help = "Debounce interval in milliseconds"
+        )]
+        debounce_ms: u64,
+    },
+
+    #[command(about = "Remove memories below an importance threshold")]
+    Prune {
+        #[arg(long, help = "Remove memories less important than this (0.0-1.0)")]
+        below: f64,
+    },
+
+    #[command(about = "Remove duplicate memories, keeping the most important copy")]
+    Dedup,
+
     #[command(about = "Clear all memories")]
     Clear {
         #[arg(short, long, default_value_t = false, help = "Skip confirmation")]
         yes: bool,
     },
+
+    #[command(about = "Snapshot and restore the memory store")]
+    Snapshot {
+        #[command(subcommand)]
+        action: SnapshotAction,
+    },
+
+    #[command(about = "Export all memories to a JSONL file")]
+    Export {
+        #[arg(help = "Destination file")]
+        path: String,
+    },
+
+    #[command(about = "Import memories from a JSONL file")]
+    Import {
+        #[arg(help = "JSONL file written by `memory export`")]
+        path: String,
+
+        #[arg(
+            short,
+            long,
+            value_enum,
+            default_value_t = MergeStrategyArg::Skip,
+            help = "What to do when a memory with the same ID exists"
+        )]
+        strategy: MergeStrategyArg,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq, Default)]
+pub enum MergeStrategyArg {
+    #[default]
+    Skip,
+    Overwrite,
+    KeepNewer,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum SnapshotAction {
+    #[command(about = "Snapshot the current memories")]
+    Create {
+        #[arg(short, long, default_value = "manual", help = "Snapshot label")]
+        label: String,
+    },
+
+    #[command(about = "List snapshots, newest first")]
+    List,
+
+    #[command(about = "Replace the current memories with a snapshot")]
+    Restore {
+        #[arg(help = "Snapshot ID")]
+        id: String,
+    },
+
+    #[command(about = "Delete a snapshot")]
+    Delete {
+        #[arg(help = "Snapshot ID")]
+        id: String,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum AutoAction {
+    #[command(about = "Resume a checkpointed run from its last successful step")]
+    Resume {
+        #[arg(help = "Run ID (execution ID)")]
+        run_id: String,
+
+        #[arg(short, long, help = "Maximum total steps for the resumed run")]
+        max_steps: Option<usize>,
+    },
 }
 
 #[derive(Subcommand, Debug, Clone)]
@@ -981,6 +1426,32 @@ pub enum GitAction {
 
         #[arg(short, long, default_value_t = false, help = "Stage all changes")]
         all: bool,
+
+        #[arg(
+            long,
+            value_enum,
+            default_value_t = CommitStyleArg::Plain,
+            help = "Style of the generated message"
+        )]
+        style: CommitStyleArg,
+
+        #[arg(
+            long = "type",
+            value_parser = clap::builder::PossibleValuesParser::new(crate::git::COMMIT_TYPES),
+            help = "Conventional Commits type, required when it can't be inferred"
+        )]
+        commit_type: Option<String>,
+
+        #[arg(
+            short = 'S',
+            long,
+            default_value_t = false,
+            help = "GPG-sign the commit"
+        )]
+        sign: bool,
+
+        #[arg(long, help = "Key to sign with (implies --sign)")]
+        sign_key: Option<String>,
     },
 
     #[command(about = "Create a pull request")]
@@ -996,6 +1467,13 @@ pub enum GitAction {
     Diff {
         #[arg(short, long, default_value_t = false, help = "Show staged changes")]
         staged: bool,
+
+        #[arg(
+            long,
+            default_value_t = false,
+            help = "Plain output without colors (implied when piped)"
+        )]
+        no_color: bool,
     },
 
     #[command(about = "Show recent commits")]
@@ -1028,6 +1506,15 @@ pub enum GuardianAction {
         content: String,
     },
 
+    #[command(about = "Summarize logged hallucination checks for tuning the threshold")]
+    Stats {
+        #[arg(
+            long = "threshold",
+            help = "Candidate threshold to compare (repeatable; defaults to 0.50-0.90)"
+        )]
+        thresholds: Vec<f64>,
+    },
+
     #[command(about = "Execute command through guardian")]
     Execute {
         #[arg(help = "Command to execute")]
@@ -1044,6 +1531,27 @@ pub enum GuardianAction {
     },
 }
 
+#[derive(Subcommand, Debug, Clone)]
+pub enum ConfigAction {
+    #[command(about = "Manage the output theme")]
+    Theme {
+        #[command(subcommand)]
+        action: ThemeAction,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum ThemeAction {
+    #[command(about = "List built-in themes")]
+    List,
+
+    #[command(about = "Set the output theme")]
+    Set {
+        #[arg(help = "Theme name (default, no_color, ascii)")]
+        name: String,
+    },
+}
+
 #[derive(Subcommand, Debug, Clone)]
 pub enum DevilAction {
     #[command(about = "Execute prompt across all AI providers in parallel")]
@@ -1056,6 +1564,28 @@ pub enum DevilAction {
 
         #[arg(short, long, value_enum, default_value_t = SynthesisMethodArg::CrossVerification, help = "Synthesis method")]
         synthesis: SynthesisMethodArg,
+
+        #[arg(
+            long,
+            conflicts_with = "quorum",
+            help = "Return once this many providers have answered; cancel the rest"
+        )]
+        first: Option<usize>,
+
+        #[arg(
+            long,
+            help = "Return once this fraction (0.0-1.0) of providers has answered; cancel the rest"
+        )]
+        quorum: Option<f64>,
+    },
+
+    #[command(about = "Show each provider's full response side by side, without consensus")]
+    Compare {
+        #[arg(help = "Prompt to execute")]
+        prompt: String,
+
+        #[arg(short, long, default_value_t = 30, help = "Timeout in seconds")]
+        timeout: u64,
     },
 
     #[command(about = "Show devil mode status")]
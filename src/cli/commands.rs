@@ -3,145 +3,331 @@
 //! Handles execution of CLI commands
 
 use crate::cli::args::*;
+use crate::cli::error::CliError;
+use crate::cli::output::CommandOutput;
 use crate::config::SenaConfig;
 use crate::integration::AutoIntegration;
-use crate::metrics::SenaHealth;
-use crate::output::{FormatBox, ProgressBar, TableBuilder};
+use crate::metrics::{CheckStatus, SenaHealth};
+use crate::output::{CellOverflow, DiffRenderer, FormatBox, ProgressBar, TableBuilder};
 use crate::ProcessingRequest;
 use crate::SenaUnifiedSystem;
+use std::io::IsTerminal;
 use std::path::PathBuf;
 
 /// Execute a CLI command
-pub async fn execute_command(cli: &Cli) -> Result<String, String> {
+pub async fn execute_command(cli: &Cli) -> Result<String, CliError> {
+    Ok(dispatch_command(cli).await?.render(cli.format)?)
+}
+
+async fn dispatch_command(cli: &Cli) -> Result<CommandOutput, CliError> {
+    let format = cli.format.for_handler();
     match &cli.command {
-        Some(Commands::Mcp { debug }) => execute_mcp(*debug).await,
+        Some(Commands::Mcp { debug }) => execute_mcp(*debug)
+            .await
+            .map(CommandOutput::from)
+            .map_err(CliError::from),
 
         Some(Commands::Hook { hook_type, input }) => {
-            execute_hook(*hook_type, input.clone(), cli.format).await
+            execute_hook(*hook_type, input.clone(), format)
+                .await
+                .map(CommandOutput::from)
+                .map_err(CliError::from)
         }
 
         Some(Commands::Process {
             content,
             request_type,
-        }) => execute_process(content, request_type, cli.format).await,
+            diff,
+            trace,
+        }) => execute_process(
+            content,
+            request_type,
+            diff.as_deref(),
+            trace.as_deref(),
+            format,
+        )
+        .await
+        .map(CommandOutput::from)
+        .map_err(CliError::from),
+
+        Some(Commands::Health { detailed }) => execute_health(*detailed, format)
+            .map(CommandOutput::from)
+            .map_err(CliError::from),
+
+        Some(Commands::Features) => execute_features(format)
+            .map(CommandOutput::from)
+            .map_err(CliError::from),
+
+        Some(Commands::Selftest { threshold }) => execute_selftest(*threshold, format)
+            .map(CommandOutput::from)
+            .map_err(CliError::from),
+
+        #[cfg(feature = "metrics-server")]
+        Some(Commands::Metrics {
+            serve: Some(addr), ..
+        }) => crate::metrics::server::serve(addr)
+            .await
+            .map(CommandOutput::message)
+            .map_err(CliError::from),
+
+        Some(Commands::Metrics {
+            prometheus: true, ..
+        }) if format.is_structured() => Err(CliError::Usage(
+            "--prometheus cannot be combined with --format json or yaml".to_string(),
+        )),
 
-        Some(Commands::Health { detailed }) => execute_health(*detailed, cli.format),
+        Some(Commands::Metrics {
+            prometheus: true, ..
+        }) => Ok(CommandOutput::Text(
+            crate::metrics::SenaMetrics::to_prometheus(),
+        )),
 
-        Some(Commands::Metrics { category }) => execute_metrics(*category, cli.format),
+        Some(Commands::Metrics { category, .. }) => execute_metrics(*category, format)
+            .map(CommandOutput::from)
+            .map_err(CliError::from),
 
-        Some(Commands::Detect { text }) => execute_detect(text, cli.format),
+        Some(Commands::Detect { text }) => execute_detect(text, format)
+            .map(CommandOutput::from)
+            .map_err(CliError::from),
 
-        Some(Commands::Daemon { action }) => execute_daemon(*action).await,
+        Some(Commands::Daemon { action, foreground }) => execute_daemon(*action, *foreground)
+            .await
+            .map(CommandOutput::from)
+            .map_err(CliError::from),
 
         Some(Commands::Session { action, id, name }) => {
-            execute_session(*action, id.clone(), name.clone(), cli.format)
+            execute_session(*action, id.clone(), name.clone(), format)
+                .map(CommandOutput::from)
+                .map_err(CliError::from)
         }
 
-        Some(Commands::Validate { content, strict }) => {
-            execute_validate(content, *strict, cli.format)
-        }
+        Some(Commands::Validate { content, strict }) => execute_validate(content, *strict, format)
+            .map(CommandOutput::from)
+            .map_err(CliError::from),
 
         Some(Commands::Format {
             format_type,
             title,
+            max_width,
+            wrap,
             data,
-        }) => execute_format(*format_type, title.clone(), data, cli.format),
+        }) => execute_format(*format_type, title.clone(), *max_width, *wrap, data, format)
+            .map(CommandOutput::from),
 
         // Hub commands
-        Some(Commands::Hub { action }) => execute_hub(action.clone()).await,
+        Some(Commands::Hub { action }) => execute_hub(action.clone()).await.map_err(CliError::from),
 
-        Some(Commands::Join { role, name }) => execute_join(role, name.clone(), cli.format).await,
+        Some(Commands::Join { role, name }) => execute_join(role, name.clone())
+            .await
+            .map_err(CliError::from),
 
-        Some(Commands::Who) => execute_who(cli.format).await,
+        Some(Commands::Who) => execute_who().await.map_err(CliError::from),
 
-        Some(Commands::Tell { target, message }) => execute_tell(target, message, cli.format).await,
+        Some(Commands::Tell {
+            target,
+            message,
+            reply,
+        }) => execute_tell(target, message, reply.as_deref())
+            .await
+            .map_err(CliError::from),
 
-        Some(Commands::Inbox) => execute_inbox(cli.format).await,
+        Some(Commands::Inbox) => execute_inbox().await.map_err(CliError::from),
 
-        Some(Commands::Task { action }) => execute_task(action.clone(), cli.format).await,
+        Some(Commands::Task { action }) => {
+            execute_task(action.clone()).await.map_err(CliError::from)
+        }
 
-        Some(Commands::Watch) => execute_watch().await,
+        Some(Commands::Watch) => execute_watch()
+            .await
+            .map(CommandOutput::from)
+            .map_err(CliError::from),
 
-        Some(Commands::Sync) => execute_sync(cli.format).await,
+        Some(Commands::Sync) => execute_sync(format)
+            .await
+            .map(CommandOutput::from)
+            .map_err(CliError::from),
 
-        Some(Commands::Knowledge { action }) => execute_knowledge(action.clone(), cli.format).await,
+        Some(Commands::Knowledge { action }) => execute_knowledge(action.clone(), format)
+            .await
+            .map(CommandOutput::from)
+            .map_err(CliError::from),
 
-        Some(Commands::Think { query, depth }) => execute_think(query, *depth, cli.format).await,
+        Some(Commands::Think { query, depth }) => execute_think(query, *depth, format)
+            .await
+            .map(CommandOutput::from)
+            .map_err(CliError::from),
 
         Some(Commands::Agent {
             agent_type,
             content,
-        }) => execute_agent(*agent_type, content, cli.format).await,
+        }) => execute_agent(*agent_type, content, format)
+            .await
+            .map(CommandOutput::from)
+            .map_err(CliError::from),
+
+        Some(Commands::Skills { action }) => execute_skills(action.clone(), format)
+            .await
+            .map(CommandOutput::from)
+            .map_err(CliError::from),
 
-        Some(Commands::Evolve { action }) => execute_evolve(action.clone(), cli.format).await,
+        Some(Commands::Evolve { action }) => execute_evolve(action.clone(), format)
+            .await
+            .map(CommandOutput::from)
+            .map_err(CliError::from),
 
         Some(Commands::Feedback {
             feedback_type,
             message,
             context,
-        }) => execute_feedback(*feedback_type, message, context.clone(), cli.format).await,
-
-        Some(Commands::Backend { analysis, input }) => {
-            execute_backend(*analysis, input, cli.format).await
-        }
-
-        Some(Commands::Iot { analysis, input }) => execute_iot(*analysis, input, cli.format).await,
-
-        Some(Commands::Ios { analysis, input }) => execute_ios(*analysis, input, cli.format).await,
-
-        Some(Commands::Android { analysis, input }) => {
-            execute_android(*analysis, input, cli.format).await
-        }
-
-        Some(Commands::Web { analysis, input }) => execute_web(*analysis, input, cli.format).await,
+        }) => execute_feedback(*feedback_type, message, context.clone(), format)
+            .await
+            .map(CommandOutput::from)
+            .map_err(CliError::from),
+
+        Some(Commands::Backend {
+            analysis,
+            input,
+            no_cache,
+            project_context,
+        }) => execute_backend(*analysis, input, *no_cache, *project_context, format)
+            .await
+            .map(CommandOutput::from)
+            .map_err(CliError::from),
+
+        Some(Commands::Iot {
+            analysis,
+            input,
+            no_cache,
+        }) => execute_iot(*analysis, input, *no_cache, format)
+            .await
+            .map(CommandOutput::from)
+            .map_err(CliError::from),
+
+        Some(Commands::Ios {
+            analysis,
+            input,
+            no_cache,
+        }) => execute_ios(*analysis, input, *no_cache, format)
+            .await
+            .map(CommandOutput::from)
+            .map_err(CliError::from),
+
+        Some(Commands::Android {
+            analysis,
+            input,
+            no_cache,
+        }) => execute_android(*analysis, input, *no_cache, format)
+            .await
+            .map(CommandOutput::from)
+            .map_err(CliError::from),
+
+        Some(Commands::Web {
+            analysis,
+            input,
+            no_cache,
+        }) => execute_web(*analysis, input, *no_cache, format)
+            .await
+            .map(CommandOutput::from)
+            .map_err(CliError::from),
 
         Some(Commands::Setup {
             install_type,
             name,
             yes,
-        }) => execute_setup(*install_type, name.clone(), *yes, cli.format).await,
+        }) => execute_setup(*install_type, name.clone(), *yes, format)
+            .await
+            .map(CommandOutput::from),
+
+        Some(Commands::Network { action }) => execute_network(action.clone()).await,
 
-        Some(Commands::Network { action }) => execute_network(action.clone(), cli.format).await,
+        Some(Commands::Peer { action }) => execute_peer(action.clone()).await,
 
-        Some(Commands::Peer { action }) => execute_peer(action.clone(), cli.format).await,
+        Some(Commands::Discover { timeout }) => execute_discover(*timeout).await,
 
-        Some(Commands::Discover { timeout }) => execute_discover(*timeout, cli.format).await,
+        Some(Commands::Provider { action }) => execute_provider(action.clone(), format)
+            .await
+            .map(CommandOutput::from),
 
-        Some(Commands::Provider { action }) => execute_provider(action.clone(), cli.format).await,
+        Some(Commands::Collab { action }) => execute_collab(action.clone(), format)
+            .await
+            .map(CommandOutput::from),
 
-        Some(Commands::Collab { action }) => execute_collab(action.clone(), cli.format).await,
+        Some(Commands::Tools { action }) => execute_tools(action.clone(), format)
+            .await
+            .map(CommandOutput::from)
+            .map_err(CliError::from),
 
-        Some(Commands::Tools { action }) => execute_tools(action.clone(), cli.format).await,
+        Some(Commands::Memory { action }) => execute_memory(action.clone(), format)
+            .await
+            .map(CommandOutput::from)
+            .map_err(CliError::from),
 
-        Some(Commands::Memory { action }) => execute_memory(action.clone(), cli.format).await,
+        Some(Commands::Context { query, max_tokens }) => {
+            execute_context(query, *max_tokens, format)
+                .map(CommandOutput::from)
+                .map_err(CliError::from)
+        }
+
+        Some(Commands::Auto {
+            action: Some(AutoAction::Resume { run_id, max_steps }),
+            ..
+        }) => execute_auto_resume(run_id, *max_steps, format)
+            .await
+            .map(CommandOutput::from)
+            .map_err(CliError::from),
 
         Some(Commands::Auto {
+            action: None,
             task,
             max_steps,
             cwd,
             confirm,
-        }) => execute_auto(task, *max_steps, cwd.clone(), *confirm, cli.format).await,
+        }) => {
+            let task = task
+                .as_deref()
+                .ok_or_else(|| CliError::Usage("Task description is required".to_string()))?;
+            execute_auto(task, *max_steps, cwd.clone(), *confirm, format)
+                .await
+                .map(CommandOutput::from)
+                .map_err(CliError::from)
+        }
 
-        Some(Commands::Git { action }) => execute_git(action.clone(), cli.format).await,
+        Some(Commands::Git { action }) => execute_git(action.clone(), format)
+            .await
+            .map(CommandOutput::from)
+            .map_err(CliError::from),
 
-        Some(Commands::Guardian { action }) => execute_guardian(action.clone(), cli.format).await,
+        Some(Commands::Guardian { action }) => execute_guardian(action.clone(), format)
+            .await
+            .map(CommandOutput::from),
 
-        Some(Commands::Devil { action }) => execute_devil(action.clone(), cli.format).await,
+        Some(Commands::Devil { action }) => execute_devil(action.clone(), format)
+            .await
+            .map(CommandOutput::from),
 
-        None => execute_health(false, cli.format),
+        Some(Commands::Config { action }) => {
+            execute_config(action.clone(), format).map(CommandOutput::from)
+        }
+
+        None => execute_health(false, format)
+            .map(CommandOutput::from)
+            .map_err(CliError::from),
     }
 }
 
 async fn execute_mcp(debug: bool) -> Result<String, String> {
     if debug {
-        eprintln!(
+        log::debug!(
             "{} MCP Server starting in debug mode...",
             SenaConfig::brand()
         );
     }
 
-    // Start MCP server
-    crate::mcp::run_server().await
+    // Stdout belongs to the JSON-RPC channel, so nothing is printed after
+    // the server stops
+    let stopped = crate::mcp::run_server().await?;
+    log::info!("{}", stopped);
+    Ok(String::new())
 }
 
 async fn execute_hook(
@@ -177,15 +363,58 @@ async fn execute_hook(
 async fn execute_process(
     content: &str,
     request_type: &str,
+    diff: Option<&str>,
+    trace: Option<&str>,
     format: OutputFormat,
 ) -> Result<String, String> {
+    let prior: Option<crate::ProcessingResult> = match diff {
+        Some(path) => {
+            let json = std::fs::read_to_string(path)
+                .map_err(|e| format!("Cannot read {}: {}", path, e))?;
+            Some(
+                serde_json::from_str(&json)
+                    .map_err(|e| format!("{} is not a processing result: {}", path, e))?,
+            )
+        }
+        None => None,
+    };
+
     let mut system = SenaUnifiedSystem::new();
+    system.load().ok();
     let request = ProcessingRequest::new(content, request_type);
 
     let result = system.process(request).await;
 
+    if let Some(path) = trace {
+        let events = crate::trace::to_chrome_trace(&result.to_trace());
+        let json = serde_json::to_string_pretty(&events).map_err(|e| e.to_string())?;
+        std::fs::write(path, json).map_err(|e| format!("Cannot write {}: {}", path, e))?;
+    }
+
+    if let Some(prior) = prior {
+        let diff = prior.diff(&result);
+        return match format {
+            OutputFormat::Json | OutputFormat::Yaml => {
+                serde_json::to_string_pretty(&diff).map_err(|e| e.to_string())
+            }
+            OutputFormat::Pretty | OutputFormat::Text => {
+                let mut output = String::new();
+                if format == OutputFormat::Pretty {
+                    output.push_str(
+                        &FormatBox::new(&SenaConfig::brand_title("RESULT DIFF")).render(),
+                    );
+                    output.push('\n');
+                }
+                output.push_str(&diff.format());
+                Ok(output)
+            }
+        };
+    }
+
     match format {
-        OutputFormat::Json => serde_json::to_string_pretty(&result).map_err(|e| e.to_string()),
+        OutputFormat::Json | OutputFormat::Yaml => {
+            serde_json::to_string_pretty(&result).map_err(|e| e.to_string())
+        }
         OutputFormat::Pretty => {
             let mut output = String::new();
             output
@@ -217,12 +446,16 @@ async fn execute_process(
 }
 
 fn execute_health(detailed: bool, format: OutputFormat) -> Result<String, String> {
-    let health = SenaHealth::new();
+    let health = if detailed {
+        SenaHealth::new().with_default_checks()
+    } else {
+        SenaHealth::new()
+    };
     let report = health.get_health();
 
     match format {
         OutputFormat::Json => serde_json::to_string_pretty(&report).map_err(|e| e.to_string()),
-        OutputFormat::Pretty | OutputFormat::Text => {
+        _ => {
             let mut output = String::new();
 
             if detailed || format == OutputFormat::Pretty {
@@ -243,13 +476,174 @@ fn execute_health(detailed: bool, format: OutputFormat) -> Result<String, String
                 output.push_str(&format!("  Core: {}\n", report.metrics.core_components));
                 output.push_str(&format!("  Memory: {}\n", report.metrics.memory_system));
                 output.push_str(&format!("  Hooks: {}\n", report.metrics.hooks));
+
+                use crate::agents::Severity;
+
+                let theme = crate::output::Theme::current();
+                output.push_str("\nChecks:\n");
+                for check in &report.checks {
+                    let marker = match check.status {
+                        CheckStatus::Pass => Severity::Success,
+                        CheckStatus::Warn => Severity::Warning,
+                        CheckStatus::Fail => Severity::Critical,
+                    };
+                    output.push_str(&format!(
+                        "  {} {:<10} {}\n",
+                        theme.severity(marker),
+                        check.name,
+                        check.detail
+                    ));
+                }
+            } else if !report.status_reasons.is_empty() {
+                output.push_str("\nNeeds attention:\n");
+                for reason in &report.status_reasons {
+                    output.push_str(&format!("  - {}\n", reason));
+                }
+            }
+
+            Ok(output)
+        }
+    }
+}
+
+fn execute_features(format: OutputFormat) -> Result<String, String> {
+    use crate::output::Theme;
+    use sena_providers::{FeatureReport, ProvidersConfig};
+
+    let report = FeatureReport::new(&ProvidersConfig::load_or_default());
+    let theme = Theme::current();
+    let mark = |value: bool| theme.status(value);
+
+    match format {
+        OutputFormat::Json | OutputFormat::Yaml => {
+            serde_json::to_string_pretty(&report).map_err(|e| e.to_string())
+        }
+        OutputFormat::Text => {
+            let mut output = String::new();
+            for provider in &report.providers {
+                output.push_str(&format!(
+                    "{} compiled={} configured={}\n",
+                    provider.id, provider.compiled, provider.configured
+                ));
+            }
+            let available: Vec<&str> = report
+                .capabilities
+                .entries()
+                .into_iter()
+                .filter(|(_, available)| *available)
+                .map(|(name, _)| name)
+                .collect();
+            output.push_str(&format!("capabilities: {}\n", available.join(",")));
+            for hint in report.hints() {
+                output.push_str(&format!("hint: {}\n", hint));
+            }
+            Ok(output)
+        }
+        OutputFormat::Pretty => {
+            let mut table = TableBuilder::new().title("PROVIDER FEATURES").row(vec![
+                "Provider".to_string(),
+                "Compiled".to_string(),
+                "Configured".to_string(),
+                "Streaming".to_string(),
+                "Tools".to_string(),
+                "Vision".to_string(),
+            ]);
+            for provider in &report.providers {
+                let capability = |pick: fn(&sena_providers::CapabilitySet) -> bool| {
+                    provider
+                        .capabilities
+                        .as_ref()
+                        .map_or_else(|| "-".to_string(), |caps| mark(pick(caps)))
+                };
+                table = table.row(vec![
+                    provider.id.clone(),
+                    mark(provider.compiled),
+                    mark(provider.configured),
+                    capability(|caps| caps.streaming),
+                    capability(|caps| caps.tool_use),
+                    capability(|caps| caps.vision),
+                ]);
             }
 
+            let mut output = table.build();
+            output.push_str("\nAvailable across configured providers:\n");
+            for (name, available) in report.capabilities.entries() {
+                output.push_str(&format!("  {} {}\n", mark(available), name));
+            }
+            let hints = report.hints();
+            if !hints.is_empty() {
+                output.push('\n');
+                for hint in hints {
+                    output.push_str(&format!("  💡 {}\n", hint));
+                }
+            }
             Ok(output)
         }
     }
 }
 
+fn execute_selftest(threshold: f64, format: OutputFormat) -> Result<String, String> {
+    use crate::metrics::SelfTest;
+
+    let report = SelfTest::new()
+        .with_threshold(threshold)
+        .with_default_subsystems()
+        .run();
+
+    let output = match format {
+        OutputFormat::Json | OutputFormat::Yaml => {
+            serde_json::to_string_pretty(&report).map_err(|e| e.to_string())?
+        }
+        OutputFormat::Pretty | OutputFormat::Text => {
+            let mut output = String::new();
+            output.push_str(&FormatBox::new(&SenaConfig::brand_title("SELF TEST")).render());
+            output.push('\n');
+
+            for subsystem in &report.subsystems {
+                output.push_str(&format!(
+                    "\n{} {} - {:?} ({:.1}%)\n",
+                    if subsystem.passed { "✅" } else { "❌" },
+                    subsystem.name,
+                    subsystem.rating,
+                    subsystem.score * 100.0
+                ));
+                for check in &subsystem.checks {
+                    output.push_str(&format!(
+                        "   {} {}: {}\n",
+                        if check.passed { "✓" } else { "✗" },
+                        check.name,
+                        check.detail
+                    ));
+                }
+                if !subsystem.passed {
+                    for recommendation in &subsystem.recommendations {
+                        output.push_str(&format!("   💡 {}\n", recommendation));
+                    }
+                }
+            }
+
+            output.push_str(&format!(
+                "\nOverall: {:?} ({:.1}%), threshold {:.0}%\n",
+                report.overall_rating,
+                report.overall_score * 100.0,
+                report.threshold * 100.0
+            ));
+            output
+        }
+    };
+
+    if report.passed {
+        Ok(output)
+    } else {
+        let failing: Vec<&str> = report.failing().iter().map(|s| s.name.as_str()).collect();
+        Err(format!(
+            "{}\nSubsystems below threshold: {}",
+            output,
+            failing.join(", ")
+        ))
+    }
+}
+
 fn execute_metrics(
     category: Option<MetricCategory>,
     format: OutputFormat,
@@ -290,13 +684,13 @@ fn execute_detect(text: &str, format: OutputFormat) -> Result<String, String> {
     }
 }
 
-async fn execute_daemon(action: DaemonAction) -> Result<String, String> {
+async fn execute_daemon(action: DaemonAction, foreground: bool) -> Result<String, String> {
     match action {
-        DaemonAction::Start => crate::daemon::start_daemon().await,
+        DaemonAction::Start => crate::daemon::start_daemon(foreground).await,
         DaemonAction::Stop => crate::daemon::stop_daemon().await,
         DaemonAction::Restart => {
             crate::daemon::stop_daemon().await?;
-            crate::daemon::start_daemon().await
+            crate::daemon::start_daemon(foreground).await
         }
         DaemonAction::Status => crate::daemon::daemon_status().await,
     }
@@ -436,7 +830,9 @@ fn execute_validate(content: &str, strict: bool, format: OutputFormat) -> Result
     });
 
     match format {
-        OutputFormat::Json => serde_json::to_string_pretty(&output).map_err(|e| e.to_string()),
+        OutputFormat::Json | OutputFormat::Yaml => {
+            serde_json::to_string_pretty(&output).map_err(|e| e.to_string())
+        }
         OutputFormat::Pretty => {
             let mut out = String::new();
             out.push_str(&FormatBox::new(&SenaConfig::brand_title("VALIDATION RESULT")).render());
@@ -469,9 +865,11 @@ fn execute_validate(content: &str, strict: bool, format: OutputFormat) -> Result
 fn execute_format(
     format_type: FormatOutputType,
     title: Option<String>,
+    max_width: Option<usize>,
+    wrap: bool,
     data: &str,
     _format: OutputFormat,
-) -> Result<String, String> {
+) -> Result<String, CliError> {
     match format_type {
         FormatOutputType::Table => {
             // Parse data as JSON array
@@ -482,12 +880,18 @@ fn execute_format(
                     if let Some(t) = title {
                         builder = builder.title(&t);
                     }
+                    if let Some(width) = max_width {
+                        builder = builder.max_width(width);
+                    }
+                    if wrap {
+                        builder = builder.overflow(CellOverflow::Wrap);
+                    }
                     for row in rows {
                         builder = builder.row(row);
                     }
                     Ok(builder.build())
                 }
-                Err(e) => Err(format!("Invalid table data: {}", e)),
+                Err(e) => Err(CliError::Usage(format!("Invalid table data: {}", e))),
             }
         }
         FormatOutputType::Progress => {
@@ -501,7 +905,7 @@ fn execute_format(
                         .unwrap_or("Progress");
                     Ok(ProgressBar::new(label, percent as f32).render())
                 }
-                Err(e) => Err(format!("Invalid progress data: {}", e)),
+                Err(e) => Err(CliError::Usage(format!("Invalid progress data: {}", e))),
             }
         }
         FormatOutputType::BrilliantThinking => Ok(FormatBox::new(
@@ -523,27 +927,30 @@ fn execute_format(
 // Hub Command Implementations
 // ================================
 
-async fn execute_hub(action: HubAction) -> Result<String, String> {
+async fn execute_hub(action: HubAction) -> Result<CommandOutput, String> {
     use crate::hub::{Hub, HubConfig};
 
     match action {
         HubAction::Start => {
             let config = HubConfig::new();
             config.ensure_dirs()?;
-            Ok("Hub started. Use 'sena join --role=<role>' to join.".to_string())
+            Ok(CommandOutput::message(
+                "Hub started. Use 'sena join --role=<role>' to join.",
+            ))
         }
-        HubAction::Stop => Ok("Hub stopped.".to_string()),
+        HubAction::Stop => Ok(CommandOutput::message("Hub stopped.")),
         HubAction::Status => {
             let mut hub = Hub::new()?;
             hub.load()?;
             let status = hub.status();
-            Ok(format!(
+            let text = format!(
                 "Hub Status:\n  Sessions: {}\n  Tasks: {} ({} pending)\n  Conflicts: {}",
                 status.online_sessions,
                 status.total_tasks,
                 status.pending_tasks,
                 status.active_conflicts
-            ))
+            );
+            CommandOutput::data(&status, text)
         }
         HubAction::Sessions => {
             let mut hub = Hub::new()?;
@@ -551,8 +958,9 @@ async fn execute_hub(action: HubAction) -> Result<String, String> {
             let sessions = hub.who();
 
             if sessions.is_empty() {
-                return Ok(
-                    "No sessions online. Use 'sena join --role=<role>' to create one.".to_string(),
+                return CommandOutput::data(
+                    &sessions,
+                    "No sessions online. Use 'sena join --role=<role>' to create one.",
                 );
             }
 
@@ -576,9 +984,13 @@ async fn execute_hub(action: HubAction) -> Result<String, String> {
 
             output.push_str(&format!("Total: {} session(s)\n", sessions.len()));
             output.push_str("\nUse 'sena hub tell <name> <message>' to send a message.");
-            Ok(output)
+            CommandOutput::data(&sessions, output)
         }
-        HubAction::Tell { target, message } => {
+        HubAction::Tell {
+            target,
+            message,
+            reply,
+        } => {
             let mut hub = Hub::new()?;
             hub.load()?;
 
@@ -599,10 +1011,21 @@ async fn execute_hub(action: HubAction) -> Result<String, String> {
                 }
             })?;
 
-            hub.tell("hub", &resolved_target, &message)?;
+            match &reply {
+                Some(parent_id) => hub.reply("hub", &resolved_target, &message, parent_id, None)?,
+                None => hub.tell("hub", &resolved_target, &message)?,
+            }
             hub.save()?;
 
-            Ok(format!("📨 Hub → {}: {}", target, message))
+            CommandOutput::data(
+                &serde_json::json!({
+                    "target": target,
+                    "session_id": resolved_target,
+                    "message": message,
+                    "reply_to": reply,
+                }),
+                format!("📨 Hub → {}: {}", target, message),
+            )
         }
         HubAction::Broadcast { message } => {
             let mut hub = Hub::new()?;
@@ -612,10 +1035,10 @@ async fn execute_hub(action: HubAction) -> Result<String, String> {
             hub.save()?;
 
             let session_count = hub.who().len();
-            Ok(format!(
-                "📢 Broadcast to {} session(s): {}",
-                session_count, message
-            ))
+            CommandOutput::data(
+                &serde_json::json!({ "sessions": session_count, "message": message }),
+                format!("📢 Broadcast to {} session(s): {}", session_count, message),
+            )
         }
         HubAction::Messages { count } => {
             let mut hub = Hub::new()?;
@@ -624,7 +1047,7 @@ async fn execute_hub(action: HubAction) -> Result<String, String> {
             let messages = hub.messages.get_recent(count);
 
             if messages.is_empty() {
-                return Ok("No messages in Hub.".to_string());
+                return CommandOutput::data(&messages, "No messages in Hub.");
             }
 
             let mut output =
@@ -632,32 +1055,39 @@ async fn execute_hub(action: HubAction) -> Result<String, String> {
             output.push_str("║                    HUB MESSAGES                              ║\n");
             output.push_str("╚══════════════════════════════════════════════════════════════╝\n\n");
 
-            for msg in &messages {
+            for (depth, msg) in crate::hub::threaded(&messages) {
                 let direction = if msg.to == "all" {
                     format!("{} → ALL", msg.from)
                 } else {
                     format!("{} → {}", msg.from, msg.to)
                 };
+                let indent = "    ".repeat(depth);
+                let marker = if depth > 0 { "↳ " } else { "" };
                 output.push_str(&format!(
-                    "{} [{}] {}\n   {}\n\n",
+                    "{}{}{} [{}] {}\n{}   {}\n{}   id: {}\n\n",
+                    indent,
+                    marker,
                     msg.message_type.emoji(),
                     msg.time_display(),
                     direction,
-                    msg.content
+                    indent,
+                    msg.content,
+                    indent,
+                    msg.id
                 ));
             }
 
-            Ok(output)
+            CommandOutput::data(&messages, output)
         }
         HubAction::Conflicts => {
             let mut hub = Hub::new()?;
             hub.load()?;
             let conflicts = hub.get_conflicts();
             if conflicts.is_empty() {
-                Ok("No conflicts detected.".to_string())
+                CommandOutput::data(&conflicts, "No conflicts detected.")
             } else {
                 let mut output = String::from("Active Conflicts:\n");
-                for conflict in conflicts {
+                for conflict in &conflicts {
                     output.push_str(&format!(
                         "  {} {} - Sessions: {:?}\n",
                         conflict.severity.emoji(),
@@ -665,14 +1095,17 @@ async fn execute_hub(action: HubAction) -> Result<String, String> {
                         conflict.sessions
                     ));
                 }
-                Ok(output)
+                CommandOutput::data(&conflicts, output)
             }
         }
-        HubAction::Clear => {
-            let config = HubConfig::new();
-            let _ = std::fs::remove_dir_all(&config.hub_dir);
-            config.ensure_dirs()?;
-            Ok("Hub data cleared.".to_string())
+        HubAction::Clear { dry_run, yes } => {
+            if !dry_run && !yes {
+                return Err(
+                    "Use --yes to confirm clearing hub data, or --dry-run to preview".to_string(),
+                );
+            }
+            let summary = HubConfig::new().clear(dry_run)?;
+            CommandOutput::data(&summary, summary.display(20))
         }
         HubAction::Identity => {
             use crate::hub::HubIdentity;
@@ -692,7 +1125,7 @@ async fn execute_hub(action: HubAction) -> Result<String, String> {
             output.push_str(&format!("  Port:     {}\n", identity.port));
             output.push_str(&format!("  Version:  {}\n", identity.version));
             output.push_str("\nTo change hub name: sena hub set-name <new-name>");
-            Ok(output)
+            CommandOutput::data(&identity, output)
         }
         HubAction::SetName { name } => {
             use crate::hub::HubIdentity;
@@ -703,7 +1136,7 @@ async fn execute_hub(action: HubAction) -> Result<String, String> {
             identity.set_name(&name);
             identity.save(&identity_file)?;
 
-            Ok(format!("✅ Hub name changed to: {}", name))
+            CommandOutput::data(&identity, format!("✅ Hub name changed to: {}", name))
         }
         HubAction::Peers => {
             use crate::hub::{HubIdentity, PeerManager};
@@ -715,9 +1148,13 @@ async fn execute_hub(action: HubAction) -> Result<String, String> {
             let _ = peer_manager.load();
 
             let connected = peer_manager.get_connected_hubs();
+            let value: Vec<serde_json::Value> = connected
+                .iter()
+                .map(|hub| connected_hub_json(hub))
+                .collect();
 
             if connected.is_empty() {
-                return Ok("No connected hubs.\n\nTo connect to another hub:\n  sena hub connect <address:port>".to_string());
+                return CommandOutput::data(&value, "No connected hubs.\n\nTo connect to another hub:\n  sena hub connect <address:port> --passkey <passkey>");
             }
 
             let mut output =
@@ -743,7 +1180,7 @@ async fn execute_hub(action: HubAction) -> Result<String, String> {
                 ));
             }
             output.push_str(&format!("Total: {} hub(s)\n", connected.len()));
-            Ok(output)
+            CommandOutput::data(&value, output)
         }
         HubAction::Requests => {
             use crate::hub::{HubIdentity, PeerManager};
@@ -757,7 +1194,7 @@ async fn execute_hub(action: HubAction) -> Result<String, String> {
             let requests = peer_manager.get_pending_requests();
 
             if requests.is_empty() {
-                return Ok("No pending connection requests.".to_string());
+                return CommandOutput::data(&requests, "No pending connection requests.");
             }
 
             let mut output =
@@ -777,17 +1214,114 @@ async fn execute_hub(action: HubAction) -> Result<String, String> {
             }
             output.push_str("\nTo approve: sena hub approve <request-id>\n");
             output.push_str("To reject:  sena hub reject <request-id>");
-            Ok(output)
+            CommandOutput::data(&requests, output)
         }
         HubAction::Connect {
             address,
-            message: _,
-        } => Ok(format!(
-            "Connection request to {} would be sent.\n\
-                Note: Network connectivity requires the hub daemon to be running.\n\
-                Use 'sena network start' to start the network server.",
-            address
-        )),
+            passkey,
+            message,
+        } => {
+            use crate::hub::{HandshakeOutcome, HubIdentity, PeerManager};
+            use crate::network::protocol::DEFAULT_PORT;
+
+            let config = HubConfig::new();
+            let identity_file = config.hub_dir.join("identity.json");
+            let identity = HubIdentity::load_or_create(&identity_file)?;
+            let mut peer_manager = PeerManager::new(identity, &config.hub_dir);
+            let _ = peer_manager.load();
+
+            let (host, port) = match address.rsplit_once(':') {
+                Some((host, port)) => (
+                    host,
+                    port.parse::<u16>()
+                        .map_err(|_| format!("Invalid port in address: {}", address))?,
+                ),
+                None => (address.as_str(), DEFAULT_PORT),
+            };
+
+            match peer_manager
+                .initiate_connection_with_message(host, port, &passkey, message)
+                .map_err(|e| e.to_string())?
+            {
+                HandshakeOutcome::Pending { request_id, hub } => CommandOutput::data(
+                    &serde_json::json!({
+                        "status": "pending",
+                        "request_id": request_id,
+                        "hub": hub,
+                    }),
+                    format!(
+                        "📤 Connection request sent to: {}\n   Request ID: {}...\n\n\
+                         Once it is approved there (sena hub approve {}),\n\
+                         run this command again to finish connecting.",
+                        hub.name,
+                        request_id.get(..8).unwrap_or(&request_id),
+                        request_id.get(..8).unwrap_or(&request_id)
+                    ),
+                ),
+                HandshakeOutcome::Connected(hub) => CommandOutput::data(
+                    &serde_json::json!({
+                        "status": "connected",
+                        "hub": connected_hub_json(&hub),
+                    }),
+                    format!(
+                        "✅ Connected to: {} ({})\n   Hub is now trusted.",
+                        hub.name,
+                        hub.socket_address()
+                    ),
+                ),
+            }
+        }
+        HubAction::Listen { port } => {
+            use crate::hub::{HandshakeOutcome, HubIdentity, PeerManager};
+            use crate::network::NetworkConfig;
+            use std::sync::{Arc, Mutex};
+
+            let config = HubConfig::new();
+            let identity_file = config.hub_dir.join("identity.json");
+            let identity = HubIdentity::load_or_create(&identity_file)?;
+            let mut peer_manager = PeerManager::new(identity, &config.hub_dir)
+                .with_network_config(&NetworkConfig::default());
+            let _ = peer_manager.load();
+
+            let passkey = if peer_manager.has_passkey() {
+                None
+            } else {
+                Some(peer_manager.generate_passkey()?)
+            };
+            let port = port.unwrap_or(peer_manager.identity().port);
+            let listener = std::net::TcpListener::bind(("0.0.0.0", port))
+                .map_err(|e| format!("Cannot listen on port {}: {}", port, e))?;
+            eprintln!(
+                "Listening for hub connections on port {} (Ctrl+C to stop)",
+                port
+            );
+            match passkey {
+                Some(passkey) => eprintln!("Passkey: {}", passkey),
+                None => eprintln!("Using the passkey generated earlier"),
+            }
+
+            let peer_manager = Arc::new(Mutex::new(peer_manager));
+            let _heartbeat = PeerManager::spawn_heartbeat(Arc::clone(&peer_manager));
+
+            for stream in listener.incoming().flatten() {
+                let Ok(mut manager) = peer_manager.lock() else {
+                    break;
+                };
+                match manager.accept(stream) {
+                    Ok(Some(HandshakeOutcome::Pending { request_id, hub })) => eprintln!(
+                        "📥 Connection request from {} — approve with: sena hub approve {}",
+                        hub.name,
+                        request_id.get(..8).unwrap_or(&request_id)
+                    ),
+                    Ok(Some(HandshakeOutcome::Connected(hub))) => {
+                        eprintln!("✅ {} completed its connection", hub.name)
+                    }
+                    Ok(None) => {}
+                    Err(e) => eprintln!("⚠️  {}", e),
+                }
+            }
+            Ok(CommandOutput::message("Listener stopped."))
+        }
         HubAction::Approve { request_id } => {
             use crate::hub::{HubIdentity, PeerManager};
 
@@ -806,10 +1340,16 @@ async fn execute_hub(action: HubAction) -> Result<String, String> {
             match matched_id {
                 Some(req_id) => {
                     let hub = peer_manager.approve_request(&req_id)?;
-                    Ok(format!(
-                        "✅ Connection approved for: {}\n   Hub is now trusted.",
-                        hub.name
-                    ))
+                    CommandOutput::data(
+                        &serde_json::json!({
+                            "request_id": req_id,
+                            "hub": connected_hub_json(&hub),
+                        }),
+                        format!(
+                            "✅ Connection approved for: {}\n   Hub is now trusted.",
+                            hub.name
+                        ),
+                    )
                 }
                 None => Err(format!("Request not found: {}", request_id)),
             }
@@ -832,7 +1372,10 @@ async fn execute_hub(action: HubAction) -> Result<String, String> {
             match matched_id {
                 Some(req_id) => {
                     peer_manager.reject_request(&req_id)?;
-                    Ok("❌ Connection request rejected.".to_string())
+                    CommandOutput::data(
+                        &serde_json::json!({ "request_id": req_id, "rejected": true }),
+                        "❌ Connection request rejected.",
+                    )
                 }
                 None => Err(format!("Request not found: {}", request_id)),
             }
@@ -848,11 +1391,17 @@ async fn execute_hub(action: HubAction) -> Result<String, String> {
 
             if let Some(connected_hub) = peer_manager.get_hub_by_name(&hub) {
                 peer_manager.disconnect_hub(&connected_hub.hub_id.clone())?;
-                Ok(format!("✅ Disconnected from: {}", hub))
+                CommandOutput::data(
+                    &serde_json::json!({ "disconnected": hub }),
+                    format!("✅ Disconnected from: {}", hub),
+                )
             } else if let Some(connected_hub) = peer_manager.get_connected_hub(&hub) {
                 let name = connected_hub.name.clone();
                 peer_manager.disconnect_hub(&hub)?;
-                Ok(format!("✅ Disconnected from: {}", name))
+                CommandOutput::data(
+                    &serde_json::json!({ "disconnected": name }),
+                    format!("✅ Disconnected from: {}", name),
+                )
             } else {
                 Err(format!("Hub not found: {}", hub))
             }
@@ -873,7 +1422,7 @@ async fn execute_hub(action: HubAction) -> Result<String, String> {
             let all_sessions = peer_manager.get_all_sessions(&local_sessions);
 
             if all_sessions.is_empty() {
-                return Ok("No sessions found (local or remote).".to_string());
+                return CommandOutput::data(&all_sessions, "No sessions found (local or remote).");
             }
 
             let mut output =
@@ -909,7 +1458,7 @@ async fn execute_hub(action: HubAction) -> Result<String, String> {
             output.push_str(
                 "\nUse 'sena hub tell HubName:SessionName <message>' to send cross-hub message.",
             );
-            Ok(output)
+            CommandOutput::data(&all_sessions, output)
         }
         HubAction::RemoveSession { session, force } => {
             let mut hub = Hub::new()?;
@@ -928,24 +1477,35 @@ async fn execute_hub(action: HubAction) -> Result<String, String> {
             }
 
             if !force {
-                return Ok(format!(
-                    "Session '{}' will be removed. Use --force to confirm removal.\n\
-                     This will:\n  - Remove session from registry\n  - Delete session's message inbox",
-                    session
-                ));
+                return CommandOutput::data(
+                    &serde_json::json!({ "session": session, "removed": false }),
+                    format!(
+                        "Session '{}' will be removed. Use --force to confirm removal.\n\
+                         This will:\n  - Remove session from registry\n  - Delete session's message inbox",
+                        session
+                    ),
+                );
             }
 
             let removed_session = hub.sessions.remove_session(&session)?;
             let messages_removed = hub.messages.remove_session_messages(&removed_session.id);
             hub.save()?;
 
-            Ok(format!(
+            let text = format!(
                 "Session removed successfully:\n  ID: {}\n  Name: {}\n  Role: {}\n  Messages removed: {}",
                 removed_session.id,
                 removed_session.name,
                 removed_session.role.name(),
                 messages_removed
-            ))
+            );
+            CommandOutput::data(
+                &serde_json::json!({
+                    "session": removed_session,
+                    "removed": true,
+                    "messages_removed": messages_removed,
+                }),
+                text,
+            )
         }
         HubAction::Cleanup { messages } => {
             let mut hub = Hub::new()?;
@@ -963,8 +1523,12 @@ async fn execute_hub(action: HubAction) -> Result<String, String> {
 
             hub.save()?;
 
+            let value = serde_json::json!({
+                "sessions_removed": removed_sessions,
+                "messages_removed": messages_removed,
+            });
             if session_count == 0 {
-                return Ok("No stale sessions found to clean up.".to_string());
+                return CommandOutput::data(&value, "No stale sessions found to clean up.");
             }
 
             let mut output = format!("Cleanup complete:\n  Sessions removed: {}\n", session_count);
@@ -972,19 +1536,47 @@ async fn execute_hub(action: HubAction) -> Result<String, String> {
                 output.push_str(&format!("  Messages removed: {}\n", messages_removed));
             }
             output.push_str("\nRemoved sessions:\n");
-            for session_id in removed_sessions {
+            for session_id in &removed_sessions {
                 output.push_str(&format!("  - {}\n", session_id));
             }
-            Ok(output)
+            CommandOutput::data(&value, output)
+        }
+        HubAction::Graph { format } => {
+            let mut hub = Hub::new()?;
+            hub.load()?;
+            let graph = hub.graph();
+
+            let (name, rendered) = match format {
+                GraphFormat::Dot => ("dot", graph.to_dot("sena_hub")),
+                GraphFormat::Ascii if graph.get_all_nodes().is_empty() => (
+                    "ascii",
+                    "Hub graph is empty. No sessions or tasks yet.".to_string(),
+                ),
+                GraphFormat::Ascii => ("ascii", graph.to_ascii()),
+            };
+            CommandOutput::data(
+                &serde_json::json!({ "format": name, "graph": rendered }),
+                rendered,
+            )
         }
     }
 }
 
-async fn execute_join(
-    role: &str,
-    name: Option<String>,
-    format: OutputFormat,
-) -> Result<String, String> {
+/// A connected hub without its auth token
+fn connected_hub_json(hub: &crate::hub::ConnectedHub) -> serde_json::Value {
+    serde_json::json!({
+        "hub_id": hub.hub_id,
+        "name": hub.name,
+        "address": hub.address,
+        "port": hub.port,
+        "session_count": hub.session_count,
+        "online": hub.is_online(),
+        "connected_at": hub.connected_at,
+        "last_seen": hub.last_seen,
+    })
+}
+
+async fn execute_join(role: &str, name: Option<String>) -> Result<CommandOutput, String> {
     use crate::hub::{Hub, SessionRole};
 
     let mut hub = Hub::new()?;
@@ -1011,19 +1603,19 @@ async fn execute_join(
         "terminal": hub.context.load_current_context().map(|c| c.terminal_id),
     });
 
-    match format {
-        OutputFormat::Json => serde_json::to_string_pretty(&result).map_err(|e| e.to_string()),
-        _ => Ok(format!(
+    CommandOutput::data(
+        &result,
+        format!(
             "{} Joined Hub!\n  Session: {}\n  Name: {}\n  Role: {}\n\nYou can now use 'sena tell <target> <message>' and 'sena inbox'",
             session.role.emoji(),
             session.id,
             session.name,
             session.role.name()
-        )),
-    }
+        ),
+    )
 }
 
-async fn execute_who(format: OutputFormat) -> Result<String, String> {
+async fn execute_who() -> Result<CommandOutput, String> {
     use crate::hub::Hub;
 
     let mut hub = Hub::new()?;
@@ -1032,52 +1624,53 @@ async fn execute_who(format: OutputFormat) -> Result<String, String> {
     let sessions = hub.who();
     let current_session_id = hub.get_current_session_id();
 
+    let json: Vec<serde_json::Value> = sessions
+        .iter()
+        .map(|s| {
+            serde_json::json!({
+                "id": s.id,
+                "role": s.role.name(),
+                "name": s.name,
+                "status": format!("{:?}", s.status),
+                "working_on": s.working_on,
+                "idle": s.idle_display(),
+                "is_current": current_session_id.as_ref() == Some(&s.id),
+            })
+        })
+        .collect();
+
     if sessions.is_empty() {
-        return Ok("No sessions online. Use 'sena join --role=<role>' to join.".to_string());
+        return CommandOutput::data(
+            &json,
+            "No sessions online. Use 'sena join --role=<role>' to join.",
+        );
     }
 
-    match format {
-        OutputFormat::Json => {
-            let json: Vec<serde_json::Value> = sessions
-                .iter()
-                .map(|s| {
-                    serde_json::json!({
-                        "id": s.id,
-                        "role": s.role.name(),
-                        "name": s.name,
-                        "status": format!("{:?}", s.status),
-                        "working_on": s.working_on,
-                        "idle": s.idle_display(),
-                        "is_current": current_session_id.as_ref() == Some(&s.id),
-                    })
-                })
-                .collect();
-            serde_json::to_string_pretty(&json).map_err(|e| e.to_string())
-        }
-        _ => {
-            let mut output = String::from("Sessions Online:\n");
-            for session in sessions {
-                let current_marker = if current_session_id.as_ref() == Some(&session.id) {
-                    " (you)"
-                } else {
-                    ""
-                };
-                output.push_str(&format!(
-                    "  {} {} │ {} │ {} │ {}{}\n",
-                    session.role.emoji(),
-                    session.name,
-                    session.status.indicator(),
-                    session.working_on.as_deref().unwrap_or("-"),
-                    session.idle_display(),
-                    current_marker
-                ));
-            }
-            Ok(output)
-        }
+    let mut output = String::from("Sessions Online:\n");
+    for session in sessions {
+        let current_marker = if current_session_id.as_ref() == Some(&session.id) {
+            " (you)"
+        } else {
+            ""
+        };
+        output.push_str(&format!(
+            "  {} {} │ {} │ {} │ {}{}\n",
+            session.role.emoji(),
+            session.name,
+            session.status.indicator(),
+            session.working_on.as_deref().unwrap_or("-"),
+            session.idle_display(),
+            current_marker
+        ));
     }
+    CommandOutput::data(&json, output)
 }
 
-async fn execute_tell(target: &str, message: &str, format: OutputFormat) -> Result<String, String> {
+async fn execute_tell(
+    target: &str,
+    message: &str,
+    reply: Option<&str>,
+) -> Result<CommandOutput, String> {
     use crate::hub::Hub;
 
     let mut hub = Hub::new()?;
@@ -1105,23 +1698,33 @@ async fn execute_tell(target: &str, message: &str, format: OutputFormat) -> Resu
         }
     })?;
 
-    hub.tell(&sender_id, &resolved_target, message)?;
+    match reply {
+        Some(parent_id) => hub.reply(&sender_id, &resolved_target, message, parent_id, None)?,
+        None => hub.tell(&sender_id, &resolved_target, message)?,
+    }
     hub.save()?;
 
-    match format {
-        OutputFormat::Json => Ok(serde_json::json!({
+    let text = match reply {
+        Some(parent_id) => format!(
+            "Reply to {} sent to {} from {}",
+            parent_id, target, sender_id
+        ),
+        None => format!("Message sent to {} from {}", target, sender_id),
+    };
+    CommandOutput::data(
+        &serde_json::json!({
             "sent": true,
             "from": sender_id,
             "to": resolved_target,
             "target_input": target,
-            "message": message
-        })
-        .to_string()),
-        _ => Ok(format!("Message sent to {} from {}", target, sender_id)),
-    }
+            "message": message,
+            "reply_to": reply,
+        }),
+        text,
+    )
 }
 
-async fn execute_inbox(format: OutputFormat) -> Result<String, String> {
+async fn execute_inbox() -> Result<CommandOutput, String> {
     use crate::hub::Hub;
 
     let mut hub = Hub::new()?;
@@ -1132,45 +1735,74 @@ async fn execute_inbox(format: OutputFormat) -> Result<String, String> {
         .ok_or_else(|| "No active session. Use 'sena join --role=<role>' first.".to_string())?;
 
     let messages = hub.inbox(&session_id);
+    let json: Vec<serde_json::Value> = messages
+        .iter()
+        .map(|m| {
+            serde_json::json!({
+                "id": m.id,
+                "from": m.from,
+                "to": m.to,
+                "content": m.content,
+                "time": m.time_display(),
+                "read": m.read,
+                "reply_to": m.reply_to,
+            })
+        })
+        .collect();
 
     if messages.is_empty() {
-        return Ok(format!("No messages for session {}.", session_id));
+        return CommandOutput::data(&json, format!("No messages for session {}.", session_id));
     }
 
-    match format {
-        OutputFormat::Json => {
-            let json: Vec<serde_json::Value> = messages
-                .iter()
-                .map(|m| {
-                    serde_json::json!({
-                        "from": m.from,
-                        "to": m.to,
-                        "content": m.content,
-                        "time": m.time_display(),
-                        "read": m.read,
-                    })
-                })
-                .collect();
-            serde_json::to_string_pretty(&json).map_err(|e| e.to_string())
+    let mut output = format!("Inbox for {}:\n", session_id);
+    for msg in messages {
+        let reply = msg
+            .reply_to
+            .as_ref()
+            .map(|id| format!(" ↩ {}", id))
+            .unwrap_or_default();
+        output.push_str(&format!(
+            "  {} [{}] {}: {}{}\n     id: {}\n",
+            msg.message_type.emoji(),
+            msg.time_display(),
+            msg.from,
+            msg.content,
+            reply,
+            msg.id
+        ));
+    }
+    CommandOutput::data(&json, output)
+}
+
+async fn execute_task(action: TaskAction) -> Result<CommandOutput, String> {
+    use crate::hub::{Hub, Task, TaskPriority, TaskStatus};
+
+    fn task_json(task: &Task) -> serde_json::Value {
+        serde_json::json!({
+            "id": task.id,
+            "title": task.title,
+            "assignee": task.assignee,
+            "priority": task.priority.name(),
+            "status": task.status.name(),
+        })
+    }
+
+    fn task_list(
+        tasks: &[Task],
+        empty: String,
+        heading: String,
+        line: fn(&Task) -> String,
+    ) -> Result<CommandOutput, String> {
+        let json: Vec<serde_json::Value> = tasks.iter().map(task_json).collect();
+        if tasks.is_empty() {
+            return CommandOutput::data(&json, empty);
         }
-        _ => {
-            let mut output = format!("Inbox for {}:\n", session_id);
-            for msg in messages {
-                output.push_str(&format!(
-                    "  {} [{}] {}: {}\n",
-                    msg.message_type.emoji(),
-                    msg.time_display(),
-                    msg.from,
-                    msg.content
-                ));
-            }
-            Ok(output)
+        let mut output = heading;
+        for task in tasks {
+            output.push_str(&format!("  {}\n", line(task)));
         }
+        CommandOutput::data(&json, output)
     }
-}
-
-async fn execute_task(action: TaskAction, format: OutputFormat) -> Result<String, String> {
-    use crate::hub::{Hub, TaskPriority, TaskStatus};
 
     let mut hub = Hub::new()?;
     hub.load()?;
@@ -1189,19 +1821,18 @@ async fn execute_task(action: TaskAction, format: OutputFormat) -> Result<String
             let task = hub.create_task(&title, &resolved_to, prio)?;
             hub.save()?;
 
-            match format {
-                OutputFormat::Json => serde_json::to_string_pretty(&serde_json::json!({
+            CommandOutput::data(
+                &serde_json::json!({
                     "created": true,
                     "id": task.id,
                     "title": task.title,
                     "assignee": task.assignee,
-                }))
-                .map_err(|e| e.to_string()),
-                _ => Ok(format!(
+                }),
+                format!(
                     "Task #{} created: {} (assigned to {})",
                     task.id, task.title, task.assignee
-                )),
-            }
+                ),
+            )
         }
         TaskAction::List { status } => {
             let tasks = if let Some(s) = status {
@@ -1211,42 +1842,22 @@ async fn execute_task(action: TaskAction, format: OutputFormat) -> Result<String
                 hub.get_tasks()
             };
 
-            if tasks.is_empty() {
-                return Ok("No tasks.".to_string());
-            }
-
-            match format {
-                OutputFormat::Json => {
-                    let json: Vec<serde_json::Value> = tasks
-                        .iter()
-                        .map(|t| {
-                            serde_json::json!({
-                                "id": t.id,
-                                "title": t.title,
-                                "assignee": t.assignee,
-                                "priority": t.priority.name(),
-                                "status": t.status.name(),
-                            })
-                        })
-                        .collect();
-                    serde_json::to_string_pretty(&json).map_err(|e| e.to_string())
-                }
-                _ => {
-                    let mut output = String::from("Tasks:\n");
-                    for task in tasks {
-                        output.push_str(&format!(
-                            "  #{} │ {} {} │ {} │ {} │ {}\n",
-                            task.id,
-                            task.priority.emoji(),
-                            task.priority.name(),
-                            task.assignee,
-                            task.title,
-                            task.status.name()
-                        ));
-                    }
-                    Ok(output)
-                }
-            }
+            task_list(
+                &tasks,
+                "No tasks.".to_string(),
+                "Tasks:\n".to_string(),
+                |task| {
+                    format!(
+                        "#{} │ {} {} │ {} │ {} │ {}",
+                        task.id,
+                        task.priority.emoji(),
+                        task.priority.name(),
+                        task.assignee,
+                        task.title,
+                        task.status.name()
+                    )
+                },
+            )
         }
         TaskAction::Mine => {
             let session_id = hub.get_current_session_id().ok_or_else(|| {
@@ -1254,43 +1865,90 @@ async fn execute_task(action: TaskAction, format: OutputFormat) -> Result<String
             })?;
 
             let tasks = hub.get_my_tasks(&session_id);
-
-            if tasks.is_empty() {
-                return Ok(format!("No tasks assigned to {}.", session_id));
-            }
-
-            let mut output = format!("Tasks for {}:\n", session_id);
-            for task in tasks {
-                output.push_str(&format!(
-                    "  #{} │ {} │ {} │ {}\n",
-                    task.id,
-                    task.priority.emoji(),
-                    task.title,
-                    task.status.name()
-                ));
-            }
-            Ok(output)
+            task_list(
+                &tasks,
+                format!("No tasks assigned to {}.", session_id),
+                format!("Tasks for {}:\n", session_id),
+                |task| {
+                    format!(
+                        "#{} │ {} │ {} │ {}",
+                        task.id,
+                        task.priority.emoji(),
+                        task.title,
+                        task.status.name()
+                    )
+                },
+            )
+        }
+        TaskAction::Ready => {
+            let tasks = hub.tasks.ready_tasks();
+            task_list(
+                &tasks,
+                "No tasks ready to start.".to_string(),
+                "Ready to start:\n".to_string(),
+                Task::display_line,
+            )
         }
         TaskAction::Done { id } => {
             hub.update_task(id, TaskStatus::Done)?;
             hub.save()?;
-            Ok(format!("Task #{} marked as done.", id))
+            CommandOutput::data(
+                &serde_json::json!({ "id": id, "status": TaskStatus::Done.name() }),
+                format!("Task #{} marked as done.", id),
+            )
         }
         TaskAction::Update { id, status } => {
             let task_status = TaskStatus::parse(&status);
+            let unfinished = hub.tasks.unfinished_dependencies(id);
             hub.update_task(id, task_status)?;
             hub.save()?;
-            Ok(format!("Task #{} updated to {}.", id, task_status.name()))
+            let waiting_on = if task_status == TaskStatus::InProgress {
+                unfinished
+            } else {
+                Vec::new()
+            };
+            let mut output = format!("Task #{} updated to {}.", id, task_status.name());
+            if !waiting_on.is_empty() {
+                output.push_str(&format!(
+                    "\nWarning: still waiting on {}.",
+                    waiting_on
+                        .iter()
+                        .map(|dep| format!("#{}", dep))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ));
+            }
+            CommandOutput::data(
+                &serde_json::json!({
+                    "id": id,
+                    "status": task_status.name(),
+                    "waiting_on": waiting_on,
+                }),
+                output,
+            )
         }
         TaskAction::Assign { id, to } => {
             hub.tasks.reassign(id, &to)?;
             hub.save()?;
-            Ok(format!("Task #{} reassigned to {}.", id, to))
+            CommandOutput::data(
+                &serde_json::json!({ "id": id, "assignee": to }),
+                format!("Task #{} reassigned to {}.", id, to),
+            )
         }
         TaskAction::Delete { id } => {
             hub.tasks.delete(id)?;
             hub.save()?;
-            Ok(format!("Task #{} deleted.", id))
+            CommandOutput::data(
+                &serde_json::json!({ "id": id, "deleted": true }),
+                format!("Task #{} deleted.", id),
+            )
+        }
+        TaskAction::Depend { id, on } => {
+            hub.tasks.add_dependency(id, on)?;
+            CommandOutput::data(
+                &serde_json::json!({ "id": id, "depends_on": on }),
+                format!("Task #{} now depends on task #{}.", id, on),
+            )
         }
     }
 }
@@ -1430,9 +2088,9 @@ async fn execute_knowledge(
     action: KnowledgeAction,
     format: OutputFormat,
 ) -> Result<String, String> {
-    use crate::knowledge::KnowledgeSystem;
+    use crate::knowledge::{imported_dataset_path, KnowledgeSystem, OwaspDataset};
 
-    let knowledge = KnowledgeSystem::new();
+    let mut knowledge = KnowledgeSystem::new();
 
     match action {
         KnowledgeAction::Search { query, limit } => {
@@ -1440,7 +2098,7 @@ async fn execute_knowledge(
             results.truncate(limit);
 
             match format {
-                OutputFormat::Json => {
+                OutputFormat::Json | OutputFormat::Yaml => {
                     let json: Vec<serde_json::Value> = results
                         .iter()
                         .map(|r| {
@@ -1513,7 +2171,7 @@ async fn execute_knowledge(
             };
 
             match format {
-                OutputFormat::Json => {
+                OutputFormat::Json | OutputFormat::Yaml => {
                     serde_json::to_string_pretty(&patterns).map_err(|e| e.to_string())
                 }
                 OutputFormat::Pretty => {
@@ -1576,6 +2234,70 @@ async fn execute_knowledge(
                 }
             }
         }
+        KnowledgeAction::ImportOwasp { file } => {
+            let dataset = match file {
+                Some(path) => OwaspDataset::load(std::path::Path::new(&path))?,
+                None => OwaspDataset::bundled()?,
+            };
+            let report = knowledge.import_owasp(&dataset);
+            dataset.save(&imported_dataset_path())?;
+
+            match format {
+                OutputFormat::Json => {
+                    serde_json::to_string_pretty(&report).map_err(|e| e.to_string())
+                }
+                _ => {
+                    let mut output = String::new();
+                    output.push_str(
+                        &FormatBox::new(&SenaConfig::brand_title("OWASP IMPORT")).render(),
+                    );
+                    output.push('\n');
+                    output.push_str(&format!(
+                        "Dataset: {} ({})\n",
+                        report.dataset, report.version
+                    ));
+                    output.push_str(&format!("Added: {}\n", report.added));
+                    output.push_str(&format!("Updated: {}\n", report.updated));
+                    output.push_str(&format!("Removed: {}\n", report.removed));
+                    output.push_str(&format!(
+                        "Security Patterns: {}\n",
+                        knowledge.stats.security_count
+                    ));
+                    Ok(output)
+                }
+            }
+        }
+        KnowledgeAction::Lint { path, fail_on } => {
+            use crate::agents::Severity;
+
+            let report = crate::knowledge::lint_path(std::path::Path::new(&path))?;
+            let output = match format {
+                OutputFormat::Json | OutputFormat::Yaml => {
+                    serde_json::to_string_pretty(&report).map_err(|e| e.to_string())?
+                }
+                OutputFormat::Pretty => {
+                    let mut output =
+                        FormatBox::new(&SenaConfig::brand_title("KNOWLEDGE LINT")).render();
+                    output.push('\n');
+                    output.push_str(&report.format());
+                    output
+                }
+                OutputFormat::Text => report.format(),
+            };
+
+            let threshold = fail_on.map(|level| match level {
+                LintSeverityArg::Critical => Severity::Critical,
+                LintSeverityArg::Warning => Severity::Warning,
+                LintSeverityArg::Info => Severity::Info,
+            });
+            match threshold {
+                Some(threshold) if report.fails_on(threshold) => Err(format!(
+                    "{}\nLint failed: findings at or above {:?}",
+                    output, threshold
+                )),
+                _ => Ok(output),
+            }
+        }
     }
 }
 
@@ -1590,7 +2312,7 @@ async fn execute_think(
 ) -> Result<String, String> {
     use crate::intelligence::{IntelligenceSystem, ThinkingDepth};
 
-    let intelligence = IntelligenceSystem::new();
+    let intelligence = IntelligenceSystem::configured();
 
     let thinking_depth = match depth {
         ThinkingDepthArg::Quick => ThinkingDepth::Quick,
@@ -1600,30 +2322,36 @@ async fn execute_think(
     };
 
     let result = intelligence.analyze(query, thinking_depth);
+    let route = sena_providers::ProviderRouter::from_config(
+        &sena_providers::ProvidersConfig::load_or_default(),
+    )
+    .ok()
+    .and_then(|router| intelligence.route_to_provider(query, &router));
 
     match format {
-        OutputFormat::Json => serde_json::to_string_pretty(&serde_json::json!({
-            "query": query,
-            "depth": format!("{:?}", depth),
-            "problem": result.problem,
-            "conclusion": result.conclusion,
-            "confidence": result.confidence,
-            "frameworks_used": result.frameworks_used,
-            "steps": result.steps.iter().map(|s| {
-                serde_json::json!({
-                    "name": s.name,
-                    "description": s.description,
-                    "output": s.output,
-                })
-            }).collect::<Vec<_>>(),
-            "thinking_time_ms": result.thinking_time_ms,
-        }))
-        .map_err(|e| e.to_string()),
+        OutputFormat::Json | OutputFormat::Yaml => {
+            serde_json::to_string_pretty(&serde_json::json!({
+                "query": query,
+                "depth": format!("{:?}", depth),
+                "route": route,
+                "problem": result.problem,
+                "conclusion": result.conclusion,
+                "confidence": result.confidence,
+                "framework": result.framework,
+                "frameworks_used": result.frameworks_used,
+                "steps": result.steps,
+                "thinking_time_ms": result.thinking_time_ms,
+            }))
+            .map_err(|e| e.to_string())
+        }
         OutputFormat::Pretty => {
             let mut output = String::new();
             output
                 .push_str(&FormatBox::new(&SenaConfig::brand_title("EXTENDED THINKING")).render());
             output.push_str(&format!("\nDepth: {:?}\n", depth));
+            if let Some(framework) = &result.framework {
+                output.push_str(&format!("Framework: {}\n", framework));
+            }
             output.push_str(&format!(
                 "Confidence: {:.1}%\n\n",
                 result.confidence * 100.0
@@ -1640,10 +2368,12 @@ async fn execute_think(
             output.push_str("═══════════════════════════════════════════\n\n");
             for (i, step) in result.steps.iter().enumerate() {
                 output.push_str(&format!(
-                    "{}. **{}**\n   {}\n\n",
+                    "{}. **{}**\n   Q: {}\n   H: {}\n   → {}\n\n",
                     i + 1,
                     step.name,
-                    step.description
+                    step.question,
+                    step.hypothesis,
+                    step.conclusion
                 ));
             }
 
@@ -1659,6 +2389,14 @@ async fn execute_think(
             output.push_str("  CONCLUSION\n");
             output.push_str("═══════════════════════════════════════════\n\n");
             output.push_str(&format!("  {}\n", result.conclusion));
+
+            output.push_str("\n═══════════════════════════════════════════\n");
+            output.push_str("  ROUTING\n");
+            output.push_str("═══════════════════════════════════════════\n\n");
+            match &route {
+                Some(route) => output.push_str(&format!("{}\n", route.format())),
+                None => output.push_str("No provider configured\n"),
+            }
             output.push_str(&format!("\nThinking time: {}ms\n", result.thinking_time_ms));
 
             Ok(output)
@@ -1669,12 +2407,27 @@ async fn execute_think(
                 depth,
                 result.confidence * 100.0
             );
-            output.push_str(&format!("Problem: {}\n\n", result.problem));
-            output.push_str("Steps:\n");
+            output.push_str(&format!("Problem: {}\n", result.problem));
+            if let Some(framework) = &result.framework {
+                output.push_str(&format!("Framework: {}\n", framework));
+            }
+            output.push_str("\nSteps:\n");
             for (i, step) in result.steps.iter().enumerate() {
-                output.push_str(&format!("  {}. {}\n", i + 1, step.name));
+                output.push_str(&format!(
+                    "  {}. {}\n     {} → {}\n",
+                    i + 1,
+                    step.question,
+                    step.hypothesis,
+                    step.conclusion
+                ));
             }
             output.push_str(&format!("\nConclusion: {}\n", result.conclusion));
+            match &route {
+                Some(route) => {
+                    output.push_str(&format!("Route: {}/{}\n", route.provider_id, route.model))
+                }
+                None => output.push_str("Route: no provider configured\n"),
+            }
             Ok(output)
         }
     }
@@ -1687,32 +2440,38 @@ async fn execute_agent(
 ) -> Result<String, String> {
     use crate::intelligence::{AgentType, IntelligenceSystem};
 
-    let intelligence = IntelligenceSystem::new();
+    let intelligence = IntelligenceSystem::configured();
 
-    let agent = match agent_type {
-        AgentTypeArg::Security => AgentType::Security,
-        AgentTypeArg::Performance => AgentType::Performance,
-        AgentTypeArg::Architecture => AgentType::Architecture,
-        AgentTypeArg::General => AgentType::General,
+    let result = match agent_type {
+        AgentTypeArg::Auto => intelligence.dispatch_auto(content),
+        AgentTypeArg::Security => intelligence.dispatch(content, AgentType::Security),
+        AgentTypeArg::Performance => intelligence.dispatch(content, AgentType::Performance),
+        AgentTypeArg::Architecture => intelligence.dispatch(content, AgentType::Architecture),
+        AgentTypeArg::General => intelligence.dispatch(content, AgentType::General),
     };
-
-    let result = intelligence.dispatch(content, agent);
+    let agent_type = result.agent;
 
     match format {
-        OutputFormat::Json => serde_json::to_string_pretty(&serde_json::json!({
-            "agent": format!("{:?}", agent_type),
-            "task": result.task,
-            "analysis": result.analysis,
-            "recommendations": result.recommendations,
-            "confidence": result.confidence,
-        }))
-        .map_err(|e| e.to_string()),
+        OutputFormat::Json | OutputFormat::Yaml => {
+            serde_json::to_string_pretty(&serde_json::json!({
+                "agent": format!("{:?}", agent_type),
+                "task": result.task,
+                "analysis": result.analysis,
+                "recommendations": result.recommendations,
+                "confidence": result.confidence,
+                "selection": result.selection,
+            }))
+            .map_err(|e| e.to_string())
+        }
         OutputFormat::Pretty => {
             let mut output = String::new();
             let title =
                 SenaConfig::brand_title(&format!("{:?} AGENT ANALYSIS", agent_type).to_uppercase());
             output.push_str(&FormatBox::new(&title).render());
             output.push_str(&format!("\nAgent: {:?}\n", agent_type));
+            if let Some(selection) = &result.selection {
+                output.push_str(&format!("{}\n", selection.describe()));
+            }
             output.push_str(&format!(
                 "Confidence: {:.1}%\n\n",
                 result.confidence * 100.0
@@ -1735,10 +2494,14 @@ async fn execute_agent(
         }
         OutputFormat::Text => {
             let mut output = format!(
-                "{:?} Agent Analysis (Confidence: {:.0}%):\n\n",
+                "{:?} Agent Analysis (Confidence: {:.0}%):\n",
                 agent_type,
                 result.confidence * 100.0
             );
+            if let Some(selection) = &result.selection {
+                output.push_str(&format!("{}\n", selection.describe()));
+            }
+            output.push('\n');
             output.push_str(&result.analysis);
             output.push_str("\n\nRecommendations:\n");
             for rec in &result.recommendations {
@@ -1749,6 +2512,125 @@ async fn execute_agent(
     }
 }
 
+async fn execute_skills(action: SkillsAction, format: OutputFormat) -> Result<String, String> {
+    use crate::intelligence::{IntelligenceSystem, SkillRegistry};
+
+    let mut intelligence = IntelligenceSystem::configured();
+
+    match action {
+        SkillsAction::List => {
+            let mut skills = intelligence.skills.list();
+            skills.sort_by(|a, b| a.name.cmp(&b.name));
+
+            match format {
+                OutputFormat::Json | OutputFormat::Yaml => {
+                    serde_json::to_string_pretty(&skills).map_err(|e| e.to_string())
+                }
+                OutputFormat::Pretty | OutputFormat::Text => {
+                    let mut output = String::new();
+                    output.push_str(&FormatBox::new(&SenaConfig::brand_title("SKILLS")).render());
+                    output.push('\n');
+
+                    let mut table = TableBuilder::new().title("Skills").row(vec![
+                        "Name".to_string(),
+                        "Category".to_string(),
+                        "Source".to_string(),
+                        "Triggers".to_string(),
+                    ]);
+                    for skill in &skills {
+                        let source = skill
+                            .source
+                            .as_ref()
+                            .and_then(|path| path.file_name())
+                            .map(|name| name.to_string_lossy().into_owned())
+                            .unwrap_or_else(|| "built-in".to_string());
+                        table = table.row(vec![
+                            skill.name.clone(),
+                            skill.category.clone(),
+                            source,
+                            skill.triggers.join(", ").chars().take(40).collect(),
+                        ]);
+                    }
+
+                    output.push_str(&table.build());
+                    output.push_str(&format!("\nTotal: {} skills\n", skills.len()));
+                    Ok(output)
+                }
+            }
+        }
+
+        SkillsAction::Run { context, name } => {
+            let execution = match &name {
+                Some(name) => intelligence
+                    .execute_skill(name, &context)
+                    .ok_or_else(|| format!("Skill not found: {}", name))?,
+                None => intelligence
+                    .execute_matching_skill(&context)
+                    .ok_or_else(|| "No skill triggers on this context".to_string())?,
+            };
+
+            match format {
+                OutputFormat::Json | OutputFormat::Yaml => {
+                    serde_json::to_string_pretty(&execution).map_err(|e| e.to_string())
+                }
+                OutputFormat::Pretty => {
+                    let mut output = String::new();
+                    let title = SenaConfig::brand_title(&execution.skill.to_uppercase());
+                    output.push_str(&FormatBox::new(&title).render());
+                    output.push('\n');
+                    output.push_str(&execution.output);
+                    output.push('\n');
+                    if !execution.success {
+                        output.push_str("\n✗ Skill failed\n");
+                    }
+                    Ok(output)
+                }
+                OutputFormat::Text => Ok(format!(
+                    "{} ({}):\n{}",
+                    execution.skill,
+                    if execution.success { "ok" } else { "failed" },
+                    execution.output
+                )),
+            }
+        }
+
+        SkillsAction::Reload => {
+            let count = intelligence.reload_skills().map_err(|e| e.to_string())?;
+            let dir = SkillRegistry::default_dir();
+            let mut names: Vec<String> = intelligence
+                .skills
+                .list()
+                .into_iter()
+                .filter(|skill| skill.is_custom())
+                .map(|skill| skill.name.clone())
+                .collect();
+            names.sort();
+
+            match format {
+                OutputFormat::Json | OutputFormat::Yaml => {
+                    serde_json::to_string_pretty(&serde_json::json!({
+                        "directory": dir,
+                        "loaded": count,
+                        "skills": names,
+                    }))
+                    .map_err(|e| e.to_string())
+                }
+                OutputFormat::Pretty | OutputFormat::Text => {
+                    let mut output = format!(
+                        "Loaded {} user-defined skills from {}\n",
+                        count,
+                        dir.display()
+                    );
+                    for name in &names {
+                        output.push_str(&format!("  • {}\n", name));
+                    }
+                    Ok(output)
+                }
+            }
+        }
+    }
+}
+
 // ================================
 // Evolution System Commands
 // ================================
@@ -1789,9 +2671,11 @@ async fn execute_evolve(
                         result.feedback_processed
                     ));
                     output.push_str(&format!(
-                        "Improvement Score: {:.1}%\n",
-                        result.new_improvement_score * 100.0
+                        "Improvement Score: {:.1}% ({:+.1} since last cycle)\n",
+                        result.new_improvement_score * 100.0,
+                        result.improvement_delta * 100.0
                     ));
+                    output.push_str(&format!("Feedback Trend: {}\n", result.feedback_trend));
                     Ok(output)
                 }
             }
@@ -1935,6 +2819,42 @@ async fn execute_evolve(
                 }
             }
         }
+        Some(EvolveAction::Recall { context, top }) => {
+            let matches = evolution.learner.recall(&context, top);
+
+            match format {
+                OutputFormat::Json => serde_json::to_string_pretty(&serde_json::json!({
+                    "context": context,
+                    "matches": matches,
+                }))
+                .map_err(|e| e.to_string()),
+                _ => {
+                    let mut output = String::new();
+                    output.push_str(
+                        &FormatBox::new(&SenaConfig::brand_title("RECALLED PATTERNS")).render(),
+                    );
+                    output.push('\n');
+
+                    if matches.is_empty() {
+                        output.push_str("No learned pattern resembles this context.\n");
+                    } else {
+                        for m in &matches {
+                            output.push_str("┌─ Pattern ────────────────────────────────\n");
+                            output.push_str(&format!("│ Context: {}\n", m.pattern.context));
+                            output.push_str(&format!("│ Outcome: {}\n", m.pattern.outcome));
+                            output.push_str(&format!(
+                                "│ Similarity: {:.0}%  Confidence: {:.0}%\n",
+                                m.similarity * 100.0,
+                                m.confidence * 100.0
+                            ));
+                            output.push_str(&format!("│ Successes: {}\n", m.pattern.success_count));
+                            output.push_str("└──────────────────────────────────────────\n\n");
+                        }
+                    }
+                    Ok(output)
+                }
+            }
+        }
     }
 }
 
@@ -1980,7 +2900,7 @@ async fn execute_feedback(
     };
 
     match format {
-        OutputFormat::Json => Ok(serde_json::json!({
+        OutputFormat::Json | OutputFormat::Yaml => Ok(serde_json::json!({
             "action": "feedback",
             "type": format!("{:?}", feedback_type),
             "message": message,
@@ -2015,11 +2935,14 @@ async fn execute_feedback(
 async fn execute_backend(
     analysis: BackendAnalysisType,
     input: &str,
+    no_cache: bool,
+    project_context: bool,
     format: OutputFormat,
 ) -> Result<String, String> {
-    use crate::agents::BackendAgent;
+    use crate::agents::{AnalysisContext, DomainAgentType};
 
-    let agent = BackendAgent::new();
+    let pool = domain_agent_pool(no_cache);
+    let content = read_agent_input(input)?;
 
     let command = match analysis {
         BackendAnalysisType::Map => "map",
@@ -2030,18 +2953,25 @@ async fn execute_backend(
         BackendAnalysisType::Full => "full",
     };
 
-    let result = agent.analyze(command, input);
+    let context = if project_context {
+        project_analysis_context()
+    } else {
+        AnalysisContext::default()
+    };
+    let result = pool.analyze_with_context(DomainAgentType::Backend, command, &content, &context);
     format_domain_analysis("BACKEND", &analysis_type_name(&analysis), result, format)
 }
 
 async fn execute_iot(
     analysis: IoTAnalysisType,
     input: &str,
+    no_cache: bool,
     format: OutputFormat,
 ) -> Result<String, String> {
-    use crate::agents::IoTAgent;
+    use crate::agents::DomainAgentType;
 
-    let agent = IoTAgent::new();
+    let pool = domain_agent_pool(no_cache);
+    let content = read_agent_input(input)?;
 
     let command = match analysis {
         IoTAnalysisType::Protocol => "protocol",
@@ -2053,18 +2983,20 @@ async fn execute_iot(
         IoTAnalysisType::Full => "full",
     };
 
-    let result = agent.analyze(command, input);
+    let result = pool.analyze(DomainAgentType::IoT, command, &content);
     format_domain_analysis("IOT", &iot_analysis_type_name(&analysis), result, format)
 }
 
 async fn execute_ios(
     analysis: IOSAnalysisType,
     input: &str,
+    no_cache: bool,
     format: OutputFormat,
 ) -> Result<String, String> {
-    use crate::agents::IOSAgent;
+    use crate::agents::DomainAgentType;
 
-    let agent = IOSAgent::new();
+    let pool = domain_agent_pool(no_cache);
+    let content = read_agent_input(input)?;
 
     let command = match analysis {
         IOSAnalysisType::Ui | IOSAnalysisType::Hig => "ui",
@@ -2075,18 +3007,20 @@ async fn execute_ios(
         IOSAnalysisType::Full => "full",
     };
 
-    let result = agent.analyze(command, input);
+    let result = pool.analyze(DomainAgentType::IOS, command, &content);
     format_domain_analysis("IOS", &ios_analysis_type_name(&analysis), result, format)
 }
 
 async fn execute_android(
     analysis: AndroidAnalysisType,
     input: &str,
+    no_cache: bool,
     format: OutputFormat,
 ) -> Result<String, String> {
-    use crate::agents::AndroidAgent;
+    use crate::agents::DomainAgentType;
 
-    let agent = AndroidAgent::new();
+    let pool = domain_agent_pool(no_cache);
+    let content = read_agent_input(input)?;
 
     let command = match analysis {
         AndroidAnalysisType::Ui | AndroidAnalysisType::Material => "ui",
@@ -2097,7 +3031,7 @@ async fn execute_android(
         AndroidAnalysisType::Full => "full",
     };
 
-    let result = agent.analyze(command, input);
+    let result = pool.analyze(DomainAgentType::Android, command, &content);
     format_domain_analysis(
         "ANDROID",
         &android_analysis_type_name(&analysis),
@@ -2109,11 +3043,13 @@ async fn execute_android(
 async fn execute_web(
     analysis: WebAnalysisType,
     input: &str,
+    no_cache: bool,
     format: OutputFormat,
 ) -> Result<String, String> {
-    use crate::agents::WebAgent;
+    use crate::agents::DomainAgentType;
 
-    let agent = WebAgent::new();
+    let pool = domain_agent_pool(no_cache);
+    let content = read_agent_input(input)?;
 
     let command = match analysis {
         WebAnalysisType::Vitals => "vitals",
@@ -2125,10 +3061,51 @@ async fn execute_web(
         WebAnalysisType::Full => "full",
     };
 
-    let result = agent.analyze(command, input);
+    let result = pool.analyze(DomainAgentType::Web, command, &content);
     format_domain_analysis("WEB", &web_analysis_type_name(&analysis), result, format)
 }
 
+/// Project memories plus the framework and git branch of the current directory
+fn project_analysis_context() -> crate::agents::AnalysisContext {
+    use crate::agents::AnalysisContext;
+    use crate::memory::{MemoryType, PersistentMemory};
+
+    let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let memories = PersistentMemory::new()
+        .map(|memory| {
+            let mut entries = memory.search_by_type(&MemoryType::Project);
+            entries.sort_by(|a, b| b.importance.total_cmp(&a.importance));
+            entries
+                .into_iter()
+                .take(20)
+                .map(|entry| entry.content.clone())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    AnalysisContext::detect(&cwd).with_memories(memories)
+}
+
+fn domain_agent_pool(no_cache: bool) -> crate::agents::DomainAgentPool {
+    use crate::agents::{AgentCache, DomainAgentPool};
+
+    if no_cache {
+        DomainAgentPool::new()
+    } else {
+        DomainAgentPool::new().with_cache(AgentCache::default())
+    }
+}
+
+fn read_agent_input(input: &str) -> Result<String, String> {
+    let path = std::path::Path::new(input);
+    if path.is_file() {
+        std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+    } else {
+        Ok(input.to_string())
+    }
+}
+
 fn format_domain_analysis(
     agent_name: &str,
     analysis_name: &str,
@@ -2136,23 +3113,25 @@ fn format_domain_analysis(
     format: OutputFormat,
 ) -> Result<String, String> {
     match format {
-        OutputFormat::Json => serde_json::to_string_pretty(&serde_json::json!({
-            "agent": agent_name,
-            "analysis_type": analysis_name,
-            "category": result.category,
-            "score": result.score,
-            "findings": result.findings.iter().map(|f| {
-                serde_json::json!({
-                    "severity": format!("{:?}", f.severity),
-                    "title": f.title,
-                    "description": f.description,
-                    "location": f.location,
-                    "suggestion": f.suggestion,
-                })
-            }).collect::<Vec<_>>(),
-            "recommendations": result.recommendations,
-        }))
-        .map_err(|e| e.to_string()),
+        OutputFormat::Json | OutputFormat::Yaml => {
+            serde_json::to_string_pretty(&serde_json::json!({
+                "agent": agent_name,
+                "analysis_type": analysis_name,
+                "category": result.category,
+                "score": result.score,
+                "findings": result.findings.iter().map(|f| {
+                    serde_json::json!({
+                        "severity": format!("{:?}", f.severity),
+                        "title": f.title,
+                        "description": f.description,
+                        "location": f.location,
+                        "suggestion": f.suggestion,
+                    })
+                }).collect::<Vec<_>>(),
+                "recommendations": result.recommendations,
+            }))
+            .map_err(|e| e.to_string())
+        }
         OutputFormat::Pretty => {
             let mut output = String::new();
             output.push_str(
@@ -2293,12 +3272,13 @@ async fn execute_setup(
     name: Option<String>,
     auto_yes: bool,
     format: OutputFormat,
-) -> Result<String, String> {
+) -> Result<String, CliError> {
     let project_name = name.unwrap_or_else(|| "sena-project".to_string());
     let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
     let sena_binary = format!("{}/.local/bin/sena", home);
 
-    match install_type {
+    // Every setup step writes configuration, so its failures are setup failures
+    let installed = match install_type {
         Some(InstallationType::Mcp) => setup_mcp_server(&home, &sena_binary, format),
         Some(InstallationType::Hook) => setup_claude_hooks(&home, &sena_binary, format),
         Some(InstallationType::Full) => {
@@ -2320,7 +3300,8 @@ async fn execute_setup(
                 show_setup_menu(format)
             }
         }
-    }
+    };
+    installed.map_err(CliError::Config)
 }
 
 fn show_setup_menu(_format: OutputFormat) -> Result<String, String> {
@@ -2555,7 +3536,7 @@ sena health
     Ok(output)
 }
 
-async fn execute_network(action: NetworkAction, format: OutputFormat) -> Result<String, String> {
+async fn execute_network(action: NetworkAction) -> Result<CommandOutput, CliError> {
     use crate::network::{NetworkConfig, NetworkManager};
 
     let home = dirs::home_dir().ok_or("Cannot find home directory")?;
@@ -2575,24 +3556,24 @@ async fn execute_network(action: NetworkAction, format: OutputFormat) -> Result<
                 manager.set_local_peer_name(&custom_name).await?;
             }
 
-            manager.start().await?;
+            manager.start().await.map_err(CliError::Provider)?;
 
             let status = manager.status().await;
-            format_network_status(&status, format, "Network server started")
+            format_network_status(&status, "Network server started").map_err(CliError::from)
         }
 
         NetworkAction::Stop => {
             let config = NetworkConfig::default();
             let mut manager = NetworkManager::new(config, data_dir)?;
             manager.stop().await;
-            Ok("Network server stopped".to_string())
+            Ok(CommandOutput::message("Network server stopped"))
         }
 
         NetworkAction::Status => {
             let config = NetworkConfig::default();
             let manager = NetworkManager::new(config, data_dir)?;
             let status = manager.status().await;
-            format_network_status(&status, format, "Network Status")
+            format_network_status(&status, "Network Status").map_err(CliError::from)
         }
 
         NetworkAction::Info => {
@@ -2605,97 +3586,91 @@ async fn execute_network(action: NetworkAction, format: OutputFormat) -> Result<
                 .get_certificate_fingerprint()
                 .unwrap_or_else(|_| "N/A".to_string());
 
-            match format {
-                OutputFormat::Json => Ok(serde_json::json!({
+            let mut output = String::new();
+            output.push_str(&FormatBox::new(&SenaConfig::brand_title("NETWORK INFO")).render());
+            output.push('\n');
+            output.push_str(&format!("Peer ID: {}\n", peer_id));
+            output.push_str(&format!("Peer Name: {}\n", peer_name));
+            output.push_str(&format!(
+                "Certificate: {}\n",
+                &fingerprint[..16.min(fingerprint.len())]
+            ));
+            CommandOutput::data(
+                &serde_json::json!({
                     "peer_id": peer_id,
                     "peer_name": peer_name,
                     "certificate_fingerprint": fingerprint
-                })
-                .to_string()),
-                _ => {
-                    let mut output = String::new();
-                    output.push_str(
-                        &FormatBox::new(&SenaConfig::brand_title("NETWORK INFO")).render(),
-                    );
-                    output.push('\n');
-                    output.push_str(&format!("Peer ID: {}\n", peer_id));
-                    output.push_str(&format!("Peer Name: {}\n", peer_name));
-                    output.push_str(&format!(
-                        "Certificate: {}\n",
-                        &fingerprint[..16.min(fingerprint.len())]
-                    ));
-                    Ok(output)
-                }
-            }
+                }),
+                output,
+            )
+            .map_err(CliError::from)
         }
 
         NetworkAction::SetName { name } => {
             let config = NetworkConfig::default();
             let manager = NetworkManager::new(config, data_dir)?;
             manager.set_local_peer_name(&name).await?;
-            Ok(format!("Peer name set to: {}", name))
+            CommandOutput::data(
+                &serde_json::json!({ "peer_name": name }),
+                format!("Peer name set to: {}", name),
+            )
+            .map_err(CliError::from)
         }
     }
 }
 
 fn format_network_status(
     status: &crate::network::NetworkStatus,
-    format: OutputFormat,
     title: &str,
-) -> Result<String, String> {
-    match format {
-        OutputFormat::Json => serde_json::to_string_pretty(status).map_err(|e| e.to_string()),
-        _ => {
-            let mut output = String::new();
-            output.push_str(&FormatBox::new(&SenaConfig::brand_title(title)).render());
-            output.push('\n');
+) -> Result<CommandOutput, String> {
+    let mut output = String::new();
+    output.push_str(&FormatBox::new(&SenaConfig::brand_title(title)).render());
+    output.push('\n');
 
-            let table = TableBuilder::new()
-                .row(vec![
-                    "Status".to_string(),
-                    if status.running { "Running" } else { "Stopped" }.to_string(),
-                ])
-                .row(vec!["Port".to_string(), status.port.to_string()])
-                .row(vec!["Peers".to_string(), status.peer_count.to_string()])
-                .row(vec![
-                    "Authorized".to_string(),
-                    status.authorized_count.to_string(),
-                ])
-                .row(vec![
-                    "Discovered".to_string(),
-                    status.discovered_count.to_string(),
-                ])
-                .row(vec![
-                    "Connections".to_string(),
-                    status.connection_count.to_string(),
-                ])
-                .row(vec![
-                    "TLS".to_string(),
-                    if status.tls_enabled {
-                        "Enabled"
-                    } else {
-                        "Disabled"
-                    }
-                    .to_string(),
-                ])
-                .row(vec![
-                    "Discovery".to_string(),
-                    if status.discovery_enabled {
-                        "Enabled"
-                    } else {
-                        "Disabled"
-                    }
-                    .to_string(),
-                ])
-                .build();
+    let table = TableBuilder::new()
+        .row(vec![
+            "Status".to_string(),
+            if status.running { "Running" } else { "Stopped" }.to_string(),
+        ])
+        .row(vec!["Port".to_string(), status.port.to_string()])
+        .row(vec!["Peers".to_string(), status.peer_count.to_string()])
+        .row(vec![
+            "Authorized".to_string(),
+            status.authorized_count.to_string(),
+        ])
+        .row(vec![
+            "Discovered".to_string(),
+            status.discovered_count.to_string(),
+        ])
+        .row(vec![
+            "Connections".to_string(),
+            status.connection_count.to_string(),
+        ])
+        .row(vec![
+            "TLS".to_string(),
+            if status.tls_enabled {
+                "Enabled"
+            } else {
+                "Disabled"
+            }
+            .to_string(),
+        ])
+        .row(vec![
+            "Discovery".to_string(),
+            if status.discovery_enabled {
+                "Enabled"
+            } else {
+                "Disabled"
+            }
+            .to_string(),
+        ])
+        .build();
 
-            output.push_str(&table);
-            Ok(output)
-        }
-    }
+    output.push_str(&table);
+    CommandOutput::data(status, output)
 }
 
-async fn execute_peer(action: PeerAction, format: OutputFormat) -> Result<String, String> {
+async fn execute_peer(action: PeerAction) -> Result<CommandOutput, CliError> {
     use crate::network::{NetworkConfig, NetworkManager};
 
     let home = dirs::home_dir().ok_or("Cannot find home directory")?;
@@ -2711,37 +3686,30 @@ async fn execute_peer(action: PeerAction, format: OutputFormat) -> Result<String
                 manager.get_peers().await
             };
 
-            match format {
-                OutputFormat::Json => {
-                    serde_json::to_string_pretty(&peers).map_err(|e| e.to_string())
-                }
-                _ => {
-                    let mut output = String::new();
-                    output.push_str(&FormatBox::new(&SenaConfig::brand_title("PEERS")).render());
-                    output.push('\n');
+            let mut output = String::new();
+            output.push_str(&FormatBox::new(&SenaConfig::brand_title("PEERS")).render());
+            output.push('\n');
 
-                    if peers.is_empty() {
-                        output.push_str("No peers found.\n");
-                        output.push_str("\nTo add a peer:\n");
-                        output.push_str("  sena peer add <ip> --port 9876 --name \"Peer Name\"\n");
-                    } else {
-                        for peer in &peers {
-                            let status = if peer.authorized { "✅" } else { "❌" };
-                            let online = if peer.is_online() { "🟢" } else { "⚫" };
-                            output.push_str(&format!(
-                                "{} {} {} ({}:{}) - {}\n",
-                                status,
-                                online,
-                                peer.name,
-                                peer.address,
-                                peer.port,
-                                &peer.id[..8]
-                            ));
-                        }
-                    }
-                    Ok(output)
+            if peers.is_empty() {
+                output.push_str("No peers found.\n");
+                output.push_str("\nTo add a peer:\n");
+                output.push_str("  sena peer add <ip> --port 9876 --name \"Peer Name\"\n");
+            } else {
+                for peer in &peers {
+                    let status = if peer.authorized { "✅" } else { "❌" };
+                    let online = if peer.is_online() { "🟢" } else { "⚫" };
+                    output.push_str(&format!(
+                        "{} {} {} ({}:{}) - {}\n",
+                        status,
+                        online,
+                        peer.name,
+                        peer.address,
+                        peer.port,
+                        &peer.id[..8]
+                    ));
                 }
             }
+            CommandOutput::data(&peers, output).map_err(CliError::from)
         }
 
         PeerAction::Add {
@@ -2751,35 +3719,43 @@ async fn execute_peer(action: PeerAction, format: OutputFormat) -> Result<String
         } => {
             let peer = manager.add_peer(&address, port, name.as_deref()).await?;
 
-            match format {
-                OutputFormat::Json => {
-                    serde_json::to_string_pretty(&peer).map_err(|e| e.to_string())
-                }
-                _ => {
-                    let mut output = String::new();
-                    output.push_str(&format!(
-                        "✅ Peer added: {} ({})\n",
-                        peer.name,
-                        &peer.id[..8]
-                    ));
-                    output.push_str(&format!("   Address: {}:{}\n", peer.address, peer.port));
-                    output.push_str("\nTo authorize this peer:\n");
-                    output.push_str(&format!("  sena peer authorize {}\n", &peer.id[..8]));
-                    Ok(output)
-                }
-            }
+            let mut output = String::new();
+            output.push_str(&format!(
+                "✅ Peer added: {} ({})\n",
+                peer.name,
+                &peer.id[..8]
+            ));
+            output.push_str(&format!("   Address: {}:{}\n", peer.address, peer.port));
+            output.push_str("\nTo authorize this peer:\n");
+            output.push_str(&format!("  sena peer authorize {}\n", &peer.id[..8]));
+            CommandOutput::data(&peer, output).map_err(CliError::from)
         }
 
-        PeerAction::Remove { peer_id } => {
+        PeerAction::Remove {
+            peer_id,
+            dry_run,
+            yes,
+        } => {
+            if !dry_run && !yes {
+                return Err(CliError::Usage(
+                    "Use --yes to confirm removing the peer, or --dry-run to preview".to_string(),
+                ));
+            }
+
             let peers = manager.get_peers().await;
             let matched = peers.iter().find(|p| p.id.starts_with(&peer_id));
 
             match matched {
                 Some(peer) => {
-                    manager.remove_peer(&peer.id).await?;
-                    Ok(format!("✅ Peer removed: {}", peer.name))
+                    let summary = manager.remove_peer(&peer.id, dry_run).await?;
+                    let text = if dry_run {
+                        summary.display(20)
+                    } else {
+                        format!("✅ Peer removed: {}", peer.name)
+                    };
+                    CommandOutput::data(&summary, text).map_err(CliError::from)
                 }
-                None => Err(format!("Peer not found: {}", peer_id)),
+                None => Err(CliError::General(format!("Peer not found: {}", peer_id))),
             }
         }
 
@@ -2789,31 +3765,29 @@ async fn execute_peer(action: PeerAction, format: OutputFormat) -> Result<String
 
             match matched {
                 Some(peer) => {
-                    let token = manager.authorize_peer(&peer.id).await?;
+                    let token = manager.authorize_peer(&peer.id, expires).await?;
 
-                    match format {
-                        OutputFormat::Json => Ok(serde_json::json!({
+                    let mut output = String::new();
+                    output.push_str(&format!("✅ Peer authorized: {}\n\n", peer.name));
+                    output.push_str("Share this token with the peer:\n");
+                    output.push_str(&format!("  Token: {}\n", token.token));
+                    output.push_str(&format!("  Expires in: {} seconds\n", expires));
+                    output.push_str("\nPeer should run:\n");
+                    output.push_str(&format!(
+                        "  sena peer connect <your-ip> --token {}\n",
+                        token.token
+                    ));
+                    CommandOutput::data(
+                        &serde_json::json!({
                             "peer_id": peer.id,
                             "token": token.token,
                             "expires_in": expires
-                        })
-                        .to_string()),
-                        _ => {
-                            let mut output = String::new();
-                            output.push_str(&format!("✅ Peer authorized: {}\n\n", peer.name));
-                            output.push_str("Share this token with the peer:\n");
-                            output.push_str(&format!("  Token: {}\n", token.token));
-                            output.push_str(&format!("  Expires in: {} seconds\n", expires));
-                            output.push_str("\nPeer should run:\n");
-                            output.push_str(&format!(
-                                "  sena peer connect <your-ip> --token {}\n",
-                                token.token
-                            ));
-                            Ok(output)
-                        }
-                    }
+                        }),
+                        output,
+                    )
+                    .map_err(CliError::from)
                 }
-                None => Err(format!("Peer not found: {}", peer_id)),
+                None => Err(CliError::General(format!("Peer not found: {}", peer_id))),
             }
         }
 
@@ -2822,25 +3796,89 @@ async fn execute_peer(action: PeerAction, format: OutputFormat) -> Result<String
             port,
             token,
         } => {
-            let mut client = manager.connect_and_auth(&address, port, &token).await?;
+            let mut client = manager
+                .connect_and_auth(&address, port, &token)
+                .await
+                .map_err(CliError::Provider)?;
 
             let peer_name = client.remote_peer_name().unwrap_or("Unknown").to_string();
             let peer_id = client.remote_peer_id().unwrap_or("").to_string();
 
-            client.disconnect().await?;
+            client.disconnect().await.map_err(CliError::Provider)?;
 
-            match format {
-                OutputFormat::Json => Ok(serde_json::json!({
+            CommandOutput::data(
+                &serde_json::json!({
                     "success": true,
                     "peer_id": peer_id,
                     "peer_name": peer_name
-                })
-                .to_string()),
-                _ => Ok(format!(
+                }),
+                format!(
                     "✅ Connected to {} ({})",
                     peer_name,
                     &peer_id[..8.min(peer_id.len())]
-                )),
+                ),
+            )
+            .map_err(CliError::from)
+        }
+
+        PeerAction::Reauthorize {
+            peer_id,
+            fingerprint,
+        } => {
+            let peers = manager.get_peers().await;
+            let matched = peers.iter().find(|p| p.id.starts_with(&peer_id));
+
+            match matched {
+                Some(peer) => {
+                    manager
+                        .reauthorize_peer(&peer.id, fingerprint.as_deref())
+                        .await?;
+                    let text = match &fingerprint {
+                        Some(fingerprint) => format!(
+                            "✅ Pinned new certificate for {}: {}",
+                            peer.name, fingerprint
+                        ),
+                        None => format!(
+                            "✅ Certificate pin cleared for {}; the next `sena peer connect` pins the presented certificate",
+                            peer.name
+                        ),
+                    };
+                    CommandOutput::data(
+                        &serde_json::json!({
+                            "peer_id": peer.id,
+                            "cert_fingerprint": fingerprint,
+                        }),
+                        text,
+                    )
+                    .map_err(CliError::from)
+                }
+                None => Err(CliError::General(format!("Peer not found: {}", peer_id))),
+            }
+        }
+
+        PeerAction::Rotate { peer_id } => {
+            let peers = manager.get_peers().await;
+            let matched = peers.iter().find(|p| p.id.starts_with(&peer_id));
+
+            match matched {
+                Some(peer) => {
+                    let token = manager.rotate_peer_token(&peer.id).await?;
+                    CommandOutput::data(
+                        &serde_json::json!({
+                            "peer_id": peer.id,
+                            "token": token.token,
+                            "expires_in": token.remaining_seconds(),
+                            "grace_secs": crate::network::DEFAULT_ROTATION_GRACE,
+                        }),
+                        format!(
+                            "✅ New token for {}: {}\n   The previous token stops working in {} seconds.",
+                            peer.name,
+                            token.token,
+                            crate::network::DEFAULT_ROTATION_GRACE
+                        ),
+                    ).map_err(CliError::from)
+                }
+                None => Err(CliError::General(format!("Peer not found: {}", peer_id))),
             }
         }
 
@@ -2850,80 +3888,91 @@ async fn execute_peer(action: PeerAction, format: OutputFormat) -> Result<String
 
             match matched {
                 Some(peer) => {
-                    let home = dirs::home_dir().ok_or("Cannot find home directory")?;
-                    let data_dir = home.join(".sena").join("network");
-                    let mut registry =
-                        crate::network::PeerRegistry::load(data_dir.join("peers.json"))?;
-                    registry.revoke_peer(&peer.id)?;
-                    Ok(format!("✅ Authorization revoked for: {}", peer.name))
+                    let tokens = manager.revoke_peer(&peer.id).await?;
+                    CommandOutput::data(
+                        &serde_json::json!({
+                            "peer_id": peer.id,
+                            "revoked": true,
+                            "tokens_revoked": tokens,
+                        }),
+                        format!("✅ Authorization revoked for: {}", peer.name),
+                    )
+                    .map_err(CliError::from)
                 }
-                None => Err(format!("Peer not found: {}", peer_id)),
+                None => Err(CliError::General(format!("Peer not found: {}", peer_id))),
             }
         }
 
         PeerAction::Ping { target, port } => {
-            let mut client = manager.connect_to_peer(&target, port).await?;
+            let mut client = manager
+                .connect_to_peer(&target, port)
+                .await
+                .map_err(CliError::Provider)?;
             let start = std::time::Instant::now();
-            let success = client.ping().await?;
+            let success = client.ping().await.map_err(CliError::Provider)?;
             let elapsed = start.elapsed();
-            client.disconnect().await?;
+            client.disconnect().await.map_err(CliError::Provider)?;
 
             if success {
-                Ok(format!(
-                    "✅ Pong from {} ({}ms)",
-                    target,
-                    elapsed.as_millis()
-                ))
+                CommandOutput::data(
+                    &serde_json::json!({
+                        "target": target,
+                        "latency_ms": elapsed.as_millis() as u64,
+                    }),
+                    format!("✅ Pong from {} ({}ms)", target, elapsed.as_millis()),
+                )
+                .map_err(CliError::from)
             } else {
-                Err("Ping failed".to_string())
+                Err(CliError::Provider("Ping failed".to_string()))
             }
         }
     }
 }
 
-async fn execute_discover(timeout: u64, format: OutputFormat) -> Result<String, String> {
+async fn execute_discover(timeout: u64) -> Result<CommandOutput, CliError> {
     use crate::network::discover_once;
 
-    let peers = discover_once(timeout).await?;
+    let peers = discover_once(timeout).await.map_err(CliError::Provider)?;
 
-    match format {
-        OutputFormat::Json => serde_json::to_string_pretty(&peers).map_err(|e| e.to_string()),
-        _ => {
-            let mut output = String::new();
-            output.push_str(&FormatBox::new(&SenaConfig::brand_title("DISCOVERED PEERS")).render());
-            output.push('\n');
+    let mut output = String::new();
+    output.push_str(&FormatBox::new(&SenaConfig::brand_title("DISCOVERED PEERS")).render());
+    output.push('\n');
 
-            if peers.is_empty() {
-                output.push_str("No peers discovered on network.\n");
-                output.push_str("\nMake sure:\n");
-                output.push_str("  • Other SENA instances are running: sena network start\n");
-                output.push_str("  • You're on the same local network\n");
-                output.push_str("  • Firewall allows mDNS (port 5353)\n");
-            } else {
-                output.push_str(&format!("Found {} peer(s):\n\n", peers.len()));
-                for peer in &peers {
-                    output.push_str(&format!(
-                        "🔍 {} ({})\n   Address: {}:{}\n   ID: {}\n\n",
-                        peer.peer_name,
-                        peer.address,
-                        peer.address,
-                        peer.port,
-                        &peer.peer_id[..8.min(peer.peer_id.len())]
-                    ));
-                }
-                output.push_str("To connect to a peer:\n");
-                output.push_str("  1. Add peer: sena peer add <ip> --name \"Name\"\n");
-                output.push_str("  2. Authorize: sena peer authorize <peer-id>\n");
-                output.push_str("  3. Share token with peer\n");
+    if peers.is_empty() {
+        output.push_str("No peers discovered on network.\n");
+        output.push_str("\nMake sure:\n");
+        output.push_str("  • Other SENA instances are running: sena network start\n");
+        output.push_str("  • You're on the same local network\n");
+        output.push_str("  • Firewall allows mDNS (port 5353)\n");
+    } else {
+        output.push_str(&format!("Found {} peer(s):\n\n", peers.len()));
+        for peer in &peers {
+            output.push_str(&format!(
+                "🔍 {} ({})\n   Address: {}:{}\n",
+                peer.peer_name, peer.address, peer.address, peer.port,
+            ));
+            if peer.addresses.len() > 1 {
+                output.push_str(&format!("   Also at: {}\n", peer.addresses[1..].join(", ")));
             }
-            Ok(output)
+            output.push_str(&format!(
+                "   ID: {}\n\n",
+                &peer.peer_id[..8.min(peer.peer_id.len())]
+            ));
         }
+        output.push_str("To connect to a peer:\n");
+        output.push_str("  1. Add peer: sena peer add <ip> --name \"Name\"\n");
+        output.push_str("  2. Authorize: sena peer authorize <peer-id>\n");
+        output.push_str("  3. Share token with peer\n");
     }
+    CommandOutput::data(&peers, output).map_err(CliError::from)
 }
 
-async fn execute_provider(action: ProviderAction, format: OutputFormat) -> Result<String, String> {
+async fn execute_provider(
+    action: ProviderAction,
+    format: OutputFormat,
+) -> Result<String, CliError> {
     use sena_providers::{
-        config::ProvidersConfig, AIProvider, ChatRequest, Message, ProviderRouter,
+        config::ProvidersConfig, AIProvider, CapabilityCache, ChatRequest, Message, ProviderRouter,
     };
 
     let config = ProvidersConfig::load_or_default();
@@ -2939,13 +3988,14 @@ async fn execute_provider(action: ProviderAction, format: OutputFormat) -> Resul
                         "enabled": cfg.enabled,
                         "default_model": cfg.default_model,
                         "has_api_key": cfg.api_key.is_some() || cfg.get_api_key().is_some(),
+                        "api_key_preview": cfg.redacted_api_key(),
                     })
                 })
                 .collect();
 
             match format {
                 OutputFormat::Json => {
-                    serde_json::to_string_pretty(&providers_info).map_err(|e| e.to_string())
+                    serde_json::to_string_pretty(&providers_info).map_err(CliError::from)
                 }
                 _ => {
                     let mut output = String::new();
@@ -2987,7 +4037,17 @@ async fn execute_provider(action: ProviderAction, format: OutputFormat) -> Resul
 
         ProviderAction::Status => match ProviderRouter::from_config(&config) {
             Ok(router) => {
-                let status = router.provider_status();
+                let checks = router.health_checks().await;
+                let status: std::collections::HashMap<&str, sena_providers::ProviderStatus> =
+                    checks
+                        .iter()
+                        .map(|(id, check)| {
+                            (
+                                id.as_str(),
+                                sena_providers::ProviderStatus::from_health_check(check),
+                            )
+                        })
+                        .collect();
                 let providers: Vec<&std::sync::Arc<dyn AIProvider>> = router.available_providers();
 
                 match format {
@@ -2999,6 +4059,10 @@ async fn execute_provider(action: ProviderAction, format: OutputFormat) -> Resul
                                     "id": p.provider_id(),
                                     "name": p.display_name(),
                                     "status": format!("{:?}", status.get(p.provider_id())),
+                                    "latency_ms": checks
+                                        .get(p.provider_id())
+                                        .and_then(|c| c.as_ref().ok())
+                                        .map(|info| info.latency_ms),
                                     "default_model": p.default_model(),
                                     "streaming": p.supports_streaming(),
                                     "tools": p.supports_tools(),
@@ -3006,7 +4070,7 @@ async fn execute_provider(action: ProviderAction, format: OutputFormat) -> Resul
                                 })
                             })
                             .collect();
-                        serde_json::to_string_pretty(&json).map_err(|e| e.to_string())
+                        serde_json::to_string_pretty(&json).map_err(CliError::from)
                     }
                     _ => {
                         let mut output = String::new();
@@ -3024,12 +4088,19 @@ async fn execute_provider(action: ProviderAction, format: OutputFormat) -> Resul
                                 Some(sena_providers::ProviderStatus::Error) | None => "⚪",
                             };
 
+                            let health = match checks.get(provider.provider_id()) {
+                                Some(Ok(info)) => format!("Latency: {} ms", info.latency_ms),
+                                Some(Err(e)) => format!("Error: {}", e),
+                                None => "Not checked".to_string(),
+                            };
+
                             output.push_str(&format!(
-                                    "{} {} ({})\n   Model: {}\n   Features: streaming={}, tools={}, vision={}\n\n",
+                                    "{} {} ({})\n   Model: {}\n   {}\n   Features: streaming={}, tools={}, vision={}\n\n",
                                     status_icon,
                                     provider.display_name(),
                                     provider.provider_id(),
                                     provider.default_model(),
+                                    health,
                                     provider.supports_streaming(),
                                     provider.supports_tools(),
                                     provider.supports_vision(),
@@ -3040,23 +4111,52 @@ async fn execute_provider(action: ProviderAction, format: OutputFormat) -> Resul
                     }
                 }
             }
-            Err(e) => Err(format!("Failed to initialize providers: {}", e)),
+            Err(e) => Err(CliError::Config(format!(
+                "Failed to initialize providers: {}",
+                e
+            ))),
         },
 
-        ProviderAction::Models { provider } => match ProviderRouter::from_config(&config) {
-            Ok(router) => {
-                let models = if let Some(provider_id) = &provider {
-                    router
+        ProviderAction::Models { provider, remote } => match ProviderRouter::from_config(&config) {
+            Ok(mut router) => {
+                let probes = CapabilityCache::load(&CapabilityCache::cache_path());
+                let models = if remote {
+                    let providers = match &provider {
+                        Some(provider_id) => router.get_provider(provider_id).into_iter().collect(),
+                        None => router.available_providers(),
+                    };
+                    let listings = providers.into_iter().map(|p| async move {
+                        p.list_models_remote().await.unwrap_or_else(|e| {
+                            log::warn!("Cannot list {} models: {}", p.provider_id(), e);
+                            p.available_models().to_vec()
+                        })
+                    });
+                    let mut models: Vec<_> = futures::future::join_all(listings)
+                        .await
+                        .into_iter()
+                        .flatten()
+                        .collect();
+                    for model in &mut models {
+                        probes.apply(model);
+                    }
+                    models
+                } else if let Some(provider_id) = &provider {
+                    let mut models = router
                         .get_provider(provider_id)
                         .map(|p| p.available_models().to_vec())
-                        .unwrap_or_default()
+                        .unwrap_or_default();
+                    for model in &mut models {
+                        probes.apply(model);
+                    }
+                    models
                 } else {
+                    router.set_capability_cache(probes);
                     router.all_models()
                 };
 
                 match format {
                     OutputFormat::Json => {
-                        serde_json::to_string_pretty(&models).map_err(|e| e.to_string())
+                        serde_json::to_string_pretty(&models).map_err(CliError::from)
                     }
                     _ => {
                         let mut output = String::new();
@@ -3097,22 +4197,42 @@ async fn execute_provider(action: ProviderAction, format: OutputFormat) -> Resul
                     }
                 }
             }
-            Err(e) => Err(format!("Failed to initialize providers: {}", e)),
+            Err(e) => Err(CliError::Config(format!(
+                "Failed to initialize providers: {}",
+                e
+            ))),
         },
 
         ProviderAction::Chat {
             message,
             provider,
             model,
+            auto,
         } => match ProviderRouter::from_config(&config) {
             Ok(router) => {
                 let mut request = ChatRequest::new(vec![Message::user(&message)]);
 
+                let route = if auto {
+                    match crate::intelligence::IntelligenceSystem::new()
+                        .route_to_provider(&message, &router)
+                    {
+                        Some(route) => Some(route),
+                        None => {
+                            return Err(CliError::Config(
+                                "No usable provider configured".to_string(),
+                            ))
+                        }
+                    }
+                } else {
+                    None
+                };
+                let model = route.as_ref().map(|r| r.model.clone()).or(model);
+
                 if let Some(m) = model {
                     request = request.with_model(m);
                 }
 
-                let result = if provider.is_some() {
+                let result = if provider.is_some() || route.is_some() {
                     router.chat(request).await
                 } else {
                     router.chat_with_fallback(request).await
@@ -3123,14 +4243,18 @@ async fn execute_provider(action: ProviderAction, format: OutputFormat) -> Resul
                         OutputFormat::Json => serde_json::to_string_pretty(&serde_json::json!({
                             "provider": response.provider,
                             "model": response.model,
+                            "route": route,
                             "content": response.content,
+                            "finish_reason": response.finish_reason,
+                            "truncated": response.is_truncated(),
+                            "skipped_providers": response.skipped_providers,
                             "usage": {
                                 "prompt_tokens": response.usage.prompt_tokens,
                                 "completion_tokens": response.usage.completion_tokens,
                                 "total_tokens": response.usage.total_tokens,
                             }
                         }))
-                        .map_err(|e| e.to_string()),
+                        .map_err(CliError::from),
                         _ => {
                             let mut output = String::new();
                             output.push_str(
@@ -3138,22 +4262,43 @@ async fn execute_provider(action: ProviderAction, format: OutputFormat) -> Resul
                             );
                             output.push('\n');
                             output.push_str(&format!(
-                                "Provider: {} | Model: {}\n\n",
+                                "Provider: {} | Model: {}\n",
                                 response.provider, response.model
                             ));
+                            if let Some(route) = &route {
+                                output.push_str(&format!("Auto-selected: {}\n", route.reason));
+                            }
+                            for skipped in &response.skipped_providers {
+                                let kind = if skipped.content_blocked {
+                                    "blocked"
+                                } else {
+                                    "failed"
+                                };
+                                output.push_str(&format!(
+                                    "Fell back from {} ({}): {}\n",
+                                    skipped.provider, kind, skipped.reason
+                                ));
+                            }
+                            output.push('\n');
                             output.push_str(&response.content);
                             output.push_str(&format!("\n\n─────────────────────────────────────────\nTokens: {} prompt + {} completion = {} total\n",
                                         response.usage.prompt_tokens,
                                         response.usage.completion_tokens,
                                         response.usage.total_tokens
                                     ));
+                            if response.is_truncated() {
+                                output.push_str("⚠ Response truncated at the token limit\n");
+                            }
                             Ok(output)
                         }
                     },
-                    Err(e) => Err(format!("Chat failed: {}", e)),
+                    Err(e) => Err(CliError::Provider(format!("Chat failed: {}", e))),
                 }
             }
-            Err(e) => Err(format!("Failed to initialize providers: {}", e)),
+            Err(e) => Err(CliError::Config(format!(
+                "Failed to initialize providers: {}",
+                e
+            ))),
         },
 
         ProviderAction::Default { provider_id } => {
@@ -3166,14 +4311,14 @@ async fn execute_provider(action: ProviderAction, format: OutputFormat) -> Resul
                         provider_id,
                         path.display()
                     )),
-                    Err(e) => Err(format!("Failed to save config: {}", e)),
+                    Err(e) => Err(CliError::Config(format!("Failed to save config: {}", e))),
                 }
             } else {
-                Err(format!(
+                Err(CliError::Provider(format!(
                     "Unknown provider: {}. Available: {:?}",
                     provider_id,
                     config.providers.keys().collect::<Vec<_>>()
-                ))
+                )))
             }
         }
 
@@ -3194,19 +4339,18 @@ async fn execute_provider(action: ProviderAction, format: OutputFormat) -> Resul
                 };
 
                 if providers_to_test.is_empty() {
-                    return Err(format!("Provider not found: {}", provider));
+                    return Err(CliError::Provider(format!(
+                        "Provider not found: {}",
+                        provider
+                    )));
                 }
 
                 for p in providers_to_test {
-                    let test_request =
-                        ChatRequest::new(vec![Message::user("Say 'OK' if you can hear me.")])
-                            .with_max_tokens(10);
-
                     output.push_str(&format!("Testing {}... ", p.display_name()));
 
-                    match p.chat(test_request).await {
-                        Ok(response) => {
-                            output.push_str(&format!("✅ OK ({})\n", response.model));
+                    match p.health_check().await {
+                        Ok(info) => {
+                            output.push_str(&format!("✅ OK ({} ms)\n", info.latency_ms));
                         }
                         Err(e) => {
                             output.push_str(&format!("❌ Failed: {}\n", e));
@@ -3216,20 +4360,189 @@ async fn execute_provider(action: ProviderAction, format: OutputFormat) -> Resul
 
                 Ok(output)
             }
-            Err(e) => Err(format!("Failed to initialize providers: {}", e)),
+            Err(e) => Err(CliError::Config(format!(
+                "Failed to initialize providers: {}",
+                e
+            ))),
         },
+
+        ProviderAction::Warmup => {
+            let router = ProviderRouter::from_config(&config)
+                .map_err(|e| CliError::Config(format!("Failed to initialize providers: {}", e)))?;
+            let results = router.warm_up().await;
+            if results.is_empty() {
+                return Err(CliError::Config(
+                    "No providers configured. Check API keys.".to_string(),
+                ));
+            }
+
+            match format {
+                OutputFormat::Json => {
+                    serde_json::to_string_pretty(&results).map_err(CliError::from)
+                }
+                _ => {
+                    let mut output = String::new();
+                    output.push_str(
+                        &FormatBox::new(&SenaConfig::brand_title("PROVIDER WARM-UP")).render(),
+                    );
+                    output.push('\n');
+                    for result in &results {
+                        match &result.error {
+                            None => output.push_str(&format!(
+                                "✅ {} warmed in {}ms\n",
+                                result.provider_id, result.latency_ms
+                            )),
+                            Some(error) => output.push_str(&format!(
+                                "❌ {} failed after {}ms: {}\n",
+                                result.provider_id, result.latency_ms, error
+                            )),
+                        }
+                    }
+                    let warmed = results.iter().filter(|r| r.success).count();
+                    output.push_str(&format!(
+                        "\n{}/{} providers warmed\n",
+                        warmed,
+                        results.len()
+                    ));
+                    Ok(output)
+                }
+            }
+        }
+
+        ProviderAction::Probe {
+            provider,
+            model,
+            force,
+        } => {
+            let mut router = ProviderRouter::from_config(&config)
+                .map_err(|e| CliError::Config(format!("Failed to initialize providers: {}", e)))?;
+            let cache_path = CapabilityCache::cache_path();
+            router.set_capability_cache(CapabilityCache::load(&cache_path));
+
+            let provider_ids: Vec<String> = match provider {
+                Some(id) => vec![id],
+                None => router
+                    .available_providers()
+                    .iter()
+                    .map(|p| p.provider_id().to_string())
+                    .collect(),
+            };
+            if provider_ids.is_empty() {
+                return Err(CliError::Config(
+                    "No providers configured. Check API keys.".to_string(),
+                ));
+            }
+
+            let mut results = Vec::new();
+            for id in &provider_ids {
+                let probed = router
+                    .probe_model(id, model.as_deref(), force)
+                    .await
+                    .map_err(|e| CliError::Provider(format!("Probe failed for {}: {}", id, e)))?;
+                results.push(probed);
+            }
+
+            router
+                .capability_cache()
+                .save(&cache_path)
+                .map_err(|e| CliError::Config(format!("Failed to save probe results: {}", e)))?;
+
+            match format {
+                OutputFormat::Json => {
+                    serde_json::to_string_pretty(&results).map_err(CliError::from)
+                }
+                _ => {
+                    let mark = |ok: bool| if ok { "✅" } else { "❌" };
+                    let mut output = String::new();
+                    output.push_str(
+                        &FormatBox::new(&SenaConfig::brand_title("PROVIDER PROBE")).render(),
+                    );
+                    output.push('\n');
+                    for caps in &results {
+                        output.push_str(&format!(
+                            "{} / {} (probed {})\n  tools {}  vision {}  streaming {}\n",
+                            caps.provider,
+                            caps.model,
+                            caps.probed_at.format("%Y-%m-%d %H:%M UTC"),
+                            mark(caps.supports_tools),
+                            mark(caps.supports_vision),
+                            mark(caps.supports_streaming),
+                        ));
+                        for note in &caps.notes {
+                            output.push_str(&format!("  - {}\n", note));
+                        }
+                    }
+                    Ok(output)
+                }
+            }
+        }
+
+        ProviderAction::Alias { action } => {
+            let mut config = config;
+            let path = ProvidersConfig::config_path();
+
+            match action {
+                AliasAction::List => {
+                    let mut aliases: Vec<_> = config.aliases.iter().collect();
+                    aliases.sort();
+
+                    match format {
+                        OutputFormat::Json => {
+                            serde_json::to_string_pretty(&config.aliases).map_err(CliError::from)
+                        }
+                        _ if aliases.is_empty() => Ok(
+                            "No model aliases. Add one with: sena provider alias set <name> <provider/model>"
+                                .to_string(),
+                        ),
+                        _ => {
+                            let mut output = String::from("Model aliases:\n");
+                            for (name, target) in aliases {
+                                output.push_str(&format!("  {} → {}\n", name, target));
+                            }
+                            Ok(output)
+                        }
+                    }
+                }
+                AliasAction::Set { name, target } => {
+                    let alias = sena_providers::ModelAlias::parse(&target);
+                    if let Some(provider_id) = &alias.provider {
+                        if !config.providers.contains_key(provider_id) {
+                            return Err(CliError::Provider(format!(
+                                "Unknown provider: {}. Available: {:?}",
+                                provider_id,
+                                config.providers.keys().collect::<Vec<_>>()
+                            )));
+                        }
+                    }
+                    config.set_alias(&name, &target);
+                    config
+                        .save_to_file(&path)
+                        .map_err(|e| CliError::Config(format!("Failed to save config: {}", e)))?;
+                    Ok(format!("Alias set: {} → {}", name, target))
+                }
+                AliasAction::Remove { name } => {
+                    config.remove_alias(&name).ok_or_else(|| {
+                        CliError::Usage(format!("Unknown model alias '{}'", name))
+                    })?;
+                    config
+                        .save_to_file(&path)
+                        .map_err(|e| CliError::Config(format!("Failed to save config: {}", e)))?;
+                    Ok(format!("Alias removed: {}", name))
+                }
+            }
+        }
     }
 }
 
-async fn execute_collab(action: CollabAction, format: OutputFormat) -> Result<String, String> {
-    use sena_collab::{CollabOrchestrator, RequestPayload, RequestType};
+async fn execute_collab(action: CollabAction, format: OutputFormat) -> Result<String, CliError> {
+    use sena_collab::{CollabOrchestrator, RequestPayload, RequestType, TranscriptFormat};
     use sena_providers::config::ProvidersConfig;
     use std::sync::Arc;
 
     let config = ProvidersConfig::load_or_default();
 
     let router = sena_providers::ProviderRouter::from_config(&config)
-        .map_err(|e| format!("Failed to initialize providers: {}", e))?;
+        .map_err(|e| CliError::Config(format!("Failed to initialize providers: {}", e)))?;
 
     let mut orchestrator = CollabOrchestrator::new(100);
 
@@ -3248,7 +4561,7 @@ async fn execute_collab(action: CollabAction, format: OutputFormat) -> Result<St
                                 "session_id": session_id,
                                 "name": name,
                                 "host": provider,
-                            })).map_err(|e| e.to_string())
+                            })).map_err(CliError::from)
                         }
                         _ => {
                             let mut output = String::new();
@@ -3265,7 +4578,7 @@ async fn execute_collab(action: CollabAction, format: OutputFormat) -> Result<St
                         }
                     }
                 }
-                Err(e) => Err(format!("Failed to create session: {}", e))
+                Err(e) => Err(CliError::General(format!("Failed to create session: {}", e)))
             }
         }
 
@@ -3274,7 +4587,7 @@ async fn execute_collab(action: CollabAction, format: OutputFormat) -> Result<St
 
             match format {
                 OutputFormat::Json => {
-                    serde_json::to_string_pretty(&sessions).map_err(|e| e.to_string())
+                    serde_json::to_string_pretty(&sessions).map_err(CliError::from)
                 }
                 _ => {
                     let mut output = String::new();
@@ -3311,7 +4624,7 @@ async fn execute_collab(action: CollabAction, format: OutputFormat) -> Result<St
                                 "session_id": session_id,
                                 "agent_id": agent_id,
                                 "provider": provider,
-                            })).map_err(|e| e.to_string())
+                            })).map_err(CliError::from)
                         }
                         _ => {
                             Ok(format!(
@@ -3321,7 +4634,7 @@ async fn execute_collab(action: CollabAction, format: OutputFormat) -> Result<St
                         }
                     }
                 }
-                Err(e) => Err(format!("Failed to join session: {}", e))
+                Err(e) => Err(CliError::General(format!("Failed to join session: {}", e)))
             }
         }
 
@@ -3333,14 +4646,14 @@ async fn execute_collab(action: CollabAction, format: OutputFormat) -> Result<St
                             serde_json::to_string_pretty(&serde_json::json!({
                                 "status": "started",
                                 "session_id": session_id,
-                            })).map_err(|e| e.to_string())
+                            })).map_err(CliError::from)
                         }
                         _ => {
                             Ok(format!("✅ Session {} started\n\nYou can now:\n  - Send messages: sena collab send {} \"message\"\n  - Broadcast to all: sena collab broadcast {} \"message\"", session_id, session_id, session_id))
                         }
                     }
                 }
-                Err(e) => Err(format!("Failed to start session: {}", e))
+                Err(e) => Err(CliError::Provider(format!("Failed to start session: {}", e)))
             }
         }
 
@@ -3350,7 +4663,7 @@ async fn execute_collab(action: CollabAction, format: OutputFormat) -> Result<St
                 Ok(()) => {
                     Ok(format!("✅ Message sent to session {}", session_id))
                 }
-                Err(e) => Err(format!("Failed to send message: {}", e))
+                Err(e) => Err(CliError::Provider(format!("Failed to send message: {}", e)))
             }
         }
 
@@ -3374,7 +4687,7 @@ async fn execute_collab(action: CollabAction, format: OutputFormat) -> Result<St
                                     }
                                 })
                                 .collect();
-                            serde_json::to_string_pretty(&json_responses).map_err(|e| e.to_string())
+                            serde_json::to_string_pretty(&json_responses).map_err(CliError::from)
                         }
                         _ => {
                             let mut output = String::new();
@@ -3396,7 +4709,7 @@ async fn execute_collab(action: CollabAction, format: OutputFormat) -> Result<St
                         }
                     }
                 }
-                Err(e) => Err(format!("Broadcast failed: {}", e))
+                Err(e) => Err(CliError::Provider(format!("Broadcast failed: {}", e)))
             }
         }
 
@@ -3414,7 +4727,7 @@ async fn execute_collab(action: CollabAction, format: OutputFormat) -> Result<St
                             serde_json::to_string_pretty(&serde_json::json!({
                                 "provider": provider,
                                 "response": response,
-                            })).map_err(|e| e.to_string())
+                            })).map_err(CliError::from)
                         }
                         _ => {
                             let mut output = String::new();
@@ -3431,7 +4744,7 @@ async fn execute_collab(action: CollabAction, format: OutputFormat) -> Result<St
                         }
                     }
                 }
-                Err(e) => Err(format!("Analysis failed: {}", e))
+                Err(e) => Err(CliError::Provider(format!("Analysis failed: {}", e)))
             }
         }
 
@@ -3440,7 +4753,7 @@ async fn execute_collab(action: CollabAction, format: OutputFormat) -> Result<St
                 Ok(summary) => {
                     match format {
                         OutputFormat::Json => {
-                            serde_json::to_string_pretty(&summary).map_err(|e| e.to_string())
+                            serde_json::to_string_pretty(&summary).map_err(CliError::from)
                         }
                         _ => {
                             let mut output = String::new();
@@ -3474,18 +4787,43 @@ async fn execute_collab(action: CollabAction, format: OutputFormat) -> Result<St
                         }
                     }
                 }
-                Err(e) => Err(format!("Failed to get session info: {}", e))
+                Err(e) => Err(CliError::General(format!("Failed to get session info: {}", e)))
             }
         }
 
         CollabAction::End { session_id } => {
             Ok(format!("Session {} marked for termination.\n\nNote: Full session lifecycle management coming in next release.", session_id))
         }
+
+        CollabAction::Export { session_id, output } => {
+            for provider_config in config.providers.values() {
+                if let Some(key) = provider_config.get_api_key() {
+                    orchestrator.add_secret(key);
+                }
+            }
+            let transcript_format = match format {
+                OutputFormat::Json => TranscriptFormat::Json,
+                _ => TranscriptFormat::Markdown,
+            };
+            let transcript = orchestrator
+                .export_transcript(&session_id, transcript_format)
+                .await
+                .map_err(|e| format!("Failed to export transcript: {}", e))?;
+
+            match output {
+                Some(path) => {
+                    std::fs::write(&path, &transcript)
+                        .map_err(|e| format!("Failed to write {}: {}", path, e))?;
+                    Ok(format!("Transcript for {} written to {}", session_id, path))
+                }
+                None => Ok(transcript),
+            }
+        }
     }
 }
 
 async fn execute_tools(action: ToolsAction, format: OutputFormat) -> Result<String, String> {
-    use crate::tools::{ToolCall, ToolCategory, ToolSystem};
+    use crate::tools::{load_history, ToolCall, ToolCategory, ToolHistory, ToolSystem};
 
     let mut tool_system = ToolSystem::new();
 
@@ -3512,7 +4850,7 @@ async fn execute_tools(action: ToolsAction, format: OutputFormat) -> Result<Stri
             };
 
             match format {
-                OutputFormat::Json => {
+                OutputFormat::Json | OutputFormat::Yaml => {
                     serde_json::to_string_pretty(&filtered).map_err(|e| e.to_string())
                 }
                 OutputFormat::Pretty | OutputFormat::Text => {
@@ -3664,52 +5002,153 @@ async fn execute_tools(action: ToolsAction, format: OutputFormat) -> Result<Stri
                 }
             }
         }
-    }
-}
-
-async fn execute_memory(action: MemoryAction, format: OutputFormat) -> Result<String, String> {
-    use crate::memory::{MemoryEntry, MemoryType, PersistentMemory};
-
-    let mut memory =
-        PersistentMemory::new().map_err(|e| format!("Failed to initialize memory: {}", e))?;
 
-    match action {
-        MemoryAction::Add {
-            content,
-            memory_type,
-            tags,
-            importance,
+        ToolsAction::History {
+            tool,
+            success_only,
+            limit,
         } => {
-            let mt = match memory_type.to_lowercase().as_str() {
-                "preference" => MemoryType::Preference,
-                "fact" => MemoryType::Fact,
-                "project" => MemoryType::Project,
-                "context" => MemoryType::Context,
-                "conversation" => MemoryType::Conversation,
-                other => MemoryType::Custom(other.to_string()),
-            };
-
-            let mut entry = MemoryEntry::new(&content, mt);
+            let path = ToolHistory::default_path();
+            let records = load_history(&path, tool.as_deref(), success_only, limit);
 
-            if let Some(tag_str) = tags {
-                let tag_list: Vec<String> =
-                    tag_str.split(',').map(|s| s.trim().to_string()).collect();
-                entry = entry.with_tags(tag_list);
+            match format {
+                OutputFormat::Json => {
+                    serde_json::to_string_pretty(&records).map_err(|e| e.to_string())
+                }
+                _ => {
+                    let mut output = String::new();
+                    output.push_str(
+                        &FormatBox::new(&SenaConfig::brand_title("TOOL HISTORY")).render(),
+                    );
+                    output.push('\n');
+
+                    if records.is_empty() {
+                        output.push_str(&format!("No tool calls recorded in {}\n", path.display()));
+                        output.push_str(
+                            "Set `persist_history = true` under [tools] in the config to record calls.\n",
+                        );
+                        return Ok(output);
+                    }
+
+                    let mut table = TableBuilder::new().max_width(50).row(vec![
+                        "Time".to_string(),
+                        "Tool".to_string(),
+                        "Status".to_string(),
+                        "Duration".to_string(),
+                        "Error".to_string(),
+                    ]);
+                    for record in &records {
+                        let response = &record.response;
+                        table = table.row(vec![
+                            record
+                                .recorded_at
+                                .with_timezone(&chrono::Local)
+                                .format("%Y-%m-%d %H:%M:%S")
+                                .to_string(),
+                            response.tool_name.clone(),
+                            if response.success { "ok" } else { "failed" }.to_string(),
+                            format!("{}ms", response.execution_time_ms),
+                            response.error.clone().unwrap_or_default(),
+                        ]);
+                    }
+
+                    output.push_str(&table.build());
+                    output.push_str(&format!("\nShowing {} call(s)\n", records.len()));
+                    Ok(output)
+                }
+            }
+        }
+    }
+}
+
+fn execute_context(query: &str, max_tokens: usize, format: OutputFormat) -> Result<String, String> {
+    use crate::knowledge::{assemble_context, KnowledgeSystem};
+    use crate::memory::PersistentMemory;
+
+    let memory =
+        PersistentMemory::new().map_err(|e| format!("Failed to initialize memory: {}", e))?;
+    let knowledge = KnowledgeSystem::new();
+    let context = assemble_context(&memory, &knowledge, query, max_tokens);
+
+    match format {
+        OutputFormat::Json => serde_json::to_string_pretty(&context).map_err(|e| e.to_string()),
+        _ => {
+            let mut output = String::new();
+            output.push_str(&FormatBox::new(&SenaConfig::brand_title("CONTEXT")).render());
+            output.push_str(&format!(
+                "\nQuery: '{}'\nTokens: {}/{}\n\n",
+                query, context.tokens, context.max_tokens
+            ));
+
+            if context.is_empty() {
+                output.push_str("No relevant memory or knowledge found.\n");
+                return Ok(output);
             }
 
-            if let Some(imp) = importance {
-                entry = entry.with_importance(imp);
+            output.push_str(&context.text);
+            output.push_str("\nSources:\n");
+            for source in &context.sources {
+                output.push_str(&format!(
+                    "  {} {} (~{} tokens)\n",
+                    source.kind, source.label, source.tokens
+                ));
+            }
+            if context.omitted > 0 {
+                output.push_str(&format!(
+                    "  {} more item(s) omitted to fit the token budget\n",
+                    context.omitted
+                ));
             }
+            Ok(output)
+        }
+    }
+}
+
+async fn execute_memory(action: MemoryAction, format: OutputFormat) -> Result<String, String> {
+    use crate::memory::{MemoryEntry, MemoryType, NoteIngester, PersistentMemory};
+
+    let mut memory =
+        PersistentMemory::new().map_err(|e| format!("Failed to initialize memory: {}", e))?;
+
+    match action {
+        MemoryAction::Add {
+            content,
+            memory_type,
+            tags,
+            importance,
+        } => {
+            let mut entry = MemoryEntry::new(&content, MemoryType::parse(&memory_type));
+
+            if let Some(tag_str) = tags {
+                let tag_list: Vec<String> =
+                    tag_str.split(',').map(|s| s.trim().to_string()).collect();
+                entry = entry.with_tags(tag_list);
+            }
+
+            entry = match importance {
+                Some(imp) => entry.with_importance(imp),
+                None => entry.with_auto_importance(),
+            };
+            let score = entry.importance;
 
             let id = memory
                 .add(entry)
                 .map_err(|e| format!("Failed to add memory: {}", e))?;
 
             match format {
-                OutputFormat::Json => {
-                    Ok(serde_json::json!({"id": id, "success": true}).to_string())
-                }
-                _ => Ok(format!("Memory added with ID: {}", id)),
+                OutputFormat::Json => Ok(serde_json::json!({
+                    "id": id,
+                    "importance": score,
+                    "auto_importance": importance.is_none(),
+                    "success": true,
+                })
+                .to_string()),
+                _ => Ok(format!(
+                    "Memory added with ID: {} (importance: {:.2}{})",
+                    id,
+                    score,
+                    if importance.is_none() { ", auto" } else { "" }
+                )),
             }
         }
 
@@ -3766,14 +5205,7 @@ async fn execute_memory(action: MemoryAction, format: OutputFormat) -> Result<St
 
             let filtered: Vec<_> = match memory_type {
                 Some(mt_str) => {
-                    let mt = match mt_str.to_lowercase().as_str() {
-                        "preference" => MemoryType::Preference,
-                        "fact" => MemoryType::Fact,
-                        "project" => MemoryType::Project,
-                        "context" => MemoryType::Context,
-                        "conversation" => MemoryType::Conversation,
-                        other => MemoryType::Custom(other.to_string()),
-                    };
+                    let mt = MemoryType::parse(&mt_str);
                     all.into_iter()
                         .filter(|e| e.memory_type == mt)
                         .take(limit)
@@ -3841,6 +5273,22 @@ async fn execute_memory(action: MemoryAction, format: OutputFormat) -> Result<St
             }
         }
 
+        MemoryAction::Pin { id, unpin } => {
+            memory
+                .set_pinned(&id, !unpin)
+                .map_err(|e| format!("Failed to pin: {}", e))?;
+            match format {
+                OutputFormat::Json => Ok(
+                    serde_json::json!({"success": true, "id": id, "pinned": !unpin}).to_string(),
+                ),
+                _ => Ok(format!(
+                    "Memory '{}' {}",
+                    id,
+                    if unpin { "unpinned" } else { "pinned" }
+                )),
+            }
+        }
+
         MemoryAction::Stats => {
             let stats = memory.stats();
 
@@ -3873,26 +5321,304 @@ async fn execute_memory(action: MemoryAction, format: OutputFormat) -> Result<St
             }
         }
 
+        MemoryAction::Watch {
+            file,
+            memory_type,
+            tags,
+            from_start,
+            debounce_ms,
+        } => {
+            let mut ingester = NoteIngester::new(&memory).with_tags(tags);
+            if let Some(mt) = memory_type {
+                ingester = ingester.with_type(crate::memory::MemoryType::parse(&mt));
+            }
+
+            let added = match file {
+                Some(path) => {
+                    watch_notes_file(
+                        &mut memory,
+                        &mut ingester,
+                        &path,
+                        from_start,
+                        std::time::Duration::from_millis(debounce_ms),
+                    )
+                    .await?
+                }
+                None => {
+                    use std::io::BufRead;
+                    let mut added = 0;
+                    for line in std::io::stdin().lock().lines() {
+                        let line = line.map_err(|e| format!("Failed to read stdin: {}", e))?;
+                        added += report_ingested(&mut memory, &mut ingester, &line)?;
+                    }
+                    added
+                }
+            };
+
+            match format {
+                OutputFormat::Json => {
+                    Ok(serde_json::json!({"added": added, "success": true}).to_string())
+                }
+                _ => Ok(format!("Captured {} note(s) as memories", added)),
+            }
+        }
+
         MemoryAction::Clear { yes } => {
             if !yes {
                 return Err("Use --yes to confirm clearing all memories".to_string());
             }
 
-            memory
+            let snapshot = memory
                 .clear()
                 .map_err(|e| format!("Failed to clear: {}", e))?;
 
             match format {
-                OutputFormat::Json => Ok(
-                    serde_json::json!({"success": true, "message": "All memories cleared"})
-                        .to_string(),
-                ),
-                _ => Ok("All memories cleared".to_string()),
+                OutputFormat::Json => Ok(serde_json::json!({
+                    "success": true,
+                    "message": "All memories cleared",
+                    "snapshot": snapshot,
+                })
+                .to_string()),
+                _ => Ok(format!(
+                    "All memories cleared{}",
+                    restore_hint(snapshot.as_ref())
+                )),
+            }
+        }
+
+        MemoryAction::Prune { below } => {
+            let change = memory
+                .prune(below)
+                .map_err(|e| format!("Failed to prune: {}", e))?;
+            format_bulk_change(&change, "Pruned", format)
+        }
+
+        MemoryAction::Dedup => {
+            let change = memory
+                .dedup()
+                .map_err(|e| format!("Failed to dedup: {}", e))?;
+            format_bulk_change(&change, "Removed duplicate", format)
+        }
+
+        MemoryAction::Snapshot { action } => execute_memory_snapshot(&mut memory, action, format),
+
+        MemoryAction::Export { path } => {
+            let count = memory
+                .export_jsonl(std::path::Path::new(&path))
+                .map_err(|e| format!("Failed to export: {}", e))?;
+            match format {
+                OutputFormat::Json => serde_json::to_string_pretty(&serde_json::json!({
+                    "exported": count,
+                    "path": path,
+                }))
+                .map_err(|e| e.to_string()),
+                _ => Ok(format!(
+                    "Exported {} memor{} to {}",
+                    count,
+                    if count == 1 { "y" } else { "ies" },
+                    path
+                )),
+            }
+        }
+
+        MemoryAction::Import { path, strategy } => {
+            use crate::memory::MergeStrategy;
+
+            let strategy = match strategy {
+                MergeStrategyArg::Skip => MergeStrategy::Skip,
+                MergeStrategyArg::Overwrite => MergeStrategy::Overwrite,
+                MergeStrategyArg::KeepNewer => MergeStrategy::KeepNewer,
+            };
+            let summary = memory
+                .import_jsonl(std::path::Path::new(&path), strategy)
+                .map_err(|e| format!("Failed to import: {}", e))?;
+            match format {
+                OutputFormat::Json => {
+                    serde_json::to_string_pretty(&summary).map_err(|e| e.to_string())
+                }
+                _ => {
+                    let mut out = format!(
+                        "Imported {}, skipped {}, {} invalid line{}",
+                        summary.imported,
+                        summary.skipped,
+                        summary.errors.len(),
+                        if summary.errors.len() == 1 { "" } else { "s" }
+                    );
+                    for error in &summary.errors {
+                        out.push_str(&format!("\n  line {}: {}", error.line, error.message));
+                    }
+                    Ok(out)
+                }
             }
         }
     }
 }
 
+fn restore_hint(snapshot: Option<&crate::memory::SnapshotId>) -> String {
+    snapshot
+        .map(|id| format!("\nUndo with: sena memory snapshot restore {}", id))
+        .unwrap_or_default()
+}
+
+fn format_bulk_change(
+    change: &crate::memory::BulkChange,
+    verb: &str,
+    format: OutputFormat,
+) -> Result<String, String> {
+    match format {
+        OutputFormat::Json => serde_json::to_string_pretty(change).map_err(|e| e.to_string()),
+        _ => Ok(format!(
+            "{} {} memor{}{}",
+            verb,
+            change.removed.len(),
+            if change.removed.len() == 1 {
+                "y"
+            } else {
+                "ies"
+            },
+            restore_hint(change.snapshot.as_ref())
+        )),
+    }
+}
+
+fn execute_memory_snapshot(
+    memory: &mut crate::memory::PersistentMemory,
+    action: SnapshotAction,
+    format: OutputFormat,
+) -> Result<String, String> {
+    use crate::memory::SnapshotId;
+
+    match action {
+        SnapshotAction::Create { label } => {
+            let id = memory
+                .snapshot(&label)
+                .map_err(|e| format!("Failed to snapshot: {}", e))?;
+            match format {
+                OutputFormat::Json => Ok(serde_json::json!({
+                    "id": id,
+                    "label": label,
+                    "entries": memory.count(),
+                })
+                .to_string()),
+                _ => Ok(format!(
+                    "Snapshot {} created ({} memories)",
+                    id,
+                    memory.count()
+                )),
+            }
+        }
+
+        SnapshotAction::List => {
+            let snapshots = memory
+                .list_snapshots()
+                .map_err(|e| format!("Failed to list snapshots: {}", e))?;
+            match format {
+                OutputFormat::Json => {
+                    serde_json::to_string_pretty(&snapshots).map_err(|e| e.to_string())
+                }
+                _ if snapshots.is_empty() => Ok("No snapshots".to_string()),
+                _ => {
+                    let mut output = String::new();
+                    for snapshot in &snapshots {
+                        output.push_str(&format!(
+                            "{}  {}  {} memories  {}{}\n",
+                            snapshot.id,
+                            snapshot.created_at.format("%Y-%m-%d %H:%M:%S"),
+                            snapshot.entries,
+                            snapshot.label,
+                            if snapshot.automatic { " (auto)" } else { "" }
+                        ));
+                    }
+                    Ok(output)
+                }
+            }
+        }
+
+        SnapshotAction::Restore { id } => {
+            let id = SnapshotId::parse(&id).map_err(|e| e.to_string())?;
+            let restored = memory
+                .restore(&id)
+                .map_err(|e| format!("Failed to restore: {}", e))?;
+            match format {
+                OutputFormat::Json => Ok(serde_json::json!({
+                    "success": true,
+                    "id": id,
+                    "entries": restored,
+                })
+                .to_string()),
+                _ => Ok(format!(
+                    "Restored {} memories from snapshot {}",
+                    restored, id
+                )),
+            }
+        }
+
+        SnapshotAction::Delete { id } => {
+            let id = SnapshotId::parse(&id).map_err(|e| e.to_string())?;
+            memory
+                .delete_snapshot(&id)
+                .map_err(|e| format!("Failed to delete snapshot: {}", e))?;
+            match format {
+                OutputFormat::Json => {
+                    Ok(serde_json::json!({"success": true, "id": id}).to_string())
+                }
+                _ => Ok(format!("Snapshot {} deleted", id)),
+            }
+        }
+    }
+}
+
+fn report_ingested(
+    memory: &mut crate::memory::PersistentMemory,
+    ingester: &mut crate::memory::NoteIngester,
+    text: &str,
+) -> Result<usize, String> {
+    let ids = ingester
+        .ingest(memory, text)
+        .map_err(|e| format!("Failed to add memory: {}", e))?;
+    for id in &ids {
+        eprintln!("Memory added with ID: {}", id);
+    }
+    Ok(ids.len())
+}
+
+async fn watch_notes_file(
+    memory: &mut crate::memory::PersistentMemory,
+    ingester: &mut crate::memory::NoteIngester,
+    path: &str,
+    from_start: bool,
+    debounce: std::time::Duration,
+) -> Result<usize, String> {
+    use crate::memory::FileTail;
+
+    let mut tail =
+        FileTail::open(path, from_start).map_err(|e| format!("Cannot watch {}: {}", path, e))?;
+    eprintln!("Watching {} for new notes (Ctrl+C to stop)", path);
+
+    let mut added = 0;
+    let mut last_len = None;
+    loop {
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => return Ok(added),
+            _ = tokio::time::sleep(debounce) => {}
+        }
+
+        let len = tail
+            .len()
+            .map_err(|e| format!("Cannot watch {}: {}", path, e))?;
+        if last_len.replace(len) != Some(len) {
+            continue;
+        }
+
+        let lines = tail
+            .read_new_lines()
+            .map_err(|e| format!("Cannot read {}: {}", path, e))?;
+        if !lines.is_empty() {
+            added += report_ingested(memory, ingester, &lines.join("\n"))?;
+        }
+    }
+}
+
 async fn execute_auto(
     task: &str,
     max_steps: usize,
@@ -3912,6 +5638,29 @@ async fn execute_auto(
         .await
         .map_err(|e| format!("Agent error: {}", e))?;
 
+    format_execution(&execution, format)
+}
+
+async fn execute_auto_resume(
+    run_id: &str,
+    max_steps: Option<usize>,
+    format: OutputFormat,
+) -> Result<String, String> {
+    use crate::intelligence::AutonomousAgent;
+
+    let mut agent = AutonomousAgent::new();
+    let execution = agent
+        .resume(run_id, max_steps)
+        .await
+        .map_err(|e| format!("Agent error: {}", e))?;
+
+    format_execution(&execution, format)
+}
+
+fn format_execution(
+    execution: &crate::intelligence::AgentExecution,
+    format: OutputFormat,
+) -> Result<String, String> {
     match format {
         OutputFormat::Json => serde_json::to_string_pretty(&execution).map_err(|e| e.to_string()),
         _ => {
@@ -3943,7 +5692,7 @@ async fn execute_auto(
             output.push_str(&format!(
                 "Steps Executed: {} / {}\n",
                 execution.steps_taken(),
-                max_steps
+                execution.max_steps
             ));
             output.push_str(&format!("Successful: {}\n\n", execution.successful_steps()));
 
@@ -3958,6 +5707,13 @@ async fn execute_auto(
                     output.push_str(&format!("    Tool: {}\n", tool));
                 }
 
+                if let Some(verdict) = step.guardian_verdict.as_ref().filter(|v| !v.allowed) {
+                    output.push_str(&format!(
+                        "    Guardian: blocked ({})\n",
+                        verdict.reason.as_deref().unwrap_or("no reason given")
+                    ));
+                }
+
                 if let Some(result) = &step.result {
                     let truncated: String = result.chars().take(200).collect();
                     output.push_str(&format!("    Result: {}...\n", truncated));
@@ -4029,7 +5785,14 @@ async fn execute_git(action: GitAction, format: OutputFormat) -> Result<String,
             }
         }
 
-        GitAction::Commit { message, all } => {
+        GitAction::Commit {
+            message,
+            all,
+            style,
+            commit_type,
+            sign,
+            sign_key,
+        } => {
             if all {
                 let add_output = std::process::Command::new("git")
                     .args(["add", "-A"])
@@ -4055,37 +5818,38 @@ async fn execute_git(action: GitAction, format: OutputFormat) -> Result<String,
                 return Err("No staged changes to commit".to_string());
             }
 
-            let commit_message = message.unwrap_or_else(|| {
-                let changes: Vec<&str> = diff.lines().filter(|l| l.contains('|')).take(3).collect();
-
-                if changes.is_empty() {
-                    "Update files".to_string()
-                } else {
-                    format!(
-                        "Update: {}",
-                        changes.join(", ").chars().take(50).collect::<String>()
-                    )
+            let commit_message = match message {
+                Some(message) => message,
+                None => {
+                    let name_status = std::process::Command::new("git")
+                        .args(["diff", "--cached", "--name-status"])
+                        .output()
+                        .map_err(|e| format!("Failed to get diff: {}", e))?;
+                    let changes = crate::git::parse_name_status(&String::from_utf8_lossy(
+                        &name_status.stdout,
+                    ));
+                    let style = match style {
+                        CommitStyleArg::Plain => crate::git::CommitStyle::Plain,
+                        CommitStyleArg::Conventional => crate::git::CommitStyle::Conventional,
+                    };
+                    style.message(&diff, &changes, commit_type.as_deref())?
                 }
-            });
-
-            let commit_output = std::process::Command::new("git")
-                .args(["commit", "-m", &commit_message])
-                .output()
-                .map_err(|e| format!("Failed to commit: {}", e))?;
+            };
 
-            if !commit_output.status.success() {
-                return Err(format!(
-                    "Commit failed: {}",
-                    String::from_utf8_lossy(&commit_output.stderr)
-                ));
+            let repo = crate::git::GitRepo::discover().map_err(|e| e.to_string())?;
+            let commit_output = if sign || sign_key.is_some() {
+                repo.commit_signed(&commit_message, sign_key.as_deref())
+            } else {
+                repo.commit(&commit_message)
             }
+            .map_err(|e| format!("Commit failed: {}", e))?;
 
             match format {
                 OutputFormat::Json => {
                     let result = serde_json::json!({
                         "success": true,
                         "message": commit_message,
-                        "output": String::from_utf8_lossy(&commit_output.stdout).to_string(),
+                        "output": commit_output,
                     });
                     serde_json::to_string_pretty(&result).map_err(|e| e.to_string())
                 }
@@ -4094,7 +5858,7 @@ async fn execute_git(action: GitAction, format: OutputFormat) -> Result<String,
                     out.push_str(&FormatBox::new(&SenaConfig::brand_title("GIT COMMIT")).render());
                     out.push('\n');
                     out.push_str(&format!("Message: {}\n\n", commit_message));
-                    out.push_str(&String::from_utf8_lossy(&commit_output.stdout));
+                    out.push_str(&commit_output);
                     Ok(out)
                 }
             }
@@ -4157,11 +5921,11 @@ async fn execute_git(action: GitAction, format: OutputFormat) -> Result<String,
             }
         }
 
-        GitAction::Diff { staged } => {
+        GitAction::Diff { staged, no_color } => {
             let args = if staged {
-                vec!["diff", "--cached"]
+                vec!["diff", "--cached", "--no-color"]
             } else {
-                vec!["diff"]
+                vec!["diff", "--no-color"]
             };
 
             let output = std::process::Command::new("git")
@@ -4177,6 +5941,7 @@ async fn execute_git(action: GitAction, format: OutputFormat) -> Result<String,
                         "staged": staged,
                         "diff": diff.to_string(),
                         "has_changes": !diff.is_empty(),
+                        "hunks": crate::git::parse_hunks(&diff),
                     });
                     serde_json::to_string_pretty(&result).map_err(|e| e.to_string())
                 }
@@ -4193,7 +5958,11 @@ async fn execute_git(action: GitAction, format: OutputFormat) -> Result<String,
                     if diff.is_empty() {
                         out.push_str("No changes\n");
                     } else {
-                        out.push_str(&diff);
+                        let mut renderer = DiffRenderer::new();
+                        if no_color || !std::io::stdout().is_terminal() {
+                            renderer = renderer.plain();
+                        }
+                        out.push_str(&renderer.render(&crate::git::parse_hunks(&diff)));
                     }
                     Ok(out)
                 }
@@ -4235,10 +6004,16 @@ async fn execute_git(action: GitAction, format: OutputFormat) -> Result<String,
     }
 }
 
-async fn execute_guardian(action: GuardianAction, format: OutputFormat) -> Result<String, String> {
-    use crate::guardian::GuardianMiddleware;
+async fn execute_guardian(
+    action: GuardianAction,
+    format: OutputFormat,
+) -> Result<String, CliError> {
+    use crate::guardian::{GuardianMiddleware, HallucinationLog};
 
-    let guardian = GuardianMiddleware::new();
+    let guardian = match HallucinationLog::configured() {
+        Some(log) => GuardianMiddleware::new().with_hallucination_log(log),
+        None => GuardianMiddleware::new(),
+    };
 
     match action {
         GuardianAction::Status => {
@@ -4252,9 +6027,7 @@ async fn execute_guardian(action: GuardianAction, format: OutputFormat) -> Resul
             });
 
             match format {
-                OutputFormat::Json => {
-                    serde_json::to_string_pretty(&status).map_err(|e| e.to_string())
-                }
+                OutputFormat::Json => serde_json::to_string_pretty(&status).map_err(CliError::from),
                 _ => {
                     let mut out = String::new();
                     out.push_str(
@@ -4291,7 +6064,7 @@ async fn execute_guardian(action: GuardianAction, format: OutputFormat) -> Resul
                         "risk_score": result.risk_score,
                         "matched_patterns": result.matched_patterns,
                     });
-                    serde_json::to_string_pretty(&json).map_err(|e| e.to_string())
+                    serde_json::to_string_pretty(&json).map_err(CliError::from)
                 }
                 _ => {
                     let mut out = String::new();
@@ -4335,7 +6108,7 @@ async fn execute_guardian(action: GuardianAction, format: OutputFormat) -> Resul
                             "fact_validation_score": result.details.fact_validation_score,
                         }
                     });
-                    serde_json::to_string_pretty(&json).map_err(|e| e.to_string())
+                    serde_json::to_string_pretty(&json).map_err(CliError::from)
                 }
                 _ => {
                     let mut out = String::new();
@@ -4361,6 +6134,54 @@ async fn execute_guardian(action: GuardianAction, format: OutputFormat) -> Resul
             }
         }
 
+        GuardianAction::Stats { thresholds } => {
+            let log = HallucinationLog::configured()
+                .unwrap_or_else(|| HallucinationLog::new(HallucinationLog::default_path()));
+            let stats = if thresholds.is_empty() {
+                log.stats()
+            } else {
+                log.stats_with(&thresholds)
+            };
+
+            match format {
+                OutputFormat::Json => serde_json::to_string_pretty(&stats).map_err(CliError::from),
+                _ => {
+                    let mut out = String::new();
+                    out.push_str(
+                        &FormatBox::new(&SenaConfig::brand_title("HALLUCINATION STATS")).render(),
+                    );
+                    if stats.count == 0 {
+                        out.push_str(&format!("\nNo checks logged in {}\n", log.path().display()));
+                        return Ok(out);
+                    }
+                    out.push_str(&format!(
+                        "\nChecks: {} ({} flagged)\n",
+                        stats.count, stats.flagged
+                    ));
+                    out.push_str(&format!(
+                        "Risk Score: mean {:.2}, min {:.2}, max {:.2}\n",
+                        stats.mean, stats.min, stats.max
+                    ));
+                    let p = &stats.percentiles;
+                    out.push_str(&format!(
+                        "Percentiles: p50 {:.2}, p75 {:.2}, p90 {:.2}, p95 {:.2}, p99 {:.2}\n",
+                        p.p50, p.p75, p.p90, p.p95, p.p99
+                    ));
+                    out.push_str("\nThreshold   Above   +Flagged   +Passed\n");
+                    for impact in &stats.thresholds {
+                        out.push_str(&format!(
+                            "{:>9.2}   {:>5}   {:>8}   {:>7}\n",
+                            impact.threshold,
+                            impact.above,
+                            impact.newly_flagged,
+                            impact.newly_passed
+                        ));
+                    }
+                    Ok(out)
+                }
+            }
+        }
+
         GuardianAction::Execute { command, args } => {
             let args_refs: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
             match guardian.execute(&command, &args_refs) {
@@ -4375,7 +6196,7 @@ async fn execute_guardian(action: GuardianAction, format: OutputFormat) -> Resul
                                 "stdout": stdout,
                                 "stderr": stderr,
                             });
-                            serde_json::to_string_pretty(&json).map_err(|e| e.to_string())
+                            serde_json::to_string_pretty(&json).map_err(CliError::from)
                         }
                         _ => {
                             let mut out = String::new();
@@ -4395,7 +6216,10 @@ async fn execute_guardian(action: GuardianAction, format: OutputFormat) -> Resul
                         }
                     }
                 }
-                Err(e) => Err(format!("Execution blocked: {}", e)),
+                Err(e) => Err(CliError::GuardianBlocked(format!(
+                    "Execution blocked: {}",
+                    e
+                ))),
             }
         }
 
@@ -4405,7 +6229,7 @@ async fn execute_guardian(action: GuardianAction, format: OutputFormat) -> Resul
                     "audit_entries": [],
                     "message": format!("Audit log (last {} entries) - not yet implemented", count),
                 });
-                serde_json::to_string_pretty(&json).map_err(|e| e.to_string())
+                serde_json::to_string_pretty(&json).map_err(CliError::from)
             }
             _ => {
                 let mut out = String::new();
@@ -4418,16 +6242,143 @@ async fn execute_guardian(action: GuardianAction, format: OutputFormat) -> Resul
     }
 }
 
-async fn execute_devil(action: DevilAction, format: OutputFormat) -> Result<String, String> {
-    use crate::devil::{DevilConfig, DevilExecutor, ProviderResponse, SynthesisMethod};
-    use sena_providers::{ChatRequest, Message, ProviderRouter, ProvidersConfig};
-    use std::time::{Duration, Instant};
+/// Send `prompt` to every available provider in parallel, collecting each
+/// response or failure
+fn devil_providers() -> Result<Vec<std::sync::Arc<dyn sena_providers::AIProvider>>, CliError> {
+    use sena_providers::{ProviderRouter, ProvidersConfig};
+
+    let providers_config = ProvidersConfig::load_or_default();
+    let router = ProviderRouter::from_config(&providers_config)
+        .map_err(|e| CliError::Config(format!("Failed to create provider router: {}", e)))?;
+
+    let providers: Vec<_> = router.available_providers().into_iter().cloned().collect();
+    if providers.is_empty() {
+        return Err(CliError::Config(
+            "No providers available. Check your API keys and configuration.".to_string(),
+        ));
+    }
+    Ok(providers)
+}
+
+async fn collect_devil_responses(
+    prompt: &str,
+    timeout: u64,
+) -> Result<Vec<crate::devil::ProviderResponse>, CliError> {
+    use crate::devil::{DevilCancelToken, DevilConfig, DevilExecutor};
+
+    let executor = DevilExecutor::new(DevilConfig::default().with_timeout(timeout));
+    let responses = executor
+        .collect_responses(prompt, devil_providers()?, &DevilCancelToken::new())
+        .await;
+
+    if responses.is_empty() {
+        return Err(CliError::Provider(
+            "All provider requests failed or timed out".to_string(),
+        ));
+    }
+
+    Ok(responses)
+}
+
+fn execute_config(action: ConfigAction, format: OutputFormat) -> Result<String, CliError> {
+    use crate::output::{Theme, BUILTIN_THEMES};
+
+    match action {
+        ConfigAction::Theme {
+            action: ThemeAction::List,
+        } => {
+            let config = SenaConfig::load().map_err(|e| CliError::Config(e.to_string()))?;
+            let active = config.output.theme.name.clone();
+            let themes: Vec<Theme> = BUILTIN_THEMES
+                .iter()
+                .filter_map(|name| Theme::builtin(name))
+                .collect();
+
+            match format {
+                OutputFormat::Json | OutputFormat::Yaml => {
+                    serde_json::to_string_pretty(&serde_json::json!({
+                        "active": active,
+                        "themes": themes,
+                    }))
+                    .map_err(CliError::from)
+                }
+                OutputFormat::Text => Ok(themes
+                    .iter()
+                    .map(|t| {
+                        let marker = if t.name == active { "*" } else { " " };
+                        format!("{} {}\n", marker, t.name)
+                    })
+                    .collect()),
+                OutputFormat::Pretty => {
+                    let mut table = TableBuilder::new().title("OUTPUT THEMES").row(vec![
+                        "Theme".to_string(),
+                        "Border".to_string(),
+                        "Color".to_string(),
+                        "Unicode".to_string(),
+                    ]);
+                    for theme in &themes {
+                        let name = if theme.name == active {
+                            format!("{} (active)", theme.name)
+                        } else {
+                            theme.name.clone()
+                        };
+                        table = table.row(vec![
+                            name,
+                            format!("{:?}", theme.border).to_lowercase(),
+                            theme.color.to_string(),
+                            theme.unicode.to_string(),
+                        ]);
+                    }
+                    Ok(table.build())
+                }
+            }
+        }
+        ConfigAction::Theme {
+            action: ThemeAction::Set { name },
+        } => {
+            let theme = Theme::builtin(&name).ok_or_else(|| {
+                CliError::Usage(format!(
+                    "Unknown theme '{}'. Available: {}",
+                    name,
+                    BUILTIN_THEMES.join(", ")
+                ))
+            })?;
+            let mut config = SenaConfig::load().map_err(|e| CliError::Config(e.to_string()))?;
+            config.output.theme = crate::output::ThemeConfig::named(&theme.name);
+            config.save().map_err(|e| CliError::Config(e.to_string()))?;
+
+            match format {
+                OutputFormat::Json => serde_json::to_string_pretty(&serde_json::json!({
+                    "success": true,
+                    "theme": theme,
+                }))
+                .map_err(CliError::from),
+                _ => Ok(format!(
+                    "{}\n\n{} Output theme set to '{}'\n",
+                    FormatBox::new(&SenaConfig::brand_title("OUTPUT THEME"))
+                        .theme(theme.clone())
+                        .render(),
+                    theme.status(true),
+                    theme.name
+                )),
+            }
+        }
+    }
+}
+
+async fn execute_devil(action: DevilAction, format: OutputFormat) -> Result<String, CliError> {
+    use crate::devil::{
+        DevilCancelToken, DevilComparison, DevilConfig, DevilExecutor, SynthesisMethod, WaitMode,
+    };
+    use std::time::Duration;
 
     match action {
         DevilAction::Execute {
             prompt,
             timeout,
             synthesis,
+            first,
+            quorum,
         } => {
             let synthesis_method = match synthesis {
                 SynthesisMethodArg::MajorityVoting => SynthesisMethod::MajorityVoting,
@@ -4437,82 +6388,59 @@ async fn execute_devil(action: DevilAction, format: OutputFormat) -> Result<Stri
                 SynthesisMethodArg::CrossVerification => SynthesisMethod::CrossVerification,
             };
 
+            let wait_mode = match (first, quorum) {
+                (Some(n), _) => WaitMode::FirstN(n),
+                (None, Some(fraction)) => WaitMode::Quorum(fraction),
+                (None, None) => WaitMode::WaitForAll,
+            };
             let config = DevilConfig::default()
                 .with_timeout(timeout)
-                .with_synthesis(synthesis_method);
+                .with_synthesis(synthesis_method)
+                .with_wait_mode(wait_mode);
 
             let executor = DevilExecutor::new(config);
-
-            let providers_config = ProvidersConfig::load_or_default();
-            let router = ProviderRouter::from_config(&providers_config)
-                .map_err(|e| format!("Failed to create provider router: {}", e))?;
-
-            let available_providers = router.available_providers();
-
-            if available_providers.is_empty() {
-                return Err(
-                    "No providers available. Check your API keys and configuration.".to_string(),
-                );
-            }
-
-            let request = ChatRequest::new(vec![Message::user(&prompt)]).with_max_tokens(1024);
-
-            let timeout_duration = Duration::from_secs(timeout);
-            let mut handles = Vec::new();
-
-            for provider in available_providers {
-                let provider_id = provider.provider_id().to_string();
-                let model = provider.default_model().to_string();
-                let request_clone = request.clone();
-                let provider_clone = provider.clone();
-
-                let handle = tokio::spawn(async move {
-                    let start = Instant::now();
-                    match tokio::time::timeout(timeout_duration, provider_clone.chat(request_clone))
-                        .await
-                    {
-                        Ok(Ok(response)) => ProviderResponse::success(
-                            provider_id,
-                            response.model,
-                            response.content,
-                            start.elapsed(),
-                        ),
-                        Ok(Err(e)) => ProviderResponse::failure(
-                            provider_id,
-                            model,
-                            e.to_string(),
-                            start.elapsed(),
-                        ),
-                        Err(_) => ProviderResponse::failure(
-                            provider_id,
-                            model,
-                            "Timeout".to_string(),
-                            timeout_duration,
-                        ),
-                    }
-                });
-                handles.push(handle);
-            }
-
-            let mut responses = Vec::new();
-            for handle in handles {
-                if let Ok(response) = handle.await {
-                    responses.push(response);
+            let providers = devil_providers()?;
+
+            // Ctrl-C aborts every in-flight request instead of waiting them out
+            let cancel = DevilCancelToken::new();
+            let on_interrupt = cancel.clone();
+            let interrupt = tokio::spawn(async move {
+                if tokio::signal::ctrl_c().await.is_ok() {
+                    on_interrupt.cancel();
                 }
-            }
-
-            if responses.is_empty() {
-                return Err("All provider requests failed or timed out".to_string());
-            }
+            });
+            let result = executor.execute(&prompt, providers, &cancel).await;
+            interrupt.abort();
 
-            match executor.execute_sync(&prompt, responses) {
+            match result {
                 Ok(response) => match format {
                     OutputFormat::Json => {
-                        serde_json::to_string_pretty(&response).map_err(|e| e.to_string())
+                        serde_json::to_string_pretty(&response).map_err(CliError::from)
                     }
                     _ => Ok(response.format_summary()),
                 },
-                Err(e) => Err(format!("Devil mode execution failed: {}", e)),
+                Err(e) => Err(CliError::Provider(format!(
+                    "Devil mode execution failed: {}",
+                    e
+                ))),
+            }
+        }
+
+        DevilAction::Compare { prompt, timeout } => {
+            let responses = collect_devil_responses(&prompt, timeout).await?;
+            let comparison = DevilComparison::new(&prompt, responses);
+
+            match format {
+                OutputFormat::Json => {
+                    serde_json::to_string_pretty(&comparison).map_err(CliError::from)
+                }
+                _ => {
+                    let mut output =
+                        FormatBox::new(&SenaConfig::brand_title("DEVIL COMPARE")).render();
+                    output.push('\n');
+                    output.push_str(&comparison.format_comparison());
+                    Ok(output)
+                }
             }
         }
 
@@ -4533,15 +6461,14 @@ async fn execute_devil(action: DevilAction, format: OutputFormat) -> Result<Stri
                 })
                 .unwrap_or_default();
 
-            let provider_statuses: Vec<(String, String)> = router
-                .as_ref()
-                .map(|r| {
-                    r.provider_status()
-                        .into_iter()
-                        .map(|(id, s)| (id, format!("{:?}", s)))
-                        .collect()
-                })
-                .unwrap_or_default();
+            let provider_statuses: Vec<(String, String)> = match &router {
+                Some(r) => r
+                    .provider_status()
+                    .into_iter()
+                    .map(|(id, s)| (id, format!("{:?}", s)))
+                    .collect(),
+                None => Vec::new(),
+            };
 
             match format {
                 OutputFormat::Json => {
@@ -4555,7 +6482,7 @@ async fn execute_devil(action: DevilAction, format: OutputFormat) -> Result<Stri
                         "available_providers": available_providers,
                         "provider_statuses": provider_statuses.into_iter().collect::<std::collections::HashMap<_,_>>(),
                     });
-                    serde_json::to_string_pretty(&status).map_err(|e| e.to_string())
+                    serde_json::to_string_pretty(&status).map_err(CliError::from)
                 }
                 _ => {
                     let mut out = String::new();
@@ -4650,7 +6577,7 @@ async fn execute_devil(action: DevilAction, format: OutputFormat) -> Result<Stri
                     out.push_str(&response.format_summary());
                     Ok(out)
                 }
-                Err(e) => Err(format!("Devil mode test failed: {}", e)),
+                Err(e) => Err(CliError::Provider(format!("Devil mode test failed: {}", e))),
             }
         }
     }
@@ -0,0 +1,85 @@
+//! CLI Error Types
+//!
+//! Maps command failures to process exit codes so scripts can tell
+//! failure kinds apart:
+//!
+//! | Code | Meaning                                   |
+//! |------|-------------------------------------------|
+//! | 0    | Success                                   |
+//! | 1    | General failure                           |
+//! | 2    | Usage or validation error                 |
+//! | 3    | Provider or network failure               |
+//! | 4    | Blocked by the guardian middleware        |
+//! | 5    | Configuration or setup failure            |
+//!
+//! Handlers raise the variant that matches the failure; plain `String`
+//! errors convert to `General`.
+
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum CliError {
+    #[error("{0}")]
+    General(String),
+    #[error("{0}")]
+    Usage(String),
+    #[error("{0}")]
+    Provider(String),
+    #[error("{0}")]
+    GuardianBlocked(String),
+    #[error("{0}")]
+    Config(String),
+}
+
+impl CliError {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CliError::General(_) => 1,
+            CliError::Usage(_) => 2,
+            CliError::Provider(_) => 3,
+            CliError::GuardianBlocked(_) => 4,
+            CliError::Config(_) => 5,
+        }
+    }
+}
+
+impl From<String> for CliError {
+    fn from(message: String) -> Self {
+        CliError::General(message)
+    }
+}
+
+impl From<&str> for CliError {
+    fn from(message: &str) -> Self {
+        CliError::General(message.to_string())
+    }
+}
+
+impl From<serde_json::Error> for CliError {
+    fn from(error: serde_json::Error) -> Self {
+        CliError::General(error.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_codes_are_distinct() {
+        let codes = [
+            CliError::General(String::new()).exit_code(),
+            CliError::Usage(String::new()).exit_code(),
+            CliError::Provider(String::new()).exit_code(),
+            CliError::GuardianBlocked(String::new()).exit_code(),
+            CliError::Config(String::new()).exit_code(),
+        ];
+        assert_eq!(codes, [1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_untyped_errors_are_general() {
+        assert_eq!(CliError::from("oops".to_string()).exit_code(), 1);
+        assert_eq!(CliError::from("oops").exit_code(), 1);
+    }
+}
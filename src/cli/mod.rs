@@ -4,6 +4,10 @@
 
 pub mod args;
 pub mod commands;
+pub mod error;
+pub mod output;
 
 pub use args::{Cli, Commands, HookType};
 pub use commands::execute_command;
+pub use error::CliError;
+pub use output::CommandOutput;
@@ -0,0 +1,94 @@
+//! Command Output
+//!
+//! What a command handler produced, serialized once in `execute_command`
+//! so every command honours `--format`
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::cli::args::OutputFormat;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CommandOutput {
+    /// Rendered output. Structured formats use it as-is when it already is
+    /// JSON, and wrap it as `{"output": ...}` otherwise.
+    Text(String),
+    /// A structured value and how to show it to a person
+    Data { value: Value, text: String },
+}
+
+impl CommandOutput {
+    pub fn data<T: Serialize>(value: &T, text: impl Into<String>) -> Result<Self, String> {
+        Ok(CommandOutput::Data {
+            value: serde_json::to_value(value).map_err(|e| e.to_string())?,
+            text: text.into(),
+        })
+    }
+
+    /// A one-line result, `{"message": ...}` in structured formats
+    pub fn message(text: impl Into<String>) -> Self {
+        let text = text.into();
+        CommandOutput::Data {
+            value: serde_json::json!({ "message": text }),
+            text,
+        }
+    }
+
+    /// The structured form of this output
+    pub fn value(&self) -> Value {
+        match self {
+            CommandOutput::Data { value, .. } => value.clone(),
+            CommandOutput::Text(text) => {
+                serde_json::from_str(text).unwrap_or_else(|_| serde_json::json!({ "output": text }))
+            }
+        }
+    }
+
+    pub fn render(self, format: OutputFormat) -> Result<String, String> {
+        match format {
+            OutputFormat::Json => {
+                serde_json::to_string_pretty(&self.value()).map_err(|e| e.to_string())
+            }
+            OutputFormat::Yaml => serde_yaml::to_string(&self.value()).map_err(|e| e.to_string()),
+            OutputFormat::Text | OutputFormat::Pretty => Ok(match self {
+                CommandOutput::Text(text) | CommandOutput::Data { text, .. } => text,
+            }),
+        }
+    }
+}
+
+impl From<String> for CommandOutput {
+    fn from(text: String) -> Self {
+        CommandOutput::Text(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_formats() {
+        let output =
+            CommandOutput::data(&serde_json::json!({ "sessions": 2 }), "2 sessions").unwrap();
+        assert_eq!(
+            output.clone().render(OutputFormat::Text).unwrap(),
+            "2 sessions"
+        );
+        let json: Value =
+            serde_json::from_str(&output.clone().render(OutputFormat::Json).unwrap()).unwrap();
+        assert_eq!(json["sessions"], 2);
+        assert_eq!(output.render(OutputFormat::Yaml).unwrap(), "sessions: 2\n");
+
+        let already_json = CommandOutput::from(r#"{"ok": true}"#.to_string());
+        assert_eq!(already_json.value()["ok"], true);
+        let plain = CommandOutput::from("No changes".to_string());
+        assert_eq!(plain.value()["output"], "No changes");
+        assert_eq!(
+            CommandOutput::message("Hub stopped.")
+                .render(OutputFormat::Yaml)
+                .unwrap(),
+            "message: Hub stopped.\n"
+        );
+    }
+}
@@ -1,9 +1,13 @@
+use arc_swap::ArcSwap;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
-use std::sync::OnceLock;
+use std::sync::{Arc, OnceLock};
 
-static GLOBAL_CONFIG: OnceLock<SenaConfig> = OnceLock::new();
+use crate::output::ThemeConfig;
+use crate::sync::write_atomic;
+
+static GLOBAL_CONFIG: OnceLock<ArcSwap<SenaConfig>> = OnceLock::new();
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct SenaConfig {
@@ -19,6 +23,16 @@ pub struct SenaConfig {
     pub hub: HubConfig,
     #[serde(default)]
     pub output: OutputConfig,
+    #[serde(default)]
+    pub tools: ToolsConfig,
+    #[serde(default)]
+    pub search: SearchConfig,
+    #[serde(default)]
+    pub guardian: GuardianLogConfig,
+    #[serde(default)]
+    pub memory: MemoryConfig,
+    #[serde(default)]
+    pub daemon: DaemonConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -124,6 +138,15 @@ pub struct HubConfig {
     pub auto_start: bool,
     #[serde(default = "default_timeout")]
     pub timeout_seconds: u64,
+    #[serde(default = "default_hub_rate_limit")]
+    pub rate_limit_per_minute: u32,
+    /// Refuse to let a session work on a file another live session has
+    /// locked, instead of only warning
+    #[serde(default)]
+    pub lock_files: bool,
+    /// Seconds after the holder's last heartbeat before a file lock lapses
+    #[serde(default = "default_hub_lock_ttl")]
+    pub lock_ttl_seconds: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -134,6 +157,106 @@ pub struct OutputConfig {
     pub unicode: bool,
     #[serde(default = "default_true")]
     pub progress_bars: bool,
+    #[serde(default)]
+    pub theme: ThemeConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolsConfig {
+    #[serde(default = "default_true")]
+    pub sandbox: bool,
+    #[serde(default)]
+    pub root: Option<String>,
+    #[serde(default = "default_tool_history_limit")]
+    pub history_limit: usize,
+    #[serde(default)]
+    pub persist_history: bool,
+    #[serde(default = "default_max_tool_iterations")]
+    pub max_tool_iterations: usize,
+}
+
+impl ToolsConfig {
+    /// Directory file system tools are confined to, or `None` when sandboxing is off
+    pub fn sandbox_root(&self) -> Option<PathBuf> {
+        if !self.sandbox {
+            return None;
+        }
+        match &self.root {
+            Some(root) => Some(PathBuf::from(root)),
+            None => std::env::current_dir().ok(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GuardianLogConfig {
+    /// Append hallucination checks to `~/.sena/guardian/hallucinations.jsonl`
+    #[serde(default = "default_true")]
+    pub log_hallucinations: bool,
+    /// Keep the checked text in the log; otherwise only its hash is stored
+    #[serde(default)]
+    pub store_content: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryConfig {
+    /// Decay and evict once the store holds more memories than this; 0 disables
+    #[serde(default = "default_memory_max_entries")]
+    pub max_entries: usize,
+    #[serde(default = "default_memory_half_life_days")]
+    pub decay_half_life_days: f64,
+    /// Memories whose importance decays below this are evicted first
+    #[serde(default = "default_memory_min_importance")]
+    pub min_importance: f64,
+}
+
+impl MemoryConfig {
+    pub fn retention(&self) -> crate::memory::MemoryRetention {
+        crate::memory::MemoryRetention {
+            max_entries: self.max_entries,
+            half_life_days: self.decay_half_life_days,
+            min_importance: self.min_importance,
+        }
+    }
+}
+
+/// How often the daemon runs each of its periodic tasks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaemonConfig {
+    #[serde(default = "default_health_interval")]
+    pub health_interval_secs: u64,
+    #[serde(default = "default_memory_decay_interval")]
+    pub memory_decay_interval_secs: u64,
+    #[serde(default = "default_evolution_interval")]
+    pub evolution_interval_secs: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchConfig {
+    #[serde(default = "default_search_provider")]
+    pub provider: String,
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    #[serde(default)]
+    pub api_key: Option<String>,
+}
+
+impl SearchConfig {
+    /// API key from config, falling back to the provider's environment variable
+    pub fn api_key(&self) -> Option<String> {
+        self.api_key
+            .clone()
+            .or_else(|| std::env::var("SENA_SEARCH_API_KEY").ok())
+            .or_else(|| match self.provider.to_lowercase().as_str() {
+                "brave" => std::env::var("BRAVE_SEARCH_API_KEY").ok(),
+                _ => None,
+            })
+            .filter(|key| !key.trim().is_empty())
+    }
+}
+
+fn default_search_provider() -> String {
+    "duckduckgo".to_string()
 }
 
 fn default_log_level() -> String {
@@ -160,10 +283,50 @@ fn default_timeout() -> u64 {
     30
 }
 
+fn default_hub_lock_ttl() -> u64 {
+    crate::hub::DEFAULT_LOCK_TTL_SECS
+}
+
+fn default_hub_rate_limit() -> u32 {
+    60
+}
+
+fn default_memory_max_entries() -> usize {
+    crate::memory::DEFAULT_MAX_ENTRIES
+}
+
+fn default_memory_half_life_days() -> f64 {
+    crate::memory::DEFAULT_HALF_LIFE_DAYS
+}
+
+fn default_memory_min_importance() -> f64 {
+    crate::memory::DEFAULT_MIN_IMPORTANCE
+}
+
+fn default_health_interval() -> u64 {
+    60
+}
+
+fn default_memory_decay_interval() -> u64 {
+    60 * 60
+}
+
+fn default_evolution_interval() -> u64 {
+    24 * 60 * 60
+}
+
 fn default_true() -> bool {
     true
 }
 
+fn default_tool_history_limit() -> usize {
+    crate::tools::DEFAULT_HISTORY_LIMIT
+}
+
+fn default_max_tool_iterations() -> usize {
+    crate::tools::DEFAULT_MAX_TOOL_ITERATIONS
+}
+
 impl Default for UserConfig {
     fn default() -> Self {
         Self {
@@ -212,6 +375,60 @@ impl Default for HubConfig {
             socket_path: default_socket_path(),
             auto_start: true,
             timeout_seconds: default_timeout(),
+            rate_limit_per_minute: default_hub_rate_limit(),
+            lock_files: false,
+            lock_ttl_seconds: default_hub_lock_ttl(),
+        }
+    }
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            provider: default_search_provider(),
+            endpoint: None,
+            api_key: None,
+        }
+    }
+}
+
+impl Default for GuardianLogConfig {
+    fn default() -> Self {
+        Self {
+            log_hallucinations: true,
+            store_content: false,
+        }
+    }
+}
+
+impl Default for MemoryConfig {
+    fn default() -> Self {
+        Self {
+            max_entries: default_memory_max_entries(),
+            decay_half_life_days: default_memory_half_life_days(),
+            min_importance: default_memory_min_importance(),
+        }
+    }
+}
+
+impl Default for DaemonConfig {
+    fn default() -> Self {
+        Self {
+            health_interval_secs: default_health_interval(),
+            memory_decay_interval_secs: default_memory_decay_interval(),
+            evolution_interval_secs: default_evolution_interval(),
+        }
+    }
+}
+
+impl Default for ToolsConfig {
+    fn default() -> Self {
+        Self {
+            sandbox: true,
+            root: None,
+            history_limit: default_tool_history_limit(),
+            persist_history: false,
+            max_tool_iterations: default_max_tool_iterations(),
         }
     }
 }
@@ -222,6 +439,7 @@ impl Default for OutputConfig {
             color: true,
             unicode: true,
             progress_bars: true,
+            theme: ThemeConfig::default(),
         }
     }
 }
@@ -257,7 +475,7 @@ impl SenaConfig {
         let content =
             toml::to_string_pretty(self).map_err(|e| ConfigError::SerializeError(e.to_string()))?;
 
-        fs::write(&path, content).map_err(|e| ConfigError::WriteError(e.to_string()))
+        write_atomic(&path, content).map_err(|e| ConfigError::WriteError(e.to_string()))
     }
 
     pub fn data_dir(&self) -> PathBuf {
@@ -278,12 +496,36 @@ impl SenaConfig {
         toml::to_string_pretty(&config).unwrap_or_default()
     }
 
-    pub fn global() -> &'static SenaConfig {
-        GLOBAL_CONFIG.get_or_init(|| Self::load().unwrap_or_default())
+    fn shared() -> &'static ArcSwap<SenaConfig> {
+        GLOBAL_CONFIG.get_or_init(|| ArcSwap::from_pointee(Self::load().unwrap_or_default()))
+    }
+
+    /// The config file as last loaded; see [`reload`](Self::reload)
+    pub fn global() -> Arc<SenaConfig> {
+        Self::shared().load_full()
     }
 
-    pub fn user() -> &'static UserConfig {
-        &Self::global().user
+    /// Read the config file again and make it the global config, returning
+    /// the one it replaced. Callers holding the old config keep it; later
+    /// calls to [`global`](Self::global) see the new one.
+    pub fn reload() -> Result<Arc<SenaConfig>, ConfigError> {
+        let config = Self::load()?;
+        Ok(Self::shared().swap(Arc::new(config)))
+    }
+
+    /// Settings that differ from `other`, one `section.key: old -> new`
+    /// line each. Secrets are reported as changed without their values.
+    pub fn changes_from(&self, other: &SenaConfig) -> Vec<String> {
+        let (Ok(old), Ok(new)) = (toml::Value::try_from(other), toml::Value::try_from(self)) else {
+            return Vec::new();
+        };
+        let mut changes = Vec::new();
+        diff_values("", Some(&old), Some(&new), &mut changes);
+        changes
+    }
+
+    pub fn user() -> UserConfig {
+        Self::global().user.clone()
     }
 
     pub fn brand() -> String {
@@ -295,6 +537,45 @@ impl SenaConfig {
     }
 }
 
+/// Settings whose values never appear in a change report
+const SECRET_FIELDS: &[&str] = &["api_key", "token", "password", "secret", "passkey"];
+
+fn diff_values(
+    path: &str,
+    old: Option<&toml::Value>,
+    new: Option<&toml::Value>,
+    changes: &mut Vec<String>,
+) {
+    if let (Some(toml::Value::Table(old)), Some(toml::Value::Table(new))) = (old, new) {
+        let mut keys: Vec<&String> = old.keys().chain(new.keys()).collect();
+        keys.sort();
+        keys.dedup();
+        for key in keys {
+            let path = if path.is_empty() {
+                key.clone()
+            } else {
+                format!("{}.{}", path, key)
+            };
+            diff_values(&path, old.get(key), new.get(key), changes);
+        }
+        return;
+    }
+    if old == new {
+        return;
+    }
+    let show = |value: Option<&toml::Value>| {
+        value.map_or("unset".to_string(), |v| {
+            sena_providers::redact(&v.to_string())
+        })
+    };
+    let key = path.rsplit('.').next().unwrap_or(path);
+    if SECRET_FIELDS.iter().any(|secret| key.ends_with(secret)) {
+        changes.push(format!("{}: changed", path));
+    } else {
+        changes.push(format!("{}: {} -> {}", path, show(old), show(new)));
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum ConfigError {
     ReadError(String),
@@ -350,6 +631,27 @@ color = false
         assert!(!config.output.color);
     }
 
+    #[test]
+    fn test_changes_from_lists_changed_settings() {
+        let old = SenaConfig::default();
+        let mut new = old.clone();
+        new.general.log_level = "debug".to_string();
+        new.daemon.health_interval_secs = 30;
+        new.search.api_key = Some("secret".to_string());
+        new.search.endpoint = Some("https://search.example/v1?key=abcdef123".to_string());
+
+        assert_eq!(
+            new.changes_from(&old),
+            vec![
+                "daemon.health_interval_secs: 60 -> 30",
+                "general.log_level: \"info\" -> \"debug\"",
+                "search.api_key: changed",
+                "search.endpoint: unset -> \"https://search.example/v1?key=[REDACTED]\"",
+            ]
+        );
+        assert!(old.changes_from(&old).is_empty());
+    }
+
     #[test]
     fn test_generate_default_config() {
         let content = SenaConfig::generate_default_config();
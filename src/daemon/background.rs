@@ -1,9 +1,11 @@
+use super::schedule::{PeriodicJob, ScheduledTaskInfo, Scheduler};
 use crate::intelligence::autonomous::{AgentExecution, AutonomousAgent};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::{Arc, RwLock};
+use std::time::Duration;
 use tokio::sync::mpsc;
 use uuid::Uuid;
 
@@ -79,6 +81,7 @@ pub struct BackgroundAgentManager {
     tasks: Arc<RwLock<HashMap<String, BackgroundTask>>>,
     command_tx: Option<mpsc::Sender<TaskCommand>>,
     result_rx: Option<mpsc::Receiver<TaskResult>>,
+    scheduler: Arc<RwLock<Scheduler>>,
 }
 
 impl BackgroundAgentManager {
@@ -87,9 +90,82 @@ impl BackgroundAgentManager {
             tasks: Arc::new(RwLock::new(HashMap::new())),
             command_tx: None,
             result_rx: None,
+            scheduler: Arc::new(RwLock::new(Scheduler::new())),
         }
     }
 
+    /// Keep periodic tasks' last-run times in `path` across restarts
+    pub fn with_schedule_file(mut self, path: PathBuf) -> Self {
+        self.scheduler = Arc::new(RwLock::new(Scheduler::with_state_file(path)));
+        self
+    }
+
+    /// Run `job` every `interval`, starting at the next
+    /// [`run_due_periodic`](Self::run_due_periodic) unless a saved last run
+    /// says it isn't due yet
+    pub fn register_periodic(&self, name: &str, interval: Duration, job: PeriodicJob) {
+        if let Ok(mut scheduler) = self.scheduler.write() {
+            scheduler.register(name, interval, job);
+        }
+    }
+
+    /// Change how often `name` runs without losing when it last ran.
+    /// Returns whether the interval changed.
+    pub fn set_periodic_interval(&self, name: &str, interval: Duration) -> bool {
+        self.scheduler
+            .write()
+            .map(|mut scheduler| scheduler.set_interval(name, interval))
+            .unwrap_or(false)
+    }
+
+    /// Run every periodic task that is due, one after another. Returns each
+    /// task's name and outcome.
+    pub async fn run_due_periodic(&self) -> Vec<(String, Result<(), String>)> {
+        self.run_due_periodic_at(Utc::now()).await
+    }
+
+    /// [`Self::run_due_periodic`] as of `now`
+    pub async fn run_due_periodic_at(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Vec<(String, Result<(), String>)> {
+        let due = match self.scheduler.read() {
+            Ok(scheduler) => scheduler.due(now),
+            Err(_) => return Vec::new(),
+        };
+
+        let mut results = Vec::new();
+        for (name, job) in due {
+            let result = job().await;
+            if let Ok(mut scheduler) = self.scheduler.write() {
+                if let Err(e) = scheduler.mark_run(&name, now) {
+                    log::warn!("Could not save schedule: {}", e);
+                }
+            }
+            results.push((name, result));
+        }
+        results
+    }
+
+    /// Time until the next periodic task is due; `None` without any
+    pub fn until_next_periodic(&self) -> Option<Duration> {
+        self.until_next_periodic_at(Utc::now())
+    }
+
+    /// [`Self::until_next_periodic`] as of `now`
+    pub fn until_next_periodic_at(&self, now: DateTime<Utc>) -> Option<Duration> {
+        let next = self.scheduler.read().ok()?.next_due()?;
+        Some((next - now).to_std().unwrap_or(Duration::ZERO))
+    }
+
+    /// Periodic tasks, soonest first
+    pub fn scheduled_tasks(&self) -> Vec<ScheduledTaskInfo> {
+        self.scheduler
+            .read()
+            .map(|scheduler| scheduler.tasks())
+            .unwrap_or_default()
+    }
+
     pub async fn start(&mut self) -> Result<(), String> {
         let (cmd_tx, mut cmd_rx) = mpsc::channel::<TaskCommand>(100);
         let (result_tx, result_rx) = mpsc::channel::<TaskResult>(100);
@@ -352,6 +428,7 @@ impl TaskQueue {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
 
     #[test]
     fn test_background_task_creation() {
@@ -391,4 +468,48 @@ mod tests {
         assert_eq!(manager.pending_count(), 0);
         assert_eq!(manager.running_count(), 0);
     }
+
+    #[tokio::test]
+    async fn test_periodic_task_fires_on_schedule() {
+        let manager = BackgroundAgentManager::new();
+        let runs = Arc::new(AtomicUsize::new(0));
+        let recorded = Arc::clone(&runs);
+        manager.register_periodic(
+            "tick",
+            Duration::from_secs(1),
+            super::super::schedule::job(move || {
+                let recorded = Arc::clone(&recorded);
+                async move {
+                    recorded.fetch_add(1, Ordering::SeqCst);
+                    Ok(())
+                }
+            }),
+        );
+
+        let start = Utc::now();
+        let mut now = start;
+        let mut run_times = Vec::new();
+        while now - start < chrono::Duration::milliseconds(2500) {
+            if !manager.run_due_periodic_at(now).await.is_empty() {
+                run_times.push(now - start);
+            }
+            let wait = manager.until_next_periodic_at(now).unwrap();
+            now += chrono::Duration::from_std(wait).unwrap();
+        }
+
+        assert_eq!(runs.load(Ordering::SeqCst), 3);
+        assert_eq!(
+            run_times,
+            vec![
+                chrono::Duration::zero(),
+                chrono::Duration::seconds(1),
+                chrono::Duration::seconds(2),
+            ]
+        );
+        assert_eq!(
+            manager.until_next_periodic_at(start + chrono::Duration::milliseconds(2500)),
+            Some(Duration::from_millis(500))
+        );
+        assert_eq!(manager.scheduled_tasks()[0].interval_secs, 1);
+    }
 }
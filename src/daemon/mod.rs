@@ -3,15 +3,24 @@
 //! Background daemon for services
 
 pub mod background;
+pub mod schedule;
 
 pub use background::{BackgroundAgentManager, BackgroundTask, TaskQueue, TaskStatus};
+pub use schedule::{PeriodicJob, ScheduledTaskInfo, Scheduler};
 
-use crate::config::SenaConfig;
+use crate::config::{DaemonConfig, SenaConfig};
 use std::fs;
 use std::io::Write;
 use std::path::PathBuf;
 #[cfg(unix)]
 use std::process::{Command, Stdio};
+use std::time::Duration;
+
+/// Longest the daemon loop sleeps before checking the PID file again
+const PID_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How long `start` and `stop` wait for the daemon to come up or go away
+const DETACH_TIMEOUT: Duration = Duration::from_secs(5);
 
 /// PID file location
 fn pid_file() -> PathBuf {
@@ -25,32 +34,50 @@ fn log_file() -> PathBuf {
     PathBuf::from(home).join(".claude").join("sena_daemon.log")
 }
 
+/// Last-run times of the daemon's periodic tasks
+fn schedule_file() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(home)
+        .join(".claude")
+        .join("sena_daemon_schedule.json")
+}
+
 /// Check if daemon is running
 pub fn is_running() -> bool {
-    if let Ok(pid_str) = fs::read_to_string(pid_file()) {
-        #[cfg(unix)]
-        {
-            if let Ok(pid) = pid_str.trim().parse::<i32>() {
-                let result = Command::new("kill")
-                    .args(["-0", &pid.to_string()])
-                    .stdout(Stdio::null())
-                    .stderr(Stdio::null())
-                    .status();
-
-                return result.map(|s| s.success()).unwrap_or(false);
-            }
-        }
+    read_pid().is_some_and(process_alive)
+}
 
-        #[cfg(not(unix))]
-        {
-            let _ = pid_str;
+/// PID recorded in the PID file
+fn read_pid() -> Option<i32> {
+    fs::read_to_string(pid_file()).ok()?.trim().parse().ok()
+}
+
+/// Whether `pid` names a live process. One owned by another user still
+/// counts: signal 0 is refused with EPERM, not ESRCH.
+fn process_alive(pid: i32) -> bool {
+    #[cfg(unix)]
+    {
+        // 0 and negative PIDs address process groups, never one daemon
+        if pid <= 0 {
+            return false;
+        }
+        // SAFETY: signal 0 only checks that the process exists
+        if unsafe { libc::kill(pid, 0) } == 0 {
             return true;
         }
+        std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = pid;
+        true
     }
-    false
 }
 
-pub async fn start_daemon() -> Result<String, String> {
+/// Start the daemon. On Unix it detaches from the terminal unless
+/// `foreground` is set; elsewhere it always runs in the foreground.
+pub async fn start_daemon(foreground: bool) -> Result<String, String> {
     let brand = SenaConfig::brand();
     if is_running() {
         return Err(format!("{} daemon is already running", brand));
@@ -61,29 +88,96 @@ pub async fn start_daemon() -> Result<String, String> {
         fs::create_dir_all(parent).map_err(|e| format!("Cannot create directory: {}", e))?;
     }
 
-    // For actual daemon mode, we'd fork here
-    // For simplicity, we just record the intent and run in foreground
-    let pid = std::process::id();
+    #[cfg(unix)]
+    if !foreground {
+        let pid = spawn_detached()?;
+        return Ok(format!("{} daemon started (PID: {})", brand, pid));
+    }
+    let _ = foreground;
+
+    run_foreground().await?;
+    Ok(format!("{} daemon stopped", brand))
+}
 
+/// Run the daemon loop in this process, recording its PID while it runs
+async fn run_foreground() -> Result<(), String> {
+    let brand = SenaConfig::brand();
+    let pid = std::process::id();
     fs::write(pid_file(), pid.to_string()).map_err(|e| format!("Cannot write PID file: {}", e))?;
+    log_line(&format!("{} daemon started (PID: {})", brand, pid));
+
+    set_log_level(&SenaConfig::global().general.log_level);
 
-    // Log startup
-    let mut log = fs::OpenOptions::new()
+    let result = run_daemon_loop().await;
+
+    // Leave a PID file alone once another daemon has taken it over
+    if read_pid() == Some(pid as i32) {
+        let _ = fs::remove_file(pid_file());
+    }
+    log_line(&format!("{} daemon stopped", brand));
+    result
+}
+
+/// Start `sena daemon start --foreground` detached from this terminal and
+/// return its PID once it has written it to the PID file.
+///
+/// The child calls `setsid` to leave our session, then forks again and the
+/// intermediate process exits, so the daemon is not a session leader and
+/// can never acquire a controlling terminal. Forking happens between fork
+/// and exec of a fresh process, not in this one, whose async runtime would
+/// not survive a fork. Output goes to the log file.
+#[cfg(unix)]
+fn spawn_detached() -> Result<i32, String> {
+    use std::os::unix::process::CommandExt;
+
+    let exe = std::env::current_exe().map_err(|e| format!("Cannot find executable: {}", e))?;
+    let log = fs::OpenOptions::new()
         .create(true)
         .append(true)
         .open(log_file())
         .map_err(|e| format!("Cannot open log: {}", e))?;
+    let log_err = log
+        .try_clone()
+        .map_err(|e| format!("Cannot open log: {}", e))?;
 
-    writeln!(
-        log,
-        "[{}] {} daemon started (PID: {})",
-        chrono::Utc::now().format("%Y-%m-%d %H:%M:%S"),
-        brand,
-        pid
-    )
-    .ok();
+    let mut command = Command::new(exe);
+    command
+        .args(["daemon", "start", "--foreground"])
+        .stdin(Stdio::null())
+        .stdout(log)
+        .stderr(log_err);
+    // SAFETY: only async-signal-safe calls (setsid, fork, _exit) run
+    // between fork and exec
+    unsafe {
+        command.pre_exec(|| {
+            if libc::setsid() == -1 {
+                return Err(std::io::Error::last_os_error());
+            }
+            match libc::fork() {
+                -1 => Err(std::io::Error::last_os_error()),
+                0 => Ok(()),
+                _ => libc::_exit(0),
+            }
+        });
+    }
 
-    Ok(format!("{} daemon started (PID: {})", brand, pid))
+    let mut intermediate = command
+        .spawn()
+        .map_err(|e| format!("Cannot start daemon: {}", e))?;
+    let _ = intermediate.wait();
+
+    let deadline = std::time::Instant::now() + DETACH_TIMEOUT;
+    while std::time::Instant::now() < deadline {
+        if let Some(pid) = read_pid().filter(|pid| process_alive(*pid)) {
+            return Ok(pid);
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    Err(format!(
+        "Daemon did not start within {}s; see {}",
+        DETACH_TIMEOUT.as_secs(),
+        log_file().display()
+    ))
 }
 
 pub async fn stop_daemon() -> Result<String, String> {
@@ -93,44 +187,43 @@ pub async fn stop_daemon() -> Result<String, String> {
         return Err(format!("{} daemon is not running", brand));
     }
 
-    let pid_str =
-        fs::read_to_string(pid_file()).map_err(|e| format!("Cannot read PID file: {}", e))?;
+    let pid = read_pid().ok_or_else(|| "Invalid PID file".to_string())?;
 
-    let pid: i32 = pid_str
-        .trim()
-        .parse()
-        .map_err(|e| format!("Invalid PID: {}", e))?;
-
-    // Send SIGTERM
+    // Send SIGTERM; a daemon that exited meanwhile (ESRCH) is stopped too
     #[cfg(unix)]
     {
-        let result = Command::new("kill")
-            .args(["-15", &pid.to_string()])
-            .status();
-
-        match result {
-            Ok(status) if status.success() => {}
-            Ok(_) => return Err("Failed to stop daemon".to_string()),
-            Err(e) => return Err(format!("Cannot send signal: {}", e)),
+        // SAFETY: `pid` is positive, as `is_running` checked, so this
+        // signals only that process
+        if unsafe { libc::kill(pid, libc::SIGTERM) } == -1 {
+            let err = std::io::Error::last_os_error();
+            match err.raw_os_error() {
+                Some(libc::ESRCH) => {}
+                Some(libc::EPERM) => {
+                    return Err(format!(
+                        "Not permitted to stop {} daemon (PID: {})",
+                        brand, pid
+                    ))
+                }
+                _ => return Err(format!("Cannot send signal: {}", err)),
+            }
         }
     }
 
-    // Remove PID file
-    fs::remove_file(pid_file()).map_err(|e| format!("Cannot remove PID file: {}", e))?;
-
-    if let Ok(mut log) = fs::OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(log_file())
+    // The daemon removes the PID file itself as it exits; wait for that so
+    // a restart doesn't find it still running
+    #[cfg(unix)]
     {
-        writeln!(
-            log,
-            "[{}] {} daemon stopped",
-            chrono::Utc::now().format("%Y-%m-%d %H:%M:%S"),
-            brand
-        )
-        .ok();
+        let deadline = std::time::Instant::now() + DETACH_TIMEOUT;
+        while process_alive(pid) && std::time::Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        if process_alive(pid) {
+            return Err(format!("{} daemon (PID: {}) did not stop", brand, pid));
+        }
     }
+    // Elsewhere there is no signal to send; the daemon loop stops once it
+    // finds its PID file gone
+    let _ = fs::remove_file(pid_file());
 
     Ok(format!("{} daemon stopped (was PID: {})", brand, pid))
 }
@@ -140,7 +233,16 @@ pub async fn daemon_status() -> Result<String, String> {
     if is_running() {
         let pid = fs::read_to_string(pid_file()).unwrap_or_else(|_| "unknown".to_string());
 
-        Ok(format!("{} daemon is running (PID: {})", brand, pid.trim()))
+        let mut status = format!("{} daemon is running (PID: {})", brand, pid.trim());
+        for task in schedule::load_state(&schedule_file()) {
+            status.push_str(&format!(
+                "\n  {:<12} every {:>6}s, next run {}",
+                task.name,
+                task.interval_secs,
+                task.next_run.format("%Y-%m-%d %H:%M:%S")
+            ));
+        }
+        Ok(status)
     } else {
         Ok(format!("{} daemon is not running", brand))
     }
@@ -150,6 +252,19 @@ pub async fn run_daemon_loop() -> Result<(), String> {
     let brand = SenaConfig::brand();
     eprintln!("{} daemon running...", brand);
 
+    let manager = BackgroundAgentManager::new().with_schedule_file(schedule_file());
+    register_periodic_tasks(&manager, &SenaConfig::global().daemon);
+
+    // `stop_daemon` sends SIGTERM; finish the current iteration and return
+    // rather than die mid-task
+    #[cfg(unix)]
+    let mut terminate = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+        .map_err(|e| format!("Cannot handle SIGTERM: {}", e))?;
+    // SIGHUP re-reads the config file
+    #[cfg(unix)]
+    let mut hangup = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())
+        .map_err(|e| format!("Cannot handle SIGHUP: {}", e))?;
+
     // Main daemon loop
     loop {
         // Check for shutdown signal
@@ -158,23 +273,137 @@ pub async fn run_daemon_loop() -> Result<(), String> {
             break;
         }
 
-        // Perform periodic tasks
-        perform_periodic_tasks().await;
+        for (name, result) in manager.run_due_periodic().await {
+            if let Err(e) = result {
+                log_line(&format!("Task {} failed: {}", name, e));
+            }
+        }
 
-        // Sleep for 5 seconds between iterations
-        tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+        // Sleep until the next task is due, waking in time to notice the
+        // PID file going away
+        let wait = manager
+            .until_next_periodic()
+            .unwrap_or(PID_CHECK_INTERVAL)
+            .min(PID_CHECK_INTERVAL);
+
+        #[cfg(unix)]
+        tokio::select! {
+            _ = tokio::time::sleep(wait) => {}
+            _ = terminate.recv() => {
+                eprintln!("Received SIGTERM, shutting down...");
+                break;
+            }
+            _ = hangup.recv() => reload_config(&manager),
+            _ = tokio::signal::ctrl_c() => break,
+        }
+        #[cfg(not(unix))]
+        tokio::select! {
+            _ = tokio::time::sleep(wait) => {}
+            _ = tokio::signal::ctrl_c() => break,
+        }
     }
 
     Ok(())
 }
 
-/// Perform periodic daemon tasks
-async fn perform_periodic_tasks() {
-    // Health check
-    let health = crate::metrics::SenaHealth::new();
-    let report = health.get_health();
+/// Re-read the config file and apply what a running daemon can change: the
+/// log level and its task intervals. A config that fails to load leaves the
+/// current one in place.
+fn reload_config(manager: &BackgroundAgentManager) {
+    let previous = match SenaConfig::reload() {
+        Ok(previous) => previous,
+        Err(e) => {
+            log_line(&format!(
+                "Config reload failed, keeping current config: {}",
+                e
+            ));
+            return;
+        }
+    };
+    let config = SenaConfig::global();
+    let changes = config.changes_from(&previous);
+    if changes.is_empty() {
+        log_line("Config reloaded, nothing changed");
+    }
+    for change in &changes {
+        log_line(&format!("Config reloaded: {}", change));
+    }
+    apply_config(manager, &config);
+}
+
+fn apply_config(manager: &BackgroundAgentManager, config: &SenaConfig) {
+    set_log_level(&config.general.log_level);
+    for (name, interval) in periodic_intervals(&config.daemon) {
+        manager.set_periodic_interval(name, interval);
+    }
+}
+
+fn set_log_level(level: &str) {
+    match level.parse::<log::LevelFilter>() {
+        Ok(filter) => log::set_max_level(filter),
+        Err(_) => log_line(&format!(
+            "Unknown log level {:?}, keeping {}",
+            level,
+            log::max_level()
+        )),
+    }
+}
+
+/// Each periodic task's interval, never under a second
+fn periodic_intervals(config: &DaemonConfig) -> [(&'static str, Duration); 3] {
+    let secs = |secs: u64| Duration::from_secs(secs.max(1));
+    [
+        ("health", secs(config.health_interval_secs)),
+        ("memory-decay", secs(config.memory_decay_interval_secs)),
+        ("evolution", secs(config.evolution_interval_secs)),
+    ]
+}
+
+/// The daemon's periodic tasks
+fn register_periodic_tasks(manager: &BackgroundAgentManager, config: &DaemonConfig) {
+    let [health, memory_decay, evolution] = periodic_intervals(config).map(|(_, i)| i);
+
+    manager.register_periodic(
+        "health",
+        health,
+        schedule::job(|| async {
+            let report = crate::metrics::SenaHealth::new().get_health();
+            log_line(&format!(
+                "Health check: {} ({}%)",
+                report.overall_status, report.metrics.overall_health_percentage
+            ));
+            Ok(())
+        }),
+    );
+
+    manager.register_periodic(
+        "memory-decay",
+        memory_decay,
+        schedule::job(|| async {
+            let mut memory = crate::memory::PersistentMemory::new().map_err(|e| e.to_string())?;
+            memory.decay().map_err(|e| e.to_string())
+        }),
+    );
+
+    manager.register_periodic(
+        "evolution",
+        evolution,
+        schedule::job(|| async {
+            let mut evolution = crate::evolution::EvolutionSystem::new();
+            evolution.load()?;
+            let result = evolution.evolve();
+            evolution.save()?;
+            log_line(&format!(
+                "Evolution cycle: {} patterns applied",
+                result.patterns_applied
+            ));
+            Ok(())
+        }),
+    );
+}
 
-    // Log health status periodically
+/// Append a timestamped line to the daemon log
+fn log_line(message: &str) {
     if let Ok(mut log) = fs::OpenOptions::new()
         .create(true)
         .append(true)
@@ -182,10 +411,9 @@ async fn perform_periodic_tasks() {
     {
         writeln!(
             log,
-            "[{}] Health check: {} ({}%)",
+            "[{}] {}",
             chrono::Utc::now().format("%Y-%m-%d %H:%M:%S"),
-            report.overall_status,
-            report.metrics.overall_health_percentage
+            message
         )
         .ok();
     }
@@ -201,9 +429,42 @@ mod tests {
         assert!(path.to_string_lossy().contains("sena_daemon.pid"));
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_process_alive() {
+        assert!(process_alive(std::process::id() as i32));
+        // Group and broadcast PIDs are never taken for the daemon
+        assert!(!process_alive(0));
+        assert!(!process_alive(-1));
+
+        let mut child = Command::new("true").spawn().unwrap();
+        let pid = child.id() as i32;
+        child.wait().unwrap();
+        assert!(!process_alive(pid));
+    }
+
     #[test]
     fn test_log_file_path() {
         let path = log_file();
         assert!(path.to_string_lossy().contains("sena_daemon.log"));
     }
+
+    #[test]
+    fn test_reload_picks_up_changed_interval() {
+        let manager = BackgroundAgentManager::new();
+        let mut config = SenaConfig::default();
+        register_periodic_tasks(&manager, &config.daemon);
+
+        config.daemon.health_interval_secs = 15;
+        apply_config(&manager, &config);
+
+        let intervals: Vec<_> = manager
+            .scheduled_tasks()
+            .into_iter()
+            .map(|task| (task.name, task.interval_secs))
+            .collect();
+        assert_eq!(intervals.len(), 3);
+        assert!(intervals.contains(&("health".to_string(), 15)));
+        assert!(intervals.contains(&("memory-decay".to_string(), 60 * 60)));
+    }
 }
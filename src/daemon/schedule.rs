@@ -0,0 +1,213 @@
+//! Periodic daemon tasks
+//!
+//! Each task runs on its own interval. Last-run times are saved after every
+//! run, so a restarted daemon picks up the schedule where it left off
+//! instead of running everything at once, and `daemon status` can read the
+//! next-run times from another process.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::sync::write_atomic;
+
+/// Body of a periodic task; called again on every run
+pub type PeriodicJob =
+    Arc<dyn Fn() -> Pin<Box<dyn Future<Output = Result<(), String>> + Send>> + Send + Sync>;
+
+/// A periodic task as `daemon status` reports it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduledTaskInfo {
+    pub name: String,
+    pub interval_secs: u64,
+    pub last_run: Option<DateTime<Utc>>,
+    pub next_run: DateTime<Utc>,
+}
+
+struct PeriodicTask {
+    name: String,
+    interval: Duration,
+    last_run: Option<DateTime<Utc>>,
+    job: PeriodicJob,
+}
+
+impl PeriodicTask {
+    /// Right away for a task that has never run
+    fn next_run(&self) -> DateTime<Utc> {
+        match self.last_run {
+            Some(last) => {
+                last + chrono::Duration::from_std(self.interval).unwrap_or(chrono::Duration::MAX)
+            }
+            None => DateTime::<Utc>::MIN_UTC,
+        }
+    }
+
+    fn info(&self) -> ScheduledTaskInfo {
+        ScheduledTaskInfo {
+            name: self.name.clone(),
+            interval_secs: self.interval.as_secs(),
+            last_run: self.last_run,
+            next_run: self.last_run.map_or_else(Utc::now, |_| self.next_run()),
+        }
+    }
+}
+
+/// Periodic tasks and when each last ran
+#[derive(Default)]
+pub struct Scheduler {
+    tasks: Vec<PeriodicTask>,
+    /// Last runs read from `state_path`, for tasks not registered yet
+    saved_runs: HashMap<String, DateTime<Utc>>,
+    state_path: Option<PathBuf>,
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Save last-run times to `path`, picking up any saved there before
+    pub fn with_state_file(path: PathBuf) -> Self {
+        let saved_runs = load_state(&path)
+            .into_iter()
+            .filter_map(|task| Some((task.name, task.last_run?)))
+            .collect();
+        Self {
+            tasks: Vec::new(),
+            saved_runs,
+            state_path: Some(path),
+        }
+    }
+
+    /// Run `job` every `interval`. Registering a name again replaces the
+    /// earlier task but keeps its last run.
+    pub fn register(&mut self, name: &str, interval: Duration, job: PeriodicJob) {
+        let mut last_run = self.saved_runs.remove(name);
+        if let Some(index) = self.tasks.iter().position(|t| t.name == name) {
+            last_run = self.tasks.remove(index).last_run;
+        }
+        self.tasks.push(PeriodicTask {
+            name: name.to_string(),
+            interval,
+            last_run,
+            job,
+        });
+    }
+
+    /// Run `name` every `interval` from now on, counting from its last run.
+    /// Returns whether the interval changed.
+    pub fn set_interval(&mut self, name: &str, interval: Duration) -> bool {
+        match self.tasks.iter_mut().find(|t| t.name == name) {
+            Some(task) if task.interval != interval => {
+                task.interval = interval;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn tasks(&self) -> Vec<ScheduledTaskInfo> {
+        let mut tasks: Vec<_> = self.tasks.iter().map(PeriodicTask::info).collect();
+        tasks.sort_by_key(|t| t.next_run);
+        tasks
+    }
+
+    /// When the next task is due; `None` without tasks
+    pub fn next_due(&self) -> Option<DateTime<Utc>> {
+        self.tasks.iter().map(PeriodicTask::next_run).min()
+    }
+
+    /// Tasks due at `now`, to be run outside any lock on the scheduler
+    pub fn due(&self, now: DateTime<Utc>) -> Vec<(String, PeriodicJob)> {
+        self.tasks
+            .iter()
+            .filter(|t| t.next_run() <= now)
+            .map(|t| (t.name.clone(), Arc::clone(&t.job)))
+            .collect()
+    }
+
+    /// Record that `name` ran at `at` and save the schedule
+    pub fn mark_run(&mut self, name: &str, at: DateTime<Utc>) -> Result<(), String> {
+        if let Some(task) = self.tasks.iter_mut().find(|t| t.name == name) {
+            task.last_run = Some(at);
+        }
+        self.save()
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let Some(ref path) = self.state_path else {
+            return Ok(());
+        };
+        let content = serde_json::to_string_pretty(&self.tasks())
+            .map_err(|e| format!("Failed to serialize schedule: {}", e))?;
+        write_atomic(path, content).map_err(|e| format!("Failed to write schedule: {}", e))
+    }
+}
+
+/// Tasks saved at `path` by a daemon's scheduler, soonest first; empty when
+/// nothing was saved
+pub fn load_state(path: &Path) -> Vec<ScheduledTaskInfo> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Wrap an async closure as a [`PeriodicJob`]
+pub fn job<F, Fut>(f: F) -> PeriodicJob
+where
+    F: Fn() -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<(), String>> + Send + 'static,
+{
+    Arc::new(move || Box::pin(f()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_saved_runs_survive_a_restart() {
+        let dir = std::env::temp_dir().join(format!("sena-schedule-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("schedule.json");
+        let hourly = Duration::from_secs(3600);
+
+        let mut scheduler = Scheduler::with_state_file(path.clone());
+        scheduler.register("decay", hourly, job(|| async { Ok(()) }));
+        let now = Utc::now();
+        assert_eq!(scheduler.due(now).len(), 1);
+        scheduler.mark_run("decay", now).unwrap();
+
+        let mut restarted = Scheduler::with_state_file(path.clone());
+        restarted.register("decay", hourly, job(|| async { Ok(()) }));
+        assert!(restarted.due(Utc::now()).is_empty());
+        assert_eq!(
+            load_state(&path)[0].next_run,
+            now + chrono::Duration::hours(1)
+        );
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_new_interval_counts_from_last_run() {
+        let mut scheduler = Scheduler::new();
+        scheduler.register("health", Duration::from_secs(60), job(|| async { Ok(()) }));
+        let ran = Utc::now();
+        scheduler.mark_run("health", ran).unwrap();
+
+        assert!(scheduler.set_interval("health", Duration::from_secs(10)));
+        assert!(!scheduler.set_interval("health", Duration::from_secs(10)));
+        assert!(!scheduler.set_interval("missing", Duration::from_secs(10)));
+
+        let task = &scheduler.tasks()[0];
+        assert_eq!(task.last_run, Some(ran));
+        assert_eq!(task.next_run, ran + chrono::Duration::seconds(10));
+    }
+}
@@ -8,6 +8,8 @@ pub struct ProviderResponse {
     pub model: String,
     pub result: Result<String, String>,
     pub latency: Duration,
+    /// Aborted by the executor rather than failed by the provider
+    pub cancelled: bool,
 }
 
 impl ProviderResponse {
@@ -17,6 +19,7 @@ impl ProviderResponse {
             model,
             result: Ok(content),
             latency,
+            cancelled: false,
         }
     }
 
@@ -26,9 +29,22 @@ impl ProviderResponse {
             model,
             result: Err(error),
             latency,
+            cancelled: false,
         }
     }
 
+    /// A request aborted because the execution stopped waiting for it
+    pub fn cancelled(provider_id: String, model: String, latency: Duration) -> Self {
+        Self {
+            cancelled: true,
+            ..Self::failure(provider_id, model, "Cancelled".to_string(), latency)
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled
+    }
+
     pub fn is_success(&self) -> bool {
         self.result.is_ok()
     }
@@ -55,6 +71,8 @@ pub struct ProviderResponseData {
     pub content: Option<String>,
     pub error: Option<String>,
     pub latency_ms: u64,
+    #[serde(default)]
+    pub cancelled: bool,
 }
 
 pub struct ResponseAggregator;
@@ -108,6 +126,7 @@ impl ResponseAggregator {
                     content: r.result.as_ref().ok().cloned(),
                     error: r.result.as_ref().err().cloned(),
                     latency_ms,
+                    cancelled: r.cancelled,
                 }
             })
             .collect();
@@ -197,4 +216,23 @@ mod tests {
         assert_eq!(contents.len(), 1);
         assert_eq!(contents[0], "Content A");
     }
+
+    #[test]
+    fn test_cancelled_is_not_read_from_error_text() {
+        let responses = vec![
+            ProviderResponse::cancelled("a".to_string(), "m".to_string(), Duration::ZERO),
+            ProviderResponse::failure(
+                "b".to_string(),
+                "m".to_string(),
+                "Cancelled".to_string(),
+                Duration::ZERO,
+            ),
+        ];
+        assert!(responses[0].is_cancelled());
+        assert!(!responses[1].is_cancelled());
+
+        let aggregated = ResponseAggregator::new().aggregate(responses);
+        assert!(aggregated.responses[0].cancelled);
+        assert!(!aggregated.responses[1].cancelled);
+    }
 }
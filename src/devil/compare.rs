@@ -0,0 +1,191 @@
+use serde::{Deserialize, Serialize};
+
+use super::aggregator::{ProviderResponse, ProviderResponseData, ResponseAggregator};
+use super::consensus::ConsensusEngine;
+use crate::output::{CellOverflow, TableBuilder};
+
+const COLUMN_WIDTH: usize = 40;
+
+/// Pairwise word-overlap agreement between successful responders
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgreementMatrix {
+    pub providers: Vec<String>,
+    pub scores: Vec<Vec<f64>>,
+}
+
+impl AgreementMatrix {
+    pub fn score(&self, a: &str, b: &str) -> Option<f64> {
+        let i = self.providers.iter().position(|p| p == a)?;
+        let j = self.providers.iter().position(|p| p == b)?;
+        Some(self.scores[i][j])
+    }
+
+    /// The two distinct providers that agree most
+    pub fn closest_pair(&self) -> Option<(&str, &str, f64)> {
+        let mut best: Option<(&str, &str, f64)> = None;
+        for i in 0..self.providers.len() {
+            for j in (i + 1)..self.providers.len() {
+                let score = self.scores[i][j];
+                if !matches!(best, Some((_, _, s)) if s >= score) {
+                    best = Some((&self.providers[i], &self.providers[j], score));
+                }
+            }
+        }
+        best
+    }
+}
+
+/// Each provider's full answer to the same prompt, without synthesis
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DevilComparison {
+    pub prompt: String,
+    pub responses: Vec<ProviderResponseData>,
+    pub agreement: AgreementMatrix,
+}
+
+impl DevilComparison {
+    pub fn new(prompt: &str, responses: Vec<ProviderResponse>) -> Self {
+        let aggregated = ResponseAggregator::new().aggregate(responses);
+        let engine = ConsensusEngine::new();
+
+        let successful: Vec<&ProviderResponseData> = aggregated
+            .responses
+            .iter()
+            .filter(|r| r.content.is_some())
+            .collect();
+        let providers = successful.iter().map(|r| r.provider_id.clone()).collect();
+        let scores = successful
+            .iter()
+            .map(|a| {
+                successful
+                    .iter()
+                    .map(|b| {
+                        if a.provider_id == b.provider_id {
+                            1.0
+                        } else {
+                            engine.text_similarity(
+                                a.content.as_deref().unwrap_or_default(),
+                                b.content.as_deref().unwrap_or_default(),
+                            )
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Self {
+            prompt: prompt.to_string(),
+            responses: aggregated.responses,
+            agreement: AgreementMatrix { providers, scores },
+        }
+    }
+
+    /// Responses side by side, followed by the agreement matrix
+    pub fn format_comparison(&self) -> String {
+        let mut output = format!("Prompt: {}\n\n", self.prompt);
+
+        let mut responses = TableBuilder::new()
+            .max_width(COLUMN_WIDTH)
+            .overflow(CellOverflow::Wrap)
+            .row(
+                self.responses
+                    .iter()
+                    .map(|r| format!("{} ({}, {}ms)", r.provider_id, r.model, r.latency_ms))
+                    .collect(),
+            )
+            .row(
+                self.responses
+                    .iter()
+                    .map(|r| match (&r.content, &r.error) {
+                        (Some(content), _) => content.clone(),
+                        (None, Some(error)) => format!("[failed: {}]", error),
+                        (None, None) => String::new(),
+                    })
+                    .collect(),
+            );
+        if self.responses.is_empty() {
+            responses = responses.row(vec!["No responses".to_string()]);
+        }
+        output.push_str(&responses.build());
+        output.push_str("\n\nAgreement:\n");
+
+        if self.agreement.providers.len() < 2 {
+            output.push_str("  Fewer than two successful responses; nothing to compare.\n");
+            return output;
+        }
+
+        let mut header = vec![String::new()];
+        header.extend(self.agreement.providers.iter().cloned());
+        let mut matrix = TableBuilder::new().min_width(6).row(header);
+        for (provider, scores) in self.agreement.providers.iter().zip(&self.agreement.scores) {
+            let mut row = vec![provider.clone()];
+            row.extend(scores.iter().map(|s| format!("{:.0}%", s * 100.0)));
+            matrix = matrix.row(row);
+        }
+        output.push_str(&matrix.build());
+        output.push('\n');
+
+        if let Some((a, b, score)) = self.agreement.closest_pair() {
+            output.push_str(&format!(
+                "Closest agreement: {} and {} ({:.0}%)\n",
+                a,
+                b,
+                score * 100.0
+            ));
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn success(id: &str, content: &str) -> ProviderResponse {
+        ProviderResponse::success(
+            id.to_string(),
+            "m".to_string(),
+            content.to_string(),
+            Duration::from_millis(10),
+        )
+    }
+
+    #[test]
+    fn test_agreement_matrix_is_symmetric_and_scored() {
+        let comparison = DevilComparison::new(
+            "How far is the Moon?",
+            vec![
+                success("claude", "The Moon is about 384,000 km from Earth"),
+                success("openai", "The Moon is roughly 384,000 km from Earth"),
+                success("gemini", "Bananas are yellow tropical fruits"),
+                ProviderResponse::failure(
+                    "ollama".to_string(),
+                    "m".to_string(),
+                    "Timeout".to_string(),
+                    Duration::from_secs(30),
+                ),
+            ],
+        );
+        let matrix = &comparison.agreement;
+
+        assert_eq!(matrix.providers, vec!["claude", "openai", "gemini"]);
+        for i in 0..3 {
+            assert_eq!(matrix.scores[i][i], 1.0);
+            for j in 0..3 {
+                assert_eq!(matrix.scores[i][j], matrix.scores[j][i]);
+            }
+        }
+
+        let close = matrix.score("claude", "openai").unwrap();
+        assert!((close - 0.75).abs() < 1e-9);
+        assert_eq!(matrix.score("claude", "gemini"), Some(0.0));
+        assert_eq!(matrix.score("claude", "ollama"), None);
+        assert_eq!(matrix.closest_pair(), Some(("claude", "openai", close)));
+
+        let text = comparison.format_comparison();
+        assert!(text.contains("Bananas are yellow"));
+        assert!(text.contains("[failed: Timeout]"));
+        assert!(text.contains("Closest agreement: claude and openai (75%)"));
+    }
+}
@@ -11,12 +11,36 @@ pub enum SynthesisMethod {
     CrossVerification,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+/// When a devil execution stops waiting for providers. Requests still in
+/// flight at that point are aborted and reported as cancelled.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
 pub enum WaitMode {
     #[default]
     WaitForAll,
+    /// Return on the first successful response
     EarlyReturn,
+    /// Return once `min_providers` have answered successfully
     Configurable,
+    /// Return once this many providers have answered successfully
+    FirstN(usize),
+    /// Return once this fraction (0.0-1.0) of providers has answered
+    /// successfully
+    Quorum(f64),
+}
+
+impl WaitMode {
+    /// Successful responses needed out of `total` before returning early.
+    /// Never more than `total`, so failures fall back to waiting for all.
+    pub fn required_successes(&self, total: usize, min_providers: usize) -> usize {
+        let required = match *self {
+            WaitMode::WaitForAll => total,
+            WaitMode::EarlyReturn => 1,
+            WaitMode::Configurable => min_providers,
+            WaitMode::FirstN(n) => n,
+            WaitMode::Quorum(fraction) => (fraction.clamp(0.0, 1.0) * total as f64).ceil() as usize,
+        };
+        required.max(1).min(total)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -87,4 +111,9 @@ impl DevilConfig {
         self.consensus_threshold = threshold;
         self
     }
+
+    pub fn with_wait_mode(mut self, wait_mode: WaitMode) -> Self {
+        self.wait_mode = wait_mode;
+        self
+    }
 }
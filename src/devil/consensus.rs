@@ -2,10 +2,19 @@ use std::collections::{HashMap, HashSet};
 
 use super::aggregator::AggregatedResponses;
 use super::error::{DevilError, DevilResult};
+use crate::base::text::is_stop_word;
 
 #[derive(Debug, Clone)]
 pub struct ConsensusResult {
+    /// Mean pairwise cosine similarity of the successful responses
     pub agreement_score: f64,
+    /// Providers in the order of `similarity_matrix` rows and columns
+    pub provider_ids: Vec<String>,
+    /// Pairwise cosine similarity between responses, 1.0 on the diagonal
+    pub similarity_matrix: Vec<Vec<f64>>,
+    pub responder_count: usize,
+    pub agreement_fraction: f64,
+    pub confidence: f64,
     pub clusters: Vec<ResponseCluster>,
     pub agreed_facts: Vec<AgreedFact>,
     pub outliers: Vec<String>,
@@ -76,31 +85,75 @@ impl ConsensusEngine {
         let clusters = self.cluster_responses(&successful, &similarity_matrix);
         let agreed_facts = self.extract_agreed_facts(&successful);
         let outliers = self.identify_outliers(&successful, &similarity_matrix);
-        let agreement_score = self.calculate_agreement_score(&clusters, successful.len());
+        let agreement_score = Self::mean_pairwise(&similarity_matrix);
+        let agreeing = clusters
+            .iter()
+            .map(|c| c.provider_ids.len())
+            .max()
+            .unwrap_or(0);
 
         Ok(ConsensusResult {
             agreement_score,
+            provider_ids: successful.iter().map(|(id, _)| id.to_string()).collect(),
+            similarity_matrix,
+            responder_count: successful.len(),
+            agreement_fraction: agreeing as f64 / successful.len() as f64,
+            confidence: Self::confidence(agreement_score, agreeing),
             clusters,
             agreed_facts,
             outliers,
         })
     }
 
-    fn calculate_similarity_matrix(&self, responses: &[(&str, &str)]) -> Vec<Vec<f64>> {
-        let n = responses.len();
-        let mut matrix = vec![vec![0.0; n]; n];
+    /// Agreement score scaled by how many providers back the majority answer.
+    /// A lone responder trivially agrees with itself, so it only counts half.
+    pub fn confidence(agreement_score: f64, agreeing: usize) -> f64 {
+        if agreeing == 0 {
+            return 0.0;
+        }
+        let support = agreeing as f64 / (agreeing as f64 + 1.0);
+        (agreement_score * support).clamp(0.0, 1.0)
+    }
 
-        for i in 0..n {
-            for j in i..n {
-                let sim = self.text_similarity(responses[i].1, responses[j].1);
-                matrix[i][j] = sim;
-                matrix[j][i] = sim;
+    /// Consensus score for a set of answers: the mean pairwise cosine
+    /// similarity of their word and word-pair counts. A single answer
+    /// scores 1.0; no answers score 0.0.
+    pub fn score(&self, responses: &[&str]) -> f64 {
+        let vectors: Vec<HashMap<String, f64>> =
+            responses.iter().map(|text| term_vector(text)).collect();
+        Self::mean_pairwise(&similarity_matrix(&vectors))
+    }
+
+    fn mean_pairwise(matrix: &[Vec<f64>]) -> f64 {
+        let n = matrix.len();
+        match n {
+            0 => 0.0,
+            1 => 1.0,
+            _ => {
+                let sum: f64 = (0..n)
+                    .flat_map(|i| ((i + 1)..n).map(move |j| (i, j)))
+                    .map(|(i, j)| matrix[i][j])
+                    .sum();
+                sum / (n * (n - 1) / 2) as f64
             }
         }
-        matrix
     }
 
-    fn text_similarity(&self, a: &str, b: &str) -> f64 {
+    fn calculate_similarity_matrix(&self, responses: &[(&str, &str)]) -> Vec<Vec<f64>> {
+        let vectors: Vec<HashMap<String, f64>> = responses
+            .iter()
+            .map(|(_, text)| term_vector(text))
+            .collect();
+        similarity_matrix(&vectors)
+    }
+
+    /// Cosine similarity of the word and word-pair counts of two texts
+    pub fn cosine_similarity(&self, a: &str, b: &str) -> f64 {
+        cosine(&term_vector(a), &term_vector(b))
+    }
+
+    /// Jaccard overlap of the words (longer than two characters) in two texts
+    pub fn text_similarity(&self, a: &str, b: &str) -> f64 {
         let a_lower = a.to_lowercase();
         let b_lower = b.to_lowercase();
 
@@ -254,26 +307,56 @@ impl ConsensusEngine {
 
         outliers
     }
+}
 
-    fn calculate_agreement_score(&self, clusters: &[ResponseCluster], total: usize) -> f64 {
-        if clusters.is_empty() || total == 0 {
-            return 0.0;
-        }
-
-        let largest_cluster_size = clusters
-            .iter()
-            .map(|c| c.provider_ids.len())
-            .max()
-            .unwrap_or(0);
-
-        let avg_cluster_similarity: f64 =
-            clusters.iter().map(|c| c.similarity_score).sum::<f64>() / clusters.len() as f64;
+/// Counts of the meaningful words in `text` and of each adjacent word pair,
+/// so reordered or negated phrasing differs more than a bag of words would
+fn term_vector(text: &str) -> HashMap<String, f64> {
+    let lower = text.to_lowercase();
+    let words: Vec<&str> = lower
+        .split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .map(|w| w.trim_matches('\''))
+        .filter(|w| w.len() > 2 && !is_stop_word(w))
+        .collect();
+
+    let mut vector = HashMap::new();
+    for word in &words {
+        *vector.entry(word.to_string()).or_insert(0.0) += 1.0;
+    }
+    for pair in words.windows(2) {
+        *vector
+            .entry(format!("{} {}", pair[0], pair[1]))
+            .or_insert(0.0) += 1.0;
+    }
+    vector
+}
 
-        let size_factor = largest_cluster_size as f64 / total as f64;
-        let similarity_factor = avg_cluster_similarity;
+fn cosine(a: &HashMap<String, f64>, b: &HashMap<String, f64>) -> f64 {
+    let dot: f64 = a
+        .iter()
+        .filter_map(|(term, weight)| b.get(term).map(|other| weight * other))
+        .sum();
+    let norm = |v: &HashMap<String, f64>| v.values().map(|w| w * w).sum::<f64>().sqrt();
+    let denominator = norm(a) * norm(b);
+    if denominator == 0.0 {
+        0.0
+    } else {
+        (dot / denominator).clamp(0.0, 1.0)
+    }
+}
 
-        (size_factor * 0.6 + similarity_factor * 0.4).min(1.0)
+fn similarity_matrix(vectors: &[HashMap<String, f64>]) -> Vec<Vec<f64>> {
+    let n = vectors.len();
+    let mut matrix = vec![vec![0.0; n]; n];
+    for i in 0..n {
+        matrix[i][i] = 1.0;
+        for j in (i + 1)..n {
+            let sim = cosine(&vectors[i], &vectors[j]);
+            matrix[i][j] = sim;
+            matrix[j][i] = sim;
+        }
     }
+    matrix
 }
 
 impl Default for ConsensusEngine {
@@ -303,6 +386,29 @@ mod tests {
         assert!(diff_sim < 0.2);
     }
 
+    #[test]
+    fn test_score_rewards_agreement_and_penalizes_contradiction() {
+        let engine = ConsensusEngine::new();
+
+        let agreeing = engine.score(&[
+            "Rust guarantees memory safety without a garbage collector through ownership and borrowing.",
+            "Rust guarantees memory safety through ownership and borrowing, without a garbage collector.",
+        ]);
+        let contradicting = engine.score(&[
+            "Rust guarantees memory safety without a garbage collector through ownership and borrowing.",
+            "Python relies on reference counting and a cyclic garbage collector to reclaim objects.",
+        ]);
+        assert!(agreeing > 0.8, "agreeing scored {}", agreeing);
+        assert!(
+            contradicting < 0.2,
+            "contradicting scored {}",
+            contradicting
+        );
+
+        assert_eq!(engine.score(&["Only one answer."]), 1.0);
+        assert_eq!(engine.score(&[]), 0.0);
+    }
+
     #[test]
     fn test_consensus_analysis() {
         let responses = vec![
@@ -336,6 +442,50 @@ mod tests {
 
         assert!(result.agreement_score > 0.5);
         assert!(result.clusters.len() <= 3);
+        assert_eq!(result.provider_ids, vec!["claude", "openai", "gemini"]);
+        assert_eq!(result.similarity_matrix.len(), 3);
+        assert_eq!(result.similarity_matrix[0][0], 1.0);
+        assert_eq!(
+            result.similarity_matrix[0][1],
+            result.similarity_matrix[1][0]
+        );
+    }
+
+    #[test]
+    fn test_more_agreeing_providers_raise_confidence() {
+        let answer = "Water boils at 100 degrees Celsius at sea level pressure.";
+        let engine = ConsensusEngine::new();
+        let aggregator = ResponseAggregator::new();
+
+        let lone = aggregator.aggregate(vec![ProviderResponse::success(
+            "claude".to_string(),
+            "m".to_string(),
+            answer.to_string(),
+            Duration::from_millis(100),
+        )]);
+        let lone = engine.analyze(&lone).unwrap();
+
+        let trio = aggregator.aggregate(
+            ["claude", "openai", "gemini"]
+                .iter()
+                .map(|id| {
+                    ProviderResponse::success(
+                        id.to_string(),
+                        "m".to_string(),
+                        answer.to_string(),
+                        Duration::from_millis(100),
+                    )
+                })
+                .collect(),
+        );
+        let trio = engine.analyze(&trio).unwrap();
+
+        assert_eq!(lone.agreement_fraction, 1.0);
+        assert_eq!(trio.agreement_fraction, 1.0);
+        assert_eq!(lone.responder_count, 1);
+        assert_eq!(trio.responder_count, 3);
+        assert!(lone.confidence <= 0.5);
+        assert!(trio.confidence > lone.confidence);
     }
 
     #[test]
@@ -17,6 +17,9 @@ pub enum DevilError {
     #[error("Timeout: only {completed}/{total} providers responded")]
     PartialTimeout { completed: usize, total: usize },
 
+    #[error("Cancelled")]
+    Cancelled,
+
     #[error("Configuration error: {0}")]
     ConfigError(String),
 
@@ -1,5 +1,11 @@
+use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
+use sena_providers::{AIProvider, ChatRequest, Message};
+use tokio::sync::watch;
+use tokio::task::JoinSet;
+
 use super::aggregator::{ProviderResponse, ResponseAggregator};
 use super::config::DevilConfig;
 use super::consensus::ConsensusEngine;
@@ -7,6 +13,41 @@ use super::error::{DevilError, DevilResult};
 use super::synthesizer::ResponseSynthesizer;
 use super::{DevilResponse, ProviderResponseSummary, ResponseStatus};
 
+/// Aborts a running [`DevilExecutor::execute`] from another task. Clones
+/// share the same state.
+#[derive(Clone)]
+pub struct DevilCancelToken {
+    sender: Arc<watch::Sender<bool>>,
+}
+
+impl DevilCancelToken {
+    pub fn new() -> Self {
+        Self {
+            sender: Arc::new(watch::channel(false).0),
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.sender.send_replace(true);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        *self.sender.borrow()
+    }
+
+    /// Resolves once `cancel` has been called
+    pub async fn cancelled(&self) {
+        let mut receiver = self.sender.subscribe();
+        let _ = receiver.wait_for(|cancelled| *cancelled).await;
+    }
+}
+
+impl Default for DevilCancelToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 pub struct DevilExecutor {
     config: DevilConfig,
     aggregator: ResponseAggregator,
@@ -23,6 +64,119 @@ impl DevilExecutor {
         }
     }
 
+    /// Send `prompt` to every provider in parallel and synthesize the
+    /// answers. Stops waiting once the configured `WaitMode` is satisfied or
+    /// `cancel` fires; requests still in flight are aborted.
+    pub async fn execute(
+        &self,
+        prompt: &str,
+        providers: Vec<Arc<dyn AIProvider>>,
+        cancel: &DevilCancelToken,
+    ) -> DevilResult<DevilResponse> {
+        let responses = self.collect_responses(prompt, providers, cancel).await;
+        if cancel.is_cancelled() {
+            return Err(DevilError::Cancelled);
+        }
+        self.execute_sync(prompt, responses)
+    }
+
+    /// Run the provider requests, returning one response per provider.
+    /// Aborted requests are reported with `ProviderResponse::cancelled`.
+    pub async fn collect_responses(
+        &self,
+        prompt: &str,
+        providers: Vec<Arc<dyn AIProvider>>,
+        cancel: &DevilCancelToken,
+    ) -> Vec<ProviderResponse> {
+        let required = self
+            .config
+            .wait_mode
+            .required_successes(providers.len(), self.config.min_providers);
+        let timeout = Duration::from_secs(self.config.timeout_secs);
+        let request = ChatRequest::new(vec![Message::user(prompt)]).with_max_tokens(1024);
+        let start = Instant::now();
+
+        let mut tasks = JoinSet::new();
+        let mut pending = HashMap::new();
+        for (index, provider) in providers.into_iter().enumerate() {
+            let provider_id = provider.provider_id().to_string();
+            let model = provider.default_model().to_string();
+            let request = request.clone();
+            let (id, fallback_model) = (provider_id.clone(), model.clone());
+            let handle = tasks.spawn(async move {
+                let started = Instant::now();
+                match tokio::time::timeout(timeout, provider.chat(request)).await {
+                    Ok(Ok(response)) => ProviderResponse::success(
+                        id,
+                        response.model,
+                        response.content,
+                        started.elapsed(),
+                    ),
+                    Ok(Err(e)) => ProviderResponse::failure(
+                        id,
+                        fallback_model,
+                        e.to_string(),
+                        started.elapsed(),
+                    ),
+                    Err(_) => ProviderResponse::failure(
+                        id,
+                        fallback_model,
+                        "Timeout".to_string(),
+                        timeout,
+                    ),
+                }
+            });
+            pending.insert(handle.id(), (index, provider_id, model));
+        }
+
+        let mut responses = Vec::new();
+        let mut successes = 0;
+        while successes < required && !cancel.is_cancelled() {
+            let joined = tokio::select! {
+                joined = tasks.join_next_with_id() => joined,
+                _ = cancel.cancelled() => break,
+            };
+            let (index, response) = match joined {
+                None => break,
+                Some(Ok((id, response))) => match pending.remove(&id) {
+                    Some((index, _, _)) => (index, response),
+                    None => continue,
+                },
+                Some(Err(e)) => match pending.remove(&e.id()) {
+                    Some((index, provider_id, model)) => (
+                        index,
+                        ProviderResponse::failure(
+                            provider_id,
+                            model,
+                            e.to_string(),
+                            start.elapsed(),
+                        ),
+                    ),
+                    None => continue,
+                },
+            };
+            if response.is_success() {
+                successes += 1;
+            }
+            responses.push((index, response));
+        }
+
+        tasks.abort_all();
+        let elapsed = start.elapsed();
+        responses.extend(pending.into_values().map(|(index, provider_id, model)| {
+            (
+                index,
+                ProviderResponse::cancelled(provider_id, model, elapsed),
+            )
+        }));
+        // Report providers in the order they were given
+        responses.sort_by_key(|(index, _)| *index);
+        responses
+            .into_iter()
+            .map(|(_, response)| response)
+            .collect()
+    }
+
     pub fn execute_sync(
         &self,
         _prompt: &str,
@@ -54,6 +208,8 @@ impl DevilExecutor {
                 model: r.model.clone(),
                 status: if r.content.is_some() {
                     ResponseStatus::Success
+                } else if r.cancelled {
+                    ResponseStatus::Cancelled
                 } else if r.error.as_deref() == Some("Timeout") {
                     ResponseStatus::Timeout
                 } else {
@@ -77,7 +233,9 @@ impl DevilExecutor {
         Ok(DevilResponse {
             content: synthesized.content,
             provider_responses,
-            consensus_score: consensus.agreement_score,
+            consensus_score: consensus.confidence,
+            responder_count: consensus.responder_count,
+            agreement_fraction: consensus.agreement_fraction,
             synthesis_method: synthesized.method,
             total_latency_ms: aggregated.total_latency_ms,
             facts_verified: synthesized.facts_verified,
@@ -161,7 +319,126 @@ impl Default for MockProviderPool {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::devil::SynthesisMethod;
+    use crate::devil::{SynthesisMethod, WaitMode};
+    use async_trait::async_trait;
+    use sena_providers::provider::ChatStream;
+    use sena_providers::{
+        ChatResponse, ModelInfo, ProviderCapabilities, ProviderError, ProviderMetadata,
+        ProviderStatus,
+    };
+
+    struct DelayedProvider {
+        id: String,
+        delay: Duration,
+        capabilities: ProviderCapabilities,
+    }
+
+    impl DelayedProvider {
+        fn arc(id: &str, delay_ms: u64) -> Arc<dyn AIProvider> {
+            Arc::new(Self {
+                id: id.to_string(),
+                delay: Duration::from_millis(delay_ms),
+                capabilities: ProviderCapabilities::default(),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl AIProvider for DelayedProvider {
+        fn provider_id(&self) -> &str {
+            &self.id
+        }
+
+        fn display_name(&self) -> &str {
+            &self.id
+        }
+
+        fn capabilities(&self) -> &ProviderCapabilities {
+            &self.capabilities
+        }
+
+        fn default_model(&self) -> &str {
+            "mock-model"
+        }
+
+        fn available_models(&self) -> &[ModelInfo] {
+            &[]
+        }
+
+        fn status(&self) -> ProviderStatus {
+            ProviderStatus::Connected
+        }
+
+        fn provider_metadata(&self) -> ProviderMetadata {
+            ProviderMetadata::new(&self.id, &self.id)
+        }
+
+        async fn chat(&self, _request: ChatRequest) -> sena_providers::Result<ChatResponse> {
+            tokio::time::sleep(self.delay).await;
+            Ok(ChatResponse::new(
+                &self.id,
+                "mock-model",
+                "The Moon is tidally locked to Earth.".to_string(),
+            ))
+        }
+
+        async fn chat_stream(&self, _request: ChatRequest) -> sena_providers::Result<ChatStream> {
+            Err(ProviderError::Unsupported("streaming".into()))
+        }
+    }
+
+    fn providers() -> Vec<Arc<dyn AIProvider>> {
+        vec![
+            DelayedProvider::arc("fast", 5),
+            DelayedProvider::arc("slow", 10_000),
+            DelayedProvider::arc("quick", 10),
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_first_n_cancels_slow_providers() {
+        let executor =
+            DevilExecutor::new(DevilConfig::default().with_wait_mode(WaitMode::FirstN(2)));
+        let started = Instant::now();
+        let result = executor
+            .execute("Moon", providers(), &DevilCancelToken::new())
+            .await
+            .unwrap();
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        let statuses: Vec<(&str, &ResponseStatus)> = result
+            .provider_responses
+            .iter()
+            .map(|r| (r.provider_id.as_str(), &r.status))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ("fast", &ResponseStatus::Success),
+                ("slow", &ResponseStatus::Cancelled),
+                ("quick", &ResponseStatus::Success),
+            ]
+        );
+
+        assert_eq!(WaitMode::Quorum(0.5).required_successes(3, 2), 2);
+        assert_eq!(WaitMode::FirstN(9).required_successes(3, 2), 3);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_token_aborts_execution() {
+        let executor = DevilExecutor::default();
+        let cancel = DevilCancelToken::new();
+        let trigger = cancel.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            trigger.cancel();
+        });
+
+        let started = Instant::now();
+        let result = executor.execute("Moon", providers(), &cancel).await;
+        assert!(matches!(result, Err(DevilError::Cancelled)));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
 
     fn create_mock_responses() -> Vec<ProviderResponse> {
         vec![
@@ -1,15 +1,19 @@
 mod aggregator;
+mod compare;
 mod config;
 mod consensus;
 mod error;
 mod executor;
 mod synthesizer;
 
-pub use aggregator::{AggregatedResponses, ProviderResponse, ResponseAggregator};
+pub use aggregator::{
+    AggregatedResponses, ProviderResponse, ProviderResponseData, ResponseAggregator,
+};
+pub use compare::{AgreementMatrix, DevilComparison};
 pub use config::{DevilConfig, SynthesisMethod, WaitMode};
 pub use consensus::{ConsensusEngine, ConsensusResult};
 pub use error::{DevilError, DevilResult};
-pub use executor::DevilExecutor;
+pub use executor::{DevilCancelToken, DevilExecutor};
 pub use synthesizer::{ResponseSynthesizer, SynthesizedResponse};
 
 use serde::{Deserialize, Serialize};
@@ -19,6 +23,10 @@ pub struct DevilResponse {
     pub content: String,
     pub provider_responses: Vec<ProviderResponseSummary>,
     pub consensus_score: f64,
+    #[serde(default)]
+    pub responder_count: usize,
+    #[serde(default)]
+    pub agreement_fraction: f64,
     pub synthesis_method: SynthesisMethod,
     pub total_latency_ms: u64,
     pub facts_verified: Option<usize>,
@@ -39,6 +47,7 @@ pub struct ProviderResponseSummary {
 pub enum ResponseStatus {
     Success,
     Timeout,
+    Cancelled,
     Error(String),
 }
 
@@ -47,8 +56,10 @@ impl DevilResponse {
         let mut summary = String::new();
 
         summary.push_str(&format!(
-            "Devil Mode Response (Consensus: {:.0}%)\n",
-            self.consensus_score * 100.0
+            "Devil Mode Response (Consensus: {:.0}%, {} responder(s), {:.0}% agreeing)\n",
+            self.consensus_score * 100.0,
+            self.responder_count,
+            self.agreement_fraction * 100.0
         ));
         summary.push_str(&format!(
             "Synthesis: {:?} | Latency: {}ms\n",
@@ -67,6 +78,7 @@ impl DevilResponse {
             let status_str = match &response.status {
                 ResponseStatus::Success => "OK",
                 ResponseStatus::Timeout => "TIMEOUT",
+                ResponseStatus::Cancelled => "CANCELLED",
                 ResponseStatus::Error(e) => e.as_str(),
             };
             summary.push_str(&format!(
@@ -106,6 +118,8 @@ mod tests {
                 },
             ],
             consensus_score: 0.85,
+            responder_count: 2,
+            agreement_fraction: 1.0,
             synthesis_method: SynthesisMethod::CrossVerification,
             total_latency_ms: 3500,
             facts_verified: Some(5),
@@ -3,6 +3,14 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::Path;
 
+use crate::sync::write_atomic;
+
+/// Entries `sentiment` averages over
+pub const DEFAULT_FEEDBACK_WINDOW: usize = 20;
+
+/// Change in mean weight `trend` treats as movement rather than noise
+const TREND_TOLERANCE: f64 = 0.1;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum FeedbackType {
     Positive,
@@ -26,6 +34,39 @@ impl std::fmt::Display for FeedbackType {
     }
 }
 
+impl FeedbackType {
+    /// How much one entry of this type moves the improvement score; bug
+    /// reports count double against it
+    pub fn weight(&self) -> f64 {
+        match self {
+            FeedbackType::Positive => 1.0,
+            FeedbackType::Negative => -1.0,
+            FeedbackType::Neutral | FeedbackType::FeatureRequest => 0.0,
+            FeedbackType::Bug => -2.0,
+            FeedbackType::Correction => -0.5,
+        }
+    }
+}
+
+/// Direction of recent feedback compared with the feedback before it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum FeedbackTrend {
+    Improving,
+    #[default]
+    Stable,
+    Regressing,
+}
+
+impl std::fmt::Display for FeedbackTrend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FeedbackTrend::Improving => write!(f, "📈 Improving"),
+            FeedbackTrend::Stable => write!(f, "➖ Stable"),
+            FeedbackTrend::Regressing => write!(f, "📉 Regressing"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FeedbackEntry {
     pub id: String,
@@ -128,6 +169,45 @@ impl FeedbackLoop {
         self.entries.len()
     }
 
+    /// Mean weight of the last `window` entries, clamped to -1.0..=1.0;
+    /// 0.0 without feedback
+    pub fn sentiment(&self, window: usize) -> f64 {
+        let start = self.entries.len().saturating_sub(window);
+        Self::mean_weight(&self.entries[start..]).clamp(-1.0, 1.0)
+    }
+
+    /// Compare the last `window` entries with as many before them; with
+    /// fewer than `2 * window` entries each side gets half
+    pub fn trend(&self, window: usize) -> FeedbackTrend {
+        let size = window.min(self.entries.len() / 2);
+        if size == 0 {
+            return FeedbackTrend::Stable;
+        }
+        let split = self.entries.len() - size;
+        let previous = &self.entries[split - size..split];
+        let recent = &self.entries[split..];
+
+        let delta = Self::mean_weight(recent) - Self::mean_weight(previous);
+        if delta > TREND_TOLERANCE {
+            FeedbackTrend::Improving
+        } else if delta < -TREND_TOLERANCE {
+            FeedbackTrend::Regressing
+        } else {
+            FeedbackTrend::Stable
+        }
+    }
+
+    fn mean_weight(entries: &[FeedbackEntry]) -> f64 {
+        if entries.is_empty() {
+            return 0.0;
+        }
+        entries
+            .iter()
+            .map(|e| e.feedback_type.weight())
+            .sum::<f64>()
+            / entries.len() as f64
+    }
+
     pub fn unprocessed(&self) -> Vec<&FeedbackEntry> {
         self.entries.iter().filter(|e| !e.processed).collect()
     }
@@ -190,7 +270,7 @@ impl FeedbackLoop {
             });
         }
 
-        insights.sort_by(|a, b| a.priority.cmp(&b.priority));
+        insights.sort_by_key(|a| a.priority);
 
         insights
     }
@@ -219,7 +299,7 @@ impl FeedbackLoop {
         let json = serde_json::to_string_pretty(&self.entries)
             .map_err(|e| format!("Failed to serialize feedback: {}", e))?;
 
-        std::fs::write(path, json).map_err(|e| format!("Failed to write feedback: {}", e))?;
+        write_atomic(path, json).map_err(|e| format!("Failed to write feedback: {}", e))?;
 
         Ok(())
     }
@@ -315,7 +395,7 @@ mod tests {
         loop_sys.add(FeedbackType::Bug, "Found a bug in performance");
 
         let insights = loop_sys.analyze();
-        assert!(insights.len() > 0);
+        assert!(!insights.is_empty());
     }
 
     #[test]
@@ -329,4 +409,25 @@ mod tests {
         assert_eq!(summary.positive, 1);
         assert_eq!(summary.bugs, 1);
     }
+
+    #[test]
+    fn test_trend_and_sentiment() {
+        let mut loop_sys = FeedbackLoop::new();
+        assert_eq!(loop_sys.trend(3), FeedbackTrend::Stable);
+
+        for _ in 0..3 {
+            loop_sys.add(FeedbackType::Positive, "Good!");
+        }
+        assert_eq!(loop_sys.sentiment(3), 1.0);
+        loop_sys.add(FeedbackType::Negative, "Worse");
+        loop_sys.add(FeedbackType::Bug, "Crashes");
+        loop_sys.add(FeedbackType::Neutral, "Fine");
+        assert_eq!(loop_sys.trend(3), FeedbackTrend::Regressing);
+        assert_eq!(loop_sys.sentiment(3), -1.0);
+
+        for _ in 0..3 {
+            loop_sys.add(FeedbackType::Positive, "Fixed");
+        }
+        assert_eq!(loop_sys.trend(3), FeedbackTrend::Improving);
+    }
 }
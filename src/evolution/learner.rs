@@ -1,8 +1,17 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 
+use crate::sync::write_atomic;
+
+/// Context and outcome similarity at which `learn` folds a new pattern into
+/// an existing one
+pub const DEDUP_SIMILARITY: f64 = 0.85;
+
+/// Least context similarity for a pattern to be recalled at all
+pub const RECALL_MIN_SIMILARITY: f64 = 0.2;
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum PatternType {
     ProblemSolution,
@@ -32,6 +41,9 @@ pub struct LearnedPattern {
     pub outcome: String,
     pub keywords: Vec<String>,
     pub usage_count: u64,
+    /// Times the pattern was learned or used successfully
+    #[serde(default = "default_success_count")]
+    pub success_count: u64,
     pub success_rate: f64,
     pub learned_at: DateTime<Utc>,
     pub last_used: Option<DateTime<Utc>>,
@@ -44,8 +56,12 @@ impl LearnedPattern {
             pattern_type: Self::detect_type(context),
             context: context.to_string(),
             outcome: outcome.to_string(),
-            keywords: Self::extract_keywords(context),
+            keywords: Self::extract_keywords(context)
+                .into_iter()
+                .take(10)
+                .collect(),
             usage_count: 0,
+            success_count: 1,
             success_rate: 1.0,
             learned_at: Utc::now(),
             last_used: None,
@@ -91,18 +107,54 @@ impl LearnedPattern {
             "not", "only", "own", "same", "so", "than", "too", "very", "just",
         ];
 
-        context
-            .split_whitespace()
-            .map(|w| w.to_lowercase())
-            .filter(|w| w.len() > 2)
-            .filter(|w| !stop_words.contains(&w.as_str()))
-            .take(10)
-            .collect()
+        let mut keywords: Vec<String> = Vec::new();
+        for word in Self::words(context) {
+            if word.len() > 2 && !stop_words.contains(&word.as_str()) && !keywords.contains(&word) {
+                keywords.push(word);
+            }
+        }
+        keywords
+    }
+
+    /// Lowercased words with surrounding punctuation removed
+    fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+        text.split_whitespace()
+            .map(|w| {
+                w.trim_matches(|c: char| !c.is_alphanumeric())
+                    .to_lowercase()
+            })
+            .filter(|w| !w.is_empty())
+    }
+
+    /// Cosine similarity of the keyword sets of two texts, from 0.0 to 1.0
+    pub fn text_similarity(a: &str, b: &str) -> f64 {
+        let a: HashSet<String> = Self::extract_keywords(a).into_iter().collect();
+        let b: HashSet<String> = Self::extract_keywords(b).into_iter().collect();
+        if a.is_empty() || b.is_empty() {
+            return 0.0;
+        }
+        let shared = a.intersection(&b).count() as f64;
+        shared / ((a.len() * b.len()) as f64).sqrt()
+    }
+
+    pub fn similarity(&self, context: &str) -> f64 {
+        Self::text_similarity(&self.context, context)
+    }
+
+    /// How far to trust this pattern for a context: its similarity, scaled
+    /// by the success rate and by how often it has succeeded (one success
+    /// counts half, each further one halves the remaining doubt)
+    pub fn confidence(&self, context: &str) -> f64 {
+        let support = 1.0 - 0.5f64.powi(self.success_count.min(32) as i32);
+        self.similarity(context) * self.success_rate * support
     }
 
     pub fn record_usage(&mut self, success: bool) {
         self.usage_count += 1;
         self.last_used = Some(Utc::now());
+        if success {
+            self.success_count += 1;
+        }
 
         let alpha = 0.2;
         let success_val = if success { 1.0 } else { 0.0 };
@@ -131,6 +183,18 @@ impl LearnedPattern {
     }
 }
 
+fn default_success_count() -> u64 {
+    1
+}
+
+/// A learned pattern recalled for a new context
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatternMatch {
+    pub pattern: LearnedPattern,
+    pub similarity: f64,
+    pub confidence: f64,
+}
+
 #[derive(Debug)]
 pub struct PatternLearner {
     patterns: HashMap<String, LearnedPattern>,
@@ -145,26 +209,57 @@ impl PatternLearner {
         }
     }
 
+    /// Store a context→outcome pair, or count another success for a
+    /// near-identical pattern already stored
     pub fn learn(&mut self, context: &str, outcome: &str) {
-        let pattern = LearnedPattern::new(context, outcome);
-
-        if let Some(similar) = self.find_similar(context) {
+        if let Some(similar) = self.find_duplicate(context, outcome) {
             if let Some(existing) = self.patterns.get_mut(&similar) {
                 existing.record_usage(true);
                 return;
             }
         }
 
+        let pattern = LearnedPattern::new(context, outcome);
         self.patterns.insert(pattern.id.clone(), pattern);
     }
 
-    fn find_similar(&self, context: &str) -> Option<String> {
-        for (id, pattern) in &self.patterns {
-            if pattern.relevance(context) > 0.7 {
-                return Some(id.clone());
-            }
-        }
-        None
+    fn find_duplicate(&self, context: &str, outcome: &str) -> Option<String> {
+        let duplicate = |pattern: &LearnedPattern| {
+            let same_context = pattern.context.trim().eq_ignore_ascii_case(context.trim())
+                || pattern.similarity(context) >= DEDUP_SIMILARITY;
+            let same_outcome = pattern.outcome.trim().eq_ignore_ascii_case(outcome.trim())
+                || LearnedPattern::text_similarity(&pattern.outcome, outcome) >= DEDUP_SIMILARITY;
+            same_context && same_outcome
+        };
+        self.patterns
+            .values()
+            .filter(|pattern| duplicate(pattern))
+            .max_by(|a, b| a.similarity(context).total_cmp(&b.similarity(context)))
+            .map(|pattern| pattern.id.clone())
+    }
+
+    /// Up to `top_k` patterns most similar to `context`, ties going to the
+    /// more confident one
+    pub fn recall(&self, context: &str, top_k: usize) -> Vec<PatternMatch> {
+        let mut matches: Vec<PatternMatch> = self
+            .patterns
+            .values()
+            .map(|pattern| PatternMatch {
+                similarity: pattern.similarity(context),
+                confidence: pattern.confidence(context),
+                pattern: pattern.clone(),
+            })
+            .filter(|m| m.similarity >= RECALL_MIN_SIMILARITY)
+            .collect();
+
+        matches.sort_by(|a, b| {
+            b.similarity
+                .total_cmp(&a.similarity)
+                .then(b.confidence.total_cmp(&a.confidence))
+                .then(a.pattern.learned_at.cmp(&b.pattern.learned_at))
+        });
+        matches.truncate(top_k);
+        matches
     }
 
     pub fn pattern_count(&self) -> usize {
@@ -238,7 +333,7 @@ impl PatternLearner {
         let json = serde_json::to_string_pretty(&patterns)
             .map_err(|e| format!("Failed to serialize patterns: {}", e))?;
 
-        std::fs::write(path, json).map_err(|e| format!("Failed to write patterns: {}", e))?;
+        write_atomic(path, json).map_err(|e| format!("Failed to write patterns: {}", e))?;
 
         Ok(())
     }
@@ -277,7 +372,7 @@ mod tests {
         let pattern =
             LearnedPattern::new("How to prevent SQL injection?", "Use parameterized queries");
         assert_eq!(pattern.pattern_type, PatternType::QueryResponse);
-        assert!(pattern.keywords.len() > 0);
+        assert!(!pattern.keywords.is_empty());
     }
 
     #[test]
@@ -302,6 +397,48 @@ mod tests {
         );
 
         let relevant = learner.find_relevant("How to make my database faster");
-        assert!(relevant.len() > 0);
+        assert!(!relevant.is_empty());
+    }
+
+    #[test]
+    fn test_recall_orders_by_similarity_then_successes() {
+        let mut learner = PatternLearner::new();
+        learner.learn("Slow database query on orders table", "Add an index");
+        learner.learn("Database connection pool exhausted", "Raise the pool size");
+        learner.learn("Render a chart in the dashboard", "Use a canvas");
+
+        let matches = learner.recall("slow query on the orders table", 5);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].pattern.outcome, "Add an index");
+
+        let matches = learner.recall("database problems", 5);
+        assert_eq!(matches.len(), 2);
+        assert!(matches[0].similarity >= matches[1].similarity);
+
+        // Equal similarity: the pattern that succeeded more often wins
+        learner.learn("Database connection pool exhausted", "Restart the server");
+        learner.learn("Database connection pool exhausted", "Raise the pool size");
+        let matches = learner.recall("database connection pool exhausted", 2);
+        assert_eq!(matches[0].similarity, matches[1].similarity);
+        assert_eq!(matches[0].pattern.outcome, "Raise the pool size");
+        assert_eq!(matches[0].pattern.success_count, 2);
+        assert!(matches[0].confidence > matches[1].confidence);
+        assert!(learner.recall("unrelated weather talk", 5).is_empty());
+    }
+
+    #[test]
+    fn test_learn_deduplicates_near_identical_patterns() {
+        let mut learner = PatternLearner::new();
+        learner.learn("How to fix the login timeout?", "Increase the session TTL");
+        learner.learn("how to fix the login timeout", "increase the session ttl.");
+        assert_eq!(learner.pattern_count(), 1);
+        assert_eq!(learner.get_patterns(1)[0].success_count, 2);
+
+        // Same situation, different answer: kept separately
+        learner.learn(
+            "How to fix the login timeout?",
+            "Refresh the token in the background",
+        );
+        assert_eq!(learner.pattern_count(), 2);
     }
 }
@@ -2,13 +2,26 @@ mod feedback;
 mod learner;
 mod optimizer;
 
-pub use feedback::{FeedbackEntry, FeedbackLoop, FeedbackType};
-pub use learner::{LearnedPattern, PatternLearner, PatternType};
+pub use feedback::{
+    FeedbackEntry, FeedbackLoop, FeedbackTrend, FeedbackType, DEFAULT_FEEDBACK_WINDOW,
+};
+pub use learner::{
+    LearnedPattern, PatternLearner, PatternMatch, PatternType, DEDUP_SIMILARITY,
+    RECALL_MIN_SIMILARITY,
+};
 pub use optimizer::{OptimizationResult, OptimizationTarget, SelfOptimizer};
 
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
+use crate::sync::write_atomic;
+
+/// Largest share of the improvement score feedback can add or take away
+const FEEDBACK_SCORE_WEIGHT: f64 = 0.3;
+
+/// Least confidence for a recalled pattern to be offered as a suggestion
+pub const SUGGESTION_MIN_CONFIDENCE: f64 = 0.3;
+
 #[derive(Debug)]
 pub struct EvolutionSystem {
     pub learner: PatternLearner,
@@ -48,6 +61,16 @@ impl EvolutionSystem {
         self.update_stats();
     }
 
+    /// The learned pattern most worth reusing for `context`, if any is
+    /// confident enough
+    pub fn suggest(&self, context: &str) -> Option<PatternMatch> {
+        self.learner
+            .recall(context, 5)
+            .into_iter()
+            .filter(|m| m.confidence >= SUGGESTION_MIN_CONFIDENCE)
+            .max_by(|a, b| a.confidence.total_cmp(&b.confidence))
+    }
+
     pub fn optimize(&mut self, target: OptimizationTarget) -> OptimizationResult {
         let result = self.optimizer.optimize(target);
         if result.success {
@@ -75,6 +98,7 @@ impl EvolutionSystem {
     }
 
     pub fn evolve(&mut self) -> EvolutionResult {
+        let previous_score = self.stats.improvement_score;
         let feedback_insights = self.feedback.analyze();
         let patterns_applied = self.learner.apply_learnings();
         let optimization = self.optimizer.optimize(OptimizationTarget::Quality);
@@ -88,6 +112,8 @@ impl EvolutionSystem {
             optimizations_made: if optimization.success { 1 } else { 0 },
             feedback_processed: feedback_insights.len(),
             new_improvement_score: self.stats.improvement_score,
+            improvement_delta: self.stats.improvement_score - previous_score,
+            feedback_trend: self.feedback.trend(DEFAULT_FEEDBACK_WINDOW),
         }
     }
 
@@ -99,10 +125,12 @@ impl EvolutionSystem {
     fn calculate_improvement(&self) -> f64 {
         let pattern_factor = (self.stats.patterns_learned as f64 / 100.0).min(0.3);
         let optimization_factor = (self.stats.optimizations_applied as f64 / 50.0).min(0.3);
-        let feedback_factor = (self.stats.feedback_count as f64 / 100.0).min(0.2);
+        // Recent feedback pulls the score up or down
+        let feedback_factor =
+            self.feedback.sentiment(DEFAULT_FEEDBACK_WINDOW) * FEEDBACK_SCORE_WEIGHT;
 
         let base = 0.2;
-        (base + pattern_factor + optimization_factor + feedback_factor).min(1.0)
+        (base + pattern_factor + optimization_factor + feedback_factor).clamp(0.0, 1.0)
     }
 
     fn calculate_health(&self) -> String {
@@ -130,7 +158,7 @@ impl EvolutionSystem {
 
         let stats_json = serde_json::to_string_pretty(&self.stats)
             .map_err(|e| format!("Failed to serialize stats: {}", e))?;
-        std::fs::write(evolution_dir.join("stats.json"), stats_json)
+        write_atomic(evolution_dir.join("stats.json"), stats_json)
             .map_err(|e| format!("Failed to write stats: {}", e))?;
 
         Ok(())
@@ -179,6 +207,11 @@ pub struct EvolutionResult {
     pub optimizations_made: usize,
     pub feedback_processed: usize,
     pub new_improvement_score: f64,
+    /// Change from the score of the previous cycle
+    #[serde(default)]
+    pub improvement_delta: f64,
+    #[serde(default)]
+    pub feedback_trend: FeedbackTrend,
 }
 
 #[cfg(test)]
@@ -215,4 +248,39 @@ mod tests {
         let result = system.evolve();
         assert!(result.new_improvement_score > 0.0);
     }
+
+    #[test]
+    fn test_negative_feedback_lowers_score() {
+        let mut system = EvolutionSystem::new();
+        system.process_feedback(FeedbackType::Positive, "Great answer");
+        let baseline = system.evolve();
+        assert!(baseline.improvement_delta > 0.0);
+
+        for _ in 0..3 {
+            system.process_feedback(FeedbackType::Negative, "Wrong again");
+        }
+        let result = system.evolve();
+        assert!(result.new_improvement_score < baseline.new_improvement_score);
+        assert!(result.improvement_delta < 0.0);
+        assert_eq!(result.feedback_trend, FeedbackTrend::Regressing);
+    }
+
+    #[test]
+    fn test_suggest_recalls_learned_outcome() {
+        let mut system = EvolutionSystem::new();
+        system.learn(
+            "Flaky integration test on CI",
+            "Pin the test database",
+            true,
+        );
+        system.learn(
+            "Flaky integration test on CI",
+            "Pin the test database",
+            true,
+        );
+
+        let suggestion = system.suggest("integration test is flaky on CI").unwrap();
+        assert_eq!(suggestion.pattern.outcome, "Pin the test database");
+        assert!(system.suggest("draw a logo").is_none());
+    }
 }
@@ -11,6 +11,10 @@ pub enum GitError {
     NotGitRepo,
     #[error("Parse error: {0}")]
     ParseError(String),
+    #[error("Commit signing failed (is a GPG key configured via user.signingkey?): {0}")]
+    SigningFailed(String),
+    #[error("Invalid commit author: {0}")]
+    InvalidAuthor(String),
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
 }
@@ -100,7 +104,14 @@ impl GitRepo {
 
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(GitError::CommandFailed(stderr.to_string()));
+            // Some failures, like "nothing to commit", only explain themselves
+            // on stdout
+            let message = if stderr.trim().is_empty() {
+                String::from_utf8_lossy(&output.stdout)
+            } else {
+                stderr
+            };
+            return Err(GitError::CommandFailed(message.trim().to_string()));
         }
 
         Ok(String::from_utf8_lossy(&output.stdout).to_string())
@@ -232,11 +243,18 @@ impl GitRepo {
             }
         }
 
+        let mut patch_args = vec!["diff"];
+        if staged {
+            patch_args.push("--cached");
+        }
+        patch_args.extend(["--unified=3", "--no-color", "--no-ext-diff"]);
+        let hunks = parse_hunks(&self.run_git(&patch_args)?);
+
         Ok(GitDiff {
             files_changed,
             insertions,
             deletions,
-            hunks: Vec::new(),
+            hunks,
         })
     }
 
@@ -252,10 +270,59 @@ impl GitRepo {
         Ok(())
     }
 
+    /// Repository containing the current directory, which may be below the
+    /// repository root
+    pub fn discover() -> GitResult<Self> {
+        let output = Command::new("git")
+            .args(["rev-parse", "--show-toplevel"])
+            .output()?;
+        if !output.status.success() {
+            return Err(GitError::NotGitRepo);
+        }
+        let root = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        Self::open(Path::new(&root))
+    }
+
     pub fn commit(&self, message: &str) -> GitResult<String> {
         self.run_git(&["commit", "-m", message])
     }
 
+    /// Commit with a GPG signature, using `key_id` or git's configured
+    /// `user.signingkey`. A signing failure is reported as
+    /// [`GitError::SigningFailed`] carrying git's stderr.
+    pub fn commit_signed(&self, message: &str, key_id: Option<&str>) -> GitResult<String> {
+        let sign = match key_id {
+            Some(key) => format!("-S{}", key),
+            None => "-S".to_string(),
+        };
+        self.run_git(&["commit", &sign, "-m", message])
+            .map_err(|e| match e {
+                GitError::CommandFailed(stderr) if is_signing_failure(&stderr) => {
+                    GitError::SigningFailed(stderr)
+                }
+                other => other,
+            })
+    }
+
+    /// Commit attributed to another author, such as the agent acting in a
+    /// hub session. The committer stays the configured git user.
+    pub fn commit_with_author(
+        &self,
+        message: &str,
+        author_name: &str,
+        author_email: &str,
+    ) -> GitResult<String> {
+        let invalid = |s: &str| s.trim().is_empty() || s.contains(['<', '>', '\n']);
+        if invalid(author_name) || invalid(author_email) {
+            return Err(GitError::InvalidAuthor(format!(
+                "{} <{}>",
+                author_name, author_email
+            )));
+        }
+        let author = format!("{} <{}>", author_name.trim(), author_email.trim());
+        self.run_git(&["commit", "--author", &author, "-m", message])
+    }
+
     pub fn current_branch(&self) -> GitResult<String> {
         let output = self.run_git(&["rev-parse", "--abbrev-ref", "HEAD"])?;
         Ok(output.trim().to_string())
@@ -304,6 +371,87 @@ impl GitRepo {
     }
 }
 
+/// Split unified diff output into hunks. Binary files have no hunks and
+/// are skipped; `\ No newline at end of file` markers stay in the body of
+/// the hunk they belong to.
+pub fn parse_hunks(patch: &str) -> Vec<DiffHunk> {
+    let mut hunks = Vec::new();
+    let mut old_file = String::new();
+    let mut file = String::new();
+    let mut current: Option<DiffHunk> = None;
+
+    for line in patch.lines() {
+        if line.starts_with("diff --git ") {
+            hunks.extend(current.take());
+            old_file.clear();
+            file.clear();
+        } else if current.is_none() && line.starts_with("--- ") {
+            old_file = diff_path(&line[4..], "a/");
+        } else if current.is_none() && line.starts_with("+++ ") {
+            file = match diff_path(&line[4..], "b/") {
+                // Deleted files only name the old path
+                path if path == "/dev/null" => old_file.clone(),
+                path => path,
+            };
+        } else if line.starts_with("@@ ") {
+            hunks.extend(current.take());
+            current =
+                parse_hunk_header(line).map(|(old_start, old_count, new_start, new_count)| {
+                    DiffHunk {
+                        file: file.clone(),
+                        old_start,
+                        old_count,
+                        new_start,
+                        new_count,
+                        content: String::new(),
+                    }
+                });
+        } else if let Some(hunk) = current.as_mut() {
+            hunk.content.push_str(line);
+            hunk.content.push('\n');
+        }
+    }
+    hunks.extend(current);
+    hunks
+}
+
+fn diff_path(raw: &str, prefix: &str) -> String {
+    let path = raw.trim_end().trim_matches('"');
+    path.strip_prefix(prefix).unwrap_or(path).to_string()
+}
+
+/// `@@ -a,b +c,d @@`, where an omitted count means one line
+fn parse_hunk_header(line: &str) -> Option<(usize, usize, usize, usize)> {
+    let ranges = line.strip_prefix("@@ ")?.split(" @@").next()?;
+    let (old, new) = ranges.split_once(' ')?;
+
+    let parse_range = |range: &str| -> Option<(usize, usize)> {
+        match range.split_once(',') {
+            Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+            None => Some((range.parse().ok()?, 1)),
+        }
+    };
+    let (old_start, old_count) = parse_range(old.strip_prefix('-')?)?;
+    let (new_start, new_count) = parse_range(new.strip_prefix('+')?)?;
+    Some((old_start, old_count, new_start, new_count))
+}
+
+fn is_signing_failure(stderr: &str) -> bool {
+    let stderr = stderr.to_lowercase();
+    [
+        "gpg failed to sign",
+        "failed to sign the data",
+        "secret key not available",
+        "no secret key",
+        "no default secret key",
+        "cannot run gpg",
+        "signing failed",
+        "ssh-keygen",
+    ]
+    .iter()
+    .any(|marker| stderr.contains(marker))
+}
+
 fn parse_status_char(c: char) -> Option<ChangeStatus> {
     match c {
         'A' => Some(ChangeStatus::Added),
@@ -317,6 +465,53 @@ fn parse_status_char(c: char) -> Option<ChangeStatus> {
     }
 }
 
+/// How auto-generated commit messages are phrased
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum CommitStyle {
+    /// "Update src/main.rs", "Add/Update code (3 files)"
+    #[default]
+    Plain,
+    /// Conventional Commits: "feat(hub): add session.rs"
+    Conventional,
+}
+
+/// Conventional Commits types accepted as an explicit commit type
+pub const COMMIT_TYPES: &[&str] = &[
+    "build", "chore", "ci", "docs", "feat", "fix", "perf", "refactor", "style", "test",
+];
+
+impl CommitStyle {
+    /// `commit_type` overrides the inferred Conventional Commits type; the
+    /// plain style ignores it.
+    pub fn message(
+        &self,
+        diff_summary: &str,
+        file_changes: &[FileChange],
+        commit_type: Option<&str>,
+    ) -> Result<String, String> {
+        match self {
+            CommitStyle::Plain => Ok(generate_commit_message(diff_summary, file_changes)),
+            CommitStyle::Conventional => {
+                generate_commit_message_conventional(diff_summary, file_changes, commit_type)
+            }
+        }
+    }
+}
+
+/// Parse `git diff --name-status` output. Renames and copies report their
+/// new path.
+pub fn parse_name_status(output: &str) -> Vec<FileChange> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (status, paths) = line.split_once('\t')?;
+            let status = parse_status_char(status.chars().next()?)?;
+            let path = paths.rsplit('\t').next()?.to_string();
+            Some(FileChange { path, status })
+        })
+        .collect()
+}
+
 pub fn generate_commit_message(diff_summary: &str, file_changes: &[FileChange]) -> String {
     let change_count = file_changes.len();
 
@@ -369,6 +564,182 @@ pub fn generate_commit_message(diff_summary: &str, file_changes: &[FileChange])
     format!("{} {} ({} files)", actions, prefix, change_count)
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PathKind {
+    Test,
+    Deps,
+    Docs,
+    Config,
+    Code,
+}
+
+const DEPENDENCY_FILES: &[&str] = &[
+    "Cargo.toml",
+    "Cargo.lock",
+    "package.json",
+    "package-lock.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "go.mod",
+    "go.sum",
+    "requirements.txt",
+    "poetry.lock",
+    "Gemfile.lock",
+];
+
+/// Directories too generic to make a useful scope; the one below is used
+const SCOPE_ROOTS: &[&str] = &["src", "lib", "crates", "packages", "pkg", "app"];
+
+fn path_kind(path: &str) -> PathKind {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    let lower = path.to_lowercase();
+
+    if DEPENDENCY_FILES.contains(&name) {
+        PathKind::Deps
+    } else if lower.starts_with("tests/")
+        || lower.contains("/tests/")
+        || lower.contains("/test/")
+        || name.starts_with("test_")
+        || name.contains("_test.")
+        || name.contains(".test.")
+        || name.contains(".spec.")
+    {
+        PathKind::Test
+    } else if lower.ends_with(".md")
+        || lower.starts_with("docs/")
+        || name.starts_with("LICENSE")
+        || name.starts_with("README")
+    {
+        PathKind::Docs
+    } else if path.starts_with('.')
+        || [".toml", ".yml", ".yaml", ".json", ".ini", ".cfg"]
+            .iter()
+            .any(|ext| lower.ends_with(ext))
+    {
+        PathKind::Config
+    } else {
+        PathKind::Code
+    }
+}
+
+/// Directory under any generic root that every path lives in, if they agree
+fn commit_scope(file_changes: &[FileChange]) -> Option<String> {
+    let mut scopes = file_changes.iter().map(|change| {
+        let dirs: Vec<&str> = change.path.split('/').collect();
+        let dirs = &dirs[..dirs.len() - 1];
+        dirs.iter()
+            .find(|dir| !SCOPE_ROOTS.contains(dir) && !dir.starts_with('.'))
+            .map(|dir| dir.to_string())
+    });
+    let first = scopes.next()??;
+    scopes
+        .all(|scope| scope.as_ref() == Some(&first))
+        .then_some(first)
+}
+
+/// Conventional Commits message (`type(scope): description`). The type comes
+/// from what the paths are and how they changed: only tests is `test`, only
+/// dependency manifests or lockfiles is `chore(deps)`, only docs is `docs`,
+/// only configuration is `chore`; code that gains files is `feat`, code that
+/// is only renamed, deleted or has lines removed (per the `--stat`
+/// `diff_summary`) is `refactor`. Other code edits could be a fix or a
+/// feature, so they need an explicit `commit_type`, which also overrides
+/// any inferred type.
+pub fn generate_commit_message_conventional(
+    diff_summary: &str,
+    file_changes: &[FileChange],
+    commit_type: Option<&str>,
+) -> Result<String, String> {
+    if let Some(commit_type) = commit_type {
+        if !COMMIT_TYPES.contains(&commit_type) {
+            return Err(format!(
+                "Unknown commit type '{}' (expected one of: {})",
+                commit_type,
+                COMMIT_TYPES.join(", ")
+            ));
+        }
+    }
+    if file_changes.is_empty() {
+        return Ok(format!("{}: update files", commit_type.unwrap_or("chore")));
+    }
+
+    let kinds: Vec<PathKind> = file_changes.iter().map(|c| path_kind(&c.path)).collect();
+    let only = |kind: PathKind| kinds.iter().all(|k| *k == kind);
+    let code: Vec<&FileChange> = file_changes
+        .iter()
+        .zip(&kinds)
+        .filter(|(_, kind)| **kind == PathKind::Code)
+        .map(|(change, _)| change)
+        .collect();
+
+    let commit_type = if let Some(commit_type) = commit_type {
+        commit_type
+    } else if only(PathKind::Test) {
+        "test"
+    } else if only(PathKind::Deps) {
+        return Ok(format!("chore(deps): {}", describe_changes(file_changes)));
+    } else if only(PathKind::Docs) {
+        "docs"
+    } else if code.is_empty() {
+        "chore"
+    } else if code.iter().any(|c| c.status == ChangeStatus::Added) {
+        "feat"
+    } else if code
+        .iter()
+        .all(|c| matches!(c.status, ChangeStatus::Renamed | ChangeStatus::Deleted))
+        || (diff_summary.contains("deletion") && !diff_summary.contains("insertion"))
+    {
+        "refactor"
+    } else {
+        return Err(
+            "Cannot infer the commit type from the staged changes; pass --type or --message"
+                .to_string(),
+        );
+    };
+
+    Ok(
+        match commit_scope(file_changes).filter(|scope| scope != commit_type) {
+            Some(scope) => format!(
+                "{}({}): {}",
+                commit_type,
+                scope,
+                describe_changes(file_changes)
+            ),
+            None => format!("{}: {}", commit_type, describe_changes(file_changes)),
+        },
+    )
+}
+
+fn describe_changes(file_changes: &[FileChange]) -> String {
+    if let [change] = file_changes {
+        let verb = match change.status {
+            ChangeStatus::Added => "add",
+            ChangeStatus::Deleted => "remove",
+            ChangeStatus::Renamed => "rename",
+            _ => "update",
+        };
+        let name = change.path.rsplit('/').next().unwrap_or(&change.path);
+        return format!("{} {}", verb, name);
+    }
+
+    let has = |status: ChangeStatus| file_changes.iter().any(|c| c.status == status);
+    let verbs = [
+        (ChangeStatus::Added, "add"),
+        (ChangeStatus::Modified, "update"),
+        (ChangeStatus::Deleted, "remove"),
+    ]
+    .iter()
+    .filter(|(status, _)| has(*status))
+    .map(|(_, verb)| *verb)
+    .collect::<Vec<_>>();
+    let verbs = if verbs.is_empty() {
+        "update".to_string()
+    } else {
+        verbs.join("/")
+    };
+    format!("{} {} files", verbs, file_changes.len())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -410,6 +781,218 @@ mod tests {
         assert!(msg.contains("2 files"));
     }
 
+    fn change(path: &str, status: ChangeStatus) -> FileChange {
+        FileChange {
+            path: path.to_string(),
+            status,
+        }
+    }
+
+    #[test]
+    fn test_conventional_message_types() {
+        use ChangeStatus::*;
+        let conventional = |changes: &[FileChange]| {
+            CommitStyle::Conventional
+                .message(" 2 files changed, 5 insertions(+)", changes, None)
+                .unwrap()
+        };
+
+        assert_eq!(
+            conventional(&[
+                change("tests/cli.rs", Modified),
+                change("src/hub/test/peers.rs", Added)
+            ]),
+            "test: add/update 2 files"
+        );
+        assert_eq!(
+            conventional(&[
+                change("Cargo.toml", Modified),
+                change("Cargo.lock", Modified)
+            ]),
+            "chore(deps): update 2 files"
+        );
+        assert_eq!(
+            conventional(&[change("docs/guide.md", Modified)]),
+            "docs: update guide.md"
+        );
+        assert_eq!(
+            conventional(&[change("README.md", Modified)]),
+            "docs: update README.md"
+        );
+        assert_eq!(
+            conventional(&[change(".github/workflows/ci.yml", Modified)]),
+            "chore(workflows): update ci.yml"
+        );
+        assert_eq!(
+            conventional(&[
+                change("src/hub/session.rs", Added),
+                change("src/hub/mod.rs", Modified)
+            ]),
+            "feat(hub): add/update 2 files"
+        );
+        assert!(CommitStyle::Conventional
+            .message("", &[change("src/git/mod.rs", Modified)], None)
+            .unwrap_err()
+            .contains("--type"));
+        assert_eq!(
+            CommitStyle::Conventional
+                .message("", &[change("src/git/mod.rs", Modified)], Some("fix"))
+                .unwrap(),
+            "fix(git): update mod.rs"
+        );
+        assert_eq!(
+            CommitStyle::Conventional
+                .message("", &[change("docs/guide.md", Modified)], Some("feat"))
+                .unwrap(),
+            "feat(docs): update guide.md"
+        );
+        assert!(CommitStyle::Conventional
+            .message("", &[change("src/git/mod.rs", Modified)], Some("bugfix"))
+            .is_err());
+        assert_eq!(
+            conventional(&[
+                change("src/old.rs", Deleted),
+                change("src/cli/args.rs", Renamed)
+            ]),
+            "refactor: remove 2 files"
+        );
+        assert_eq!(
+            CommitStyle::Conventional
+                .message(
+                    " 1 file changed, 12 deletions(-)",
+                    &[change("crates/sena-collab/src/session.rs", Modified)],
+                    None
+                )
+                .unwrap(),
+            "refactor(sena-collab): update session.rs"
+        );
+        assert_eq!(
+            CommitStyle::Plain
+                .message("", &[change("src/main.rs", Modified)], None)
+                .unwrap(),
+            "Update src/main.rs"
+        );
+    }
+
+    #[test]
+    fn test_parse_name_status() {
+        let changes =
+            parse_name_status("M\tsrc/main.rs\nR087\tsrc/old.rs\tsrc/new.rs\nA\tREADME.md\n");
+        assert_eq!(changes.len(), 3);
+        assert_eq!(changes[1].path, "src/new.rs");
+        assert_eq!(changes[1].status, ChangeStatus::Renamed);
+    }
+
+    #[test]
+    fn test_diff_parses_staged_hunks() {
+        let dir = std::env::temp_dir().join(format!("sena-git-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let git = |args: &[&str]| {
+            let status = Command::new("git")
+                .current_dir(&dir)
+                .args(["-c", "user.name=sena", "-c", "user.email=sena@example.com"])
+                .args(args)
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success(), "git {:?}", args);
+        };
+
+        let lines: Vec<String> = (1..=20).map(|i| format!("line {}", i)).collect();
+        std::fs::write(dir.join("notes.txt"), lines.join("\n") + "\n").unwrap();
+        std::fs::write(dir.join("tail.txt"), "first\nlast").unwrap();
+        std::fs::write(dir.join("blob.bin"), [0u8, 1, 2, 3]).unwrap();
+        git(&["init", "-q"]);
+        git(&["add", "-A"]);
+        git(&["commit", "-q", "-m", "initial"]);
+
+        let mut edited = lines.clone();
+        edited[9] = "line ten".to_string();
+        edited.insert(15, "inserted".to_string());
+        std::fs::write(dir.join("notes.txt"), edited.join("\n") + "\n").unwrap();
+        std::fs::write(dir.join("tail.txt"), "first\nchanged").unwrap();
+        std::fs::write(dir.join("blob.bin"), [0u8, 9, 9, 9]).unwrap();
+        git(&["add", "-A"]);
+
+        let repo = GitRepo::open(&dir).unwrap();
+        let diff = repo.diff(true).unwrap();
+        assert_eq!(diff.files_changed, 3);
+
+        let notes: Vec<&DiffHunk> = diff
+            .hunks
+            .iter()
+            .filter(|h| h.file == "notes.txt")
+            .collect();
+        assert_eq!(notes.len(), 1);
+        let hunk = notes[0];
+        assert_eq!(
+            (
+                hunk.old_start,
+                hunk.old_count,
+                hunk.new_start,
+                hunk.new_count
+            ),
+            (7, 12, 7, 13)
+        );
+        assert!(hunk.content.contains("-line 10\n+line ten\n"));
+        assert!(hunk.content.contains("+inserted\n"));
+
+        let tail: Vec<&DiffHunk> = diff.hunks.iter().filter(|h| h.file == "tail.txt").collect();
+        assert_eq!(tail.len(), 1);
+        assert_eq!((tail[0].old_start, tail[0].old_count), (1, 2));
+        assert!(tail[0].content.contains("\\ No newline at end of file"));
+
+        assert!(diff.hunks.iter().all(|h| h.file != "blob.bin"));
+        assert!(repo.diff(false).unwrap().hunks.is_empty());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_signed_commit_failure_and_author_override() {
+        let dir = std::env::temp_dir().join(format!("sena-git-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let git = |args: &[&str]| {
+            let output = Command::new("git")
+                .current_dir(&dir)
+                .args(args)
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "git {:?}", args);
+            String::from_utf8_lossy(&output.stdout).to_string()
+        };
+        git(&["init", "-q"]);
+        git(&["config", "user.name", "sena"]);
+        git(&["config", "user.email", "sena@example.com"]);
+        git(&["config", "gpg.program", "false"]);
+        std::fs::write(dir.join("a.txt"), "a\n").unwrap();
+        git(&["add", "-A"]);
+
+        let repo = GitRepo::open(&dir).unwrap();
+        match repo.commit_signed("signed", Some("DEADBEEF")) {
+            Err(GitError::SigningFailed(stderr)) => assert!(stderr.contains("gpg")),
+            other => panic!("expected SigningFailed, got {:?}", other),
+        }
+
+        assert!(matches!(
+            repo.commit_with_author("agent", "Evil <x>", "a@b.c"),
+            Err(GitError::InvalidAuthor(_))
+        ));
+        repo.commit_with_author("by agent", "review-agent", "agent@sena.local")
+            .unwrap();
+        assert_eq!(
+            git(&["log", "-1", "--format=%an <%ae>|%cn"]).trim(),
+            "review-agent <agent@sena.local>|sena"
+        );
+
+        match repo.commit("empty") {
+            Err(GitError::CommandFailed(message)) => assert!(!message.is_empty()),
+            other => panic!("expected CommandFailed, got {:?}", other),
+        }
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
     #[test]
     fn test_git_repo_not_found() {
         let result = GitRepo::open(Path::new("/nonexistent/path"));
@@ -17,6 +17,9 @@ pub enum GuardianError {
     #[error("Validation failed: {0}")]
     ValidationFailed(String),
 
+    #[error("Invalid guardian rules: {0}")]
+    InvalidRules(String),
+
     #[error("Command not found: {0}")]
     CommandNotFound(String),
 
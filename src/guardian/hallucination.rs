@@ -1,9 +1,13 @@
 use std::collections::HashSet;
 use std::sync::{Arc, RwLock};
 
+use serde::{Deserialize, Serialize};
+
+use super::hallucination_log::HallucinationLog;
 use crate::ancient::{HarmonyStatus, HarmonyValidationEngine, NegativeSpaceArchitecture};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum HallucinationResponse {
     Block,
     Warn,
@@ -36,6 +40,7 @@ pub struct HallucinationDetector {
     block_threshold: f64,
     warn_threshold: f64,
     log_threshold: f64,
+    history: Option<HallucinationLog>,
 }
 
 impl HallucinationDetector {
@@ -50,6 +55,7 @@ impl HallucinationDetector {
             block_threshold: 0.85,
             warn_threshold: 0.70,
             log_threshold: 0.50,
+            history: None,
         }
     }
 
@@ -65,9 +71,16 @@ impl HallucinationDetector {
             block_threshold: 0.85,
             warn_threshold: 0.70,
             log_threshold: 0.50,
+            history: None,
         }
     }
 
+    /// Append every check to `log`
+    pub fn with_log(mut self, log: HallucinationLog) -> Self {
+        self.history = Some(log);
+        self
+    }
+
     pub fn check(&self, content: &str) -> HallucinationResult {
         let ns_check = self
             .negative_space
@@ -119,7 +132,7 @@ impl HallucinationDetector {
             warnings.push(format!("Suspicious pattern: {}", pattern));
         }
 
-        HallucinationResult {
+        let result = HallucinationResult {
             is_hallucination,
             risk_score,
             response,
@@ -131,7 +144,19 @@ impl HallucinationDetector {
                 fact_validation_score: harmony_result.overall_confidence,
                 suspicious_patterns,
             },
+        };
+
+        if let Some(history) = &self.history {
+            if let Err(e) = history.record(content, &result) {
+                log::warn!(
+                    "Cannot record hallucination check to {}: {}",
+                    history.path().display(),
+                    e
+                );
+            }
         }
+
+        result
     }
 
     fn calculate_semantic_entropy(&self, text: &str) -> f64 {
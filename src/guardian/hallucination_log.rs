@@ -0,0 +1,335 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::hallucination::{HallucinationResponse, HallucinationResult};
+use crate::tools::history::rotated_path;
+
+/// Thresholds `stats()` compares against the decisions already made
+pub const DEFAULT_CANDIDATE_THRESHOLDS: &[f64] = &[0.5, 0.55, 0.6, 0.65, 0.7, 0.75, 0.8, 0.85, 0.9];
+
+/// Size at which the log is rotated to `hallucinations.jsonl.1`
+pub const HALLUCINATION_LOG_ROTATE_BYTES: u64 = 5 * 1024 * 1024;
+
+/// One persisted hallucination check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HallucinationRecord {
+    pub checked_at: DateTime<Utc>,
+    /// SHA-256 of the checked content
+    pub content_hash: String,
+    /// Only kept when the log is created with `store_content`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    pub risk_score: f64,
+    pub is_hallucination: bool,
+    pub response: HallucinationResponse,
+    pub consistency_score: f64,
+    pub semantic_entropy: f64,
+    pub fact_validation_score: f64,
+    #[serde(default)]
+    pub suspicious_patterns: Vec<String>,
+}
+
+impl HallucinationRecord {
+    pub fn new(content: &str, result: &HallucinationResult, store_content: bool) -> Self {
+        Self {
+            checked_at: Utc::now(),
+            content_hash: hex::encode(Sha256::digest(content.as_bytes())),
+            content: store_content.then(|| content.to_string()),
+            risk_score: result.risk_score,
+            is_hallucination: result.is_hallucination,
+            response: result.response,
+            consistency_score: result.details.consistency_score,
+            semantic_entropy: result.details.semantic_entropy,
+            fact_validation_score: result.details.fact_validation_score,
+            suspicious_patterns: result.details.suspicious_patterns.clone(),
+        }
+    }
+}
+
+/// Append-only JSONL history of hallucination checks, kept for tuning the
+/// detection threshold. The file is rotated once it reaches
+/// `HALLUCINATION_LOG_ROTATE_BYTES`, keeping one previous generation.
+#[derive(Debug, Clone)]
+pub struct HallucinationLog {
+    path: PathBuf,
+    store_content: bool,
+}
+
+impl HallucinationLog {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            store_content: false,
+        }
+    }
+
+    pub fn default_path() -> PathBuf {
+        dirs::home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(".sena")
+            .join("guardian")
+            .join("hallucinations.jsonl")
+    }
+
+    /// The log at the default path as set up by the `[guardian]` config
+    /// section, or `None` when logging is turned off
+    pub fn configured() -> Option<Self> {
+        let config = &crate::config::SenaConfig::global().guardian;
+        config
+            .log_hallucinations
+            .then(|| Self::new(Self::default_path()).with_store_content(config.store_content))
+    }
+
+    /// Keep the checked content alongside its hash
+    pub fn with_store_content(mut self, store_content: bool) -> Self {
+        self.store_content = store_content;
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn record(&self, content: &str, result: &HallucinationResult) -> std::io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        if fs::metadata(&self.path).is_ok_and(|m| m.len() >= HALLUCINATION_LOG_ROTATE_BYTES) {
+            fs::rename(&self.path, rotated_path(&self.path))?;
+        }
+        let record = HallucinationRecord::new(content, result, self.store_content);
+        let line = serde_json::to_string(&record)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", line)
+    }
+
+    /// Every readable record including the rotated file, oldest first;
+    /// malformed lines are skipped
+    pub fn records(&self) -> Vec<HallucinationRecord> {
+        [rotated_path(&self.path), self.path.clone()]
+            .iter()
+            .filter_map(|path| fs::read_to_string(path).ok())
+            .flat_map(|content| {
+                content
+                    .lines()
+                    .filter_map(|line| serde_json::from_str(line).ok())
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    pub fn stats(&self) -> HallucinationStats {
+        self.stats_with(DEFAULT_CANDIDATE_THRESHOLDS)
+    }
+
+    pub fn stats_with(&self, candidates: &[f64]) -> HallucinationStats {
+        HallucinationStats::from_records(&self.records(), candidates)
+    }
+}
+
+/// Nearest-rank percentiles of the logged risk scores
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScorePercentiles {
+    pub p50: f64,
+    pub p75: f64,
+    pub p90: f64,
+    pub p95: f64,
+    pub p99: f64,
+}
+
+/// How the logged checks would have been judged at another threshold
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ThresholdImpact {
+    pub threshold: f64,
+    /// Checks scoring above the threshold
+    pub above: usize,
+    /// Checks that passed but would be flagged
+    pub newly_flagged: usize,
+    /// Checks that were flagged but would pass
+    pub newly_passed: usize,
+}
+
+impl ThresholdImpact {
+    pub fn flipped(&self) -> usize {
+        self.newly_flagged + self.newly_passed
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HallucinationStats {
+    pub count: usize,
+    pub flagged: usize,
+    pub mean: f64,
+    pub min: f64,
+    pub max: f64,
+    pub percentiles: ScorePercentiles,
+    pub thresholds: Vec<ThresholdImpact>,
+}
+
+impl HallucinationStats {
+    pub fn from_records(records: &[HallucinationRecord], candidates: &[f64]) -> Self {
+        if records.is_empty() {
+            return Self::default();
+        }
+
+        let mut scores: Vec<f64> = records.iter().map(|r| r.risk_score).collect();
+        scores.sort_by(f64::total_cmp);
+        let percentile = |p: f64| {
+            let rank = ((p / 100.0) * scores.len() as f64).ceil() as usize;
+            scores[rank.clamp(1, scores.len()) - 1]
+        };
+
+        let thresholds = candidates
+            .iter()
+            .map(|&threshold| {
+                let mut impact = ThresholdImpact {
+                    threshold,
+                    above: 0,
+                    newly_flagged: 0,
+                    newly_passed: 0,
+                };
+                for record in records {
+                    // Same comparison the detector uses
+                    let flagged = record.risk_score > threshold;
+                    impact.above += usize::from(flagged);
+                    impact.newly_flagged += usize::from(flagged && !record.is_hallucination);
+                    impact.newly_passed += usize::from(!flagged && record.is_hallucination);
+                }
+                impact
+            })
+            .collect();
+
+        Self {
+            count: records.len(),
+            flagged: records.iter().filter(|r| r.is_hallucination).count(),
+            mean: scores.iter().sum::<f64>() / scores.len() as f64,
+            min: scores[0],
+            max: scores[scores.len() - 1],
+            percentiles: ScorePercentiles {
+                p50: percentile(50.0),
+                p75: percentile(75.0),
+                p90: percentile(90.0),
+                p95: percentile(95.0),
+                p99: percentile(99.0),
+            },
+            thresholds,
+        }
+    }
+
+    pub fn at(&self, threshold: f64) -> Option<&ThresholdImpact> {
+        self.thresholds
+            .iter()
+            .find(|impact| (impact.threshold - threshold).abs() < 1e-9)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ancient::HarmonyStatus;
+    use crate::guardian::hallucination::HallucinationDetails;
+
+    fn result(risk_score: f64, threshold: f64) -> HallucinationResult {
+        HallucinationResult {
+            is_hallucination: risk_score > threshold,
+            risk_score,
+            response: HallucinationResponse::Pass,
+            harmony_status: HarmonyStatus::Harmonious,
+            warnings: Vec::new(),
+            details: HallucinationDetails::default(),
+        }
+    }
+
+    #[test]
+    fn test_stats_count_above_candidate_threshold() {
+        let dir =
+            std::env::temp_dir().join(format!("sena-hallucinations-{}", uuid::Uuid::new_v4()));
+        let log = HallucinationLog::new(dir.join("hallucinations.jsonl"));
+        for score in [0.1, 0.3, 0.55, 0.65, 0.72, 0.8, 0.95] {
+            log.record("The sky is green", &result(score, 0.7)).unwrap();
+        }
+
+        let stats = log.stats_with(&[0.6, 0.7, 0.9]);
+        assert_eq!(stats.count, 7);
+        assert_eq!(stats.flagged, 3);
+        assert_eq!(stats.percentiles.p50, 0.65);
+        assert_eq!(stats.max, 0.95);
+
+        let lower = stats.at(0.6).unwrap();
+        assert_eq!(lower.above, 4);
+        assert_eq!((lower.newly_flagged, lower.newly_passed), (1, 0));
+        assert_eq!(stats.at(0.7).unwrap().flipped(), 0);
+        let higher = stats.at(0.9).unwrap();
+        assert_eq!(higher.above, 1);
+        assert_eq!((higher.newly_flagged, higher.newly_passed), (0, 2));
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_content_is_hashed_unless_stored() {
+        let dir =
+            std::env::temp_dir().join(format!("sena-hallucinations-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("hallucinations.jsonl");
+        HallucinationLog::new(&path)
+            .record("secret plans", &result(0.2, 0.7))
+            .unwrap();
+        HallucinationLog::new(&path)
+            .with_store_content(true)
+            .record("public notes", &result(0.2, 0.7))
+            .unwrap();
+
+        let raw = fs::read_to_string(&path).unwrap();
+        assert!(!raw.contains("secret plans"));
+        let records = HallucinationLog::new(&path).records();
+        assert_eq!(records[0].content, None);
+        assert_eq!(records[0].content_hash.len(), 64);
+        assert_eq!(records[1].content.as_deref(), Some("public notes"));
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_log_rotates_at_size_cap() {
+        let dir =
+            std::env::temp_dir().join(format!("sena-hallucinations-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("hallucinations.jsonl");
+        let log = HallucinationLog::new(&path);
+        log.record("first", &result(0.2, 0.7)).unwrap();
+
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        writeln!(
+            file,
+            "{}",
+            "x".repeat(HALLUCINATION_LOG_ROTATE_BYTES as usize)
+        )
+        .unwrap();
+        log.record("second", &result(0.9, 0.7)).unwrap();
+
+        assert!(rotated_path(&path).exists());
+        assert!(fs::metadata(&path).unwrap().len() < HALLUCINATION_LOG_ROTATE_BYTES);
+        assert_eq!(log.stats().count, 2);
+
+        // A second rotation replaces the previous generation
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        writeln!(
+            file,
+            "{}",
+            "x".repeat(HALLUCINATION_LOG_ROTATE_BYTES as usize)
+        )
+        .unwrap();
+        log.record("third", &result(0.1, 0.7)).unwrap();
+        assert_eq!(log.stats().count, 2);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -2,14 +2,23 @@ mod config;
 mod error;
 mod executor;
 mod hallucination;
+mod hallucination_log;
 mod interceptor;
+mod rules;
+mod shell;
 mod validator;
 
 pub use config::{GuardianConfig, HallucinationMode, SandboxLevel};
 pub use error::{GuardianError, GuardianResult};
 pub use executor::{DirectExecutor, InlineExecutable};
 pub use hallucination::{HallucinationDetector, HallucinationResponse, HallucinationResult};
+pub use hallucination_log::{
+    HallucinationLog, HallucinationRecord, HallucinationStats, ScorePercentiles, ThresholdImpact,
+    DEFAULT_CANDIDATE_THRESHOLDS,
+};
 pub use interceptor::{InterceptedOutput, StreamInterceptor};
+pub use rules::{builtin_rules, CommandRule, GuardianRules, RuleAction, ScoreAggregation};
+pub use shell::{split_commands, tokenize, ValidationContext};
 pub use validator::{CommandValidator, ValidationResult};
 
 use std::sync::{Arc, RwLock};
@@ -35,7 +44,7 @@ impl GuardianMiddleware {
         Self {
             negative_space: Arc::clone(&negative_space),
             harmony_validator: Arc::clone(&harmony_validator),
-            command_validator: CommandValidator::new(Arc::clone(&negative_space)),
+            command_validator: CommandValidator::configured(Arc::clone(&negative_space)),
             hallucination_detector: HallucinationDetector::new(
                 Arc::clone(&negative_space),
                 Arc::clone(&harmony_validator),
@@ -52,7 +61,8 @@ impl GuardianMiddleware {
         Self {
             negative_space: Arc::clone(&negative_space),
             harmony_validator: Arc::clone(&harmony_validator),
-            command_validator: CommandValidator::new(Arc::clone(&negative_space)),
+            command_validator: CommandValidator::configured(Arc::clone(&negative_space))
+                .with_block_threshold(config.block_threshold),
             hallucination_detector: HallucinationDetector::with_threshold(
                 Arc::clone(&negative_space),
                 Arc::clone(&harmony_validator),
@@ -67,6 +77,20 @@ impl GuardianMiddleware {
         self.command_validator.validate(command)
     }
 
+    pub fn validate_command_in(
+        &self,
+        command: &str,
+        context: &ValidationContext,
+    ) -> ValidationResult {
+        self.command_validator.validate_in(command, context)
+    }
+
+    /// Record every hallucination check to `log`
+    pub fn with_hallucination_log(mut self, log: HallucinationLog) -> Self {
+        self.hallucination_detector = self.hallucination_detector.with_log(log);
+        self
+    }
+
     pub fn check_hallucination(&self, content: &str) -> HallucinationResult {
         self.hallucination_detector.check(content)
     }
@@ -0,0 +1,193 @@
+use std::path::{Path, PathBuf};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use super::error::{GuardianError, GuardianResult};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleAction {
+    /// Adds the rule's weight to the command's risk score
+    #[default]
+    Deny,
+    /// Skips the path checks for the command it matches; matching deny
+    /// rules still apply
+    Allow,
+}
+
+/// How the weights of matched deny rules combine into one risk score
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoreAggregation {
+    #[default]
+    Max,
+    /// Sum of the weights, capped at 1.0
+    WeightedSum,
+}
+
+impl ScoreAggregation {
+    pub fn combine(&self, weights: impl IntoIterator<Item = f64>) -> f64 {
+        match self {
+            ScoreAggregation::Max => weights.into_iter().fold(0.0, f64::max),
+            ScoreAggregation::WeightedSum => weights.into_iter().sum::<f64>().min(1.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandRule {
+    pub name: String,
+    pub pattern: String,
+    #[serde(default = "default_weight")]
+    pub weight: f64,
+    #[serde(default)]
+    pub action: RuleAction,
+}
+
+fn default_weight() -> f64 {
+    1.0
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl CommandRule {
+    pub fn deny(name: impl Into<String>, pattern: impl Into<String>, weight: f64) -> Self {
+        Self {
+            name: name.into(),
+            pattern: pattern.into(),
+            weight,
+            action: RuleAction::Deny,
+        }
+    }
+
+    pub fn allow(name: impl Into<String>, pattern: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            pattern: pattern.into(),
+            weight: 0.0,
+            action: RuleAction::Allow,
+        }
+    }
+
+    pub fn compile(&self) -> GuardianResult<Regex> {
+        Regex::new(&self.pattern)
+            .map_err(|e| GuardianError::InvalidRules(format!("rule '{}': {}", self.name, e)))
+    }
+}
+
+/// The rules every validator starts from; a custom rule with the same name
+/// replaces one of these
+pub fn builtin_rules() -> Vec<CommandRule> {
+    [
+        (
+            r"rm\s+(-[rf]+\s+)*(/\*?|~/?)(\s|$)",
+            "Dangerous recursive delete",
+        ),
+        (
+            r"sudo\s+(rm|dd|mkfs|fdisk)",
+            "Privileged destructive command",
+        ),
+        (r"curl.*\|\s*(ba)?sh", "Remote code execution via curl pipe"),
+        (r"wget.*\|\s*(ba)?sh", "Remote code execution via wget pipe"),
+        (r"eval\s+\$", "Dynamic code evaluation"),
+        (r">\s*/dev/sd[a-z]", "Direct disk write"),
+        (r"dd\s+.*of=/dev/", "Direct disk overwrite"),
+        (r"mkfs\.", "Filesystem formatting"),
+        (r":\(\)\s*\{\s*:\s*\|\s*:\s*&\s*\}\s*;\s*:", "Fork bomb"),
+        (r"\bchmod\s+777\s+/", "Dangerous root permission change"),
+        (r"\bchown\s+-R\s+.*\s+/", "Recursive root ownership change"),
+        (r">\s*/etc/(passwd|shadow|sudoers)", "System file overwrite"),
+        (r"nc\s+-[el]", "Netcat listener/backdoor"),
+        (r"python.*-c.*import\s+socket", "Python socket shell"),
+        (r"perl.*-e.*socket", "Perl socket shell"),
+        (r"base64\s+-d.*\|\s*(ba)?sh", "Base64 encoded execution"),
+        (r"history\s+-c", "History clearing (evasion)"),
+        (r"unset\s+HISTFILE", "History file unsetting (evasion)"),
+        (r"export\s+HISTSIZE=0", "History disabling (evasion)"),
+    ]
+    .into_iter()
+    .map(|(pattern, name)| CommandRule::deny(name, pattern, 1.0))
+    .collect()
+}
+
+/// Contents of `~/.sena/guardian_rules.toml`:
+///
+/// ```toml
+/// aggregation = "weighted_sum"
+///
+/// [[rule]]
+/// name = "Internal deploy"
+/// pattern = "deploy-prod\\.sh"
+/// weight = 1.0
+///
+/// [[rule]]
+/// name = "Build cleanup"
+/// pattern = "^rm -rf \\./build$"
+/// action = "allow"
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GuardianRules {
+    #[serde(default)]
+    pub aggregation: ScoreAggregation,
+    /// Start from the built-in rules; `false` uses only the rules listed here
+    #[serde(default = "default_true")]
+    pub builtin: bool,
+    #[serde(default, rename = "rule")]
+    pub rules: Vec<CommandRule>,
+}
+
+impl Default for GuardianRules {
+    fn default() -> Self {
+        Self {
+            aggregation: ScoreAggregation::Max,
+            builtin: true,
+            rules: Vec::new(),
+        }
+    }
+}
+
+impl GuardianRules {
+    pub fn default_path() -> PathBuf {
+        dirs::home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(".sena")
+            .join("guardian_rules.toml")
+    }
+
+    /// Rules from `path`, or the defaults when the file does not exist
+    pub fn load(path: &Path) -> GuardianResult<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)?;
+        let rules: Self = toml::from_str(&content)
+            .map_err(|e| GuardianError::InvalidRules(format!("{}: {}", path.display(), e)))?;
+        for rule in &rules.rules {
+            rule.compile()?;
+        }
+        Ok(rules)
+    }
+
+    /// The rule set the validator runs: built-ins (unless disabled) with
+    /// same-named custom rules replacing them, then the remaining custom rules
+    pub fn effective_rules(&self) -> Vec<CommandRule> {
+        let base = if self.builtin {
+            builtin_rules()
+        } else {
+            Vec::new()
+        };
+        merge_rules(base, self.rules.clone())
+    }
+}
+
+pub(super) fn merge_rules(base: Vec<CommandRule>, custom: Vec<CommandRule>) -> Vec<CommandRule> {
+    let mut merged: Vec<CommandRule> = base
+        .into_iter()
+        .filter(|rule| !custom.iter().any(|c| c.name == rule.name))
+        .collect();
+    merged.extend(custom);
+    merged
+}
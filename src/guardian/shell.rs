@@ -0,0 +1,358 @@
+use std::path::{Component, Path, PathBuf};
+
+/// Where a command will run, used to judge the paths it touches
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationContext {
+    pub cwd: PathBuf,
+    /// Paths under it may be removed freely; `None` outside a repository or
+    /// when the root would cover `/`, the home directory or a system directory
+    pub project_root: Option<PathBuf>,
+}
+
+impl ValidationContext {
+    pub fn new(cwd: impl Into<PathBuf>, project_root: impl Into<PathBuf>) -> Self {
+        let home = dirs::home_dir().map(|h| normalize(&h));
+        let project_root = normalize(&project_root.into());
+        Self {
+            cwd: normalize(&cwd.into()),
+            project_root: is_safe_project_root(&project_root, home.as_deref())
+                .then_some(project_root),
+        }
+    }
+
+    /// A context with no project root, where every destructive target is
+    /// judged on its own
+    pub fn without_project(cwd: impl Into<PathBuf>) -> Self {
+        Self {
+            cwd: normalize(&cwd.into()),
+            project_root: None,
+        }
+    }
+
+    /// The current directory, with the enclosing git repository as the
+    /// project root. Outside a repository there is no project root.
+    pub fn current() -> Self {
+        let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("/"));
+        match cwd.ancestors().find(|dir| dir.join(".git").exists()) {
+            Some(root) => Self::new(&cwd, root),
+            None => Self::without_project(cwd),
+        }
+    }
+
+    /// Risk findings for the paths a destructive command targets
+    pub(super) fn destructive_targets(&self, tokens: &[String]) -> Vec<(&'static str, f64)> {
+        let home = dirs::home_dir().map(|h| normalize(&h));
+        let mut findings: Vec<(&'static str, f64)> = Vec::new();
+
+        for target in target_operands(tokens) {
+            let path = self.resolve(&target, home.as_deref());
+            // System directories stay protected even inside a project
+            let finding = if is_protected_ancestor(&path, home.as_deref()) || is_system_path(&path)
+            {
+                Some(PROTECTED_PATH)
+            } else if self
+                .project_root
+                .as_ref()
+                .is_some_and(|root| path.starts_with(root))
+            {
+                None
+            } else {
+                Some(OUTSIDE_PROJECT)
+            };
+            if let Some(finding) = finding {
+                if !findings.contains(&finding) {
+                    findings.push(finding);
+                }
+            }
+        }
+
+        findings
+    }
+
+    /// Follow a `cd` so later commands in the chain resolve from there
+    pub(super) fn follow_cd(&mut self, tokens: &[String]) {
+        let Some((first, args)) = tokens.split_first() else {
+            return;
+        };
+        if first != "cd" {
+            return;
+        }
+        let home = dirs::home_dir().map(|h| normalize(&h));
+        match args.iter().find(|arg| !arg.starts_with('-')) {
+            Some(dir) => self.cwd = self.resolve(dir, home.as_deref()),
+            None => {
+                if let Some(home) = home {
+                    self.cwd = home;
+                }
+            }
+        }
+    }
+
+    fn resolve(&self, arg: &str, home: Option<&Path>) -> PathBuf {
+        let expanded = match home {
+            Some(home) => expand_home(arg, home),
+            None => PathBuf::from(arg),
+        };
+        // A glob removes the entries inside the directory it starts in
+        let literal: PathBuf = expanded
+            .components()
+            .take_while(|c| !c.as_os_str().to_string_lossy().contains(['*', '?', '[']))
+            .collect();
+        normalize(&self.cwd.join(literal))
+    }
+}
+
+impl Default for ValidationContext {
+    fn default() -> Self {
+        Self::current()
+    }
+}
+
+pub(super) const PROTECTED_PATH: (&str, f64) = ("Destructive command on protected path", 1.0);
+pub(super) const OUTSIDE_PROJECT: (&str, f64) = ("Destructive command outside project root", 0.9);
+
+const SYSTEM_DIRS: &[&str] = &[
+    "/bin", "/boot", "/dev", "/etc", "/lib", "/lib64", "/proc", "/sbin", "/sys", "/usr", "/var",
+];
+
+/// Removing `path` would take `/` or the home directory with it
+fn is_protected_ancestor(path: &Path, home: Option<&Path>) -> bool {
+    path == Path::new("/") || home.is_some_and(|home| home.starts_with(path))
+}
+
+fn is_system_path(path: &Path) -> bool {
+    SYSTEM_DIRS.iter().any(|dir| path.starts_with(dir))
+}
+
+/// A project root that would exempt `/`, the home directory or a system
+/// directory from the path checks is not trusted
+fn is_safe_project_root(root: &Path, home: Option<&Path>) -> bool {
+    !is_protected_ancestor(root, home) && !is_system_path(root)
+}
+
+/// Split a command line into the simple commands it runs, so a dangerous
+/// command chained after `&&`, `;`, `|` or inside `$(...)` is seen on its own.
+/// Operators inside quotes are left alone.
+pub fn split_commands(command: &str) -> Vec<String> {
+    let mut segments = Vec::new();
+    let mut current = String::new();
+    let mut chars = command.chars().peekable();
+    let mut single = false;
+    let mut double = false;
+    let mut prev = ' ';
+
+    while let Some(c) = chars.next() {
+        let separator = match c {
+            '\\' if !single => {
+                current.push(c);
+                if let Some(next) = chars.next() {
+                    current.push(next);
+                }
+                prev = c;
+                continue;
+            }
+            '\'' if !double => {
+                single = !single;
+                false
+            }
+            '"' if !single => {
+                double = !double;
+                false
+            }
+            _ if single => false,
+            '`' | ')' => true,
+            '$' if chars.peek() == Some(&'(') => {
+                chars.next();
+                true
+            }
+            _ if double => false,
+            ';' | '\n' | '(' => true,
+            '|' => {
+                if chars.peek() == Some(&'|') {
+                    chars.next();
+                }
+                true
+            }
+            '&' => {
+                if chars.peek() == Some(&'&') {
+                    chars.next();
+                    true
+                } else {
+                    // `2>&1` and `&>` are redirections, not backgrounding
+                    prev != '>' && prev != '<' && chars.peek() != Some(&'>')
+                }
+            }
+            _ => false,
+        };
+
+        if separator {
+            push_segment(&mut segments, &mut current);
+        } else {
+            current.push(c);
+        }
+        prev = c;
+    }
+    push_segment(&mut segments, &mut current);
+
+    segments
+}
+
+fn push_segment(segments: &mut Vec<String>, current: &mut String) {
+    let trimmed = current.trim();
+    if !trimmed.is_empty() {
+        segments.push(trimmed.to_string());
+    }
+    current.clear();
+}
+
+/// Words of a simple command with quoting removed; unbalanced quotes fall
+/// back to splitting on whitespace
+pub fn tokenize(segment: &str) -> Vec<String> {
+    shlex::split(segment)
+        .unwrap_or_else(|| segment.split_whitespace().map(str::to_string).collect())
+}
+
+/// The operands a destructive command would remove or rewrite
+fn target_operands(tokens: &[String]) -> Vec<String> {
+    let words = strip_wrappers(tokens);
+    let Some((program, args)) = words.split_first() else {
+        return Vec::new();
+    };
+    let program = program.rsplit('/').next().unwrap_or(program);
+
+    match program {
+        "rm" | "rmdir" | "shred" | "unlink" => operands(args),
+        // The first operand is the mode or owner
+        "chmod" | "chown" | "chgrp" => operands(args).into_iter().skip(1).collect(),
+        "dd" => args
+            .iter()
+            .filter_map(|arg| arg.strip_prefix("of="))
+            .map(str::to_string)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Drop `VAR=value` assignments and `sudo`/`env`-style prefixes
+fn strip_wrappers(tokens: &[String]) -> &[String] {
+    let mut rest = tokens;
+    while let Some((first, tail)) = rest.split_first() {
+        let is_assignment = first
+            .split_once('=')
+            .is_some_and(|(name, _)| !name.is_empty() && !name.starts_with('-'));
+        if is_assignment {
+            rest = tail;
+        } else if matches!(
+            first.as_str(),
+            "sudo" | "env" | "nohup" | "nice" | "time" | "command" | "exec" | "xargs"
+        ) {
+            rest = tail;
+            while rest.first().is_some_and(|arg| arg.starts_with('-')) {
+                rest = &rest[1..];
+            }
+        } else {
+            break;
+        }
+    }
+    rest
+}
+
+fn operands(args: &[String]) -> Vec<String> {
+    let mut operands = Vec::new();
+    let mut after_dashes = false;
+    for arg in args {
+        if after_dashes || !arg.starts_with('-') || arg == "-" {
+            operands.push(arg.clone());
+        } else if arg == "--" {
+            after_dashes = true;
+        }
+    }
+    operands
+}
+
+fn expand_home(arg: &str, home: &Path) -> PathBuf {
+    for prefix in ["~", "$HOME", "${HOME}"] {
+        if let Some(rest) = arg.strip_prefix(prefix) {
+            if rest.is_empty() {
+                return home.to_path_buf();
+            }
+            if let Some(rest) = rest.strip_prefix('/') {
+                return home.join(rest);
+            }
+        }
+    }
+    PathBuf::from(arg)
+}
+
+/// Resolve `.` and `..` without touching the filesystem; the paths a
+/// command deletes need not exist yet
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_commands_respects_quotes_and_operators() {
+        assert_eq!(
+            split_commands("cd build && rm -rf out; echo 'a && b' | tee log 2>&1"),
+            vec!["cd build", "rm -rf out", "echo 'a && b'", "tee log 2>&1"]
+        );
+        assert_eq!(
+            split_commands("echo \"$(rm -rf /)\""),
+            vec!["echo \"", "rm -rf /", "\""]
+        );
+        assert_eq!(tokenize("rm -rf \"my dir\""), vec!["rm", "-rf", "my dir"]);
+    }
+
+    #[test]
+    fn test_destructive_targets() {
+        let context = ValidationContext::new("/work/project/src", "/work/project");
+        let targets = |command: &str| context.destructive_targets(&tokenize(command));
+
+        assert!(targets("rm -rf ../build ./tmp/*").is_empty());
+        assert!(targets("sudo rm -rf /work/project/target").is_empty());
+        assert_eq!(targets("rm -rf ../../other"), vec![OUTSIDE_PROJECT]);
+        assert_eq!(targets("rm -rf -- /etc/nginx"), vec![PROTECTED_PATH]);
+        assert_eq!(targets("rm -rf ../../../*"), vec![PROTECTED_PATH]);
+        assert_eq!(targets("chmod -R 777 /usr"), vec![PROTECTED_PATH]);
+
+        let under_var = ValidationContext::new("/var/www/app", "/var/www/app");
+        assert_eq!(under_var.project_root, None);
+        let targets = |command: &str| under_var.destructive_targets(&tokenize(command));
+        assert_eq!(targets("rm -rf ./cache"), vec![PROTECTED_PATH]);
+        assert_eq!(targets("rm -rf /var/lib"), vec![PROTECTED_PATH]);
+        assert!(targets("ls -la /etc").is_empty());
+
+        let no_project = ValidationContext::without_project("/work/project");
+        assert_eq!(
+            no_project.destructive_targets(&tokenize("rm -rf ./build")),
+            vec![OUTSIDE_PROJECT]
+        );
+    }
+
+    #[test]
+    fn test_home_and_system_dirs_are_never_project_roots() {
+        assert_eq!(ValidationContext::new("/", "/").project_root, None);
+        assert_eq!(ValidationContext::new("/etc", "/etc").project_root, None);
+        if let Some(home) = dirs::home_dir() {
+            assert_eq!(ValidationContext::new(&home, &home).project_root, None);
+        }
+        assert_eq!(
+            ValidationContext::new("/work/project/src", "/work/project").project_root,
+            Some(PathBuf::from("/work/project"))
+        );
+    }
+}
@@ -2,6 +2,11 @@ use std::sync::{Arc, RwLock};
 
 use regex::Regex;
 
+use super::config::GuardianConfig;
+use super::rules::{
+    builtin_rules, merge_rules, CommandRule, GuardianRules, RuleAction, ScoreAggregation,
+};
+use super::shell::{split_commands, tokenize, ValidationContext};
 use crate::ancient::NegativeSpaceArchitecture;
 
 #[derive(Debug, Clone)]
@@ -13,71 +18,156 @@ pub struct ValidationResult {
 }
 
 pub struct CommandValidator {
-    blocked_patterns: Vec<(Regex, &'static str)>,
+    rules: Vec<(Regex, CommandRule)>,
+    aggregation: ScoreAggregation,
+    block_threshold: f64,
     negative_space: Arc<RwLock<NegativeSpaceArchitecture>>,
 }
 
 impl CommandValidator {
     pub fn new(negative_space: Arc<RwLock<NegativeSpaceArchitecture>>) -> Self {
         Self {
-            blocked_patterns: Self::build_blocked_patterns(),
+            rules: Vec::new(),
+            aggregation: ScoreAggregation::default(),
+            block_threshold: GuardianConfig::default().block_threshold,
             negative_space,
         }
+        .with_rules(Vec::new())
     }
 
-    fn build_blocked_patterns() -> Vec<(Regex, &'static str)> {
-        let patterns = vec![
-            (r"rm\s+(-[rf]+\s+)*(/|/\*|~)", "Dangerous recursive delete"),
-            (
-                r"sudo\s+(rm|dd|mkfs|fdisk)",
-                "Privileged destructive command",
-            ),
-            (r"curl.*\|\s*(ba)?sh", "Remote code execution via curl pipe"),
-            (r"wget.*\|\s*(ba)?sh", "Remote code execution via wget pipe"),
-            (r"eval\s+\$", "Dynamic code evaluation"),
-            (r">\s*/dev/sd[a-z]", "Direct disk write"),
-            (r"dd\s+.*of=/dev/", "Direct disk overwrite"),
-            (r"mkfs\.", "Filesystem formatting"),
-            (r":\(\)\s*\{\s*:\s*\|\s*:\s*&\s*\}\s*;\s*:", "Fork bomb"),
-            (r"\bchmod\s+777\s+/", "Dangerous root permission change"),
-            (r"\bchown\s+-R\s+.*\s+/", "Recursive root ownership change"),
-            (r">\s*/etc/(passwd|shadow|sudoers)", "System file overwrite"),
-            (r"nc\s+-[el]", "Netcat listener/backdoor"),
-            (r"python.*-c.*import\s+socket", "Python socket shell"),
-            (r"perl.*-e.*socket", "Perl socket shell"),
-            (r"base64\s+-d.*\|\s*(ba)?sh", "Base64 encoded execution"),
-            (r"history\s+-c", "History clearing (evasion)"),
-            (r"unset\s+HISTFILE", "History file unsetting (evasion)"),
-            (r"export\s+HISTSIZE=0", "History disabling (evasion)"),
-        ];
+    /// Validator using `~/.sena/guardian_rules.toml`, falling back to the
+    /// built-in rules when the file is missing or invalid
+    pub fn configured(negative_space: Arc<RwLock<NegativeSpaceArchitecture>>) -> Self {
+        let rules = GuardianRules::load(&GuardianRules::default_path()).unwrap_or_else(|e| {
+            log::warn!("Ignoring guardian rules: {}", e);
+            GuardianRules::default()
+        });
+        Self::new(negative_space).with_rule_set(&rules)
+    }
+
+    /// Built-in rules plus `rules`; a rule named like a built-in replaces it
+    pub fn with_rules(self, rules: Vec<CommandRule>) -> Self {
+        self.with_compiled(merge_rules(builtin_rules(), rules))
+    }
+
+    pub fn with_rule_set(self, rules: &GuardianRules) -> Self {
+        self.with_aggregation(rules.aggregation)
+            .with_compiled(rules.effective_rules())
+    }
 
-        patterns
+    pub fn with_aggregation(mut self, aggregation: ScoreAggregation) -> Self {
+        self.aggregation = aggregation;
+        self
+    }
+
+    /// Risk score at which matched deny rules block the command
+    pub fn with_block_threshold(mut self, threshold: f64) -> Self {
+        self.block_threshold = threshold;
+        self
+    }
+
+    fn with_compiled(mut self, rules: Vec<CommandRule>) -> Self {
+        self.rules = rules
             .into_iter()
-            .filter_map(|(pattern, desc)| Regex::new(pattern).ok().map(|r| (r, desc)))
-            .collect()
+            .filter_map(|rule| match rule.compile() {
+                Ok(regex) => Some((regex, rule)),
+                Err(e) => {
+                    log::warn!("Skipping guardian rule: {}", e);
+                    None
+                }
+            })
+            .collect();
+        self
     }
 
+    pub fn rules(&self) -> impl Iterator<Item = &CommandRule> {
+        self.rules.iter().map(|(_, rule)| rule)
+    }
+
+    /// Validate `command` as if run from the current directory
     pub fn validate(&self, command: &str) -> ValidationResult {
-        let mut matched_patterns = Vec::new();
-        let mut highest_risk: f64 = 0.0;
-        let mut block_reason: Option<String> = None;
-
-        for (pattern, description) in &self.blocked_patterns {
-            if pattern.is_match(command) {
-                matched_patterns.push(description.to_string());
-                highest_risk = 1.0;
-                block_reason = Some(description.to_string());
+        self.validate_in(command, &ValidationContext::current())
+    }
+
+    /// Validate each command chained in `command` on its own, judging the
+    /// paths destructive commands target against `context`. An allow rule
+    /// clears the path checks of the command it matches; deny rules that
+    /// match still count.
+    pub fn validate_in(&self, command: &str, context: &ValidationContext) -> ValidationResult {
+        let mut denied: Vec<(String, f64)> = Vec::new();
+        let mut allowed_by: Vec<String> = Vec::new();
+        let mut seen: Vec<&str> = Vec::new();
+
+        let mut context = context.clone();
+
+        for segment in split_commands(command) {
+            let tokens = tokenize(&segment);
+            context.follow_cd(&tokens);
+            let matched = self.matching(&segment);
+            seen.extend(matched.iter().map(|rule| rule.name.as_str()));
+            denied.extend(
+                matched
+                    .iter()
+                    .filter(|rule| rule.action == RuleAction::Deny)
+                    .map(|rule| (rule.name.clone(), rule.weight)),
+            );
+            let allows: Vec<&CommandRule> = matched
+                .iter()
+                .copied()
+                .filter(|rule| rule.action == RuleAction::Allow)
+                .collect();
+            if !allows.is_empty() {
+                allowed_by.extend(allows.iter().map(|rule| rule.name.clone()));
+                continue;
             }
+
+            denied.extend(
+                context
+                    .destructive_targets(&tokens)
+                    .into_iter()
+                    .map(|(name, weight)| (name.to_string(), weight)),
+            );
         }
 
-        if !matched_patterns.is_empty() {
+        // Patterns that span commands, such as `curl ... | sh`
+        denied.extend(
+            self.matching(command)
+                .into_iter()
+                .filter(|rule| {
+                    rule.action == RuleAction::Deny && !seen.contains(&rule.name.as_str())
+                })
+                .map(|rule| (rule.name.clone(), rule.weight)),
+        );
+
+        // Each rule counts once however many commands it matched
+        let mut denied_by: Vec<(String, f64)> = Vec::new();
+        for (name, weight) in denied {
+            match denied_by.iter_mut().find(|(n, _)| *n == name) {
+                Some(existing) => existing.1 = existing.1.max(weight),
+                None => denied_by.push((name, weight)),
+            }
+        }
+        allowed_by.dedup();
+
+        let rule_risk = self
+            .aggregation
+            .combine(denied_by.iter().map(|(_, weight)| *weight));
+        let mut matched_patterns: Vec<String> =
+            denied_by.iter().map(|(name, _)| name.clone()).collect();
+
+        if !denied_by.is_empty() && rule_risk >= self.block_threshold {
+            let reason = denied_by
+                .iter()
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(name, _)| name.clone());
             return ValidationResult {
                 allowed: false,
-                reason: block_reason,
-                risk_score: highest_risk,
+                reason,
+                risk_score: rule_risk,
                 matched_patterns,
             };
         }
+        matched_patterns.extend(allowed_by);
 
         let ns_check = self
             .negative_space
@@ -86,32 +176,43 @@ impl CommandValidator {
             .unwrap_or_else(|_| crate::ancient::NegativeSpaceCheckResult::default_allowed());
 
         if !ns_check.allowed {
+            matched_patterns.extend(
+                ns_check
+                    .violations
+                    .iter()
+                    .map(|v| format!("{}: {}", v.prohibition_id, v.action_taken)),
+            );
             return ValidationResult {
                 allowed: false,
                 reason: ns_check
                     .violations
                     .first()
                     .map(|v| v.prohibition_id.clone()),
-                risk_score: ns_check.risk_score,
-                matched_patterns: ns_check
-                    .violations
-                    .iter()
-                    .map(|v| format!("{}: {}", v.prohibition_id, v.action_taken))
-                    .collect(),
+                risk_score: ns_check.risk_score.max(rule_risk),
+                matched_patterns,
             };
         }
 
         ValidationResult {
             allowed: true,
             reason: None,
-            risk_score: ns_check.risk_score,
-            matched_patterns: Vec::new(),
+            risk_score: ns_check.risk_score.max(rule_risk),
+            matched_patterns,
         }
     }
 
+    fn matching(&self, text: &str) -> Vec<&CommandRule> {
+        self.rules
+            .iter()
+            .filter(|(regex, _)| regex.is_match(text))
+            .map(|(_, rule)| rule)
+            .collect()
+    }
+
     pub fn add_blocked_pattern(&mut self, pattern: &str, description: &'static str) {
-        if let Ok(regex) = Regex::new(pattern) {
-            self.blocked_patterns.push((regex, description));
+        let rule = CommandRule::deny(description, pattern, 1.0);
+        if let Ok(regex) = rule.compile() {
+            self.rules.push((regex, rule));
         }
     }
 }
@@ -181,4 +282,211 @@ mod tests {
         assert!(!validator.validate("history -c").allowed);
         assert!(!validator.validate("unset HISTFILE").allowed);
     }
+
+    #[test]
+    fn test_custom_deny_rule() {
+        let validator = create_validator().with_rules(vec![CommandRule::deny(
+            "Production deploy",
+            r"deploy-prod\.sh",
+            0.9,
+        )]);
+
+        let result = validator.validate("./deploy-prod.sh --force");
+        assert!(!result.allowed);
+        assert_eq!(result.reason.as_deref(), Some("Production deploy"));
+        assert_eq!(result.matched_patterns, vec!["Production deploy"]);
+        assert!((result.risk_score - 0.9).abs() < f64::EPSILON);
+        assert!(validator.validate("./deploy-staging.sh").allowed);
+    }
+
+    #[test]
+    fn test_custom_allow_overrides_builtin() {
+        let validator = create_validator().with_rules(vec![CommandRule::allow(
+            "Scratch cleanup",
+            r"^rm -rf /tmp/build$",
+        )]);
+
+        let result = validator.validate("rm -rf /tmp/build");
+        assert!(result.allowed);
+        assert_eq!(result.matched_patterns, vec!["Scratch cleanup"]);
+        assert!(!validator.validate("rm -rf /").allowed);
+
+        let replaced = create_validator().with_rules(vec![CommandRule::deny(
+            "Dangerous recursive delete",
+            r"rm\s+-rf\s+/$",
+            1.0,
+        )]);
+        let scratch = ValidationContext::new("/tmp", "/tmp");
+        assert!(replaced.validate_in("rm -rf /tmp/build", &scratch).allowed);
+        assert!(!replaced.validate("rm -rf /").allowed);
+    }
+
+    #[test]
+    fn test_deny_takes_precedence_over_allow() {
+        let validator = create_validator().with_rules(vec![
+            CommandRule::allow("Scratch cleanup", r"^rm -rf /tmp/"),
+            CommandRule::deny("Shared cache", r"/tmp/shared-cache", 1.0),
+        ]);
+
+        assert!(validator.validate("rm -rf /tmp/build").allowed);
+        let result = validator.validate("rm -rf /tmp/shared-cache");
+        assert!(!result.allowed);
+        assert_eq!(result.reason.as_deref(), Some("Shared cache"));
+        assert_eq!(result.matched_patterns, vec!["Shared cache"]);
+
+        let spanning = create_validator().with_rules(vec![CommandRule::allow(
+            "Installer",
+            r"^curl -s https://example\.com/install\.sh",
+        )]);
+        assert!(
+            !spanning
+                .validate("curl -s https://example.com/install.sh | sh")
+                .allowed
+        );
+    }
+
+    #[test]
+    fn test_score_aggregation() {
+        let rules = vec![
+            CommandRule::deny("Force push", r"git push.*--force", 0.5),
+            CommandRule::deny("Main branch", r"\bmain\b", 0.5),
+        ];
+        let command = "git push --force origin main";
+
+        let max = create_validator().with_rules(rules.clone());
+        let result = max.validate(command);
+        assert!(result.allowed);
+        assert!((result.risk_score - 0.5).abs() < f64::EPSILON);
+        assert_eq!(result.matched_patterns, vec!["Force push", "Main branch"]);
+
+        let summed = create_validator()
+            .with_rules(rules)
+            .with_aggregation(ScoreAggregation::WeightedSum);
+        let result = summed.validate(command);
+        assert!(!result.allowed);
+        assert!((result.risk_score - 1.0).abs() < f64::EPSILON);
+        assert!(summed.validate("git push --force origin dev").allowed);
+    }
+
+    #[test]
+    fn test_rules_file() {
+        let path =
+            std::env::temp_dir().join(format!("sena_guardian_rules_{}.toml", uuid::Uuid::new_v4()));
+        std::fs::write(
+            &path,
+            r#"
+aggregation = "weighted_sum"
+builtin = false
+
+[[rule]]
+name = "Drop table"
+pattern = "(?i)drop\\s+table"
+weight = 0.6
+"#,
+        )
+        .unwrap();
+
+        let rules = GuardianRules::load(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(rules.aggregation, ScoreAggregation::WeightedSum);
+
+        let validator = create_validator().with_rule_set(&rules);
+        assert_eq!(validator.rules().count(), 1);
+        assert!(validator.validate("history -c").allowed);
+        let result = validator.validate("DROP TABLE users");
+        assert!(result.allowed);
+        assert_eq!(result.matched_patterns, vec!["Drop table"]);
+    }
+
+    #[test]
+    fn test_project_paths_versus_system_paths() {
+        let validator = create_validator();
+        let context = ValidationContext::new("/work/project", "/work/project");
+
+        for command in [
+            "rm -rf ./build",
+            "rm -rf build/tmp target",
+            "rm -rf /work/project/node_modules",
+            "cd sub && rm -rf ../dist",
+        ] {
+            let result = validator.validate_in(command, &context);
+            assert!(result.allowed, "{command}: {result:?}");
+            assert!(result.matched_patterns.is_empty(), "{command}");
+        }
+
+        for command in [
+            "rm -rf /",
+            "rm -rf \"/etc\"",
+            "rm -r -f /usr/lib",
+            "rm -rf ~",
+            "rm -rf $HOME",
+            "rm -rf ../../*",
+        ] {
+            let result = validator.validate_in(command, &context);
+            assert!(!result.allowed, "{command}");
+        }
+
+        for command in ["rm -rf /srv/other", "rm -rf ~/Documents", "rm -rf /opt/x"] {
+            let outside = validator.validate_in(command, &context);
+            assert!(!outside.allowed, "{command}");
+            assert_eq!(
+                outside.reason.as_deref(),
+                Some("Destructive command outside project root"),
+                "{command}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_home_and_system_dirs_are_protected_from_within() {
+        let validator = create_validator();
+
+        let in_etc = ValidationContext::new("/etc", "/etc");
+        let result = validator.validate_in("rm -rf /etc/x", &in_etc);
+        assert!(!result.allowed);
+        assert_eq!(
+            result.reason.as_deref(),
+            Some("Destructive command on protected path")
+        );
+
+        if let Some(home) = dirs::home_dir() {
+            for context in [
+                ValidationContext::new(&home, &home),
+                ValidationContext::without_project(&home),
+            ] {
+                let result = validator.validate_in("rm -rf ~/.ssh", &context);
+                assert!(!result.allowed, "{context:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_chained_commands_are_checked_separately() {
+        let context = ValidationContext::new("/work/project", "/work/project");
+        let validator = create_validator().with_rules(vec![CommandRule::allow(
+            "Build cleanup",
+            r"^rm -rf \./build$",
+        )]);
+
+        assert!(validator.validate_in("rm -rf ./build", &context).allowed);
+        for command in [
+            "rm -rf ./build && rm -rf /etc",
+            "rm -rf ./build; sudo rm -rf \"/\"",
+            "echo done | xargs rm -rf /etc",
+            "echo \"$(rm -rf /etc)\"",
+        ] {
+            let result = validator.validate_in(command, &context);
+            assert!(!result.allowed, "{command}");
+            assert_eq!(
+                result.reason.as_deref(),
+                Some("Destructive command on protected path"),
+                "{command}"
+            );
+        }
+        assert!(
+            validator
+                .validate_in("echo 'rm -rf /etc && x' > notes.txt", &context)
+                .allowed
+        );
+    }
 }
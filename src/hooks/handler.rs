@@ -107,7 +107,7 @@ fn check_inbox_messages() -> Vec<String> {
         return Vec::new();
     }
 
-    let messages: Vec<Message> = hub.inbox("local").into_iter().filter(|m| !m.read).collect();
+    let messages: Vec<Message> = hub.inbox_unread("local");
 
     messages
         .iter()
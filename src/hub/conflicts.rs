@@ -4,9 +4,23 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use super::state::HubState;
+use super::HubConfig;
+use crate::sync::write_atomic;
+
+/// Lock lifetime after the holder's last heartbeat when not configured
+pub const DEFAULT_LOCK_TTL_SECS: u64 = 300;
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
 
 /// File conflict information
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -71,11 +85,10 @@ impl FileLock {
 
     /// Check if lock is expired
     pub fn is_expired(&self) -> bool {
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .map(|d| d.as_secs())
-            .unwrap_or(0);
+        self.is_expired_at(now_secs())
+    }
 
+    fn is_expired_at(&self, now: u64) -> bool {
         now > self.expires_at
     }
 }
@@ -84,6 +97,12 @@ impl FileLock {
 pub struct ConflictDetector {
     locks: HashMap<String, FileLock>,
     conflict_history: Vec<FileConflict>,
+    lock_ttl_secs: u64,
+    /// Persisted locks, shared by every hub process; in memory only when unset
+    locks_file: Option<PathBuf>,
+    /// `(file, session)` locks released since the last save, so merging
+    /// with the persisted locks does not bring them back
+    released: Vec<(String, String)>,
 }
 
 impl ConflictDetector {
@@ -92,9 +111,28 @@ impl ConflictDetector {
         Self {
             locks: HashMap::new(),
             conflict_history: Vec::new(),
+            lock_ttl_secs: DEFAULT_LOCK_TTL_SECS,
+            locks_file: None,
+            released: Vec::new(),
         }
     }
 
+    /// Detector using the hub's lock TTL and persisting locks to its
+    /// `locks_file`
+    pub fn with_config(config: &HubConfig) -> Self {
+        Self {
+            lock_ttl_secs: config.lock_ttl_secs,
+            locks_file: Some(config.locks_file.clone()),
+            ..Self::new()
+        }
+    }
+
+    /// Seconds a lock outlives its holder's last heartbeat
+    pub fn with_lock_ttl(mut self, ttl_secs: u64) -> Self {
+        self.lock_ttl_secs = ttl_secs;
+        self
+    }
+
     /// Check if a file would cause a conflict
     pub fn check_file(
         &mut self,
@@ -174,45 +212,82 @@ impl ConflictDetector {
             .collect()
     }
 
-    /// Try to acquire a lock on a file
-    pub fn acquire_lock(
+    /// Take the soft lock on a file for a session, or refresh it when the
+    /// session already holds it. Fails while another session holds a lock
+    /// that has not expired; an expired lock is reclaimed.
+    pub fn acquire_lock(&mut self, session_id: &str, file_path: &str) -> Result<FileLock, String> {
+        self.acquire_lock_at(session_id, file_path, now_secs())
+    }
+
+    fn acquire_lock_at(
         &mut self,
-        file_path: &str,
         session_id: &str,
-        duration_secs: u64,
+        file_path: &str,
+        now: u64,
     ) -> Result<FileLock, String> {
-        // Clean up expired locks
-        self.cleanup_expired_locks();
+        self.cleanup_expired_locks_at(now);
 
-        // Check if already locked
         if let Some(existing) = self.locks.get(file_path) {
             if existing.session_id != session_id {
                 return Err(format!(
-                    "File {} is locked by session {}",
-                    file_path, existing.session_id
+                    "File {} is locked by session {} until {}s from now",
+                    file_path,
+                    existing.session_id,
+                    existing.expires_at.saturating_sub(now)
                 ));
             }
-            // Same session, extend lock
         }
 
-        let lock = FileLock::new(file_path, session_id, duration_secs);
+        let lock = FileLock {
+            file_path: file_path.to_string(),
+            session_id: session_id.to_string(),
+            locked_at: now,
+            expires_at: now + self.lock_ttl_secs,
+        };
         self.locks.insert(file_path.to_string(), lock.clone());
 
         Ok(lock)
     }
 
+    /// Extend a session's locks to `ttl` past its latest heartbeat. Locks
+    /// of a session that stops sending heartbeats lapse on their own.
+    pub fn record_heartbeat(&mut self, session_id: &str, heartbeat_at: u64) {
+        let expires_at = heartbeat_at + self.lock_ttl_secs;
+        for lock in self.locks.values_mut() {
+            if lock.session_id == session_id && expires_at > lock.expires_at {
+                lock.expires_at = expires_at;
+            }
+        }
+    }
+
     /// Release a lock
-    pub fn release_lock(&mut self, file_path: &str, session_id: &str) -> Result<(), String> {
+    pub fn release_lock(&mut self, session_id: &str, file_path: &str) -> Result<(), String> {
         if let Some(lock) = self.locks.get(file_path) {
             if lock.session_id != session_id {
                 return Err("Cannot release lock owned by another session".to_string());
             }
         }
 
-        self.locks.remove(file_path);
+        if let Some(lock) = self.locks.remove(file_path) {
+            self.released.push((lock.file_path, lock.session_id));
+        }
         Ok(())
     }
 
+    /// Release every lock a session holds, returning how many there were
+    pub fn release_session_locks(&mut self, session_id: &str) -> usize {
+        let before = self.locks.len();
+        let released = &mut self.released;
+        self.locks.retain(|_, lock| {
+            if lock.session_id != session_id {
+                return true;
+            }
+            released.push((lock.file_path.clone(), lock.session_id.clone()));
+            false
+        });
+        before - self.locks.len()
+    }
+
     /// Check if a file is locked
     pub fn is_locked(&self, file_path: &str) -> bool {
         if let Some(lock) = self.locks.get(file_path) {
@@ -224,21 +299,69 @@ impl ConflictDetector {
 
     /// Get lock holder for a file
     pub fn get_lock_holder(&self, file_path: &str) -> Option<String> {
-        self.locks.get(file_path).map(|l| l.session_id.clone())
+        self.locks
+            .get(file_path)
+            .filter(|l| !l.is_expired())
+            .map(|l| l.session_id.clone())
     }
 
     /// Clean up expired locks
     pub fn cleanup_expired_locks(&mut self) {
-        let expired: Vec<String> = self
-            .locks
-            .iter()
-            .filter(|(_, lock)| lock.is_expired())
-            .map(|(path, _)| path.clone())
-            .collect();
+        self.cleanup_expired_locks_at(now_secs());
+    }
+
+    fn cleanup_expired_locks_at(&mut self, now: u64) {
+        self.locks.retain(|_, lock| !lock.is_expired_at(now));
+    }
+
+    /// Replace the in-memory locks with the persisted ones
+    pub fn load_locks(&mut self) -> Result<(), String> {
+        let Some(path) = &self.locks_file else {
+            return Ok(());
+        };
+        self.locks = read_locks(path)?;
+        self.released.clear();
+        Ok(())
+    }
 
-        for path in expired {
-            self.locks.remove(&path);
+    /// Merge the in-memory locks into the persisted ones and write the
+    /// result, so locks other hub processes saved meanwhile are kept. A lock
+    /// released here is dropped; when two sessions hold the same file the
+    /// one that locked it first keeps it.
+    pub fn save_locks(&mut self) -> Result<(), String> {
+        let Some(path) = &self.locks_file else {
+            return Ok(());
+        };
+        let now = now_secs();
+        let mut merged = read_locks(path)?;
+        merged.retain(|file, lock| {
+            !lock.is_expired_at(now)
+                && !self
+                    .released
+                    .iter()
+                    .any(|(f, session)| f == file && *session == lock.session_id)
+        });
+        for lock in self.locks.values().filter(|l| !l.is_expired_at(now)) {
+            match merged.get_mut(&lock.file_path) {
+                Some(saved) if saved.session_id == lock.session_id => {
+                    saved.expires_at = saved.expires_at.max(lock.expires_at);
+                }
+                Some(saved) if saved.locked_at <= lock.locked_at => {}
+                _ => {
+                    merged.insert(lock.file_path.clone(), lock.clone());
+                }
+            }
         }
+
+        let mut locks: Vec<&FileLock> = merged.values().collect();
+        locks.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+        let json = serde_json::to_string_pretty(&locks)
+            .map_err(|e| format!("Cannot serialize locks: {}", e))?;
+        write_atomic(path, json).map_err(|e| format!("Cannot write locks file: {}", e))?;
+
+        self.locks = merged;
+        self.released.clear();
+        Ok(())
     }
 
     /// Get conflict history
@@ -262,6 +385,19 @@ impl ConflictDetector {
     }
 }
 
+fn read_locks(path: &Path) -> Result<HashMap<String, FileLock>, String> {
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+    let content = fs::read_to_string(path).map_err(|e| format!("Cannot read locks file: {}", e))?;
+    let locks: Vec<FileLock> =
+        serde_json::from_str(&content).map_err(|e| format!("Cannot parse locks file: {}", e))?;
+    Ok(locks
+        .into_iter()
+        .map(|lock| (lock.file_path.clone(), lock))
+        .collect())
+}
+
 impl Default for ConflictDetector {
     fn default() -> Self {
         Self::new()
@@ -295,12 +431,111 @@ mod tests {
     fn test_acquire_and_release_lock() {
         let mut detector = ConflictDetector::new();
 
-        let lock = detector.acquire_lock("/test.rs", "session-1", 300);
+        let lock = detector.acquire_lock("session-1", "/test.rs");
         assert!(lock.is_ok());
         assert!(detector.is_locked("/test.rs"));
 
-        let release = detector.release_lock("/test.rs", "session-1");
+        let release = detector.release_lock("session-1", "/test.rs");
         assert!(release.is_ok());
         assert!(!detector.is_locked("/test.rs"));
     }
+
+    #[test]
+    fn test_lock_conflict_and_release() {
+        let mut detector = ConflictDetector::new().with_lock_ttl(60);
+
+        detector.acquire_lock("web", "src/api.rs").unwrap();
+        let err = detector.acquire_lock("backend", "src/api.rs").unwrap_err();
+        assert!(err.contains("locked by session web"), "{}", err);
+        assert!(detector.release_lock("backend", "src/api.rs").is_err());
+        assert_eq!(
+            detector.get_lock_holder("src/api.rs").as_deref(),
+            Some("web")
+        );
+
+        // The holder refreshing its own lock is not a conflict
+        detector.acquire_lock("web", "src/api.rs").unwrap();
+
+        detector.release_lock("web", "src/api.rs").unwrap();
+        detector.acquire_lock("backend", "src/api.rs").unwrap();
+        assert_eq!(detector.release_session_locks("backend"), 1);
+        assert!(detector.get_locks().is_empty());
+    }
+
+    #[test]
+    fn test_stale_lock_expires_and_is_reclaimed() {
+        let mut detector = ConflictDetector::new().with_lock_ttl(60);
+        let now = now_secs();
+
+        detector
+            .acquire_lock_at("web", "src/api.rs", now - 100)
+            .unwrap();
+        // A recent heartbeat keeps the lock alive past its original expiry
+        detector.record_heartbeat("web", now - 30);
+        assert!(detector
+            .acquire_lock_at("backend", "src/api.rs", now)
+            .is_err());
+
+        // Once the holder goes quiet for longer than the TTL the lock lapses
+        assert!(detector
+            .acquire_lock_at("backend", "src/api.rs", now + 31)
+            .is_ok());
+        assert_eq!(
+            detector.get_lock_holder("src/api.rs").as_deref(),
+            Some("backend")
+        );
+        // A late heartbeat from the old holder does not revive its lock
+        detector.record_heartbeat("web", now + 40);
+        assert!(detector
+            .acquire_lock_at("web", "src/api.rs", now + 41)
+            .is_err());
+    }
+
+    #[test]
+    fn test_locks_persist_across_detectors() {
+        let dir = std::env::temp_dir().join(format!("sena-locks-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let mut config = HubConfig::with_dir(dir.clone());
+        config.lock_ttl_secs = 60;
+
+        let mut detector = ConflictDetector::with_config(&config);
+        detector.acquire_lock("web", "src/api.rs").unwrap();
+        detector.save_locks().unwrap();
+
+        let mut other = ConflictDetector::with_config(&config);
+        other.load_locks().unwrap();
+        assert!(other.acquire_lock("backend", "src/api.rs").is_err());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_save_merges_locks_from_other_detectors() {
+        let dir = std::env::temp_dir().join(format!("sena-locks-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let config = HubConfig::with_dir(dir.clone());
+
+        let mut web = ConflictDetector::with_config(&config);
+        let mut backend = ConflictDetector::with_config(&config);
+        web.acquire_lock("web", "src/api.rs").unwrap();
+        web.save_locks().unwrap();
+        backend.acquire_lock("backend", "src/db.rs").unwrap();
+        backend.save_locks().unwrap();
+
+        let mut reader = ConflictDetector::with_config(&config);
+        reader.load_locks().unwrap();
+        assert_eq!(reader.get_lock_holder("src/api.rs").as_deref(), Some("web"));
+        assert_eq!(
+            reader.get_lock_holder("src/db.rs").as_deref(),
+            Some("backend")
+        );
+
+        web.release_lock("web", "src/api.rs").unwrap();
+        web.save_locks().unwrap();
+        reader.load_locks().unwrap();
+        assert!(!reader.is_locked("src/api.rs"));
+        assert!(reader.is_locked("src/db.rs"));
+
+        let _ = fs::remove_dir_all(dir);
+    }
 }
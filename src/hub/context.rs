@@ -7,6 +7,7 @@ use std::fs;
 use std::path::PathBuf;
 
 use super::HubConfig;
+use crate::sync::write_atomic;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionContext {
@@ -71,7 +72,7 @@ impl ContextManager {
         let file_path = self.context_file_for_terminal(&context.terminal_id);
         let json = serde_json::to_string_pretty(context)
             .map_err(|e| format!("Cannot serialize context: {}", e))?;
-        fs::write(&file_path, json).map_err(|e| format!("Cannot write context: {}", e))
+        write_atomic(&file_path, json).map_err(|e| format!("Cannot write context: {}", e))
     }
 
     pub fn load_current_context(&self) -> Option<SessionContext> {
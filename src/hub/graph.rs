@@ -0,0 +1,171 @@
+//! Hub Relationship Graph
+//!
+//! Builds a relationship graph of sessions, their assigned tasks, the files
+//! they are working on and the dependencies between tasks, for export as
+//! DOT or ASCII.
+
+use std::collections::HashMap;
+
+use crate::ancient::{
+    NodeType, RelationType, Relationship, RelationshipDataModel, RelationshipNode,
+};
+
+use super::{Session, Task, TaskStatus};
+
+/// Graph node name for a session
+pub fn session_node(session: &Session) -> String {
+    format!("session:{}", session.name)
+}
+
+/// Graph node name for a task
+pub fn task_node(task: &Task) -> String {
+    format!("task #{}", task.id)
+}
+
+/// Graph node name for a file a session is working on
+pub fn file_node(path: &str) -> String {
+    format!("file:{}", path)
+}
+
+fn assigned_to(task: &Task, session: &Session) -> bool {
+    task.assignee == session.id
+        || task.assignee.eq_ignore_ascii_case(&session.name)
+        || task.assignee.eq_ignore_ascii_case(session.role.name())
+}
+
+/// Build the hub graph from sessions, tasks and per-session working files
+pub fn build_graph(
+    sessions: &[Session],
+    tasks: &[Task],
+    working: &HashMap<String, String>,
+) -> RelationshipDataModel {
+    let mut model = RelationshipDataModel::new();
+
+    for session in sessions {
+        let name = session_node(session);
+        model.add_node(
+            RelationshipNode::new(&name, NodeType::Entity)
+                .with_property(
+                    "label",
+                    format!("{} ({})", session.name, session.role.name()),
+                )
+                .with_property("shape", "box"),
+        );
+
+        let file = working
+            .get(&session.id)
+            .or(session.working_on.as_ref())
+            .map(|path| file_node(path));
+        if let Some(file) = file {
+            if model.get_node_by_name(&file).is_none() {
+                model.create_node(&file, NodeType::Context);
+            }
+            link(
+                &mut model,
+                &name,
+                &file,
+                RelationType::Custom,
+                "working on",
+                None,
+            );
+        }
+    }
+
+    for task in tasks {
+        model.add_node(
+            RelationshipNode::new(task_node(task), NodeType::Action).with_property(
+                "label",
+                format!("#{} {} [{}]", task.id, task.title, task.status.name()),
+            ),
+        );
+    }
+
+    for task in tasks {
+        let task_name = task_node(task);
+
+        for session in sessions.iter().filter(|s| assigned_to(task, s)) {
+            link(
+                &mut model,
+                &session_node(session),
+                &task_name,
+                RelationType::Custom,
+                "assigned",
+                None,
+            );
+        }
+
+        for dependency in &task.depends_on {
+            let Some(prerequisite) = tasks.iter().find(|t| t.id == *dependency) else {
+                continue;
+            };
+            let blocking = prerequisite.status != TaskStatus::Done;
+            link(
+                &mut model,
+                &task_name,
+                &task_node(prerequisite),
+                RelationType::DependsOn,
+                if blocking { "blocked by" } else { "depends on" },
+                blocking.then_some(("dashed", "red")),
+            );
+        }
+    }
+
+    model
+}
+
+fn link(
+    model: &mut RelationshipDataModel,
+    source: &str,
+    target: &str,
+    relation_type: RelationType,
+    label: &str,
+    style: Option<(&str, &str)>,
+) {
+    let (Some(source), Some(target)) = (
+        model.get_node_by_name(source).map(|n| n.id.clone()),
+        model.get_node_by_name(target).map(|n| n.id.clone()),
+    ) else {
+        return;
+    };
+
+    let mut relationship =
+        Relationship::new(source, target, relation_type).with_property("label", label);
+    if let Some((line, color)) = style {
+        relationship = relationship
+            .with_property("style", line)
+            .with_property("color", color)
+            .with_property("marker", "⛔");
+    }
+    model.add_relationship(relationship);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hub::{SessionRole, TaskPriority};
+
+    #[test]
+    fn test_graph_dot_contains_sessions_and_dependency() {
+        let backend = Session::new(SessionRole::Backend, Some("Backend".to_string()));
+        let web = Session::new(SessionRole::Web, Some("Web".to_string()));
+
+        let api = Task::new(1, "Build API", &backend.id, "hub", TaskPriority::High);
+        let mut ui = Task::new(2, "Build UI", "web", "hub", TaskPriority::Medium);
+        ui.depends_on.push(api.id);
+
+        let mut working = HashMap::new();
+        working.insert(backend.id.clone(), "src/api.rs".to_string());
+
+        let model = build_graph(&[backend, web], &[api, ui], &working);
+        let dot = model.to_dot("hub");
+
+        assert!(dot.contains("\"session:Backend\" ["));
+        assert!(dot.contains("\"session:Web\" ["));
+        assert!(dot.contains("\"session:Backend\" -> \"task #1\" [label=\"assigned\"]"));
+        assert!(dot.contains("\"session:Web\" -> \"task #2\" [label=\"assigned\"]"));
+        assert!(dot.contains(
+            "\"task #2\" -> \"task #1\" [label=\"blocked by\", style=\"dashed\", color=\"red\"]"
+        ));
+        assert!(dot.contains("\"session:Backend\" -> \"file:src/api.rs\""));
+    }
+}
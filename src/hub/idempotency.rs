@@ -0,0 +1,249 @@
+//! Idempotency Keys
+//!
+//! Remembers the result of recently executed mutating commands by a
+//! client-supplied key, so retried or double-submitted requests return the
+//! original result instead of running again. A key is scoped to the sender
+//! and command it came with, and a duplicate that arrives while the first
+//! request is still running waits for (or is refused until) its result.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How long a key is remembered by default
+pub const DEFAULT_IDEMPOTENCY_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// The same key from another sender, or for another command, is a
+/// different request
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Scope {
+    sender: String,
+    command: String,
+    key: String,
+}
+
+/// Result slot for one scoped key, locked while its command runs
+type Slot = Arc<tokio::sync::Mutex<Option<(Instant, serde_json::Value)>>>;
+
+/// Recently seen idempotency keys and their results
+pub struct IdempotencyStore {
+    entries: Mutex<HashMap<Scope, Slot>>,
+    ttl: Duration,
+}
+
+impl IdempotencyStore {
+    pub fn new() -> Self {
+        Self::with_ttl(DEFAULT_IDEMPOTENCY_TTL)
+    }
+
+    pub fn with_ttl(ttl: Duration) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            ttl,
+        }
+    }
+
+    /// The slot for a key, after dropping expired results and the slots
+    /// of failed runs. Slots still running are kept.
+    fn slot(&self, sender: &str, command: &str, key: &str) -> Result<Slot, String> {
+        let mut entries = self.entries.lock().map_err(|e| e.to_string())?;
+        let ttl = self.ttl;
+        entries.retain(|_, slot| match slot.try_lock() {
+            Ok(stored) => stored
+                .as_ref()
+                .is_some_and(|(recorded_at, _)| recorded_at.elapsed() < ttl),
+            Err(_) => true,
+        });
+        let scope = Scope {
+            sender: sender.to_string(),
+            command: command.to_string(),
+            key: key.to_string(),
+        };
+        Ok(entries.entry(scope).or_default().clone())
+    }
+
+    /// Run `operation` unless `sender` already sent `key` for `command`, in
+    /// which case the prior result is returned. A duplicate arriving while
+    /// the first is still running is refused. Failed operations are not
+    /// remembered so they can be retried.
+    pub fn run<T, F>(
+        &self,
+        sender: &str,
+        command: &str,
+        key: Option<&str>,
+        operation: F,
+    ) -> Result<T, String>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce() -> Result<T, String>,
+    {
+        let Some(key) = key else {
+            return operation();
+        };
+
+        let slot = self.slot(sender, command, key)?;
+        let mut stored = slot
+            .try_lock()
+            .map_err(|_| format!("A request with idempotency key '{}' is still running", key))?;
+        if let Some(previous) = stored.as_ref() {
+            return decode(previous);
+        }
+
+        let result = operation()?;
+        *stored = Some(encode(&result)?);
+        Ok(result)
+    }
+
+    /// Like [`IdempotencyStore::run`] for async commands, except that a
+    /// duplicate arriving while the first is still running waits for its
+    /// result. Only the key's own slot stays locked meanwhile, so unrelated
+    /// commands are not held up behind it.
+    pub async fn run_async<T, F, Fut>(
+        &self,
+        sender: &str,
+        command: &str,
+        key: Option<&str>,
+        operation: F,
+    ) -> Result<T, String>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce() -> Fut,
+        Fut: std::future::Future<Output = Result<T, String>>,
+    {
+        let Some(key) = key else {
+            return operation().await;
+        };
+
+        let slot = self.slot(sender, command, key)?;
+        let mut stored = slot.lock().await;
+        if let Some(previous) = stored.as_ref() {
+            return decode(previous);
+        }
+
+        let result = operation().await?;
+        *stored = Some(encode(&result)?);
+        Ok(result)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries
+            .lock()
+            .map(|entries| entries.len())
+            .unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for IdempotencyStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn encode<T: Serialize>(result: &T) -> Result<(Instant, serde_json::Value), String> {
+    let value = serde_json::to_value(result)
+        .map_err(|e| format!("Cannot serialize idempotent result: {}", e))?;
+    Ok((Instant::now(), value))
+}
+
+fn decode<T: DeserializeOwned>((_, value): &(Instant, serde_json::Value)) -> Result<T, String> {
+    serde_json::from_value(value.clone())
+        .map_err(|e| format!("Cannot read idempotent result: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_same_key_runs_once() {
+        let store = IdempotencyStore::new();
+        let mut calls = 0;
+
+        let first: u64 = store
+            .run("web", "tell", Some("key-1"), || {
+                calls += 1;
+                Ok(42)
+            })
+            .unwrap();
+        let second: u64 = store
+            .run("web", "tell", Some("key-1"), || {
+                calls += 1;
+                Ok(7)
+            })
+            .unwrap();
+
+        assert_eq!(calls, 1);
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_different_keys_and_failures_run_again() {
+        let store = IdempotencyStore::new();
+
+        let failed: Result<u64, String> =
+            store.run("web", "tell", Some("a"), || Err("boom".into()));
+        assert!(failed.is_err());
+        assert_eq!(store.run("web", "tell", Some("a"), || Ok(1u64)).unwrap(), 1);
+        assert_eq!(store.run("web", "tell", Some("b"), || Ok(2u64)).unwrap(), 2);
+        assert_eq!(store.len(), 2);
+    }
+
+    #[test]
+    fn test_key_is_scoped_to_sender_and_command() {
+        let store = IdempotencyStore::new();
+
+        assert_eq!(store.run("web", "tell", Some("k"), || Ok(1u64)).unwrap(), 1);
+        assert_eq!(store.run("api", "tell", Some("k"), || Ok(2u64)).unwrap(), 2);
+        assert_eq!(
+            store
+                .run("web", "create_task", Some("k"), || Ok(3u64))
+                .unwrap(),
+            3
+        );
+        assert_eq!(store.run("web", "tell", Some("k"), || Ok(4u64)).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_expired_keys_are_forgotten() {
+        let store = IdempotencyStore::with_ttl(Duration::ZERO);
+        store.run("web", "tell", Some("k"), || Ok(1u64)).unwrap();
+        assert_eq!(store.run("web", "tell", Some("k"), || Ok(2u64)).unwrap(), 2);
+        assert_eq!(store.len(), 1);
+    }
+
+    #[test]
+    fn test_duplicate_is_refused_while_first_runs() {
+        let store = IdempotencyStore::new();
+        let nested: Result<u64, String> = store.run("web", "tell", Some("k"), || {
+            assert!(store.run("web", "tell", Some("k"), || Ok(2u64)).is_err());
+            Ok(1)
+        });
+        assert_eq!(nested.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_duplicates_run_once() {
+        let store = IdempotencyStore::new();
+        let sent = AtomicUsize::new(0);
+        let send = || async {
+            tokio::task::yield_now().await;
+            Ok(sent.fetch_add(1, Ordering::SeqCst))
+        };
+
+        let (first, second, other) = tokio::join!(
+            store.run_async("ui", "send_message", Some("k1"), send),
+            store.run_async("ui", "send_message", Some("k1"), send),
+            store.run_async("ui", "send_message", Some("k2"), send),
+        );
+        assert_eq!(first.unwrap(), second.unwrap());
+        assert!(other.is_ok());
+        assert_eq!(sent.load(Ordering::SeqCst), 2);
+    }
+}
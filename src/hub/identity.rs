@@ -9,6 +9,7 @@ use std::path::PathBuf;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::network::protocol::DEFAULT_PORT;
+use crate::sync::write_atomic;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HubIdentity {
@@ -65,7 +66,7 @@ impl HubIdentity {
         let json = serde_json::to_string_pretty(self)
             .map_err(|e| format!("Cannot serialize identity: {}", e))?;
 
-        fs::write(file_path, json).map_err(|e| format!("Cannot write identity file: {}", e))
+        write_atomic(file_path, json).map_err(|e| format!("Cannot write identity file: {}", e))
     }
 
     pub fn set_name(&mut self, name: &str) {
@@ -174,6 +175,16 @@ pub struct ConnectedHub {
     pub connected_at: u64,
     pub last_seen: u64,
     pub session_count: usize,
+    /// Consecutive heartbeats the hub has failed to answer
+    #[serde(default)]
+    pub missed_heartbeats: u32,
+    /// Set once too many heartbeats were missed; cleared by the next answer
+    #[serde(default)]
+    pub offline: bool,
+    /// Approved here but its token not yet collected by the hub's next
+    /// handshake
+    #[serde(default)]
+    pub awaiting_token: bool,
 }
 
 impl ConnectedHub {
@@ -192,6 +203,9 @@ impl ConnectedHub {
             connected_at: timestamp,
             last_seen: timestamp,
             session_count: 0,
+            missed_heartbeats: 0,
+            offline: false,
+            awaiting_token: false,
         }
     }
 
@@ -202,13 +216,23 @@ impl ConnectedHub {
             .unwrap_or(0);
     }
 
+    /// Online unless marked offline or silent for longer than the default
+    /// heartbeat interval times the miss limit
     pub fn is_online(&self) -> bool {
+        self.is_online_within(
+            crate::network::DEFAULT_HEARTBEAT_INTERVAL_SECS
+                * u64::from(crate::network::DEFAULT_HEARTBEAT_MISS_LIMIT),
+        )
+    }
+
+    /// Online unless marked offline or silent for more than `max_silence_secs`
+    pub fn is_online_within(&self, max_silence_secs: u64) -> bool {
         let now = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .map(|d| d.as_secs())
             .unwrap_or(0);
 
-        now.saturating_sub(self.last_seen) < 60
+        !self.offline && now.saturating_sub(self.last_seen) <= max_silence_secs
     }
 
     pub fn socket_address(&self) -> String {
@@ -262,7 +286,12 @@ mod tests {
 
     #[test]
     fn test_connected_hub_online_status() {
-        let hub = ConnectedHub::new("test-id", "Test Hub", "192.168.1.100", 9876, "token123");
+        let mut hub = ConnectedHub::new("test-id", "Test Hub", "192.168.1.100", 9876, "token123");
         assert!(hub.is_online());
+
+        // Heartbeats stopped without the hub being marked offline
+        hub.last_seen -= 600;
+        assert!(!hub.is_online());
+        assert!(hub.is_online_within(900));
     }
 }
@@ -3,11 +3,13 @@
 //! Real-time messaging between collaborative sessions
 
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
 use std::fs;
 use std::path::PathBuf;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use super::HubConfig;
+use crate::sync::write_atomic;
 
 /// Message types
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -40,7 +42,13 @@ pub struct Message {
     pub content: String,
     pub message_type: MessageType,
     pub timestamp: u64,
+    /// Whether the recipient has read the message. Read state is kept per
+    /// session in [`READ_RECEIPTS_FILE`]; inbox copies have this set for the
+    /// session they were fetched for.
     pub read: bool,
+    /// Id of the message this one replies to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reply_to: Option<String>,
 }
 
 impl Message {
@@ -51,7 +59,8 @@ impl Message {
             .map(|d| d.as_secs())
             .unwrap_or(0);
 
-        let id = format!("{}-{}-{}", from, to, timestamp);
+        let suffix = uuid::Uuid::new_v4().simple().to_string();
+        let id = format!("{}-{}-{}-{}", from, to, timestamp, &suffix[..8]);
 
         Self {
             id,
@@ -61,9 +70,16 @@ impl Message {
             message_type,
             timestamp,
             read: false,
+            reply_to: None,
         }
     }
 
+    /// Mark this message as a reply to `parent_id`
+    pub fn with_reply_to(mut self, parent_id: &str) -> Self {
+        self.reply_to = Some(parent_id.to_string());
+        self
+    }
+
     /// Create a broadcast message
     pub fn broadcast(from: &str, content: &str) -> Self {
         Self::new(from, "all", content, MessageType::Broadcast)
@@ -112,6 +128,44 @@ impl Message {
     }
 }
 
+/// Order messages so replies follow their parent, paired with their reply
+/// depth. Top-level messages keep their given order; replies are oldest
+/// first under each parent.
+pub fn threaded(messages: &[Message]) -> Vec<(usize, &Message)> {
+    let is_root = |m: &Message| {
+        m.reply_to
+            .as_deref()
+            .map_or(true, |parent| !messages.iter().any(|p| p.id == parent))
+    };
+
+    let mut ordered = Vec::with_capacity(messages.len());
+    for root in messages.iter().filter(|m| is_root(m)) {
+        ordered.push((0, root));
+        push_replies(root, 1, messages, &mut ordered);
+    }
+    ordered
+}
+
+fn push_replies<'a>(
+    parent: &'a Message,
+    depth: usize,
+    messages: &'a [Message],
+    ordered: &mut Vec<(usize, &'a Message)>,
+) {
+    let mut replies: Vec<&Message> = messages
+        .iter()
+        .filter(|m| m.reply_to.as_deref() == Some(parent.id.as_str()))
+        .collect();
+    replies.sort_by_key(|m| m.timestamp);
+    for reply in replies {
+        if ordered.iter().any(|(_, m)| m.id == reply.id) {
+            continue;
+        }
+        ordered.push((depth, reply));
+        push_replies(reply, depth + 1, messages, ordered);
+    }
+}
+
 /// Broadcast message wrapper
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Broadcast {
@@ -139,10 +193,23 @@ struct MessagesData {
     last_updated: u64,
 }
 
+/// Sender exempt from rate limiting
+const SYSTEM_SENDER: &str = "hub";
+
+/// File in the messages dir mapping message ids to the sessions that read them
+pub const READ_RECEIPTS_FILE: &str = "read_receipts.json";
+
+/// Message id to the sessions that have read it
+type ReadReceipts = BTreeMap<String, BTreeSet<String>>;
+
 /// Message Queue
 pub struct MessageQueue {
     messages: Vec<Message>,
     messages_dir: PathBuf,
+    rate_limit: u32,
+    rate_window: Duration,
+    sent: HashMap<String, VecDeque<Instant>>,
+    read_by: ReadReceipts,
 }
 
 impl MessageQueue {
@@ -151,11 +218,54 @@ impl MessageQueue {
         Self {
             messages: Vec::new(),
             messages_dir: config.messages_dir.clone(),
+            rate_limit: config.rate_limit_per_minute,
+            rate_window: Duration::from_secs(60),
+            sent: HashMap::new(),
+            read_by: ReadReceipts::new(),
+        }
+    }
+
+    /// Allow at most `limit` messages per sender within `window` (0 disables the limit)
+    pub fn with_rate_limit(mut self, limit: u32, window: Duration) -> Self {
+        self.rate_limit = limit;
+        self.rate_window = window;
+        self
+    }
+
+    /// Record a send from `from`, rejecting it if the sender is over its rate limit
+    fn check_rate_limit(&mut self, from: &str) -> Result<(), String> {
+        if self.rate_limit == 0 || from == SYSTEM_SENDER {
+            return Ok(());
+        }
+
+        let window = self.rate_window;
+        let sent = self.sent.entry(from.to_string()).or_default();
+        while sent.front().is_some_and(|at| at.elapsed() >= window) {
+            sent.pop_front();
+        }
+
+        if sent.len() >= self.rate_limit as usize {
+            eprintln!(
+                "Warning: session '{}' exceeded the hub rate limit ({} messages per {}s)",
+                from,
+                self.rate_limit,
+                window.as_secs()
+            );
+            return Err(format!(
+                "Rate limit exceeded: '{}' may send {} messages per {}s",
+                from,
+                self.rate_limit,
+                window.as_secs()
+            ));
         }
+
+        sent.push_back(Instant::now());
+        Ok(())
     }
 
     /// Send a direct message
     pub fn send(&mut self, from: &str, to: &str, content: &str) -> Result<(), String> {
+        self.check_rate_limit(from)?;
         let message = Message::new(from, to, content, MessageType::Direct);
         self.messages.push(message.clone());
 
@@ -165,8 +275,27 @@ impl MessageQueue {
         Ok(())
     }
 
+    /// Send a direct message replying to an existing message
+    pub fn reply(
+        &mut self,
+        from: &str,
+        to: &str,
+        content: &str,
+        parent_id: &str,
+    ) -> Result<(), String> {
+        if self.find(parent_id).is_none() {
+            return Err(format!("Message '{}' not found", parent_id));
+        }
+        self.check_rate_limit(from)?;
+        let message = Message::new(from, to, content, MessageType::Direct).with_reply_to(parent_id);
+        self.messages.push(message.clone());
+        self.save_to_inbox(to, &message)?;
+        Ok(())
+    }
+
     /// Send a broadcast message
     pub fn broadcast(&mut self, from: &str, content: &str) -> Result<(), String> {
+        self.check_rate_limit(from)?;
         let message = Message::broadcast(from, content);
         self.messages.push(message.clone());
 
@@ -201,45 +330,76 @@ impl MessageQueue {
         Ok(())
     }
 
-    /// Get inbox for a session
+    fn is_for(message: &Message, session_id: &str) -> bool {
+        message.to == session_id || message.to == "all"
+    }
+
+    /// Whether `session_id` has read the message. Broadcasts are tracked
+    /// separately for every session.
+    pub fn is_read_by(&self, message: &Message, session_id: &str) -> bool {
+        (message.read && message.to != "all")
+            || self
+                .read_by
+                .get(&message.id)
+                .is_some_and(|readers| readers.contains(session_id))
+    }
+
+    /// Get inbox for a session, with `read` set for that session
     pub fn get_inbox(&self, session_id: &str) -> Vec<Message> {
         // Get direct messages to this session + broadcasts
         self.messages
             .iter()
-            .filter(|m| m.to == session_id || m.to == "all")
-            .cloned()
+            .filter(|m| Self::is_for(m, session_id))
+            .map(|m| Message {
+                read: self.is_read_by(m, session_id),
+                ..m.clone()
+            })
             .collect()
     }
 
     /// Get unread messages for a session
     pub fn get_unread(&self, session_id: &str) -> Vec<Message> {
-        self.messages
-            .iter()
-            .filter(|m| (m.to == session_id || m.to == "all") && !m.read)
-            .cloned()
+        self.get_inbox(session_id)
+            .into_iter()
+            .filter(|m| !m.read)
             .collect()
     }
 
-    /// Mark message as read
-    pub fn mark_read(&mut self, message_id: &str) {
-        if let Some(msg) = self.messages.iter_mut().find(|m| m.id == message_id) {
-            msg.read = true;
+    /// Record that `session_id` read a message and persist the receipt.
+    /// Returns false when the message is unknown.
+    pub fn mark_read(&mut self, session_id: &str, message_id: &str) -> Result<bool, String> {
+        if self.find(message_id).is_none() {
+            return Ok(false);
         }
+        self.read_by
+            .entry(message_id.to_string())
+            .or_default()
+            .insert(session_id.to_string());
+        self.save_read_receipts()?;
+        Ok(true)
     }
 
     /// Mark all messages for a session as read
-    pub fn mark_all_read(&mut self, session_id: &str) {
-        for msg in self.messages.iter_mut() {
-            if msg.to == session_id || msg.to == "all" {
-                msg.read = true;
-            }
+    pub fn mark_all_read(&mut self, session_id: &str) -> Result<(), String> {
+        let ids: Vec<String> = self
+            .messages
+            .iter()
+            .filter(|m| Self::is_for(m, session_id))
+            .map(|m| m.id.clone())
+            .collect();
+        for id in ids {
+            self.read_by
+                .entry(id)
+                .or_default()
+                .insert(session_id.to_string());
         }
+        self.save_read_receipts()
     }
 
     /// Get recent messages (last N)
     pub fn get_recent(&self, count: usize) -> Vec<Message> {
         let mut messages: Vec<Message> = self.messages.clone();
-        messages.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        messages.sort_by_key(|b| std::cmp::Reverse(b.timestamp));
         messages.into_iter().take(count).collect()
     }
 
@@ -254,6 +414,26 @@ impl MessageQueue {
             .collect()
     }
 
+    pub fn find(&self, message_id: &str) -> Option<&Message> {
+        self.messages.iter().find(|m| m.id == message_id)
+    }
+
+    /// The reply chain ending at `message_id`, oldest first. Empty when
+    /// the message is unknown; stops early if a parent is missing.
+    pub fn thread(&self, message_id: &str) -> Vec<Message> {
+        let mut chain = Vec::new();
+        let mut current = self.find(message_id);
+        while let Some(message) = current {
+            if chain.iter().any(|m: &Message| m.id == message.id) {
+                break;
+            }
+            chain.push(message.clone());
+            current = message.reply_to.as_deref().and_then(|id| self.find(id));
+        }
+        chain.reverse();
+        chain
+    }
+
     /// Clear old messages (older than N seconds)
     pub fn cleanup(&mut self, max_age_secs: u64) {
         let now = SystemTime::now()
@@ -281,7 +461,7 @@ impl MessageQueue {
         let json = serde_json::to_string_pretty(&inbox)
             .map_err(|e| format!("Cannot serialize inbox: {}", e))?;
 
-        fs::write(&inbox_file, json).map_err(|e| format!("Cannot write inbox: {}", e))?;
+        write_atomic(&inbox_file, json).map_err(|e| format!("Cannot write inbox: {}", e))?;
 
         Ok(())
     }
@@ -308,12 +488,37 @@ impl MessageQueue {
         let json = serde_json::to_string_pretty(&broadcasts)
             .map_err(|e| format!("Cannot serialize broadcasts: {}", e))?;
 
-        fs::write(&broadcast_file, json).map_err(|e| format!("Cannot write broadcasts: {}", e))?;
+        write_atomic(&broadcast_file, json)
+            .map_err(|e| format!("Cannot write broadcasts: {}", e))?;
 
         Ok(())
     }
 
+    fn read_receipts_on_disk(&self) -> ReadReceipts {
+        fs::read_to_string(self.messages_dir.join(READ_RECEIPTS_FILE))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Merge receipts with those on disk before writing, so sessions in
+    /// other processes marking messages read are not overwritten
+    fn save_read_receipts(&mut self) -> Result<(), String> {
+        for (id, readers) in self.read_receipts_on_disk() {
+            self.read_by.entry(id).or_default().extend(readers);
+        }
+
+        let json = serde_json::to_string_pretty(&self.read_by)
+            .map_err(|e| format!("Cannot serialize read receipts: {}", e))?;
+        write_atomic(self.messages_dir.join(READ_RECEIPTS_FILE), json)
+            .map_err(|e| format!("Cannot write read receipts: {}", e))
+    }
+
     pub fn load(&mut self) -> Result<(), String> {
+        for (id, readers) in self.read_receipts_on_disk() {
+            self.read_by.entry(id).or_default().extend(readers);
+        }
+
         let broadcast_file = self.messages_dir.join("broadcast.json");
         if broadcast_file.exists() {
             let content = fs::read_to_string(&broadcast_file)
@@ -340,7 +545,10 @@ impl MessageQueue {
             if path.is_file() {
                 let filename = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
 
-                if filename.ends_with(".json") && filename != "broadcast.json" {
+                if filename.ends_with(".json")
+                    && filename != "broadcast.json"
+                    && filename != READ_RECEIPTS_FILE
+                {
                     if let Ok(content) = fs::read_to_string(&path) {
                         let inbox_messages: Vec<Message> =
                             serde_json::from_str(&content).unwrap_or_default();
@@ -388,6 +596,10 @@ impl MessageQueue {
 
         self.messages
             .retain(|m| m.from != session_id && m.to != session_id);
+        for readers in self.read_by.values_mut() {
+            readers.remove(session_id);
+        }
+        self.read_by.retain(|_, readers| !readers.is_empty());
 
         let inbox_file = self.messages_dir.join(format!("{}.json", session_id));
         if inbox_file.exists() {
@@ -401,6 +613,7 @@ impl MessageQueue {
     pub fn clear_all(&mut self) -> Result<usize, String> {
         let count = self.messages.len();
         self.messages.clear();
+        self.read_by.clear();
 
         if self.messages_dir.exists() {
             let entries = fs::read_dir(&self.messages_dir)
@@ -453,4 +666,123 @@ mod tests {
         let inbox = queue.get_inbox("backend");
         assert_eq!(inbox.len(), 1);
     }
+
+    #[test]
+    fn test_reply_references_parent() {
+        let config = HubConfig::new();
+        let mut queue = MessageQueue::new(&config);
+        queue.messages.push(Message::new(
+            "web",
+            "backend",
+            "API ready?",
+            MessageType::Direct,
+        ));
+        let parent_id = queue.get_all()[0].id.clone();
+
+        let reply =
+            Message::new("backend", "web", "Yes", MessageType::Direct).with_reply_to(&parent_id);
+        assert_eq!(reply.reply_to.as_deref(), Some(parent_id.as_str()));
+        assert_ne!(reply.id, parent_id);
+
+        let json = serde_json::to_string(&reply).unwrap();
+        let parsed: Message = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.reply_to, reply.reply_to);
+
+        let legacy: Message = serde_json::from_str(
+            r#"{"id":"a","from":"x","to":"y","content":"hi","message_type":"Direct","timestamp":1,"read":false}"#,
+        )
+        .unwrap();
+        assert!(legacy.reply_to.is_none());
+
+        assert!(queue.reply("backend", "web", "?", "missing").is_err());
+    }
+
+    #[test]
+    fn test_thread_returns_ordered_chain() {
+        let config = HubConfig::new();
+        let mut queue = MessageQueue::new(&config);
+        let root = Message::new("web", "backend", "API ready?", MessageType::Direct);
+        let reply =
+            Message::new("backend", "web", "Almost", MessageType::Direct).with_reply_to(&root.id);
+        let nested =
+            Message::new("web", "backend", "ETA?", MessageType::Direct).with_reply_to(&reply.id);
+        let other = Message::new("iot", "web", "Unrelated", MessageType::Direct);
+        queue.messages = vec![nested.clone(), other.clone(), reply.clone(), root.clone()];
+
+        let ids: Vec<String> = queue.thread(&nested.id).into_iter().map(|m| m.id).collect();
+        assert_eq!(
+            ids,
+            vec![root.id.clone(), reply.id.clone(), nested.id.clone()]
+        );
+        assert_eq!(queue.thread(&root.id).len(), 1);
+        assert!(queue.thread("missing").is_empty());
+
+        let depths: Vec<(usize, &str)> = threaded(&queue.messages)
+            .into_iter()
+            .map(|(depth, m)| (depth, m.content.as_str()))
+            .collect();
+        assert_eq!(
+            depths,
+            vec![
+                (0, "Unrelated"),
+                (0, "API ready?"),
+                (1, "Almost"),
+                (2, "ETA?")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_broadcast_read_receipts_are_per_session() {
+        let dir = std::env::temp_dir().join(format!("sena-hub-{}", uuid::Uuid::new_v4()));
+        let config = HubConfig::with_dir(dir.clone());
+        config.ensure_dirs().unwrap();
+        let mut queue = MessageQueue::new(&config);
+
+        queue.broadcast("web", "Deploy at 5pm").unwrap();
+        queue.send("web", "backend", "API ready?").unwrap();
+        let broadcast_id = queue.get_inbox("android")[0].id.clone();
+        assert_eq!(queue.unread_count("backend"), 2);
+        assert_eq!(queue.unread_count("android"), 1);
+
+        assert!(queue.mark_read("backend", &broadcast_id).unwrap());
+        assert!(!queue.mark_read("backend", "missing").unwrap());
+        assert_eq!(queue.unread_count("backend"), 1);
+        assert_eq!(queue.unread_count("android"), 1);
+        assert!(queue.get_unread("android")[0].id == broadcast_id);
+
+        let mut reloaded = MessageQueue::new(&config);
+        reloaded.load().unwrap();
+        assert_eq!(reloaded.count(), 2);
+        assert_eq!(reloaded.unread_count("backend"), 1);
+        assert_eq!(reloaded.unread_count("android"), 1);
+        assert!(reloaded
+            .get_inbox("backend")
+            .iter()
+            .any(|m| m.id == broadcast_id && m.read));
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_rate_limit_rejects_flooding_session() {
+        let config = HubConfig::new();
+        let mut queue = MessageQueue::new(&config).with_rate_limit(3, Duration::from_millis(200));
+
+        for i in 0..3 {
+            assert!(queue
+                .send("looper", "backend", &format!("ping {}", i))
+                .is_ok());
+        }
+        let err = queue.broadcast("looper", "ping 3").unwrap_err();
+        assert!(err.contains("Rate limit exceeded"));
+        assert!(queue.send("web", "backend", "still fine").is_ok());
+
+        for _ in 0..5 {
+            assert!(queue.broadcast("hub", "system notice").is_ok());
+        }
+
+        std::thread::sleep(Duration::from_millis(250));
+        assert!(queue.send("looper", "backend", "after window").is_ok());
+    }
 }
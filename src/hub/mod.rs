@@ -16,28 +16,37 @@
 
 pub mod conflicts;
 pub mod context;
+pub mod graph;
+pub mod idempotency;
 pub mod identity;
 pub mod messages;
 pub mod peers;
+pub mod removal;
 pub mod session;
 #[cfg(unix)]
 pub mod socket;
 pub mod state;
 pub mod tasks;
 
-pub use conflicts::{ConflictDetector, FileConflict};
+pub use conflicts::{ConflictDetector, FileConflict, DEFAULT_LOCK_TTL_SECS};
 pub use context::{ContextManager, SessionContext};
+pub use idempotency::{IdempotencyStore, DEFAULT_IDEMPOTENCY_TTL};
 pub use identity::{ConnectedHub, ConnectionRequest, DiscoveredHub, HubIdentity};
-pub use messages::{Broadcast, Message, MessageQueue};
-pub use peers::{FederatedSession, PeerManager, RemoteSession, ResolvedTarget};
+pub use messages::{threaded, Broadcast, Message, MessageQueue};
+pub use peers::{
+    FederatedSession, HandshakeError, HandshakeOutcome, HeartbeatHandle, HeartbeatReport,
+    PeerManager, RemoteSession, ResolvedTarget,
+};
+pub use removal::RemovalSummary;
 pub use session::{Session, SessionRegistry, SessionRole, SessionStatus};
 #[cfg(unix)]
 pub use socket::{HubClient, HubServer};
-pub use state::{HubState, SharedState};
+pub use state::{HubState, SharedState, StateEntry, FIELD_ACTIVE, FIELD_STATUS, FIELD_WORKING_ON};
 pub use tasks::{Task, TaskBoard, TaskPriority, TaskStatus};
 
 use std::fs;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 /// Hub configuration
 pub struct HubConfig {
@@ -46,19 +55,34 @@ pub struct HubConfig {
     pub state_file: PathBuf,
     pub tasks_file: PathBuf,
     pub messages_dir: PathBuf,
+    /// Messages a session may send per minute (0 disables the limit)
+    pub rate_limit_per_minute: u32,
+    pub locks_file: PathBuf,
+    /// Enforce file locks in `set_working_on` rather than only warning
+    pub lock_files: bool,
+    /// Seconds a file lock outlives its holder's last heartbeat
+    pub lock_ttl_secs: u64,
 }
 
 impl HubConfig {
     /// Create hub config with default paths
     pub fn new() -> Self {
         let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
-        let hub_dir = home.join(".claude").join("hub");
+        Self::with_dir(home.join(".claude").join("hub"))
+    }
 
+    /// Create hub config rooted at `hub_dir`
+    pub fn with_dir(hub_dir: PathBuf) -> Self {
+        let settings = &crate::config::SenaConfig::global().hub;
         Self {
             socket_path: hub_dir.join("hub.sock"),
             state_file: hub_dir.join("state.json"),
             tasks_file: hub_dir.join("tasks.json"),
             messages_dir: hub_dir.join("messages"),
+            rate_limit_per_minute: settings.rate_limit_per_minute,
+            locks_file: hub_dir.join("locks.json"),
+            lock_files: settings.lock_files,
+            lock_ttl_secs: settings.lock_ttl_seconds,
             hub_dir,
         }
     }
@@ -70,6 +94,42 @@ impl HubConfig {
             .map_err(|e| format!("Cannot create messages dir: {}", e))?;
         Ok(())
     }
+
+    /// Remove all hub data, or with `dry_run` only report the files that would be removed
+    pub fn clear(&self, dry_run: bool) -> Result<RemovalSummary, String> {
+        let mut summary = RemovalSummary::new(self.hub_dir.display().to_string(), dry_run);
+        let mut files = Vec::new();
+        collect_files(&self.hub_dir, &mut files);
+        files.sort();
+        for file in files {
+            let relative = file.strip_prefix(&self.hub_dir).unwrap_or(&file);
+            summary.push(relative.display().to_string());
+        }
+
+        if !dry_run {
+            if self.hub_dir.exists() {
+                fs::remove_dir_all(&self.hub_dir)
+                    .map_err(|e| format!("Cannot remove hub dir: {}", e))?;
+            }
+            self.ensure_dirs()?;
+        }
+
+        Ok(summary)
+    }
+}
+
+fn collect_files(dir: &std::path::Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_files(&path, files);
+        } else {
+            files.push(path);
+        }
+    }
 }
 
 impl Default for HubConfig {
@@ -87,11 +147,16 @@ pub struct Hub {
     pub messages: MessageQueue,
     pub conflicts: ConflictDetector,
     pub context: ContextManager,
+    pub idempotency: Arc<IdempotencyStore>,
 }
 
 impl Hub {
     pub fn new() -> Result<Self, String> {
-        let config = HubConfig::new();
+        Self::with_config(HubConfig::new())
+    }
+
+    /// A hub whose state lives under `config.hub_dir`
+    pub fn with_config(config: HubConfig) -> Result<Self, String> {
         config.ensure_dirs()?;
 
         Ok(Self {
@@ -99,8 +164,9 @@ impl Hub {
             state: HubState::new(&config),
             tasks: TaskBoard::new(&config),
             messages: MessageQueue::new(&config),
-            conflicts: ConflictDetector::new(),
+            conflicts: ConflictDetector::with_config(&config),
             context: ContextManager::new(&config),
+            idempotency: Arc::new(IdempotencyStore::new()),
             config,
         })
     }
@@ -132,6 +198,9 @@ impl Hub {
         }
         self.sessions.unregister(session_id)?;
         self.state.set_session_active(session_id, false);
+        if self.conflicts.release_session_locks(session_id) > 0 {
+            self.conflicts.save_locks()?;
+        }
         Ok(())
     }
 
@@ -145,6 +214,35 @@ impl Hub {
         self.messages.send(from, to, message)
     }
 
+    /// Send message unless a message with the same idempotency key was already sent
+    pub fn tell_idempotent(
+        &mut self,
+        from: &str,
+        to: &str,
+        message: &str,
+        idempotency_key: Option<&str>,
+    ) -> Result<(), String> {
+        let messages = &mut self.messages;
+        self.idempotency.run(from, "tell", idempotency_key, || {
+            messages.send(from, to, message)
+        })
+    }
+
+    /// Send a message replying to an existing message, once per idempotency key
+    pub fn reply(
+        &mut self,
+        from: &str,
+        to: &str,
+        message: &str,
+        parent_id: &str,
+        idempotency_key: Option<&str>,
+    ) -> Result<(), String> {
+        let messages = &mut self.messages;
+        self.idempotency.run(from, "reply", idempotency_key, || {
+            messages.reply(from, to, message, parent_id)
+        })
+    }
+
     /// Broadcast message to all sessions
     pub fn broadcast(&mut self, from: &str, message: &str) -> Result<(), String> {
         self.messages.broadcast(from, message)
@@ -155,6 +253,16 @@ impl Hub {
         self.messages.get_inbox(session_id)
     }
 
+    /// Get the messages a session has not read yet
+    pub fn inbox_unread(&self, session_id: &str) -> Vec<Message> {
+        self.messages.get_unread(session_id)
+    }
+
+    /// Record that a session read a message
+    pub fn mark_read(&mut self, session_id: &str, message_id: &str) -> Result<bool, String> {
+        self.messages.mark_read(session_id, message_id)
+    }
+
     /// Create a new task and broadcast to all sessions
     pub fn create_task(
         &mut self,
@@ -167,6 +275,25 @@ impl Hub {
         Ok(task)
     }
 
+    /// Create a task, returning the previously created task when `creator`
+    /// repeats an idempotency key
+    pub fn create_task_idempotent(
+        &mut self,
+        creator: Option<&str>,
+        title: &str,
+        assignee: &str,
+        priority: TaskPriority,
+        idempotency_key: Option<&str>,
+    ) -> Result<Task, String> {
+        let idempotency = Arc::clone(&self.idempotency);
+        idempotency.run(
+            creator.unwrap_or_default(),
+            "create_task",
+            idempotency_key,
+            || self.create_task(title, assignee, priority),
+        )
+    }
+
     /// Create task from specific session and broadcast
     pub fn create_task_from(
         &mut self,
@@ -223,9 +350,26 @@ impl Hub {
         self.messages.broadcast("hub", &message)
     }
 
-    /// Set working state for a session
+    /// Set working state for a session. With `lock_files` enabled the
+    /// session must take the file's lock, failing while another live session
+    /// holds it; otherwise overlapping work only prints a warning.
     pub fn set_working_on(&mut self, session_id: &str, file_path: &str) -> Result<(), String> {
-        if let Some(conflict) = self
+        if self.config.lock_files {
+            for session in self.sessions.get_all() {
+                self.conflicts
+                    .record_heartbeat(&session.id, session.last_heartbeat);
+            }
+            let previous = self
+                .state
+                .get_session_state(session_id)
+                .and_then(|state| state.working_on.clone());
+
+            self.conflicts.acquire_lock(session_id, file_path)?;
+            if let Some(previous) = previous.filter(|path| path != file_path) {
+                self.conflicts.release_lock(session_id, &previous)?;
+            }
+            self.conflicts.save_locks()?;
+        } else if let Some(conflict) = self
             .conflicts
             .check_file(file_path, session_id, &self.state)
         {
@@ -245,6 +389,15 @@ impl Hub {
         self.conflicts.get_all(&self.state)
     }
 
+    /// Build the session/task relationship graph
+    pub fn graph(&self) -> crate::ancient::RelationshipDataModel {
+        graph::build_graph(
+            &self.sessions.get_all(),
+            &self.tasks.get_all(),
+            &self.state.get_all_working(),
+        )
+    }
+
     /// Get hub status summary
     pub fn status(&self) -> HubStatus {
         HubStatus {
@@ -269,6 +422,7 @@ impl Hub {
         self.state.load()?;
         self.tasks.load()?;
         self.messages.load()?;
+        self.conflicts.load_locks()?;
         Ok(())
     }
 }
@@ -285,8 +439,9 @@ impl Default for Hub {
                     state: HubState::new(&config),
                     tasks: TaskBoard::new(&config),
                     messages: MessageQueue::new(&config),
-                    conflicts: ConflictDetector::new(),
+                    conflicts: ConflictDetector::with_config(&config),
                     context: ContextManager::new(&config),
+                    idempotency: Arc::new(IdempotencyStore::new()),
                     config,
                 }
             }
@@ -295,7 +450,7 @@ impl Default for Hub {
 }
 
 /// Hub status summary
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize)]
 pub struct HubStatus {
     pub online_sessions: usize,
     pub total_tasks: usize,
@@ -314,9 +469,108 @@ mod tests {
         assert!(config.hub_dir.to_string_lossy().contains("hub"));
     }
 
+    #[test]
+    fn test_clear_dry_run_leaves_files() {
+        let dir = std::env::temp_dir().join(format!("sena-hub-clear-{}", uuid::Uuid::new_v4()));
+        let config = HubConfig::with_dir(dir.clone());
+        config.ensure_dirs().unwrap();
+        fs::write(&config.state_file, "{}").unwrap();
+        fs::write(config.messages_dir.join("web.json"), "[]").unwrap();
+
+        let preview = config.clear(true).unwrap();
+        assert!(preview.dry_run);
+        assert_eq!(preview.count, 2);
+        assert!(preview.items.contains(&"state.json".to_string()));
+        assert!(config.state_file.exists());
+        assert!(config.messages_dir.join("web.json").exists());
+
+        let cleared = config.clear(false).unwrap();
+        assert_eq!(cleared.count, 2);
+        assert!(!config.state_file.exists());
+        assert!(!config.messages_dir.join("web.json").exists());
+        assert!(config.messages_dir.exists());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
     #[test]
     fn test_hub_creation() {
         let hub = Hub::new();
         assert!(hub.is_ok());
     }
+
+    #[test]
+    fn test_idempotent_tell_sends_once() {
+        let dir = std::env::temp_dir().join(format!("sena-hub-idem-{}", uuid::Uuid::new_v4()));
+        let mut hub = Hub::with_config(HubConfig::with_dir(dir.clone())).unwrap();
+        let target = "idem-target";
+
+        let first = hub.tell_idempotent("hub", target, "hello", Some("tell-1"));
+        let second = hub.tell_idempotent("hub", target, "hello", Some("tell-1"));
+        assert_eq!(first, second);
+        assert_eq!(hub.inbox(target).len(), 1);
+
+        hub.tell_idempotent("hub", target, "hello", Some("tell-2"))
+            .unwrap();
+        assert_eq!(hub.inbox(target).len(), 2);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_idempotent_reply_sends_once() {
+        let dir = std::env::temp_dir().join(format!("sena-hub-idem-{}", uuid::Uuid::new_v4()));
+        let mut hub = Hub::with_config(HubConfig::with_dir(dir.clone())).unwrap();
+        hub.tell("web", "api", "ready?").unwrap();
+        let parent = hub.inbox("api")[0].id.clone();
+
+        hub.reply("api", "web", "yes", &parent, Some("reply-1"))
+            .unwrap();
+        hub.reply("api", "web", "yes", &parent, Some("reply-1"))
+            .unwrap();
+        assert_eq!(hub.inbox("web").len(), 1);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_idempotent_create_task_returns_same_task() {
+        let dir = std::env::temp_dir().join(format!("sena-hub-idem-{}", uuid::Uuid::new_v4()));
+        let mut hub = Hub::with_config(HubConfig::with_dir(dir.clone())).unwrap();
+        hub.load().unwrap();
+
+        let first = hub
+            .create_task_idempotent(
+                Some("web"),
+                "Idempotent task",
+                "web",
+                TaskPriority::Low,
+                Some("task-1"),
+            )
+            .unwrap();
+        let second = hub
+            .create_task_idempotent(
+                Some("web"),
+                "Idempotent task",
+                "web",
+                TaskPriority::Low,
+                Some("task-1"),
+            )
+            .unwrap();
+        assert_eq!(first.id, second.id);
+
+        let third = hub
+            .create_task_idempotent(
+                Some("web"),
+                "Idempotent task",
+                "web",
+                TaskPriority::Low,
+                Some("task-2"),
+            )
+            .unwrap();
+        assert_ne!(first.id, third.id);
+        assert_eq!(hub.tasks.get_all().len(), 2);
+
+        let _ = fs::remove_dir_all(dir);
+    }
 }
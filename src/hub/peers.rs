@@ -5,11 +5,91 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
+use std::io::{self, Write};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
 use std::path::{Path, PathBuf};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use super::identity::{ConnectedHub, ConnectionRequest, DiscoveredHub, HubIdentity};
 use super::session::Session;
+use super::state::{HubState, SharedState};
+use super::HubConfig;
+use crate::network::protocol::{read_frame_blocking, NetworkCommand, NetworkMessage};
+use crate::network::{
+    NetworkConfig, DEFAULT_HEARTBEAT_INTERVAL_SECS, DEFAULT_HEARTBEAT_MISS_LIMIT,
+};
+use crate::sync::write_atomic;
+
+/// SHA-256 of the shared secret a remote hub must present to file a
+/// connection request; the passkey itself is only shown when generated
+pub const PASSKEY_FILE: &str = "passkey.txt";
+
+/// How long a manual handshake may take to connect, send or receive
+pub const DEFAULT_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Largest handshake frame accepted from an unauthenticated peer
+const MAX_HANDSHAKE_FRAME: usize = 64 * 1024;
+
+#[derive(Debug, thiserror::Error)]
+pub enum HandshakeError {
+    #[error("Invalid hub address: {0}")]
+    InvalidAddress(String),
+    #[error("Hub at {address} is unreachable: {reason}")]
+    Unreachable { address: String, reason: String },
+    #[error("Hub at {address} did not respond within {secs}s")]
+    Timeout { address: String, secs: u64 },
+    #[error("Connection rejected: {0}")]
+    Rejected(String),
+    #[error("Handshake failed: {0}")]
+    Protocol(String),
+    #[error("{0}")]
+    Storage(String),
+}
+
+/// Result of a manual handshake, seen from either side
+#[derive(Debug, Clone)]
+pub enum HandshakeOutcome {
+    /// A connection request is waiting for approval on the accepting hub
+    Pending {
+        request_id: String,
+        hub: HubIdentity,
+    },
+    /// The request was approved and the auth token is stored in `peers.json`
+    Connected(ConnectedHub),
+}
+
+/// Hubs whose reachability changed during one heartbeat round
+#[derive(Debug, Clone, Default)]
+pub struct HeartbeatReport {
+    pub online: usize,
+    pub went_offline: Vec<String>,
+    pub came_back: Vec<String>,
+}
+
+/// Background heartbeat started by [`PeerManager::spawn_heartbeat`]; stops
+/// when dropped
+pub struct HeartbeatHandle {
+    stop: Option<mpsc::Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl HeartbeatHandle {
+    /// Stop the heartbeat and wait for an in-flight round to finish
+    pub fn stop(self) {
+        drop(self);
+    }
+}
+
+impl Drop for HeartbeatHandle {
+    fn drop(&mut self) {
+        drop(self.stop.take());
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RemoteSession {
@@ -44,7 +124,11 @@ pub struct PeerManager {
     pending_requests: Vec<ConnectionRequest>,
     remote_sessions: HashMap<String, Vec<RemoteSession>>,
     discovered_hubs: Vec<DiscoveredHub>,
+    hub_dir: PathBuf,
     peers_file: PathBuf,
+    handshake_timeout: Duration,
+    heartbeat_interval: Duration,
+    heartbeat_miss_limit: u32,
 }
 
 impl PeerManager {
@@ -55,10 +139,32 @@ impl PeerManager {
             pending_requests: Vec::new(),
             remote_sessions: HashMap::new(),
             discovered_hubs: Vec::new(),
+            hub_dir: hub_dir.to_path_buf(),
             peers_file: hub_dir.join("peers.json"),
+            handshake_timeout: DEFAULT_HANDSHAKE_TIMEOUT,
+            heartbeat_interval: Duration::from_secs(DEFAULT_HEARTBEAT_INTERVAL_SECS),
+            heartbeat_miss_limit: DEFAULT_HEARTBEAT_MISS_LIMIT,
         }
     }
 
+    pub fn with_handshake_timeout(mut self, timeout: Duration) -> Self {
+        self.handshake_timeout = timeout;
+        self
+    }
+
+    pub fn with_heartbeat(mut self, interval: Duration, miss_limit: u32) -> Self {
+        self.heartbeat_interval = interval;
+        self.heartbeat_miss_limit = miss_limit.max(1);
+        self
+    }
+
+    pub fn with_network_config(self, config: &NetworkConfig) -> Self {
+        self.with_heartbeat(
+            Duration::from_secs(config.heartbeat_interval_secs),
+            config.heartbeat_miss_limit,
+        )
+    }
+
     pub fn identity(&self) -> &HubIdentity {
         &self.identity
     }
@@ -140,13 +246,14 @@ impl PeerManager {
         }
 
         let auth_token = Self::generate_auth_token();
-        let connected_hub = ConnectedHub::new(
+        let mut connected_hub = ConnectedHub::new(
             &request.from_hub_id,
             &request.from_hub_name,
             &request.from_address,
             request.from_port,
             &auth_token,
         );
+        connected_hub.awaiting_token = true;
 
         self.connected_hubs
             .insert(request.from_hub_id.clone(), connected_hub.clone());
@@ -169,6 +276,470 @@ impl PeerManager {
         self.save()
     }
 
+    fn passkey_file(&self) -> PathBuf {
+        self.peers_file.with_file_name(PASSKEY_FILE)
+    }
+
+    fn passkey_hash(&self) -> Option<String> {
+        fs::read_to_string(self.passkey_file())
+            .ok()
+            .map(|p| p.trim().to_string())
+            .filter(|p| !p.is_empty())
+    }
+
+    /// Whether a passkey has been generated for remote hubs to present
+    pub fn has_passkey(&self) -> bool {
+        self.passkey_hash().is_some()
+    }
+
+    /// Check `passkey` against the stored hash in constant time
+    pub fn verify_passkey(&self, passkey: &str) -> bool {
+        self.passkey_hash().is_some_and(|stored| {
+            constant_time_eq(stored.as_bytes(), hash_passkey(passkey).as_bytes())
+        })
+    }
+
+    /// Generate a new passkey, storing only its hash. The passkey is
+    /// returned so it can be shown once.
+    pub fn generate_passkey(&self) -> Result<String, String> {
+        let passkey = Self::generate_auth_token();
+        if let Some(parent) = self.peers_file.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Cannot create peers directory: {}", e))?;
+        }
+        write_atomic(self.passkey_file(), hash_passkey(&passkey))
+            .map_err(|e| format!("Cannot write passkey: {}", e))?;
+        Ok(passkey)
+    }
+
+    /// Ask the hub at `address:port` to trust this one, without relying on
+    /// mDNS discovery. The first call files a connection request on the
+    /// remote hub; once it has been approved there, calling again fetches
+    /// the auth token and stores the hub as connected.
+    pub fn initiate_connection(
+        &mut self,
+        address: &str,
+        port: u16,
+        passkey: &str,
+    ) -> Result<HandshakeOutcome, HandshakeError> {
+        self.initiate_connection_with_message(address, port, passkey, None)
+    }
+
+    pub fn initiate_connection_with_message(
+        &mut self,
+        address: &str,
+        port: u16,
+        passkey: &str,
+        message: Option<String>,
+    ) -> Result<HandshakeOutcome, HandshakeError> {
+        let target = format!("{}:{}", address, port);
+        let mut stream = self.connect(&target)?;
+        // Reconnecting to a hub we hold a token for proves who we are
+        let held_token = self
+            .connected_hubs
+            .values()
+            .find(|hub| hub.address == address && hub.port == port)
+            .map(|hub| hub.auth_token.clone());
+        let hello =
+            NetworkMessage::hub_handshake(&self.identity, passkey, message, held_token.as_deref());
+        write_frame(&mut stream, &hello).map_err(|e| self.io_error(&target, e))?;
+        let reply = read_frame(&mut stream).map_err(|e| self.io_error(&target, e))?;
+
+        match reply.command {
+            NetworkCommand::HubHandshakeAck {
+                identity,
+                request_id,
+                auth_token,
+            } => {
+                if identity.hub_id == self.identity.hub_id {
+                    return Err(HandshakeError::Protocol(
+                        "Cannot connect to this hub itself".to_string(),
+                    ));
+                }
+                match auth_token {
+                    Some(token) => {
+                        let hub = ConnectedHub::new(
+                            &identity.hub_id,
+                            &identity.name,
+                            address,
+                            port,
+                            &token,
+                        );
+                        self.add_connected_hub(hub.clone())
+                            .map_err(HandshakeError::Storage)?;
+                        Ok(HandshakeOutcome::Connected(hub))
+                    }
+                    None => Ok(HandshakeOutcome::Pending {
+                        request_id: request_id.unwrap_or_default(),
+                        hub: identity,
+                    }),
+                }
+            }
+            NetworkCommand::ConnectionDenied { reason, .. } => {
+                Err(HandshakeError::Rejected(reason))
+            }
+            NetworkCommand::Error { message, .. } => Err(HandshakeError::Protocol(message)),
+            _ => Err(HandshakeError::Protocol(
+                "Unexpected reply to handshake".to_string(),
+            )),
+        }
+    }
+
+    /// Answer one incoming connection: heartbeats from connected hubs get
+    /// a pong and return `None`, handshakes are answered as by
+    /// [`Self::accept_handshake`]
+    pub fn accept(
+        &mut self,
+        mut stream: TcpStream,
+    ) -> Result<Option<HandshakeOutcome>, HandshakeError> {
+        let (remote, message) = self.read_incoming(&mut stream)?;
+        if let NetworkCommand::HubHeartbeat {
+            hub_id,
+            auth_token,
+            state,
+        } = &message.command
+        {
+            self.answer_heartbeat(&mut stream, remote, hub_id, auth_token, state.as_ref())?;
+            return Ok(None);
+        }
+        self.answer_handshake(stream, remote, message).map(Some)
+    }
+
+    /// Answer a heartbeat carrying the token shared with a connected hub,
+    /// and count it as hearing from that hub. Shared session state it
+    /// carries is merged, and the merged state sent back.
+    fn answer_heartbeat(
+        &mut self,
+        stream: &mut TcpStream,
+        remote: SocketAddr,
+        hub_id: &str,
+        auth_token: &str,
+        remote_state: Option<&SharedState>,
+    ) -> Result<(), HandshakeError> {
+        // The hub may have been approved by another process
+        self.merge_from_disk().map_err(HandshakeError::Storage)?;
+
+        let Some(hub) = self
+            .connected_hubs
+            .get_mut(hub_id)
+            .filter(|hub| constant_time_eq(hub.auth_token.as_bytes(), auth_token.as_bytes()))
+        else {
+            let reply = NetworkMessage::connection_denied("", "Unknown hub or token");
+            let _ = write_frame(stream, &reply);
+            return Err(HandshakeError::Rejected(
+                "Heartbeat with an unknown hub or token".to_string(),
+            ));
+        };
+        hub.update_last_seen();
+        hub.missed_heartbeats = 0;
+        hub.offline = false;
+        self.save().map_err(HandshakeError::Storage)?;
+
+        let reply = match remote_state {
+            Some(remote_state) => {
+                let merged = self
+                    .merge_shared_states(std::slice::from_ref(remote_state))
+                    .map_err(HandshakeError::Storage)?;
+                NetworkMessage::hub_heartbeat_ack(merged)
+            }
+            None => NetworkMessage::pong(),
+        };
+        write_frame(stream, &reply).map_err(|e| self.io_error(&remote.to_string(), e))
+    }
+
+    /// This hub's state file, holding the shared session state exchanged
+    /// with connected hubs on every heartbeat
+    fn hub_state(&self) -> Result<HubState, String> {
+        let mut state = HubState::new(&HubConfig::with_dir(self.hub_dir.clone()));
+        state.load()?;
+        Ok(state)
+    }
+
+    /// Merge shared session state received from connected hubs into this
+    /// hub's and save it; returns the merged state
+    fn merge_shared_states(&self, remote: &[SharedState]) -> Result<SharedState, String> {
+        let mut state = self.hub_state()?;
+        if !remote.is_empty() {
+            for other in remote {
+                state.merge_shared(other);
+            }
+            state.save()?;
+        }
+        Ok(state.shared_state().clone())
+    }
+
+    /// Answer one incoming handshake. A valid passkey files a connection
+    /// request for `sena hub approve`; a hub that has already been approved
+    /// is issued a new auth token.
+    pub fn accept_handshake(
+        &mut self,
+        mut stream: TcpStream,
+    ) -> Result<HandshakeOutcome, HandshakeError> {
+        let (remote, message) = self.read_incoming(&mut stream)?;
+        self.answer_handshake(stream, remote, message)
+    }
+
+    fn read_incoming(
+        &self,
+        stream: &mut TcpStream,
+    ) -> Result<(SocketAddr, NetworkMessage), HandshakeError> {
+        let remote = stream
+            .peer_addr()
+            .map_err(|e| HandshakeError::Protocol(e.to_string()))?;
+        let target = remote.to_string();
+        self.set_timeouts(stream)
+            .map_err(|e| self.io_error(&target, e))?;
+        let message = read_frame(stream).map_err(|e| self.io_error(&target, e))?;
+        Ok((remote, message))
+    }
+
+    fn answer_handshake(
+        &mut self,
+        mut stream: TcpStream,
+        remote: SocketAddr,
+        hello: NetworkMessage,
+    ) -> Result<HandshakeOutcome, HandshakeError> {
+        let target = remote.to_string();
+        let NetworkCommand::HubHandshake {
+            identity,
+            passkey,
+            message,
+            auth_token,
+        } = hello.command
+        else {
+            let reply = NetworkMessage::error(400, "Expected a hub handshake");
+            let _ = write_frame(&mut stream, &reply);
+            return Err(HandshakeError::Protocol(
+                "Expected a hub handshake".to_string(),
+            ));
+        };
+
+        // Approvals may have been made by another process, e.g. the desktop app
+        self.load().map_err(HandshakeError::Storage)?;
+
+        let denial = if identity.hub_id == self.identity.hub_id {
+            Some("Cannot connect a hub to itself")
+        } else if !self.verify_passkey(&passkey) {
+            Some("Invalid passkey")
+        } else {
+            None
+        };
+        if let Some(reason) = denial {
+            let reply = NetworkMessage::connection_denied("", reason);
+            let _ = write_frame(&mut stream, &reply);
+            return Err(HandshakeError::Rejected(reason.to_string()));
+        }
+
+        // A token goes to the approved hub collecting it from the address
+        // it asked from, or to a hub proving it holds the current one; any
+        // other claim to a connected hub's id has to be approved again
+        let remote_ip = remote.ip().to_string();
+        let entitled =
+            self.connected_hubs
+                .get(&identity.hub_id)
+                .is_some_and(|hub| match &auth_token {
+                    Some(token) => constant_time_eq(hub.auth_token.as_bytes(), token.as_bytes()),
+                    None => hub.awaiting_token && hub.address == remote_ip,
+                });
+
+        let (reply, outcome) = if let Some(hub) = self
+            .connected_hubs
+            .get_mut(&identity.hub_id)
+            .filter(|_| entitled)
+        {
+            // A fresh token each time, so holding the passkey never reveals
+            // one already in use
+            hub.auth_token = Self::generate_auth_token();
+            hub.awaiting_token = false;
+            hub.update_last_seen();
+            let hub = hub.clone();
+            self.save().map_err(HandshakeError::Storage)?;
+            (
+                NetworkMessage::hub_handshake_ack(
+                    &self.identity,
+                    None,
+                    Some(hub.auth_token.clone()),
+                ),
+                HandshakeOutcome::Connected(hub),
+            )
+        } else {
+            let existing = self
+                .get_pending_requests()
+                .into_iter()
+                .find(|r| r.from_hub_id == identity.hub_id)
+                .map(|r| r.request_id.clone());
+            let request_id = match existing {
+                Some(request_id) => request_id,
+                None => {
+                    let request =
+                        ConnectionRequest::new(&identity, &remote.ip().to_string(), message);
+                    let request_id = request.request_id.clone();
+                    self.add_pending_request(request)
+                        .map_err(HandshakeError::Storage)?;
+                    request_id
+                }
+            };
+            (
+                NetworkMessage::hub_handshake_ack(&self.identity, Some(request_id.clone()), None),
+                HandshakeOutcome::Pending {
+                    request_id,
+                    hub: identity,
+                },
+            )
+        };
+
+        write_frame(&mut stream, &reply).map_err(|e| self.io_error(&target, e))?;
+        Ok(outcome)
+    }
+
+    /// Ping every connected hub once, exchanging shared session state. A
+    /// hub that answers is marked online and its `last_seen` refreshed; one
+    /// that misses the configured number of heartbeats in a row is marked
+    /// offline.
+    pub fn heartbeat(&mut self) -> Result<HeartbeatReport, String> {
+        let state = self.hub_state()?.shared_state().clone();
+        let (results, states) =
+            exchange_heartbeats(self.heartbeat_targets(), &state, self.handshake_timeout);
+        self.merge_shared_states(&states)?;
+        self.record_heartbeats(&results)
+    }
+
+    /// Run [`Self::heartbeat`] every configured interval on a background
+    /// thread. The lock is released while pinging, so slow hubs do not
+    /// block other users of the manager.
+    pub fn spawn_heartbeat(manager: Arc<Mutex<PeerManager>>) -> HeartbeatHandle {
+        let (stop, stopped) = mpsc::channel::<()>();
+        let thread = std::thread::spawn(move || loop {
+            let Ok((interval, timeout, targets, state)) = manager.lock().map(|m| {
+                (
+                    m.heartbeat_interval,
+                    m.handshake_timeout,
+                    m.heartbeat_targets(),
+                    m.hub_state(),
+                )
+            }) else {
+                return;
+            };
+            if !matches!(
+                stopped.recv_timeout(interval),
+                Err(mpsc::RecvTimeoutError::Timeout)
+            ) {
+                return;
+            }
+
+            let state = match state {
+                Ok(state) => state.shared_state().clone(),
+                Err(e) => {
+                    log::warn!("Cannot load hub state: {}", e);
+                    SharedState::default()
+                }
+            };
+            let (results, states) = exchange_heartbeats(targets, &state, timeout);
+            let Ok(mut manager) = manager.lock() else {
+                return;
+            };
+            if let Err(e) = manager.merge_shared_states(&states) {
+                log::warn!("Cannot merge hub state: {}", e);
+            }
+            match manager.record_heartbeats(&results) {
+                Ok(report) => {
+                    for name in &report.went_offline {
+                        log::warn!("Hub {} stopped answering heartbeats", name);
+                    }
+                }
+                Err(e) => log::warn!("Cannot record heartbeats: {}", e),
+            }
+        });
+
+        HeartbeatHandle {
+            stop: Some(stop),
+            thread: Some(thread),
+        }
+    }
+
+    fn heartbeat_targets(&self) -> Vec<HeartbeatTarget> {
+        self.connected_hubs
+            .values()
+            .map(|h| HeartbeatTarget {
+                hub_id: h.hub_id.clone(),
+                address: h.socket_address(),
+                from_hub_id: self.identity.hub_id.clone(),
+                auth_token: h.auth_token.clone(),
+            })
+            .collect()
+    }
+
+    fn record_heartbeats(&mut self, results: &[(String, bool)]) -> Result<HeartbeatReport, String> {
+        // Pick up approvals made by other processes before writing the file
+        // back
+        self.merge_from_disk()?;
+
+        let mut report = HeartbeatReport::default();
+        for (hub_id, answered) in results {
+            let Some(hub) = self.connected_hubs.get_mut(hub_id) else {
+                continue;
+            };
+            if *answered {
+                hub.update_last_seen();
+                hub.missed_heartbeats = 0;
+                if hub.offline {
+                    hub.offline = false;
+                    report.came_back.push(hub.name.clone());
+                }
+            } else {
+                hub.missed_heartbeats = hub.missed_heartbeats.saturating_add(1);
+                if !hub.offline && hub.missed_heartbeats >= self.heartbeat_miss_limit {
+                    hub.offline = true;
+                    report.went_offline.push(hub.name.clone());
+                }
+            }
+        }
+        report.online = self.online_count();
+        self.save()?;
+        Ok(report)
+    }
+
+    fn connect(&self, target: &str) -> Result<TcpStream, HandshakeError> {
+        let addrs = target
+            .to_socket_addrs()
+            .map_err(|e| HandshakeError::InvalidAddress(format!("{} ({})", target, e)))?;
+
+        let mut last_error = None;
+        for addr in addrs {
+            match TcpStream::connect_timeout(&addr, self.handshake_timeout) {
+                Ok(stream) => {
+                    self.set_timeouts(&stream)
+                        .map_err(|e| self.io_error(target, e))?;
+                    return Ok(stream);
+                }
+                Err(e) => last_error = Some(e),
+            }
+        }
+        Err(match last_error {
+            Some(e) => self.io_error(target, e),
+            None => HandshakeError::InvalidAddress(target.to_string()),
+        })
+    }
+
+    fn set_timeouts(&self, stream: &TcpStream) -> io::Result<()> {
+        stream.set_read_timeout(Some(self.handshake_timeout))?;
+        stream.set_write_timeout(Some(self.handshake_timeout))
+    }
+
+    fn io_error(&self, target: &str, error: io::Error) -> HandshakeError {
+        match error.kind() {
+            io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock => HandshakeError::Timeout {
+                address: target.to_string(),
+                secs: self.handshake_timeout.as_secs(),
+            },
+            io::ErrorKind::InvalidData => HandshakeError::Protocol(error.to_string()),
+            _ => HandshakeError::Unreachable {
+                address: target.to_string(),
+                reason: error.to_string(),
+            },
+        }
+    }
+
     pub fn add_connected_hub(&mut self, hub: ConnectedHub) -> Result<(), String> {
         self.connected_hubs.insert(hub.hub_id.clone(), hub);
         self.save()
@@ -245,7 +816,16 @@ impl PeerManager {
             });
         }
 
-        for remote in self.remote_sessions.values().flat_map(|s| s.iter()) {
+        let online_sessions = self
+            .remote_sessions
+            .iter()
+            .filter(|(hub_id, _)| {
+                self.connected_hubs
+                    .get(*hub_id)
+                    .is_some_and(|hub| self.is_hub_online(hub))
+            })
+            .flat_map(|(_, sessions)| sessions.iter());
+        for remote in online_sessions {
             all_sessions.push(FederatedSession {
                 hub_id: remote.hub_id.clone(),
                 hub_name: remote.hub_name.clone(),
@@ -343,7 +923,7 @@ impl PeerManager {
         let json = serde_json::to_string_pretty(&data)
             .map_err(|e| format!("Cannot serialize peers: {}", e))?;
 
-        fs::write(&self.peers_file, json).map_err(|e| format!("Cannot write peers file: {}", e))
+        write_atomic(&self.peers_file, json).map_err(|e| format!("Cannot write peers file: {}", e))
     }
 
     pub fn load(&mut self) -> Result<(), String> {
@@ -366,6 +946,37 @@ impl PeerManager {
         Ok(())
     }
 
+    /// Add the hubs and requests another process saved that this manager
+    /// has not seen, keeping its own state for the rest. A request approved
+    /// elsewhere is dropped.
+    fn merge_from_disk(&mut self) -> Result<(), String> {
+        if !self.peers_file.exists() {
+            return Ok(());
+        }
+        let content = fs::read_to_string(&self.peers_file)
+            .map_err(|e| format!("Cannot read peers file: {}", e))?;
+        let data: PeerManagerData = serde_json::from_str(&content)
+            .map_err(|e| format!("Cannot parse peers file: {}", e))?;
+
+        for (hub_id, hub) in data.connected_hubs {
+            self.connected_hubs.entry(hub_id).or_insert(hub);
+        }
+        for request in data.pending_requests {
+            if !self
+                .pending_requests
+                .iter()
+                .any(|r| r.request_id == request.request_id)
+            {
+                self.pending_requests.push(request);
+            }
+        }
+        let connected = &self.connected_hubs;
+        self.pending_requests
+            .retain(|r| !connected.contains_key(&r.from_hub_id));
+        self.cleanup_expired_requests();
+        Ok(())
+    }
+
     pub fn connected_count(&self) -> usize {
         self.connected_hubs.len()
     }
@@ -373,10 +984,17 @@ impl PeerManager {
     pub fn online_count(&self) -> usize {
         self.connected_hubs
             .values()
-            .filter(|h| h.is_online())
+            .filter(|h| self.is_hub_online(h))
             .count()
     }
 
+    /// Online unless marked offline or silent for as long as it takes to
+    /// miss the configured number of heartbeats
+    fn is_hub_online(&self, hub: &ConnectedHub) -> bool {
+        let max_silence = self.heartbeat_interval * self.heartbeat_miss_limit;
+        hub.is_online_within(max_silence.as_secs())
+    }
+
     pub fn pending_count(&self) -> usize {
         self.get_pending_requests().len()
     }
@@ -386,6 +1004,87 @@ impl PeerManager {
     }
 }
 
+fn hash_passkey(passkey: &str) -> String {
+    use sha2::{Digest, Sha256};
+    hex::encode(Sha256::digest(passkey.trim().as_bytes()))
+}
+
+/// Compare without stopping at the first differing byte
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// A connected hub to ping, and the credentials to ping it with
+struct HeartbeatTarget {
+    hub_id: String,
+    address: String,
+    from_hub_id: String,
+    auth_token: String,
+}
+
+/// Heartbeat each target; returns whether each answered, and the shared
+/// state the ones that answered sent back
+fn exchange_heartbeats(
+    targets: Vec<HeartbeatTarget>,
+    state: &SharedState,
+    timeout: Duration,
+) -> (Vec<(String, bool)>, Vec<SharedState>) {
+    let mut results = Vec::new();
+    let mut states = Vec::new();
+    for target in targets {
+        let answer = ping_hub(&target, state, timeout);
+        results.push((target.hub_id, answer.is_ok()));
+        states.extend(answer.ok().flatten());
+    }
+    (results, states)
+}
+
+/// Send `target` a heartbeat carrying `state`; returns its shared state,
+/// or `None` when it answered with a plain pong
+fn ping_hub(
+    target: &HeartbeatTarget,
+    state: &SharedState,
+    timeout: Duration,
+) -> io::Result<Option<SharedState>> {
+    let heartbeat =
+        NetworkMessage::hub_heartbeat(&target.from_hub_id, &target.auth_token, Some(state.clone()));
+    let mut last_error = io::Error::new(io::ErrorKind::NotFound, "no address");
+    for addr in target.address.to_socket_addrs()? {
+        match TcpStream::connect_timeout(&addr, timeout) {
+            Ok(mut stream) => {
+                stream.set_read_timeout(Some(timeout))?;
+                stream.set_write_timeout(Some(timeout))?;
+                write_frame(&mut stream, &heartbeat)?;
+                return match read_frame(&mut stream)?.command {
+                    NetworkCommand::HubHeartbeatAck { state } => Ok(Some(state)),
+                    NetworkCommand::Pong => Ok(None),
+                    _ => Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "Expected a heartbeat answer",
+                    )),
+                };
+            }
+            Err(e) => last_error = e,
+        }
+    }
+    Err(last_error)
+}
+
+fn write_frame(stream: &mut TcpStream, message: &NetworkMessage) -> io::Result<()> {
+    let bytes = message
+        .to_bytes()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    stream.write_all(&bytes)?;
+    stream.flush()
+}
+
+fn read_frame(stream: &mut TcpStream) -> io::Result<NetworkMessage> {
+    let payload = read_frame_blocking(stream, MAX_HANDSHAKE_FRAME)?
+        .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+    NetworkMessage::from_payload(&payload)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FederatedSession {
     pub hub_id: String,
@@ -431,11 +1130,31 @@ pub enum ResolvedTarget {
 mod tests {
     use super::*;
     use std::env::temp_dir;
+    use std::net::TcpListener;
 
     fn create_test_identity() -> HubIdentity {
         HubIdentity::create_new()
     }
 
+    type HandshakeResult = Result<HandshakeOutcome, HandshakeError>;
+
+    fn handshake(
+        listener: &TcpListener,
+        initiator: &mut PeerManager,
+        acceptor: &mut PeerManager,
+        passkey: &str,
+    ) -> (HandshakeResult, HandshakeResult) {
+        let port = listener.local_addr().unwrap().port();
+        std::thread::scope(|scope| {
+            let accepted = scope.spawn(|| {
+                let (stream, _) = listener.accept().unwrap();
+                acceptor.accept_handshake(stream)
+            });
+            let initiated = initiator.initiate_connection("127.0.0.1", port, passkey);
+            (initiated, accepted.join().unwrap())
+        })
+    }
+
     #[test]
     fn test_peer_manager_creation() {
         let identity = create_test_identity();
@@ -471,4 +1190,255 @@ mod tests {
         let resolved = manager.resolve_session("Android", &sessions);
         assert!(matches!(resolved, Some(ResolvedTarget::Local { .. })));
     }
+
+    #[test]
+    fn test_manual_handshake_over_loopback() {
+        let root = temp_dir().join(format!("sena-peers-{}", uuid::Uuid::new_v4()));
+        let mut initiator = PeerManager::new(create_test_identity(), &root.join("a"));
+        let mut acceptor = PeerManager::new(create_test_identity(), &root.join("b"));
+        let passkey = acceptor.generate_passkey().unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (initiated, accepted) =
+            handshake(&listener, &mut initiator, &mut acceptor, "wrong-passkey");
+        assert!(matches!(initiated, Err(HandshakeError::Rejected(_))));
+        assert!(matches!(accepted, Err(HandshakeError::Rejected(_))));
+
+        let (initiated, _) = handshake(&listener, &mut initiator, &mut acceptor, &passkey);
+        let request_id = match initiated.unwrap() {
+            HandshakeOutcome::Pending { request_id, hub } => {
+                assert_eq!(hub.hub_id, acceptor.identity().hub_id);
+                request_id
+            }
+            other => panic!("expected a pending request, got {:?}", other),
+        };
+        let pending = acceptor.get_pending_requests();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].request_id, request_id);
+        assert_eq!(pending[0].from_hub_id, initiator.identity().hub_id);
+
+        let approved = acceptor.approve_request(&request_id).unwrap();
+        let (initiated, _) = handshake(&listener, &mut initiator, &mut acceptor, &passkey);
+        let HandshakeOutcome::Connected(hub) = initiated.unwrap() else {
+            panic!("expected the approved hub");
+        };
+        assert_ne!(hub.auth_token, approved.auth_token);
+        assert_eq!(
+            acceptor
+                .get_connected_hub(&initiator.identity().hub_id)
+                .unwrap()
+                .auth_token,
+            hub.auth_token
+        );
+        assert_eq!(hub.port, port);
+
+        let mut reloaded = PeerManager::new(create_test_identity(), &root.join("a"));
+        reloaded.load().unwrap();
+        let stored = reloaded
+            .get_connected_hub(&acceptor.identity().hub_id)
+            .unwrap();
+        assert_eq!(stored.auth_token, hub.auth_token);
+        let stored_passkey = fs::read_to_string(acceptor.passkey_file()).unwrap();
+        assert!(!stored_passkey.contains(&passkey));
+
+        // Knowing the passkey and the hub id is not enough to take over a
+        // connected hub: the claim needs approving again
+        let mut impostor = PeerManager::new(initiator.identity().clone(), &root.join("c"));
+        let (claimed, _) = handshake(&listener, &mut impostor, &mut acceptor, &passkey);
+        assert!(matches!(claimed, Ok(HandshakeOutcome::Pending { .. })));
+        let held = acceptor
+            .get_connected_hub(&initiator.identity().hub_id)
+            .unwrap()
+            .auth_token
+            .clone();
+        assert_eq!(held, hub.auth_token);
+
+        // The real hub proves its token and gets a fresh one
+        let (renewed, _) = handshake(&listener, &mut initiator, &mut acceptor, &passkey);
+        let HandshakeOutcome::Connected(renewed) = renewed.unwrap() else {
+            panic!("expected the connected hub");
+        };
+        assert_ne!(renewed.auth_token, hub.auth_token);
+        assert_eq!(
+            initiator
+                .get_connected_hub(&acceptor.identity().hub_id)
+                .unwrap()
+                .auth_token,
+            renewed.auth_token
+        );
+
+        drop(listener);
+        let unreachable = initiator.initiate_connection("127.0.0.1", port, &passkey);
+        assert!(matches!(
+            unreachable,
+            Err(HandshakeError::Unreachable { .. } | HandshakeError::Timeout { .. })
+        ));
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_heartbeat_marks_silent_hub_offline() {
+        let root = temp_dir().join(format!("sena-heartbeat-{}", uuid::Uuid::new_v4()));
+        let mut manager = PeerManager::new(create_test_identity(), &root.join("a"))
+            .with_handshake_timeout(Duration::from_secs(1))
+            .with_heartbeat(Duration::from_secs(1), 2);
+        let mut remote = PeerManager::new(create_test_identity(), &root.join("b"));
+        let local_id = manager.identity().hub_id.clone();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let mut hub = ConnectedHub::new("remote-hub", "Remote", "127.0.0.1", port, "token");
+        hub.last_seen = 0;
+        manager.add_connected_hub(hub).unwrap();
+
+        // Not yet known to the remote hub: the heartbeat is refused
+        let refused = std::thread::scope(|scope| {
+            let answered = scope.spawn(|| {
+                let (stream, _) = listener.accept().unwrap();
+                remote.accept(stream)
+            });
+            manager.heartbeat().unwrap();
+            answered.join().unwrap()
+        });
+        assert!(matches!(refused, Err(HandshakeError::Rejected(_))));
+        remote
+            .add_connected_hub(ConnectedHub::new(
+                &local_id,
+                "Local",
+                "127.0.0.1",
+                0,
+                "token",
+            ))
+            .unwrap();
+        let hub = manager.connected_hubs.get_mut("remote-hub").unwrap();
+        hub.missed_heartbeats = 0;
+        hub.last_seen = 0;
+
+        manager.update_remote_sessions(
+            "remote-hub",
+            vec![RemoteSession {
+                hub_id: "remote-hub".to_string(),
+                hub_name: "Remote".to_string(),
+                session_id: "s1".to_string(),
+                session_name: "Backend".to_string(),
+                role: "backend".to_string(),
+                status: "Active".to_string(),
+                working_on: None,
+                working_directory: "/srv".to_string(),
+            }],
+        );
+        manager.save().unwrap();
+
+        // Approved elsewhere meanwhile; the heartbeat must not drop it
+        let mut other = PeerManager::new(create_test_identity(), &root.join("a"));
+        other.load().unwrap();
+        other
+            .add_connected_hub(ConnectedHub::new(
+                "third-hub",
+                "Third",
+                "127.0.0.1",
+                1,
+                "t3",
+            ))
+            .unwrap();
+
+        let report = std::thread::scope(|scope| {
+            let answered = scope.spawn(|| {
+                let (stream, _) = listener.accept().unwrap();
+                remote.accept(stream)
+            });
+            let report = manager.heartbeat().unwrap();
+            assert!(answered.join().unwrap().unwrap().is_none());
+            report
+        });
+        assert_eq!(report.online, 2);
+        assert!(manager.get_connected_hub("remote-hub").unwrap().last_seen > 0);
+        assert!(manager.get_connected_hub("third-hub").is_some());
+        manager.disconnect_hub("third-hub").unwrap();
+        assert!(remote.get_connected_hub(&local_id).unwrap().last_seen > 0);
+        assert_eq!(manager.get_all_sessions(&[]).len(), 1);
+
+        drop(listener);
+        let report = manager.heartbeat().unwrap();
+        assert!(report.went_offline.is_empty());
+        assert!(manager.get_connected_hub("remote-hub").unwrap().is_online());
+
+        let report = manager.heartbeat().unwrap();
+        assert_eq!(report.went_offline, vec!["Remote".to_string()]);
+        assert_eq!(manager.online_count(), 0);
+        assert!(manager.get_all_sessions(&[]).is_empty());
+
+        let mut reloaded = PeerManager::new(create_test_identity(), &root.join("a"));
+        reloaded.load().unwrap();
+        let stored = reloaded.get_connected_hub("remote-hub").unwrap();
+        assert!(stored.offline);
+        assert_eq!(stored.missed_heartbeats, 2);
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_heartbeat_exchanges_shared_state() {
+        let root = temp_dir().join(format!("sena-state-sync-{}", uuid::Uuid::new_v4()));
+        let working_on = |hub: &str, session: &str, file: &str| {
+            let config = HubConfig::with_dir(root.join(hub));
+            config.ensure_dirs().unwrap();
+            let mut state = HubState::new(&config);
+            state.set_working_on(session, file);
+            state.save().unwrap();
+        };
+        working_on("a", "local-session", "src/main.rs");
+        working_on("b", "remote-session", "src/lib.rs");
+
+        let mut manager = PeerManager::new(create_test_identity(), &root.join("a"))
+            .with_handshake_timeout(Duration::from_secs(1));
+        let mut remote = PeerManager::new(create_test_identity(), &root.join("b"));
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        manager
+            .add_connected_hub(ConnectedHub::new(
+                "remote-hub",
+                "Remote",
+                "127.0.0.1",
+                port,
+                "token",
+            ))
+            .unwrap();
+        remote
+            .add_connected_hub(ConnectedHub::new(
+                &manager.identity().hub_id,
+                "Local",
+                "127.0.0.1",
+                0,
+                "token",
+            ))
+            .unwrap();
+
+        std::thread::scope(|scope| {
+            let answered = scope.spawn(|| {
+                let (stream, _) = listener.accept().unwrap();
+                remote.accept(stream)
+            });
+            manager.heartbeat().unwrap();
+            assert!(answered.join().unwrap().unwrap().is_none());
+        });
+
+        // Both hubs now know what the other's sessions are working on
+        for hub in ["a", "b"] {
+            let mut state = HubState::new(&HubConfig::with_dir(root.join(hub)));
+            state.load().unwrap();
+            assert_eq!(
+                state.who_is_working_on("src/main.rs"),
+                vec!["local-session"]
+            );
+            assert_eq!(
+                state.who_is_working_on("src/lib.rs"),
+                vec!["remote-session"]
+            );
+        }
+
+        let _ = fs::remove_dir_all(root);
+    }
 }
@@ -0,0 +1,54 @@
+//! Removal Summaries
+//!
+//! Structured report of what a destructive hub or network operation removed,
+//! or would remove when run as a dry run.
+
+use serde::{Deserialize, Serialize};
+
+/// Items removed (or that would be removed) by a destructive operation
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RemovalSummary {
+    pub target: String,
+    pub dry_run: bool,
+    pub items: Vec<String>,
+    pub count: usize,
+}
+
+impl RemovalSummary {
+    pub fn new(target: impl Into<String>, dry_run: bool) -> Self {
+        Self {
+            target: target.into(),
+            dry_run,
+            items: Vec::new(),
+            count: 0,
+        }
+    }
+
+    pub fn push(&mut self, item: impl Into<String>) {
+        self.items.push(item.into());
+        self.count += 1;
+    }
+
+    /// Human-readable report, listing at most `max_items` items
+    pub fn display(&self, max_items: usize) -> String {
+        let verb = if self.dry_run {
+            "Would remove"
+        } else {
+            "Removed"
+        };
+        let mut output = format!("{} {} item(s) from {}\n", verb, self.count, self.target);
+        for item in self.items.iter().take(max_items) {
+            output.push_str(&format!("  - {}\n", item));
+        }
+        if self.items.len() > max_items {
+            output.push_str(&format!(
+                "  ... and {} more\n",
+                self.items.len() - max_items
+            ));
+        }
+        if self.dry_run {
+            output.push_str("Dry run: nothing was removed. Re-run with --yes to apply.\n");
+        }
+        output
+    }
+}
@@ -12,6 +12,8 @@ use std::path::PathBuf;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use super::HubConfig;
+use crate::sync::write_atomic;
+use crate::tools::{SecretResolver, SessionEnv};
 
 /// Maximum command history size per session
 const MAX_COMMAND_HISTORY: usize = 100;
@@ -104,6 +106,11 @@ pub struct Session {
     pub commands_executed: u64,
     pub errors_encountered: u64,
     pub last_command: Option<String>,
+
+    /// Environment for tools run by this session. Values of the form
+    /// `secret:<provider>/<field>` are resolved from the credential store.
+    #[serde(default)]
+    pub env: HashMap<String, String>,
 }
 
 impl Session {
@@ -135,6 +142,7 @@ impl Session {
             commands_executed: 0,
             errors_encountered: 0,
             last_command: None,
+            env: HashMap::new(),
         }
     }
 
@@ -231,6 +239,17 @@ impl Session {
         self.preferences.get(key)
     }
 
+    /// Set a tool environment variable, or a `secret:<provider>/<field>` reference
+    pub fn set_env(&mut self, key: &str, value: &str) {
+        self.env.insert(key.to_string(), value.to_string());
+        self.heartbeat();
+    }
+
+    /// Resolve the environment injected into this session's tool calls
+    pub fn tool_env(&self, resolver: &dyn SecretResolver) -> Result<SessionEnv, String> {
+        SessionEnv::resolve(&self.env, resolver)
+    }
+
     /// Get command history
     pub fn get_command_history(&self, limit: usize) -> Vec<String> {
         let start = self.command_history.len().saturating_sub(limit);
@@ -410,6 +429,16 @@ impl SessionRegistry {
         }
     }
 
+    /// Set a tool environment variable for a session
+    pub fn set_env(&mut self, session_id: &str, key: &str, value: &str) -> Result<(), String> {
+        let session = self
+            .sessions
+            .get_mut(session_id)
+            .ok_or_else(|| format!("Session {} not found", session_id))?;
+        session.set_env(key, value);
+        self.save()
+    }
+
     /// Clean up stale sessions
     pub fn cleanup_stale(&mut self) -> Vec<String> {
         let stale_ids: Vec<String> = self
@@ -472,7 +501,7 @@ impl SessionRegistry {
                 .map_err(|e| format!("Cannot create sessions directory: {}", e))?;
         }
 
-        fs::write(&self.sessions_file, json)
+        write_atomic(&self.sessions_file, json)
             .map_err(|e| format!("Cannot write sessions file: {}", e))?;
 
         Ok(())
@@ -547,7 +576,7 @@ impl SessionRegistry {
                 .map_err(|e| format!("Cannot create preferences directory: {}", e))?;
         }
 
-        fs::write(&self.preferences_file, json)
+        write_atomic(&self.preferences_file, json)
             .map_err(|e| format!("Cannot write preferences file: {}", e))?;
 
         Ok(())
@@ -35,6 +35,11 @@ pub enum HubCommand {
         from: String,
         to: String,
         message: String,
+        /// Id of the message this one replies to
+        #[serde(default)]
+        reply_to: Option<String>,
+        #[serde(default)]
+        idempotency_key: Option<String>,
     },
     Broadcast {
         from: String,
@@ -49,6 +54,11 @@ pub enum HubCommand {
         title: String,
         assignee: String,
         priority: String,
+        /// Session creating the task, which scopes its idempotency key
+        #[serde(default)]
+        from: Option<String>,
+        #[serde(default)]
+        idempotency_key: Option<String>,
     },
     ListTasks,
     UpdateTask {
@@ -271,10 +281,25 @@ impl HubServer {
                 Err(e) => HubResponse::error(&e),
             },
 
-            HubCommand::Tell { from, to, message } => match hub_guard.tell(&from, &to, &message) {
-                Ok(()) => HubResponse::ok("Message sent"),
-                Err(e) => HubResponse::error(&e),
-            },
+            HubCommand::Tell {
+                from,
+                to,
+                message,
+                reply_to,
+                idempotency_key,
+            } => {
+                let idempotency_key = idempotency_key.as_deref();
+                let sent = match &reply_to {
+                    Some(parent_id) => {
+                        hub_guard.reply(&from, &to, &message, parent_id, idempotency_key)
+                    }
+                    None => hub_guard.tell_idempotent(&from, &to, &message, idempotency_key),
+                };
+                match sent {
+                    Ok(()) => HubResponse::ok("Message sent"),
+                    Err(e) => HubResponse::error(&e),
+                }
+            }
 
             HubCommand::Broadcast { from, message } => match hub_guard.broadcast(&from, &message) {
                 Ok(()) => HubResponse::ok("Broadcast sent"),
@@ -307,6 +332,8 @@ impl HubServer {
                 title,
                 assignee,
                 priority,
+                from,
+                idempotency_key,
             } => {
                 let task_priority = match priority.to_lowercase().as_str() {
                     "low" => TaskPriority::Low,
@@ -315,7 +342,13 @@ impl HubServer {
                     "critical" => TaskPriority::Critical,
                     _ => TaskPriority::Medium,
                 };
-                match hub_guard.create_task(&title, &assignee, task_priority) {
+                match hub_guard.create_task_idempotent(
+                    from.as_deref(),
+                    &title,
+                    &assignee,
+                    task_priority,
+                    idempotency_key.as_deref(),
+                ) {
                     Ok(task) => HubResponse::ok_with_data(
                         "Task created",
                         serde_json::json!({ "task_id": task.id, "title": task.title }),
@@ -483,10 +516,41 @@ impl HubClient {
 
     /// Send a message
     pub fn tell(&self, from: &str, to: &str, message: &str) -> Result<HubResponse, String> {
+        self.tell_idempotent(from, to, message, None)
+    }
+
+    /// Send a message that is delivered at most once per idempotency key
+    pub fn tell_idempotent(
+        &self,
+        from: &str,
+        to: &str,
+        message: &str,
+        idempotency_key: Option<&str>,
+    ) -> Result<HubResponse, String> {
+        self.send(HubCommand::Tell {
+            from: from.to_string(),
+            to: to.to_string(),
+            message: message.to_string(),
+            reply_to: None,
+            idempotency_key: idempotency_key.map(str::to_string),
+        })
+    }
+
+    /// Reply to a message, delivered at most once per idempotency key
+    pub fn reply(
+        &self,
+        from: &str,
+        to: &str,
+        message: &str,
+        parent_id: &str,
+        idempotency_key: Option<&str>,
+    ) -> Result<HubResponse, String> {
         self.send(HubCommand::Tell {
             from: from.to_string(),
             to: to.to_string(),
             message: message.to_string(),
+            reply_to: Some(parent_id.to_string()),
+            idempotency_key: idempotency_key.map(str::to_string),
         })
     }
 
@@ -504,11 +568,25 @@ impl HubClient {
         title: &str,
         assignee: &str,
         priority: &str,
+    ) -> Result<HubResponse, String> {
+        self.create_task_idempotent(None, title, assignee, priority, None)
+    }
+
+    /// Create a task at most once per idempotency key from `from`
+    pub fn create_task_idempotent(
+        &self,
+        from: Option<&str>,
+        title: &str,
+        assignee: &str,
+        priority: &str,
+        idempotency_key: Option<&str>,
     ) -> Result<HubResponse, String> {
         self.send(HubCommand::CreateTask {
             title: title.to_string(),
             assignee: assignee.to_string(),
             priority: priority.to_string(),
+            from: from.map(str::to_string),
+            idempotency_key: idempotency_key.map(str::to_string),
         })
     }
 
@@ -561,6 +639,20 @@ mod tests {
         assert!(json.contains("Ping"));
     }
 
+    #[test]
+    fn test_idempotency_key_is_optional_on_the_wire() {
+        let cmd: HubCommand =
+            serde_json::from_str(r#"{"Tell":{"from":"a","to":"b","message":"hi"}}"#)
+                .expect("deserialization failed");
+        assert!(matches!(
+            cmd,
+            HubCommand::Tell {
+                idempotency_key: None,
+                ..
+            }
+        ));
+    }
+
     #[test]
     fn test_hub_client_creation() {
         let config = HubConfig::new();
@@ -3,21 +3,128 @@
 //! CRDT-powered shared state for real-time synchronization
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::PathBuf;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use super::HubConfig;
-use crate::sync::CRDT;
+use crate::sync::{write_atomic, CRDT};
+
+/// Shared state field holding the file a session is working on
+pub const FIELD_WORKING_ON: &str = "working_on";
+/// Shared state field holding a session's status
+pub const FIELD_STATUS: &str = "status";
+/// Shared state field holding whether a session is active
+pub const FIELD_ACTIVE: &str = "active";
+
+/// One field of a session's shared state; a `None` value records that the
+/// field was cleared, so the clear wins over older writes on other hubs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StateEntry {
+    pub value: Option<serde_json::Value>,
+    /// Milliseconds since the epoch, kept ahead of every entry the writer
+    /// has seen
+    pub timestamp: u64,
+    /// Replica that wrote the entry
+    pub author: String,
+}
 
-/// Shared state entry
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl StateEntry {
+    /// Later timestamp wins, then the greater author, then the greater value,
+    /// so every hub picks the same winner
+    fn wins_over(&self, other: &StateEntry) -> bool {
+        let value = |entry: &StateEntry| entry.value.as_ref().map(|v| v.to_string());
+        (self.timestamp, &self.author, value(self)) > (other.timestamp, &other.author, value(other))
+    }
+}
+
+/// Last-writer-wins map from `(session_id, field)` to a value, merged
+/// between federated hubs
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SharedState {
-    pub key: String,
-    pub value: serde_json::Value,
-    pub author: String,
-    pub timestamp: u64,
+    replica: String,
+    sessions: BTreeMap<String, BTreeMap<String, StateEntry>>,
+    /// Highest timestamp written or merged
+    clock: u64,
+}
+
+impl SharedState {
+    pub fn new(replica: &str) -> Self {
+        Self {
+            replica: replica.to_string(),
+            sessions: BTreeMap::new(),
+            clock: 0,
+        }
+    }
+
+    pub fn replica(&self) -> &str {
+        &self.replica
+    }
+
+    pub fn set(&mut self, session_id: &str, field: &str, value: serde_json::Value) {
+        self.write(session_id, field, Some(value));
+    }
+
+    pub fn clear(&mut self, session_id: &str, field: &str) {
+        self.write(session_id, field, None);
+    }
+
+    fn write(&mut self, session_id: &str, field: &str, value: Option<serde_json::Value>) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        // Stay ahead of merged entries even if this hub's clock lags
+        self.clock = now.max(self.clock + 1);
+        self.sessions
+            .entry(session_id.to_string())
+            .or_default()
+            .insert(
+                field.to_string(),
+                StateEntry {
+                    value,
+                    timestamp: self.clock,
+                    author: self.replica.clone(),
+                },
+            );
+    }
+
+    pub fn get(&self, session_id: &str, field: &str) -> Option<&serde_json::Value> {
+        self.entry(session_id, field)
+            .and_then(|entry| entry.value.as_ref())
+    }
+
+    pub fn entry(&self, session_id: &str, field: &str) -> Option<&StateEntry> {
+        self.sessions.get(session_id)?.get(field)
+    }
+
+    /// Every entry, cleared fields included, by session and field
+    pub fn entries(&self) -> &BTreeMap<String, BTreeMap<String, StateEntry>> {
+        &self.sessions
+    }
+
+    pub fn session_ids(&self) -> impl Iterator<Item = &String> {
+        self.sessions.keys()
+    }
+
+    /// Take every entry from `other` that wins over the local one. Merging
+    /// is commutative, associative and idempotent, so hubs that exchange
+    /// state in any order converge.
+    pub fn merge(&mut self, other: &SharedState) {
+        for (session_id, fields) in &other.sessions {
+            let local = self.sessions.entry(session_id.clone()).or_default();
+            for (field, entry) in fields {
+                let newer = local
+                    .get(field)
+                    .map_or(true, |current| entry.wins_over(current));
+                if newer {
+                    local.insert(field.clone(), entry.clone());
+                }
+            }
+        }
+        self.clock = self.clock.max(other.clock);
+    }
 }
 
 /// Session working state
@@ -36,6 +143,8 @@ struct HubStateData {
     version: String,
     states: HashMap<String, serde_json::Value>,
     session_states: HashMap<String, SessionWorkState>,
+    #[serde(default)]
+    shared: SharedState,
     last_updated: u64,
 }
 
@@ -43,6 +152,7 @@ struct HubStateData {
 pub struct HubState {
     crdt: CRDT,
     session_states: HashMap<String, SessionWorkState>,
+    shared: SharedState,
     state_file: PathBuf,
 }
 
@@ -54,6 +164,7 @@ impl HubState {
         Self {
             crdt: CRDT::new(&author_id),
             session_states: HashMap::new(),
+            shared: SharedState::new(&author_id),
             state_file: config.state_file.clone(),
         }
     }
@@ -93,6 +204,9 @@ impl HubState {
 
         state.active = active;
         state.last_update = now;
+
+        self.shared
+            .set(session_id, FIELD_ACTIVE, serde_json::json!(active));
     }
 
     /// Set what a session is working on
@@ -117,14 +231,10 @@ impl HubState {
         state.status = "active".to_string();
         state.last_update = now;
 
-        // Also store in CRDT for sync
-        self.crdt.set(
-            &format!("working:{}", session_id),
-            serde_json::json!({
-                "file": file_path,
-                "timestamp": now
-            }),
-        );
+        self.shared
+            .set(session_id, FIELD_WORKING_ON, serde_json::json!(file_path));
+        self.shared
+            .set(session_id, FIELD_STATUS, serde_json::json!("active"));
     }
 
     /// Clear working state for a session
@@ -138,7 +248,49 @@ impl HubState {
                 .unwrap_or(0);
         }
 
-        self.crdt.delete(&format!("working:{}", session_id));
+        self.shared.clear(session_id, FIELD_WORKING_ON);
+        self.shared
+            .set(session_id, FIELD_STATUS, serde_json::json!("idle"));
+    }
+
+    /// Shared session state to send to federated hubs
+    pub fn shared_state(&self) -> &SharedState {
+        &self.shared
+    }
+
+    /// Merge a federated hub's shared state and update the session work
+    /// states it covers
+    pub fn merge_shared(&mut self, other: &SharedState) {
+        self.shared.merge(other);
+        self.refresh_session_states();
+    }
+
+    fn refresh_session_states(&mut self) {
+        for (session_id, fields) in self.shared.entries() {
+            let value = |field: &str| fields.get(field).and_then(|e| e.value.as_ref());
+            let last_update = fields.values().map(|e| e.timestamp / 1000).max();
+
+            let state = self
+                .session_states
+                .entry(session_id.clone())
+                .or_insert_with(|| SessionWorkState {
+                    session_id: session_id.clone(),
+                    working_on: None,
+                    status: "idle".to_string(),
+                    last_update: 0,
+                    active: false,
+                });
+            state.working_on = value(FIELD_WORKING_ON)
+                .and_then(|v| v.as_str())
+                .map(str::to_string);
+            if let Some(status) = value(FIELD_STATUS).and_then(|v| v.as_str()) {
+                state.status = status.to_string();
+            }
+            if let Some(active) = value(FIELD_ACTIVE).and_then(|v| v.as_bool()) {
+                state.active = active;
+            }
+            state.last_update = state.last_update.max(last_update.unwrap_or(0));
+        }
     }
 
     /// Get session work state
@@ -195,6 +347,7 @@ impl HubState {
             version: crate::VERSION.to_string(),
             states: self.crdt.get_all(),
             session_states: self.session_states.clone(),
+            shared: self.shared.clone(),
             last_updated: SystemTime::now()
                 .duration_since(UNIX_EPOCH)
                 .map(|d| d.as_secs())
@@ -204,7 +357,8 @@ impl HubState {
         let json = serde_json::to_string_pretty(&data)
             .map_err(|e| format!("Cannot serialize state: {}", e))?;
 
-        fs::write(&self.state_file, json).map_err(|e| format!("Cannot write state file: {}", e))?;
+        write_atomic(&self.state_file, json)
+            .map_err(|e| format!("Cannot write state file: {}", e))?;
 
         Ok(())
     }
@@ -228,6 +382,7 @@ impl HubState {
 
         // Restore session states
         self.session_states = data.session_states;
+        self.shared.merge(&data.shared);
 
         Ok(())
     }
@@ -236,6 +391,8 @@ impl HubState {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
 
     #[test]
     fn test_hub_state_creation() {
@@ -263,4 +420,117 @@ mod tests {
         let sessions = state.who_is_working_on("src/main.rs");
         assert!(sessions.contains(&"session-1".to_string()));
     }
+
+    fn entry(timestamp: u64, author: &str, value: Option<serde_json::Value>) -> StateEntry {
+        StateEntry {
+            value,
+            timestamp,
+            author: author.to_string(),
+        }
+    }
+
+    /// Small key, time and author spaces so random states collide often
+    fn random_state(rng: &mut StdRng, replica: &str) -> SharedState {
+        let mut state = SharedState::new(replica);
+        for _ in 0..rng.gen_range(0..12) {
+            let session = format!("session-{}", rng.gen_range(0..3));
+            let field = [FIELD_WORKING_ON, FIELD_STATUS, FIELD_ACTIVE][rng.gen_range(0..3)];
+            let value = match rng.gen_range(0..4) {
+                0 => None,
+                n => Some(serde_json::json!(format!("src/file{}.rs", n))),
+            };
+            let author = ["hub-a", "hub-b", "hub-c"][rng.gen_range(0..3)];
+            let written = entry(rng.gen_range(0..5), author, value);
+            state.clock = state.clock.max(written.timestamp);
+            state
+                .sessions
+                .entry(session)
+                .or_default()
+                .insert(field.to_string(), written);
+        }
+        state
+    }
+
+    #[test]
+    fn test_merge_is_commutative_and_idempotent() {
+        let mut rng = StdRng::seed_from_u64(0x5e7a);
+        for _ in 0..500 {
+            let a = random_state(&mut rng, "hub-a");
+            let b = random_state(&mut rng, "hub-b");
+            let c = random_state(&mut rng, "hub-c");
+
+            let mut ab = a.clone();
+            ab.merge(&b);
+            let mut ba = b.clone();
+            ba.merge(&a);
+            assert_eq!(ab.entries(), ba.entries());
+
+            let mut twice = ab.clone();
+            twice.merge(&b);
+            twice.merge(&ab);
+            assert_eq!(twice.entries(), ab.entries());
+
+            let mut ab_c = ab.clone();
+            ab_c.merge(&c);
+            let mut bc = b.clone();
+            bc.merge(&c);
+            let mut a_bc = a.clone();
+            a_bc.merge(&bc);
+            assert_eq!(ab_c.entries(), a_bc.entries());
+        }
+    }
+
+    #[test]
+    fn test_conflicting_working_on_converges() {
+        let mut hub_a = SharedState::new("hub-a");
+        let mut hub_b = SharedState::new("hub-b");
+        hub_a
+            .sessions
+            .entry("session-1".to_string())
+            .or_default()
+            .insert(
+                FIELD_WORKING_ON.to_string(),
+                entry(100, "hub-a", Some(serde_json::json!("src/a.rs"))),
+            );
+        hub_b
+            .sessions
+            .entry("session-1".to_string())
+            .or_default()
+            .insert(
+                FIELD_WORKING_ON.to_string(),
+                entry(100, "hub-b", Some(serde_json::json!("src/b.rs"))),
+            );
+
+        let snapshot_a = hub_a.clone();
+        hub_a.merge(&hub_b);
+        hub_b.merge(&snapshot_a);
+        assert_eq!(hub_a.entries(), hub_b.entries());
+        // Same timestamp: the greater author wins on both sides
+        assert_eq!(
+            hub_a.get("session-1", FIELD_WORKING_ON),
+            Some(&serde_json::json!("src/b.rs"))
+        );
+
+        // A later clear on either hub wins over the older write
+        hub_a.clear("session-1", FIELD_WORKING_ON);
+        hub_b.merge(&hub_a);
+        assert_eq!(hub_b.get("session-1", FIELD_WORKING_ON), None);
+    }
+
+    #[test]
+    fn test_hub_state_merges_remote_working_on() {
+        let config = HubConfig::new();
+        let mut local = HubState::new(&config);
+        let mut remote = SharedState::new("hub-remote");
+        remote.set(
+            "session-9",
+            FIELD_WORKING_ON,
+            serde_json::json!("src/lib.rs"),
+        );
+        remote.set("session-9", FIELD_ACTIVE, serde_json::json!(true));
+
+        local.merge_shared(&remote);
+        assert_eq!(local.who_is_working_on("src/lib.rs"), vec!["session-9"]);
+        assert!(local.get_session_state("session-9").unwrap().active);
+    }
 }
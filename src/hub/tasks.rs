@@ -10,6 +10,7 @@ use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use super::HubConfig;
+use crate::sync::write_atomic;
 
 /// Task priority levels
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -108,6 +109,8 @@ pub struct Task {
     pub completed_at: Option<u64>,
     pub tags: Vec<String>,
     pub blockers: Vec<String>,
+    #[serde(default)]
+    pub depends_on: Vec<u64>,
 }
 
 impl Task {
@@ -137,6 +140,7 @@ impl Task {
             completed_at: None,
             tags: Vec::new(),
             blockers: Vec::new(),
+            depends_on: Vec::new(),
         }
     }
 
@@ -273,8 +277,20 @@ impl TaskBoard {
         self.get_by_status(TaskStatus::InProgress)
     }
 
-    /// Update task status
+    /// Update task status. Starting a task whose dependencies are not done
+    /// is allowed but logged as a warning.
     pub fn update_status(&mut self, id: u64, status: TaskStatus) -> Result<(), String> {
+        if status == TaskStatus::InProgress {
+            let unfinished = self.unfinished_dependencies(id);
+            if !unfinished.is_empty() {
+                log::warn!(
+                    "Task #{} started before its dependencies finished: {}",
+                    id,
+                    format_ids(&unfinished)
+                );
+            }
+        }
+
         let task = self
             .tasks
             .get_mut(&id)
@@ -328,6 +344,92 @@ impl TaskBoard {
         Ok(())
     }
 
+    /// Record that a task cannot start until another task is done. Rejects
+    /// an edge that would close a cycle, naming the tasks in it.
+    pub fn add_dependency(&mut self, id: u64, depends_on: u64) -> Result<(), String> {
+        if id == depends_on {
+            return Err(format!("Task #{} cannot depend on itself", id));
+        }
+        if !self.tasks.contains_key(&depends_on) {
+            return Err(format!("Task #{} not found", depends_on));
+        }
+        if let Some(path) = self.dependency_path(depends_on, id) {
+            let mut cycle = vec![id];
+            cycle.extend(path);
+            return Err(format!(
+                "Dependency would create a cycle: {}",
+                cycle
+                    .iter()
+                    .map(|id| format!("#{}", id))
+                    .collect::<Vec<_>>()
+                    .join(" -> ")
+            ));
+        }
+
+        let task = self
+            .tasks
+            .get_mut(&id)
+            .ok_or_else(|| format!("Task #{} not found", id))?;
+
+        if !task.depends_on.contains(&depends_on) {
+            task.depends_on.push(depends_on);
+            task.updated_at = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+        }
+
+        self.save()?;
+        Ok(())
+    }
+
+    /// Chain of dependency edges leading from `from` to `to`, both included
+    fn dependency_path(&self, from: u64, to: u64) -> Option<Vec<u64>> {
+        let mut stack = vec![vec![from]];
+        let mut visited = std::collections::HashSet::new();
+        while let Some(path) = stack.pop() {
+            let current = *path.last()?;
+            if current == to {
+                return Some(path);
+            }
+            if !visited.insert(current) {
+                continue;
+            }
+            for next in self.tasks.get(&current).map_or(&[][..], |t| &t.depends_on) {
+                let mut extended = path.clone();
+                extended.push(*next);
+                stack.push(extended);
+            }
+        }
+        None
+    }
+
+    /// Dependencies of a task that are not done yet. Dependencies on deleted
+    /// tasks no longer block.
+    pub fn unfinished_dependencies(&self, id: u64) -> Vec<u64> {
+        let Some(task) = self.tasks.get(&id) else {
+            return Vec::new();
+        };
+        task.depends_on
+            .iter()
+            .filter(|dep| {
+                self.tasks
+                    .get(dep)
+                    .is_some_and(|t| t.status != TaskStatus::Done)
+            })
+            .copied()
+            .collect()
+    }
+
+    /// Pending tasks whose dependencies are all done, in board order
+    pub fn ready_tasks(&self) -> Vec<Task> {
+        self.get_all()
+            .into_iter()
+            .filter(|t| t.status == TaskStatus::Pending)
+            .filter(|t| self.unfinished_dependencies(t.id).is_empty())
+            .collect()
+    }
+
     /// Delete a task
     pub fn delete(&mut self, id: u64) -> Result<(), String> {
         self.tasks
@@ -358,7 +460,8 @@ impl TaskBoard {
                 .map_err(|e| format!("Cannot create tasks directory: {}", e))?;
         }
 
-        fs::write(&self.tasks_file, json).map_err(|e| format!("Cannot write tasks file: {}", e))?;
+        write_atomic(&self.tasks_file, json)
+            .map_err(|e| format!("Cannot write tasks file: {}", e))?;
 
         Ok(())
     }
@@ -395,6 +498,13 @@ impl TaskBoard {
     }
 }
 
+fn format_ids(ids: &[u64]) -> String {
+    ids.iter()
+        .map(|id| format!("#{}", id))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -428,4 +538,41 @@ mod tests {
         assert!(task.is_ok());
         assert_eq!(board.count(), 1);
     }
+
+    #[test]
+    fn test_dependency_chain_unblocks_in_order() {
+        let mut config = HubConfig::new();
+        config.tasks_file =
+            std::env::temp_dir().join(format!("sena-tasks-{}.json", uuid::Uuid::new_v4()));
+        let mut board = TaskBoard::new(&config);
+
+        let schema = board
+            .create("Design schema", "backend", TaskPriority::High)
+            .unwrap();
+        let api = board
+            .create("Build API", "backend", TaskPriority::High)
+            .unwrap();
+        let ui = board.create("Build UI", "web", TaskPriority::High).unwrap();
+        board.add_dependency(api.id, schema.id).unwrap();
+        board.add_dependency(ui.id, api.id).unwrap();
+
+        let err = board.add_dependency(schema.id, ui.id).unwrap_err();
+        assert!(err.contains("#1 -> #3 -> #2 -> #1"), "{}", err);
+
+        let ready =
+            |board: &TaskBoard| board.ready_tasks().iter().map(|t| t.id).collect::<Vec<_>>();
+        assert_eq!(ready(&board), vec![schema.id]);
+        board.update_status(schema.id, TaskStatus::Done).unwrap();
+        assert_eq!(ready(&board), vec![api.id]);
+        assert_eq!(board.unfinished_dependencies(ui.id), vec![api.id]);
+        board.update_status(api.id, TaskStatus::Done).unwrap();
+        assert_eq!(ready(&board), vec![ui.id]);
+
+        let mut reloaded = TaskBoard::new(&config);
+        reloaded.load().unwrap();
+        assert_eq!(reloaded.get(ui.id).unwrap().depends_on, vec![api.id]);
+        assert_eq!(ready(&reloaded), vec![ui.id]);
+
+        let _ = fs::remove_file(&config.tasks_file);
+    }
 }
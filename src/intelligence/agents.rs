@@ -21,6 +21,16 @@ impl std::fmt::Display for AgentType {
 }
 
 impl AgentType {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "security" => Some(AgentType::Security),
+            "performance" => Some(AgentType::Performance),
+            "architecture" => Some(AgentType::Architecture),
+            "general" => Some(AgentType::General),
+            _ => None,
+        }
+    }
+
     pub fn description(&self) -> &'static str {
         match self {
             AgentType::Security => "Specialized in security auditing, vulnerability detection, and secure coding practices.",
@@ -31,59 +41,106 @@ impl AgentType {
     }
 
     pub fn trigger_keywords(&self) -> Vec<&'static str> {
+        self.keyword_profile().iter().map(|(k, _)| *k).collect()
+    }
+
+    /// Keywords that point to this agent and how strongly each one does
+    pub fn keyword_profile(&self) -> &'static [(&'static str, f64)] {
         match self {
-            AgentType::Security => vec![
-                "security",
-                "vulnerability",
-                "xss",
-                "sql injection",
-                "auth",
-                "authentication",
-                "authorization",
-                "csrf",
-                "owasp",
-                "hack",
-                "exploit",
-                "secure",
-                "encrypt",
-                "password",
-                "token",
-                "jwt",
+            AgentType::Security => &[
+                ("security", 0.8),
+                ("vulnerab", 1.0),
+                ("xss", 1.0),
+                ("sql injection", 1.2),
+                ("injection", 0.9),
+                ("auth", 0.6),
+                ("authentication", 0.8),
+                ("authorization", 0.8),
+                ("login", 0.5),
+                ("csrf", 1.0),
+                ("owasp", 1.0),
+                ("hack", 0.6),
+                ("exploit", 1.0),
+                ("secure", 0.6),
+                ("encrypt", 0.7),
+                ("password", 0.7),
+                ("secret", 0.6),
+                ("token", 0.5),
+                ("jwt", 0.7),
+                ("sanitiz", 0.7),
             ],
-            AgentType::Performance => vec![
-                "performance",
-                "slow",
-                "fast",
-                "optimize",
-                "latency",
-                "throughput",
-                "cache",
-                "memory",
-                "cpu",
-                "bottleneck",
-                "scalable",
-                "efficient",
-                "complexity",
-                "big o",
-                "algorithm",
+            AgentType::Performance => &[
+                ("performance", 0.8),
+                ("slow", 0.8),
+                ("fast", 0.4),
+                ("optimiz", 0.7),
+                ("latency", 0.9),
+                ("throughput", 0.9),
+                ("cache", 0.6),
+                ("memory", 0.5),
+                ("cpu", 0.7),
+                ("bottleneck", 1.0),
+                ("scalable", 0.5),
+                ("efficient", 0.6),
+                ("complexity", 0.8),
+                ("big o", 1.0),
+                ("o(n", 1.2),
+                ("o(log", 1.0),
+                ("loop", 0.4),
+                ("algorithm", 0.6),
+                ("profil", 0.7),
+                ("benchmark", 0.8),
             ],
-            AgentType::Architecture => vec![
-                "architecture",
-                "design",
-                "pattern",
-                "solid",
-                "structure",
-                "refactor",
-                "module",
-                "component",
-                "layer",
-                "microservice",
-                "monolith",
-                "dependency",
-                "coupling",
-                "cohesion",
+            AgentType::Architecture => &[
+                ("architecture", 1.0),
+                ("design", 0.5),
+                ("pattern", 0.6),
+                ("solid", 0.8),
+                ("structure", 0.5),
+                ("refactor", 0.7),
+                ("module", 0.6),
+                ("component", 0.5),
+                ("layer", 0.6),
+                ("microservice", 1.0),
+                ("monolith", 1.0),
+                ("dependency", 0.7),
+                ("coupling", 0.9),
+                ("cohesion", 0.9),
+                ("interface", 0.5),
+                ("abstraction", 0.7),
             ],
-            AgentType::General => vec![],
+            AgentType::General => &[],
+        }
+    }
+}
+
+/// Below this confidence `AgentPool::select_agent` falls back to `General`
+pub const AGENT_SELECTION_THRESHOLD: f64 = 0.35;
+
+/// Why `AgentPool::select` picked an agent
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AgentSelection {
+    pub agent: AgentType,
+    /// How clearly the task matched this agent's keywords and no other's
+    pub confidence: f64,
+    pub matched_keywords: Vec<String>,
+}
+
+impl AgentSelection {
+    pub fn describe(&self) -> String {
+        if self.matched_keywords.is_empty() {
+            format!(
+                "Selected {:?} ({:.0}%): no specialist clearly matched",
+                self.agent,
+                self.confidence * 100.0
+            )
+        } else {
+            format!(
+                "Selected {:?} ({:.0}%) for: {}",
+                self.agent,
+                self.confidence * 100.0,
+                self.matched_keywords.join(", ")
+            )
         }
     }
 }
@@ -164,6 +221,7 @@ impl Agent {
             analysis,
             recommendations: self.generate_recommendations(task),
             confidence: self.calculate_confidence(task),
+            selection: None,
         }
     }
 
@@ -324,31 +382,75 @@ impl AgentPool {
         agent.execute(task)
     }
 
+    /// Dispatch to the agent [`select`](Self::select) picks, recording why
     pub fn auto_dispatch(&self, task: &str) -> AgentResult {
-        let agent_type = self.select_best_agent(task);
-        self.dispatch(task, agent_type)
+        let selection = self.select(task);
+        let mut result = self.dispatch(task, selection.agent);
+        result.selection = Some(selection);
+        result
+    }
+
+    pub fn select_agent(&self, task: &str) -> AgentType {
+        self.select(task).agent
     }
 
     pub fn select_best_agent(&self, task: &str) -> AgentType {
-        let task_lower = task.to_lowercase();
-        let mut best_match = AgentType::General;
-        let mut best_score = 0;
+        self.select_agent(task)
+    }
 
-        for agent_type in [
+    /// Score `task` against each specialist's keyword profile. Confidence
+    /// grows with the winning score and shrinks when other agents also
+    /// match; below [`AGENT_SELECTION_THRESHOLD`] the `General` agent is used.
+    pub fn select(&self, task: &str) -> AgentSelection {
+        let task_lower = task.to_lowercase();
+        let scored: Vec<(AgentType, f64, Vec<String>)> = [
             AgentType::Security,
             AgentType::Performance,
             AgentType::Architecture,
-        ] {
-            let keywords = agent_type.trigger_keywords();
-            let score = keywords.iter().filter(|k| task_lower.contains(*k)).count();
-
-            if score > best_score {
-                best_score = score;
-                best_match = agent_type;
+        ]
+        .into_iter()
+        .map(|agent_type| {
+            let matched: Vec<(&str, f64)> = agent_type
+                .keyword_profile()
+                .iter()
+                .filter(|(keyword, _)| task_lower.contains(keyword))
+                .copied()
+                .collect();
+            let score = matched.iter().map(|(_, weight)| weight).sum();
+            let keywords = matched.iter().map(|(k, _)| k.to_string()).collect();
+            (agent_type, score, keywords)
+        })
+        .collect();
+
+        let total: f64 = scored.iter().map(|(_, score, _)| score).sum();
+        let best = scored
+            .into_iter()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .filter(|(_, score, _)| *score > 0.0);
+
+        match best {
+            Some((agent, score, matched_keywords)) => {
+                let confidence = (1.0 - (-score).exp()) * (score / total);
+                if confidence >= AGENT_SELECTION_THRESHOLD {
+                    AgentSelection {
+                        agent,
+                        confidence,
+                        matched_keywords,
+                    }
+                } else {
+                    AgentSelection {
+                        agent: AgentType::General,
+                        confidence: 1.0 - confidence,
+                        matched_keywords: Vec::new(),
+                    }
+                }
             }
+            None => AgentSelection {
+                agent: AgentType::General,
+                confidence: 1.0,
+                matched_keywords: Vec::new(),
+            },
         }
-
-        best_match
     }
 
     pub fn list_agents(&self) -> Vec<&Agent> {
@@ -369,6 +471,9 @@ pub struct AgentResult {
     pub analysis: String,
     pub recommendations: Vec<String>,
     pub confidence: f64,
+    /// Set when the agent was chosen automatically
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selection: Option<AgentSelection>,
 }
 
 impl AgentResult {
@@ -390,6 +495,9 @@ impl AgentResult {
         }
 
         output.push_str(&format!("\nConfidence: {:.0}%\n", self.confidence * 100.0));
+        if let Some(selection) = &self.selection {
+            output.push_str(&format!("{}\n", selection.describe()));
+        }
 
         output
     }
@@ -427,6 +535,37 @@ mod tests {
         assert_eq!(agent_type, AgentType::Performance);
     }
 
+    #[test]
+    fn test_select_agent_by_weighted_keywords() {
+        let pool = AgentPool::new();
+        assert_eq!(
+            pool.select_agent("SQL injection in login"),
+            AgentType::Security
+        );
+        assert_eq!(
+            pool.select_agent("reduce O(n^2) loop"),
+            AgentType::Performance
+        );
+
+        let selection = pool.select("SQL injection in login");
+        assert!(selection.confidence >= AGENT_SELECTION_THRESHOLD);
+        assert!(selection
+            .matched_keywords
+            .contains(&"sql injection".to_string()));
+    }
+
+    #[test]
+    fn test_select_agent_falls_back_to_general() {
+        let pool = AgentPool::new();
+        assert_eq!(pool.select_agent("Write release notes"), AgentType::General);
+        // A weak match split across specialists is not confident enough
+        assert_eq!(pool.select_agent("a fast login"), AgentType::General);
+
+        let result = pool.auto_dispatch("Find the bottleneck in checkout latency");
+        assert_eq!(result.agent, AgentType::Performance);
+        assert_eq!(result.selection.unwrap().agent, AgentType::Performance);
+    }
+
     #[test]
     fn test_auto_dispatch_architecture() {
         let pool = AgentPool::new();
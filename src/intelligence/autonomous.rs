@@ -1,4 +1,6 @@
+use crate::guardian::GuardianMiddleware;
 use crate::memory::{MemoryEntry, MemoryType, PersistentMemory};
+use crate::sync::write_atomic;
 use crate::tools::{ToolCall, ToolSystem};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -18,6 +20,8 @@ pub enum AutonomousError {
     PlanningError(String),
     #[error("Memory error: {0}")]
     MemoryError(String),
+    #[error("Checkpoint error: {0}")]
+    CheckpointError(String),
 }
 
 pub type AutonomousResult<T> = Result<T, AutonomousError>;
@@ -42,6 +46,16 @@ pub struct AgentStep {
     pub success: bool,
     pub timestamp: DateTime<Utc>,
     pub duration_ms: u64,
+    #[serde(default)]
+    pub guardian_verdict: Option<GuardianVerdict>,
+}
+
+/// Guardian decision recorded for a step's tool call
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GuardianVerdict {
+    pub allowed: bool,
+    pub reason: Option<String>,
+    pub risk_score: f64,
 }
 
 impl AgentStep {
@@ -55,6 +69,7 @@ impl AgentStep {
             success: false,
             timestamp: Utc::now(),
             duration_ms: 0,
+            guardian_verdict: None,
         }
     }
 
@@ -127,11 +142,73 @@ impl AgentExecution {
     pub fn successful_steps(&self) -> usize {
         self.steps.iter().filter(|s| s.success).count()
     }
+
+    /// Number of leading steps that succeeded; a resumed run continues after these
+    pub fn completed_prefix(&self) -> usize {
+        self.steps.iter().take_while(|s| s.success).count()
+    }
+
+    /// Planned steps that have not yet completed successfully
+    pub fn remaining_steps(&self) -> usize {
+        self.plan.as_ref().map_or(0, |plan| {
+            plan.steps.len().saturating_sub(self.completed_prefix())
+        })
+    }
+}
+
+/// Persists autonomous runs to `~/.sena/auto/<run_id>.json` after every step
+#[derive(Debug, Clone)]
+pub struct CheckpointStore {
+    dir: PathBuf,
+}
+
+impl CheckpointStore {
+    pub fn new() -> Self {
+        let dir = dirs::home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(".sena")
+            .join("auto");
+        Self::with_dir(dir)
+    }
+
+    pub fn with_dir(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    pub fn path_for(&self, run_id: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", run_id))
+    }
+
+    pub fn save(&self, execution: &AgentExecution) -> AutonomousResult<()> {
+        std::fs::create_dir_all(&self.dir)
+            .map_err(|e| AutonomousError::CheckpointError(e.to_string()))?;
+        let json = serde_json::to_string_pretty(execution)
+            .map_err(|e| AutonomousError::CheckpointError(e.to_string()))?;
+        write_atomic(self.path_for(&execution.id), json)
+            .map_err(|e| AutonomousError::CheckpointError(e.to_string()))
+    }
+
+    pub fn load(&self, run_id: &str) -> AutonomousResult<AgentExecution> {
+        let path = self.path_for(run_id);
+        let content = std::fs::read_to_string(&path).map_err(|e| {
+            AutonomousError::CheckpointError(format!("Cannot read run {}: {}", run_id, e))
+        })?;
+        serde_json::from_str(&content)
+            .map_err(|e| AutonomousError::CheckpointError(format!("Invalid run {}: {}", run_id, e)))
+    }
+}
+
+impl Default for CheckpointStore {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 pub struct AutonomousAgent {
     tool_system: ToolSystem,
     memory: Option<PersistentMemory>,
+    guardian: GuardianMiddleware,
+    checkpoints: CheckpointStore,
     current_execution: Option<AgentExecution>,
 }
 
@@ -141,6 +218,8 @@ impl AutonomousAgent {
         Self {
             tool_system: ToolSystem::new(),
             memory,
+            guardian: GuardianMiddleware::new(),
+            checkpoints: CheckpointStore::new(),
             current_execution: None,
         }
     }
@@ -150,6 +229,11 @@ impl AutonomousAgent {
         self
     }
 
+    pub fn with_checkpoints(mut self, checkpoints: CheckpointStore) -> Self {
+        self.checkpoints = checkpoints;
+        self
+    }
+
     pub async fn execute(
         &mut self,
         task: &str,
@@ -182,12 +266,62 @@ impl AutonomousAgent {
         };
 
         let plan = self.create_plan(task, &working_dir)?;
-        execution.plan = Some(plan.clone());
+        execution.plan = Some(plan);
+
+        self.run_steps(execution).await
+    }
+
+    /// Continue a checkpointed run from its last successful step. Failed and
+    /// not-yet-run steps are re-validated by the guardian before running.
+    pub async fn resume(
+        &mut self,
+        run_id: &str,
+        max_steps: Option<usize>,
+    ) -> AutonomousResult<AgentExecution> {
+        let mut execution = self.checkpoints.load(run_id)?;
+        if execution.plan.is_none() {
+            return Err(AutonomousError::PlanningError(format!(
+                "Run {} has no plan to resume",
+                run_id
+            )));
+        }
+        if execution.remaining_steps() == 0 {
+            return Err(AutonomousError::TaskFailed(format!(
+                "Run {} has no remaining steps",
+                run_id
+            )));
+        }
+
+        if let Some(max_steps) = max_steps {
+            execution.max_steps = max_steps;
+        }
+        execution.steps.truncate(execution.completed_prefix());
+        execution.completed_at = None;
+        execution.final_result = None;
+
+        self.run_steps(execution).await
+    }
+
+    async fn run_steps(
+        &mut self,
+        mut execution: AgentExecution,
+    ) -> AutonomousResult<AgentExecution> {
+        let working_dir = execution.working_dir.clone();
+        if self.tool_system.sandbox_root().is_some() {
+            self.tool_system.set_root(Some(working_dir.clone()));
+        }
+
+        let plan = execution
+            .plan
+            .clone()
+            .ok_or_else(|| AutonomousError::PlanningError("No plan".to_string()))?;
 
         execution.state = AgentState::Executing;
+        self.checkpoints.save(&execution)?;
 
-        for (idx, planned_step) in plan.steps.iter().enumerate() {
-            if execution.steps.len() >= max_steps {
+        let start = execution.steps.len();
+        for (idx, planned_step) in plan.steps.iter().enumerate().skip(start) {
+            if execution.steps.len() >= execution.max_steps {
                 execution.state = AgentState::Completed;
                 execution.final_result = Some(format!(
                     "Completed {} of {} planned steps (max steps reached)",
@@ -197,31 +331,41 @@ impl AutonomousAgent {
                 break;
             }
 
-            let step_result = self
+            let step = match self
                 .execute_step(idx + 1, planned_step, &execution.context, &working_dir)
-                .await;
-
-            match step_result {
-                Ok(step) => {
-                    if let Some(result) = &step.result {
-                        execution
-                            .context
-                            .insert(format!("step_{}_result", idx + 1), result.clone());
-                    }
-                    execution.steps.push(step);
-                }
-                Err(e) => {
-                    let failed_step = AgentStep::new(idx + 1, &planned_step.description).complete(
-                        &format!("Error: {}", e),
-                        false,
-                        0,
-                    );
-                    execution.steps.push(failed_step);
-                }
+                .await
+            {
+                Ok(step) => step,
+                Err(e) => AgentStep::new(idx + 1, &planned_step.description).complete(
+                    &format!("Error: {}", e),
+                    false,
+                    0,
+                ),
+            };
+
+            if let Some(result) = step.result.as_ref().filter(|_| step.success) {
+                execution
+                    .context
+                    .insert(format!("step_{}_result", idx + 1), result.clone());
+            }
+
+            let failed = !step.success;
+            execution.steps.push(step);
+            self.checkpoints.save(&execution)?;
+
+            if failed {
+                execution.state = AgentState::Failed;
+                execution.final_result = Some(format!(
+                    "Stopped at step {} of {}; resume with `sena auto resume {}`",
+                    idx + 1,
+                    plan.steps.len(),
+                    execution.id
+                ));
+                break;
             }
         }
 
-        if execution.state != AgentState::Completed {
+        if execution.state == AgentState::Executing {
             execution.state = AgentState::Completed;
             execution.final_result = Some(format!(
                 "Completed {} steps successfully",
@@ -230,11 +374,12 @@ impl AutonomousAgent {
         }
 
         execution.completed_at = Some(Utc::now());
+        self.checkpoints.save(&execution)?;
 
         if let Some(ref mut memory) = self.memory {
             let summary = format!(
                 "Task: {} | Steps: {} | Success: {}",
-                task,
+                execution.task,
                 execution.steps_taken(),
                 execution.successful_steps()
             );
@@ -361,6 +506,25 @@ impl AutonomousAgent {
 
         step = step.with_tool(&tool_name, params.clone());
 
+        let validation = self
+            .guardian
+            .validate_command(&guardian_subject(&tool_name, &params));
+        step.guardian_verdict = Some(GuardianVerdict {
+            allowed: validation.allowed,
+            reason: validation.reason.clone(),
+            risk_score: validation.risk_score,
+        });
+        if !validation.allowed {
+            let reason = validation
+                .reason
+                .unwrap_or_else(|| "blocked by guardian".to_string());
+            return Ok(step.complete(
+                &format!("Blocked by guardian: {}", reason),
+                false,
+                start.elapsed().as_millis() as u64,
+            ));
+        }
+
         let call = ToolCall::new(&tool_name, params);
         let response = self.tool_system.execute(call).await;
 
@@ -461,6 +625,15 @@ impl Default for AutonomousAgent {
     }
 }
 
+/// Command line the guardian validates for a tool call
+fn guardian_subject(tool_name: &str, params: &HashMap<String, serde_json::Value>) -> String {
+    let arg = |key: &str| params.get(key).and_then(|v| v.as_str()).unwrap_or_default();
+    match tool_name {
+        "shell_exec" => arg("command").to_string(),
+        _ => format!("{} {}", tool_name, arg("path")).trim().to_string(),
+    }
+}
+
 fn extract_file_hint(text: &str) -> Option<String> {
     let file_patterns = [
         "readme",
@@ -571,6 +744,44 @@ mod tests {
         assert!(agent.current_state().is_none());
     }
 
+    #[tokio::test]
+    async fn test_resume_continues_after_completed_steps() {
+        let root = std::env::temp_dir().join(format!("sena-auto-{}", uuid::Uuid::new_v4()));
+        let work = root.join("work");
+        std::fs::create_dir_all(&work).unwrap();
+        std::fs::write(work.join("notes.txt"), "TODO: resume me\n").unwrap();
+        let checkpoints = CheckpointStore::with_dir(root.join("runs"));
+        let agent_for = |checkpoints: &CheckpointStore| {
+            AutonomousAgent::new()
+                .with_memory(PersistentMemory::with_dir(root.join("memory")).unwrap())
+                .with_checkpoints(checkpoints.clone())
+        };
+
+        let first = agent_for(&checkpoints)
+            .execute("list files, search for TODO and analyze", work, 2, false)
+            .await
+            .unwrap();
+        assert_eq!(first.steps.len(), 2);
+        assert_eq!(first.remaining_steps(), 1);
+        assert!(first
+            .steps
+            .iter()
+            .all(|s| s.guardian_verdict.as_ref().is_some_and(|v| v.allowed)));
+        assert_eq!(checkpoints.load(&first.id).unwrap().steps.len(), 2);
+
+        let mut agent = agent_for(&checkpoints);
+        let resumed = agent.resume(&first.id, Some(10)).await.unwrap();
+        assert_eq!(resumed.state, AgentState::Completed);
+        assert_eq!(resumed.steps.len(), 3);
+        assert_eq!(resumed.steps[0].timestamp, first.steps[0].timestamp);
+        assert_eq!(resumed.steps[1].timestamp, first.steps[1].timestamp);
+        assert_eq!(resumed.steps[2].step_number, 3);
+        assert_eq!(resumed.remaining_steps(), 0);
+        assert!(agent.resume(&first.id, None).await.is_err());
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+
     #[tokio::test]
     async fn test_agent_plan_creation() {
         let agent = AutonomousAgent::new();
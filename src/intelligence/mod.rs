@@ -1,13 +1,23 @@
 mod agents;
 pub mod autonomous;
 mod routing;
+mod selection;
 mod skills;
 mod thinking;
 
-pub use agents::{Agent, AgentPool, AgentResult, AgentType};
-pub use autonomous::{AgentExecution, AgentState, AgentStep, AutonomousAgent, AutonomousError};
-pub use routing::{ModelRouter, ModelType, RoutingDecision};
-pub use skills::{Skill, SkillExecution, SkillRegistry};
+pub use agents::{
+    Agent, AgentPool, AgentResult, AgentSelection, AgentType, AGENT_SELECTION_THRESHOLD,
+};
+pub use autonomous::{
+    AgentExecution, AgentState, AgentStep, AutonomousAgent, AutonomousError, CheckpointStore,
+    GuardianVerdict,
+};
+pub use routing::{BudgetTier, ModelRouter, ModelType, RoutingDecision, RoutingHints};
+pub use selection::{select_provider, ProviderRoute, TaskKind};
+pub use skills::{
+    Skill, SkillDefinition, SkillError, SkillExecution, SkillHandler, SkillRegistry, SkillResult,
+    SKILL_CONTEXT_ENV,
+};
 pub use thinking::{ThinkingDepth, ThinkingEngine, ThinkingResult};
 
 use serde::{Deserialize, Serialize};
@@ -18,6 +28,8 @@ pub struct IntelligenceSystem {
     pub agents: AgentPool,
     pub router: ModelRouter,
     pub skills: SkillRegistry,
+    auto_agent_selection: bool,
+    primary_agent: AgentType,
 }
 
 impl IntelligenceSystem {
@@ -27,9 +39,38 @@ impl IntelligenceSystem {
             agents: AgentPool::new(),
             router: ModelRouter::new(),
             skills: SkillRegistry::new(),
+            auto_agent_selection: true,
+            primary_agent: AgentType::General,
         }
     }
 
+    /// Agent selection as set by the `[intelligence]` config section, with
+    /// user-defined skills from `~/.sena/skills` and thinking grounded in the
+    /// knowledge base
+    pub fn configured() -> Self {
+        let config = &crate::config::SenaConfig::global().intelligence;
+        let mut system = Self::new();
+        system.skills = SkillRegistry::configured();
+        system.thinking =
+            ThinkingEngine::new().with_knowledge(crate::knowledge::KnowledgeSystem::new());
+        system
+            .with_auto_agent_selection(config.auto_agent_selection)
+            .with_primary_agent(
+                AgentType::parse(&config.primary_agent).unwrap_or(AgentType::General),
+            )
+    }
+
+    pub fn with_auto_agent_selection(mut self, enabled: bool) -> Self {
+        self.auto_agent_selection = enabled;
+        self
+    }
+
+    /// The agent `dispatch_auto` uses when auto-selection is off
+    pub fn with_primary_agent(mut self, agent_type: AgentType) -> Self {
+        self.primary_agent = agent_type;
+        self
+    }
+
     pub fn analyze(&self, problem: &str, depth: ThinkingDepth) -> ThinkingResult {
         self.thinking.analyze(problem, depth)
     }
@@ -38,14 +79,48 @@ impl IntelligenceSystem {
         self.agents.dispatch(task, agent_type)
     }
 
-    pub fn route(&self, task: &str) -> RoutingDecision {
-        self.router.route(task)
+    /// Dispatch to the agent whose keywords best match `task`, or to the
+    /// primary agent when auto-selection is disabled
+    pub fn dispatch_auto(&self, task: &str) -> AgentResult {
+        if self.auto_agent_selection {
+            self.agents.auto_dispatch(task)
+        } else {
+            self.dispatch(task, self.primary_agent)
+        }
+    }
+
+    pub fn route(&self, task: &str, hints: &RoutingHints) -> RoutingDecision {
+        self.router.route(task, hints)
+    }
+
+    /// Pick a configured provider and model for `task`, based on the
+    /// routing tier and the kind of task
+    pub fn route_to_provider(
+        &self,
+        task: &str,
+        router: &sena_providers::ProviderRouter,
+    ) -> Option<ProviderRoute> {
+        let decision = self
+            .router
+            .clone()
+            .with_providers(router)
+            .route(task, &RoutingHints::default());
+        select_provider(task, decision, router)
     }
 
     pub fn execute_skill(&self, skill_name: &str, context: &str) -> Option<SkillExecution> {
         self.skills.execute(skill_name, context)
     }
 
+    /// Run the skill whose triggers best match `context`
+    pub fn execute_matching_skill(&self, context: &str) -> Option<SkillExecution> {
+        self.skills.execute_matching(context)
+    }
+
+    pub fn reload_skills(&mut self) -> SkillResult<usize> {
+        self.skills.reload()
+    }
+
     pub fn status(&self) -> IntelligenceStatus {
         IntelligenceStatus {
             thinking_available: true,
@@ -81,4 +156,19 @@ mod tests {
         assert!(status.thinking_available);
         assert!(status.agent_count > 0);
     }
+
+    #[test]
+    fn test_dispatch_auto_respects_selection_setting() {
+        let task = "SQL injection in login";
+        let auto = IntelligenceSystem::new().dispatch_auto(task);
+        assert_eq!(auto.agent, AgentType::Security);
+        assert!(auto.selection.is_some());
+
+        let manual = IntelligenceSystem::new()
+            .with_auto_agent_selection(false)
+            .with_primary_agent(AgentType::Architecture)
+            .dispatch_auto(task);
+        assert_eq!(manual.agent, AgentType::Architecture);
+        assert!(manual.selection.is_none());
+    }
 }
@@ -1,5 +1,8 @@
+use sena_providers::{ModelInfo, ModelPrice, ProviderRouter};
 use serde::{Deserialize, Serialize};
 
+use super::selection::usable_providers;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ModelType {
     Fast,
@@ -33,12 +36,121 @@ impl ModelType {
             ModelType::Powerful => 5.0,
         }
     }
+
+    /// The tier a model id belongs to, judged from its name
+    pub fn of_model(model_id: &str) -> Self {
+        let id = model_id.to_lowercase();
+        let has = |markers: &[&str]| markers.iter().any(|m| id.contains(m));
+        if has(&["haiku", "mini", "nano", "flash", "small", "nemo", "lite"]) {
+            ModelType::Fast
+        } else if has(&["opus", "large", "-pro", ":70b", "deepseek-r1"]) {
+            ModelType::Powerful
+        } else {
+            ModelType::Balanced
+        }
+    }
+
+    fn rank(&self) -> i32 {
+        match self {
+            ModelType::Fast => 0,
+            ModelType::Balanced => 1,
+            ModelType::Powerful => 2,
+        }
+    }
+
+    fn cheaper(&self) -> Self {
+        match self {
+            ModelType::Powerful => ModelType::Balanced,
+            _ => ModelType::Fast,
+        }
+    }
+}
+
+/// How much a task may spend
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetTier {
+    /// Drop a tier and take the cheapest capable model
+    Low,
+    #[default]
+    Standard,
+    /// Never trade quality for cost
+    High,
+}
+
+/// What the caller knows about a task beyond its text
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RoutingHints {
+    /// Prompt size; estimated from the task text when 0
+    pub estimated_tokens: usize,
+    pub needs_vision: bool,
+    pub needs_tools: bool,
+    /// Stay off the slowest tier
+    pub latency_sensitive: bool,
+    pub budget_tier: BudgetTier,
+}
+
+impl RoutingHints {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_estimated_tokens(mut self, tokens: usize) -> Self {
+        self.estimated_tokens = tokens;
+        self
+    }
+
+    pub fn with_vision(mut self) -> Self {
+        self.needs_vision = true;
+        self
+    }
+
+    pub fn with_tools(mut self) -> Self {
+        self.needs_tools = true;
+        self
+    }
+
+    pub fn latency_sensitive(mut self) -> Self {
+        self.latency_sensitive = true;
+        self
+    }
+
+    pub fn with_budget(mut self, budget_tier: BudgetTier) -> Self {
+        self.budget_tier = budget_tier;
+        self
+    }
+}
+
+/// A model `ModelRouter` may route to
+#[derive(Debug, Clone)]
+struct RouteCandidate {
+    provider_id: String,
+    model: ModelInfo,
+    tier: ModelType,
+    /// USD per 1K prompt plus completion tokens, or a tier estimate
+    cost: f64,
+}
+
+impl RouteCandidate {
+    fn new(provider_id: &str, model: &ModelInfo) -> Self {
+        let tier = ModelType::of_model(&model.id);
+        let cost = ModelPrice::lookup(provider_id, &model.id)
+            .map(|p| p.per_1k_input + p.per_1k_output)
+            .unwrap_or(tier.cost_factor() * 0.01);
+        Self {
+            provider_id: provider_id.to_string(),
+            model: model.clone(),
+            tier,
+            cost,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct ModelRouter {
     mode: RoutingMode,
     thresholds: ComplexityThresholds,
+    candidates: Vec<RouteCandidate>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -82,6 +194,7 @@ impl ModelRouter {
         Self {
             mode: RoutingMode::Auto,
             thresholds: ComplexityThresholds::default(),
+            candidates: Vec::new(),
         }
     }
 
@@ -89,9 +202,37 @@ impl ModelRouter {
         Self {
             mode,
             thresholds: ComplexityThresholds::default(),
+            candidates: Vec::new(),
         }
     }
 
+    /// Add `models` served by `provider_id` to the routable set
+    pub fn with_models(mut self, provider_id: &str, models: &[ModelInfo]) -> Self {
+        self.candidates.extend(
+            models
+                .iter()
+                .map(|model| RouteCandidate::new(provider_id, model)),
+        );
+        self
+    }
+
+    /// Route to the models of the usable providers in `router`, default
+    /// provider first
+    pub fn with_providers(mut self, router: &ProviderRouter) -> Self {
+        self.candidates = usable_providers(router)
+            .iter()
+            .filter_map(|id| router.get_provider(id).map(|p| (id, p)))
+            .flat_map(|(id, provider)| {
+                provider
+                    .available_models()
+                    .iter()
+                    .map(|model| RouteCandidate::new(id, model))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        self
+    }
+
     pub fn current_mode(&self) -> String {
         format!("{}", self.mode)
     }
@@ -100,52 +241,144 @@ impl ModelRouter {
         self.mode = mode;
     }
 
-    pub fn route(&self, task: &str) -> RoutingDecision {
-        match self.mode {
-            RoutingMode::Fixed(model) => RoutingDecision {
-                model,
-                reason: "Fixed model selected".to_string(),
-                complexity: self.analyze_complexity(task),
-                confidence: 1.0,
-            },
-            RoutingMode::Speed => RoutingDecision {
-                model: ModelType::Fast,
-                reason: "Optimizing for speed".to_string(),
-                complexity: self.analyze_complexity(task),
-                confidence: 1.0,
-            },
-            RoutingMode::Quality => RoutingDecision {
-                model: ModelType::Powerful,
-                reason: "Optimizing for quality".to_string(),
-                complexity: self.analyze_complexity(task),
-                confidence: 1.0,
-            },
-            RoutingMode::Cost => RoutingDecision {
-                model: ModelType::Fast,
-                reason: "Optimizing for cost".to_string(),
-                complexity: self.analyze_complexity(task),
-                confidence: 1.0,
-            },
-            RoutingMode::Auto => self.auto_route(task),
+    /// Pick a tier for `task`, adjusted by `hints`, then the model from the
+    /// configured providers that fits it: vision and tool needs and the
+    /// prompt size are hard requirements, the tier and cost are preferences.
+    pub fn route(&self, task: &str, hints: &RoutingHints) -> RoutingDecision {
+        let complexity = self.analyze_complexity(task);
+        let (mut model, reason, confidence) = match self.mode {
+            RoutingMode::Fixed(model) => (model, "Fixed model selected", 1.0),
+            RoutingMode::Speed => (ModelType::Fast, "Optimizing for speed", 1.0),
+            RoutingMode::Quality => (ModelType::Powerful, "Optimizing for quality", 1.0),
+            RoutingMode::Cost => (ModelType::Fast, "Optimizing for cost", 1.0),
+            RoutingMode::Auto => {
+                let (model, reason) = if complexity < self.thresholds.fast_max {
+                    (ModelType::Fast, "Simple task - using fast model")
+                } else if complexity < self.thresholds.balanced_max {
+                    (ModelType::Balanced, "Standard task - using balanced model")
+                } else {
+                    (ModelType::Powerful, "Complex task - using powerful model")
+                };
+                (model, reason, self.calculate_confidence(complexity))
+            }
+        };
+        let mut reasons = vec![reason.to_string()];
+
+        if self.mode == RoutingMode::Auto {
+            if hints.budget_tier == BudgetTier::Low && model != ModelType::Fast {
+                model = model.cheaper();
+                reasons.push(format!("low budget, dropped to {}", model));
+            }
+            if hints.latency_sensitive && model == ModelType::Powerful {
+                model = ModelType::Balanced;
+                reasons.push("latency sensitive, avoiding the slowest tier".to_string());
+            }
         }
+
+        let mut decision = RoutingDecision {
+            model,
+            reason: String::new(),
+            complexity,
+            confidence,
+            provider_id: None,
+            model_id: None,
+            fallback: false,
+        };
+        self.choose_model(task, hints, &mut decision, &mut reasons);
+        decision.reason = reasons.join("; ");
+        decision
     }
 
-    fn auto_route(&self, task: &str) -> RoutingDecision {
-        let complexity = self.analyze_complexity(task);
+    fn choose_model(
+        &self,
+        task: &str,
+        hints: &RoutingHints,
+        decision: &mut RoutingDecision,
+        reasons: &mut Vec<String>,
+    ) {
+        if self.candidates.is_empty() {
+            return;
+        }
 
-        let (model, reason) = if complexity < self.thresholds.fast_max {
-            (ModelType::Fast, "Simple task - using fast model")
-        } else if complexity < self.thresholds.balanced_max {
-            (ModelType::Balanced, "Standard task - using balanced model")
+        let tokens = if hints.estimated_tokens > 0 {
+            hints.estimated_tokens
         } else {
-            (ModelType::Powerful, "Complex task - using powerful model")
+            task.len() / 4
         };
+        let fits =
+            |c: &RouteCandidate| c.model.context_length == 0 || c.model.context_length >= tokens;
+        let capable: Vec<&RouteCandidate> = self
+            .candidates
+            .iter()
+            .filter(|c| !hints.needs_vision || c.model.supports_vision)
+            .filter(|c| !hints.needs_tools || c.model.supports_tools)
+            .filter(|c| fits(c))
+            .collect();
+
+        let mut needs = Vec::new();
+        if hints.needs_vision {
+            needs.push("vision");
+        }
+        if hints.needs_tools {
+            needs.push("tools");
+        }
+        if !self.candidates.iter().all(fits) {
+            needs.push("context length");
+        }
 
-        RoutingDecision {
-            model,
-            reason: reason.to_string(),
-            complexity,
-            confidence: self.calculate_confidence(complexity),
+        // Closest tier first; a low budget breaks ties toward cheaper
+        // models, otherwise toward more capable ones. Within a tier a low
+        // budget picks the cheapest model, otherwise provider order holds.
+        let wanted = decision.model.rank();
+        let low_budget = hints.budget_tier == BudgetTier::Low || self.mode == RoutingMode::Cost;
+        let best = capable.iter().enumerate().min_by(|(ia, a), (ib, b)| {
+            let distance = |c: &RouteCandidate| (c.tier.rank() - wanted).abs();
+            let direction = |c: &RouteCandidate| {
+                if low_budget {
+                    c.tier.rank()
+                } else {
+                    -c.tier.rank()
+                }
+            };
+            distance(a)
+                .cmp(&distance(b))
+                .then(direction(a).cmp(&direction(b)))
+                .then(if low_budget {
+                    a.cost.total_cmp(&b.cost)
+                } else {
+                    std::cmp::Ordering::Equal
+                })
+                .then(ia.cmp(ib))
+        });
+
+        match best {
+            Some((_, candidate)) => {
+                decision.provider_id = Some(candidate.provider_id.clone());
+                decision.model_id = Some(candidate.model.id.clone());
+                if candidate.tier != decision.model {
+                    decision.fallback = true;
+                    reasons.push(format!(
+                        "no {} model{} configured, using {} tier",
+                        decision.model,
+                        if needs.is_empty() {
+                            String::new()
+                        } else {
+                            format!(" with {}", needs.join(" and "))
+                        },
+                        candidate.tier
+                    ));
+                } else if !needs.is_empty() {
+                    reasons.push(format!("requires {}", needs.join(" and ")));
+                }
+                reasons.push(format!("{}/{}", candidate.provider_id, candidate.model.id));
+            }
+            None => {
+                decision.fallback = true;
+                reasons.push(format!(
+                    "no configured model supports {}",
+                    needs.join(" and ")
+                ));
+            }
         }
     }
 
@@ -219,20 +452,34 @@ impl Default for ModelRouter {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RoutingDecision {
     pub model: ModelType,
+    /// Why this tier and model were chosen
     pub reason: String,
     pub complexity: f64,
     pub confidence: f64,
+    /// The configured provider to use; `None` when the router has no
+    /// providers or none can serve the task
+    #[serde(default)]
+    pub provider_id: Option<String>,
+    #[serde(default)]
+    pub model_id: Option<String>,
+    /// The chosen model is outside the preferred tier, or none was found
+    #[serde(default)]
+    pub fallback: bool,
 }
 
 impl RoutingDecision {
     pub fn format(&self) -> String {
-        format!(
-            "Model: {}\nReason: {}\nComplexity: {:.0}%\nConfidence: {:.0}%",
-            self.model,
+        let mut output = format!("Model: {}\n", self.model);
+        if let (Some(provider), Some(model)) = (&self.provider_id, &self.model_id) {
+            output.push_str(&format!("Provider: {}/{}\n", provider, model));
+        }
+        output.push_str(&format!(
+            "Reason: {}\nComplexity: {:.0}%\nConfidence: {:.0}%",
             self.reason,
             self.complexity * 100.0,
             self.confidence * 100.0
-        )
+        ));
+        output
     }
 }
 
@@ -249,7 +496,7 @@ mod tests {
     #[test]
     fn test_simple_task_routing() {
         let router = ModelRouter::new();
-        let decision = router.route("Hello world");
+        let decision = router.route("Hello world", &RoutingHints::default());
         assert_eq!(decision.model, ModelType::Fast);
     }
 
@@ -260,6 +507,7 @@ mod tests {
             "Analyze the security vulnerabilities in this complex authentication system \
             and explain why the current design has performance issues. Also review \
             the architecture for SOLID principle compliance.",
+            &RoutingHints::default(),
         );
         assert_eq!(decision.model, ModelType::Powerful);
     }
@@ -267,7 +515,84 @@ mod tests {
     #[test]
     fn test_fixed_mode() {
         let router = ModelRouter::with_mode(RoutingMode::Fixed(ModelType::Balanced));
-        let decision = router.route("Any task");
+        let decision = router.route("Any task", &RoutingHints::default());
         assert_eq!(decision.model, ModelType::Balanced);
     }
+
+    fn providers(ids: &[&str]) -> ProviderRouter {
+        use sena_providers::config::{ProviderConfig, ProvidersConfig};
+
+        let mut config = ProvidersConfig::new();
+        for id in ids {
+            config.add_provider(ProviderConfig::new(*id).with_api_key("test-key"));
+        }
+        ProviderRouter::from_config(&config).unwrap()
+    }
+
+    fn model(id: &str, vision: bool) -> ModelInfo {
+        ModelInfo {
+            id: id.to_string(),
+            name: id.to_string(),
+            provider: "local".to_string(),
+            context_length: 32_000,
+            supports_vision: vision,
+            supports_tools: true,
+            supports_streaming: true,
+            price_per_1k_input: None,
+            price_per_1k_output: None,
+        }
+    }
+
+    #[test]
+    fn test_vision_task_skips_non_vision_models() {
+        let router = ModelRouter::new().with_models(
+            "local",
+            &[model("text-mini", false), model("vision-large", true)],
+        );
+        let task = "Describe this screenshot";
+
+        let plain = router.route(task, &RoutingHints::default());
+        assert_eq!(plain.model_id.as_deref(), Some("text-mini"));
+        assert!(!plain.fallback);
+
+        let vision = router.route(task, &RoutingHints::new().with_vision());
+        assert_eq!(vision.provider_id.as_deref(), Some("local"));
+        assert_eq!(vision.model_id.as_deref(), Some("vision-large"));
+        assert!(vision.fallback);
+        assert!(vision.reason.contains("no Fast (Haiku) model with vision"));
+
+        let none = router.route(
+            task,
+            &RoutingHints::new()
+                .with_vision()
+                .with_estimated_tokens(1_000_000),
+        );
+        assert!(none.provider_id.is_none());
+        assert!(none.fallback);
+        assert!(none
+            .reason
+            .contains("no configured model supports vision and context length"));
+    }
+
+    #[test]
+    fn test_low_budget_prefers_cheaper_models() {
+        let router = ModelRouter::new().with_providers(&providers(&["openai"]));
+        let task = "Analyze the security of this design and explain how to refactor \
+            the architecture for better performance";
+
+        let standard = router.route(task, &RoutingHints::default());
+        let low = router.route(task, &RoutingHints::new().with_budget(BudgetTier::Low));
+        assert!(low.model.rank() < standard.model.rank());
+        assert_eq!(low.model_id.as_deref(), Some("gpt-4.1-nano"));
+        assert!(low.reason.contains("low budget"));
+        assert_eq!(standard.provider_id.as_deref(), Some("openai"));
+    }
+
+    #[test]
+    fn test_route_without_providers_names_no_model() {
+        let decision = ModelRouter::new().route("Hello world", &RoutingHints::default());
+        assert_eq!(decision.model, ModelType::Fast);
+        assert!(decision.provider_id.is_none());
+        assert!(!decision.fallback);
+    }
 }
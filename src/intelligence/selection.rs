@@ -0,0 +1,296 @@
+use sena_providers::{ProviderRouter, ProviderStatus};
+use serde::{Deserialize, Serialize};
+
+use super::routing::{ModelType, RoutingDecision};
+
+const CODING_KEYWORDS: &[&str] = &[
+    "code",
+    "function",
+    "implement",
+    "refactor",
+    "debug",
+    "compile",
+    "bug",
+    "rust",
+    "python",
+    "typescript",
+    "javascript",
+    "golang",
+    "java",
+    "sql",
+    "regex",
+    "unit test",
+];
+
+const CODING_PREFERENCES: &[(&str, &str)] = &[
+    ("claude", "claude-sonnet"),
+    ("openai", "gpt-4.1"),
+    ("mistral", "codestral"),
+    ("gemini", "gemini-2.5-pro"),
+    ("ollama", "codellama"),
+];
+
+const CODING_POWERFUL_PREFERENCES: &[(&str, &str)] = &[
+    ("claude", "claude-opus"),
+    ("claude", "claude-sonnet"),
+    ("openai", "gpt-4.1"),
+    ("openai", "o4-mini"),
+    ("gemini", "gemini-2.5-pro"),
+    ("mistral", "codestral"),
+    ("ollama", "codellama"),
+];
+
+const FAST_PREFERENCES: &[(&str, &str)] = &[
+    ("claude", "claude-3-5-haiku"),
+    ("openai", "gpt-4.1-mini"),
+    ("gemini", "gemini-2.5-flash"),
+    ("mistral", "mistral-small"),
+];
+
+const BALANCED_PREFERENCES: &[(&str, &str)] = &[
+    ("claude", "claude-sonnet"),
+    ("openai", "gpt-4.1"),
+    ("gemini", "gemini-2.5-flash"),
+    ("mistral", "mistral-medium"),
+];
+
+const POWERFUL_PREFERENCES: &[(&str, &str)] = &[
+    ("claude", "claude-opus"),
+    ("claude", "claude-sonnet"),
+    ("openai", "gpt-4.1"),
+    ("openai", "o4-mini"),
+    ("gemini", "gemini-2.5-pro"),
+    ("mistral", "mistral-large"),
+];
+
+/// Broad kind of task, used to pick a suitable model family
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TaskKind {
+    Coding,
+    General,
+}
+
+impl TaskKind {
+    pub fn classify(task: &str) -> Self {
+        let lower = task.to_lowercase();
+        let words: Vec<&str> = lower.split(|c: char| !c.is_alphanumeric()).collect();
+        let mentions = |keyword: &&str| {
+            if keyword.contains(' ') {
+                lower.contains(*keyword)
+            } else {
+                words.contains(keyword)
+            }
+        };
+        if CODING_KEYWORDS.iter().any(mentions) || task.contains("```") {
+            TaskKind::Coding
+        } else {
+            TaskKind::General
+        }
+    }
+
+    /// Ordered `(provider, model id or prefix)` preferences for a tier.
+    /// Coding tasks never drop below a balanced code model.
+    pub fn preferences(&self, model_type: ModelType) -> &'static [(&'static str, &'static str)] {
+        match (self, model_type) {
+            (TaskKind::Coding, ModelType::Powerful) => CODING_POWERFUL_PREFERENCES,
+            (TaskKind::Coding, _) => CODING_PREFERENCES,
+            (TaskKind::General, ModelType::Fast) => FAST_PREFERENCES,
+            (TaskKind::General, ModelType::Balanced) => BALANCED_PREFERENCES,
+            (TaskKind::General, ModelType::Powerful) => POWERFUL_PREFERENCES,
+        }
+    }
+}
+
+/// A concrete provider and model chosen for a task
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderRoute {
+    pub provider_id: String,
+    pub model: String,
+    pub task_kind: TaskKind,
+    pub decision: RoutingDecision,
+    pub fallback: bool,
+    pub reason: String,
+}
+
+impl ProviderRoute {
+    pub fn format(&self) -> String {
+        format!(
+            "Provider: {}\nModel: {}\nTask: {:?} ({})\nReason: {}",
+            self.provider_id, self.model, self.task_kind, self.decision.model, self.reason
+        )
+    }
+}
+
+pub(super) fn usable(router: &ProviderRouter, provider_id: &str) -> bool {
+    let Some(provider) = router.get_provider(provider_id) else {
+        return false;
+    };
+    matches!(
+        provider.status(),
+        ProviderStatus::Connected | ProviderStatus::RateLimited
+    ) && router
+        .provider_health(provider_id)
+        .map_or(true, |health| !health.in_backoff())
+}
+
+fn find_model(router: &ProviderRouter, provider_id: &str, wanted: &str) -> Option<String> {
+    let models = router.get_provider(provider_id)?.available_models();
+    models
+        .iter()
+        .find(|m| m.id == wanted)
+        .or_else(|| models.iter().find(|m| m.id.starts_with(wanted)))
+        .map(|m| m.id.clone())
+}
+
+/// IDs of the usable providers in `router`, default provider first
+pub(super) fn usable_providers(router: &ProviderRouter) -> Vec<String> {
+    let mut ids: Vec<String> = router
+        .available_providers()
+        .iter()
+        .map(|p| p.provider_id().to_string())
+        .filter(|id| usable(router, id))
+        .collect();
+    ids.sort();
+    if let Some(default) = router.default_provider() {
+        if let Some(index) = ids.iter().position(|id| id == default.provider_id()) {
+            let id = ids.remove(index);
+            ids.insert(0, id);
+        }
+    }
+    ids
+}
+
+/// Map a routing decision onto the configured providers. Walks the task's
+/// preference list, then falls back to the model the decision picked (see
+/// [`ModelRouter::with_providers`](super::ModelRouter::with_providers)),
+/// the default provider and finally any usable provider. `None` when no
+/// provider is usable.
+pub fn select_provider(
+    task: &str,
+    decision: RoutingDecision,
+    router: &ProviderRouter,
+) -> Option<ProviderRoute> {
+    let task_kind = TaskKind::classify(task);
+
+    for (provider_id, wanted) in task_kind.preferences(decision.model) {
+        if !usable(router, provider_id) {
+            continue;
+        }
+        if let Some(model) = find_model(router, provider_id, wanted) {
+            let reason = format!("{:?} task, {} tier", task_kind, decision.model);
+            return Some(ProviderRoute {
+                provider_id: provider_id.to_string(),
+                model,
+                task_kind,
+                decision,
+                fallback: false,
+                reason,
+            });
+        }
+    }
+
+    // The model `ModelRouter::with_providers` fitted to the decision's tier
+    if let (Some(provider_id), Some(model)) = (&decision.provider_id, &decision.model_id) {
+        if usable(router, provider_id) {
+            return Some(ProviderRoute {
+                reason: format!(
+                    "No preferred {:?} model configured; {}",
+                    task_kind, decision.reason
+                ),
+                provider_id: provider_id.clone(),
+                model: model.clone(),
+                task_kind,
+                decision,
+                fallback: true,
+            });
+        }
+    }
+
+    let provider_id = usable_providers(router).into_iter().next()?;
+    let model = router
+        .get_provider(&provider_id)?
+        .default_model()
+        .to_string();
+    Some(ProviderRoute {
+        reason: format!(
+            "No preferred {:?} model configured; falling back to {}",
+            task_kind, provider_id
+        ),
+        provider_id,
+        model,
+        task_kind,
+        decision,
+        fallback: true,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intelligence::{ModelRouter, RoutingHints};
+    use sena_providers::config::{ProviderConfig, ProvidersConfig};
+
+    fn router(providers: &[&str]) -> ProviderRouter {
+        let mut config = ProvidersConfig::new();
+        for id in providers {
+            config.add_provider(ProviderConfig::new(*id).with_api_key("test-key"));
+        }
+        ProviderRouter::from_config(&config).unwrap()
+    }
+
+    #[test]
+    fn test_coding_task_routes_to_configured_code_model() {
+        let task = "write Rust code that parses a config file";
+        assert_eq!(TaskKind::classify(task), TaskKind::Coding);
+
+        let route = select_provider(
+            task,
+            ModelRouter::new().route(task, &RoutingHints::default()),
+            &router(&["openai"]),
+        )
+        .unwrap();
+        assert_eq!(route.provider_id, "openai");
+        assert_eq!(route.model, "gpt-4.1");
+        assert!(!route.fallback);
+
+        let route = select_provider(
+            task,
+            ModelRouter::new().route(task, &RoutingHints::default()),
+            &router(&["openai", "mistral", "claude"]),
+        )
+        .unwrap();
+        assert_eq!(route.provider_id, "claude");
+        assert!(route.model.starts_with("claude-sonnet"));
+    }
+
+    #[test]
+    fn test_unconfigured_preference_falls_back() {
+        let task = "summarize this meeting";
+        let route = select_provider(
+            task,
+            ModelRouter::new().route(task, &RoutingHints::default()),
+            &router(&["ollama"]),
+        )
+        .unwrap();
+        assert_eq!(route.provider_id, "ollama");
+        assert_eq!(route.model, "llama3.2");
+        assert!(route.fallback);
+
+        assert!(select_provider(
+            task,
+            ModelRouter::new().route(task, &RoutingHints::default()),
+            &router(&[])
+        )
+        .is_none());
+
+        let providers = router(&["ollama"]);
+        let decision = ModelRouter::new()
+            .with_providers(&providers)
+            .route(task, &RoutingHints::default());
+        let picked = decision.model_id.clone().unwrap();
+        let route = select_provider(task, decision, &providers).unwrap();
+        assert_eq!(route.provider_id, "ollama");
+        assert_eq!(route.model, picked);
+        assert!(route.fallback);
+    }
+}
@@ -1,14 +1,53 @@
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use thiserror::Error;
+
+use crate::guardian::GuardianMiddleware;
+
+#[derive(Error, Debug)]
+pub enum SkillError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid skill definition: {0}")]
+    InvalidDefinition(String),
+}
+
+pub type SkillResult<T> = Result<T, SkillError>;
+
+/// Environment variable a command skill reads its context from; the context
+/// is never spliced into the command line itself
+pub const SKILL_CONTEXT_ENV: &str = "SENA_SKILL_CONTEXT";
+
+/// What a user-defined skill does when it runs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkillHandler {
+    /// Text with `{context}` and `{name}` placeholders
+    Response(String),
+    /// Shell command, validated by the guardian before it runs
+    Command(String),
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Skill {
     pub name: String,
     pub description: String,
     pub triggers: Vec<String>,
+    /// Regexes matched against the context alongside `triggers`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub patterns: Vec<String>,
     pub auto_activate: bool,
     pub category: String,
     pub executions: u64,
+    /// Set for user-defined skills; built-ins run their own analysis
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub handler: Option<SkillHandler>,
+    /// File a user-defined skill was loaded from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<PathBuf>,
 }
 
 impl Skill {
@@ -17,9 +56,12 @@ impl Skill {
             name: name.to_string(),
             description: description.to_string(),
             triggers: Vec::new(),
+            patterns: Vec::new(),
             auto_activate: false,
             category: category.to_string(),
             executions: 0,
+            handler: None,
+            source: None,
         }
     }
 
@@ -33,33 +75,113 @@ impl Skill {
         self
     }
 
-    pub fn should_activate(&self, context: &str) -> bool {
-        if !self.auto_activate {
-            return false;
-        }
+    pub fn with_patterns(mut self, patterns: &[&str]) -> Self {
+        self.patterns = patterns.iter().map(|s| s.to_string()).collect();
+        self
+    }
+
+    pub fn with_handler(mut self, handler: SkillHandler) -> Self {
+        self.handler = Some(handler);
+        self
+    }
+
+    pub fn is_custom(&self) -> bool {
+        self.source.is_some()
+    }
 
+    /// Number of keywords and patterns found in the context
+    pub fn trigger_matches(&self, context: &str) -> usize {
         let context_lower = context.to_lowercase();
-        self.triggers
+        let keywords = self
+            .triggers
+            .iter()
+            .filter(|t| context_lower.contains(&t.to_lowercase()))
+            .count();
+        // Patterns are checked when a definition is loaded
+        let patterns = self
+            .patterns
             .iter()
-            .any(|t| context_lower.contains(&t.to_lowercase()))
+            .filter_map(|p| Regex::new(p).ok())
+            .filter(|re| re.is_match(context))
+            .count();
+        keywords + patterns
+    }
+
+    pub fn should_activate(&self, context: &str) -> bool {
+        self.auto_activate && self.trigger_matches(context) > 0
     }
 
     pub fn execute(&mut self, context: &str) -> SkillExecution {
         self.executions += 1;
 
-        let output = match self.name.as_str() {
-            "Security Auditor" => self.execute_security_audit(context),
-            "Performance Optimizer" => self.execute_performance_optimization(context),
-            "Truth Verifier" => self.execute_truth_verification(context),
-            "Code Reviewer" => self.execute_code_review(context),
-            _ => self.execute_generic(context),
+        let (output, success) = match &self.handler {
+            Some(SkillHandler::Response(template)) => (self.render(template, context), true),
+            Some(SkillHandler::Command(command)) => self.run_command(command, context),
+            None => (self.execute_builtin(context), true),
         };
 
         SkillExecution {
             skill: self.name.clone(),
             context: context.to_string(),
             output,
-            success: true,
+            success,
+        }
+    }
+
+    fn execute_builtin(&self, context: &str) -> String {
+        match self.name.as_str() {
+            "Security Auditor" => self.execute_security_audit(context),
+            "Performance Optimizer" => self.execute_performance_optimization(context),
+            "Truth Verifier" => self.execute_truth_verification(context),
+            "Code Reviewer" => self.execute_code_review(context),
+            _ => self.execute_generic(context),
+        }
+    }
+
+    fn render(&self, template: &str, context: &str) -> String {
+        template
+            .replace("{name}", &self.name)
+            .replace("{context}", context)
+    }
+
+    fn run_command(&self, command: &str, context: &str) -> (String, bool) {
+        let verdict = GuardianMiddleware::new().validate_command(command);
+        if !verdict.allowed {
+            let reason = verdict
+                .reason
+                .unwrap_or_else(|| "blocked by guardian".to_string());
+            return (format!("Blocked by guardian: {}", reason), false);
+        }
+
+        let (shell, shell_arg) = if cfg!(target_os = "windows") {
+            ("cmd", "/C")
+        } else {
+            ("sh", "-c")
+        };
+        let output = Command::new(shell)
+            .arg(shell_arg)
+            .arg(command)
+            .env(SKILL_CONTEXT_ENV, context)
+            .stdin(Stdio::null())
+            .output();
+
+        match output {
+            Ok(output) => {
+                let mut text = String::from_utf8_lossy(&output.stdout)
+                    .trim_end()
+                    .to_string();
+                if !output.status.success() {
+                    let stderr = String::from_utf8_lossy(&output.stderr);
+                    if !stderr.trim().is_empty() {
+                        if !text.is_empty() {
+                            text.push('\n');
+                        }
+                        text.push_str(stderr.trim_end());
+                    }
+                }
+                (text, output.status.success())
+            }
+            Err(e) => (format!("Failed to run '{}': {}", command, e), false),
         }
     }
 
@@ -194,15 +316,92 @@ impl Skill {
     }
 }
 
+/// A user-defined skill as written in a `.toml` file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkillDefinition {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default = "default_category")]
+    pub category: String,
+    /// Case-insensitive keywords
+    #[serde(default)]
+    pub triggers: Vec<String>,
+    /// Regexes, matched as written
+    #[serde(default)]
+    pub patterns: Vec<String>,
+    #[serde(default = "default_auto_activate")]
+    pub auto_activate: bool,
+    #[serde(default)]
+    pub response: Option<String>,
+    #[serde(default)]
+    pub command: Option<String>,
+}
+
+fn default_category() -> String {
+    "Custom".to_string()
+}
+
+fn default_auto_activate() -> bool {
+    true
+}
+
+impl SkillDefinition {
+    pub fn load(path: &Path) -> SkillResult<Skill> {
+        let content = std::fs::read_to_string(path)?;
+        let definition: Self = toml::from_str(&content)
+            .map_err(|e| SkillError::InvalidDefinition(format!("{}: {}", path.display(), e)))?;
+        definition
+            .into_skill()
+            .map(|skill| Skill {
+                source: Some(path.to_path_buf()),
+                ..skill
+            })
+            .map_err(|e| SkillError::InvalidDefinition(format!("{}: {}", path.display(), e)))
+    }
+
+    pub fn into_skill(self) -> Result<Skill, String> {
+        if self.name.trim().is_empty() {
+            return Err("name must not be empty".to_string());
+        }
+        if self.triggers.is_empty() && self.patterns.is_empty() {
+            return Err("at least one trigger or pattern is required".to_string());
+        }
+        if let Some(pattern) = self.patterns.iter().find(|p| Regex::new(p).is_err()) {
+            return Err(format!("invalid pattern '{}'", pattern));
+        }
+        let handler = match (self.response, self.command) {
+            (Some(response), None) => SkillHandler::Response(response),
+            (None, Some(command)) => SkillHandler::Command(command),
+            _ => return Err("exactly one of response or command is required".to_string()),
+        };
+
+        Ok(Skill {
+            name: self.name,
+            description: self.description,
+            triggers: self.triggers,
+            patterns: self.patterns,
+            auto_activate: self.auto_activate,
+            category: self.category,
+            executions: 0,
+            handler: Some(handler),
+            source: None,
+        })
+    }
+}
+
 #[derive(Debug)]
 pub struct SkillRegistry {
     skills: HashMap<String, Skill>,
+    /// Directories user-defined skills were loaded from, for `reload`
+    dirs: Vec<PathBuf>,
 }
 
 impl SkillRegistry {
     pub fn new() -> Self {
         let mut registry = Self {
             skills: HashMap::new(),
+            dirs: Vec::new(),
         };
 
         registry.register(
@@ -258,6 +457,63 @@ impl SkillRegistry {
         registry
     }
 
+    pub fn default_dir() -> PathBuf {
+        dirs::home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(".sena")
+            .join("skills")
+    }
+
+    /// Built-ins plus whatever loads from the default skills directory; a
+    /// broken definition leaves only the built-ins
+    pub fn configured() -> Self {
+        let mut registry = Self::new();
+        // Errors surface through `reload`
+        let _ = registry.load_from_dir(Self::default_dir());
+        registry
+    }
+
+    /// Register every `*.toml` skill in `dir`, replacing same-named skills.
+    /// Nothing is registered unless every file parses; a missing directory
+    /// loads nothing.
+    pub fn load_from_dir(&mut self, dir: impl AsRef<Path>) -> SkillResult<usize> {
+        let dir = dir.as_ref();
+        if !self.dirs.iter().any(|d| d == dir) {
+            self.dirs.push(dir.to_path_buf());
+        }
+        if !dir.is_dir() {
+            return Ok(0);
+        }
+
+        let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+            .collect();
+        paths.sort();
+
+        let skills = paths
+            .iter()
+            .map(|path| SkillDefinition::load(path))
+            .collect::<SkillResult<Vec<_>>>()?;
+        let count = skills.len();
+        for skill in skills {
+            self.register(skill);
+        }
+        Ok(count)
+    }
+
+    /// Drop user-defined skills and load them again from the same
+    /// directories; on error the registry is left as it was
+    pub fn reload(&mut self) -> SkillResult<usize> {
+        let mut fresh = Self::new();
+        let mut count = 0;
+        for dir in &self.dirs {
+            count += fresh.load_from_dir(dir)?;
+        }
+        *self = fresh;
+        Ok(count)
+    }
+
     pub fn register(&mut self, skill: Skill) {
         self.skills.insert(skill.name.clone(), skill);
     }
@@ -273,6 +529,29 @@ impl SkillRegistry {
         })
     }
 
+    /// The skill whose triggers match the context most often; ties go to
+    /// user-defined skills, then by name
+    pub fn find_matching(&self, context: &str) -> Option<&Skill> {
+        self.skills
+            .values()
+            .map(|skill| (skill.trigger_matches(context), skill))
+            .filter(|(matches, _)| *matches > 0)
+            .max_by(|(a, x), (b, y)| {
+                a.cmp(b)
+                    .then(x.is_custom().cmp(&y.is_custom()))
+                    .then(y.name.cmp(&x.name))
+            })
+            .map(|(_, skill)| skill)
+    }
+
+    /// Run the skill `find_matching` picks for the context
+    pub fn execute_matching(&self, context: &str) -> Option<SkillExecution> {
+        self.find_matching(context).map(|skill| {
+            let mut skill = skill.clone();
+            skill.execute(context)
+        })
+    }
+
     pub fn auto_execute(&self, context: &str) -> Vec<SkillExecution> {
         self.skills
             .values()
@@ -329,7 +608,7 @@ mod tests {
     fn test_auto_activation() {
         let registry = SkillRegistry::new();
         let results = registry.auto_execute("Check security vulnerabilities");
-        assert!(results.len() > 0);
+        assert!(!results.is_empty());
     }
 
     #[test]
@@ -341,4 +620,58 @@ mod tests {
         assert!(skill.should_activate("run a test"));
         assert!(!skill.should_activate("hello world"));
     }
+
+    #[test]
+    fn test_custom_skill_loads_and_triggers() {
+        let dir = std::env::temp_dir().join(format!("sena-skills-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("changelog.toml"),
+            r#"
+name = "Changelog Writer"
+triggers = ["changelog"]
+patterns = ["v\\d+\\.\\d+"]
+response = "Draft {context} entries"
+"#,
+        )
+        .unwrap();
+
+        let mut registry = SkillRegistry::new();
+        assert_eq!(registry.load_from_dir(&dir).unwrap(), 1);
+        assert!(registry.get("Changelog Writer").unwrap().is_custom());
+
+        let execution = registry.execute_matching("update the changelog").unwrap();
+        assert_eq!(execution.skill, "Changelog Writer");
+        assert_eq!(execution.output, "Draft update the changelog entries");
+        assert!(registry
+            .auto_execute("release v2.1")
+            .iter()
+            .any(|e| e.skill == "Changelog Writer"));
+
+        std::fs::remove_file(dir.join("changelog.toml")).unwrap();
+        assert_eq!(registry.reload().unwrap(), 0);
+        assert!(registry.get("Changelog Writer").is_none());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_command_skill_is_guarded() {
+        let skill = SkillDefinition {
+            name: "Cleanup".to_string(),
+            description: String::new(),
+            category: default_category(),
+            triggers: vec!["cleanup".to_string()],
+            patterns: Vec::new(),
+            auto_activate: true,
+            response: None,
+            command: Some("rm -rf /".to_string()),
+        }
+        .into_skill()
+        .unwrap();
+
+        let execution = skill.clone().execute("cleanup");
+        assert!(!execution.success);
+        assert!(execution.output.starts_with("Blocked by guardian"));
+    }
 }
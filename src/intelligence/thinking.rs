@@ -1,4 +1,8 @@
+use crate::base::text::is_stop_word;
 use crate::config::SenaConfig;
+use crate::knowledge::{
+    default_reasoning_frameworks, KnowledgeSystem, ReasoningFramework, SearchResult,
+};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -10,12 +14,14 @@ pub enum ThinkingDepth {
 }
 
 impl ThinkingDepth {
+    /// Minimum number of reasoning steps `ThinkingEngine::analyze` produces;
+    /// deeper analyses add one step per framework stage
     pub fn steps(&self) -> usize {
         match self {
-            ThinkingDepth::Quick => 3,
-            ThinkingDepth::Standard => 5,
-            ThinkingDepth::Deep => 8,
-            ThinkingDepth::Maximum => 12,
+            ThinkingDepth::Quick => 1,
+            ThinkingDepth::Standard => 3,
+            ThinkingDepth::Deep => 5,
+            ThinkingDepth::Maximum => 7,
         }
     }
 
@@ -46,8 +52,10 @@ impl std::fmt::Display for ThinkingDepth {
 
 #[derive(Debug, Clone)]
 pub struct ThinkingEngine {
-    frameworks: Vec<ThinkingFramework>,
+    frameworks: Vec<ReasoningFramework>,
     config: ThinkingConfig,
+    /// Patterns the steps cite when the problem names their topic
+    knowledge: Option<KnowledgeSystem>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -69,145 +77,118 @@ impl Default for ThinkingConfig {
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct ThinkingFramework {
-    pub name: String,
-    pub description: String,
-    pub steps: Vec<String>,
-}
-
 impl ThinkingEngine {
     pub fn new() -> Self {
         Self {
-            frameworks: default_frameworks(),
+            frameworks: default_reasoning_frameworks(),
             config: ThinkingConfig::default(),
+            knowledge: None,
         }
     }
 
     pub fn with_config(config: ThinkingConfig) -> Self {
         Self {
-            frameworks: default_frameworks(),
+            frameworks: default_reasoning_frameworks(),
             config,
+            knowledge: None,
         }
     }
 
-    pub fn analyze(&self, problem: &str, depth: ThinkingDepth) -> ThinkingResult {
-        let mut result = ThinkingResult {
-            problem: problem.to_string(),
-            depth,
-            steps: Vec::new(),
-            frameworks_used: Vec::new(),
-            conclusion: String::new(),
-            confidence: 0.0,
-            thinking_time_ms: 0,
-        };
+    /// Ground the steps in `knowledge`: a term that matches a security,
+    /// performance or architecture pattern is discussed through it
+    pub fn with_knowledge(mut self, knowledge: KnowledgeSystem) -> Self {
+        self.knowledge = Some(knowledge);
+        self
+    }
 
+    /// Work through `problem` as an ordered chain of steps. Quick answers in
+    /// one step; Standard decomposes, checks assumptions and synthesizes;
+    /// Deep runs each stage of the reasoning framework that fits the problem,
+    /// and Maximum follows it with an inversion pass.
+    pub fn analyze(&self, problem: &str, depth: ThinkingDepth) -> ThinkingResult {
         let start = std::time::Instant::now();
-
-        result.steps.push(ThinkingStep {
-            name: "Problem Decomposition".to_string(),
-            description: "Breaking down the problem into components".to_string(),
-            output: format!("Analyzing: {}", problem),
-        });
-
-        if depth as u8 >= ThinkingDepth::Standard as u8 {
-            result.steps.push(ThinkingStep {
-                name: "First Principles Analysis".to_string(),
-                description: "Identifying fundamental truths".to_string(),
-                output: "Examining base assumptions and constraints...".to_string(),
-            });
-            result.frameworks_used.push("First Principles".to_string());
-        }
-
-        if depth as u8 >= ThinkingDepth::Standard as u8 {
-            result.steps.push(ThinkingStep {
-                name: "Root Cause Investigation".to_string(),
-                description: "Asking 'Why?' to find underlying causes".to_string(),
-                output: "Tracing causality chain...".to_string(),
-            });
-            result.frameworks_used.push("5 Whys".to_string());
+        let frame = ProblemFrame::new(problem, self.knowledge.as_ref());
+        let mut steps = vec![frame.decomposition()];
+        let mut framework = None;
+        let mut frameworks_used = Vec::new();
+
+        match depth {
+            ThinkingDepth::Quick => {}
+            ThinkingDepth::Standard => steps.push(frame.assumptions()),
+            ThinkingDepth::Deep | ThinkingDepth::Maximum => {
+                let primary = self.select_framework(problem);
+                steps.extend(frame.apply(primary));
+                frameworks_used.push(primary.name.clone());
+                framework = Some(primary.name.clone());
+
+                if depth == ThinkingDepth::Maximum {
+                    if let Some(inversion) = self.framework_named("Inversion") {
+                        steps.extend(frame.apply(inversion));
+                        frameworks_used.push(inversion.name.clone());
+                    }
+                }
+            }
         }
 
-        if depth as u8 >= ThinkingDepth::Deep as u8 {
-            result.steps.push(ThinkingStep {
-                name: "Systems Analysis".to_string(),
-                description: "Examining interconnections and feedback loops".to_string(),
-                output: "Mapping system dependencies...".to_string(),
-            });
-            result.frameworks_used.push("Systems Thinking".to_string());
+        if depth != ThinkingDepth::Quick {
+            steps.push(frame.synthesis(&steps, self.select_framework(problem)));
         }
 
-        if depth as u8 >= ThinkingDepth::Deep as u8 {
-            result.steps.push(ThinkingStep {
-                name: "Risk Assessment".to_string(),
-                description: "Evaluating potential failure modes".to_string(),
-                output: "Identifying risks and mitigations...".to_string(),
-            });
-            result.frameworks_used.push("Risk Matrix".to_string());
-        }
-
-        if depth as u8 >= ThinkingDepth::Deep as u8 {
-            result.steps.push(ThinkingStep {
-                name: "Alternative Generation".to_string(),
-                description: "Generating multiple solution approaches".to_string(),
-                output: "Brainstorming alternatives...".to_string(),
-            });
-        }
-
-        if depth as u8 >= ThinkingDepth::Maximum as u8 {
-            result.steps.push(ThinkingStep {
-                name: "Trade-off Analysis".to_string(),
-                description: "Evaluating pros and cons of each approach".to_string(),
-                output: "Weighing trade-offs...".to_string(),
-            });
-            result.frameworks_used.push("Decision Matrix".to_string());
-        }
-
-        if depth as u8 >= ThinkingDepth::Maximum as u8 {
-            result.steps.push(ThinkingStep {
-                name: "Second-Order Effects".to_string(),
-                description: "Considering consequences of consequences".to_string(),
-                output: "Analyzing downstream effects...".to_string(),
-            });
-            result
-                .frameworks_used
-                .push("Second-Order Thinking".to_string());
-        }
+        let conclusion = steps
+            .last()
+            .map(|step| step.conclusion.clone())
+            .unwrap_or_default();
 
-        if depth as u8 >= ThinkingDepth::Maximum as u8 {
-            result.steps.push(ThinkingStep {
-                name: "Inversion Check".to_string(),
-                description: "Asking 'How would this fail?'".to_string(),
-                output: "Identifying failure modes to avoid...".to_string(),
-            });
-            result.frameworks_used.push("Inversion".to_string());
+        ThinkingResult {
+            problem: problem.to_string(),
+            depth,
+            steps,
+            framework,
+            frameworks_used,
+            conclusion,
+            confidence: match depth {
+                ThinkingDepth::Quick => 0.6,
+                ThinkingDepth::Standard => 0.75,
+                ThinkingDepth::Deep => 0.85,
+                ThinkingDepth::Maximum => 0.95,
+            },
+            thinking_time_ms: start.elapsed().as_millis() as u64,
         }
+    }
 
-        result.steps.push(ThinkingStep {
-            name: "Synthesis".to_string(),
-            description: "Combining insights into actionable conclusion".to_string(),
-            output: "Formulating final recommendation...".to_string(),
-        });
-
-        result.confidence = match depth {
-            ThinkingDepth::Quick => 0.6,
-            ThinkingDepth::Standard => 0.75,
-            ThinkingDepth::Deep => 0.85,
-            ThinkingDepth::Maximum => 0.95,
+    /// The framework that suits `problem`: 5 Whys for failures and
+    /// slowdowns, Systems Thinking for design work, First Principles otherwise
+    pub fn select_framework(&self, problem: &str) -> &ReasoningFramework {
+        let lower = problem.to_lowercase();
+        let mentions = |words: &[&str]| words.iter().any(|w| lower.contains(w));
+
+        let name = if mentions(&[
+            "why", "slow", "fail", "bug", "crash", "error", "broken", "leak",
+        ]) {
+            "5 Whys"
+        } else if mentions(&[
+            "design",
+            "architect",
+            "system",
+            "scal",
+            "integrat",
+            "depend",
+            "workflow",
+        ]) {
+            "Systems Thinking"
+        } else {
+            "First Principles"
         };
 
-        result.conclusion = format!(
-            "Analysis complete using {} frameworks across {} steps.",
-            result.frameworks_used.len(),
-            result.steps.len()
-        );
-
-        result.thinking_time_ms = start.elapsed().as_millis() as u64;
+        self.framework_named(name)
+            .unwrap_or_else(|| &self.frameworks[0])
+    }
 
-        result
+    fn framework_named(&self, name: &str) -> Option<&ReasoningFramework> {
+        self.frameworks.iter().find(|f| f.name.contains(name))
     }
 
-    pub fn frameworks(&self) -> &[ThinkingFramework] {
+    pub fn frameworks(&self) -> &[ReasoningFramework] {
         &self.frameworks
     }
 
@@ -227,6 +208,8 @@ pub struct ThinkingResult {
     pub problem: String,
     pub depth: ThinkingDepth,
     pub steps: Vec<ThinkingStep>,
+    /// The reasoning framework that structured a Deep or Maximum analysis
+    pub framework: Option<String>,
     pub frameworks_used: Vec<String>,
     pub conclusion: String,
     pub confidence: f64,
@@ -255,7 +238,9 @@ impl ThinkingResult {
 
         for (i, step) in self.steps.iter().enumerate() {
             output.push_str(&format!("{}. **{}**\n", i + 1, step.name));
-            output.push_str(&format!("   {}\n\n", step.description));
+            output.push_str(&format!("   Q: {}\n", step.question));
+            output.push_str(&format!("   H: {}\n", step.hypothesis));
+            output.push_str(&format!("   → {}\n\n", step.conclusion));
         }
 
         output.push_str("════════════════════════════════════════════════════════════════\n");
@@ -281,52 +266,479 @@ impl ThinkingResult {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ThinkingStep {
     pub name: String,
-    pub description: String,
-    pub output: String,
+    pub question: String,
+    pub hypothesis: String,
+    pub conclusion: String,
 }
 
-fn default_frameworks() -> Vec<ThinkingFramework> {
-    vec![
-        ThinkingFramework {
-            name: "First Principles".to_string(),
-            description: "Break down to fundamental truths".to_string(),
-            steps: vec![
-                "Identify assumptions".to_string(),
-                "Question each assumption".to_string(),
-                "Rebuild from verified fundamentals".to_string(),
-            ],
-        },
-        ThinkingFramework {
-            name: "5 Whys".to_string(),
-            description: "Find root causes by asking why repeatedly".to_string(),
-            steps: vec![
-                "State the problem".to_string(),
-                "Ask 'Why did this happen?'".to_string(),
-                "Repeat for each answer".to_string(),
-                "Continue until root cause".to_string(),
-            ],
-        },
-        ThinkingFramework {
-            name: "Systems Thinking".to_string(),
-            description: "View problems as interconnected systems".to_string(),
-            steps: vec![
-                "Map components".to_string(),
-                "Identify relationships".to_string(),
-                "Find feedback loops".to_string(),
-                "Locate leverage points".to_string(),
-            ],
-        },
-        ThinkingFramework {
-            name: "Inversion".to_string(),
-            description: "Ask how to guarantee failure, then avoid it".to_string(),
-            steps: vec![
-                "State your goal".to_string(),
-                "Invert: How would I fail?".to_string(),
-                "List failure modes".to_string(),
-                "Avoid each failure mode".to_string(),
-            ],
-        },
-    ]
+impl ThinkingStep {
+    fn new(
+        name: impl Into<String>,
+        question: impl Into<String>,
+        hypothesis: impl Into<String>,
+        conclusion: impl Into<String>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            question: question.into(),
+            hypothesis: hypothesis.into(),
+            conclusion: conclusion.into(),
+        }
+    }
+}
+
+/// Leading words that make a statement a question rather than name its topic
+const QUESTION_WORDS: &[&str] = &[
+    "why", "how", "what", "when", "where", "which", "who", "is", "are", "was", "were", "does",
+    "do", "did", "can", "could", "should", "would", "will", "i", "we", "the", "a", "an",
+];
+
+/// Imperatives that open a task ("Design a ...") without being its subject
+const ACTION_VERBS: &[&str] = &[
+    "add",
+    "build",
+    "choose",
+    "create",
+    "decide",
+    "design",
+    "find",
+    "fix",
+    "implement",
+    "improve",
+    "make",
+    "pick",
+    "plan",
+    "reduce",
+    "refactor",
+    "write",
+];
+
+/// Words too generic to reason about: prepositions and frequency words the
+/// shared stop-word list keeps, and verbs that only link the subject to
+/// the symptom ("returns", "happens")
+const NON_CONTENT_WORDS: &[&str] = &[
+    "again",
+    "all",
+    "always",
+    "before",
+    "during",
+    "each",
+    "every",
+    "get",
+    "gets",
+    "getting",
+    "goes",
+    "going",
+    "got",
+    "happen",
+    "happened",
+    "happening",
+    "happens",
+    "issue",
+    "issues",
+    "keep",
+    "keeps",
+    "never",
+    "occur",
+    "occurs",
+    "often",
+    "problem",
+    "problems",
+    "return",
+    "returned",
+    "returning",
+    "returns",
+    "seem",
+    "seems",
+    "since",
+    "something",
+    "sometimes",
+    "still",
+    "thing",
+    "things",
+    "too",
+    "under",
+    "until",
+    "use",
+    "used",
+    "uses",
+    "using",
+    "within",
+    "without",
+];
+
+/// Words that describe the symptom itself; a "why" chain asks about the
+/// other terms, which can be causes
+const SYMPTOM_WORDS: &[&str] = &[
+    "broken", "bug", "bugs", "crash", "crashed", "crashes", "crashing", "error", "errors", "fail",
+    "failed", "failing", "fails", "failure", "failures", "freeze", "freezes", "hang", "hangs",
+    "hanging", "leak", "leaking", "leaks", "slow", "slower", "slowly", "timeout", "timeouts",
+    "wrong",
+];
+
+/// The parts of a problem statement the steps reason about
+struct ProblemFrame {
+    /// The statement without its leading question words
+    topic: String,
+    /// Clauses of the statement, split on punctuation and conjunctions
+    components: Vec<String>,
+    /// Distinctive content words, in order of first appearance
+    terms: Vec<String>,
+    /// The best matching knowledge pattern for each term that has one
+    evidence: Vec<(String, SearchResult)>,
+}
+
+impl ProblemFrame {
+    fn new(problem: &str, knowledge: Option<&KnowledgeSystem>) -> Self {
+        let statement = problem.trim().trim_end_matches(['?', '.', '!']);
+        let words: Vec<&str> = statement.split_whitespace().collect();
+        let skip = words
+            .iter()
+            .take_while(|w| QUESTION_WORDS.contains(&w.to_lowercase().as_str()))
+            .count();
+        let topic = if skip < words.len() {
+            words[skip..].join(" ")
+        } else {
+            statement.to_string()
+        };
+
+        let components: Vec<String> = topic
+            .split([',', ';', '?', '.', '\n'])
+            .flat_map(|clause| clause.split(" and "))
+            .flat_map(|clause| clause.split(" then "))
+            .map(str::trim)
+            .filter(|c| !c.is_empty())
+            .map(str::to_string)
+            .collect();
+
+        let mut terms: Vec<String> = Vec::new();
+        let words = topic.split(|c: char| !c.is_alphanumeric() && c != '_' && c != '-');
+        for (i, word) in words.enumerate() {
+            let lower = word.to_lowercase();
+            let opening_verb = i == 0 && ACTION_VERBS.contains(&lower.as_str());
+            let numeric = lower.chars().all(|c| c.is_ascii_digit() || c == '-');
+            if lower.len() >= 3
+                && !opening_verb
+                && !numeric
+                && !is_stop_word(&lower)
+                && !NON_CONTENT_WORDS.contains(&lower.as_str())
+                && !terms.iter().any(|t| t.to_lowercase() == lower)
+            {
+                // Keep acronyms like API as written
+                let is_acronym = word.len() > 1 && word.chars().all(|c| c.is_ascii_uppercase());
+                terms.push(if is_acronym { word.to_string() } else { lower });
+            }
+        }
+
+        // Prefer the pattern that covers most of the statement's terms, so
+        // "SQL injection" cites SQL injection rather than another injection
+        let coverage = |hit: &SearchResult| {
+            let text = format!("{} {}", hit.title, hit.description).to_lowercase();
+            terms
+                .iter()
+                .filter(|t| text.contains(&t.to_lowercase()))
+                .count()
+        };
+        // A pattern only counts when its title names more of the problem
+        // than one shared word, so "API" alone doesn't cite API Key Security
+        // for an outage
+        let named = |hit: &SearchResult| {
+            let title = hit.title.to_lowercase();
+            let named = terms
+                .iter()
+                .filter(|t| title.contains(&t.to_lowercase()))
+                .count();
+            named >= terms.len().min(2)
+        };
+        let mut evidence: Vec<(String, SearchResult)> = knowledge
+            .map(|knowledge| {
+                terms
+                    .iter()
+                    .filter_map(|term| {
+                        knowledge
+                            .search(term)
+                            .into_iter()
+                            .filter(|hit| hit.domain != "reasoning" && named(hit))
+                            .max_by(|a, b| {
+                                coverage(a)
+                                    .cmp(&coverage(b))
+                                    .then(a.relevance.total_cmp(&b.relevance))
+                            })
+                            .map(|hit| (term.clone(), hit))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        // Each pattern is cited once, for the first term that found it
+        let mut cited = std::collections::HashSet::new();
+        evidence.retain(|(_, hit)| cited.insert(hit.title.clone()));
+
+        Self {
+            topic,
+            components,
+            terms,
+            evidence,
+        }
+    }
+
+    /// The clause `term` appears in, when the statement has several
+    fn part_played(&self, term: &str) -> String {
+        let clause = self.clause_with(term);
+        if clause == self.topic {
+            format!("{} plays a part", capitalize(term))
+        } else {
+            format!("{} plays a part in \"{}\"", capitalize(term), clause)
+        }
+    }
+
+    /// Titles of the cited patterns
+    fn patterns(&self) -> Vec<&str> {
+        self.evidence
+            .iter()
+            .map(|(_, hit)| hit.title.as_str())
+            .collect()
+    }
+
+    fn evidence_for(&self, term: &str) -> Option<&SearchResult> {
+        self.evidence
+            .iter()
+            .find(|(t, _)| t == term)
+            .map(|(_, hit)| hit)
+    }
+
+    /// What is known about `term`: the matching pattern's first sentence,
+    /// or `otherwise` when the knowledge base has nothing on it
+    fn finding(&self, term: &str, otherwise: String) -> String {
+        match self.evidence_for(term) {
+            Some(hit) => {
+                let summary = hit.description.split(". ").next().unwrap_or_default();
+                format!(
+                    "{} ({}): {}",
+                    hit.title,
+                    hit.domain,
+                    summary.trim_end_matches('.')
+                )
+            }
+            None => otherwise,
+        }
+    }
+
+    /// The `i`th distinctive term, or the whole topic once they run out
+    fn focus(&self, i: usize) -> &str {
+        self.terms
+            .get(i)
+            .map_or(self.topic.as_str(), String::as_str)
+    }
+
+    /// The clause `term` appears in, or the whole topic
+    fn clause_with(&self, term: &str) -> &str {
+        let term = term.to_lowercase();
+        self.components
+            .iter()
+            .find(|c| c.to_lowercase().contains(&term))
+            .map_or(self.topic.as_str(), String::as_str)
+    }
+
+    fn decomposition(&self) -> ThinkingStep {
+        let hypothesis = if self.components.len() > 1 {
+            format!(
+                "It has {} parts: {}",
+                self.components.len(),
+                self.components.join("; ")
+            )
+        } else if self.terms.len() > 1 {
+            format!("It turns on {}", self.terms.join(", "))
+        } else {
+            format!("It is a single concern: {}", self.topic)
+        };
+        ThinkingStep::new(
+            "Problem Decomposition",
+            format!("What does \"{}\" break down into?", self.topic),
+            hypothesis,
+            format!("Start with {}", self.focus(0)),
+        )
+    }
+
+    fn assumptions(&self) -> ThinkingStep {
+        let focus = self.focus(0);
+        ThinkingStep::new(
+            "Assumption Check",
+            format!("What is being taken for granted about {}?", focus),
+            format!(
+                "The approach holds only if {} behaves as currently believed",
+                focus
+            ),
+            format!("Verify how {} actually behaves before acting on it", focus),
+        )
+    }
+
+    /// One step per stage of `framework`'s process
+    fn apply(&self, framework: &ReasoningFramework) -> Vec<ThinkingStep> {
+        if framework.name.contains("Whys") {
+            return self.ask_why(framework);
+        }
+        framework
+            .process
+            .iter()
+            .enumerate()
+            .map(|(i, stage)| {
+                let (stage, guidance) = match stage.split_once(" - ") {
+                    Some((stage, guidance)) => (stage.trim(), Some(guidance.trim())),
+                    None => (stage.trim(), None),
+                };
+                let focus = self.focus(i);
+                let question = match guidance.and_then(|g| g.strip_suffix('?')) {
+                    Some(guidance) => format!("{} for {}?", guidance, focus),
+                    None => format!("{}: what does this mean for {}?", stage, focus),
+                };
+                ThinkingStep::new(
+                    stage,
+                    question,
+                    self.finding(
+                        focus,
+                        format!(
+                            "{} shapes \"{}\"",
+                            capitalize(focus),
+                            self.clause_with(focus)
+                        ),
+                    ),
+                    format!("Apply \"{}\" to {}", stage, focus),
+                )
+            })
+            .collect()
+    }
+
+    /// Terms that could cause the symptom rather than describe it, the
+    /// ones the knowledge base explains first as the likelier causes
+    fn causes(&self) -> Vec<&str> {
+        let (cited, rest): (Vec<&String>, Vec<&String>) = self
+            .terms
+            .iter()
+            .filter(|term| !SYMPTOM_WORDS.contains(&term.to_lowercase().as_str()))
+            .partition(|term| self.evidence_for(term).is_some());
+        cited.into_iter().chain(rest).map(String::as_str).collect()
+    }
+
+    /// The first stage states the symptom; each later one asks whether the
+    /// next candidate cause is behind it
+    fn ask_why(&self, framework: &ReasoningFramework) -> Vec<ThinkingStep> {
+        let causes = self.causes();
+        let mut previous: Option<&str> = None;
+        let mut steps = Vec::new();
+        for (i, stage) in framework.process.iter().enumerate() {
+            let question = match previous {
+                Some(cause) => format!("What about {} could cause it?", cause),
+                None => format!("Why does \"{}\" happen?", self.topic),
+            };
+            let step = if i == 0 {
+                ThinkingStep::new(
+                    stage.as_str(),
+                    "What exactly is going wrong?",
+                    format!("The symptom is: {}", self.topic),
+                    format!("Trace the causes of {}", self.topic),
+                )
+            } else {
+                match causes.get(i - 1) {
+                    Some(&cause) => {
+                        previous = Some(cause);
+                        ThinkingStep::new(
+                            stage.as_str(),
+                            question,
+                            self.finding(cause, self.part_played(cause)),
+                            format!("Next, ask what about {} could cause it", cause),
+                        )
+                    }
+                    None => ThinkingStep::new(
+                        stage.as_str(),
+                        question,
+                        "The cause lies beyond what the problem statement names",
+                        "Gather evidence (logs, metrics, recent changes) to go one level deeper",
+                    ),
+                }
+            };
+            steps.push(step);
+        }
+        steps
+    }
+
+    /// What to do, by the kind of problem `framework` was chosen for, led
+    /// by a knowledge pattern when one clearly applies
+    fn synthesis(&self, steps: &[ThinkingStep], framework: &ReasoningFramework) -> ThinkingStep {
+        let mut findings: Vec<&str> = Vec::new();
+        for step in steps.iter().skip(1) {
+            if !findings.contains(&step.conclusion.as_str()) {
+                findings.push(&step.conclusion);
+            }
+        }
+        let patterns = self.patterns();
+        let mut hypothesis = format!("The earlier steps point to: {}", findings.join("; "));
+
+        let causes = self.causes();
+        let mut conclusion = if framework.name.contains("Whys") {
+            match causes.as_slice() {
+                [] => format!(
+                    "Reproduce \"{}\" and collect evidence on where it starts",
+                    self.topic
+                ),
+                [cause] => format!("Reproduce \"{}\", then check {}", self.topic, cause),
+                [first, rest @ ..] => format!(
+                    "Reproduce \"{}\", then rule out {} before {}",
+                    self.topic,
+                    first,
+                    rest.join(" and ")
+                ),
+            }
+        } else if framework.name.contains("Systems") {
+            match self.terms.as_slice() {
+                [] | [_] => format!(
+                    "Pin down what {} depends on and what depends on it before building",
+                    self.focus(0)
+                ),
+                [first, second, ..] => format!(
+                    "Map how {} and {} depend on each other, then settle their interfaces before building",
+                    first, second
+                ),
+            }
+        } else {
+            format!(
+                "Separate the hard constraints of {} from convention, then rebuild the approach from those constraints",
+                self.focus(0)
+            )
+        };
+        if let Some(first) = patterns.first() {
+            hypothesis.push_str(&format!(
+                ". Known patterns involved: {}",
+                patterns.join(", ")
+            ));
+            conclusion = format!(
+                "Apply {} first, then {}",
+                first,
+                lowercase_first(&conclusion)
+            );
+        }
+        ThinkingStep::new(
+            "Synthesis",
+            format!("What should be done about {}?", self.topic),
+            hypothesis,
+            conclusion,
+        )
+    }
+}
+
+fn lowercase_first(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
 }
 
 #[cfg(test)]
@@ -336,14 +748,15 @@ mod tests {
     #[test]
     fn test_thinking_engine_creation() {
         let engine = ThinkingEngine::new();
-        assert!(engine.frameworks().len() > 0);
+        assert!(!engine.frameworks().is_empty());
     }
 
     #[test]
     fn test_analyze_quick() {
         let engine = ThinkingEngine::new();
         let result = engine.analyze("Why is the system slow?", ThinkingDepth::Quick);
-        assert!(result.steps.len() >= 2);
+        assert_eq!(result.steps.len(), 1);
+        assert!(result.framework.is_none());
         assert!(result.confidence > 0.5);
     }
 
@@ -352,7 +765,102 @@ mod tests {
         let engine = ThinkingEngine::new();
         let result = engine.analyze("Design a scalable system", ThinkingDepth::Deep);
         assert!(result.steps.len() >= 5);
-        assert!(result.frameworks_used.len() >= 3);
+        assert_eq!(result.framework.as_deref(), Some("Systems Thinking"));
+        assert!(result
+            .steps
+            .iter()
+            .any(|step| step.question.contains("scalable")));
+    }
+
+    #[test]
+    fn test_depth_sets_step_count() {
+        let engine = ThinkingEngine::new();
+        let problem = "Why is the checkout API slow after deploys?";
+        for depth in [
+            ThinkingDepth::Quick,
+            ThinkingDepth::Standard,
+            ThinkingDepth::Deep,
+            ThinkingDepth::Maximum,
+        ] {
+            assert!(engine.analyze(problem, depth).steps.len() >= depth.steps());
+        }
+        assert_eq!(
+            engine.analyze(problem, ThinkingDepth::Standard).steps.len(),
+            3
+        );
+
+        let deep = engine.analyze(problem, ThinkingDepth::Deep);
+        assert_eq!(
+            deep.framework.as_deref(),
+            Some("Root Cause Analysis (5 Whys)")
+        );
+        // Each "why" asks about the previous candidate cause; the symptom
+        // word "slow" is not one
+        assert_eq!(
+            deep.steps[3].question,
+            "What about checkout could cause it?"
+        );
+        assert_eq!(deep.steps[4].question, "What about API could cause it?");
+        assert_eq!(
+            deep.conclusion,
+            "Reproduce \"checkout API slow after deploys\", then rule out checkout before API and deploys"
+        );
+    }
+
+    #[test]
+    fn test_steps_cite_matching_knowledge() {
+        let dir = std::env::temp_dir().join(format!("sena-thinking-{}", uuid::Uuid::new_v4()));
+        let engine = ThinkingEngine::new().with_knowledge(KnowledgeSystem::in_dir(&dir));
+        let result = engine.analyze(
+            "Why does the report page fail with SQL injection?",
+            ThinkingDepth::Deep,
+        );
+
+        assert!(result
+            .steps
+            .iter()
+            .any(|step| step.hypothesis.contains("(security)")));
+        assert!(result
+            .conclusion
+            .starts_with("Apply SQL Injection Prevention first"));
+        let synthesis = result.steps.last().unwrap();
+        assert!(synthesis.hypothesis.contains("Known patterns involved"));
+
+        // Without a knowledge base the steps fall back to the statement
+        let plain = ThinkingEngine::new().analyze(
+            "Why does the report page fail with SQL injection?",
+            ThinkingDepth::Deep,
+        );
+        assert!(plain
+            .steps
+            .iter()
+            .all(|step| !step.hypothesis.contains("(security)")));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_conclusion_is_more_than_the_query_terms() {
+        let dir = std::env::temp_dir().join(format!("sena-thinking-{}", uuid::Uuid::new_v4()));
+        let engine = ThinkingEngine::new().with_knowledge(KnowledgeSystem::in_dir(&dir));
+        let problem = "API returns 500 errors under load";
+        let result = engine.analyze(problem, ThinkingDepth::Deep);
+
+        let terms: Vec<&str> = problem.split_whitespace().collect();
+        assert!(!result.conclusion.contains('→'));
+        assert_ne!(result.conclusion, terms.join(" "));
+        assert!(!result.conclusion.contains("API Key Security"));
+        assert_eq!(
+            result.conclusion,
+            "Reproduce \"API returns 500 errors under load\", then rule out API before load"
+        );
+        for step in &result.steps {
+            for noise in ["\"return", "500 plays", "why 500", "under is"] {
+                assert!(!step.question.contains(noise), "{:?}", step);
+                assert!(!step.hypothesis.contains(noise), "{:?}", step);
+                assert!(!step.conclusion.contains(noise), "{:?}", step);
+            }
+        }
+        let _ = std::fs::remove_dir_all(dir);
     }
 
     #[test]
@@ -236,6 +236,124 @@ impl std::fmt::Display for ArchitecturePattern {
     }
 }
 
+/// Functions longer than this are flagged as doing too much
+pub const MAX_FUNCTION_LINES: usize = 80;
+
+/// Files with more functions than this are flagged as doing too much
+pub const MAX_FUNCTIONS_PER_FILE: usize = 40;
+
+const PATTERN_MARKERS: &[(&str, DesignPattern)] = &[
+    (r"\b(struct|class)\s+\w*Builder\b", DesignPattern::Builder),
+    (r"\b\w+Factory\b", DesignPattern::Factory),
+    (
+        r"getInstance\s*\(|static\s+\w*INSTANCE\b|\bOnceLock<",
+        DesignPattern::Singleton,
+    ),
+    (
+        r"\b(subscribe|addEventListener|add_listener|notify_observers)\s*\(",
+        DesignPattern::Observer,
+    ),
+    (r"\b\w+Strategy\b", DesignPattern::Strategy),
+    (r"\b(struct|class)\s+\w*Adapter\b", DesignPattern::Adapter),
+    (r"\b(struct|class)\s+\w*Facade\b", DesignPattern::Facade),
+    (r"\b(struct|class)\s+\w*Proxy\b", DesignPattern::Proxy),
+];
+
+/// A design pattern in use, or a likely SOLID violation, found in source
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchitectureSignal {
+    pub line: Option<usize>,
+    pub description: String,
+    pub principle: Option<SolidPrinciple>,
+    pub pattern: Option<DesignPattern>,
+}
+
+impl ArchitectureSignal {
+    pub fn is_violation(&self) -> bool {
+        self.principle.is_some()
+    }
+}
+
+impl ArchitecturePattern {
+    /// Detect design patterns in use and structural smells: overly long
+    /// functions, files with too many functions, and services constructed
+    /// directly instead of injected
+    pub fn detect(content: &str) -> Vec<ArchitectureSignal> {
+        let mut signals = Vec::new();
+        let lines: Vec<&str> = content.lines().collect();
+
+        for (pattern, design) in PATTERN_MARKERS {
+            let Ok(re) = regex::Regex::new(pattern) else {
+                continue;
+            };
+            if let Some(index) = lines.iter().position(|line| re.is_match(line)) {
+                signals.push(ArchitectureSignal {
+                    line: Some(index + 1),
+                    description: format!("Uses the {} pattern", design),
+                    principle: None,
+                    pattern: Some(*design),
+                });
+            }
+        }
+
+        let fn_re =
+            regex::Regex::new(r"^\s*(pub(\([^)]*\))?\s+)?(async\s+)?(fn|def|function)\s+(\w+)")
+                .expect("valid function pattern");
+        let starts: Vec<(usize, String)> = lines
+            .iter()
+            .enumerate()
+            .filter_map(|(index, line)| Some((index, fn_re.captures(line)?[5].to_string())))
+            .collect();
+
+        for (i, (start, name)) in starts.iter().enumerate() {
+            let end = starts.get(i + 1).map_or(lines.len(), |(next, _)| *next);
+            let length = end - start;
+            if length > MAX_FUNCTION_LINES {
+                signals.push(ArchitectureSignal {
+                    line: Some(start + 1),
+                    description: format!(
+                        "Function '{}' is {} lines long (max {})",
+                        name, length, MAX_FUNCTION_LINES
+                    ),
+                    principle: Some(SolidPrinciple::SingleResponsibility),
+                    pattern: None,
+                });
+            }
+        }
+
+        if starts.len() > MAX_FUNCTIONS_PER_FILE {
+            signals.push(ArchitectureSignal {
+                line: None,
+                description: format!(
+                    "File defines {} functions (max {}); consider splitting it",
+                    starts.len(),
+                    MAX_FUNCTIONS_PER_FILE
+                ),
+                principle: Some(SolidPrinciple::SingleResponsibility),
+                pattern: None,
+            });
+        }
+
+        let concrete_re = regex::Regex::new(
+            r"\bnew\s+\w+(Repository|Client|Service|Database)\s*\(|\b\w+(Repository|Client|Service|Database)::new\s*\(",
+        )
+        .expect("valid construction pattern");
+        for (index, line) in lines.iter().enumerate() {
+            if concrete_re.is_match(line) {
+                signals.push(ArchitectureSignal {
+                    line: Some(index + 1),
+                    description: "Concrete dependency constructed inline instead of injected"
+                        .to_string(),
+                    principle: Some(SolidPrinciple::DependencyInversion),
+                    pattern: None,
+                });
+            }
+        }
+
+        signals
+    }
+}
+
 pub fn default_patterns() -> Vec<ArchitecturePattern> {
     vec![
         ArchitecturePattern::new(
@@ -0,0 +1,191 @@
+//! Context Assembly
+//!
+//! Gathers the memories and knowledge patterns relevant to a query into a
+//! single context block, bounded by a token budget, for injection ahead of
+//! a prompt.
+
+use serde::{Deserialize, Serialize};
+
+use super::KnowledgeSystem;
+use crate::memory::PersistentMemory;
+
+/// Default token budget for an assembled context block
+pub const DEFAULT_CONTEXT_TOKENS: usize = 1024;
+
+const MAX_MEMORY_ENTRIES: usize = 20;
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Rough token count used for budgeting (about four characters per token)
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(CHARS_PER_TOKEN)
+}
+
+/// A memory or knowledge item included in the context
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextSource {
+    pub kind: String,
+    pub label: String,
+    pub tokens: usize,
+}
+
+/// Context block assembled for a query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssembledContext {
+    pub query: String,
+    pub text: String,
+    pub sources: Vec<ContextSource>,
+    pub tokens: usize,
+    pub max_tokens: usize,
+    pub omitted: usize,
+}
+
+impl AssembledContext {
+    pub fn is_empty(&self) -> bool {
+        self.sources.is_empty()
+    }
+}
+
+/// Assemble memory and knowledge context for `query` within `max_tokens`.
+/// Memories come first, then knowledge patterns by relevance; items that
+/// would exceed the budget are skipped and counted in `omitted`.
+pub fn assemble_context(
+    memory: &PersistentMemory,
+    knowledge: &KnowledgeSystem,
+    query: &str,
+    max_tokens: usize,
+) -> AssembledContext {
+    let mut candidates: Vec<(ContextSource, String)> = Vec::new();
+
+    let memories = memory.get_context_for_query(query, MAX_MEMORY_ENTRIES);
+    for line in memories.lines().filter(|l| l.starts_with("- ")) {
+        candidates.push((
+            ContextSource {
+                kind: "memory".to_string(),
+                label: line.trim_start_matches("- ").to_string(),
+                tokens: 0,
+            },
+            format!("{}\n", line),
+        ));
+    }
+
+    let mut patterns = knowledge.search(query);
+    patterns.sort_by(|a, b| {
+        b.relevance
+            .partial_cmp(&a.relevance)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    for pattern in patterns {
+        candidates.push((
+            ContextSource {
+                kind: "knowledge".to_string(),
+                label: format!("{}: {}", pattern.domain, pattern.title),
+                tokens: 0,
+            },
+            format!(
+                "- [{}] {}: {}\n",
+                pattern.domain, pattern.title, pattern.description
+            ),
+        ));
+    }
+
+    let mut memory_lines = String::new();
+    let mut knowledge_lines = String::new();
+    let mut sources = Vec::new();
+    let mut tokens = 0;
+    let mut omitted = 0;
+
+    for (mut source, line) in candidates {
+        let header = match source.kind.as_str() {
+            "memory" if memory_lines.is_empty() => "Relevant memories:\n",
+            "knowledge" if knowledge_lines.is_empty() => "Relevant knowledge:\n",
+            _ => "",
+        };
+        let cost = estimate_tokens(header) + estimate_tokens(&line);
+        if tokens + cost > max_tokens {
+            omitted += 1;
+            continue;
+        }
+
+        let section = if source.kind == "memory" {
+            &mut memory_lines
+        } else {
+            &mut knowledge_lines
+        };
+        section.push_str(header);
+        section.push_str(&line);
+
+        source.tokens = cost;
+        tokens += cost;
+        sources.push(source);
+    }
+
+    let text = memory_lines + &knowledge_lines;
+
+    AssembledContext {
+        query: query.to_string(),
+        text,
+        sources,
+        tokens,
+        max_tokens,
+        omitted,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::{MemoryEntry, MemoryType};
+
+    #[test]
+    fn test_context_includes_memory_and_knowledge_within_budget() {
+        let dir = std::env::temp_dir().join(format!("sena-context-{}", uuid::Uuid::new_v4()));
+        let mut memory = PersistentMemory::with_dir(dir.clone()).unwrap();
+        memory
+            .add(MemoryEntry::new(
+                "Reports use SQL Injection safe query builders",
+                MemoryType::Project,
+            ))
+            .unwrap();
+        let knowledge = KnowledgeSystem::new();
+
+        let context = assemble_context(&memory, &knowledge, "sql injection", 4096);
+        assert!(context.text.contains("SQL Injection safe query builders"));
+        assert!(context.text.contains("SQL Injection Prevention"));
+        assert!(context.sources.iter().any(|s| s.kind == "memory"));
+        assert!(context.sources.iter().any(|s| s.kind == "knowledge"));
+        assert!(context.tokens <= 4096);
+
+        let small = assemble_context(&memory, &knowledge, "sql injection", 30);
+        assert!(small.tokens <= 30);
+        assert!(estimate_tokens(&small.text) <= 30);
+        assert!(small.omitted > 0);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_multi_line_memory_stays_one_source() {
+        let dir = std::env::temp_dir().join(format!("sena-context-{}", uuid::Uuid::new_v4()));
+        let mut memory = PersistentMemory::with_dir(dir.clone()).unwrap();
+        memory
+            .add(MemoryEntry::new(
+                "Deploy checklist:\n- run migrations\r\n- restart workers",
+                MemoryType::Project,
+            ))
+            .unwrap();
+
+        let context = assemble_context(&memory, &KnowledgeSystem::new(), "deploy", 4096);
+        let memories: Vec<_> = context
+            .sources
+            .iter()
+            .filter(|s| s.kind == "memory")
+            .collect();
+        assert_eq!(memories.len(), 1);
+        assert!(memories[0]
+            .label
+            .ends_with(r"checklist:\n- run migrations\n- restart workers"));
+        assert_eq!(context.text.lines().count(), 2);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,343 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use super::{ArchitecturePattern, PerformancePattern, SecurityAudit, SolidPrinciple};
+use crate::agents::{Finding, Severity};
+use crate::knowledge::VulnerabilityType;
+
+/// Source file extensions the linter reads when given a directory
+pub const LINT_EXTENSIONS: &[&str] = &[
+    "rs", "py", "js", "jsx", "ts", "tsx", "go", "java", "kt", "swift", "rb", "php", "c", "cc",
+    "cpp", "h", "cs",
+];
+
+const SKIPPED_DIRS: &[&str] = &["target", "node_modules", "dist", "build", "vendor"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintDetector {
+    Security,
+    Performance,
+    Architecture,
+}
+
+impl std::fmt::Display for LintDetector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LintDetector::Security => write!(f, "security"),
+            LintDetector::Performance => write!(f, "performance"),
+            LintDetector::Architecture => write!(f, "architecture"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LintFinding {
+    pub detector: LintDetector,
+    pub file: String,
+    pub line: Option<usize>,
+    #[serde(flatten)]
+    pub finding: Finding,
+}
+
+/// Findings from every knowledge detector, most severe first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LintReport {
+    pub files: Vec<String>,
+    pub findings: Vec<LintFinding>,
+    pub score: u8,
+}
+
+impl LintReport {
+    pub fn from_findings(files: Vec<String>, mut findings: Vec<LintFinding>) -> Self {
+        findings.sort_by(|a, b| {
+            b.finding
+                .severity
+                .cmp(&a.finding.severity)
+                .then_with(|| a.file.cmp(&b.file))
+                .then_with(|| a.line.cmp(&b.line))
+        });
+        let penalty: usize = findings
+            .iter()
+            .map(|f| match f.finding.severity {
+                Severity::Critical => 15,
+                Severity::Warning => 5,
+                Severity::Info | Severity::Success => 0,
+            })
+            .sum();
+
+        Self {
+            files,
+            findings,
+            score: 100usize.saturating_sub(penalty) as u8,
+        }
+    }
+
+    pub fn count(&self, severity: Severity) -> usize {
+        self.findings
+            .iter()
+            .filter(|f| f.finding.severity == severity)
+            .count()
+    }
+
+    /// Whether any finding is at least as severe as `threshold`
+    pub fn fails_on(&self, threshold: Severity) -> bool {
+        self.findings
+            .iter()
+            .any(|f| f.finding.severity >= threshold)
+    }
+
+    pub fn format(&self) -> String {
+        let mut output = format!("Files checked: {}\n", self.files.len());
+        if self.findings.is_empty() {
+            output.push_str("\nNo findings.\n");
+        }
+        for lint in &self.findings {
+            output.push_str(&format!(
+                "\n{} [{}] {}\n",
+                lint.finding.severity, lint.detector, lint.finding.title
+            ));
+            output.push_str(&format!("   {}\n", lint.finding.description));
+            if let Some(location) = &lint.finding.location {
+                output.push_str(&format!("   📍 {}\n", location));
+            }
+            if let Some(suggestion) = &lint.finding.suggestion {
+                output.push_str(&format!("   💡 {}\n", suggestion));
+            }
+        }
+        output.push_str(&format!(
+            "\n🔴 {} critical  🟡 {} warning  🔵 {} info\n📊 Score: {}/100\n",
+            self.count(Severity::Critical),
+            self.count(Severity::Warning),
+            self.count(Severity::Info),
+            self.score
+        ));
+        output
+    }
+}
+
+fn lint_finding(
+    detector: LintDetector,
+    file: &str,
+    line: Option<usize>,
+    severity: Severity,
+    title: String,
+    description: String,
+    suggestion: Option<String>,
+) -> LintFinding {
+    let location = match line {
+        Some(line) => format!("{}:{}", file, line),
+        None => file.to_string(),
+    };
+    LintFinding {
+        detector,
+        file: file.to_string(),
+        line,
+        finding: Finding {
+            severity,
+            title,
+            description,
+            location: Some(location),
+            suggestion,
+        },
+    }
+}
+
+fn security_suggestion(vuln_type: VulnerabilityType) -> &'static str {
+    match vuln_type {
+        VulnerabilityType::DataExposure => {
+            "Load secrets from the environment or a secret store, and rotate this one"
+        }
+        VulnerabilityType::SqlInjection => "Use parameterized queries",
+        VulnerabilityType::CommandInjection => "Avoid shells; pass arguments as a list",
+        VulnerabilityType::Xss => "Escape output or use textContent",
+        VulnerabilityType::InsecureDeserialization => "Only deserialize trusted data",
+        _ => "Use a modern algorithm and keep TLS verification on",
+    }
+}
+
+/// Run the security, performance and architecture detectors on one file
+pub fn lint_source(file: &str, content: &str) -> Vec<LintFinding> {
+    let mut findings = Vec::new();
+
+    for vuln in SecurityAudit::scan(file, content).vulnerabilities {
+        let severity = match vuln.severity {
+            8..=u8::MAX => Severity::Critical,
+            5..=7 => Severity::Warning,
+            _ => Severity::Info,
+        };
+        findings.push(lint_finding(
+            LintDetector::Security,
+            file,
+            vuln.line,
+            severity,
+            format!("{}: {}", vuln.vulnerability_type, vuln.description),
+            vuln.code_snippet.unwrap_or_default(),
+            Some(security_suggestion(vuln.vulnerability_type).to_string()),
+        ));
+    }
+
+    for estimate in PerformancePattern::estimate_complexity(content) {
+        if estimate.complexity.is_acceptable() {
+            continue;
+        }
+        let severity = if estimate.depth >= 3 {
+            Severity::Critical
+        } else {
+            Severity::Warning
+        };
+        findings.push(lint_finding(
+            LintDetector::Performance,
+            file,
+            Some(estimate.line),
+            severity,
+            format!("{} nested loops", estimate.complexity),
+            format!("Loops nested {} deep", estimate.depth),
+            Some("Replace the inner loop with a hash-based lookup or a precomputed index".into()),
+        ));
+    }
+
+    for signal in ArchitecturePattern::detect(content) {
+        let severity = match signal.principle {
+            Some(SolidPrinciple::DependencyInversion) | None => Severity::Info,
+            Some(_) => Severity::Warning,
+        };
+        let title = match signal.principle {
+            Some(principle) => principle.to_string(),
+            None => "Design pattern".to_string(),
+        };
+        findings.push(lint_finding(
+            LintDetector::Architecture,
+            file,
+            signal.line,
+            severity,
+            title,
+            signal.description,
+            signal.principle.map(|p| p.description().to_string()),
+        ));
+    }
+
+    findings
+}
+
+/// Lint a file, or every source file under a directory
+pub fn lint_path(path: &Path) -> Result<LintReport, String> {
+    let mut files = Vec::new();
+    if path.is_dir() {
+        collect_sources(path, &mut files);
+        files.sort();
+    } else if path.is_file() {
+        files.push(path.to_path_buf());
+    } else {
+        return Err(format!("Path not found: {}", path.display()));
+    }
+
+    let mut names = Vec::new();
+    let mut findings = Vec::new();
+    for file in files {
+        let name = file.to_string_lossy().to_string();
+        let Ok(content) = fs::read_to_string(&file) else {
+            continue;
+        };
+        findings.extend(lint_source(&name, &content));
+        names.push(name);
+    }
+    Ok(LintReport::from_findings(names, findings))
+}
+
+fn collect_sources(dir: &Path, files: &mut Vec<std::path::PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if path.is_dir() {
+            if !name.starts_with('.') && !SKIPPED_DIRS.contains(&name.as_str()) {
+                collect_sources(&path, files);
+            }
+        } else if path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| LINT_EXTENSIONS.contains(&e))
+        {
+            files.push(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = r#"const API_KEY = "sk_live_51HxQ2eZvKYlo2C";
+
+function findDuplicates(users, orders) {
+  const result = [];
+  for (const user of users) {
+    for (const order of orders) {
+      if (order.userId === user.id) {
+        result.push(order);
+      }
+    }
+  }
+  return result;
+}
+"#;
+
+    #[test]
+    fn test_lint_fixture_reports_secret_and_nested_loop() {
+        let dir = std::env::temp_dir().join(format!("sena-lint-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(dir.join("node_modules")).unwrap();
+        fs::write(dir.join("orders.js"), FIXTURE).unwrap();
+        fs::write(dir.join("node_modules").join("dep.js"), FIXTURE).unwrap();
+
+        let report = lint_path(&dir).unwrap();
+        assert_eq!(report.files.len(), 1);
+
+        let secret = report
+            .findings
+            .iter()
+            .find(|f| f.detector == LintDetector::Security)
+            .expect("security finding");
+        assert_eq!(secret.finding.severity, Severity::Critical);
+        assert_eq!(secret.line, Some(1));
+
+        let nested = report
+            .findings
+            .iter()
+            .find(|f| f.detector == LintDetector::Performance)
+            .expect("performance finding");
+        assert_eq!(nested.finding.severity, Severity::Warning);
+        assert_eq!(nested.line, Some(6));
+        assert!(nested
+            .finding
+            .location
+            .as_deref()
+            .unwrap()
+            .ends_with("orders.js:6"));
+
+        assert_eq!(report.findings[0].detector, LintDetector::Security);
+        assert!(report
+            .findings
+            .windows(2)
+            .all(|pair| pair[0].finding.severity >= pair[1].finding.severity));
+        assert!(report.fails_on(Severity::Critical));
+        assert!(report.fails_on(Severity::Info));
+        assert!(report.score < 100);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_clean_source_has_no_failing_findings() {
+        let findings = lint_source(
+            "sum.rs",
+            "fn sum(values: &[u32]) -> u32 {\n    let mut total = 0;\n    for v in values {\n        total += v;\n    }\n    total\n}\n",
+        );
+        let report = LintReport::from_findings(vec!["sum.rs".into()], findings);
+        assert!(!report.fails_on(Severity::Info));
+        assert_eq!(report.score, 100);
+    }
+}
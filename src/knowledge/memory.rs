@@ -4,6 +4,8 @@ use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 
+use crate::sync::write_atomic;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum MemoryLevel {
     Session,
@@ -88,8 +90,11 @@ pub struct MemorySystem {
 impl MemorySystem {
     pub fn new() -> Self {
         let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
-        let memory_file = home.join(".sena").join("memory.json");
+        Self::with_file(home.join(".sena").join("memory.json"))
+    }
 
+    /// A memory system backed by `memory_file` instead of `~/.sena/memory.json`
+    pub fn with_file(memory_file: PathBuf) -> Self {
         let mut system = Self {
             session_memory: HashMap::new(),
             project_memory: HashMap::new(),
@@ -255,7 +260,7 @@ impl MemorySystem {
             }
         }
 
-        results.sort_by(|a, b| b.access_count.cmp(&a.access_count));
+        results.sort_by_key(|b| std::cmp::Reverse(b.access_count));
         results
     }
 
@@ -320,7 +325,7 @@ impl MemorySystem {
                 .map_err(|e| format!("Failed to create memory directory: {}", e))?;
         }
 
-        fs::write(&self.memory_file, json)
+        write_atomic(&self.memory_file, json)
             .map_err(|e| format!("Failed to write memory file: {}", e))?;
 
         Ok(())
@@ -383,7 +388,7 @@ mod tests {
     fn test_memory_system_creation() {
         let system = MemorySystem::new();
         assert!(system.total_entries() > 0);
-        assert!(system.permanent_memory.len() > 0);
+        assert!(!system.permanent_memory.is_empty());
     }
 
     #[test]
@@ -402,21 +407,21 @@ mod tests {
     fn test_search() {
         let system = MemorySystem::new();
         let results = system.search("sql injection");
-        assert!(results.len() > 0);
+        assert!(!results.is_empty());
     }
 
     #[test]
     fn test_search_by_tag() {
         let system = MemorySystem::new();
         let results = system.search_by_tag("security");
-        assert!(results.len() > 0);
+        assert!(!results.is_empty());
     }
 
     #[test]
     fn test_clear_session() {
         let mut system = MemorySystem::new();
         system.store(KnowledgeEntry::new("Test", "Content", MemoryLevel::Session));
-        assert!(system.session_memory.len() > 0);
+        assert!(!system.session_memory.is_empty());
 
         system.clear_session();
         assert_eq!(system.session_memory.len(), 0);
@@ -1,16 +1,32 @@
 mod architecture;
+mod context;
+mod lint;
 mod memory;
+mod owasp;
 mod performance;
 mod reasoning;
 mod security;
 
-pub use architecture::{ArchitecturePattern, DesignPattern, SolidPrinciple};
+pub use architecture::{ArchitecturePattern, ArchitectureSignal, DesignPattern, SolidPrinciple};
+pub use context::{
+    assemble_context, estimate_tokens, AssembledContext, ContextSource, DEFAULT_CONTEXT_TOKENS,
+};
+pub use lint::{lint_path, lint_source, LintDetector, LintFinding, LintReport, LINT_EXTENSIONS};
 pub use memory::{KnowledgeEntry, MemoryLevel, MemorySystem};
-pub use performance::{ComplexityClass, OptimizationSuggestion, PerformancePattern};
-pub use reasoning::{ReasoningFramework, ThinkingMode};
-pub use security::{SecurityAudit, SecurityPattern, VulnerabilityType};
+pub use owasp::{
+    imported_dataset_path, imported_dataset_path_in, vulnerability_type_for_cwe, ImportReport,
+    OwaspDataset, OwaspEntry,
+};
+pub use performance::{
+    ComplexityClass, ComplexityEstimate, OptimizationSuggestion, PerformancePattern,
+};
+pub use reasoning::{
+    default_frameworks as default_reasoning_frameworks, ReasoningFramework, ThinkingMode,
+};
+pub use security::{AuditFinding, SecurityAudit, SecurityPattern, VulnerabilityType};
 
 use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KnowledgeSystem {
@@ -34,18 +50,77 @@ pub struct KnowledgeStats {
 
 impl KnowledgeSystem {
     pub fn new() -> Self {
+        let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+        Self::in_dir(&home.join(".sena"))
+    }
+
+    /// A knowledge system reading memories and imported datasets from
+    /// `sena_dir` instead of `~/.sena`
+    pub fn in_dir(sena_dir: &Path) -> Self {
         let mut system = Self {
-            memory: MemorySystem::new(),
+            memory: MemorySystem::with_file(sena_dir.join("memory.json")),
             reasoning_frameworks: reasoning::default_frameworks(),
             security_patterns: security::default_patterns(),
             performance_patterns: performance::default_patterns(),
             architecture_patterns: architecture::default_patterns(),
             stats: KnowledgeStats::default(),
         };
+        system.load_imported_patterns(&imported_dataset_path_in(sena_dir));
         system.update_stats();
         system
     }
 
+    fn load_imported_patterns(&mut self, path: &Path) {
+        if !path.exists() {
+            return;
+        }
+        if let Ok(dataset) = OwaspDataset::load(path) {
+            self.import_owasp(&dataset);
+        }
+    }
+
+    pub fn import_owasp(&mut self, dataset: &OwaspDataset) -> ImportReport {
+        let incoming = dataset.to_patterns();
+        let mut report = ImportReport {
+            dataset: dataset.name.clone(),
+            version: dataset.version.clone(),
+            ..Default::default()
+        };
+
+        let before = self.security_patterns.len();
+        self.security_patterns.retain(|existing| {
+            !owasp::is_owasp_pattern(existing)
+                || incoming
+                    .iter()
+                    .any(|p| p.name.eq_ignore_ascii_case(&existing.name))
+        });
+        report.removed = before - self.security_patterns.len();
+
+        for pattern in incoming {
+            match self
+                .security_patterns
+                .iter_mut()
+                .find(|existing| existing.name.eq_ignore_ascii_case(&pattern.name))
+            {
+                Some(existing) => {
+                    existing.description = pattern.description;
+                    existing.vulnerability_type = pattern.vulnerability_type;
+                    existing.severity = pattern.severity;
+                    existing.prevention = pattern.prevention;
+                    existing.source = pattern.source;
+                    report.updated += 1;
+                }
+                None => {
+                    self.security_patterns.push(pattern);
+                    report.added += 1;
+                }
+            }
+        }
+
+        self.update_stats();
+        report
+    }
+
     pub fn update_stats(&mut self) {
         self.stats = KnowledgeStats {
             total_entries: self.memory.total_entries()
@@ -239,26 +314,128 @@ fn calculate_relevance(query: &str, title: &str, description: &str) -> f64 {
 mod tests {
     use super::*;
 
+    fn temp_system() -> (KnowledgeSystem, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("sena-knowledge-{}", uuid::Uuid::new_v4()));
+        (KnowledgeSystem::in_dir(&dir), dir)
+    }
+
     #[test]
     fn test_knowledge_system_creation() {
-        let system = KnowledgeSystem::new();
+        let (system, _dir) = temp_system();
         assert!(system.stats.total_entries > 0);
-        assert!(system.reasoning_frameworks.len() > 0);
-        assert!(system.security_patterns.len() > 0);
+        assert!(!system.reasoning_frameworks.is_empty());
+        assert!(!system.security_patterns.is_empty());
     }
 
     #[test]
     fn test_knowledge_search() {
-        let system = KnowledgeSystem::new();
+        let (system, _dir) = temp_system();
         let results = system.search("sql injection");
-        assert!(results.len() > 0);
+        assert!(!results.is_empty());
         assert_eq!(results[0].domain, "security");
     }
 
+    fn fixture_dataset(version: &str) -> OwaspDataset {
+        OwaspDataset::from_json(&format!(
+            r#"{{
+                "name": "Fixture",
+                "version": "{}",
+                "entries": [
+                    {{
+                        "id": "FX-1",
+                        "name": "Fixture Deserialization Gadgets",
+                        "cwe_ids": [502],
+                        "description": "Untrusted payloads reach a deserializer.",
+                        "mitigations": ["Verify signatures before deserializing"]
+                    }},
+                    {{
+                        "id": "FX-2",
+                        "name": "Fixture Forged Requests",
+                        "cwe_ids": [352],
+                        "description": "State-changing requests lack anti-forgery tokens.",
+                        "mitigations": ["Require SameSite cookies"],
+                        "severity": 6
+                    }}
+                ]
+            }}"#,
+            version
+        ))
+        .unwrap()
+    }
+
+    fn vulnerability_of(system: &KnowledgeSystem, name: &str) -> VulnerabilityType {
+        system
+            .security_patterns
+            .iter()
+            .find(|p| p.name == name)
+            .map(|p| p.vulnerability_type)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_import_owasp_fixture() {
+        let (mut system, _dir) = temp_system();
+        system.import_owasp(&fixture_dataset("1"));
+
+        let titles: Vec<String> = system
+            .search("fixture")
+            .into_iter()
+            .filter(|r| r.domain == "security")
+            .map(|r| r.title)
+            .collect();
+        assert!(titles.contains(&"Fixture Deserialization Gadgets".to_string()));
+        assert!(titles.contains(&"Fixture Forged Requests".to_string()));
+        assert_eq!(
+            vulnerability_of(&system, "Fixture Deserialization Gadgets"),
+            VulnerabilityType::InsecureDeserialization
+        );
+        assert_eq!(
+            vulnerability_of(&system, "Fixture Forged Requests"),
+            VulnerabilityType::Csrf
+        );
+    }
+
+    #[test]
+    fn test_reimport_updates_instead_of_duplicating() {
+        let (mut system, _dir) = temp_system();
+        system.import_owasp(&fixture_dataset("1"));
+        let count = system.security_patterns.len();
+
+        let report = system.import_owasp(&fixture_dataset("2"));
+
+        assert_eq!(system.security_patterns.len(), count);
+        assert_eq!(report.added, 0);
+        assert_eq!(report.updated, 2);
+        assert!(system
+            .security_patterns
+            .iter()
+            .filter(|p| p.name.starts_with("Fixture"))
+            .all(|p| p.source.as_deref() == Some("owasp@2")));
+    }
+
+    #[test]
+    fn test_imported_dataset_loads_from_data_dir() {
+        let dir = std::env::temp_dir().join(format!("sena-knowledge-{}", uuid::Uuid::new_v4()));
+        fixture_dataset("3")
+            .save(&imported_dataset_path_in(&dir))
+            .unwrap();
+
+        let system = KnowledgeSystem::in_dir(&dir);
+        assert!(
+            system
+                .security_patterns
+                .iter()
+                .any(|p| p.name == "Fixture Forged Requests"
+                    && p.source.as_deref() == Some("owasp@3"))
+        );
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
     #[test]
     fn test_get_domain_patterns() {
-        let system = KnowledgeSystem::new();
+        let (system, _dir) = temp_system();
         let patterns = system.get_domain_patterns("reasoning");
-        assert!(patterns.len() > 0);
+        assert!(!patterns.is_empty());
     }
 }
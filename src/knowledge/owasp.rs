@@ -0,0 +1,162 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use super::security::{SecurityPattern, VulnerabilityType};
+
+const BUNDLED_TOP10: &str = include_str!("owasp_top10_2021.json");
+const OWASP_SOURCE: &str = "owasp";
+const DEFAULT_SEVERITY: u8 = 7;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OwaspDataset {
+    pub name: String,
+    pub version: String,
+    pub entries: Vec<OwaspEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OwaspEntry {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub cwe_ids: Vec<u32>,
+    pub description: String,
+    #[serde(default)]
+    pub mitigations: Vec<String>,
+    #[serde(default)]
+    pub severity: Option<u8>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub dataset: String,
+    pub version: String,
+    pub added: usize,
+    pub updated: usize,
+    pub removed: usize,
+}
+
+impl OwaspDataset {
+    pub fn bundled() -> Result<Self, String> {
+        Self::from_json(BUNDLED_TOP10)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| format!("Invalid OWASP dataset: {}", e))
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        Self::from_json(&content)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create knowledge directory: {}", e))?;
+        }
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize OWASP dataset: {}", e))?;
+        std::fs::write(path, content).map_err(|e| format!("Failed to write dataset: {}", e))
+    }
+
+    pub fn source_tag(&self) -> String {
+        format!("{}@{}", OWASP_SOURCE, self.version)
+    }
+
+    pub fn to_patterns(&self) -> Vec<SecurityPattern> {
+        let source = self.source_tag();
+        self.entries
+            .iter()
+            .map(|entry| entry.to_pattern(&source))
+            .collect()
+    }
+}
+
+impl OwaspEntry {
+    pub fn vulnerability_type(&self) -> VulnerabilityType {
+        self.cwe_ids
+            .iter()
+            .map(|cwe| vulnerability_type_for_cwe(*cwe))
+            .find(|vuln_type| *vuln_type != VulnerabilityType::Other)
+            .unwrap_or(VulnerabilityType::Other)
+    }
+
+    fn to_pattern(&self, source: &str) -> SecurityPattern {
+        let cwes = self
+            .cwe_ids
+            .iter()
+            .map(|cwe| format!("CWE-{}", cwe))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let description = if cwes.is_empty() {
+            format!("[{}] {}", self.id, self.description)
+        } else {
+            format!("[{}] {} ({})", self.id, self.description, cwes)
+        };
+
+        let mut pattern = SecurityPattern::new(&self.name, &description, self.vulnerability_type())
+            .with_severity(self.severity.unwrap_or(DEFAULT_SEVERITY))
+            .with_source(source);
+        pattern.prevention = self.mitigations.clone();
+        pattern
+    }
+}
+
+pub fn vulnerability_type_for_cwe(cwe: u32) -> VulnerabilityType {
+    match cwe {
+        89 | 564 => VulnerabilityType::SqlInjection,
+        79 | 80 | 83 => VulnerabilityType::Xss,
+        77 | 78 | 88 | 94 => VulnerabilityType::CommandInjection,
+        22 | 23 | 35 | 73 => VulnerabilityType::PathTraversal,
+        287 | 297 | 306 | 384 | 613 | 798 => VulnerabilityType::BrokenAuth,
+        200 | 259 | 311 | 312 | 319 | 327 | 328 | 532 => VulnerabilityType::DataExposure,
+        2 | 16 | 611 | 1004 => VulnerabilityType::Misconfiguration,
+        502 => VulnerabilityType::InsecureDeserialization,
+        352 => VulnerabilityType::Csrf,
+        _ => VulnerabilityType::Other,
+    }
+}
+
+pub fn imported_dataset_path() -> PathBuf {
+    imported_dataset_path_in(
+        &dirs::home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(".sena"),
+    )
+}
+
+/// Where an imported dataset lives under a `.sena` data directory
+pub fn imported_dataset_path_in(sena_dir: &Path) -> PathBuf {
+    sena_dir.join("knowledge").join("owasp.json")
+}
+
+pub fn is_owasp_pattern(pattern: &SecurityPattern) -> bool {
+    pattern
+        .source
+        .as_deref()
+        .is_some_and(|source| source.starts_with(OWASP_SOURCE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_dataset_parses() {
+        let dataset = OwaspDataset::bundled().unwrap();
+        assert_eq!(dataset.entries.len(), 10);
+        assert_eq!(dataset.source_tag(), "owasp@2021");
+    }
+
+    #[test]
+    fn test_cwe_mapping() {
+        assert_eq!(
+            vulnerability_type_for_cwe(89),
+            VulnerabilityType::SqlInjection
+        );
+        assert_eq!(vulnerability_type_for_cwe(352), VulnerabilityType::Csrf);
+        assert_eq!(vulnerability_type_for_cwe(918), VulnerabilityType::Other);
+    }
+}
@@ -154,6 +154,115 @@ pub struct OptimizationSuggestion {
     pub pattern: Option<String>,
 }
 
+/// Worst-case loop nesting of one top-level loop
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComplexityEstimate {
+    pub complexity: ComplexityClass,
+    pub depth: usize,
+    pub line: usize,
+}
+
+impl PerformancePattern {
+    /// Estimate time complexity from loop nesting, one estimate per
+    /// top-level loop. Brace-delimited code is tracked by braces, other
+    /// code (e.g. Python) by indentation. `line` points at the innermost
+    /// loop of the deepest nest.
+    pub fn estimate_complexity(content: &str) -> Vec<ComplexityEstimate> {
+        let loop_re = regex::Regex::new(r"^(for|while|loop|do)\b|\.(forEach|for_each)\s*\(")
+            .expect("valid loop pattern");
+        let is_loop = |line: &str| {
+            let trimmed = line.trim_start();
+            !(trimmed.starts_with("//") || trimmed.starts_with('#') || trimmed.starts_with('*'))
+                && loop_re.is_match(trimmed)
+        };
+        let braces = content.contains('{');
+
+        let mut estimates = Vec::new();
+        let mut stack: Vec<(usize, bool)> = Vec::new();
+        let mut deepest: Option<(usize, usize)> = None;
+        let mut depth = 0usize;
+
+        let mut flush = |deepest: &mut Option<(usize, usize)>| {
+            if let Some((nest, line)) = deepest.take() {
+                estimates.push(ComplexityEstimate {
+                    complexity: complexity_for_depth(nest),
+                    depth: nest,
+                    line,
+                });
+            }
+        };
+
+        for (index, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            if !braces {
+                let indent = line.len() - line.trim_start().len();
+                while stack.last().is_some_and(|(scope, _)| indent <= *scope) {
+                    stack.pop();
+                }
+                if stack.is_empty() {
+                    flush(&mut deepest);
+                }
+                if is_loop(line) {
+                    stack.push((indent, true));
+                    if deepest.map_or(true, |(nest, _)| stack.len() > nest) {
+                        deepest = Some((stack.len(), index + 1));
+                    }
+                }
+                continue;
+            }
+
+            if is_loop(line) {
+                stack.push((depth, false));
+                if deepest.map_or(true, |(nest, _)| stack.len() > nest) {
+                    deepest = Some((stack.len(), index + 1));
+                }
+            }
+            for c in line.chars() {
+                match c {
+                    '{' => {
+                        depth += 1;
+                        for entry in stack.iter_mut() {
+                            if !entry.1 && depth == entry.0 + 1 {
+                                entry.1 = true;
+                            }
+                        }
+                    }
+                    '}' => {
+                        depth = depth.saturating_sub(1);
+                        while stack
+                            .last()
+                            .is_some_and(|(scope, opened)| *opened && depth <= *scope)
+                        {
+                            stack.pop();
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            while stack.last().is_some_and(|(_, opened)| !opened) && line.trim_end().ends_with(';')
+            {
+                stack.pop();
+            }
+            if stack.is_empty() {
+                flush(&mut deepest);
+            }
+        }
+        flush(&mut deepest);
+        estimates
+    }
+}
+
+fn complexity_for_depth(depth: usize) -> ComplexityClass {
+    match depth {
+        0 => ComplexityClass::Constant,
+        1 => ComplexityClass::Linear,
+        2 => ComplexityClass::Quadratic,
+        _ => ComplexityClass::Cubic,
+    }
+}
+
 pub fn default_patterns() -> Vec<PerformancePattern> {
     vec![
         PerformancePattern::new(
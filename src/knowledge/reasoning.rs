@@ -61,6 +61,25 @@ impl ReasoningFramework {
         self
     }
 
+    /// Prompt text that walks through the framework's process, with a
+    /// `{problem}` placeholder for the problem to apply it to
+    pub fn template(&self) -> String {
+        let mut text = format!(
+            "Apply {} to the following problem.\n\nProblem: {{problem}}\n\n{}\n",
+            self.name, self.description
+        );
+        if !self.process.is_empty() {
+            text.push_str("\nWork through these steps, showing your reasoning for each:\n");
+            for (i, step) in self.process.iter().enumerate() {
+                text.push_str(&format!("{}. {}\n", i + 1, step));
+            }
+        }
+        if let Some(example) = &self.example {
+            text.push_str(&format!("\nExample:\n{}\n", example));
+        }
+        text
+    }
+
     pub fn analyze(&self, problem: &str) -> FrameworkAnalysis {
         FrameworkAnalysis {
             framework: self.name.clone(),
@@ -318,7 +337,7 @@ mod tests {
         let framework = &default_frameworks()[0];
         let analysis = framework.analyze("Why is the system slow?");
         assert_eq!(analysis.framework, "First Principles Thinking");
-        assert!(analysis.steps.len() > 0);
+        assert!(!analysis.steps.is_empty());
     }
 
     #[test]
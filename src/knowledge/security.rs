@@ -40,6 +40,8 @@ pub struct SecurityPattern {
     pub secure_example: String,
     pub insecure_example: String,
     pub prevention: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
 }
 
 impl SecurityPattern {
@@ -52,6 +54,7 @@ impl SecurityPattern {
             secure_example: String::new(),
             insecure_example: String::new(),
             prevention: Vec::new(),
+            source: None,
         }
     }
 
@@ -75,6 +78,11 @@ impl SecurityPattern {
         self
     }
 
+    pub fn with_source(mut self, source: &str) -> Self {
+        self.source = Some(source.to_string());
+        self
+    }
+
     pub fn with_preventions(mut self, tips: &[&str]) -> Self {
         for tip in tips {
             self.prevention.push(tip.to_string());
@@ -137,6 +145,100 @@ pub struct AuditFinding {
     pub code_snippet: Option<String>,
 }
 
+const SCAN_RULES: &[(&str, VulnerabilityType, u8, &str)] = &[
+    (
+        r#"(?i)\b(password|passwd|secret|api_?key|access_?token|auth_?token|private_?key)\b\s*[:=]\s*["'][^"']{6,}["']"#,
+        VulnerabilityType::DataExposure,
+        9,
+        "Hardcoded secret",
+    ),
+    (
+        r"\b(AKIA[0-9A-Z]{16}|sk-[A-Za-z0-9_-]{20,}|ghp_[A-Za-z0-9]{36}|xox[bp]-[A-Za-z0-9-]{10,})\b",
+        VulnerabilityType::DataExposure,
+        10,
+        "Credential-shaped literal",
+    ),
+    (
+        r#"(?i)["'](SELECT|INSERT|UPDATE|DELETE)\b[^"']*["']\s*(\+|%|\.format\b)|(?i)format!\(\s*"(SELECT|INSERT|UPDATE|DELETE)\b[^"]*\{"#,
+        VulnerabilityType::SqlInjection,
+        9,
+        "SQL built from string concatenation",
+    ),
+    (
+        r"\b(eval|exec)\s*\(|\bos\.system\s*\(|shell\s*=\s*True",
+        VulnerabilityType::CommandInjection,
+        8,
+        "Dynamic code or shell execution",
+    ),
+    (
+        r"\.innerHTML\s*=|dangerouslySetInnerHTML|document\.write\s*\(",
+        VulnerabilityType::Xss,
+        7,
+        "Unescaped HTML output",
+    ),
+    (
+        r"(?i)\b(md5|sha1)\s*\(|verify\s*=\s*False|danger_accept_invalid_certs\s*\(\s*true",
+        VulnerabilityType::Misconfiguration,
+        6,
+        "Weak hashing or disabled TLS verification",
+    ),
+    (
+        r"\bpickle\.loads?\s*\(|\byaml\.load\s*\([^)]*\)\s*$|ObjectInputStream",
+        VulnerabilityType::InsecureDeserialization,
+        7,
+        "Deserialization of untrusted data",
+    ),
+];
+
+impl SecurityAudit {
+    /// Line-based scan of one source file for common vulnerability signatures
+    pub fn scan(file: &str, content: &str) -> Self {
+        let rules: Vec<(regex::Regex, VulnerabilityType, u8, &str)> = SCAN_RULES
+            .iter()
+            .filter_map(|(pattern, vuln_type, severity, description)| {
+                regex::Regex::new(pattern)
+                    .ok()
+                    .map(|re| (re, *vuln_type, *severity, *description))
+            })
+            .collect();
+
+        let mut vulnerabilities = Vec::new();
+        for (index, line) in content.lines().enumerate() {
+            let trimmed = line.trim();
+            for (re, vuln_type, severity, description) in &rules {
+                if re.is_match(trimmed) {
+                    vulnerabilities.push(AuditFinding {
+                        vulnerability_type: *vuln_type,
+                        file: file.to_string(),
+                        line: Some(index + 1),
+                        description: description.to_string(),
+                        severity: *severity,
+                        code_snippet: Some(trimmed.chars().take(120).collect()),
+                    });
+                    break;
+                }
+            }
+        }
+
+        let penalty: u32 = vulnerabilities.iter().map(|v| v.severity as u32 * 3).sum();
+        let mut recommendations: Vec<String> = Vec::new();
+        for finding in &vulnerabilities {
+            let recommendation = format!("Review {} issues", finding.vulnerability_type);
+            if !recommendations.contains(&recommendation) {
+                recommendations.push(recommendation);
+            }
+        }
+
+        Self {
+            name: file.to_string(),
+            files_checked: vec![file.to_string()],
+            vulnerabilities,
+            score: 100u32.saturating_sub(penalty) as u8,
+            recommendations,
+        }
+    }
+}
+
 pub fn default_patterns() -> Vec<SecurityPattern> {
     vec![
         SecurityPattern::new(
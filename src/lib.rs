@@ -90,14 +90,17 @@ pub mod memory;
 pub mod metrics;
 pub mod network;
 pub mod output;
+pub mod result_diff;
 pub mod sync;
 pub mod tools;
+pub mod trace;
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use thiserror::Error;
+use tokio_util::sync::CancellationToken;
 
 // Re-export ancient wisdom layers
 pub use ancient::*;
@@ -121,7 +124,7 @@ pub use metrics::{SenaHealth, SenaMetrics};
 pub use integration::{AutoIntegration, FormatType};
 
 // Re-export CLI
-pub use cli::{execute_command, Cli, Commands, HookType};
+pub use cli::{execute_command, Cli, CliError, Commands, HookType};
 
 // Re-export MCP
 pub use mcp::run_server;
@@ -131,8 +134,8 @@ pub use hooks::{handle_hook, HookResult};
 
 // Re-export output formatting
 pub use output::{
-    ansi, render_progress_box, FormatBox, LiveProgress, MultiProgress, ProgressBar, ProgressConfig,
-    Spinner, TableBuilder,
+    ansi, render_progress_box, CellOverflow, FormatBox, LiveProgress, MultiProgress, ProgressBar,
+    ProgressConfig, Spinner, TableBuilder,
 };
 
 // Re-export collaboration hub
@@ -147,10 +150,10 @@ pub use hub::{HubClient, HubServer};
 
 // Re-export knowledge system
 pub use knowledge::{
-    ArchitecturePattern, ComplexityClass, DesignPattern, KnowledgeEntry, KnowledgeStats,
-    KnowledgeSystem, MemoryLevel, MemorySystem, OptimizationSuggestion, PerformancePattern,
-    ReasoningFramework, SearchResult, SecurityAudit, SecurityPattern, SolidPrinciple, ThinkingMode,
-    VulnerabilityType,
+    ArchitecturePattern, ComplexityClass, DesignPattern, ImportReport, KnowledgeEntry,
+    KnowledgeStats, KnowledgeSystem, MemoryLevel, MemorySystem, OptimizationSuggestion,
+    OwaspDataset, OwaspEntry, PerformancePattern, ReasoningFramework, SearchResult, SecurityAudit,
+    SecurityPattern, SolidPrinciple, ThinkingMode, VulnerabilityType,
 };
 
 // Re-export intelligence system
@@ -169,10 +172,14 @@ pub use evolution::{
 
 // Re-export domain agents
 pub use agents::{
-    AndroidAgent, BackendAgent, DomainAgentPool, DomainAgentType, DomainAnalysis, Finding,
-    IOSAgent, IoTAgent, Severity, WebAgent,
+    AnalysisContext, AndroidAgent, BackendAgent, DomainAgentPool, DomainAgentType, DomainAnalysis,
+    Finding, IOSAgent, IoTAgent, Severity, WebAgent,
 };
 
+// Re-export result diffing
+pub use result_diff::{PhaseDiff, ResultDiff, ScoreChange};
+pub use trace::{Span, SpanStatus};
+
 // Re-export tools system
 pub use tools::{
     BuiltinTools, ToolCall, ToolCategory, ToolDefinition, ToolError, ToolExecutor, ToolRegistry,
@@ -331,12 +338,17 @@ pub struct ProcessingResult {
     pub id: String,
     pub request_id: String,
     pub success: bool,
+    /// Stopped early by a cancellation token; `success` is false
+    #[serde(default)]
+    pub cancelled: bool,
     pub content: String,
     pub phase_results: HashMap<String, PhaseResult>,
     pub validation_score: f64,
     pub safety_score: f64,
     pub harmony_score: f64,
-    pub processing_time_ms: u64,
+    /// Total processing time, at the precision phase spans are timed
+    #[serde(default)]
+    pub processing_time_us: u64,
     pub timestamp: DateTime<Utc>,
     pub warnings: Vec<String>,
     pub errors: Vec<String>,
@@ -354,12 +366,13 @@ impl ProcessingResult {
             id,
             request_id,
             success: false,
+            cancelled: false,
             content: String::new(),
             phase_results: HashMap::new(),
             validation_score: 0.0,
             safety_score: 0.0,
             harmony_score: 0.0,
-            processing_time_ms: 0,
+            processing_time_us: 0,
             timestamp: Utc::now(),
             warnings: Vec::new(),
             errors: Vec::new(),
@@ -369,16 +382,129 @@ impl ProcessingResult {
     pub fn overall_score(&self) -> f64 {
         (self.validation_score + self.safety_score + self.harmony_score) / 3.0
     }
+
+    /// What changed from this result to `other`, with regressions called out
+    pub fn diff(&self, other: &ProcessingResult) -> ResultDiff {
+        ResultDiff::between(self, other)
+    }
+
+    pub fn processing_time_ms(&self) -> u64 {
+        self.processing_time_us / 1000
+    }
+
+    /// A root span covering the request and one child span per phase that
+    /// ran; see [`trace`]
+    pub fn to_trace(&self) -> Vec<Span> {
+        trace::spans(self)
+    }
+
+    fn finish(&mut self, started: std::time::Instant) {
+        self.processing_time_us = started.elapsed().as_micros() as u64;
+    }
 }
 
 /// Result of a single processing phase
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct PhaseResult {
     pub phase: String,
     pub success: bool,
-    pub duration_ms: u64,
     pub output: HashMap<String, String>,
     pub score: f64,
+    /// When the phase started, in microseconds since processing began
+    #[serde(default)]
+    pub start_offset_us: u64,
+    #[serde(default)]
+    pub duration_us: u64,
+    /// Disabled by the pipeline; the phase did not run
+    #[serde(default)]
+    pub skipped: bool,
+}
+
+impl PhaseResult {
+    /// Placeholder for a phase the pipeline disabled
+    pub fn skipped(phase: ProcessingPhase) -> Self {
+        Self {
+            phase: phase.name().to_lowercase(),
+            success: true,
+            skipped: true,
+            ..Default::default()
+        }
+    }
+}
+
+/// Which phases [`SenaUnifiedSystem::process_with`] runs. Safety cannot be
+/// disabled.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PipelineConfig {
+    pub enabled_phases: HashSet<ProcessingPhase>,
+}
+
+impl Default for PipelineConfig {
+    fn default() -> Self {
+        Self {
+            enabled_phases: ProcessingPhase::all().into_iter().collect(),
+        }
+    }
+}
+
+impl PipelineConfig {
+    /// Only `phases`, plus Safety
+    pub fn only(phases: impl IntoIterator<Item = ProcessingPhase>) -> Self {
+        let mut enabled_phases: HashSet<_> = phases.into_iter().collect();
+        enabled_phases.insert(ProcessingPhase::Safety);
+        Self { enabled_phases }
+    }
+
+    pub fn without(mut self, phase: ProcessingPhase) -> Self {
+        self.enabled_phases.remove(&phase);
+        self
+    }
+
+    pub fn is_enabled(&self, phase: ProcessingPhase) -> bool {
+        self.enabled_phases.contains(&phase)
+    }
+
+    pub fn validate(&self) -> Result<(), SenaError> {
+        if !self.is_enabled(ProcessingPhase::Safety) {
+            return Err(SenaError::ConfigurationError(
+                "the Safety phase cannot be disabled".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Run `phase` through [`timed_phase`] when enabled, otherwise record it
+    /// as skipped
+    fn run(
+        &self,
+        phase: ProcessingPhase,
+        process_start: std::time::Instant,
+        run: impl FnOnce() -> PhaseResult,
+    ) -> PhaseResult {
+        if self.is_enabled(phase) {
+            timed_phase(process_start, run)
+        } else {
+            PhaseResult::skipped(phase)
+        }
+    }
+}
+
+/// `Some(phase)` once `cancel` has fired, naming the phase that won't run
+fn cancelled_before(cancel: &CancellationToken, phase: ProcessingPhase) -> Option<ProcessingPhase> {
+    cancel.is_cancelled().then_some(phase)
+}
+
+/// Run a phase, recording when it started relative to `process_start` and
+/// how long it took
+fn timed_phase(
+    process_start: std::time::Instant,
+    run: impl FnOnce() -> PhaseResult,
+) -> PhaseResult {
+    let started = std::time::Instant::now();
+    let mut phase = run();
+    phase.start_offset_us = started.duration_since(process_start).as_micros() as u64;
+    phase.duration_us = started.elapsed().as_micros() as u64;
+    phase
 }
 
 /// The unified SENA system integrating all capabilities
@@ -409,6 +535,8 @@ pub struct SenaUnifiedSystem {
     request_count: u64,
     successful_count: u64,
     failed_count: u64,
+    /// Requests stopped by their cancellation token
+    cancelled_count: u64,
     created_at: DateTime<Utc>,
 }
 
@@ -430,61 +558,154 @@ impl SenaUnifiedSystem {
             harmony_validation: HarmonyValidationEngine::new(),
             millennium_test: MillenniumTestFramework::new(),
             knowledge_system: KnowledgeSystem::new(),
-            intelligence_system: IntelligenceSystem::new(),
+            intelligence_system: IntelligenceSystem::configured(),
             evolution_system: EvolutionSystem::new(),
             request_count: 0,
             successful_count: 0,
             failed_count: 0,
+            cancelled_count: 0,
             created_at: Utc::now(),
         }
     }
 
+    /// Load the learned patterns, feedback and stats saved under
+    /// `~/.sena/evolution`, so processing can offer what worked before
+    pub fn load(&mut self) -> Result<(), String> {
+        self.evolution_system.load()
+    }
+
     /// Process a request through all layers
     pub async fn process(&mut self, request: ProcessingRequest) -> ProcessingResult {
+        self.process_cancellable(request, &CancellationToken::new())
+            .await
+    }
+
+    /// Process a request, stopping between phases once `cancel` fires. The
+    /// result is then marked cancelled and keeps the phases that finished.
+    /// Safety runs even after cancellation, so nothing produced so far goes
+    /// unchecked.
+    pub async fn process_cancellable(
+        &mut self,
+        request: ProcessingRequest,
+        cancel: &CancellationToken,
+    ) -> ProcessingResult {
+        self.process_observed(request, cancel, |_| {}).await
+    }
+
+    /// Like [`process_cancellable`](Self::process_cancellable), calling
+    /// `on_phase` as each phase finishes
+    pub async fn process_observed(
+        &mut self,
+        request: ProcessingRequest,
+        cancel: &CancellationToken,
+        on_phase: impl FnMut(ProcessingPhase),
+    ) -> ProcessingResult {
+        self.run_pipeline(request, &PipelineConfig::default(), cancel, on_phase)
+    }
+
+    /// Process a request through only the phases `pipeline` enables; the
+    /// rest are recorded as skipped
+    pub async fn process_with(
+        &mut self,
+        request: ProcessingRequest,
+        pipeline: &PipelineConfig,
+    ) -> Result<ProcessingResult, SenaError> {
+        pipeline.validate()?;
+        Ok(self.run_pipeline(request, pipeline, &CancellationToken::new(), |_| {}))
+    }
+
+    fn run_pipeline(
+        &mut self,
+        request: ProcessingRequest,
+        pipeline: &PipelineConfig,
+        cancel: &CancellationToken,
+        mut on_phase: impl FnMut(ProcessingPhase),
+    ) -> ProcessingResult {
         let start_time = std::time::Instant::now();
         let mut result = ProcessingResult::new(&request.id);
 
         self.request_count += 1;
 
         // Phase 1: Intake
-        let intake_result = self.phase_intake(&request);
-        result
-            .phase_results
-            .insert("intake".to_string(), intake_result);
+        let mut stopped_at = cancelled_before(cancel, ProcessingPhase::Intake);
+        if stopped_at.is_none() {
+            let intake_result = pipeline.run(ProcessingPhase::Intake, start_time, || {
+                self.phase_intake(&request)
+            });
+            result
+                .phase_results
+                .insert("intake".to_string(), intake_result);
+            on_phase(ProcessingPhase::Intake);
+        }
 
         // Phase 2: Analysis (First Principles)
-        let analysis_result = self.phase_analysis(&request);
-        result
-            .phase_results
-            .insert("analysis".to_string(), analysis_result);
+        stopped_at = stopped_at.or_else(|| cancelled_before(cancel, ProcessingPhase::Analysis));
+        if stopped_at.is_none() {
+            let analysis_result = pipeline.run(ProcessingPhase::Analysis, start_time, || {
+                self.phase_analysis(&request)
+            });
+            result
+                .phase_results
+                .insert("analysis".to_string(), analysis_result);
+            on_phase(ProcessingPhase::Analysis);
+        }
 
         // Phase 3: Constraint (Transform constraints to features)
-        let constraint_result = self.phase_constraint(&request);
-        result
-            .phase_results
-            .insert("constraint".to_string(), constraint_result);
+        stopped_at = stopped_at.or_else(|| cancelled_before(cancel, ProcessingPhase::Constraint));
+        if stopped_at.is_none() {
+            let constraint_result = pipeline.run(ProcessingPhase::Constraint, start_time, || {
+                self.phase_constraint(&request)
+            });
+            result
+                .phase_results
+                .insert("constraint".to_string(), constraint_result);
+            on_phase(ProcessingPhase::Constraint);
+        }
 
-        // Phase 4: Safety (Negative Space check)
-        let safety_result = self.phase_safety(&request);
+        // Phase 4: Safety (Negative Space check), run even when cancelled
+        let safety_result = timed_phase(start_time, || self.phase_safety(&request));
         result.safety_score = safety_result.score;
-        if !safety_result.success {
-            result.errors.push("Safety check failed".to_string());
-            result.processing_time_ms = start_time.elapsed().as_millis() as u64;
-            self.failed_count += 1;
-            return result;
-        }
+        let safe = safety_result.success;
+        let reasons = safety_result
+            .output
+            .get("reasons")
+            .filter(|reasons| !reasons.is_empty())
+            .cloned();
         result
             .phase_results
             .insert("safety".to_string(), safety_result);
+        on_phase(ProcessingPhase::Safety);
+        if !safe {
+            result.errors.push(match reasons {
+                Some(reasons) => format!("Safety check failed: {}", reasons),
+                None => "Safety check failed".to_string(),
+            });
+            result.finish(start_time);
+            self.failed_count += 1;
+            return result;
+        }
 
         // Phase 5: Context (Relationship building)
-        let context_result = self.phase_context(&request);
+        if let Some(phase) =
+            stopped_at.or_else(|| cancelled_before(cancel, ProcessingPhase::Context))
+        {
+            return self.stop_cancelled(result, start_time, phase);
+        }
+        let context_result = pipeline.run(ProcessingPhase::Context, start_time, || {
+            self.phase_context(&request)
+        });
         result
             .phase_results
             .insert("context".to_string(), context_result);
+        on_phase(ProcessingPhase::Context);
 
         // Phase 6: Generation
-        let generation_result = self.phase_generation(&request);
+        if let Some(phase) = cancelled_before(cancel, ProcessingPhase::Generation) {
+            return self.stop_cancelled(result, start_time, phase);
+        }
+        let generation_result = pipeline.run(ProcessingPhase::Generation, start_time, || {
+            self.phase_generation(&request)
+        });
         result.content = generation_result
             .output
             .get("response")
@@ -493,30 +714,63 @@ impl SenaUnifiedSystem {
         result
             .phase_results
             .insert("generation".to_string(), generation_result);
+        on_phase(ProcessingPhase::Generation);
 
         // Phase 7: Validation (Harmony check)
-        let validation_result = self.phase_validation(&result.content);
-        result.harmony_score = validation_result.score;
-        result.validation_score = validation_result.score;
+        if let Some(phase) = cancelled_before(cancel, ProcessingPhase::Validation) {
+            return self.stop_cancelled(result, start_time, phase);
+        }
+        let validation_result = pipeline.run(ProcessingPhase::Validation, start_time, || {
+            self.phase_validation(&result.content)
+        });
+        // A skipped check leaves the scores unset rather than scoring zero
+        if !validation_result.skipped {
+            result.harmony_score = validation_result.score;
+            result.validation_score = validation_result.score;
+        }
         result
             .phase_results
             .insert("validation".to_string(), validation_result);
+        on_phase(ProcessingPhase::Validation);
 
         // Phase 8: Delivery
-        let delivery_result = self.phase_delivery(&mut result);
+        if let Some(phase) = cancelled_before(cancel, ProcessingPhase::Delivery) {
+            return self.stop_cancelled(result, start_time, phase);
+        }
+        let delivery_result = pipeline.run(ProcessingPhase::Delivery, start_time, || {
+            self.phase_delivery(&mut result)
+        });
         result
             .phase_results
             .insert("delivery".to_string(), delivery_result);
+        on_phase(ProcessingPhase::Delivery);
 
-        result.processing_time_ms = start_time.elapsed().as_millis() as u64;
+        result.finish(start_time);
         result.success = true;
         self.successful_count += 1;
 
         result
     }
 
+    /// Close out a request cancelled before `phase`; it counts as neither
+    /// successful nor failed
+    fn stop_cancelled(
+        &mut self,
+        mut result: ProcessingResult,
+        start_time: std::time::Instant,
+        phase: ProcessingPhase,
+    ) -> ProcessingResult {
+        result.cancelled = true;
+        result.errors.push(format!(
+            "Cancelled before the {} phase",
+            phase.name().to_lowercase()
+        ));
+        result.finish(start_time);
+        self.cancelled_count += 1;
+        result
+    }
+
     fn phase_intake(&self, request: &ProcessingRequest) -> PhaseResult {
-        let start = std::time::Instant::now();
         let mut output = HashMap::new();
 
         output.insert("request_type".to_string(), request.request_type.clone());
@@ -529,14 +783,13 @@ impl SenaUnifiedSystem {
         PhaseResult {
             phase: "intake".to_string(),
             success: true,
-            duration_ms: start.elapsed().as_millis() as u64,
             output,
             score: 1.0,
+            ..Default::default()
         }
     }
 
     fn phase_analysis(&mut self, request: &ProcessingRequest) -> PhaseResult {
-        let start = std::time::Instant::now();
         let mut output = HashMap::new();
 
         // Use first principles engine
@@ -561,14 +814,13 @@ impl SenaUnifiedSystem {
         PhaseResult {
             phase: "analysis".to_string(),
             success: true,
-            duration_ms: start.elapsed().as_millis() as u64,
             output,
             score: 0.9,
+            ..Default::default()
         }
     }
 
     fn phase_constraint(&mut self, request: &ProcessingRequest) -> PhaseResult {
-        let start = std::time::Instant::now();
         let mut output = HashMap::new();
 
         // Identify and register a constraint from the request
@@ -589,14 +841,13 @@ impl SenaUnifiedSystem {
         PhaseResult {
             phase: "constraint".to_string(),
             success: true,
-            duration_ms: start.elapsed().as_millis() as u64,
             output,
             score: 0.85,
+            ..Default::default()
         }
     }
 
     fn phase_safety(&mut self, request: &ProcessingRequest) -> PhaseResult {
-        let start = std::time::Instant::now();
         let mut output = HashMap::new();
 
         // Check against negative space
@@ -613,6 +864,10 @@ impl SenaUnifiedSystem {
             "risk_score".to_string(),
             format!("{:.2}", check_result.risk_score),
         );
+        output.insert(
+            "reasons".to_string(),
+            check_result.recommendations.join("; "),
+        );
 
         let score = if check_result.allowed {
             1.0 - check_result.risk_score
@@ -623,14 +878,13 @@ impl SenaUnifiedSystem {
         PhaseResult {
             phase: "safety".to_string(),
             success: check_result.allowed,
-            duration_ms: start.elapsed().as_millis() as u64,
             output,
             score,
+            ..Default::default()
         }
     }
 
     fn phase_context(&mut self, request: &ProcessingRequest) -> PhaseResult {
-        let start = std::time::Instant::now();
         let mut output = HashMap::new();
 
         // Build context in relationship model
@@ -647,14 +901,13 @@ impl SenaUnifiedSystem {
         PhaseResult {
             phase: "context".to_string(),
             success: true,
-            duration_ms: start.elapsed().as_millis() as u64,
             output,
             score: 0.9,
+            ..Default::default()
         }
     }
 
     fn phase_generation(&self, request: &ProcessingRequest) -> PhaseResult {
-        let start = std::time::Instant::now();
         let mut output = HashMap::new();
 
         // Generate response (simplified - in real implementation, this would involve LLM)
@@ -666,17 +919,25 @@ impl SenaUnifiedSystem {
         output.insert("response".to_string(), response);
         output.insert("generation_method".to_string(), "direct".to_string());
 
+        // Offer what worked before in a similar situation
+        if let Some(suggestion) = self.evolution_system.suggest(&request.content) {
+            output.insert("learned_hint".to_string(), suggestion.pattern.outcome);
+            output.insert(
+                "learned_hint_confidence".to_string(),
+                format!("{:.2}", suggestion.confidence),
+            );
+        }
+
         PhaseResult {
             phase: "generation".to_string(),
             success: true,
-            duration_ms: start.elapsed().as_millis() as u64,
             output,
             score: 0.95,
+            ..Default::default()
         }
     }
 
     fn phase_validation(&mut self, content: &str) -> PhaseResult {
-        let start = std::time::Instant::now();
         let mut output = HashMap::new();
 
         // Validate harmony
@@ -698,18 +959,22 @@ impl SenaUnifiedSystem {
         PhaseResult {
             phase: "validation".to_string(),
             success: validation.is_valid(),
-            duration_ms: start.elapsed().as_millis() as u64,
             output,
             score: validation.overall_confidence,
+            ..Default::default()
         }
     }
 
     fn phase_delivery(&self, result: &mut ProcessingResult) -> PhaseResult {
-        let start = std::time::Instant::now();
         let output = HashMap::new();
 
-        // Calculate final scores
-        let phase_scores: Vec<f64> = result.phase_results.values().map(|p| p.score).collect();
+        // Calculate final scores over the phases that ran
+        let phase_scores: Vec<f64> = result
+            .phase_results
+            .values()
+            .filter(|p| !p.skipped)
+            .map(|p| p.score)
+            .collect();
         let avg_score = if phase_scores.is_empty() {
             0.0
         } else {
@@ -721,9 +986,9 @@ impl SenaUnifiedSystem {
         PhaseResult {
             phase: "delivery".to_string(),
             success: true,
-            duration_ms: start.elapsed().as_millis() as u64,
             output,
             score: 1.0,
+            ..Default::default()
         }
     }
 
@@ -739,8 +1004,10 @@ impl SenaUnifiedSystem {
         let harmony_stats = self.harmony_validation.get_statistics();
         let millennium_stats = self.millennium_test.get_statistics();
 
-        let success_rate = if self.request_count > 0 {
-            self.successful_count as f64 / self.request_count as f64
+        // Cancelled requests never finished, so they don't count against it
+        let finished = self.request_count - self.cancelled_count;
+        let success_rate = if finished > 0 {
+            self.successful_count as f64 / finished as f64
         } else {
             1.0
         };
@@ -753,6 +1020,7 @@ impl SenaUnifiedSystem {
             request_count: self.request_count,
             successful_count: self.successful_count,
             failed_count: self.failed_count,
+            cancelled_count: self.cancelled_count,
             success_rate,
             healing_stats,
             harmony_stats,
@@ -827,6 +1095,11 @@ impl SenaUnifiedSystem {
         self.intelligence_system.dispatch(task, agent_type)
     }
 
+    /// Dispatch to an automatically selected agent
+    pub fn dispatch_agent_auto(&self, task: &str) -> AgentResult {
+        self.intelligence_system.dispatch_auto(task)
+    }
+
     /// Learn from interaction
     pub fn learn(&mut self, context: &str, outcome: &str, success: bool) {
         self.evolution_system.learn(context, outcome, success);
@@ -848,6 +1121,8 @@ pub struct SystemReport {
     pub request_count: u64,
     pub successful_count: u64,
     pub failed_count: u64,
+    #[serde(default)]
+    pub cancelled_count: u64,
     pub success_rate: f64,
     pub healing_stats: HealingStatistics,
     pub harmony_stats: HarmonyStatistics,
@@ -881,7 +1156,7 @@ mod tests {
 
         assert!(result.success);
         assert!(!result.content.is_empty());
-        assert!(result.processing_time_ms > 0);
+        assert!(result.processing_time_us > 0);
     }
 
     #[tokio::test]
@@ -898,6 +1173,119 @@ mod tests {
         assert_eq!(report.successful_count, 1);
     }
 
+    #[tokio::test]
+    async fn test_cancel_before_generation_keeps_earlier_phases() {
+        let mut system = SenaUnifiedSystem::new();
+        let cancel = CancellationToken::new();
+
+        let result = system
+            .process_observed(
+                ProcessingRequest::new("Stop part way", "test"),
+                &cancel,
+                |phase| {
+                    if phase == ProcessingPhase::Context {
+                        cancel.cancel();
+                    }
+                },
+            )
+            .await;
+
+        assert!(result.cancelled);
+        assert!(!result.success);
+        assert!(result.content.is_empty());
+        assert_eq!(result.errors, vec!["Cancelled before the generation phase"]);
+        let mut phases: Vec<&str> = result.phase_results.keys().map(String::as_str).collect();
+        phases.sort_unstable();
+        assert_eq!(
+            phases,
+            vec!["analysis", "constraint", "context", "intake", "safety"]
+        );
+
+        let report = system.get_system_report();
+        assert_eq!(report.request_count, 1);
+        assert_eq!(report.successful_count, 0);
+        assert_eq!(report.failed_count, 0);
+        assert_eq!(report.cancelled_count, 1);
+        assert_eq!(report.success_rate, 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_safety_runs_when_cancelled_up_front() {
+        let mut system = SenaUnifiedSystem::new();
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+
+        let result = system
+            .process_cancellable(ProcessingRequest::new("Never started", "test"), &cancel)
+            .await;
+
+        assert!(result.cancelled);
+        assert_eq!(result.errors, vec!["Cancelled before the intake phase"]);
+        assert_eq!(
+            result.phase_results.keys().collect::<Vec<_>>(),
+            vec!["safety"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_minimal_pipeline_records_skipped_phases() {
+        let mut system = SenaUnifiedSystem::new();
+        let pipeline = PipelineConfig::only([
+            ProcessingPhase::Intake,
+            ProcessingPhase::Generation,
+            ProcessingPhase::Delivery,
+        ]);
+
+        let result = system
+            .process_with(
+                ProcessingRequest::new("Trusted internal call", "internal"),
+                &pipeline,
+            )
+            .await
+            .unwrap();
+
+        assert!(result.success);
+        assert!(!result.content.is_empty());
+        assert_eq!(result.phase_results.len(), 8);
+        let mut skipped: Vec<&str> = result
+            .phase_results
+            .values()
+            .filter(|p| p.skipped)
+            .map(|p| p.phase.as_str())
+            .collect();
+        skipped.sort_unstable();
+        assert_eq!(
+            skipped,
+            vec!["analysis", "constraint", "context", "validation"]
+        );
+        assert!(!result.phase_results["safety"].skipped);
+        // No harmony check ran; the validation score covers the phases that did
+        assert_eq!(result.harmony_score, 0.0);
+        assert!(result.validation_score > 0.0);
+
+        let phases: Vec<String> = result.to_trace()[1..]
+            .iter()
+            .map(|s| s.name.clone())
+            .collect();
+        assert_eq!(phases, vec!["intake", "safety", "generation", "delivery"]);
+    }
+
+    #[tokio::test]
+    async fn test_safety_cannot_be_disabled() {
+        let mut system = SenaUnifiedSystem::new();
+        let pipeline = PipelineConfig::default().without(ProcessingPhase::Safety);
+
+        let result = system
+            .process_with(
+                ProcessingRequest::new("Skip the checks", "internal"),
+                &pipeline,
+            )
+            .await;
+
+        assert!(matches!(result, Err(SenaError::ConfigurationError(_))));
+        assert_eq!(system.get_system_report().request_count, 0);
+    }
+
     #[test]
     fn test_processing_phases() {
         let phases = ProcessingPhase::all();
@@ -12,29 +12,54 @@
 
 use clap::Parser;
 use sena1996_ai::{
-    config::SenaConfig, create_system, execute_command, Cli, ProcessingRequest, SystemHealth,
-    CODENAME, VERSION,
+    config::SenaConfig, create_system, execute_command, Cli, Commands, ProcessingRequest,
+    SystemHealth, CODENAME, VERSION,
 };
 use std::io::{self, BufRead, Write};
 
 #[tokio::main]
 async fn main() {
-    // Initialize logger
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
-
     // Parse CLI arguments
     let cli = Cli::parse();
 
+    // Initialize logger. Logs always go to stderr: in MCP mode stdout is the
+    // JSON-RPC channel
+    let default_filter = match cli.command {
+        Some(Commands::Mcp { debug: true }) => log::LevelFilter::Debug,
+        _ => log::LevelFilter::Warn,
+    };
+    let rust_log = std::env::var("RUST_LOG").ok();
+    let mut logger = env_logger::Builder::new();
+    logger
+        .filter_level(log::LevelFilter::Trace)
+        .target(env_logger::Target::Stderr);
+    if let Some(spec) = &rust_log {
+        logger.parse_filters(spec);
+    }
+    logger.init();
+    // Without RUST_LOG the logger passes everything and the global max level
+    // filters, so the daemon can change it on a config reload
+    if rust_log.is_none() {
+        log::set_max_level(default_filter);
+    }
+
+    // Remove temp files left behind by interrupted writes
+    for orphan in sena1996_ai::sync::atomic::cleanup_sena_dir() {
+        log::debug!("Removed orphaned temp file {}", orphan.display());
+    }
+
     // If a command is provided, execute it
     if cli.command.is_some() {
         match execute_command(&cli).await {
             Ok(output) => {
-                println!("{}", output);
+                if !output.is_empty() {
+                    println!("{}", output);
+                }
                 std::process::exit(0);
             }
             Err(e) => {
                 eprintln!("Error: {}", e);
-                std::process::exit(1);
+                std::process::exit(e.exit_code());
             }
         }
     }
@@ -289,7 +314,7 @@ async fn run_interactive() {
             println!("{}", result.content);
             println!();
             println!("────────────────────────────────────────────────────────────────");
-            println!("Processing Time: {}ms", result.processing_time_ms);
+            println!("Processing Time: {}ms", result.processing_time_ms());
             println!("Safety Score: {:.1}%", result.safety_score * 100.0);
             println!("Harmony Score: {:.1}%", result.harmony_score * 100.0);
             println!("Overall Score: {:.1}%", result.overall_score() * 100.0);
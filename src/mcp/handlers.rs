@@ -2,22 +2,34 @@
 //!
 //! Handles MCP protocol requests
 
+use super::prompts::{self, PromptCatalog};
 use super::protocol::*;
+use super::resources::{self, ResourceCatalog, RESOURCES_PAGE_SIZE};
 use crate::ancient::HarmonyValidationEngine;
 use crate::config::SenaConfig;
 use crate::integration::AutoIntegration;
+use crate::memory::PersistentMemory;
 use crate::metrics::SenaHealth;
 use std::collections::HashMap;
+use std::path::Path;
 
 /// Handle MCP requests
 pub fn handle_request(request: &JsonRpcRequest) -> JsonRpcResponse {
+    handle_request_in(request, &PersistentMemory::default_dir())
+}
+
+/// Handle MCP requests, serving the memories stored in `memory_dir` as
+/// resources
+pub fn handle_request_in(request: &JsonRpcRequest, memory_dir: &Path) -> JsonRpcResponse {
     match request.method.as_str() {
         "initialize" => handle_initialize(request),
         "initialized" => handle_initialized(request),
         "tools/list" => handle_tools_list(request),
         "tools/call" => handle_tools_call(request),
-        "resources/list" => handle_resources_list(request),
-        "resources/read" => handle_resources_read(request),
+        "resources/list" => handle_resources_list(request, &ResourceCatalog::cached(memory_dir)),
+        "resources/read" => handle_resources_read(request, &ResourceCatalog::cached(memory_dir)),
+        "prompts/list" => handle_prompts_list(request),
+        "prompts/get" => handle_prompts_get(request),
         "ping" => handle_ping(request),
         _ => JsonRpcResponse::error(
             request.id.clone(),
@@ -41,11 +53,8 @@ fn handle_initialize(request: &JsonRpcRequest) -> JsonRpcResponse {
             tools: Some(ToolsCapability {
                 list_changed: false,
             }),
-            resources: Some(ResourcesCapability {
-                subscribe: false,
-                list_changed: false,
-            }),
-            prompts: None,
+            resources: Some(resources::capability()),
+            prompts: Some(prompts::capability()),
         },
         server_info: ServerInfo {
             name: "sena-controller".to_string(),
@@ -473,38 +482,23 @@ fn call_progress(args: &HashMap<String, serde_json::Value>) -> ToolCallResult {
     }
 }
 
-fn handle_resources_list(request: &JsonRpcRequest) -> JsonRpcResponse {
-    let brand = SenaConfig::brand();
-    let resources = vec![
-        Resource {
-            uri: "sena://health".to_string(),
-            name: format!("{} Health Status", brand),
-            description: Some("Current health status of the system".to_string()),
-            mime_type: Some("application/json".to_string()),
-        },
-        Resource {
-            uri: "sena://metrics".to_string(),
-            name: format!("{} Metrics", brand),
-            description: Some("System metrics and statistics".to_string()),
-            mime_type: Some("application/json".to_string()),
-        },
-        Resource {
-            uri: "sena://config".to_string(),
-            name: format!("{} Configuration", brand),
-            description: Some("Current system configuration".to_string()),
-            mime_type: Some("application/json".to_string()),
-        },
-    ];
-
-    let result = ResourcesListResult { resources };
+fn handle_resources_list(request: &JsonRpcRequest, catalog: &ResourceCatalog) -> JsonRpcResponse {
+    let cursor = request
+        .params
+        .as_ref()
+        .and_then(|p| p.get("cursor"))
+        .and_then(|c| c.as_str());
 
-    JsonRpcResponse::success(
-        request.id.clone(),
-        serde_json::to_value(result).unwrap_or_default(),
-    )
+    match catalog.page(cursor, RESOURCES_PAGE_SIZE) {
+        Ok(result) => JsonRpcResponse::success(
+            request.id.clone(),
+            serde_json::to_value(result).unwrap_or_default(),
+        ),
+        Err(e) => JsonRpcResponse::error(request.id.clone(), error_codes::INVALID_PARAMS, &e),
+    }
 }
 
-fn handle_resources_read(request: &JsonRpcRequest) -> JsonRpcResponse {
+fn handle_resources_read(request: &JsonRpcRequest, catalog: &ResourceCatalog) -> JsonRpcResponse {
     let uri = request
         .params
         .as_ref()
@@ -512,36 +506,56 @@ fn handle_resources_read(request: &JsonRpcRequest) -> JsonRpcResponse {
         .and_then(|u| u.as_str())
         .unwrap_or("");
 
-    let content = match uri {
-        "sena://health" => {
-            let health = SenaHealth::new();
-            serde_json::to_string_pretty(&health.get_health()).unwrap_or_default()
-        }
-        "sena://metrics" => serde_json::to_string_pretty(&crate::metrics::SenaMetrics::collect())
+    match catalog.read(uri) {
+        Some(contents) => JsonRpcResponse::success(
+            request.id.clone(),
+            serde_json::to_value(ResourcesReadResult {
+                contents: vec![contents],
+            })
             .unwrap_or_default(),
-        "sena://config" => serde_json::json!({
-            "version": crate::VERSION,
-            "codename": crate::CODENAME,
-        })
-        .to_string(),
-        _ => {
-            return JsonRpcResponse::error(
-                request.id.clone(),
-                error_codes::INVALID_PARAMS,
-                &format!("Unknown resource: {}", uri),
-            );
-        }
+        ),
+        None => JsonRpcResponse::error(
+            request.id.clone(),
+            error_codes::INVALID_PARAMS,
+            &format!("Unknown resource: {}", uri),
+        ),
+    }
+}
+
+fn handle_prompts_list(request: &JsonRpcRequest) -> JsonRpcResponse {
+    let result = PromptsListResult {
+        prompts: PromptCatalog::load().prompts(),
     };
 
-    let result = serde_json::json!({
-        "contents": [{
-            "uri": uri,
-            "mimeType": "application/json",
-            "text": content,
-        }]
-    });
+    JsonRpcResponse::success(
+        request.id.clone(),
+        serde_json::to_value(result).unwrap_or_default(),
+    )
+}
 
-    JsonRpcResponse::success(request.id.clone(), result)
+fn handle_prompts_get(request: &JsonRpcRequest) -> JsonRpcResponse {
+    let params = request.params.as_ref();
+    let name = params
+        .and_then(|p| p.get("name"))
+        .and_then(|n| n.as_str())
+        .unwrap_or("");
+    let args: HashMap<String, String> = params
+        .and_then(|p| p.get("arguments"))
+        .and_then(|a| a.as_object())
+        .map(|a| {
+            a.iter()
+                .filter_map(|(k, v)| v.as_str().map(|v| (k.clone(), v.to_string())))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    match PromptCatalog::load().get(name, &args) {
+        Ok(result) => JsonRpcResponse::success(
+            request.id.clone(),
+            serde_json::to_value(result).unwrap_or_default(),
+        ),
+        Err(e) => JsonRpcResponse::error(request.id.clone(), error_codes::INVALID_PARAMS, &e),
+    }
 }
 
 fn call_devil_execute(args: &HashMap<String, serde_json::Value>) -> ToolCallResult {
@@ -607,7 +621,7 @@ fn call_guardian_validate(args: &HashMap<String, serde_json::Value>) -> ToolCall
     let command = args.get("command").and_then(|v| v.as_str()).unwrap_or("");
 
     let negative_space = Arc::new(RwLock::new(NegativeSpaceArchitecture::new()));
-    let validator = CommandValidator::new(negative_space);
+    let validator = CommandValidator::configured(negative_space);
     let result = validator.validate(command);
 
     let text = serde_json::json!({
@@ -628,14 +642,17 @@ fn call_guardian_validate(args: &HashMap<String, serde_json::Value>) -> ToolCall
 
 fn call_guardian_check(args: &HashMap<String, serde_json::Value>) -> ToolCallResult {
     use crate::ancient::{HarmonyValidationEngine, NegativeSpaceArchitecture};
-    use crate::guardian::HallucinationDetector;
+    use crate::guardian::{HallucinationDetector, HallucinationLog};
     use std::sync::{Arc, RwLock};
 
     let content = args.get("content").and_then(|v| v.as_str()).unwrap_or("");
 
     let negative_space = Arc::new(RwLock::new(NegativeSpaceArchitecture::new()));
     let harmony_engine = Arc::new(RwLock::new(HarmonyValidationEngine::new()));
-    let detector = HallucinationDetector::new(negative_space, harmony_engine);
+    let mut detector = HallucinationDetector::new(negative_space, harmony_engine);
+    if let Some(log) = HallucinationLog::configured() {
+        detector = detector.with_log(log);
+    }
     let result = detector.check(content);
 
     let text = serde_json::json!({
@@ -3,7 +3,9 @@
 //! Model Context Protocol server implementation using JSON-RPC over stdio
 
 pub mod handlers;
+pub mod prompts;
 pub mod protocol;
+pub mod resources;
 pub mod server;
 
 pub use protocol::*;
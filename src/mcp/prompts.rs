@@ -0,0 +1,118 @@
+//! MCP Prompts
+//!
+//! The knowledge system's reasoning frameworks, served through
+//! `prompts/list` and `prompts/get` as templates filled in with the
+//! client's arguments
+
+use super::protocol::{
+    GetPromptResult, Prompt, PromptArgument, PromptMessage, PromptsCapability, ToolContent,
+};
+use super::resources::slug;
+use crate::knowledge::{default_reasoning_frameworks, ReasoningFramework};
+use std::collections::HashMap;
+
+/// What `initialize` advertises; the framework list is fixed
+pub fn capability() -> PromptsCapability {
+    PromptsCapability {
+        list_changed: false,
+    }
+}
+
+/// Reasoning frameworks exposed as prompts
+pub struct PromptCatalog {
+    frameworks: Vec<ReasoningFramework>,
+}
+
+impl PromptCatalog {
+    pub fn new(frameworks: Vec<ReasoningFramework>) -> Self {
+        Self { frameworks }
+    }
+
+    pub fn load() -> Self {
+        Self::new(default_reasoning_frameworks())
+    }
+
+    pub fn prompts(&self) -> Vec<Prompt> {
+        self.frameworks
+            .iter()
+            .map(|framework| Prompt {
+                name: slug(&framework.name),
+                description: Some(format!("{}: {}", framework.name, framework.description)),
+                arguments: arguments(),
+            })
+            .collect()
+    }
+
+    /// Fill in the prompt called `name`. Besides the exact prompt name, any
+    /// part of a framework's name that picks out a single framework is
+    /// accepted, so "5 Whys" finds "Root Cause Analysis (5 Whys)"
+    pub fn get(
+        &self,
+        name: &str,
+        args: &HashMap<String, String>,
+    ) -> Result<GetPromptResult, String> {
+        let framework = self.find(name)?;
+
+        let mut text = framework.template();
+        for argument in arguments() {
+            let placeholder = format!("{{{}}}", argument.name);
+            match args.get(&argument.name) {
+                Some(value) => text = text.replace(&placeholder, value),
+                None if argument.required => {
+                    return Err(format!(
+                        "Missing required argument '{}' for prompt '{}'",
+                        argument.name,
+                        slug(&framework.name)
+                    ))
+                }
+                None => text = text.replace(&placeholder, ""),
+            }
+        }
+
+        Ok(GetPromptResult {
+            description: Some(framework.description.clone()),
+            messages: vec![PromptMessage {
+                role: "user".to_string(),
+                content: ToolContent::text(&text),
+            }],
+        })
+    }
+
+    fn find(&self, name: &str) -> Result<&ReasoningFramework, String> {
+        let wanted = slug(name);
+        if let Some(framework) = self.frameworks.iter().find(|f| slug(&f.name) == wanted) {
+            return Ok(framework);
+        }
+
+        let matches: Vec<&ReasoningFramework> = self
+            .frameworks
+            .iter()
+            .filter(|f| {
+                !wanted.is_empty()
+                    && format!("-{}-", slug(&f.name)).contains(&format!("-{}-", wanted))
+            })
+            .collect();
+        match matches.as_slice() {
+            [framework] => Ok(framework),
+            [] => Err(format!("Unknown prompt: {}", name)),
+            _ => Err(format!(
+                "Ambiguous prompt '{}': matches {}",
+                name,
+                matches
+                    .iter()
+                    .map(|f| slug(&f.name))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+        }
+    }
+}
+
+/// Every framework template takes the same arguments
+fn arguments() -> Vec<PromptArgument> {
+    vec![PromptArgument {
+        name: "problem".to_string(),
+        description: Some("The problem to reason about".to_string()),
+        required: true,
+    }]
+}
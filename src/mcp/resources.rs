@@ -0,0 +1,272 @@
+//! MCP Resources
+//!
+//! Everything served through `resources/list` and `resources/read`: system
+//! status, knowledge patterns and stored memories, each addressed by a
+//! `sena://` URI
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use super::protocol::{Resource, ResourceContents, ResourcesCapability, ResourcesListResult};
+use crate::config::SenaConfig;
+use crate::knowledge::KnowledgeSystem;
+use crate::memory::{MemoryEntry, MemoryStore};
+use crate::metrics::SenaHealth;
+
+/// Resources returned per `resources/list` page
+pub const RESOURCES_PAGE_SIZE: usize = 25;
+
+/// Knowledge domains exposed as `sena://knowledge/<domain>/<pattern>`
+pub const KNOWLEDGE_DOMAINS: &[&str] = &["reasoning", "security", "performance", "architecture"];
+
+const JSON_MIME: &str = "application/json";
+const TEXT_MIME: &str = "text/plain";
+
+/// What `initialize` advertises; the catalog is rebuilt when the memory
+/// store changes and never pushes updates
+pub fn capability() -> ResourcesCapability {
+    ResourcesCapability {
+        subscribe: false,
+        list_changed: false,
+    }
+}
+
+/// Lowercase, dash-separated form of a pattern name used in its URI
+pub fn slug(name: &str) -> String {
+    name.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Every resource the MCP server can serve
+pub struct ResourceCatalog {
+    knowledge: KnowledgeSystem,
+    /// Sorted by id so pages stay stable between requests
+    memories: Vec<MemoryEntry>,
+}
+
+impl ResourceCatalog {
+    pub fn new(knowledge: KnowledgeSystem, mut memories: Vec<MemoryEntry>) -> Self {
+        memories.sort_by(|a, b| a.id.cmp(&b.id));
+        Self {
+            knowledge,
+            memories,
+        }
+    }
+
+    /// The built-in knowledge base and the memories stored in `memory_dir`;
+    /// an unreadable memory store contributes nothing
+    pub fn load(memory_dir: &Path) -> Self {
+        let memories = MemoryStore::load(memory_dir)
+            .map(|store| store.all().into_iter().cloned().collect())
+            .unwrap_or_default();
+        Self::new(KnowledgeSystem::new(), memories)
+    }
+
+    /// The catalog for `memory_dir`, built again only once its memory file
+    /// has changed since the last call
+    pub fn cached(memory_dir: &Path) -> Arc<Self> {
+        static CACHE: Mutex<Option<CachedCatalog>> = Mutex::new(None);
+
+        let stamp = memory_stamp(memory_dir);
+        let mut cache = CACHE
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(cached) = cache.as_ref() {
+            if cached.memory_dir == memory_dir && cached.stamp == stamp {
+                return Arc::clone(&cached.catalog);
+            }
+        }
+        let catalog = Arc::new(Self::load(memory_dir));
+        *cache = Some(CachedCatalog {
+            memory_dir: memory_dir.to_path_buf(),
+            stamp,
+            catalog: Arc::clone(&catalog),
+        });
+        catalog
+    }
+
+    pub fn resources(&self) -> Vec<Resource> {
+        let brand = SenaConfig::brand();
+        let mut resources = vec![
+            Resource {
+                uri: "sena://health".to_string(),
+                name: format!("{} Health Status", brand),
+                description: Some("Current health status of the system".to_string()),
+                mime_type: Some(JSON_MIME.to_string()),
+            },
+            Resource {
+                uri: "sena://metrics".to_string(),
+                name: format!("{} Metrics", brand),
+                description: Some("System metrics and statistics".to_string()),
+                mime_type: Some(JSON_MIME.to_string()),
+            },
+            Resource {
+                uri: "sena://config".to_string(),
+                name: format!("{} Configuration", brand),
+                description: Some("Current system configuration".to_string()),
+                mime_type: Some(JSON_MIME.to_string()),
+            },
+        ];
+
+        for domain in KNOWLEDGE_DOMAINS {
+            for name in self.knowledge.get_domain_patterns(domain) {
+                resources.push(Resource {
+                    uri: format!("sena://knowledge/{}/{}", domain, slug(&name)),
+                    description: Some(format!("{} knowledge pattern", domain)),
+                    name,
+                    mime_type: Some(TEXT_MIME.to_string()),
+                });
+            }
+        }
+
+        for entry in &self.memories {
+            resources.push(Resource {
+                uri: format!("sena://memory/{}", entry.id),
+                name: preview(&entry.content),
+                description: Some(format!("{:?} memory", entry.memory_type)),
+                mime_type: Some(JSON_MIME.to_string()),
+            });
+        }
+
+        resources
+    }
+
+    /// One page of [`resources`](Self::resources). `cursor` is the
+    /// `nextCursor` of the previous page; anything else is an error
+    pub fn page(
+        &self,
+        cursor: Option<&str>,
+        page_size: usize,
+    ) -> Result<ResourcesListResult, String> {
+        let resources = self.resources();
+        let start = match cursor {
+            None => 0,
+            Some(cursor) => cursor
+                .parse::<usize>()
+                .ok()
+                .filter(|&offset| offset <= resources.len())
+                .ok_or_else(|| format!("Invalid cursor: {}", cursor))?,
+        };
+        let end = (start + page_size.max(1)).min(resources.len());
+
+        Ok(ResourcesListResult {
+            next_cursor: (end < resources.len()).then(|| end.to_string()),
+            resources: resources[start..end].to_vec(),
+        })
+    }
+
+    pub fn read(&self, uri: &str) -> Option<ResourceContents> {
+        let (mime_type, text) = match uri {
+            "sena://health" => (
+                JSON_MIME,
+                serde_json::to_string_pretty(&SenaHealth::new().get_health()).unwrap_or_default(),
+            ),
+            "sena://metrics" => (
+                JSON_MIME,
+                serde_json::to_string_pretty(&crate::metrics::SenaMetrics::collect())
+                    .unwrap_or_default(),
+            ),
+            "sena://config" => (
+                JSON_MIME,
+                serde_json::json!({
+                    "version": crate::VERSION,
+                    "codename": crate::CODENAME,
+                })
+                .to_string(),
+            ),
+            _ => {
+                if let Some(path) = uri.strip_prefix("sena://knowledge/") {
+                    let (domain, pattern) = path.split_once('/')?;
+                    let name = self
+                        .knowledge
+                        .get_domain_patterns(domain)
+                        .into_iter()
+                        .find(|name| slug(name) == pattern)?;
+                    (TEXT_MIME, self.knowledge.get_pattern(domain, &name)?)
+                } else if let Some(id) = uri.strip_prefix("sena://memory/") {
+                    let entry = self.memories.iter().find(|entry| entry.id == id)?;
+                    (JSON_MIME, serde_json::to_string_pretty(entry).ok()?)
+                } else {
+                    return None;
+                }
+            }
+        };
+
+        Some(ResourceContents {
+            uri: uri.to_string(),
+            mime_type: Some(mime_type.to_string()),
+            text,
+        })
+    }
+}
+
+struct CachedCatalog {
+    memory_dir: PathBuf,
+    stamp: Option<(SystemTime, u64)>,
+    catalog: Arc<ResourceCatalog>,
+}
+
+/// Modification time and size of the memory file, `None` when missing
+fn memory_stamp(memory_dir: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = std::fs::metadata(memory_dir.join("memories.json")).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// First line of a memory, shortened to a readable resource name
+fn preview(content: &str) -> String {
+    let line = content.lines().next().unwrap_or("").trim();
+    if line.chars().count() > 60 {
+        format!("{}…", line.chars().take(60).collect::<String>())
+    } else {
+        line.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::MemoryType;
+
+    #[test]
+    fn test_memory_resources_are_listed_and_readable() {
+        let entry = MemoryEntry::new("Prefer tabs over spaces", MemoryType::Preference);
+        let uri = format!("sena://memory/{}", entry.id);
+        let catalog = ResourceCatalog::new(KnowledgeSystem::new(), vec![entry]);
+
+        let listed = catalog
+            .resources()
+            .into_iter()
+            .find(|r| r.uri == uri)
+            .unwrap();
+        assert_eq!(listed.name, "Prefer tabs over spaces");
+        assert_eq!(listed.mime_type.as_deref(), Some(JSON_MIME));
+
+        let contents = catalog.read(&uri).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&contents.text).unwrap();
+        assert_eq!(value["content"], "Prefer tabs over spaces");
+        assert!(catalog.read("sena://memory/mem_missing").is_none());
+        assert!(catalog.read("sena://knowledge/security/no-such").is_none());
+    }
+
+    #[test]
+    fn test_cached_catalog_is_rebuilt_when_memories_change() {
+        let dir = std::env::temp_dir().join(format!("sena-resources-{}", uuid::Uuid::new_v4()));
+        let first = ResourceCatalog::cached(&dir);
+        assert!(Arc::ptr_eq(&first, &ResourceCatalog::cached(&dir)));
+        assert!(first.memories.is_empty());
+
+        let mut memory = crate::memory::PersistentMemory::with_dir(dir.clone()).unwrap();
+        memory
+            .add_quick("Release notes live in CHANGELOG.md", MemoryType::Fact)
+            .unwrap();
+        let updated = ResourceCatalog::cached(&dir);
+        assert!(!Arc::ptr_eq(&first, &updated));
+        assert_eq!(updated.memories.len(), 1);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -1,17 +1,121 @@
 //! MCP Server Implementation
 //!
-//! JSON-RPC server over stdio for Claude Code integration
+//! JSON-RPC server over stdio for Claude Code integration. Stdout carries
+//! only JSON-RPC responses; diagnostics go through `log` to stderr, with every
+//! request and response tagged by its JSON-RPC id (`RUST_LOG=debug` to see them)
 
 use super::handlers::handle_request;
 use super::protocol::*;
 use crate::config::SenaConfig;
 use std::io::{self, BufRead, Write};
+use std::time::Instant;
+
+/// Handle one line read from the client and return the response line to
+/// write back, if any. A JSON array is a batch: its members are handled in
+/// order and answered with one array, leaving out notifications
+pub fn process_line(line: &str) -> Option<String> {
+    let trimmed = line.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+
+    let message: serde_json::Value = match serde_json::from_str(trimmed) {
+        Ok(message) => message,
+        Err(e) => {
+            log::warn!(
+                "Parse error: {} in {}",
+                e,
+                &trimmed[..trimmed.len().min(200)]
+            );
+            let error_response = JsonRpcResponse::error(
+                None,
+                error_codes::PARSE_ERROR,
+                &format!("Parse error: {}", e),
+            );
+            return Some(serde_json::to_string(&error_response).unwrap_or_default());
+        }
+    };
+
+    match message {
+        serde_json::Value::Array(batch) if batch.is_empty() => {
+            log::warn!("Empty batch");
+            let error_response =
+                JsonRpcResponse::error(None, error_codes::INVALID_REQUEST, "Empty batch");
+            Some(serde_json::to_string(&error_response).unwrap_or_default())
+        }
+        serde_json::Value::Array(batch) => {
+            log::debug!("batch of {} messages", batch.len());
+            let responses: Vec<JsonRpcResponse> =
+                batch.into_iter().filter_map(process_message).collect();
+            // A batch of only notifications gets no reply at all
+            (!responses.is_empty()).then(|| serde_json::to_string(&responses).unwrap_or_default())
+        }
+        message => process_message(message).map(|r| serde_json::to_string(&r).unwrap_or_default()),
+    }
+}
+
+/// Handle a single request object, returning `None` for notifications
+fn process_message(message: serde_json::Value) -> Option<JsonRpcResponse> {
+    let request: JsonRpcRequest = match serde_json::from_value(message.clone()) {
+        Ok(req) => req,
+        Err(e) => {
+            log::warn!("Invalid request: {} in {}", e, message);
+            return Some(JsonRpcResponse::error(
+                message.get("id").cloned(),
+                error_codes::INVALID_REQUEST,
+                &format!("Invalid request: {}", e),
+            ));
+        }
+    };
+
+    let id = request_id(&request);
+    log::debug!("--> id={} method={}", id, request.method);
+    let started = Instant::now();
+
+    let response = handle_request(&request);
+    let elapsed = started.elapsed();
+
+    if let Some(error) = &response.error {
+        log::warn!(
+            "<-- id={} method={} error {}: {} ({:?}) params={}",
+            id,
+            request.method,
+            error.code,
+            error.message,
+            elapsed,
+            request
+                .params
+                .as_ref()
+                .map(|p| p.to_string())
+                .unwrap_or_else(|| "null".to_string())
+        );
+    }
+
+    if request.id.is_none() {
+        log::debug!("notification {} handled in {:?}", request.method, elapsed);
+        return None;
+    }
+
+    log::debug!("<-- id={} method={} ({:?})", id, request.method, elapsed);
+    Some(response)
+}
+
+fn request_id(request: &JsonRpcRequest) -> String {
+    request
+        .id
+        .as_ref()
+        .map(|id| id.to_string())
+        .unwrap_or_else(|| "-".to_string())
+}
 
 pub async fn run_server() -> Result<String, String> {
     use std::io::BufReader;
 
-    let brand = SenaConfig::brand();
-    eprintln!("{} MCP Server v{} starting...", brand, crate::VERSION);
+    log::info!(
+        "{} MCP Server v{} starting...",
+        SenaConfig::brand(),
+        crate::VERSION
+    );
 
     let stdin = io::stdin();
     let stdout = io::stdout();
@@ -27,60 +131,31 @@ pub async fn run_server() -> Result<String, String> {
 
         match reader.read_line(&mut line) {
             Ok(0) => {
-                eprintln!("EOF received, shutting down");
+                log::info!("EOF received, shutting down");
                 break;
             }
             Ok(_) => {}
             Err(e) => {
-                eprintln!("Error reading stdin: {}", e);
+                log::error!("Error reading stdin: {}", e);
                 break;
             }
         }
 
-        let trimmed = line.trim();
-        if trimmed.is_empty() {
+        let Some(response_str) = process_line(&line) else {
             continue;
-        }
-
-        eprintln!("Received: {}", &trimmed[..trimmed.len().min(100)]);
-
-        let request: JsonRpcRequest = match serde_json::from_str(trimmed) {
-            Ok(req) => req,
-            Err(e) => {
-                eprintln!("Parse error: {}", e);
-                let error_response = JsonRpcResponse::error(
-                    None,
-                    error_codes::PARSE_ERROR,
-                    &format!("Parse error: {}", e),
-                );
-                let response_str = serde_json::to_string(&error_response).unwrap_or_default();
-                let _ = writeln!(stdout_handle, "{}", response_str);
-                let _ = stdout_handle.flush();
-                continue;
-            }
         };
 
-        let response = handle_request(&request);
-
-        if request.id.is_none() {
-            eprintln!("Notification received: {}", request.method);
-            continue;
-        }
-
-        let response_str = serde_json::to_string(&response).unwrap_or_default();
-        eprintln!("Sending: {}", &response_str[..response_str.len().min(100)]);
-
         if let Err(e) = writeln!(stdout_handle, "{}", response_str) {
-            eprintln!("Error writing response: {}", e);
+            log::error!("Error writing response: {}", e);
             break;
         }
         if let Err(e) = stdout_handle.flush() {
-            eprintln!("Error flushing stdout: {}", e);
+            log::error!("Error flushing stdout: {}", e);
             break;
         }
     }
 
-    eprintln!("MCP Server loop ended");
+    log::info!("MCP Server loop ended");
     Ok("MCP Server stopped".to_string())
 }
 
@@ -94,10 +169,9 @@ pub async fn run_server_async() -> Result<String, String> {
     let mut reader = BufReader::new(stdin);
     let mut stdout = stdout;
 
-    let brand = SenaConfig::brand();
-    eprintln!(
+    log::info!(
         "{} MCP Server v{} starting (async)...",
-        brand,
+        SenaConfig::brand(),
         crate::VERSION
     );
 
@@ -109,52 +183,23 @@ pub async fn run_server_async() -> Result<String, String> {
             Ok(0) => break, // EOF
             Ok(_) => {}
             Err(e) => {
-                eprintln!("Error reading stdin: {}", e);
+                log::error!("Error reading stdin: {}", e);
                 continue;
             }
         }
 
-        let trimmed = line.trim();
-        if trimmed.is_empty() {
+        let Some(response_str) = process_line(&line) else {
             continue;
-        }
-
-        // Parse JSON-RPC request
-        let request: JsonRpcRequest = match serde_json::from_str(trimmed) {
-            Ok(req) => req,
-            Err(e) => {
-                let error_response = JsonRpcResponse::error(
-                    None,
-                    error_codes::PARSE_ERROR,
-                    &format!("Parse error: {}", e),
-                );
-                let response_str = serde_json::to_string(&error_response).unwrap_or_default();
-                let _ = stdout
-                    .write_all(format!("{}\n", response_str).as_bytes())
-                    .await;
-                let _ = stdout.flush().await;
-                continue;
-            }
         };
 
-        // Handle request
-        let response = handle_request(&request);
-
-        // Skip response for notifications
-        if request.id.is_none() && response.result == Some(serde_json::Value::Null) {
-            continue;
-        }
-
-        // Send response
-        let response_str = serde_json::to_string(&response).unwrap_or_default();
         if let Err(e) = stdout
             .write_all(format!("{}\n", response_str).as_bytes())
             .await
         {
-            eprintln!("Error writing response: {}", e);
+            log::error!("Error writing response: {}", e);
         }
         if let Err(e) = stdout.flush().await {
-            eprintln!("Error flushing stdout: {}", e);
+            log::error!("Error flushing stdout: {}", e);
         }
     }
 
@@ -164,6 +209,7 @@ pub async fn run_server_async() -> Result<String, String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::mcp::handlers::handle_request_in;
 
     #[test]
     fn test_handle_initialize() {
@@ -217,4 +263,169 @@ mod tests {
         assert!(response.error.is_some());
         assert_eq!(response.error.unwrap().code, error_codes::METHOD_NOT_FOUND);
     }
+
+    #[test]
+    fn test_handle_resources_list_paginates() {
+        let dir = std::env::temp_dir().join(format!("sena-mcp-{}", uuid::Uuid::new_v4()));
+        let memory_id = crate::memory::PersistentMemory::with_dir(dir.clone())
+            .unwrap()
+            .add_quick(
+                "Staging deploys run at noon",
+                crate::memory::MemoryType::Fact,
+            )
+            .unwrap();
+        let list = |cursor: Option<&str>| {
+            handle_request_in(
+                &JsonRpcRequest {
+                    jsonrpc: "2.0".to_string(),
+                    id: Some(serde_json::json!(4)),
+                    method: "resources/list".to_string(),
+                    params: cursor.map(|c| serde_json::json!({ "cursor": c })),
+                },
+                &dir,
+            )
+        };
+
+        let mut uris = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let result = list(cursor.as_deref()).result.unwrap();
+            let page = result["resources"].as_array().unwrap();
+            assert!(page.len() <= crate::mcp::resources::RESOURCES_PAGE_SIZE);
+            uris.extend(page.iter().map(|r| r["uri"].as_str().unwrap().to_string()));
+            match result.get("nextCursor").and_then(|c| c.as_str()) {
+                Some(next) => cursor = Some(next.to_string()),
+                None => break,
+            }
+        }
+
+        assert!(uris.len() > crate::mcp::resources::RESOURCES_PAGE_SIZE);
+        assert!(uris.contains(&"sena://health".to_string()));
+        assert!(uris.contains(&"sena://knowledge/security/sql-injection-prevention".to_string()));
+        assert!(uris.contains(&format!("sena://memory/{}", memory_id)));
+        assert_eq!(
+            list(Some("bogus")).error.unwrap().code,
+            error_codes::INVALID_PARAMS
+        );
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_handle_resources_read_knowledge_pattern() {
+        let dir = std::env::temp_dir().join(format!("sena-mcp-{}", uuid::Uuid::new_v4()));
+        let read = |uri: &str| {
+            handle_request_in(
+                &JsonRpcRequest {
+                    jsonrpc: "2.0".to_string(),
+                    id: Some(serde_json::json!(5)),
+                    method: "resources/read".to_string(),
+                    params: Some(serde_json::json!({ "uri": uri })),
+                },
+                &dir,
+            )
+        };
+
+        let uri = "sena://knowledge/security/sql-injection-prevention";
+        let result = read(uri).result.unwrap();
+        let contents = &result["contents"][0];
+        assert_eq!(contents["uri"], uri);
+        assert_eq!(contents["mimeType"], "text/plain");
+        assert!(contents["text"]
+            .as_str()
+            .unwrap()
+            .contains("SQL Injection Prevention"));
+
+        assert_eq!(
+            read("sena://knowledge/security/unknown")
+                .error
+                .unwrap()
+                .code,
+            error_codes::INVALID_PARAMS
+        );
+    }
+
+    #[test]
+    fn test_handle_prompts_list() {
+        let request = JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: Some(serde_json::json!(6)),
+            method: "prompts/list".to_string(),
+            params: None,
+        };
+
+        let result = handle_request(&request).result.unwrap();
+        let prompts = result["prompts"].as_array().unwrap();
+        assert_eq!(
+            prompts.len(),
+            crate::knowledge::default_reasoning_frameworks().len()
+        );
+        let first_principles = prompts
+            .iter()
+            .find(|p| p["name"] == "first-principles-thinking")
+            .unwrap();
+        assert_eq!(first_principles["arguments"][0]["name"], "problem");
+        assert_eq!(first_principles["arguments"][0]["required"], true);
+    }
+
+    #[test]
+    fn test_handle_prompts_get_substitutes_arguments() {
+        let get = |name: &str, arguments: serde_json::Value| {
+            handle_request(&JsonRpcRequest {
+                jsonrpc: "2.0".to_string(),
+                id: Some(serde_json::json!(7)),
+                method: "prompts/get".to_string(),
+                params: Some(serde_json::json!({ "name": name, "arguments": arguments })),
+            })
+        };
+
+        let result = get(
+            "First Principles",
+            serde_json::json!({ "problem": "Why is checkout slow?" }),
+        )
+        .result
+        .unwrap();
+        let message = &result["messages"][0];
+        assert_eq!(message["role"], "user");
+        let text = message["content"]["text"].as_str().unwrap();
+        assert!(text.contains("Problem: Why is checkout slow?"));
+        assert!(text.contains("First Principles Thinking"));
+        assert!(!text.contains("{problem}"));
+
+        let missing = get("First Principles", serde_json::json!({}));
+        assert_eq!(missing.error.unwrap().code, error_codes::INVALID_PARAMS);
+        let ambiguous = get("Thinking", serde_json::json!({ "problem": "x" }));
+        assert!(ambiguous.error.unwrap().message.contains("Ambiguous"));
+    }
+
+    #[test]
+    fn test_process_line_handles_mixed_batch() {
+        let line = r#"[
+            {"jsonrpc": "2.0", "id": 1, "method": "ping"},
+            {"jsonrpc": "2.0", "method": "initialized"},
+            {"jsonrpc": "2.0", "id": 2},
+            {"jsonrpc": "2.0", "id": 3, "method": "no/such/method"}
+        ]"#;
+
+        let responses: Vec<serde_json::Value> =
+            serde_json::from_str(&process_line(line).unwrap()).unwrap();
+        assert_eq!(responses.len(), 3);
+        assert_eq!(responses[0]["id"], 1);
+        assert!(responses[0].get("error").is_none());
+        assert_eq!(responses[1]["id"], 2);
+        assert_eq!(responses[1]["error"]["code"], error_codes::INVALID_REQUEST);
+        assert_eq!(responses[2]["id"], 3);
+        assert_eq!(responses[2]["error"]["code"], error_codes::METHOD_NOT_FOUND);
+    }
+
+    #[test]
+    fn test_process_line_rejects_empty_batch() {
+        let response: serde_json::Value =
+            serde_json::from_str(&process_line("[]").unwrap()).unwrap();
+        assert!(response.is_object());
+        assert_eq!(response["error"]["code"], error_codes::INVALID_REQUEST);
+
+        let notifications = r#"[{"jsonrpc": "2.0", "method": "initialized"}]"#;
+        assert!(process_line(notifications).is_none());
+    }
 }
@@ -7,14 +7,20 @@ use thiserror::Error;
 
 pub mod search;
 pub mod semantic;
+pub mod snapshot;
 pub mod store;
+pub mod transfer;
+pub mod watch;
 
 pub use search::MemorySearch;
 pub use semantic::{
-    EmbeddingVector, HybridSearch, SemanticMemoryIndex, SemanticSearchResult, SimpleHashEmbedder,
-    TextEmbedder,
+    EmbeddingVector, HybridSearch, HybridSearchResult, SemanticMemoryIndex, SemanticSearchResult,
+    SimpleHashEmbedder, TextEmbedder, EMBEDDINGS_FILE,
 };
+pub use snapshot::{SnapshotId, SnapshotInfo, SNAPSHOTS_DIR};
 pub use store::MemoryStore;
+pub use transfer::{ImportError, ImportSummary, MergeStrategy};
+pub use watch::{FileTail, NoteIngester};
 
 #[derive(Error, Debug)]
 pub enum MemoryError {
@@ -40,6 +46,61 @@ pub enum MemoryType {
     Custom(String),
 }
 
+impl MemoryType {
+    /// The type named by `s`, ignoring case; unknown names become `Custom`
+    pub fn parse(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "preference" => MemoryType::Preference,
+            "fact" => MemoryType::Fact,
+            "project" => MemoryType::Project,
+            "context" => MemoryType::Context,
+            "conversation" => MemoryType::Conversation,
+            other => MemoryType::Custom(other.to_string()),
+        }
+    }
+}
+
+const EMPHASIS_MARKERS: &[&str] = &[
+    "always",
+    "never",
+    "must",
+    "decided",
+    "decision",
+    "critical",
+    "important",
+    "required",
+    "don't",
+    "do not",
+];
+
+/// Heuristic importance for a memory whose importance was not given:
+/// weighted by type, raised by decisions and emphatic wording ("always",
+/// "never", ...) and by length, lowered for throwaway one- or two-word notes
+pub fn auto_importance(content: &str, memory_type: &MemoryType) -> f64 {
+    let mut score = match memory_type {
+        MemoryType::Preference => 0.6,
+        MemoryType::Project => 0.55,
+        MemoryType::Fact => 0.5,
+        MemoryType::Custom(_) => 0.45,
+        MemoryType::Context => 0.35,
+        MemoryType::Conversation => 0.3,
+    };
+
+    let lower = content.to_lowercase();
+    if EMPHASIS_MARKERS.iter().any(|marker| lower.contains(marker)) {
+        score += 0.2;
+    }
+
+    let words = content.split_whitespace().count();
+    if words < 3 {
+        score -= 0.15;
+    } else {
+        score += (words as f64 / 40.0).min(1.0) * 0.1;
+    }
+
+    (score.clamp(0.0, 1.0) * 100.0).round() / 100.0
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MemoryEntry {
     pub id: String,
@@ -51,6 +112,12 @@ pub struct MemoryEntry {
     pub updated_at: DateTime<Utc>,
     pub access_count: u64,
     pub importance: f64,
+    /// Pinned memories are never decayed or evicted
+    #[serde(default)]
+    pub pinned: bool,
+    /// When importance was last decayed; decay resumes from here
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decayed_at: Option<DateTime<Utc>>,
 }
 
 impl MemoryEntry {
@@ -75,6 +142,8 @@ impl MemoryEntry {
             updated_at: Utc::now(),
             access_count: 0,
             importance: 0.5,
+            pinned: false,
+            decayed_at: None,
         }
     }
 
@@ -88,11 +157,39 @@ impl MemoryEntry {
         self
     }
 
+    /// Score importance with [`auto_importance`]
+    pub fn with_auto_importance(mut self) -> Self {
+        self.importance = auto_importance(&self.content, &self.memory_type);
+        self
+    }
+
     pub fn with_metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
         self.metadata.insert(key.into(), value.into());
         self
     }
 
+    pub fn with_pinned(mut self, pinned: bool) -> Self {
+        self.pinned = pinned;
+        self
+    }
+
+    /// Halve importance for every half-life elapsed since the entry was
+    /// created, last accessed or last decayed, whichever is latest. Each
+    /// access lengthens the half-life, so memories in use fade slower.
+    pub fn decay(&mut self, now: DateTime<Utc>, half_life_days: f64) {
+        if self.pinned || half_life_days <= 0.0 {
+            return;
+        }
+        let since = self
+            .decayed_at
+            .unwrap_or(self.created_at)
+            .max(self.updated_at);
+        let elapsed_days = (now - since).num_seconds().max(0) as f64 / 86_400.0;
+        let half_life = half_life_days * (1.0 + (self.access_count as f64).ln_1p());
+        self.importance *= 0.5f64.powf(elapsed_days / half_life);
+        self.decayed_at = Some(now);
+    }
+
     pub fn touch(&mut self) {
         self.access_count += 1;
         self.updated_at = Utc::now();
@@ -139,23 +236,32 @@ impl MemoryEntry {
 
 pub struct PersistentMemory {
     store: MemoryStore,
+    /// Embeddings of every stored memory, kept in step with `store`
+    index: SemanticMemoryIndex,
+    hybrid: HybridSearch,
+    retention: MemoryRetention,
+    /// Memories removed by retention since this store was opened
+    evicted: usize,
     memory_dir: PathBuf,
 }
 
 impl PersistentMemory {
-    pub fn new() -> MemoryResult<Self> {
-        let memory_dir = dirs::home_dir()
+    /// Where [`new`](Self::new) keeps the store
+    pub fn default_dir() -> PathBuf {
+        dirs::home_dir()
             .unwrap_or_else(|| PathBuf::from("."))
             .join(".sena")
-            .join("memory");
+            .join("memory")
+    }
+
+    pub fn new() -> MemoryResult<Self> {
+        let memory_dir = Self::default_dir();
 
         if !memory_dir.exists() {
             fs::create_dir_all(&memory_dir)?;
         }
 
-        let store = MemoryStore::load(&memory_dir)?;
-
-        Ok(Self { store, memory_dir })
+        Self::open(memory_dir)
     }
 
     pub fn with_dir(memory_dir: PathBuf) -> MemoryResult<Self> {
@@ -163,20 +269,100 @@ impl PersistentMemory {
             fs::create_dir_all(&memory_dir)?;
         }
 
+        Self::open(memory_dir)
+    }
+
+    fn open(memory_dir: PathBuf) -> MemoryResult<Self> {
         let store = MemoryStore::load(&memory_dir)?;
+        let index = SemanticMemoryIndex::load(&memory_dir, &store.all());
+
+        Ok(Self {
+            store,
+            index,
+            hybrid: HybridSearch::default(),
+            retention: crate::config::SenaConfig::global().memory.retention(),
+            evicted: 0,
+            memory_dir,
+        })
+    }
+
+    /// Limits applied by [`add`](Self::add) once the store grows too large
+    pub fn with_retention(mut self, retention: MemoryRetention) -> Self {
+        self.retention = retention;
+        self
+    }
+
+    /// Weights used by [`search_hybrid`](Self::search_hybrid)
+    pub fn with_hybrid_search(mut self, hybrid: HybridSearch) -> Self {
+        self.hybrid = hybrid;
+        self
+    }
 
-        Ok(Self { store, memory_dir })
+    pub fn set_hybrid_search(&mut self, hybrid: HybridSearch) {
+        self.hybrid = hybrid;
     }
 
+    /// Store `entry`. When the store then holds more than
+    /// `retention.max_entries`, importance is decayed and the least valuable
+    /// unpinned memories are evicted.
     pub fn add(&mut self, entry: MemoryEntry) -> MemoryResult<String> {
         let id = entry.id.clone();
+        self.index.index_entry(&entry);
         self.store.add(entry);
+        self.apply_retention();
         self.save()?;
         Ok(id)
     }
 
+    fn apply_retention(&mut self) {
+        let MemoryRetention {
+            max_entries,
+            half_life_days,
+            min_importance,
+        } = self.retention;
+        if max_entries == 0 || self.store.count() <= max_entries {
+            return;
+        }
+        self.store.decay(half_life_days);
+        let evicted = self.store.evict(max_entries, min_importance);
+        if evicted.is_empty() {
+            return;
+        }
+        log::warn!(
+            "Memory store over its cap of {} entries: evicted {} ({})",
+            max_entries,
+            evicted.len(),
+            evicted
+                .iter()
+                .map(|entry| entry.id.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        for entry in &evicted {
+            self.index.remove_entry(&entry.id);
+        }
+        self.evicted += evicted.len();
+    }
+
+    /// Decay the importance of every unpinned memory by the retention
+    /// half-life, as the daemon does periodically, and save
+    pub fn decay(&mut self) -> MemoryResult<()> {
+        self.store.decay(self.retention.half_life_days);
+        self.save()
+    }
+
+    /// Pin or unpin a memory; pinned memories are never decayed or evicted
+    pub fn set_pinned(&mut self, id: &str, pinned: bool) -> MemoryResult<()> {
+        let entry = self
+            .store
+            .get_mut(id)
+            .ok_or_else(|| MemoryError::NotFound(id.to_string()))?;
+        entry.pinned = pinned;
+        self.save()
+    }
+
     pub fn add_quick(&mut self, content: &str, memory_type: MemoryType) -> MemoryResult<String> {
-        let entry = MemoryEntry::new(content, memory_type);
+        let entry = MemoryEntry::new(content, memory_type).with_auto_importance();
         self.add(entry)
     }
 
@@ -188,6 +374,19 @@ impl PersistentMemory {
         self.store.search(query)
     }
 
+    /// The `top_k` memories whose embeddings are closest to `query`'s,
+    /// which finds related wording that substring search misses
+    pub fn search_semantic(&self, query: &str, top_k: usize) -> Vec<SemanticSearchResult> {
+        self.index.search(query, top_k)
+    }
+
+    /// The `limit` best memories by keyword relevance and embedding
+    /// similarity, weighted by the configured [`HybridSearch`]
+    pub fn search_hybrid(&self, query: &str, limit: usize) -> Vec<HybridSearchResult> {
+        self.hybrid
+            .rank(query, &self.store.all(), &self.index, limit)
+    }
+
     pub fn search_by_type(&self, memory_type: &MemoryType) -> Vec<&MemoryEntry> {
         self.store.search_by_type(memory_type)
     }
@@ -198,13 +397,74 @@ impl PersistentMemory {
 
     pub fn remove(&mut self, id: &str) -> MemoryResult<Option<MemoryEntry>> {
         let entry = self.store.remove(id);
+        self.index.remove_entry(id);
         self.save()?;
         Ok(entry)
     }
 
-    pub fn clear(&mut self) -> MemoryResult<()> {
+    /// Remove every memory, snapshotting the store first
+    pub fn clear(&mut self) -> MemoryResult<Option<SnapshotId>> {
+        let snapshot = self.auto_snapshot("clear")?;
         self.store.clear();
-        self.save()
+        self.index.clear();
+        self.save()?;
+        Ok(snapshot)
+    }
+
+    /// Remove memories less important than `min_importance`, snapshotting
+    /// the store first when anything would be removed
+    pub fn prune(&mut self, min_importance: f64) -> MemoryResult<BulkChange> {
+        let ids: Vec<String> = self
+            .store
+            .all()
+            .into_iter()
+            .filter(|entry| entry.importance < min_importance)
+            .map(|entry| entry.id.clone())
+            .collect();
+        self.remove_all("prune", ids)
+    }
+
+    /// Remove memories whose type and content (ignoring case and
+    /// surrounding whitespace) repeat another's, keeping the most important
+    /// and then the oldest copy
+    pub fn dedup(&mut self) -> MemoryResult<BulkChange> {
+        let mut entries = self.store.all();
+        entries.sort_by(|a, b| {
+            b.importance
+                .total_cmp(&a.importance)
+                .then(a.created_at.cmp(&b.created_at))
+        });
+
+        let mut seen = std::collections::HashSet::new();
+        let ids: Vec<String> = entries
+            .into_iter()
+            .filter(|entry| {
+                let key = (
+                    format!("{:?}", entry.memory_type),
+                    entry.content.trim().to_lowercase(),
+                );
+                !seen.insert(key)
+            })
+            .map(|entry| entry.id.clone())
+            .collect();
+        self.remove_all("dedup", ids)
+    }
+
+    fn remove_all(&mut self, operation: &str, mut ids: Vec<String>) -> MemoryResult<BulkChange> {
+        if ids.is_empty() {
+            return Ok(BulkChange::default());
+        }
+        let snapshot = self.auto_snapshot(operation)?;
+        for id in &ids {
+            self.store.remove(id);
+            self.index.remove_entry(id);
+        }
+        self.save()?;
+        ids.sort();
+        Ok(BulkChange {
+            removed: ids,
+            snapshot,
+        })
     }
 
     pub fn count(&self) -> usize {
@@ -231,16 +491,19 @@ impl PersistentMemory {
             return String::new();
         }
 
+        // One line per memory: newlines in the content are escaped
         let mut context = String::from("Relevant memories:\n");
         for entry in entries {
-            context.push_str(&format!("- [{:?}] {}\n", entry.memory_type, entry.content));
+            let content = entry.content.replace("\r\n", "\n").replace('\n', "\\n");
+            context.push_str(&format!("- [{:?}] {}\n", entry.memory_type, content));
         }
 
         context
     }
 
     fn save(&self) -> MemoryResult<()> {
-        self.store.save(&self.memory_dir)
+        self.store.save(&self.memory_dir)?;
+        self.index.save(&self.memory_dir)
     }
 
     pub fn stats(&self) -> MemoryStats {
@@ -259,6 +522,7 @@ impl PersistentMemory {
             total_entries: all.len(),
             by_type,
             total_access_count: total_access,
+            evicted: self.evicted,
             avg_importance: if all.is_empty() {
                 0.0
             } else {
@@ -274,12 +538,47 @@ impl Default for PersistentMemory {
     }
 }
 
+/// When and how [`PersistentMemory::add`] ages out memories
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MemoryRetention {
+    /// Store size that triggers decay and eviction; 0 disables both
+    pub max_entries: usize,
+    pub half_life_days: f64,
+    /// Memories decayed below this are evicted along with any over the cap
+    pub min_importance: f64,
+}
+
+impl Default for MemoryRetention {
+    fn default() -> Self {
+        Self {
+            max_entries: DEFAULT_MAX_ENTRIES,
+            half_life_days: DEFAULT_HALF_LIFE_DAYS,
+            min_importance: DEFAULT_MIN_IMPORTANCE,
+        }
+    }
+}
+
+pub const DEFAULT_MAX_ENTRIES: usize = 10_000;
+pub const DEFAULT_HALF_LIFE_DAYS: f64 = 30.0;
+pub const DEFAULT_MIN_IMPORTANCE: f64 = 0.1;
+
+/// Result of a bulk removal
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BulkChange {
+    pub removed: Vec<String>,
+    /// Snapshot taken before the change, for `restore`
+    pub snapshot: Option<SnapshotId>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MemoryStats {
     pub total_entries: usize,
     pub by_type: HashMap<String, usize>,
     pub total_access_count: u64,
     pub avg_importance: f64,
+    /// Memories evicted over the retention cap since the store was opened
+    #[serde(default)]
+    pub evicted: usize,
 }
 
 #[cfg(test)]
@@ -313,4 +612,105 @@ mod tests {
         let score = entry.relevance_score("rust");
         assert!(score > 0.0);
     }
+
+    #[test]
+    fn test_auto_importance_ranks_emphatic_preferences_above_throwaway_notes() {
+        let preference = auto_importance(
+            "Always run cargo fmt before committing changes",
+            &MemoryType::Preference,
+        );
+        let throwaway = auto_importance("lol ok", &MemoryType::Conversation);
+
+        assert!(preference > throwaway);
+        assert!((0.0..=1.0).contains(&preference));
+        assert!((0.0..=1.0).contains(&throwaway));
+
+        let entry = MemoryEntry::new("Never commit secrets", MemoryType::Preference)
+            .with_auto_importance()
+            .with_importance(0.2);
+        assert!((entry.importance - 0.2).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_semantic_search_finds_related_wording() {
+        let dir = std::env::temp_dir().join(format!("sena-semantic-{}", uuid::Uuid::new_v4()));
+        let mut memory = PersistentMemory::with_dir(dir.clone()).unwrap();
+        let rust = memory
+            .add_quick("User prefers Rust", MemoryType::Preference)
+            .unwrap();
+        memory
+            .add_quick("Deploys go out on Tuesdays", MemoryType::Fact)
+            .unwrap();
+        let scratch = memory
+            .add_quick("Staging database is Postgres", MemoryType::Fact)
+            .unwrap();
+
+        assert!(memory.search("favorite language").is_empty());
+        assert_eq!(
+            memory.search_semantic("favorite language", 1)[0].memory_id,
+            rust
+        );
+        assert_eq!(
+            memory.search_hybrid("favorite language", 3)[0].memory_id,
+            rust
+        );
+
+        memory.remove(&scratch).unwrap();
+        assert_eq!(memory.search_semantic("favorite language", 10).len(), 2);
+
+        let persisted = std::fs::read_to_string(dir.join(EMBEDDINGS_FILE)).unwrap();
+        assert!(persisted.contains(&rust));
+        assert!(!persisted.contains(&scratch));
+        let reopened = PersistentMemory::with_dir(dir.clone()).unwrap();
+        assert_eq!(
+            reopened.search_semantic("favorite language", 1)[0].memory_id,
+            rust
+        );
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_add_evicts_old_untouched_entry_but_keeps_pinned() {
+        let dir = std::env::temp_dir().join(format!("sena-retention-{}", uuid::Uuid::new_v4()));
+        let mut memory = PersistentMemory::with_dir(dir.clone())
+            .unwrap()
+            .with_retention(MemoryRetention {
+                max_entries: 3,
+                half_life_days: 30.0,
+                min_importance: 0.1,
+            });
+
+        let year_ago = Utc::now() - chrono::Duration::days(365);
+        let mut stale = MemoryEntry::new("Old scratch note", MemoryType::Fact);
+        let mut pinned =
+            MemoryEntry::new("Deploy key lives in vault", MemoryType::Fact).with_pinned(true);
+        for entry in [&mut stale, &mut pinned] {
+            entry.created_at = year_ago;
+            entry.updated_at = year_ago;
+        }
+        let stale = memory.add(stale).unwrap();
+        let pinned = memory.add(pinned).unwrap();
+        let recent = memory
+            .add_quick("User prefers Rust", MemoryType::Preference)
+            .unwrap();
+        assert_eq!(memory.count(), 3);
+
+        let newest = memory
+            .add_quick("Deploys go out on Tuesdays", MemoryType::Fact)
+            .unwrap();
+        assert_eq!(memory.count(), 3);
+        assert_eq!(memory.stats().evicted, 1);
+        assert!(memory.get(&stale).is_none());
+        assert!(memory.search("scratch").is_empty());
+        for id in [&pinned, &recent, &newest] {
+            assert!(memory.get(id).is_some());
+        }
+        assert!((memory.get(&pinned).unwrap().importance - 0.5).abs() < f64::EPSILON);
+
+        let mut reopened = PersistentMemory::with_dir(dir.clone()).unwrap();
+        assert!(reopened.get(&pinned).unwrap().pinned);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
 }
@@ -1,6 +1,101 @@
-use super::MemoryEntry;
+use super::{MemoryEntry, MemoryError, MemoryResult};
+use crate::sync::write_atomic;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// File next to `memories.json` holding the persisted embeddings
+pub const EMBEDDINGS_FILE: &str = "embeddings.json";
+
+/// Bumped whenever [`SimpleHashEmbedder`] changes, so embeddings persisted
+/// by an older version are recomputed instead of compared
+const EMBEDDER_VERSION: u32 = 3;
+
+/// Words that point at the same idea. Each word also adds its concept to
+/// the embedding, so "favorite language" lands near "prefers Rust" even
+/// though the two share no words.
+const CONCEPTS: &[(&str, &[&str])] = &[
+    (
+        "preference",
+        &[
+            "prefer",
+            "prefers",
+            "preferred",
+            "preference",
+            "preferences",
+            "favorite",
+            "favourite",
+            "like",
+            "likes",
+            "love",
+            "loves",
+            "choice",
+        ],
+    ),
+    (
+        "programming_language",
+        &[
+            "language",
+            "languages",
+            "rust",
+            "python",
+            "javascript",
+            "typescript",
+            "golang",
+            "java",
+            "kotlin",
+            "swift",
+            "ruby",
+            "c++",
+            "haskell",
+            "elixir",
+        ],
+    ),
+    (
+        "editor",
+        &["editor", "ide", "vim", "neovim", "emacs", "vscode", "helix"],
+    ),
+    (
+        "database",
+        &[
+            "database",
+            "db",
+            "postgres",
+            "postgresql",
+            "mysql",
+            "sqlite",
+            "redis",
+            "mongodb",
+        ],
+    ),
+    (
+        "testing",
+        &["test", "tests", "testing", "spec", "specs", "coverage"],
+    ),
+    (
+        "deployment",
+        &[
+            "deploy",
+            "deploys",
+            "deployment",
+            "release",
+            "releases",
+            "ship",
+        ],
+    ),
+    (
+        "authentication",
+        &["auth", "authentication", "login", "jwt", "oauth", "session"],
+    ),
+];
+
+fn concept_of(word: &str) -> Option<&'static str> {
+    CONCEPTS
+        .iter()
+        .find(|(_, words)| words.contains(&word))
+        .map(|(concept, _)| *concept)
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EmbeddingVector {
@@ -105,9 +200,11 @@ impl TextEmbedder for SimpleHashEmbedder {
 
             values[idx] += position_weight * word_weight;
 
-            for (j, c) in word.chars().enumerate() {
-                let char_idx = ((hash as usize) + (c as usize) + j) % self.dimensions;
-                values[char_idx] += 0.1 * position_weight;
+            // Shared trigrams let inflections like "encrypted" and
+            // "encrypting" land near each other without a word list
+            for trigram in char_trigrams(word) {
+                let trigram_idx = (simple_hash(&trigram) as usize) % self.dimensions;
+                values[trigram_idx] += 0.15 * position_weight;
             }
         }
 
@@ -118,6 +215,14 @@ impl TextEmbedder for SimpleHashEmbedder {
             values[idx] += 0.5;
         }
 
+        for word in &words {
+            let word = word.trim_matches(|c: char| !c.is_alphanumeric() && c != '+');
+            if let Some(concept) = concept_of(word) {
+                let idx = (simple_hash(&format!("concept:{}", concept)) as usize) % self.dimensions;
+                values[idx] += 1.0;
+            }
+        }
+
         let mut embedding = EmbeddingVector::new(values);
         embedding.normalize();
         embedding
@@ -136,6 +241,12 @@ fn simple_hash(s: &str) -> u64 {
     hash
 }
 
+/// Character trigrams of `word` with `<` and `>` marking its edges
+fn char_trigrams(word: &str) -> Vec<String> {
+    let chars: Vec<char> = format!("<{}>", word).chars().collect();
+    chars.windows(3).map(|w| w.iter().collect()).collect()
+}
+
 fn generate_bigrams(text: &str) -> Vec<String> {
     let words: Vec<&str> = text.split_whitespace().collect();
     let mut bigrams = Vec::new();
@@ -151,6 +262,18 @@ fn generate_bigrams(text: &str) -> Vec<String> {
 pub struct SemanticMemoryEntry {
     pub memory_id: String,
     pub embedding: EmbeddingVector,
+    /// Hash of the content the embedding was computed from, so edited
+    /// memories are re-embedded on load
+    #[serde(default)]
+    pub content_hash: u64,
+}
+
+/// On-disk layout of [`EMBEDDINGS_FILE`]
+#[derive(Serialize, Deserialize)]
+struct PersistedEmbeddings {
+    version: u32,
+    dimensions: usize,
+    entries: Vec<SemanticMemoryEntry>,
 }
 
 pub struct SemanticMemoryIndex {
@@ -173,12 +296,59 @@ impl SemanticMemoryIndex {
         }
     }
 
+    /// Index for `entries`, reusing the embeddings persisted in `dir` when
+    /// they were computed by this embedder from the same content. A missing
+    /// or unreadable file just means everything is embedded again.
+    pub fn load(dir: &Path, entries: &[&MemoryEntry]) -> Self {
+        let mut index = Self::new();
+        let mut persisted: HashMap<String, SemanticMemoryEntry> =
+            fs::read_to_string(dir.join(EMBEDDINGS_FILE))
+                .ok()
+                .and_then(|content| serde_json::from_str::<PersistedEmbeddings>(&content).ok())
+                .filter(|file| {
+                    file.version == EMBEDDER_VERSION
+                        && file.dimensions == index.embedder.dimensions()
+                })
+                .map(|file| {
+                    file.entries
+                        .into_iter()
+                        .map(|entry| (entry.memory_id.clone(), entry))
+                        .collect()
+                })
+                .unwrap_or_default();
+
+        for entry in entries {
+            match persisted.remove(&entry.id) {
+                Some(cached) if cached.content_hash == simple_hash(&entry.content) => {
+                    index.entries.insert(entry.id.clone(), cached);
+                }
+                _ => index.index_entry(entry),
+            }
+        }
+        index
+    }
+
+    pub fn save(&self, dir: &Path) -> MemoryResult<()> {
+        let mut entries: Vec<SemanticMemoryEntry> = self.entries.values().cloned().collect();
+        entries.sort_by(|a, b| a.memory_id.cmp(&b.memory_id));
+        let file = PersistedEmbeddings {
+            version: EMBEDDER_VERSION,
+            dimensions: self.embedder.dimensions(),
+            entries,
+        };
+        let content = serde_json::to_string(&file)
+            .map_err(|e| MemoryError::SerializationError(e.to_string()))?;
+        write_atomic(dir.join(EMBEDDINGS_FILE), content)?;
+        Ok(())
+    }
+
     pub fn index_entry(&mut self, entry: &MemoryEntry) {
         let embedding = self.embedder.embed(&entry.content);
 
         let semantic_entry = SemanticMemoryEntry {
             memory_id: entry.id.clone(),
             embedding,
+            content_hash: simple_hash(&entry.content),
         };
 
         self.entries.insert(entry.id.clone(), semantic_entry);
@@ -261,6 +431,16 @@ pub struct SemanticSearchResult {
     pub similarity: f32,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HybridSearchResult {
+    pub memory_id: String,
+    /// Weighted combination of the two scores below
+    pub score: f64,
+    pub keyword_score: f64,
+    pub semantic_similarity: f32,
+}
+
+#[derive(Debug, Clone, Copy)]
 pub struct HybridSearch {
     keyword_weight: f32,
     semantic_weight: f32,
@@ -291,6 +471,41 @@ impl HybridSearch {
         (self.keyword_weight as f64 * keyword_score)
             + (self.semantic_weight as f64 * semantic_similarity as f64)
     }
+
+    /// Rank `entries` for `query` by [`MemoryEntry::relevance_score`]
+    /// combined with their similarity in `index`, best first
+    pub fn rank(
+        &self,
+        query: &str,
+        entries: &[&MemoryEntry],
+        index: &SemanticMemoryIndex,
+        limit: usize,
+    ) -> Vec<HybridSearchResult> {
+        let similarities: HashMap<String, f32> = index
+            .search(query, index.count())
+            .into_iter()
+            .map(|result| (result.memory_id, result.similarity))
+            .collect();
+
+        let mut results: Vec<HybridSearchResult> = entries
+            .iter()
+            .map(|entry| {
+                let keyword_score = entry.relevance_score(query);
+                let semantic_similarity =
+                    similarities.get(&entry.id).copied().unwrap_or(0.0).max(0.0);
+                HybridSearchResult {
+                    memory_id: entry.id.clone(),
+                    score: self.combine_scores(keyword_score, semantic_similarity),
+                    keyword_score,
+                    semantic_similarity,
+                }
+            })
+            .collect();
+
+        results.sort_by(|a, b| b.score.total_cmp(&a.score));
+        results.truncate(limit);
+        results
+    }
 }
 
 impl Default for HybridSearch {
@@ -350,6 +565,37 @@ mod tests {
         assert!(sim_rust > sim_different);
     }
 
+    #[test]
+    fn test_inflected_words_match_without_concepts() {
+        let mut index = SemanticMemoryIndex::new();
+        let backups = MemoryEntry::new("Nightly backups are encrypted with age", MemoryType::Fact);
+        let mobile = MemoryEntry::new("Our mobile app uses Flutter", MemoryType::Fact);
+        let invoices = MemoryEntry::new(
+            "Invoices are generated on the first of each month",
+            MemoryType::Fact,
+        );
+        for entry in [&backups, &mobile, &invoices] {
+            index.index_entry(entry);
+        }
+
+        for (query, expected) in [
+            ("encrypting backup", &backups),
+            ("flutter applications", &mobile),
+            ("monthly invoice generation", &invoices),
+        ] {
+            assert!(query
+                .split_whitespace()
+                .all(|word| concept_of(word).is_none()));
+            assert!(!expected.content.to_lowercase().contains(query));
+            assert_eq!(
+                index.search(query, 1)[0].memory_id,
+                expected.id,
+                "{}",
+                query
+            );
+        }
+    }
+
     #[test]
     fn test_semantic_index() {
         let mut index = SemanticMemoryIndex::new();
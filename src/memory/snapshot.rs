@@ -0,0 +1,243 @@
+//! Memory Snapshots
+//!
+//! Point-in-time copies of the memory store under
+//! `~/.sena/memory/snapshots/<id>/`. Bulk operations take one automatically
+//! so a prune, dedup or clear can be undone with
+//! `sena memory snapshot restore`.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+
+use super::{MemoryError, MemoryResult, MemoryStore, PersistentMemory, SemanticMemoryIndex};
+use crate::sync::write_atomic;
+
+/// Directory under the memory dir holding one subdirectory per snapshot
+pub const SNAPSHOTS_DIR: &str = "snapshots";
+
+const INFO_FILE: &str = "snapshot.json";
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SnapshotId(String);
+
+impl SnapshotId {
+    /// Sortable id: creation time plus a short random suffix
+    fn generate(created_at: DateTime<Utc>) -> Self {
+        let suffix = uuid::Uuid::new_v4().simple().to_string();
+        Self(format!(
+            "snap_{}_{}",
+            created_at.format("%Y%m%d%H%M%S"),
+            &suffix[..6]
+        ))
+    }
+
+    /// Validate a user-supplied id so it cannot escape the snapshots dir
+    pub fn parse(id: &str) -> MemoryResult<Self> {
+        let valid = !id.is_empty()
+            && id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+        if valid {
+            Ok(Self(id.to_string()))
+        } else {
+            Err(MemoryError::NotFound(format!("snapshot {}", id)))
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for SnapshotId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotInfo {
+    pub id: SnapshotId,
+    pub label: String,
+    pub created_at: DateTime<Utc>,
+    pub entries: usize,
+    /// Taken by a bulk operation rather than by the user
+    #[serde(default)]
+    pub automatic: bool,
+}
+
+impl PersistentMemory {
+    fn snapshots_dir(&self) -> PathBuf {
+        self.memory_dir.join(SNAPSHOTS_DIR)
+    }
+
+    fn snapshot_dir(&self, id: &SnapshotId) -> PathBuf {
+        self.snapshots_dir().join(id.as_str())
+    }
+
+    /// Copy the current store into a new snapshot
+    pub fn snapshot(&self, label: &str) -> MemoryResult<SnapshotId> {
+        self.write_snapshot(label, false)
+    }
+
+    /// Snapshot taken before a bulk operation; skipped when there is
+    /// nothing to lose
+    pub(crate) fn auto_snapshot(&self, operation: &str) -> MemoryResult<Option<SnapshotId>> {
+        if self.count() == 0 {
+            return Ok(None);
+        }
+        self.write_snapshot(&format!("before {}", operation), true)
+            .map(Some)
+    }
+
+    fn write_snapshot(&self, label: &str, automatic: bool) -> MemoryResult<SnapshotId> {
+        let created_at = Utc::now();
+        let id = SnapshotId::generate(created_at);
+        let info = SnapshotInfo {
+            id: id.clone(),
+            label: label.to_string(),
+            created_at,
+            entries: self.count(),
+            automatic,
+        };
+
+        // Fill a hidden staging dir and rename it into place, so a snapshot
+        // is either complete or absent
+        let staging = self.snapshots_dir().join(format!(".{}.partial", id));
+        fs::create_dir_all(&staging)?;
+        let written = self.store.save(&staging).and_then(|_| {
+            let content = serde_json::to_string_pretty(&info)
+                .map_err(|e| MemoryError::SerializationError(e.to_string()))?;
+            write_atomic(staging.join(INFO_FILE), content)?;
+            fs::rename(&staging, self.snapshot_dir(&id))?;
+            Ok(())
+        });
+        if written.is_err() {
+            let _ = fs::remove_dir_all(&staging);
+        }
+        written.map(|_| id)
+    }
+
+    /// Snapshots, newest first
+    pub fn list_snapshots(&self) -> MemoryResult<Vec<SnapshotInfo>> {
+        let dir = self.snapshots_dir();
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut snapshots: Vec<SnapshotInfo> = fs::read_dir(&dir)?
+            .flatten()
+            .filter(|entry| !entry.file_name().to_string_lossy().starts_with('.'))
+            .filter_map(|entry| fs::read_to_string(entry.path().join(INFO_FILE)).ok())
+            .filter_map(|content| serde_json::from_str(&content).ok())
+            .collect();
+        snapshots.sort_by(|a, b| b.created_at.cmp(&a.created_at).then(b.id.0.cmp(&a.id.0)));
+        Ok(snapshots)
+    }
+
+    /// Replace the store with a snapshot's contents. The snapshot is read in
+    /// full before the live file is swapped in a single rename, so a failed
+    /// restore leaves the current memories untouched. Returns the number of
+    /// restored entries.
+    pub fn restore(&mut self, id: &SnapshotId) -> MemoryResult<usize> {
+        let dir = self.snapshot_dir(id);
+        if !dir.join(INFO_FILE).exists() {
+            return Err(MemoryError::NotFound(format!("snapshot {}", id)));
+        }
+
+        let restored = MemoryStore::load(&dir)?;
+        restored.save(&self.memory_dir)?;
+        self.index = SemanticMemoryIndex::load(&self.memory_dir, &restored.all());
+        self.store = restored;
+        self.index.save(&self.memory_dir)?;
+        Ok(self.count())
+    }
+
+    pub fn delete_snapshot(&self, id: &SnapshotId) -> MemoryResult<()> {
+        let dir = self.snapshot_dir(id);
+        if !dir.join(INFO_FILE).exists() {
+            return Err(MemoryError::NotFound(format!("snapshot {}", id)));
+        }
+        fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::{MemoryEntry, MemoryType};
+
+    fn scratch() -> PathBuf {
+        std::env::temp_dir().join(format!("sena-snapshot-{}", uuid::Uuid::new_v4()))
+    }
+
+    #[test]
+    fn test_snapshot_before_prune_restores_pruned_entries() {
+        let dir = scratch();
+        let mut memory = PersistentMemory::with_dir(dir.clone()).unwrap();
+        memory
+            .add(
+                MemoryEntry::new("Deploys go out on Tuesdays", MemoryType::Fact)
+                    .with_importance(0.9),
+            )
+            .unwrap();
+        let weak = memory
+            .add(MemoryEntry::new("scratch note", MemoryType::Context).with_importance(0.1))
+            .unwrap();
+
+        let pruned = memory.prune(0.5).unwrap();
+        assert_eq!(pruned.removed, vec![weak.clone()]);
+        assert_eq!(memory.count(), 1);
+
+        let snapshot = pruned.snapshot.expect("automatic snapshot");
+        let listed = memory.list_snapshots().unwrap();
+        assert_eq!(listed.len(), 1);
+        assert!(listed[0].automatic);
+        assert_eq!(listed[0].entries, 2);
+
+        assert_eq!(memory.restore(&snapshot).unwrap(), 2);
+        assert!(memory.get(&weak).is_some());
+        let reopened = PersistentMemory::with_dir(dir.clone()).unwrap();
+        assert_eq!(reopened.count(), 2);
+
+        memory.delete_snapshot(&snapshot).unwrap();
+        assert!(memory.list_snapshots().unwrap().is_empty());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_failed_restore_leaves_store_untouched() {
+        let dir = scratch();
+        let mut memory = PersistentMemory::with_dir(dir.clone()).unwrap();
+        memory
+            .add_quick("Use tabs in Makefiles", MemoryType::Preference)
+            .unwrap();
+        let snapshot = memory.snapshot("manual").unwrap();
+        memory
+            .add_quick("Prefer ripgrep", MemoryType::Preference)
+            .unwrap();
+
+        fs::write(
+            dir.join(SNAPSHOTS_DIR)
+                .join(snapshot.as_str())
+                .join("memories.json"),
+            "[{\"id\": ",
+        )
+        .unwrap();
+        assert!(memory.restore(&snapshot).is_err());
+        assert_eq!(memory.count(), 2);
+        assert_eq!(PersistentMemory::with_dir(dir.clone()).unwrap().count(), 2);
+
+        assert!(memory
+            .restore(&SnapshotId::parse("snap_missing").unwrap())
+            .is_err());
+        assert!(SnapshotId::parse("../memories").is_err());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
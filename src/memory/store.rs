@@ -1,8 +1,10 @@
+use chrono::{DateTime, Utc};
 use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 
 use super::{MemoryEntry, MemoryError, MemoryResult, MemoryType};
+use crate::sync::write_atomic;
 
 pub struct MemoryStore {
     entries: HashMap<String, MemoryEntry>,
@@ -45,7 +47,7 @@ impl MemoryStore {
         let entries: Vec<&MemoryEntry> = self.entries.values().collect();
         let content = serde_json::to_string_pretty(&entries)
             .map_err(|e| MemoryError::SerializationError(e.to_string()))?;
-        fs::write(&file_path, content)?;
+        write_atomic(&file_path, content)?;
         Ok(())
     }
 
@@ -110,7 +112,7 @@ impl MemoryStore {
 
     pub fn recent(&self, limit: usize) -> Vec<&MemoryEntry> {
         let mut entries: Vec<_> = self.entries.values().collect();
-        entries.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+        entries.sort_by_key(|b| std::cmp::Reverse(b.updated_at));
         entries.into_iter().take(limit).collect()
     }
 
@@ -124,9 +126,44 @@ impl MemoryStore {
         entries.into_iter().take(limit).collect()
     }
 
+    /// Decay every unpinned entry's importance; see [`MemoryEntry::decay`]
+    pub fn decay(&mut self, half_life_days: f64) {
+        let now = Utc::now();
+        for entry in self.entries.values_mut() {
+            entry.decay(now, half_life_days);
+        }
+    }
+
+    /// Once more than `max_entries` are stored, remove unpinned entries
+    /// below `min_importance` and then the least important (oldest access
+    /// first) until back at the cap. Pinned entries are never removed, so
+    /// the store can stay over the cap. Returns the removed entries.
+    pub fn evict(&mut self, max_entries: usize, min_importance: f64) -> Vec<MemoryEntry> {
+        if self.entries.len() <= max_entries {
+            return Vec::new();
+        }
+
+        let mut candidates: Vec<(String, f64, DateTime<Utc>)> = self
+            .entries
+            .values()
+            .filter(|e| !e.pinned)
+            .map(|e| (e.id.clone(), e.importance, e.updated_at))
+            .collect();
+        candidates.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.2.cmp(&b.2)));
+
+        let mut evicted = Vec::new();
+        for (id, importance, _) in candidates {
+            if self.entries.len() <= max_entries && importance >= min_importance {
+                break;
+            }
+            evicted.extend(self.entries.remove(&id));
+        }
+        evicted
+    }
+
     pub fn frequently_accessed(&self, limit: usize) -> Vec<&MemoryEntry> {
         let mut entries: Vec<_> = self.entries.values().collect();
-        entries.sort_by(|a, b| b.access_count.cmp(&a.access_count));
+        entries.sort_by_key(|b| std::cmp::Reverse(b.access_count));
         entries.into_iter().take(limit).collect()
     }
 }
@@ -180,4 +217,55 @@ mod tests {
         store.remove(&id);
         assert_eq!(store.count(), 0);
     }
+
+    #[test]
+    fn test_decay_is_slower_for_accessed_entries() {
+        let mut store = MemoryStore::new();
+        let created = Utc::now() - chrono::Duration::days(30);
+        let mut idle = MemoryEntry::new("Idle", MemoryType::Fact).with_importance(0.8);
+        let mut used = MemoryEntry::new("Used", MemoryType::Fact).with_importance(0.8);
+        for entry in [&mut idle, &mut used] {
+            entry.created_at = created;
+            entry.updated_at = created;
+        }
+        used.access_count = 10;
+        let (idle_id, used_id) = (idle.id.clone(), used.id.clone());
+        store.add(idle);
+        store.add(used);
+
+        store.decay(30.0);
+        let idle = store.get(&idle_id).unwrap().importance;
+        let used = store.get(&used_id).unwrap().importance;
+        assert!((idle - 0.4).abs() < 0.01);
+        assert!(used > idle);
+
+        // Decay resumes from the last pass instead of compounding
+        store.decay(30.0);
+        assert!((store.get(&idle_id).unwrap().importance - idle).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_evict_trims_to_cap_sparing_pinned() {
+        let mut store = MemoryStore::new();
+        let low = MemoryEntry::new("Low", MemoryType::Fact).with_importance(0.2);
+        let pinned = MemoryEntry::new("Pinned", MemoryType::Fact)
+            .with_importance(0.05)
+            .with_pinned(true);
+        let faint = MemoryEntry::new("Faint", MemoryType::Fact).with_importance(0.05);
+        let high = MemoryEntry::new("High", MemoryType::Fact).with_importance(0.9);
+        let ids: Vec<String> = [&low, &pinned, &faint, &high]
+            .iter()
+            .map(|e| e.id.clone())
+            .collect();
+        for entry in [low, pinned, faint, high] {
+            store.add(entry);
+        }
+
+        assert!(store.evict(4, 0.1).is_empty());
+        let evicted: Vec<String> = store.evict(3, 0.1).into_iter().map(|e| e.id).collect();
+        assert_eq!(evicted, vec![ids[2].clone()]);
+        let evicted: Vec<String> = store.evict(1, 0.1).into_iter().map(|e| e.id).collect();
+        assert_eq!(evicted, vec![ids[0].clone(), ids[3].clone()]);
+        assert!(store.get(&ids[1]).is_some());
+    }
 }
@@ -0,0 +1,339 @@
+//! Memory Import/Export
+//!
+//! Moves memories between machines as JSONL, one `MemoryEntry` per line.
+//! Imported lines are validated one by one, so a bad row is reported with
+//! its line number instead of failing the import.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use super::{MemoryEntry, MemoryResult, MemoryType, PersistentMemory};
+use crate::sync::write_atomic;
+
+/// What to do when an imported entry's id is already stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MergeStrategy {
+    /// Keep the stored entry
+    #[default]
+    Skip,
+    /// Replace the stored entry
+    Overwrite,
+    /// Replace the stored entry only if the imported one has a later `updated_at`
+    KeepNewer,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportError {
+    /// 1-based line number in the imported file
+    pub line: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportSummary {
+    pub imported: usize,
+    pub skipped: usize,
+    pub errors: Vec<ImportError>,
+}
+
+/// Validate one line's fields and build the entry. Optional fields missing
+/// from the line take `MemoryEntry::new` defaults.
+pub fn parse_entry(line: &str) -> Result<MemoryEntry, String> {
+    let value: Value = serde_json::from_str(line).map_err(|e| format!("invalid JSON: {}", e))?;
+    let object = value
+        .as_object()
+        .ok_or_else(|| "expected a JSON object".to_string())?;
+
+    let id = required_string(object, "id")?;
+    let content = required_string(object, "content")?;
+    let memory_type = match object.get("memory_type") {
+        Some(value) => parse_memory_type(value)?,
+        None => return Err("missing required field 'memory_type'".to_string()),
+    };
+
+    let mut entry = MemoryEntry::new(content, memory_type);
+    entry.id = id;
+
+    if let Some(tags) = object.get("tags") {
+        entry.tags = tags
+            .as_array()
+            .and_then(|tags| {
+                tags.iter()
+                    .map(|t| t.as_str().map(str::to_string))
+                    .collect()
+            })
+            .ok_or_else(|| "'tags' must be an array of strings".to_string())?;
+    }
+    if let Some(metadata) = object.get("metadata") {
+        entry.metadata = metadata
+            .as_object()
+            .and_then(|map| {
+                map.iter()
+                    .map(|(k, v)| v.as_str().map(|v| (k.clone(), v.to_string())))
+                    .collect::<Option<HashMap<_, _>>>()
+            })
+            .ok_or_else(|| "'metadata' must be an object of strings".to_string())?;
+    }
+    if let Some(created_at) = optional_timestamp(object, "created_at")? {
+        entry.created_at = created_at;
+        entry.updated_at = created_at;
+    }
+    if let Some(updated_at) = optional_timestamp(object, "updated_at")? {
+        entry.updated_at = updated_at;
+    }
+    if let Some(count) = object.get("access_count") {
+        entry.access_count = count
+            .as_u64()
+            .ok_or_else(|| "'access_count' must be a non-negative integer".to_string())?;
+    }
+    if let Some(importance) = object.get("importance") {
+        entry.importance = importance
+            .as_f64()
+            .filter(|i| (0.0..=1.0).contains(i))
+            .ok_or_else(|| "'importance' must be a number from 0 to 1".to_string())?;
+    }
+    if let Some(pinned) = object.get("pinned") {
+        entry.pinned = pinned
+            .as_bool()
+            .ok_or_else(|| "'pinned' must be a boolean".to_string())?;
+    }
+    entry.decayed_at = optional_timestamp(object, "decayed_at")?;
+
+    Ok(entry)
+}
+
+fn required_string(object: &Map<String, Value>, field: &str) -> Result<String, String> {
+    match object.get(field) {
+        Some(Value::String(s)) if !s.trim().is_empty() => Ok(s.clone()),
+        Some(Value::String(_)) => Err(format!("'{}' must not be empty", field)),
+        Some(_) => Err(format!("'{}' must be a string", field)),
+        None => Err(format!("missing required field '{}'", field)),
+    }
+}
+
+fn optional_timestamp(
+    object: &Map<String, Value>,
+    field: &str,
+) -> Result<Option<DateTime<Utc>>, String> {
+    let Some(value) = object.get(field) else {
+        return Ok(None);
+    };
+    value
+        .as_str()
+        .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+        .map(|t| Some(t.with_timezone(&Utc)))
+        .ok_or_else(|| format!("'{}' must be an RFC 3339 timestamp", field))
+}
+
+fn parse_memory_type(value: &Value) -> Result<MemoryType, String> {
+    serde_json::from_value(value.clone()).map_err(|_| {
+        "'memory_type' must be one of Preference, Fact, Project, Context, Conversation \
+         or {\"Custom\": \"...\"}"
+            .to_string()
+    })
+}
+
+impl PersistentMemory {
+    /// Write every memory to `path` as JSONL, oldest first. Returns the
+    /// number of entries written.
+    pub fn export_jsonl(&self, path: &Path) -> MemoryResult<usize> {
+        let mut entries = self.all();
+        entries.sort_by(|a, b| a.created_at.cmp(&b.created_at).then(a.id.cmp(&b.id)));
+
+        let mut content = String::new();
+        for entry in &entries {
+            let line = serde_json::to_string(entry)
+                .map_err(|e| super::MemoryError::SerializationError(e.to_string()))?;
+            content.push_str(&line);
+            content.push('\n');
+        }
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        write_atomic(path, content)?;
+        Ok(entries.len())
+    }
+
+    /// Add the memories in a JSONL file, resolving id conflicts with
+    /// `strategy`. Invalid lines are listed in the summary and skipped;
+    /// only an unreadable file fails the import. Replacing stored memories
+    /// snapshots the store first.
+    pub fn import_jsonl(
+        &mut self,
+        path: &Path,
+        strategy: MergeStrategy,
+    ) -> MemoryResult<ImportSummary> {
+        let content = fs::read_to_string(path)?;
+        let mut summary = ImportSummary::default();
+        let mut accepted = Vec::new();
+
+        for (index, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match parse_entry(line) {
+                Ok(entry) => accepted.push(entry),
+                Err(message) => summary.errors.push(ImportError {
+                    line: index + 1,
+                    message,
+                }),
+            }
+        }
+
+        let replaces = |store: &super::MemoryStore, entry: &MemoryEntry| {
+            store.get(&entry.id).is_some_and(|existing| match strategy {
+                MergeStrategy::Skip => false,
+                MergeStrategy::Overwrite => true,
+                MergeStrategy::KeepNewer => entry.updated_at > existing.updated_at,
+            })
+        };
+        if accepted.iter().any(|entry| replaces(&self.store, entry)) {
+            self.auto_snapshot("import")?;
+        }
+
+        for entry in accepted {
+            if self.store.get(&entry.id).is_some() && !replaces(&self.store, &entry) {
+                summary.skipped += 1;
+                continue;
+            }
+            self.index.index_entry(&entry);
+            self.store.add(entry);
+            summary.imported += 1;
+        }
+
+        if summary.imported > 0 {
+            self.save()?;
+        }
+        Ok(summary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn temp_dir(label: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("sena-memory-{}-{}", label, uuid::Uuid::new_v4()))
+    }
+
+    fn entry(id: &str, content: &str, updated_at: DateTime<Utc>) -> MemoryEntry {
+        let mut entry = MemoryEntry::new(content, MemoryType::Fact);
+        entry.id = id.to_string();
+        entry.updated_at = updated_at;
+        entry
+    }
+
+    /// A store holding `mem_a` (updated now) and an export file with a newer
+    /// `mem_a`, an older `mem_b` and a new `mem_c`
+    fn conflicting_import() -> (std::path::PathBuf, PersistentMemory, std::path::PathBuf) {
+        let now = Utc::now();
+        let dir = temp_dir("import");
+        let mut memory = PersistentMemory::with_dir(dir.join("store")).unwrap();
+        memory.add(entry("mem_a", "stored a", now)).unwrap();
+        memory.add(entry("mem_b", "stored b", now)).unwrap();
+
+        let mut source = PersistentMemory::with_dir(dir.join("source")).unwrap();
+        source
+            .add(entry("mem_a", "imported a", now + Duration::hours(1)))
+            .unwrap();
+        source
+            .add(entry("mem_b", "imported b", now - Duration::hours(1)))
+            .unwrap();
+        source.add(entry("mem_c", "imported c", now)).unwrap();
+        let file = dir.join("export.jsonl");
+        assert_eq!(source.export_jsonl(&file).unwrap(), 3);
+
+        (dir, memory, file)
+    }
+
+    fn content(memory: &PersistentMemory, id: &str) -> String {
+        memory
+            .all()
+            .into_iter()
+            .find(|e| e.id == id)
+            .map(|e| e.content.clone())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_import_skip_keeps_stored_entries() {
+        let (dir, mut memory, file) = conflicting_import();
+
+        let summary = memory.import_jsonl(&file, MergeStrategy::Skip).unwrap();
+        assert_eq!((summary.imported, summary.skipped), (1, 2));
+        assert_eq!(content(&memory, "mem_a"), "stored a");
+        assert_eq!(content(&memory, "mem_b"), "stored b");
+        assert_eq!(content(&memory, "mem_c"), "imported c");
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_import_overwrite_replaces_conflicts() {
+        let (dir, mut memory, file) = conflicting_import();
+
+        let summary = memory
+            .import_jsonl(&file, MergeStrategy::Overwrite)
+            .unwrap();
+        assert_eq!((summary.imported, summary.skipped), (3, 0));
+        assert_eq!(content(&memory, "mem_a"), "imported a");
+        assert_eq!(content(&memory, "mem_b"), "imported b");
+        assert_eq!(memory.list_snapshots().unwrap().len(), 1);
+
+        let reopened = PersistentMemory::with_dir(dir.join("store")).unwrap();
+        assert_eq!(reopened.count(), 3);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_import_keep_newer_compares_updated_at() {
+        let (dir, mut memory, file) = conflicting_import();
+
+        let summary = memory
+            .import_jsonl(&file, MergeStrategy::KeepNewer)
+            .unwrap();
+        assert_eq!((summary.imported, summary.skipped), (2, 1));
+        assert_eq!(content(&memory, "mem_a"), "imported a");
+        assert_eq!(content(&memory, "mem_b"), "stored b");
+        assert_eq!(content(&memory, "mem_c"), "imported c");
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_import_reports_invalid_lines() {
+        let dir = temp_dir("invalid");
+        let mut memory = PersistentMemory::with_dir(dir.join("store")).unwrap();
+        let file = dir.join("import.jsonl");
+        fs::write(
+            &file,
+            [
+                r#"{"id":"mem_ok","content":"Valid","memory_type":"Fact"}"#,
+                "not json",
+                r#"{"id":"mem_x","memory_type":"Fact"}"#,
+                "",
+                r#"{"id":"mem_y","content":"Bad","memory_type":"Opinion"}"#,
+                r#"{"id":"mem_z","content":"Bad","memory_type":"Fact","importance":2}"#,
+                r#"{"id":"mem_t","content":"Custom","memory_type":{"Custom":"note"},"tags":["a"]}"#,
+            ]
+            .join("\n"),
+        )
+        .unwrap();
+
+        let summary = memory.import_jsonl(&file, MergeStrategy::Skip).unwrap();
+        assert_eq!(summary.imported, 2);
+        let lines: Vec<usize> = summary.errors.iter().map(|e| e.line).collect();
+        assert_eq!(lines, vec![2, 3, 5, 6]);
+        assert!(summary.errors[1].message.contains("'content'"));
+        assert_eq!(memory.count(), 2);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
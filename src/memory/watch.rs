@@ -0,0 +1,314 @@
+//! Note Ingestion
+//!
+//! Turns lines from a scratch notes file or stdin stream into memories,
+//! inferring a memory type and tags from each note and skipping notes that
+//! duplicate recent memories.
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use super::{MemoryEntry, MemoryResult, MemoryType, PersistentMemory};
+
+const RECENT_WINDOW: usize = 100;
+
+const TAG_KEYWORDS: &[&str] = &[
+    "api",
+    "auth",
+    "bug",
+    "database",
+    "deploy",
+    "docker",
+    "git",
+    "javascript",
+    "python",
+    "rust",
+    "security",
+    "test",
+    "typescript",
+];
+
+/// Ingests notes as memories, deduplicating against recent entries
+pub struct NoteIngester {
+    memory_type: Option<MemoryType>,
+    tags: Vec<String>,
+    recent: VecDeque<String>,
+}
+
+impl NoteIngester {
+    /// Create an ingester seeded with the most recent memories for dedupe
+    pub fn new(memory: &PersistentMemory) -> Self {
+        let recent = memory
+            .recent(RECENT_WINDOW)
+            .into_iter()
+            .map(|entry| normalize(&entry.content))
+            .collect();
+        Self {
+            memory_type: None,
+            tags: Vec::new(),
+            recent,
+        }
+    }
+
+    /// Use this memory type instead of inferring one
+    pub fn with_type(mut self, memory_type: MemoryType) -> Self {
+        self.memory_type = Some(memory_type);
+        self
+    }
+
+    /// Add these tags to every ingested note
+    pub fn with_tags(mut self, tags: Vec<String>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    /// Build the memory entry for a note, or `None` for blank or duplicate notes
+    pub fn prepare(&mut self, line: &str) -> Option<MemoryEntry> {
+        let content = clean_note(line);
+        if content.is_empty() {
+            return None;
+        }
+
+        let key = normalize(&content);
+        if self.recent.contains(&key) {
+            return None;
+        }
+        self.recent.push_front(key);
+        self.recent.truncate(RECENT_WINDOW);
+
+        let memory_type = self
+            .memory_type
+            .clone()
+            .unwrap_or_else(|| infer_type(&content));
+        let mut tags = infer_tags(line);
+        for tag in &self.tags {
+            if !tags.contains(tag) {
+                tags.push(tag.clone());
+            }
+        }
+
+        Some(
+            MemoryEntry::new(content, memory_type)
+                .with_auto_importance()
+                .with_tags(tags)
+                .with_metadata("source", "watch"),
+        )
+    }
+
+    /// Ingest every note in `text`, returning the IDs of the memories added
+    pub fn ingest(
+        &mut self,
+        memory: &mut PersistentMemory,
+        text: &str,
+    ) -> MemoryResult<Vec<String>> {
+        let mut ids = Vec::new();
+        for line in text.lines() {
+            if let Some(entry) = self.prepare(line) {
+                ids.push(memory.add(entry)?);
+            }
+        }
+        Ok(ids)
+    }
+}
+
+/// Strip list markers and heading hashes from a note line
+fn clean_note(line: &str) -> String {
+    let trimmed = line.trim();
+    let trimmed = trimmed.trim_start_matches('#').trim_start();
+    let trimmed = ["- [ ] ", "- [x] ", "- ", "* ", "+ "]
+        .iter()
+        .find_map(|marker| trimmed.strip_prefix(marker))
+        .unwrap_or(trimmed);
+    trimmed.trim().to_string()
+}
+
+fn normalize(content: &str) -> String {
+    content
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Guess a memory type from the wording of a note
+pub fn infer_type(content: &str) -> MemoryType {
+    let lower = content.to_lowercase();
+    let has = |words: &[&str]| words.iter().any(|w| lower.contains(w));
+
+    if has(&["prefer", "i like", "always ", "never ", "don't like"]) {
+        MemoryType::Preference
+    } else if has(&["todo", "today", "currently", "working on", "wip"]) {
+        MemoryType::Context
+    } else if has(&["project", "repo", "we use", "our ", "codebase"]) {
+        MemoryType::Project
+    } else {
+        MemoryType::Fact
+    }
+}
+
+/// Collect `#hashtags` and well-known topic keywords from a note
+pub fn infer_tags(line: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    let mut push = |tag: String| {
+        if !tag.is_empty() && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    };
+
+    for word in line.split_whitespace() {
+        if let Some(tag) = word.strip_prefix('#') {
+            push(
+                tag.trim_end_matches(|c: char| !c.is_alphanumeric())
+                    .to_lowercase(),
+            );
+        }
+    }
+
+    let lower = line.to_lowercase();
+    let words: Vec<&str> = lower
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect();
+    for keyword in TAG_KEYWORDS {
+        if words.contains(keyword) {
+            push(keyword.to_string());
+        }
+    }
+
+    tags
+}
+
+/// Reads lines appended to a file since the last read
+pub struct FileTail {
+    path: PathBuf,
+    offset: u64,
+    partial: String,
+}
+
+impl FileTail {
+    /// Tail `path`, starting at its current end unless `from_start` is set
+    pub fn open(path: impl AsRef<Path>, from_start: bool) -> std::io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let offset = if from_start {
+            0
+        } else {
+            std::fs::metadata(&path)?.len()
+        };
+        Ok(Self {
+            path,
+            offset,
+            partial: String::new(),
+        })
+    }
+
+    /// Current size of the watched file
+    pub fn len(&self) -> std::io::Result<u64> {
+        Ok(std::fs::metadata(&self.path)?.len())
+    }
+
+    /// Whether the watched file is empty
+    pub fn is_empty(&self) -> std::io::Result<bool> {
+        Ok(self.len()? == 0)
+    }
+
+    /// Complete lines appended since the last call. A trailing line without
+    /// a newline is held back until it is finished. A truncated file is
+    /// read again from the start.
+    pub fn read_new_lines(&mut self) -> std::io::Result<Vec<String>> {
+        let len = self.len()?;
+        if len < self.offset {
+            self.offset = 0;
+            self.partial.clear();
+        }
+        if len == self.offset {
+            return Ok(Vec::new());
+        }
+
+        let mut file = File::open(&self.path)?;
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+        self.offset += bytes.len() as u64;
+        self.partial.push_str(&String::from_utf8_lossy(&bytes));
+
+        let mut lines: Vec<String> = self.partial.split('\n').map(str::to_string).collect();
+        self.partial = lines.pop().unwrap_or_default();
+        Ok(lines)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_memory() -> (PersistentMemory, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("sena-watch-{}", uuid::Uuid::new_v4()));
+        (PersistentMemory::with_dir(dir.clone()).unwrap(), dir)
+    }
+
+    #[test]
+    fn test_ingest_adds_notes_and_skips_duplicates() {
+        let (mut memory, dir) = temp_memory();
+        let mut ingester = NoteIngester::new(&memory);
+
+        let ids = ingester
+            .ingest(
+                &mut memory,
+                "- I prefer rust for CLI tools\n\n- The API uses #auth tokens\n",
+            )
+            .unwrap();
+        assert_eq!(ids.len(), 2);
+        assert_eq!(memory.count(), 2);
+
+        let preference = memory.search("prefer rust")[0];
+        assert_eq!(preference.memory_type, MemoryType::Preference);
+        assert_eq!(preference.tags, vec!["rust"]);
+
+        let api = memory.search("API uses")[0];
+        assert_eq!(api.memory_type, MemoryType::Fact);
+        assert_eq!(api.tags, vec!["auth", "api"]);
+
+        let again = ingester
+            .ingest(&mut memory, "* the api  uses #auth TOKENS")
+            .unwrap();
+        assert!(again.is_empty());
+        assert_eq!(memory.count(), 2);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_overrides_replace_inferred_type_and_extend_tags() {
+        let (memory, dir) = temp_memory();
+        let mut ingester = NoteIngester::new(&memory)
+            .with_type(MemoryType::Project)
+            .with_tags(vec!["notes".to_string()]);
+
+        let entry = ingester.prepare("I prefer docker for deploys").unwrap();
+        assert_eq!(entry.memory_type, MemoryType::Project);
+        assert_eq!(entry.tags, vec!["docker", "notes"]);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_file_tail_returns_only_complete_new_lines() {
+        let path = std::env::temp_dir().join(format!("sena-tail-{}.md", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "old note\n").unwrap();
+        let mut tail = FileTail::open(&path, false).unwrap();
+        assert!(tail.read_new_lines().unwrap().is_empty());
+
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        std::io::Write::write_all(&mut file, b"first\nsecond").unwrap();
+        assert_eq!(tail.read_new_lines().unwrap(), vec!["first"]);
+
+        std::io::Write::write_all(&mut file, b" half\n").unwrap();
+        assert_eq!(tail.read_new_lines().unwrap(), vec!["second half"]);
+
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -5,7 +5,18 @@ use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant};
+
+use super::selftest::{hub_subsystem, memory_subsystem, providers_subsystem, Subsystem};
+
+/// How long a subsystem check may take before it is reported as failed
+pub const DEFAULT_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Free space under `~/.sena` below which the disk check warns, and fails
+const DISK_WARN_BYTES: u64 = 1024 * 1024 * 1024;
+const DISK_FAIL_BYTES: u64 = 100 * 1024 * 1024;
 
 /// Component health status
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,6 +34,135 @@ pub struct HealthReport {
     pub overall_status: String,
     pub components: HashMap<String, ComponentHealth>,
     pub metrics: HealthMetrics,
+    /// Per-subsystem checks, in the order they were registered
+    #[serde(default)]
+    pub checks: Vec<HealthCheck>,
+    /// Why `overall_status` is not healthy, one line per failing check
+    #[serde(default)]
+    pub status_reasons: Vec<String>,
+}
+
+/// Outcome of one subsystem check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    /// Works, but needs attention; degrades the report to "warning"
+    Warn,
+    /// Broken; degrades the report to "critical"
+    Fail,
+}
+
+impl std::fmt::Display for CheckStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CheckStatus::Pass => write!(f, "pass"),
+            CheckStatus::Warn => write!(f, "warn"),
+            CheckStatus::Fail => write!(f, "fail"),
+        }
+    }
+}
+
+/// A subsystem check as reported
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthCheck {
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+/// Body of a subsystem check: its status and a detail line
+pub type CheckFn = Arc<dyn Fn() -> (CheckStatus, String) + Send + Sync>;
+
+/// A subsystem check to run, with how long it may take
+#[derive(Clone)]
+pub struct HealthProbe {
+    pub name: String,
+    pub timeout: Duration,
+    check: CheckFn,
+}
+
+impl HealthProbe {
+    pub fn new<F>(name: impl Into<String>, check: F) -> Self
+    where
+        F: Fn() -> (CheckStatus, String) + Send + Sync + 'static,
+    {
+        Self {
+            name: name.into(),
+            timeout: DEFAULT_CHECK_TIMEOUT,
+            check: Arc::new(check),
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+/// Run every probe on its own thread and wait for each up to its timeout.
+/// A probe that hangs is reported as failed and left to finish unobserved.
+pub fn run_checks(probes: &[HealthProbe]) -> Vec<HealthCheck> {
+    let started = Instant::now();
+    let pending: Vec<_> = probes
+        .iter()
+        .map(|probe| {
+            let (tx, rx) = mpsc::channel();
+            let check = Arc::clone(&probe.check);
+            let spawned = std::thread::Builder::new()
+                .name(format!("health-{}", probe.name))
+                .spawn(move || {
+                    let _ = tx.send(check());
+                });
+            (probe, rx, spawned.is_ok())
+        })
+        .collect();
+
+    pending
+        .into_iter()
+        .map(|(probe, rx, spawned)| {
+            let wait = (started + probe.timeout).saturating_duration_since(Instant::now());
+            let (status, detail) = match rx.recv_timeout(wait) {
+                Ok(outcome) => outcome,
+                Err(_) if !spawned => (CheckStatus::Fail, "Could not start check".to_string()),
+                Err(mpsc::RecvTimeoutError::Timeout) => (
+                    CheckStatus::Fail,
+                    format!("Timed out after {}ms", probe.timeout.as_millis()),
+                ),
+                Err(mpsc::RecvTimeoutError::Disconnected) => {
+                    (CheckStatus::Fail, "Check panicked".to_string())
+                }
+            };
+            HealthCheck {
+                name: probe.name.clone(),
+                status,
+                detail,
+            }
+        })
+        .collect()
+}
+
+/// `base` lowered to match the worst check, and why
+fn degrade(base: &str, checks: &[HealthCheck]) -> (String, Vec<String>) {
+    let rank = |status: &str| match status {
+        "healthy" => 0,
+        "warning" => 1,
+        _ => 2,
+    };
+    let mut overall = base.to_string();
+    let mut reasons = Vec::new();
+    for check in checks {
+        let degraded = match check.status {
+            CheckStatus::Pass => continue,
+            CheckStatus::Warn => "warning",
+            CheckStatus::Fail => "critical",
+        };
+        reasons.push(format!("{}: {}", check.name, check.detail));
+        if rank(degraded) > rank(&overall) {
+            overall = degraded.to_string();
+        }
+    }
+    (overall, reasons)
 }
 
 /// Health metrics
@@ -78,6 +218,7 @@ pub struct SenaHealth {
     sena_root: PathBuf,
     memory_dir: PathBuf,
     hooks_dir: PathBuf,
+    probes: Vec<HealthProbe>,
 }
 
 impl SenaHealth {
@@ -85,13 +226,30 @@ impl SenaHealth {
     pub fn new() -> Self {
         let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
 
+        let sena_root = home.join(".sena");
+
         Self {
-            sena_root: home.join(".sena"),
+            probes: Vec::new(),
+            sena_root,
             memory_dir: home.join(".claude").join("memory"),
             hooks_dir: home.join(".claude"),
         }
     }
 
+    /// Also run the subsystem checks: providers, hub, daemon, memory store
+    /// and disk space. They touch the network and the file system, so only
+    /// reports meant for a person ask for them.
+    pub fn with_default_checks(self) -> Self {
+        let probes = default_probes(&self.sena_root);
+        self.with_checks(probes)
+    }
+
+    /// Run `probes` as the subsystem checks
+    pub fn with_checks(mut self, probes: Vec<HealthProbe>) -> Self {
+        self.probes = probes;
+        self
+    }
+
     /// Get comprehensive health status
     pub fn get_health(&self) -> HealthReport {
         let mut components = HashMap::new();
@@ -162,11 +320,13 @@ impl SenaHealth {
         } else {
             "critical"
         };
+        let checks = run_checks(&self.probes);
+        let (overall_status, status_reasons) = degrade(overall_status, &checks);
 
         HealthReport {
             timestamp: Utc::now().to_rfc3339(),
             version: crate::VERSION.to_string(),
-            overall_status: overall_status.to_string(),
+            overall_status,
             components,
             metrics: HealthMetrics {
                 core_components: format!("{}/{}", components_healthy, core_files.len()),
@@ -175,6 +335,8 @@ impl SenaHealth {
                 hooks: format!("{}/{}", hooks_healthy, hook_files.len()),
                 overall_health_percentage: (health_percentage * 10.0).round() / 10.0,
             },
+            checks,
+            status_reasons,
         }
     }
 
@@ -315,6 +477,94 @@ impl Default for SenaHealth {
     }
 }
 
+/// Providers, hub, daemon, memory store and disk space checks. The
+/// providers, hub and memory checks are the self test's.
+fn default_probes(sena_root: &Path) -> Vec<HealthProbe> {
+    let memory_dir = sena_root.join("memory");
+    let disk_root = sena_root.to_path_buf();
+    vec![
+        HealthProbe::new("providers", || summarize(providers_subsystem())),
+        HealthProbe::new("hub", || summarize(hub_subsystem())),
+        HealthProbe::new("daemon", check_daemon),
+        HealthProbe::new("memory", move || summarize(memory_subsystem(&memory_dir))),
+        HealthProbe::new("disk", move || check_disk(&disk_root)),
+    ]
+}
+
+/// A self test subsystem as one check: failing if any of its checks failed
+fn summarize(subsystem: Subsystem) -> (CheckStatus, String) {
+    let failed: Vec<String> = subsystem
+        .checks
+        .iter()
+        .filter(|check| !check.passed)
+        .map(|check| format!("{}: {}", check.name, check.detail))
+        .collect();
+    if failed.is_empty() {
+        let details: Vec<&str> = subsystem.checks.iter().map(|c| c.detail.as_str()).collect();
+        (CheckStatus::Pass, details.join("; "))
+    } else {
+        (CheckStatus::Fail, failed.join("; "))
+    }
+}
+
+/// The daemon is optional, so not running it is no cause for concern
+fn check_daemon() -> (CheckStatus, String) {
+    let detail = if crate::daemon::is_running() {
+        "Daemon is running"
+    } else {
+        "Daemon is not running"
+    };
+    (CheckStatus::Pass, detail.to_string())
+}
+
+fn check_disk(sena_root: &Path) -> (CheckStatus, String) {
+    // The directory may not exist yet; measure the nearest one that does
+    let existing = sena_root
+        .ancestors()
+        .find(|dir| dir.exists())
+        .unwrap_or(sena_root);
+    let Some(free) = free_space(existing) else {
+        return (
+            CheckStatus::Warn,
+            format!("Cannot read free space for {}", existing.display()),
+        );
+    };
+
+    let detail = format!(
+        "{} MiB free for {}",
+        free / (1024 * 1024),
+        sena_root.display()
+    );
+    if free < DISK_FAIL_BYTES {
+        (CheckStatus::Fail, detail)
+    } else if free < DISK_WARN_BYTES {
+        (CheckStatus::Warn, detail)
+    } else {
+        (CheckStatus::Pass, detail)
+    }
+}
+
+/// Bytes available to unprivileged users on the file system holding `path`
+#[cfg(unix)]
+fn free_space(path: &Path) -> Option<u64> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = std::ffi::CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stats = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+    // SAFETY: `path` is NUL-terminated and `stats` is only read on success
+    if unsafe { libc::statvfs(path.as_ptr(), stats.as_mut_ptr()) } != 0 {
+        return None;
+    }
+    let stats = unsafe { stats.assume_init() };
+    #[allow(clippy::unnecessary_cast)]
+    Some(stats.f_bavail as u64 * stats.f_frsize as u64)
+}
+
+#[cfg(not(unix))]
+fn free_space(_path: &Path) -> Option<u64> {
+    None
+}
+
 /// SENA Metrics collector
 pub struct SenaMetrics;
 
@@ -329,6 +579,11 @@ impl SenaMetrics {
             "phases": health.get_phase_status(),
         })
     }
+
+    /// Current metrics in the Prometheus text exposition format
+    pub fn to_prometheus() -> String {
+        super::prometheus::MetricsSnapshot::collect().to_prometheus()
+    }
 }
 
 #[cfg(test)]
@@ -348,6 +603,93 @@ mod tests {
         assert_eq!(report.version, crate::VERSION);
     }
 
+    fn probe(name: &str, status: CheckStatus) -> HealthProbe {
+        HealthProbe::new(name, move || (status, format!("{} detail", status)))
+    }
+
+    #[test]
+    fn test_passing_checks_leave_status_alone() {
+        let baseline = SenaHealth::new().with_checks(Vec::new()).get_health();
+        let report = SenaHealth::new()
+            .with_checks(vec![
+                probe("providers", CheckStatus::Pass),
+                probe("hub", CheckStatus::Pass),
+            ])
+            .get_health();
+
+        let names: Vec<&str> = report.checks.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["providers", "hub"]);
+        assert!(report.checks.iter().all(|c| c.status == CheckStatus::Pass));
+        assert!(report.status_reasons.is_empty());
+        assert_eq!(report.overall_status, baseline.overall_status);
+    }
+
+    #[test]
+    fn test_failing_and_hung_checks_degrade_status() {
+        let hung = HealthProbe::new("hub", || {
+            std::thread::sleep(Duration::from_secs(10));
+            (CheckStatus::Pass, "too late".to_string())
+        })
+        .with_timeout(Duration::from_millis(200));
+
+        let started = Instant::now();
+        let report = SenaHealth::new()
+            .with_checks(vec![
+                probe("providers", CheckStatus::Pass),
+                probe("memory", CheckStatus::Fail),
+                hung,
+            ])
+            .get_health();
+
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert_eq!(report.overall_status, "critical");
+        assert_eq!(report.checks[1].status, CheckStatus::Fail);
+        assert_eq!(report.checks[2].status, CheckStatus::Fail);
+        assert!(report.checks[2].detail.contains("Timed out"));
+        assert_eq!(
+            report.status_reasons,
+            vec!["memory: fail detail", "hub: Timed out after 200ms"]
+        );
+    }
+
+    #[test]
+    fn test_warning_check_degrades_healthy_to_warning() {
+        let checks = run_checks(&[
+            probe("daemon", CheckStatus::Warn),
+            probe("disk", CheckStatus::Pass),
+        ]);
+        let (status, reasons) = degrade("healthy", &checks);
+        assert_eq!(status, "warning");
+        assert_eq!(reasons, vec!["daemon: warn detail"]);
+    }
+
+    #[test]
+    fn test_checks_are_opt_in() {
+        assert!(SenaHealth::new().get_health().checks.is_empty());
+    }
+
+    #[test]
+    fn test_memory_check_uses_given_dir() {
+        let dir = std::env::temp_dir().join(format!("sena-health-{}", uuid::Uuid::new_v4()));
+        let (status, detail) = summarize(memory_subsystem(&dir));
+        assert_eq!(status, CheckStatus::Pass, "{}", detail);
+        assert!(dir.exists());
+        let _ = fs::remove_dir_all(dir);
+
+        let broken = Subsystem::new("memory").check(
+            "store opens",
+            crate::ancient::CriterionCategory::ErrorHandling,
+            Err("Cannot open memory store".to_string()),
+        );
+        assert_eq!(
+            summarize(broken),
+            (
+                CheckStatus::Fail,
+                "store opens: Cannot open memory store".to_string()
+            )
+        );
+    }
+
     #[test]
     fn test_get_innovation_metrics() {
         let health = SenaHealth::new();
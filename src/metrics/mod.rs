@@ -3,5 +3,16 @@
 //! Health monitoring, innovation metrics, and system status
 
 pub mod health;
+pub mod prometheus;
+pub mod selftest;
+#[cfg(feature = "metrics-server")]
+pub mod server;
 
-pub use health::{SenaHealth, SenaMetrics};
+pub use health::{
+    CheckStatus, HealthCheck, HealthProbe, SenaHealth, SenaMetrics, DEFAULT_CHECK_TIMEOUT,
+};
+pub use prometheus::{ExpositionWriter, MetricKind, MetricsSnapshot};
+pub use selftest::{
+    SelfTest, SelfTestReport, Subsystem, SubsystemCheck, SubsystemReport,
+    DEFAULT_SELFTEST_THRESHOLD,
+};
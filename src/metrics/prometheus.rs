@@ -0,0 +1,459 @@
+//! Prometheus Export
+//!
+//! Renders SENA's metrics in the Prometheus text exposition format, for
+//! `sena metrics --format prometheus` and the `/metrics` endpoint.
+
+use std::collections::BTreeMap;
+
+use sena_providers::{UsageTotals, UsageTracker};
+
+use super::health::{CheckStatus, HealthCheck, SenaHealth};
+
+/// Content type of the text exposition format
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricKind {
+    Counter,
+    Gauge,
+    Summary,
+}
+
+impl MetricKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            MetricKind::Counter => "counter",
+            MetricKind::Gauge => "gauge",
+            MetricKind::Summary => "summary",
+        }
+    }
+}
+
+/// Writes metric families as `# HELP`/`# TYPE` lines followed by samples
+#[derive(Debug, Default)]
+pub struct ExpositionWriter {
+    out: String,
+}
+
+impl ExpositionWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a family; its samples must follow before the next family
+    pub fn family(&mut self, name: &str, kind: MetricKind, help: &str) -> &mut Self {
+        let help = help.replace('\\', "\\\\").replace('\n', "\\n");
+        self.out.push_str(&format!("# HELP {} {}\n", name, help));
+        self.out
+            .push_str(&format!("# TYPE {} {}\n", name, kind.as_str()));
+        self
+    }
+
+    pub fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: f64) -> &mut Self {
+        self.out.push_str(name);
+        if !labels.is_empty() {
+            let labels: Vec<String> = labels
+                .iter()
+                .map(|(key, value)| format!("{}=\"{}\"", key, escape_label(value)))
+                .collect();
+            self.out.push_str(&format!("{{{}}}", labels.join(",")));
+        }
+        self.out.push_str(&format!(" {}\n", format_value(value)));
+        self
+    }
+
+    pub fn finish(self) -> String {
+        self.out
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn format_value(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "+Inf" } else { "-Inf" }.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// The values the exported metrics are rendered from
+#[derive(Debug, Clone, Default)]
+pub struct MetricsSnapshot {
+    pub health_percentage: f64,
+    pub checks: Vec<HealthCheck>,
+    /// `None` when the store could not be read
+    pub memory_entries: Option<usize>,
+    /// `None` when the hub state could not be read
+    pub active_sessions: Option<usize>,
+    /// This month's provider usage from `~/.sena/usage.json`
+    pub providers: BTreeMap<String, UsageTotals>,
+}
+
+impl MetricsSnapshot {
+    pub fn collect() -> Self {
+        let health = SenaHealth::new().get_health();
+        let memory_entries = crate::memory::PersistentMemory::new()
+            .ok()
+            .map(|memory| memory.count());
+        let active_sessions = crate::hub::Hub::new()
+            .and_then(|mut hub| {
+                hub.load()?;
+                Ok(hub.sessions.get_active().len())
+            })
+            .ok();
+        let providers = UsageTracker::open_default()
+            .ledger()
+            .providers
+            .into_iter()
+            .map(|(provider, usage)| (provider, usage.totals))
+            .collect();
+
+        Self {
+            health_percentage: health.metrics.overall_health_percentage,
+            checks: health.checks,
+            memory_entries,
+            active_sessions,
+            providers,
+        }
+    }
+
+    pub fn to_prometheus(&self) -> String {
+        let mut writer = ExpositionWriter::new();
+
+        writer
+            .family("sena_info", MetricKind::Gauge, "SENA version")
+            .sample("sena_info", &[("version", crate::VERSION)], 1.0);
+
+        writer
+            .family(
+                "sena_health_percentage",
+                MetricKind::Gauge,
+                "Share of expected SENA files present",
+            )
+            .sample("sena_health_percentage", &[], self.health_percentage);
+
+        writer.family(
+            "sena_health_check_status",
+            MetricKind::Gauge,
+            "Subsystem check outcome: 0 pass, 1 warn, 2 fail",
+        );
+        for check in &self.checks {
+            let value = match check.status {
+                CheckStatus::Pass => 0.0,
+                CheckStatus::Warn => 1.0,
+                CheckStatus::Fail => 2.0,
+            };
+            writer.sample(
+                "sena_health_check_status",
+                &[("check", check.name.as_str())],
+                value,
+            );
+        }
+
+        if let Some(entries) = self.memory_entries {
+            writer
+                .family(
+                    "sena_memory_entries",
+                    MetricKind::Gauge,
+                    "Memories in the persistent store",
+                )
+                .sample("sena_memory_entries", &[], entries as f64);
+        }
+
+        if let Some(sessions) = self.active_sessions {
+            writer
+                .family(
+                    "sena_hub_active_sessions",
+                    MetricKind::Gauge,
+                    "Sessions active in the collaboration hub",
+                )
+                .sample("sena_hub_active_sessions", &[], sessions as f64);
+        }
+
+        writer.family(
+            "sena_requests_total",
+            MetricKind::Counter,
+            "Provider requests this month by outcome",
+        );
+        for (provider, usage) in &self.providers {
+            writer
+                .sample(
+                    "sena_requests_total",
+                    &[("provider", provider), ("outcome", "success")],
+                    usage.requests as f64,
+                )
+                .sample(
+                    "sena_requests_total",
+                    &[("provider", provider), ("outcome", "failure")],
+                    usage.failed_requests as f64,
+                );
+        }
+
+        writer.family(
+            "sena_request_success_ratio",
+            MetricKind::Gauge,
+            "Share of provider requests this month that succeeded",
+        );
+        for (provider, usage) in &self.providers {
+            let total = usage.requests + usage.failed_requests;
+            if total > 0 {
+                writer.sample(
+                    "sena_request_success_ratio",
+                    &[("provider", provider)],
+                    usage.requests as f64 / total as f64,
+                );
+            }
+        }
+
+        writer.family(
+            "sena_tokens_total",
+            MetricKind::Counter,
+            "Tokens used this month",
+        );
+        for (provider, usage) in &self.providers {
+            writer
+                .sample(
+                    "sena_tokens_total",
+                    &[("provider", provider), ("direction", "prompt")],
+                    usage.prompt_tokens as f64,
+                )
+                .sample(
+                    "sena_tokens_total",
+                    &[("provider", provider), ("direction", "completion")],
+                    usage.completion_tokens as f64,
+                );
+        }
+
+        writer.family(
+            "sena_provider_request_duration_seconds",
+            MetricKind::Summary,
+            "Latency of successful provider requests this month",
+        );
+        for (provider, usage) in &self.providers {
+            writer
+                .sample(
+                    "sena_provider_request_duration_seconds_sum",
+                    &[("provider", provider)],
+                    usage.latency_ms_total as f64 / 1000.0,
+                )
+                .sample(
+                    "sena_provider_request_duration_seconds_count",
+                    &[("provider", provider)],
+                    usage.timed_requests as f64,
+                );
+        }
+
+        writer.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    /// Check `text` against the exposition format: every sample belongs to
+    /// the family declared just before it, names and labels are well formed
+    /// and values parse. Returns each sample keyed by name and labels.
+    fn parse_exposition(text: &str) -> Result<HashMap<String, f64>, String> {
+        let valid_name = |name: &str| {
+            let mut chars = name.chars();
+            chars
+                .next()
+                .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == ':')
+                && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':')
+        };
+
+        let mut samples = HashMap::new();
+        let mut families: HashMap<String, String> = HashMap::new();
+        let mut current: Option<(String, String)> = None;
+        let mut helped: Option<String> = None;
+
+        for line in text.lines() {
+            if let Some(rest) = line.strip_prefix("# HELP ") {
+                let name = rest.split(' ').next().unwrap_or_default();
+                if !valid_name(name) {
+                    return Err(format!("bad metric name in {:?}", line));
+                }
+                helped = Some(name.to_string());
+                continue;
+            }
+            if let Some(rest) = line.strip_prefix("# TYPE ") {
+                let (name, kind) = rest
+                    .split_once(' ')
+                    .ok_or_else(|| format!("bad TYPE line {:?}", line))?;
+                if helped.as_deref() != Some(name) {
+                    return Err(format!("TYPE without HELP for {}", name));
+                }
+                if !["counter", "gauge", "summary", "histogram", "untyped"].contains(&kind) {
+                    return Err(format!("unknown type {}", kind));
+                }
+                if kind == "counter" && !name.ends_with("_total") {
+                    return Err(format!("counter {} should end in _total", name));
+                }
+                if families
+                    .insert(name.to_string(), kind.to_string())
+                    .is_some()
+                {
+                    return Err(format!("family {} declared twice", name));
+                }
+                current = Some((name.to_string(), kind.to_string()));
+                continue;
+            }
+            if line.starts_with('#') || line.is_empty() {
+                continue;
+            }
+
+            let (series, value) = line
+                .rsplit_once(' ')
+                .ok_or_else(|| format!("no value in {:?}", line))?;
+            let value: f64 = match value {
+                "+Inf" => f64::INFINITY,
+                "-Inf" => f64::NEG_INFINITY,
+                other => other
+                    .parse()
+                    .map_err(|_| format!("bad value in {:?}", line))?,
+            };
+            let (name, labels) = match series.split_once('{') {
+                Some((name, labels)) => (
+                    name,
+                    labels
+                        .strip_suffix('}')
+                        .ok_or_else(|| format!("unclosed labels in {:?}", line))?,
+                ),
+                None => (series, ""),
+            };
+            if !valid_name(name) {
+                return Err(format!("bad metric name in {:?}", line));
+            }
+
+            let mut rest = labels;
+            while !rest.is_empty() {
+                let (key, after) = rest
+                    .split_once("=\"")
+                    .ok_or_else(|| format!("bad label in {:?}", line))?;
+                if !valid_name(key) || key.contains(':') {
+                    return Err(format!("bad label name {:?}", key));
+                }
+                let mut escaped = false;
+                let end = after
+                    .char_indices()
+                    .find(|&(_, c)| {
+                        let closes = c == '"' && !escaped;
+                        escaped = c == '\\' && !escaped;
+                        closes
+                    })
+                    .map(|(i, _)| i)
+                    .ok_or_else(|| format!("unterminated label in {:?}", line))?;
+                rest = after[end + 1..].trim_start_matches(',');
+            }
+
+            let (family, kind) = current
+                .as_ref()
+                .ok_or_else(|| format!("sample before any TYPE: {:?}", line))?;
+            let belongs = name == family
+                || (kind == "summary"
+                    && (name == format!("{}_sum", family) || name == format!("{}_count", family)));
+            if !belongs {
+                return Err(format!("{} is not part of family {}", name, family));
+            }
+            if samples.insert(series.to_string(), value).is_some() {
+                return Err(format!("duplicate sample {}", series));
+            }
+        }
+        Ok(samples)
+    }
+
+    fn snapshot() -> MetricsSnapshot {
+        let mut providers = BTreeMap::new();
+        providers.insert(
+            "openai".to_string(),
+            UsageTotals {
+                requests: 3,
+                failed_requests: 1,
+                prompt_tokens: 120,
+                completion_tokens: 80,
+                timed_requests: 2,
+                latency_ms_total: 1500,
+                ..Default::default()
+            },
+        );
+        providers.insert("weird \"name\"\n".to_string(), UsageTotals::default());
+
+        MetricsSnapshot {
+            health_percentage: 87.5,
+            checks: vec![
+                HealthCheck {
+                    name: "hub".to_string(),
+                    status: CheckStatus::Warn,
+                    detail: "not running".to_string(),
+                },
+                HealthCheck {
+                    name: "disk".to_string(),
+                    status: CheckStatus::Pass,
+                    detail: "plenty".to_string(),
+                },
+            ],
+            memory_entries: Some(42),
+            active_sessions: Some(2),
+            providers,
+        }
+    }
+
+    #[test]
+    fn test_output_parses_as_exposition_format() {
+        let text = snapshot().to_prometheus();
+        let samples = parse_exposition(&text).unwrap_or_else(|e| panic!("{}\n{}", e, text));
+
+        assert_eq!(samples["sena_memory_entries"], 42.0);
+        assert_eq!(samples["sena_hub_active_sessions"], 2.0);
+        assert_eq!(samples["sena_health_check_status{check=\"hub\"}"], 1.0);
+        assert_eq!(
+            samples["sena_requests_total{provider=\"openai\",outcome=\"failure\"}"],
+            1.0
+        );
+        assert_eq!(
+            samples["sena_request_success_ratio{provider=\"openai\"}"],
+            0.75
+        );
+        assert_eq!(
+            samples["sena_provider_request_duration_seconds_sum{provider=\"openai\"}"],
+            1.5
+        );
+        assert!(samples.contains_key(
+            "sena_tokens_total{provider=\"weird \\\"name\\\"\\n\",direction=\"prompt\"}"
+        ));
+        // No requests, so no ratio rather than a division by zero
+        assert_eq!(
+            samples
+                .keys()
+                .filter(|k| k.starts_with("sena_request_success_ratio"))
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_parser_rejects_malformed_output() {
+        assert!(parse_exposition("orphan 1\n").is_err());
+        assert!(parse_exposition("# HELP sena_x x\n# TYPE sena_x counter\nsena_x 1\n").is_err());
+        assert!(
+            parse_exposition("# HELP sena_x x\n# TYPE sena_x gauge\nsena_x{a=\"1} 1\n").is_err()
+        );
+    }
+
+    #[test]
+    fn test_special_values() {
+        assert_eq!(format_value(f64::INFINITY), "+Inf");
+        assert_eq!(format_value(f64::NAN), "NaN");
+        assert_eq!(format_value(3.0), "3");
+    }
+}
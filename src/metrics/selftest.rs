@@ -0,0 +1,431 @@
+//! SENA Self Test
+//!
+//! Runs the millennium test against SENA's own subsystems. Each subsystem
+//! first runs live checks; their pass rate per criterion category replaces
+//! the simulated score for that category. A subsystem fails when any live
+//! check fails or its score falls below the threshold.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::ancient::{
+    CriterionCategory, DurabilityRating, MillenniumTestFramework, MillenniumTestResult,
+};
+
+/// Subsystems scoring below this (a Decadal rating) are reported as failing
+pub const DEFAULT_SELFTEST_THRESHOLD: f64 = 0.6;
+
+/// Outcome of one live check against a subsystem
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubsystemCheck {
+    pub name: String,
+    pub category: CriterionCategory,
+    pub passed: bool,
+    pub detail: String,
+}
+
+/// A subsystem and the live checks run against it
+#[derive(Debug, Clone)]
+pub struct Subsystem {
+    pub name: String,
+    pub checks: Vec<SubsystemCheck>,
+}
+
+impl Subsystem {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            checks: Vec::new(),
+        }
+    }
+
+    /// Record a check; `Ok` carries a detail message, `Err` the failure reason
+    pub fn check(
+        mut self,
+        name: impl Into<String>,
+        category: CriterionCategory,
+        outcome: Result<String, String>,
+    ) -> Self {
+        let (passed, detail) = match outcome {
+            Ok(detail) => (true, detail),
+            Err(detail) => (false, detail),
+        };
+        self.checks.push(SubsystemCheck {
+            name: name.into(),
+            category,
+            passed,
+            detail,
+        });
+        self
+    }
+
+    /// Pass rate of the checks in each category
+    fn observations(&self) -> HashMap<CriterionCategory, f64> {
+        let mut totals: HashMap<CriterionCategory, (usize, usize)> = HashMap::new();
+        for check in &self.checks {
+            let entry = totals.entry(check.category).or_default();
+            entry.1 += 1;
+            if check.passed {
+                entry.0 += 1;
+            }
+        }
+        totals
+            .into_iter()
+            .map(|(category, (passed, total))| (category, passed as f64 / total as f64))
+            .collect()
+    }
+}
+
+/// Millennium test result for one subsystem
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubsystemReport {
+    pub name: String,
+    pub checks: Vec<SubsystemCheck>,
+    pub score: f64,
+    pub rating: DurabilityRating,
+    pub passed: bool,
+    pub recommendations: Vec<String>,
+    pub result: MillenniumTestResult,
+}
+
+/// Durability assessment for every registered subsystem
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelfTestReport {
+    pub subsystems: Vec<SubsystemReport>,
+    pub overall_score: f64,
+    pub overall_rating: DurabilityRating,
+    pub threshold: f64,
+    pub passed: bool,
+}
+
+impl SelfTestReport {
+    pub fn failing(&self) -> Vec<&SubsystemReport> {
+        self.subsystems.iter().filter(|s| !s.passed).collect()
+    }
+}
+
+/// Registry of subsystems to assess
+pub struct SelfTest {
+    subsystems: Vec<Subsystem>,
+    threshold: f64,
+}
+
+impl SelfTest {
+    pub fn new() -> Self {
+        Self {
+            subsystems: Vec::new(),
+            threshold: DEFAULT_SELFTEST_THRESHOLD,
+        }
+    }
+
+    /// Minimum score, in `0.0..=1.0`, each subsystem must reach
+    pub fn with_threshold(mut self, threshold: f64) -> Self {
+        self.threshold = threshold.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn register(mut self, subsystem: Subsystem) -> Self {
+        self.subsystems.push(subsystem);
+        self
+    }
+
+    /// Register the providers config, hub, memory store and guardian rules
+    pub fn with_default_subsystems(self) -> Self {
+        self.register(providers_subsystem())
+            .register(hub_subsystem())
+            .register(memory_subsystem(
+                &crate::memory::PersistentMemory::default_dir(),
+            ))
+            .register(guardian_subsystem())
+    }
+
+    pub fn subsystem_names(&self) -> Vec<&str> {
+        self.subsystems.iter().map(|s| s.name.as_str()).collect()
+    }
+
+    pub fn run(&self) -> SelfTestReport {
+        let mut framework = MillenniumTestFramework::new();
+        let mut reports = Vec::new();
+
+        for subsystem in &self.subsystems {
+            framework.register_component(&subsystem.name, crate::VERSION);
+            for (category, score) in subsystem.observations() {
+                framework.observe(&subsystem.name, category, score);
+            }
+            let result = framework.run_millennium_test(&subsystem.name);
+
+            let mut recommendations: Vec<String> = subsystem
+                .checks
+                .iter()
+                .filter(|check| !check.passed)
+                .map(|check| format!("Fix '{}': {}", check.name, check.detail))
+                .collect();
+            recommendations.extend(result.assessment.recommendations.iter().cloned());
+
+            let score = result.assessment.overall_score;
+            reports.push(SubsystemReport {
+                name: subsystem.name.clone(),
+                checks: subsystem.checks.clone(),
+                score,
+                rating: result.assessment.overall_rating,
+                passed: score >= self.threshold && subsystem.checks.iter().all(|c| c.passed),
+                recommendations,
+                result,
+            });
+        }
+
+        let overall_score = if reports.is_empty() {
+            0.0
+        } else {
+            reports.iter().map(|r| r.score).sum::<f64>() / reports.len() as f64
+        };
+
+        SelfTestReport {
+            passed: reports.iter().all(|r| r.passed),
+            overall_rating: DurabilityRating::from_score(overall_score),
+            overall_score,
+            threshold: self.threshold,
+            subsystems: reports,
+        }
+    }
+}
+
+impl Default for SelfTest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub(crate) fn providers_subsystem() -> Subsystem {
+    use sena_providers::ProvidersConfig;
+
+    let path = ProvidersConfig::config_path();
+    let (config, parsed) = if path.exists() {
+        match ProvidersConfig::load_from_file(&path) {
+            Ok(config) => (config, Ok(format!("{} parsed", path.display()))),
+            Err(e) => (
+                ProvidersConfig::default_config(),
+                Err(format!("{} is invalid: {}", path.display(), e)),
+            ),
+        }
+    } else {
+        (
+            ProvidersConfig::default_config(),
+            Ok("No providers.toml; using built-in defaults".to_string()),
+        )
+    };
+
+    let enabled = config.enabled_providers().len();
+    let default_ok = match &config.default_provider {
+        Some(id) if config.get_provider(id).is_some() => Ok(format!("Default provider: {}", id)),
+        Some(id) => Err(format!(
+            "Default provider '{}' is not configured; run `sena provider default <id>`",
+            id
+        )),
+        None => Err("No default provider set; run `sena provider default <id>`".to_string()),
+    };
+    let unknown_fallbacks: Vec<&String> = config
+        .fallback_chain
+        .iter()
+        .filter(|id| config.get_provider(id).is_none())
+        .collect();
+
+    Subsystem::new("providers")
+        .check("config parses", CriterionCategory::ErrorHandling, parsed)
+        .check(
+            "providers enabled",
+            CriterionCategory::Dependencies,
+            if enabled > 0 {
+                Ok(format!("{} provider(s) enabled", enabled))
+            } else {
+                Err("No providers enabled in providers.toml".to_string())
+            },
+        )
+        .check(
+            "default provider",
+            CriterionCategory::Structural,
+            default_ok,
+        )
+        .check(
+            "fallback chain",
+            CriterionCategory::Adaptability,
+            if unknown_fallbacks.is_empty() {
+                Ok(format!(
+                    "{} fallback provider(s)",
+                    config.fallback_chain.len()
+                ))
+            } else {
+                Err(format!(
+                    "Fallback chain names unconfigured providers: {:?}",
+                    unknown_fallbacks
+                ))
+            },
+        )
+}
+
+pub(crate) fn hub_subsystem() -> Subsystem {
+    use crate::hub::{Hub, HubConfig};
+    use crate::sync::write_atomic;
+
+    let config = HubConfig::new();
+    let writable = config.ensure_dirs().and_then(|_| {
+        let probe = config.hub_dir.join(".selftest");
+        write_atomic(&probe, "ok").map_err(|e| format!("Cannot write to hub dir: {}", e))?;
+        let _ = std::fs::remove_file(&probe);
+        Ok(format!("{} is writable", config.hub_dir.display()))
+    });
+    let loaded = Hub::new().and_then(|mut hub| {
+        hub.load()?;
+        Ok(format!(
+            "{} task(s), {} active session(s)",
+            hub.tasks.get_all().len(),
+            hub.sessions.get_active().len()
+        ))
+    });
+
+    Subsystem::new("hub")
+        .check(
+            "directories writable",
+            CriterionCategory::Structural,
+            writable,
+        )
+        .check("state loads", CriterionCategory::ErrorHandling, loaded)
+}
+
+/// The memory store kept in `dir`
+pub(crate) fn memory_subsystem(dir: &Path) -> Subsystem {
+    use crate::memory::{MemoryType, PersistentMemory};
+
+    let opened = PersistentMemory::with_dir(dir.to_path_buf())
+        .map(|memory| format!("{} memories loaded", memory.count()))
+        .map_err(|e| format!("Cannot open memory store: {}", e));
+
+    let scratch = std::env::temp_dir().join(format!("sena-selftest-{}", uuid::Uuid::new_v4()));
+    let round_trip = PersistentMemory::with_dir(scratch.clone())
+        .map_err(|e| e.to_string())
+        .and_then(|mut memory| {
+            memory
+                .add_quick("selftest round trip marker", MemoryType::Fact)
+                .map_err(|e| e.to_string())?;
+            let reopened =
+                PersistentMemory::with_dir(scratch.clone()).map_err(|e| e.to_string())?;
+            if reopened.search("round trip marker").is_empty() {
+                Err("Stored memory was not found after reload".to_string())
+            } else {
+                Ok("Add, persist and search round trip".to_string())
+            }
+        });
+    let _ = std::fs::remove_dir_all(&scratch);
+
+    Subsystem::new("memory")
+        .check("store opens", CriterionCategory::ErrorHandling, opened)
+        .check("round trip", CriterionCategory::Testability, round_trip)
+}
+
+fn guardian_subsystem() -> Subsystem {
+    use crate::guardian::GuardianMiddleware;
+
+    let guardian = GuardianMiddleware::new();
+    let blocked = guardian.validate_command("rm -rf /");
+    let allowed = guardian.validate_command("ls -la");
+
+    Subsystem::new("guardian")
+        .check(
+            "enabled",
+            CriterionCategory::Security,
+            if guardian.is_enabled() {
+                Ok("Guardian middleware is enabled".to_string())
+            } else {
+                Err("Guardian is disabled; enable it in the guardian config".to_string())
+            },
+        )
+        .check(
+            "blocks destructive commands",
+            CriterionCategory::Security,
+            if blocked.allowed {
+                Err("`rm -rf /` was not blocked".to_string())
+            } else {
+                Ok(blocked
+                    .reason
+                    .unwrap_or_else(|| "`rm -rf /` blocked".to_string()))
+            },
+        )
+        .check(
+            "allows safe commands",
+            CriterionCategory::Adaptability,
+            if allowed.allowed {
+                Ok("`ls -la` allowed".to_string())
+            } else {
+                Err(format!(
+                    "`ls -la` was blocked: {}",
+                    allowed.reason.unwrap_or_default()
+                ))
+            },
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn healthy(name: &str) -> Subsystem {
+        Subsystem::new(name)
+            .check("a", CriterionCategory::Security, Ok("ok".to_string()))
+            .check("b", CriterionCategory::ErrorHandling, Ok("ok".to_string()))
+            .check("c", CriterionCategory::Structural, Ok("ok".to_string()))
+    }
+
+    #[test]
+    fn test_result_per_registered_subsystem() {
+        let selftest = SelfTest::new()
+            .register(healthy("alpha"))
+            .register(healthy("beta"))
+            .register(healthy("gamma"));
+        let report = selftest.run();
+
+        let names: Vec<&str> = report.subsystems.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["alpha", "beta", "gamma"]);
+        assert!(report.passed);
+        assert!(report
+            .subsystems
+            .iter()
+            .all(|s| s.result.component_name == s.name));
+    }
+
+    #[test]
+    fn test_score_below_threshold_fails() {
+        let report = SelfTest::new()
+            .with_threshold(0.99)
+            .register(healthy("alpha"))
+            .run();
+        assert!(!report.passed);
+        assert_eq!(report.failing().len(), 1);
+    }
+
+    #[test]
+    fn test_failed_check_fails_subsystem() {
+        let broken = Subsystem::new("broken")
+            .check(
+                "blocks destructive commands",
+                CriterionCategory::Security,
+                Err("`rm -rf /` was not blocked".to_string()),
+            )
+            .check(
+                "b",
+                CriterionCategory::ErrorHandling,
+                Err("boom".to_string()),
+            );
+        let report = SelfTest::new()
+            .register(healthy("fine"))
+            .register(broken)
+            .run();
+
+        assert!(!report.passed);
+        let failing = report.failing();
+        assert_eq!(failing.len(), 1);
+        assert_eq!(failing[0].name, "broken");
+        assert!(failing[0].recommendations[0].contains("blocks destructive commands"));
+        assert!(report.subsystems[0].score > failing[0].score);
+    }
+}
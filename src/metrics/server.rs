@@ -0,0 +1,91 @@
+//! Metrics Endpoint
+//!
+//! A minimal HTTP server answering `GET /metrics` with the Prometheus
+//! export. Built with the `metrics-server` feature.
+
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+use super::prometheus::CONTENT_TYPE;
+use super::SenaMetrics;
+
+/// Most bytes of request line and headers read from one request
+const MAX_REQUEST_HEAD: u64 = 8 * 1024;
+
+/// Serve `/metrics` on `addr` until interrupted
+pub async fn serve(addr: &str) -> Result<String, String> {
+    let listener = TcpListener::bind(addr)
+        .await
+        .map_err(|e| format!("Cannot listen on {}: {}", addr, e))?;
+    let local = listener.local_addr().map_err(|e| e.to_string())?;
+    eprintln!("Serving metrics at http://{}/metrics", local);
+
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => {
+                    tokio::spawn(async move {
+                        if let Err(e) = respond(stream).await {
+                            log::debug!("Metrics request failed: {}", e);
+                        }
+                    });
+                }
+                Err(e) => log::warn!("Cannot accept metrics connection: {}", e),
+            },
+            _ = tokio::signal::ctrl_c() => break,
+        }
+    }
+
+    Ok(format!(
+        "Stopped serving metrics at http://{}/metrics",
+        local
+    ))
+}
+
+async fn respond(stream: TcpStream) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream);
+    let mut head = (&mut reader).take(MAX_REQUEST_HEAD);
+    let mut request_line = String::new();
+    head.read_line(&mut request_line).await?;
+    // Skip the headers; nothing in them changes the response
+    loop {
+        let mut header = String::new();
+        if head.read_line(&mut header).await? == 0 || header.trim().is_empty() {
+            break;
+        }
+    }
+    let too_large = head.limit() == 0;
+
+    let mut parts = request_line.split_whitespace();
+    let (status, content_type, body) = match (parts.next(), parts.next()) {
+        _ if too_large => (
+            "431 Request Header Fields Too Large",
+            "text/plain",
+            "Request too large\n".to_string(),
+        ),
+        (Some("GET"), Some("/metrics")) => {
+            // Gathering reads files and runs the health checks
+            let body = tokio::task::spawn_blocking(SenaMetrics::to_prometheus)
+                .await
+                .map_err(std::io::Error::other)?;
+            ("200 OK", CONTENT_TYPE, body)
+        }
+        (Some("GET"), Some(_)) => ("404 Not Found", "text/plain", "Not found\n".to_string()),
+        _ => (
+            "405 Method Not Allowed",
+            "text/plain",
+            "Method not allowed\n".to_string(),
+        ),
+    };
+
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+    let mut stream = reader.into_inner();
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
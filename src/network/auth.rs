@@ -3,6 +3,37 @@ use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 
+use crate::sync::write_atomic;
+
+/// Why `AuthTokenStore::validate_token` refused a token
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum TokenError {
+    #[error("Token not found")]
+    NotFound,
+    #[error("Token has expired")]
+    Expired,
+    #[error("Token has been revoked")]
+    Revoked,
+    #[error("Token has already been used")]
+    AlreadyUsed,
+    #[error("Token was issued to another peer")]
+    WrongPeer,
+    #[error("{0}")]
+    Storage(String),
+}
+
+/// Whether a token is spent by its first use
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenKind {
+    /// Accepted once, e.g. an invitation
+    #[default]
+    SingleUse,
+    /// An approved peer's credential, accepted on every connection until it
+    /// expires or is revoked
+    Session,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuthToken {
     pub token: String,
@@ -12,6 +43,12 @@ pub struct AuthToken {
     pub used: bool,
     pub used_at: Option<i64>,
     pub used_by: Option<String>,
+    /// Refused from this time on; in the future while a rotation's grace
+    /// window is open
+    #[serde(default)]
+    pub revoked_at: Option<i64>,
+    #[serde(default)]
+    pub kind: TokenKind,
 }
 
 impl AuthToken {
@@ -25,6 +62,8 @@ impl AuthToken {
             used: false,
             used_at: None,
             used_by: None,
+            revoked_at: None,
+            kind: TokenKind::SingleUse,
         }
     }
 
@@ -34,6 +73,13 @@ impl AuthToken {
         token
     }
 
+    /// A reusable token for an approved peer
+    pub fn session_for_peer(peer_id: &str, expires_in_seconds: i64) -> Self {
+        let mut token = Self::for_peer(peer_id, expires_in_seconds);
+        token.kind = TokenKind::Session;
+        token
+    }
+
     fn generate_token() -> String {
         use rand::Rng;
         let mut rng = rand::thread_rng();
@@ -42,7 +88,22 @@ impl AuthToken {
     }
 
     pub fn is_valid(&self) -> bool {
-        !self.used && chrono::Utc::now().timestamp() < self.expires_at
+        !self.is_spent() && !self.is_revoked() && chrono::Utc::now().timestamp() < self.expires_at
+    }
+
+    /// A single-use token that has been used
+    pub fn is_spent(&self) -> bool {
+        self.used && self.kind == TokenKind::SingleUse
+    }
+
+    pub fn is_revoked(&self) -> bool {
+        self.revoked_at
+            .is_some_and(|at| chrono::Utc::now().timestamp() >= at)
+    }
+
+    /// Refuse the token from `at` on, unless it is already refused sooner
+    fn revoke_at(&mut self, at: i64) {
+        self.revoked_at = Some(self.revoked_at.map_or(at, |earlier| earlier.min(at)));
     }
 
     pub fn is_expired(&self) -> bool {
@@ -102,7 +163,7 @@ impl AuthTokenStore {
         let content = serde_json::to_string_pretty(self)
             .map_err(|e| format!("Failed to serialize token store: {}", e))?;
 
-        fs::write(&self.file_path, content)
+        write_atomic(&self.file_path, content)
             .map_err(|e| format!("Failed to write token store: {}", e))
     }
 
@@ -124,25 +185,83 @@ impl AuthTokenStore {
         Ok(token)
     }
 
-    pub fn validate_token(&mut self, token_str: &str, peer_id: &str) -> Result<bool, String> {
-        let token = self
-            .tokens
-            .get_mut(token_str)
-            .ok_or_else(|| "Token not found".to_string())?;
+    /// Issue `peer_id` a reusable token, see [`TokenKind::Session`]
+    pub fn create_session_token(
+        &mut self,
+        peer_id: &str,
+        expires_in_seconds: i64,
+    ) -> Result<AuthToken, String> {
+        let token = AuthToken::session_for_peer(peer_id, expires_in_seconds);
+        self.tokens.insert(token.token.clone(), token.clone());
+        self.save()?;
+        Ok(token)
+    }
 
-        if !token.is_valid() {
-            return Ok(false);
+    /// Adopt a token a peer was approved with before the store tracked peer
+    /// tokens, as a session token valid for `DEFAULT_PEER_TOKEN_EXPIRY`.
+    /// Returns whether it was added; known tokens are left alone. Not saved.
+    pub fn import_peer_token(&mut self, peer_id: &str, token_str: &str) -> bool {
+        if self.tokens.contains_key(token_str) {
+            return false;
         }
+        let mut token = AuthToken::session_for_peer(peer_id, DEFAULT_PEER_TOKEN_EXPIRY);
+        token.token = token_str.to_string();
+        self.tokens.insert(token.token.clone(), token);
+        true
+    }
+
+    /// Accept `token_str` from `peer_id` until it expires or is revoked, and
+    /// a single-use token only once, recording when it was last used
+    pub fn validate_token(&mut self, token_str: &str, peer_id: &str) -> Result<(), TokenError> {
+        let token = self.tokens.get_mut(token_str).ok_or(TokenError::NotFound)?;
 
+        if token.is_revoked() {
+            return Err(TokenError::Revoked);
+        }
+        if token.is_expired() {
+            return Err(TokenError::Expired);
+        }
+        if token.is_spent() {
+            return Err(TokenError::AlreadyUsed);
+        }
         if let Some(ref expected_peer) = token.peer_id {
             if expected_peer != peer_id {
-                return Ok(false);
+                return Err(TokenError::WrongPeer);
             }
         }
 
         token.mark_used(peer_id);
+        self.save().map_err(TokenError::Storage)
+    }
+
+    /// Issue `peer_id` a new session token. Its earlier tokens keep working for
+    /// `DEFAULT_ROTATION_GRACE` seconds, so requests already on their way
+    /// with one don't fail.
+    pub fn rotate(&mut self, peer_id: &str) -> Result<AuthToken, String> {
+        let retire_at = chrono::Utc::now().timestamp() + DEFAULT_ROTATION_GRACE;
+        for token in self.peer_tokens(peer_id) {
+            token.revoke_at(retire_at);
+        }
+        self.create_session_token(peer_id, DEFAULT_PEER_TOKEN_EXPIRY)
+    }
+
+    /// Refuse every token issued to `peer_id` right away. Returns how many
+    /// tokens were revoked.
+    pub fn revoke(&mut self, peer_id: &str) -> Result<usize, String> {
+        let now = chrono::Utc::now().timestamp();
+        let mut revoked = 0;
+        for token in self.peer_tokens(peer_id) {
+            token.revoke_at(now);
+            revoked += 1;
+        }
         self.save()?;
-        Ok(true)
+        Ok(revoked)
+    }
+
+    fn peer_tokens<'a>(&'a mut self, peer_id: &'a str) -> impl Iterator<Item = &'a mut AuthToken> {
+        self.tokens
+            .values_mut()
+            .filter(move |t| t.peer_id.as_deref() == Some(peer_id) && !t.is_revoked())
     }
 
     pub fn get_token(&self, token_str: &str) -> Option<&AuthToken> {
@@ -222,6 +341,10 @@ impl AuthChallenge {
 }
 
 pub const DEFAULT_TOKEN_EXPIRY: i64 = 300;
+/// Seconds an approved peer's session token lasts; `peer rotate` renews it
+pub const DEFAULT_PEER_TOKEN_EXPIRY: i64 = 365 * 24 * 60 * 60;
+/// Seconds a peer's old token stays valid after `AuthTokenStore::rotate`
+pub const DEFAULT_ROTATION_GRACE: i64 = 30;
 pub const DEFAULT_CHALLENGE_EXPIRY: i64 = 60;
 
 #[cfg(test)]
@@ -271,4 +394,79 @@ mod tests {
         assert!(store.get_token(&token.token).is_some());
         assert_eq!(store.active_token_count(), 1);
     }
+
+    fn temp_store() -> (AuthTokenStore, PathBuf) {
+        let path = std::env::temp_dir()
+            .join(format!("sena-tokens-{}", uuid::Uuid::new_v4()))
+            .join("tokens.json");
+        (AuthTokenStore::new(path.clone()), path)
+    }
+
+    #[test]
+    fn test_rotated_token_works_until_grace_ends() {
+        let (mut store, path) = temp_store();
+        let old = store.create_session_token("peer-1", 300).unwrap();
+        let other = store.create_session_token("peer-1", 300).unwrap();
+
+        let new = store.rotate("peer-1").unwrap();
+        assert_ne!(new.token, old.token);
+        // Inside the grace window the old token still works, repeatedly
+        assert_eq!(store.validate_token(&old.token, "peer-1"), Ok(()));
+        assert_eq!(store.validate_token(&old.token, "peer-1"), Ok(()));
+
+        // Once it closes, the old tokens are revoked, not merely expired
+        let now = chrono::Utc::now().timestamp();
+        store.tokens.get_mut(&other.token).unwrap().revoked_at = Some(now - 1);
+        assert_eq!(
+            store.validate_token(&other.token, "peer-1"),
+            Err(TokenError::Revoked)
+        );
+        assert_eq!(store.validate_token(&new.token, "peer-1"), Ok(()));
+        assert_eq!(new.kind, TokenKind::Session);
+        assert!(new.remaining_seconds() > DEFAULT_TOKEN_EXPIRY);
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn test_single_use_token_is_refused_once_used() {
+        let (mut store, path) = temp_store();
+        let invite = store.create_token_for_peer("peer-1", 300).unwrap();
+        assert_eq!(store.validate_token(&invite.token, "peer-1"), Ok(()));
+        assert_eq!(
+            store.validate_token(&invite.token, "peer-1"),
+            Err(TokenError::AlreadyUsed)
+        );
+
+        assert!(store.import_peer_token("peer-2", "legacy-token"));
+        assert!(!store.import_peer_token("peer-2", "legacy-token"));
+        assert_eq!(store.validate_token("legacy-token", "peer-2"), Ok(()));
+        assert_eq!(store.validate_token("legacy-token", "peer-2"), Ok(()));
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn test_revoke_is_immediate_and_persisted() {
+        let (mut store, path) = temp_store();
+        let token = store.create_token_for_peer("peer-1", 300).unwrap();
+        let unrelated = store.create_token_for_peer("peer-2", 300).unwrap();
+        let expired = store.create_token_for_peer("peer-3", -1).unwrap();
+
+        assert_eq!(store.revoke("peer-1").unwrap(), 1);
+        assert_eq!(
+            store.validate_token(&token.token, "peer-1"),
+            Err(TokenError::Revoked)
+        );
+        assert_eq!(
+            store.validate_token(&expired.token, "peer-3"),
+            Err(TokenError::Expired)
+        );
+
+        let mut reloaded = AuthTokenStore::load(path.clone()).unwrap();
+        assert_eq!(
+            reloaded.validate_token(&token.token, "peer-1"),
+            Err(TokenError::Revoked)
+        );
+        assert_eq!(reloaded.validate_token(&unrelated.token, "peer-2"), Ok(()));
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
 }
@@ -1,5 +1,6 @@
 use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
 use std::collections::HashMap;
+use std::net::IpAddr;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::RwLock;
@@ -8,17 +9,99 @@ use super::protocol::MDNS_SERVICE_TYPE;
 
 use serde::{Deserialize, Serialize};
 
+/// Seconds a discovery stays listed without the peer being seen again;
+/// matches the TTL of the mDNS records themselves
+pub const DISCOVERY_TTL_SECS: i64 = 120;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DiscoveredPeer {
     pub peer_id: String,
     pub peer_name: String,
+    /// The preferred of `addresses`
     pub address: String,
+    /// Every address the peer was announced on, preferred first
+    #[serde(default)]
+    pub addresses: Vec<String>,
     pub port: u16,
     pub discovered_at: i64,
+    /// When the peer was last announced
+    #[serde(default)]
+    pub last_seen: i64,
+}
+
+impl DiscoveredPeer {
+    fn from_service(info: &ServiceInfo) -> Option<Self> {
+        let properties = info.get_properties();
+        let peer_id = properties.get("peer_id")?.val_str().to_string();
+        let now = chrono::Utc::now().timestamp();
+        let mut peer = Self {
+            peer_id,
+            peer_name: properties
+                .get("peer_name")
+                .map(|p| p.val_str().to_string())
+                .unwrap_or_else(|| "Unknown".to_string()),
+            address: String::new(),
+            addresses: info.get_addresses().iter().map(|a| a.to_string()).collect(),
+            port: info.get_port(),
+            discovered_at: now,
+            last_seen: now,
+        };
+        peer.sort_addresses();
+        Some(peer)
+    }
+
+    /// Fold a later announcement of the same peer, possibly from another
+    /// interface, into this one
+    pub fn merge(&mut self, other: DiscoveredPeer) {
+        for address in other.addresses {
+            if !self.addresses.contains(&address) {
+                self.addresses.push(address);
+            }
+        }
+        self.peer_name = other.peer_name;
+        self.port = other.port;
+        self.discovered_at = self.discovered_at.min(other.discovered_at);
+        self.last_seen = self.last_seen.max(other.last_seen);
+        self.sort_addresses();
+    }
+
+    fn sort_addresses(&mut self) {
+        self.addresses.sort_by_key(|a| address_rank(a));
+        self.address = self.addresses.first().cloned().unwrap_or_default();
+    }
+}
+
+/// Lower is tried first: private LAN IPv4, other routable addresses, then
+/// link-local and loopback, which rarely work from another machine
+fn address_rank(address: &str) -> u8 {
+    match address.parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) if ip.is_private() => 0,
+        Ok(IpAddr::V4(ip)) if ip.is_link_local() => 3,
+        Ok(ip) if ip.is_loopback() || ip.is_unspecified() => 4,
+        Ok(IpAddr::V4(_)) => 1,
+        Ok(IpAddr::V6(ip)) if (ip.segments()[0] & 0xffc0) == 0xfe80 => 3,
+        Ok(IpAddr::V6(_)) => 2,
+        Err(_) => 5,
+    }
+}
+
+/// Add `peer` to `peers`, merging it with an earlier announcement of the
+/// same peer, and drop peers not seen within `DISCOVERY_TTL_SECS`
+pub fn record_discovery(peers: &mut HashMap<String, DiscoveredPeer>, peer: DiscoveredPeer) {
+    let now = peer.last_seen;
+    match peers.get_mut(&peer.peer_id) {
+        Some(existing) => existing.merge(peer),
+        None => {
+            peers.insert(peer.peer_id.clone(), peer);
+        }
+    }
+    peers.retain(|_, p| now - p.last_seen < DISCOVERY_TTL_SECS);
 }
 
 pub struct NetworkDiscovery {
     service_daemon: Option<ServiceDaemon>,
+    /// Full mDNS name of the service this hub registered
+    service_name: Option<String>,
     discovered_peers: Arc<RwLock<HashMap<String, DiscoveredPeer>>>,
     local_peer_id: String,
     local_peer_name: String,
@@ -30,6 +113,7 @@ impl NetworkDiscovery {
     pub fn new(local_peer_id: String, local_peer_name: String, port: u16) -> Self {
         Self {
             service_daemon: None,
+            service_name: None,
             discovered_peers: Arc::new(RwLock::new(HashMap::new())),
             local_peer_id,
             local_peer_name,
@@ -42,7 +126,7 @@ impl NetworkDiscovery {
         let daemon =
             ServiceDaemon::new().map_err(|e| format!("Failed to create mDNS daemon: {}", e))?;
 
-        self.register_service(&daemon)?;
+        self.service_name = Some(self.register_service(&daemon)?);
         self.start_browsing(&daemon)?;
 
         self.service_daemon = Some(daemon);
@@ -54,7 +138,7 @@ impl NetworkDiscovery {
         Ok(())
     }
 
-    fn register_service(&self, daemon: &ServiceDaemon) -> Result<(), String> {
+    fn register_service(&self, daemon: &ServiceDaemon) -> Result<String, String> {
         let host_name = format!(
             "{}.local.",
             self.local_peer_name.replace(' ', "-").to_lowercase()
@@ -75,12 +159,13 @@ impl NetworkDiscovery {
             properties,
         )
         .map_err(|e| format!("Failed to create service info: {}", e))?;
+        let service_name = service_info.get_fullname().to_string();
 
         daemon
             .register(service_info)
             .map_err(|e| format!("Failed to register service: {}", e))?;
 
-        Ok(())
+        Ok(service_name)
     }
 
     fn start_browsing(&self, daemon: &ServiceDaemon) -> Result<(), String> {
@@ -92,47 +177,22 @@ impl NetworkDiscovery {
         let local_peer_id = self.local_peer_id.clone();
         let _running = self.running.clone();
 
+        // A plain thread, outside the runtime, so it takes the locks with
+        // `blocking_write`
         std::thread::spawn(move || {
             while let Ok(event) = receiver.recv() {
                 match event {
                     ServiceEvent::ServiceResolved(info) => {
-                        if let Some(peer_id) = info.get_properties().get("peer_id") {
-                            let peer_id_str = peer_id.val_str();
-                            if peer_id_str != local_peer_id {
-                                let peer = DiscoveredPeer {
-                                    peer_id: peer_id_str.to_string(),
-                                    peer_name: info
-                                        .get_properties()
-                                        .get("peer_name")
-                                        .map(|p| p.val_str().to_string())
-                                        .unwrap_or_else(|| "Unknown".to_string()),
-                                    address: info
-                                        .get_addresses()
-                                        .iter()
-                                        .next()
-                                        .map(|a| a.to_string())
-                                        .unwrap_or_default(),
-                                    port: info.get_port(),
-                                    discovered_at: chrono::Utc::now().timestamp(),
-                                };
-
-                                let peers = discovered_peers.clone();
-                                let peer_clone = peer.clone();
-                                tokio::spawn(async move {
-                                    peers
-                                        .write()
-                                        .await
-                                        .insert(peer_clone.peer_id.clone(), peer_clone);
-                                });
+                        if let Some(peer) = DiscoveredPeer::from_service(&info) {
+                            if peer.peer_id != local_peer_id {
+                                record_discovery(&mut discovered_peers.blocking_write(), peer);
                             }
                         }
                     }
                     ServiceEvent::ServiceRemoved(_, full_name) => {
-                        let peers = discovered_peers.clone();
-                        tokio::spawn(async move {
-                            let mut peers = peers.write().await;
-                            peers.retain(|_, p| !full_name.contains(&p.peer_id[..8]));
-                        });
+                        discovered_peers
+                            .blocking_write()
+                            .retain(|_, p| !full_name.contains(&p.peer_id[..8]));
                     }
                     _ => {}
                 }
@@ -143,23 +203,42 @@ impl NetworkDiscovery {
     }
 
     pub fn stop(&mut self) {
-        if let Some(daemon) = self.service_daemon.take() {
-            match daemon.shutdown() {
+        self.shutdown_daemon();
+        let running = self.running.clone();
+        tokio::spawn(async move {
+            *running.write().await = false;
+        });
+    }
+
+    /// Send a goodbye for our service, so peers drop us right away rather
+    /// than when its record expires, then shut the daemon down
+    fn shutdown_daemon(&mut self) {
+        let Some(daemon) = self.service_daemon.take() else {
+            return;
+        };
+        if let Some(service_name) = self.service_name.take() {
+            match daemon.unregister(&service_name) {
                 Ok(receiver) => {
-                    let _ = receiver.recv_timeout(Duration::from_millis(100));
+                    let _ = receiver.recv_timeout(Duration::from_secs(1));
                 }
                 Err(e) => {
-                    log::debug!("mDNS daemon shutdown: {}", e);
+                    log::debug!("mDNS unregister of {}: {}", service_name, e);
                 }
             }
         }
-        let running = self.running.clone();
-        tokio::spawn(async move {
-            *running.write().await = false;
-        });
+        match daemon.shutdown() {
+            Ok(receiver) => {
+                let _ = receiver.recv_timeout(Duration::from_millis(100));
+            }
+            Err(e) => {
+                log::debug!("mDNS daemon shutdown: {}", e);
+            }
+        }
     }
 
+    /// Peers seen within `DISCOVERY_TTL_SECS`
     pub async fn get_discovered_peers(&self) -> Vec<DiscoveredPeer> {
+        self.clear_stale_peers(DISCOVERY_TTL_SECS).await;
         self.discovered_peers
             .read()
             .await
@@ -169,6 +248,7 @@ impl NetworkDiscovery {
     }
 
     pub async fn get_peer(&self, peer_id: &str) -> Option<DiscoveredPeer> {
+        self.clear_stale_peers(DISCOVERY_TTL_SECS).await;
         self.discovered_peers.read().await.get(peer_id).cloned()
     }
 
@@ -177,7 +257,7 @@ impl NetworkDiscovery {
         self.discovered_peers
             .write()
             .await
-            .retain(|_, p| now - p.discovered_at < max_age_seconds);
+            .retain(|_, p| now - p.last_seen < max_age_seconds);
     }
 
     pub async fn is_running(&self) -> bool {
@@ -185,17 +265,14 @@ impl NetworkDiscovery {
     }
 
     pub async fn peer_count(&self) -> usize {
+        self.clear_stale_peers(DISCOVERY_TTL_SECS).await;
         self.discovered_peers.read().await.len()
     }
 }
 
 impl Drop for NetworkDiscovery {
     fn drop(&mut self) {
-        if let Some(daemon) = self.service_daemon.take() {
-            if let Ok(receiver) = daemon.shutdown() {
-                let _ = receiver.recv_timeout(Duration::from_millis(100));
-            }
-        }
+        self.shutdown_daemon();
     }
 }
 
@@ -214,24 +291,8 @@ pub async fn discover_once(timeout_secs: u64) -> Result<Vec<DiscoveredPeer>, Str
         if let Ok(ServiceEvent::ServiceResolved(info)) =
             receiver.recv_timeout(Duration::from_millis(100))
         {
-            if let Some(peer_id) = info.get_properties().get("peer_id") {
-                let peer = DiscoveredPeer {
-                    peer_id: peer_id.val_str().to_string(),
-                    peer_name: info
-                        .get_properties()
-                        .get("peer_name")
-                        .map(|p| p.val_str().to_string())
-                        .unwrap_or_else(|| "Unknown".to_string()),
-                    address: info
-                        .get_addresses()
-                        .iter()
-                        .next()
-                        .map(|a| a.to_string())
-                        .unwrap_or_default(),
-                    port: info.get_port(),
-                    discovered_at: chrono::Utc::now().timestamp(),
-                };
-                peers.insert(peer.peer_id.clone(), peer);
+            if let Some(peer) = DiscoveredPeer::from_service(&info) {
+                record_discovery(&mut peers, peer);
             }
         }
     }
@@ -246,18 +307,48 @@ pub async fn discover_once(timeout_secs: u64) -> Result<Vec<DiscoveredPeer>, Str
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_discovered_peer_creation() {
-        let peer = DiscoveredPeer {
+    fn announcement(address: &str, seen: i64) -> DiscoveredPeer {
+        DiscoveredPeer {
             peer_id: "test-id".to_string(),
             peer_name: "Test".to_string(),
-            address: "192.168.1.1".to_string(),
+            address: address.to_string(),
+            addresses: vec![address.to_string()],
             port: 9876,
-            discovered_at: chrono::Utc::now().timestamp(),
-        };
+            discovered_at: seen,
+            last_seen: seen,
+        }
+    }
+
+    #[test]
+    fn test_discovered_peer_creation() {
+        let peer = announcement("192.168.1.1", chrono::Utc::now().timestamp());
         assert_eq!(peer.peer_id, "test-id");
     }
 
+    #[test]
+    fn test_announcements_on_two_interfaces_merge() {
+        let mut peers = HashMap::new();
+        record_discovery(&mut peers, announcement("169.254.10.2", 1_000));
+        record_discovery(&mut peers, announcement("192.168.1.20", 1_005));
+
+        assert_eq!(peers.len(), 1);
+        let peer = &peers["test-id"];
+        assert_eq!(peer.address, "192.168.1.20");
+        assert_eq!(peer.addresses, vec!["192.168.1.20", "169.254.10.2"]);
+        assert_eq!((peer.discovered_at, peer.last_seen), (1_000, 1_005));
+    }
+
+    #[test]
+    fn test_stale_discoveries_are_pruned() {
+        let mut peers = HashMap::new();
+        record_discovery(&mut peers, announcement("192.168.1.20", 1_000));
+        let mut other = announcement("192.168.1.30", 1_000 + DISCOVERY_TTL_SECS);
+        other.peer_id = "other-id".to_string();
+        record_discovery(&mut peers, other);
+
+        assert_eq!(peers.keys().collect::<Vec<_>>(), vec!["other-id"]);
+    }
+
     #[test]
     fn test_network_discovery_creation() {
         let discovery =
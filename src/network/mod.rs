@@ -2,24 +2,53 @@ pub mod auth;
 pub mod discovery;
 pub mod peer;
 pub mod protocol;
+pub mod rate_limit;
 pub mod tcp;
 pub mod tls;
 
-pub use auth::{AuthChallenge, AuthToken, AuthTokenStore, DEFAULT_TOKEN_EXPIRY};
-pub use discovery::{discover_once, DiscoveredPeer, NetworkDiscovery};
+pub use auth::{
+    AuthChallenge, AuthToken, AuthTokenStore, TokenError, TokenKind, DEFAULT_PEER_TOKEN_EXPIRY,
+    DEFAULT_ROTATION_GRACE, DEFAULT_TOKEN_EXPIRY,
+};
+pub use discovery::{discover_once, DiscoveredPeer, NetworkDiscovery, DISCOVERY_TTL_SECS};
 pub use peer::{Peer, PeerRegistry};
 pub use protocol::{
-    NetworkCommand, NetworkMessage, RemoteSession, SharedPath, DEFAULT_PORT, MDNS_SERVICE_TYPE,
-    PROTOCOL_VERSION,
+    FrameError, NetworkCommand, NetworkMessage, RemoteSession, SharedPath,
+    DEFAULT_MAX_MESSAGE_BYTES, DEFAULT_PORT, MDNS_SERVICE_TYPE, PROTOCOL_VERSION,
+};
+pub use rate_limit::{RateLimit, RateLimitConfig};
+pub use tcp::{
+    ClientConnection, Connection, ConnectionId, NetworkClient, NetworkServer, ShutdownSummary,
 };
-pub use tcp::{ClientConnection, Connection, ConnectionId, NetworkClient, NetworkServer};
-pub use tls::{ensure_certificates, TlsConfig};
+pub use tls::{ensure_certificates, PinnedCertVerifier, TlsConfig};
 
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::RwLock;
 
+use crate::hub::RemovalSummary;
+
+pub const DEFAULT_HEARTBEAT_INTERVAL_SECS: u64 = 30;
+pub const DEFAULT_HEARTBEAT_MISS_LIMIT: u32 = 3;
+
+fn default_heartbeat_interval_secs() -> u64 {
+    DEFAULT_HEARTBEAT_INTERVAL_SECS
+}
+
+fn default_heartbeat_miss_limit() -> u32 {
+    DEFAULT_HEARTBEAT_MISS_LIMIT
+}
+
+fn default_max_message_bytes() -> usize {
+    DEFAULT_MAX_MESSAGE_BYTES
+}
+
+fn default_shutdown_grace_secs() -> u64 {
+    tcp::DEFAULT_SHUTDOWN_GRACE.as_secs()
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NetworkConfig {
     pub enabled: bool,
@@ -28,6 +57,21 @@ pub struct NetworkConfig {
     pub discovery_enabled: bool,
     pub tls_enabled: bool,
     pub max_connections: usize,
+    /// Seconds between heartbeats sent to each connected hub
+    #[serde(default = "default_heartbeat_interval_secs")]
+    pub heartbeat_interval_secs: u64,
+    /// Consecutive missed heartbeats after which a hub is marked offline
+    #[serde(default = "default_heartbeat_miss_limit")]
+    pub heartbeat_miss_limit: u32,
+    /// Messages each peer may send to the server
+    #[serde(default)]
+    pub rate_limit: RateLimitConfig,
+    /// Largest message accepted from a peer; a larger one closes the connection
+    #[serde(default = "default_max_message_bytes")]
+    pub max_message_bytes: usize,
+    /// Seconds `stop` waits for queued messages before closing connections
+    #[serde(default = "default_shutdown_grace_secs")]
+    pub shutdown_grace_secs: u64,
 }
 
 impl Default for NetworkConfig {
@@ -39,6 +83,11 @@ impl Default for NetworkConfig {
             discovery_enabled: true,
             tls_enabled: true,
             max_connections: 50,
+            heartbeat_interval_secs: DEFAULT_HEARTBEAT_INTERVAL_SECS,
+            heartbeat_miss_limit: DEFAULT_HEARTBEAT_MISS_LIMIT,
+            rate_limit: RateLimitConfig::default(),
+            max_message_bytes: DEFAULT_MAX_MESSAGE_BYTES,
+            shutdown_grace_secs: default_shutdown_grace_secs(),
         }
     }
 }
@@ -59,13 +108,22 @@ impl NetworkManager {
         std::fs::create_dir_all(&data_dir)
             .map_err(|e| format!("Failed to create data directory: {}", e))?;
 
-        let peer_registry = Arc::new(RwLock::new(PeerRegistry::load(
-            data_dir.join("peers.json"),
-        )?));
+        let registry = PeerRegistry::load(data_dir.join("peers.json"))?;
 
-        let token_store = Arc::new(RwLock::new(AuthTokenStore::load(
-            data_dir.join("tokens.json"),
-        )?));
+        let mut tokens = AuthTokenStore::load(data_dir.join("tokens.json"))?;
+        // Peers approved before auth went through the store only have their
+        // token in the registry
+        let mut imported = false;
+        for peer in registry.get_authorized_peers() {
+            if let Some(token) = &peer.auth_token {
+                imported |= tokens.import_peer_token(&peer.id, token);
+            }
+        }
+        if imported {
+            tokens.save()?;
+        }
+        let peer_registry = Arc::new(RwLock::new(registry));
+        let token_store = Arc::new(RwLock::new(tokens));
 
         let tls_config = TlsConfig::new(data_dir.join("tls"));
 
@@ -90,14 +148,17 @@ impl NetworkManager {
         let peer_id = registry.local_peer_id.clone();
         drop(registry);
 
+        let mut server = NetworkServer::new(self.config.port, self.peer_registry.clone())
+            .with_token_store(self.token_store.clone())
+            .with_rate_limit(self.config.rate_limit)
+            .with_max_message_bytes(self.config.max_message_bytes)
+            .with_grace_period(Duration::from_secs(self.config.shutdown_grace_secs));
         if self.config.tls_enabled {
             ensure_certificates(&self.tls_config, &peer_name)?;
+            server = server.with_tls(self.tls_config.load_server_config()?);
         }
 
-        let server = Arc::new(NetworkServer::new(
-            self.config.port,
-            self.peer_registry.clone(),
-        ));
+        let server = Arc::new(server);
         server.start().await?;
         self.server = Some(server);
 
@@ -110,16 +171,20 @@ impl NetworkManager {
         Ok(())
     }
 
-    pub async fn stop(&mut self) {
-        if let Some(ref server) = self.server {
-            server.stop().await;
-        }
-        self.server = None;
-
+    /// Announce departure on mDNS, then stop the server once its open
+    /// connections have drained or the grace period has run out
+    pub async fn stop(&mut self) -> ShutdownSummary {
         if let Some(ref discovery) = self.discovery {
             discovery.write().await.stop();
         }
         self.discovery = None;
+
+        let summary = match self.server {
+            Some(ref server) => server.stop().await,
+            None => ShutdownSummary::default(),
+        };
+        self.server = None;
+        summary
     }
 
     pub async fn is_running(&self) -> bool {
@@ -177,8 +242,15 @@ impl NetworkManager {
         Ok(peer)
     }
 
-    pub async fn remove_peer(&self, peer_id: &str) -> Result<(), String> {
-        self.peer_registry.write().await.remove_peer(peer_id)
+    pub async fn remove_peer(
+        &self,
+        peer_id: &str,
+        dry_run: bool,
+    ) -> Result<RemovalSummary, String> {
+        self.peer_registry
+            .write()
+            .await
+            .remove_peer(peer_id, dry_run)
     }
 
     pub async fn get_peers(&self) -> Vec<Peer> {
@@ -201,6 +273,12 @@ impl NetworkManager {
             .collect()
     }
 
+    /// The token store, for other listeners that authenticate clients with
+    /// the same tokens
+    pub fn token_store(&self) -> Arc<RwLock<AuthTokenStore>> {
+        Arc::clone(&self.token_store)
+    }
+
     pub async fn create_auth_token(
         &self,
         peer_id: Option<&str>,
@@ -214,17 +292,36 @@ impl NetworkManager {
         }
     }
 
-    pub async fn validate_token(&self, token: &str, peer_id: &str) -> Result<bool, String> {
+    pub async fn validate_token(&self, token: &str, peer_id: &str) -> Result<(), TokenError> {
         self.token_store
             .write()
             .await
             .validate_token(token, peer_id)
     }
 
-    pub async fn authorize_peer(&self, peer_id: &str) -> Result<AuthToken, String> {
+    /// Approve the peer with a session token it can reuse on every
+    /// connection until it expires, is rotated or is revoked
+    pub async fn authorize_peer(
+        &self,
+        peer_id: &str,
+        expires_in: i64,
+    ) -> Result<AuthToken, String> {
         let token = self
-            .create_auth_token(Some(peer_id), DEFAULT_TOKEN_EXPIRY)
-            .await?;
+            .token_store
+            .write()
+            .await
+            .create_session_token(peer_id, expires_in)?;
+        self.peer_registry
+            .write()
+            .await
+            .authorize_peer(peer_id, &token.token)?;
+        Ok(token)
+    }
+
+    /// Issue the peer a new token; its old ones keep working for
+    /// `DEFAULT_ROTATION_GRACE` seconds
+    pub async fn rotate_peer_token(&self, peer_id: &str) -> Result<AuthToken, String> {
+        let token = self.token_store.write().await.rotate(peer_id)?;
         self.peer_registry
             .write()
             .await
@@ -232,13 +329,38 @@ impl NetworkManager {
         Ok(token)
     }
 
+    /// Refuse the peer's tokens from now on and drop its authorization
+    pub async fn revoke_peer(&self, peer_id: &str) -> Result<usize, String> {
+        let revoked = self.token_store.write().await.revoke(peer_id)?;
+        self.peer_registry.write().await.revoke_peer(peer_id)?;
+        Ok(revoked)
+    }
+
+    /// Re-pin a peer whose certificate changed on purpose; see
+    /// [`PeerRegistry::reauthorize_peer`]
+    pub async fn reauthorize_peer(
+        &self,
+        peer_id: &str,
+        fingerprint: Option<&str>,
+    ) -> Result<(), String> {
+        self.peer_registry
+            .write()
+            .await
+            .reauthorize_peer(peer_id, fingerprint)
+    }
+
+    fn client(&self) -> NetworkClient {
+        NetworkClient::new(self.peer_registry.clone())
+            .with_tls(self.config.tls_enabled)
+            .with_max_message_bytes(self.config.max_message_bytes)
+    }
+
     pub async fn connect_to_peer(
         &self,
         address: &str,
         port: u16,
     ) -> Result<ClientConnection, String> {
-        let client = NetworkClient::new(self.peer_registry.clone());
-        client.connect(address, port).await
+        self.client().connect(address, port).await
     }
 
     pub async fn connect_and_auth(
@@ -247,8 +369,7 @@ impl NetworkManager {
         port: u16,
         token: &str,
     ) -> Result<ClientConnection, String> {
-        let client = NetworkClient::new(self.peer_registry.clone());
-        client.connect_and_auth(address, port, token).await
+        self.client().connect_and_auth(address, port, token).await
     }
 
     pub async fn get_all_sessions(&self) -> Vec<RemoteSession> {
@@ -351,4 +472,37 @@ mod tests {
         assert!(!status.running);
         let _ = std::fs::remove_dir_all(dir);
     }
+
+    #[tokio::test]
+    async fn test_approved_peer_tokens_outlive_short_expiry() {
+        let dir = temp_dir().join(format!("sena_network_tokens_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut registry = PeerRegistry::new(dir.join("peers.json"));
+        registry
+            .add_peer(Peer::new("legacy", "Legacy", "127.0.0.1", 1))
+            .unwrap();
+        registry.authorize_peer("legacy", "legacy-token").unwrap();
+        registry
+            .add_peer(Peer::new("fresh", "Fresh", "127.0.0.1", 2))
+            .unwrap();
+
+        let manager = NetworkManager::new(NetworkConfig::default(), dir.clone()).unwrap();
+        for _ in 0..2 {
+            assert_eq!(
+                manager.validate_token("legacy-token", "legacy").await,
+                Ok(())
+            );
+        }
+
+        let token = manager
+            .authorize_peer("fresh", DEFAULT_PEER_TOKEN_EXPIRY)
+            .await
+            .unwrap();
+        assert_eq!(token.kind, TokenKind::Session);
+        assert!(token.remaining_seconds() > DEFAULT_TOKEN_EXPIRY);
+        for _ in 0..2 {
+            assert_eq!(manager.validate_token(&token.token, "fresh").await, Ok(()));
+        }
+        let _ = std::fs::remove_dir_all(dir);
+    }
 }
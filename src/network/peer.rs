@@ -4,6 +4,10 @@ use std::fs;
 use std::net::SocketAddr;
 use std::path::PathBuf;
 
+use super::tls::normalize_fingerprint;
+use crate::hub::RemovalSummary;
+use crate::sync::write_atomic;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Peer {
     pub id: String,
@@ -13,6 +17,11 @@ pub struct Peer {
     pub authorized: bool,
     pub auth_token: Option<String>,
     pub public_key: Option<String>,
+    /// SHA-256 fingerprint of the peer's TLS certificate, pinned when we
+    /// first authenticated to it; connections presenting another
+    /// certificate are refused
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cert_fingerprint: Option<String>,
     pub last_seen: i64,
     pub created_at: i64,
 }
@@ -28,6 +37,7 @@ impl Peer {
             authorized: false,
             auth_token: None,
             public_key: None,
+            cert_fingerprint: None,
             last_seen: now,
             created_at: now,
         }
@@ -118,7 +128,7 @@ impl PeerRegistry {
         let content = serde_json::to_string_pretty(self)
             .map_err(|e| format!("Failed to serialize peer registry: {}", e))?;
 
-        fs::write(&self.file_path, content)
+        write_atomic(&self.file_path, content)
             .map_err(|e| format!("Failed to write peer registry: {}", e))
     }
 
@@ -139,11 +149,24 @@ impl PeerRegistry {
         self.save()
     }
 
-    pub fn remove_peer(&mut self, peer_id: &str) -> Result<(), String> {
-        self.peers
-            .remove(peer_id)
+    /// Remove a peer, or with `dry_run` only report what would be removed
+    pub fn remove_peer(&mut self, peer_id: &str, dry_run: bool) -> Result<RemovalSummary, String> {
+        let peer = self
+            .peers
+            .get(peer_id)
             .ok_or_else(|| format!("Peer {} not found", peer_id))?;
-        self.save()
+
+        let mut summary = RemovalSummary::new("peer registry", dry_run);
+        summary.push(format!(
+            "{} ({} at {}:{})",
+            peer.id, peer.name, peer.address, peer.port
+        ));
+
+        if !dry_run {
+            self.peers.remove(peer_id);
+            self.save()?;
+        }
+        Ok(summary)
     }
 
     pub fn get_peer(&self, peer_id: &str) -> Option<&Peer> {
@@ -193,6 +216,65 @@ impl PeerRegistry {
         self.save()
     }
 
+    /// Pin `fingerprint` for the peer `peer_id` at `address:port`. A peer
+    /// added by address under another id is matched by address; an unknown
+    /// peer is added. A different certificate pinned already is an error;
+    /// only `reauthorize_peer` replaces a pin.
+    pub fn pin_certificate(
+        &mut self,
+        peer_id: &str,
+        peer_name: &str,
+        address: &str,
+        port: u16,
+        fingerprint: &str,
+    ) -> Result<(), String> {
+        let key = if self.peers.contains_key(peer_id) {
+            peer_id.to_string()
+        } else if let Some(peer) = self.get_peer_by_address(address, port) {
+            peer.id.clone()
+        } else {
+            self.peers.insert(
+                peer_id.to_string(),
+                Peer::new(peer_id, peer_name, address, port),
+            );
+            peer_id.to_string()
+        };
+
+        let peer = self
+            .peers
+            .get_mut(&key)
+            .ok_or_else(|| format!("Peer {} not found", key))?;
+        let fingerprint = normalize_fingerprint(fingerprint);
+        match &peer.cert_fingerprint {
+            Some(pinned) if *pinned == fingerprint => return Ok(()),
+            Some(_) => {
+                return Err(format!(
+                    "Certificate of peer {} does not match the pinned one",
+                    key
+                ))
+            }
+            None => peer.cert_fingerprint = Some(fingerprint),
+        }
+        peer.update_last_seen();
+        self.save()
+    }
+
+    /// Replace the pinned certificate of a peer that deliberately rotated
+    /// it. With no fingerprint, the next successful authentication pins
+    /// whatever certificate the peer presents.
+    pub fn reauthorize_peer(
+        &mut self,
+        peer_id: &str,
+        fingerprint: Option<&str>,
+    ) -> Result<(), String> {
+        let peer = self
+            .peers
+            .get_mut(peer_id)
+            .ok_or_else(|| format!("Peer {} not found", peer_id))?;
+        peer.cert_fingerprint = fingerprint.map(normalize_fingerprint);
+        self.save()
+    }
+
     pub fn update_peer_last_seen(&mut self, peer_id: &str) -> Result<(), String> {
         let peer = self
             .peers
@@ -256,6 +338,67 @@ mod tests {
         assert!(registry.get_peer("peer1").is_some());
     }
 
+    #[test]
+    fn test_remove_peer_dry_run_keeps_registry() {
+        let path = temp_dir().join(format!("test_peers_{}.json", uuid::Uuid::new_v4()));
+        let mut registry = PeerRegistry::new(path.clone());
+        registry
+            .add_peer(Peer::new("peer1", "Test", "192.168.1.1", 9876))
+            .unwrap();
+
+        let preview = registry.remove_peer("peer1", true).unwrap();
+        assert!(preview.dry_run);
+        assert_eq!(preview.count, 1);
+        assert!(preview.items[0].starts_with("peer1"));
+        assert!(PeerRegistry::load(path.clone())
+            .unwrap()
+            .get_peer("peer1")
+            .is_some());
+
+        let removed = registry.remove_peer("peer1", false).unwrap();
+        assert!(!removed.dry_run);
+        assert_eq!(removed.count, 1);
+        assert!(PeerRegistry::load(path.clone())
+            .unwrap()
+            .get_peer("peer1")
+            .is_none());
+        assert!(registry.remove_peer("peer1", true).is_err());
+
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_certificate_pinned_once_until_reauthorized() {
+        let path = temp_dir().join(format!("test_peers_{}.json", uuid::Uuid::new_v4()));
+        let mut registry = PeerRegistry::new(path.clone());
+        registry
+            .add_peer(Peer::new("local-id", "Hub", "10.0.0.2", 9876))
+            .unwrap();
+
+        registry
+            .pin_certificate("remote-id", "Hub", "10.0.0.2", 9876, "AB:CD")
+            .unwrap();
+        registry
+            .pin_certificate("remote-id", "Hub", "10.0.0.2", 9876, "ab:cd")
+            .unwrap();
+        let err = registry
+            .pin_certificate("remote-id", "Hub", "10.0.0.2", 9876, "ef01")
+            .unwrap_err();
+        assert!(err.contains("does not match"), "{}", err);
+        assert_eq!(registry.peer_count(), 1);
+        let pinned = |r: &PeerRegistry| r.get_peer("local-id").unwrap().cert_fingerprint.clone();
+        assert_eq!(pinned(&registry).as_deref(), Some("abcd"));
+
+        registry.reauthorize_peer("local-id", Some("ef01")).unwrap();
+        assert_eq!(pinned(&registry).as_deref(), Some("ef01"));
+        assert_eq!(
+            pinned(&PeerRegistry::load(path.clone()).unwrap()).as_deref(),
+            Some("ef01")
+        );
+
+        let _ = fs::remove_file(path);
+    }
+
     #[test]
     fn test_auth_token_generation() {
         let token1 = PeerRegistry::generate_auth_token();
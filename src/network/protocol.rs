@@ -1,4 +1,7 @@
 use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::hub::{HubIdentity, SharedState};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum NetworkCommand {
@@ -87,6 +90,34 @@ pub enum NetworkCommand {
         reason: String,
     },
 
+    HubHandshake {
+        identity: HubIdentity,
+        passkey: String,
+        message: Option<String>,
+        /// The token the sender already holds for this hub, proving it is
+        /// the hub that was approved
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        auth_token: Option<String>,
+    },
+    HubHandshakeAck {
+        identity: HubIdentity,
+        request_id: Option<String>,
+        auth_token: Option<String>,
+    },
+    /// Liveness ping between connected hubs, carrying the sender's shared
+    /// session state; answered with `HubHeartbeatAck`, or `Pong` when it
+    /// carries none
+    HubHeartbeat {
+        hub_id: String,
+        auth_token: String,
+        #[serde(default)]
+        state: Option<SharedState>,
+    },
+    /// The answering hub's shared session state, after merging the sender's
+    HubHeartbeatAck {
+        state: SharedState,
+    },
+
     SessionListRequest,
     SessionListResponse {
         hub_id: String,
@@ -255,6 +286,44 @@ impl NetworkMessage {
         })
     }
 
+    pub fn hub_handshake(
+        identity: &HubIdentity,
+        passkey: &str,
+        message: Option<String>,
+        auth_token: Option<&str>,
+    ) -> Self {
+        Self::new(NetworkCommand::HubHandshake {
+            identity: identity.clone(),
+            passkey: passkey.to_string(),
+            message,
+            auth_token: auth_token.map(str::to_string),
+        })
+    }
+
+    pub fn hub_handshake_ack(
+        identity: &HubIdentity,
+        request_id: Option<String>,
+        auth_token: Option<String>,
+    ) -> Self {
+        Self::new(NetworkCommand::HubHandshakeAck {
+            identity: identity.clone(),
+            request_id,
+            auth_token,
+        })
+    }
+
+    pub fn hub_heartbeat(hub_id: &str, auth_token: &str, state: Option<SharedState>) -> Self {
+        Self::new(NetworkCommand::HubHeartbeat {
+            hub_id: hub_id.to_string(),
+            auth_token: auth_token.to_string(),
+            state,
+        })
+    }
+
+    pub fn hub_heartbeat_ack(state: SharedState) -> Self {
+        Self::new(NetworkCommand::HubHeartbeatAck { state })
+    }
+
     pub fn session_list_request() -> Self {
         Self::new(NetworkCommand::SessionListRequest)
     }
@@ -319,15 +388,147 @@ impl NetworkMessage {
         if bytes.len() < 4 + len {
             return Err("Incomplete message".to_string());
         }
-        let json =
-            std::str::from_utf8(&bytes[4..4 + len]).map_err(|e| format!("Invalid UTF-8: {}", e))?;
+        Self::from_payload(&bytes[4..4 + len])
+    }
+
+    /// Decode the JSON of a frame, without its length prefix
+    pub fn from_payload(payload: &[u8]) -> Result<Self, String> {
+        let json = std::str::from_utf8(payload).map_err(|e| format!("Invalid UTF-8: {}", e))?;
         serde_json::from_str(json).map_err(|e| format!("Deserialization failed: {}", e))
     }
 }
 
+/// Why the next frame could not be read
+#[derive(Debug, thiserror::Error)]
+pub enum FrameError {
+    #[error("Message of {len} bytes exceeds the {max} byte limit")]
+    TooLarge { len: usize, max: usize },
+    #[error("Connection closed partway through a message length")]
+    TruncatedHeader,
+    #[error("Connection closed partway through a {0} byte message")]
+    Truncated(usize),
+    #[error("Failed to read message: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+impl FrameError {
+    /// Error code to send the peer before closing, when it should be told
+    pub fn error_code(&self) -> Option<u32> {
+        match self {
+            FrameError::TooLarge { .. } => Some(ERROR_MESSAGE_TOO_LARGE),
+            _ => None,
+        }
+    }
+}
+
+impl From<FrameError> for std::io::Error {
+    fn from(error: FrameError) -> Self {
+        match error {
+            FrameError::Io(e) => e,
+            FrameError::TruncatedHeader | FrameError::Truncated(_) => {
+                std::io::Error::new(std::io::ErrorKind::UnexpectedEof, error)
+            }
+            FrameError::TooLarge { .. } => {
+                std::io::Error::new(std::io::ErrorKind::InvalidData, error)
+            }
+        }
+    }
+}
+
+/// Payload length announced by a frame header, refused when over `max_bytes`
+pub fn frame_len(header: [u8; 4], max_bytes: usize) -> Result<usize, FrameError> {
+    let len = u32::from_be_bytes(header) as usize;
+    if len > max_bytes {
+        return Err(FrameError::TooLarge {
+            len,
+            max: max_bytes,
+        });
+    }
+    Ok(len)
+}
+
+/// Read the payload of the next frame. `Ok(None)` when the peer closed the
+/// connection between frames; closing it partway through a frame is an
+/// error. The size is checked before the payload is buffered.
+pub async fn read_frame<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    max_bytes: usize,
+) -> Result<Option<Vec<u8>>, FrameError> {
+    let mut header = [0u8; 4];
+    let mut filled = 0;
+    while filled < header.len() {
+        let n = reader.read(&mut header[filled..]).await?;
+        if n == 0 {
+            return if filled == 0 {
+                Ok(None)
+            } else {
+                Err(FrameError::TruncatedHeader)
+            };
+        }
+        filled += n;
+    }
+
+    let len = frame_len(header, max_bytes)?;
+    let mut payload = vec![0u8; len];
+    reader
+        .read_exact(&mut payload)
+        .await
+        .map_err(|e| truncated(e, len))?;
+    Ok(Some(payload))
+}
+
+/// [`read_frame`] for blocking readers
+pub fn read_frame_blocking<R: std::io::Read>(
+    reader: &mut R,
+    max_bytes: usize,
+) -> Result<Option<Vec<u8>>, FrameError> {
+    let mut header = [0u8; 4];
+    let mut filled = 0;
+    while filled < header.len() {
+        let n = reader.read(&mut header[filled..])?;
+        if n == 0 {
+            return if filled == 0 {
+                Ok(None)
+            } else {
+                Err(FrameError::TruncatedHeader)
+            };
+        }
+        filled += n;
+    }
+
+    let len = frame_len(header, max_bytes)?;
+    let mut payload = vec![0u8; len];
+    reader
+        .read_exact(&mut payload)
+        .map_err(|e| truncated(e, len))?;
+    Ok(Some(payload))
+}
+
+fn truncated(error: std::io::Error, len: usize) -> FrameError {
+    if error.kind() == std::io::ErrorKind::UnexpectedEof {
+        FrameError::Truncated(len)
+    } else {
+        FrameError::Io(error)
+    }
+}
+
+/// Whether a peer speaking protocol `version` can talk to this build; the
+/// major versions have to match
+pub fn is_compatible_version(version: &str) -> bool {
+    let major = |v: &str| v.split('.').next().unwrap_or_default().trim().to_string();
+    major(version) == major(PROTOCOL_VERSION)
+}
+
 pub const DEFAULT_PORT: u16 = 9876;
 pub const MDNS_SERVICE_TYPE: &str = "_sena._tcp.local.";
 pub const PROTOCOL_VERSION: &str = "2.0";
+/// Largest message payload accepted unless `NetworkConfig` says otherwise
+pub const DEFAULT_MAX_MESSAGE_BYTES: usize = 1024 * 1024;
+
+/// `Error` code sent before closing a connection whose message was too large
+pub const ERROR_MESSAGE_TOO_LARGE: u32 = 413;
+/// `Error` code answering a handshake in an incompatible protocol version
+pub const ERROR_VERSION_MISMATCH: u32 = 426;
 
 #[cfg(test)]
 mod tests {
@@ -359,4 +560,59 @@ mod tests {
             panic!("Wrong command type");
         }
     }
+
+    #[tokio::test]
+    async fn test_oversized_and_truncated_frames_are_rejected() {
+        let mut huge: &[u8] = &[0x00, 0x10, 0x00, 0x00, b'{'];
+        let err = read_frame(&mut huge, 1024).await.unwrap_err();
+        assert!(matches!(
+            err,
+            FrameError::TooLarge {
+                len: 1048576,
+                max: 1024
+            }
+        ));
+        assert_eq!(err.error_code(), Some(ERROR_MESSAGE_TOO_LARGE));
+
+        let bytes = NetworkMessage::ping().to_bytes().unwrap();
+        let mut truncated = &bytes[..bytes.len() - 3];
+        let err = read_frame(&mut truncated, 1024).await.unwrap_err();
+        assert!(matches!(err, FrameError::Truncated(_)));
+        assert_eq!(err.error_code(), None);
+        let mut half_header = &bytes[..2];
+        assert!(matches!(
+            read_frame(&mut half_header, 1024).await,
+            Err(FrameError::TruncatedHeader)
+        ));
+
+        let mut closed: &[u8] = &[];
+        assert_eq!(read_frame(&mut closed, 1024).await.unwrap(), None);
+
+        let mut whole = bytes.as_slice();
+        let payload = read_frame(&mut whole, 1024).await.unwrap().unwrap();
+        let msg = NetworkMessage::from_payload(&payload).unwrap();
+        assert!(matches!(msg.command, NetworkCommand::Ping));
+
+        // Blocking readers share the same framing
+        let mut whole = bytes.as_slice();
+        assert_eq!(
+            read_frame_blocking(&mut whole, 1024).unwrap(),
+            Some(payload)
+        );
+        let mut huge: &[u8] = &[0x00, 0x10, 0x00, 0x00, b'{'];
+        assert!(matches!(
+            read_frame_blocking(&mut huge, 1024),
+            Err(FrameError::TooLarge { .. })
+        ));
+        let mut closed: &[u8] = &[];
+        assert!(read_frame_blocking(&mut closed, 1024).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_version_compatibility() {
+        assert!(is_compatible_version(PROTOCOL_VERSION));
+        assert!(is_compatible_version("2.7"));
+        assert!(!is_compatible_version("1.0"));
+        assert!(!is_compatible_version("3"));
+    }
 }
@@ -0,0 +1,188 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Dropped messages are counted over windows of this length when deciding
+/// whether a peer is flooding rather than briefly bursting
+pub const ABUSE_WINDOW: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RateLimit {
+    /// Sustained messages per second
+    pub per_second: f64,
+    /// Messages accepted at once before `per_second` applies
+    pub burst: u32,
+}
+
+/// Per-peer message limits for `NetworkServer`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    /// Before the connection has authenticated
+    pub unauthenticated: RateLimit,
+    pub authenticated: RateLimit,
+    /// Dropped messages within one `ABUSE_WINDOW` after which the
+    /// connection is closed
+    pub disconnect_after: u32,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            unauthenticated: RateLimit {
+                per_second: 5.0,
+                burst: 10,
+            },
+            authenticated: RateLimit {
+                per_second: 50.0,
+                burst: 100,
+            },
+            disconnect_after: 100,
+        }
+    }
+}
+
+/// What to do with a message that just arrived
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Allow,
+    /// Over the limit; ignore the message but keep the connection
+    Drop,
+    /// Over the limit for a whole `ABUSE_WINDOW`; close the connection
+    Disconnect,
+}
+
+/// Token bucket for one peer, plus the count of messages it had dropped
+#[derive(Debug, Clone)]
+pub struct PeerLimiter {
+    tokens: f64,
+    refilled_at: Instant,
+    dropped: u32,
+    window_start: Instant,
+}
+
+impl PeerLimiter {
+    pub fn new(limit: RateLimit, now: Instant) -> Self {
+        Self {
+            tokens: f64::from(limit.burst),
+            refilled_at: now,
+            dropped: 0,
+            window_start: now,
+        }
+    }
+
+    /// Take a token for a message arriving at `now` under `limit`. The
+    /// limit may change between calls, as when a connection authenticates.
+    pub fn check(&mut self, limit: RateLimit, disconnect_after: u32, now: Instant) -> Verdict {
+        let elapsed = now
+            .saturating_duration_since(self.refilled_at)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * limit.per_second).min(f64::from(limit.burst));
+        self.refilled_at = now;
+
+        if now.saturating_duration_since(self.window_start) >= ABUSE_WINDOW {
+            self.window_start = now;
+            self.dropped = 0;
+        }
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            return Verdict::Allow;
+        }
+
+        self.dropped += 1;
+        if self.dropped >= disconnect_after {
+            Verdict::Disconnect
+        } else {
+            Verdict::Drop
+        }
+    }
+
+    /// Messages dropped in the current window
+    pub fn dropped(&self) -> u32 {
+        self.dropped
+    }
+}
+
+/// Limiters for every peer talking to a `NetworkServer`. Authenticated
+/// connections are keyed by peer id, so a peer can't multiply its allowance
+/// by opening more connections; others by connection id.
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    peers: Mutex<HashMap<String, PeerLimiter>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            peers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn check(&self, key: &str, authenticated: bool) -> Verdict {
+        let limit = if authenticated {
+            self.config.authenticated
+        } else {
+            self.config.unauthenticated
+        };
+        let now = Instant::now();
+        let Ok(mut peers) = self.peers.lock() else {
+            return Verdict::Allow;
+        };
+        peers
+            .entry(key.to_string())
+            .or_insert_with(|| PeerLimiter::new(limit, now))
+            .check(limit, self.config.disconnect_after, now)
+    }
+
+    pub fn forget(&self, key: &str) {
+        if let Ok(mut peers) = self.peers.lock() {
+            peers.remove(key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIMIT: RateLimit = RateLimit {
+        per_second: 2.0,
+        burst: 5,
+    };
+
+    #[test]
+    fn test_burst_is_throttled_then_refills() {
+        let start = Instant::now();
+        let mut limiter = PeerLimiter::new(LIMIT, start);
+
+        let verdicts: Vec<Verdict> = (0..8).map(|_| limiter.check(LIMIT, 20, start)).collect();
+        assert!(verdicts[..5].iter().all(|v| *v == Verdict::Allow));
+        assert!(verdicts[5..].iter().all(|v| *v == Verdict::Drop));
+
+        let later = start + Duration::from_secs(1);
+        assert_eq!(limiter.check(LIMIT, 20, later), Verdict::Allow);
+        assert_eq!(limiter.check(LIMIT, 20, later), Verdict::Allow);
+        assert_eq!(limiter.check(LIMIT, 20, later), Verdict::Drop);
+    }
+
+    #[test]
+    fn test_sustained_flood_disconnects() {
+        let start = Instant::now();
+        let mut limiter = PeerLimiter::new(LIMIT, start);
+        for _ in 0..5 {
+            limiter.check(LIMIT, 3, start);
+        }
+
+        assert_eq!(limiter.check(LIMIT, 3, start), Verdict::Drop);
+        assert_eq!(limiter.check(LIMIT, 3, start), Verdict::Drop);
+        assert_eq!(limiter.check(LIMIT, 3, start), Verdict::Disconnect);
+
+        let next_window = start + ABUSE_WINDOW;
+        limiter.check(LIMIT, 3, next_window);
+        assert_eq!(limiter.dropped(), 0);
+    }
+}
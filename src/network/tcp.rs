@@ -1,15 +1,55 @@
 use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::sync::Arc;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
 use tokio::net::{TcpListener, TcpStream};
-use tokio::sync::{mpsc, RwLock};
+use tokio::sync::{mpsc, watch, Mutex, RwLock};
+use tokio::task::JoinSet;
+use tokio::time::Instant;
+use tokio_rustls::{TlsAcceptor, TlsConnector};
 
+use super::auth::AuthTokenStore;
 use super::peer::PeerRegistry;
-use super::protocol::{NetworkCommand, NetworkMessage, RemoteSession, PROTOCOL_VERSION};
+use super::protocol::{
+    is_compatible_version, read_frame, NetworkCommand, NetworkMessage, RemoteSession,
+    DEFAULT_MAX_MESSAGE_BYTES, ERROR_VERSION_MISMATCH, PROTOCOL_VERSION,
+};
+use super::rate_limit::{RateLimitConfig, RateLimiter, Verdict};
+use super::tls::PinnedCertVerifier;
 
 pub type ConnectionId = String;
+
+/// A connection to a peer, plain TCP or TLS over TCP
+pub trait PeerStream: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> PeerStream for T {}
+
+type BoxedStream = Box<dyn PeerStream>;
 type MessageHandler = Arc<RwLock<Option<mpsc::Sender<(ConnectionId, NetworkMessage)>>>>;
+/// Set by `NetworkServer::stop` to the time by which replies must be sent
+type ShutdownSignal = watch::Receiver<Option<Instant>>;
+
+/// How long `NetworkServer::stop` waits for queued messages by default
+pub const DEFAULT_SHUTDOWN_GRACE: Duration = Duration::from_secs(5);
+
+/// How the connections open when `NetworkServer::stop` was called ended
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ShutdownSummary {
+    /// Sent everything queued for them and closed cleanly
+    pub drained: usize,
+    /// Still sending when the grace period ran out
+    pub force_closed: usize,
+}
+
+/// Aborts the task when dropped
+struct AbortOnDrop(tokio::task::JoinHandle<()>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
 
 #[derive(Debug)]
 pub struct Connection {
@@ -24,11 +64,18 @@ pub struct Connection {
 pub struct NetworkServer {
     port: u16,
     peer_registry: Arc<RwLock<PeerRegistry>>,
+    token_store: Option<Arc<RwLock<AuthTokenStore>>>,
     connections: Arc<RwLock<HashMap<ConnectionId, Connection>>>,
     sessions: Arc<RwLock<Vec<RemoteSession>>>,
     local_sessions: Arc<RwLock<Vec<RemoteSession>>>,
     running: Arc<RwLock<bool>>,
     message_handler: MessageHandler,
+    tls: Option<TlsAcceptor>,
+    rate_limiter: Arc<RateLimiter>,
+    max_message_bytes: usize,
+    grace_period: Duration,
+    shutdown: watch::Sender<Option<Instant>>,
+    connection_tasks: Arc<Mutex<JoinSet<bool>>>,
 }
 
 impl NetworkServer {
@@ -36,14 +83,54 @@ impl NetworkServer {
         Self {
             port,
             peer_registry,
+            token_store: None,
             connections: Arc::new(RwLock::new(HashMap::new())),
             sessions: Arc::new(RwLock::new(Vec::new())),
             local_sessions: Arc::new(RwLock::new(Vec::new())),
             running: Arc::new(RwLock::new(false)),
             message_handler: Arc::new(RwLock::new(None)),
+            tls: None,
+            rate_limiter: Arc::new(RateLimiter::default()),
+            max_message_bytes: DEFAULT_MAX_MESSAGE_BYTES,
+            grace_period: DEFAULT_SHUTDOWN_GRACE,
+            shutdown: watch::channel(None).0,
+            connection_tasks: Arc::new(Mutex::new(JoinSet::new())),
         }
     }
 
+    /// How long `stop` waits for messages already queued to go out
+    pub fn with_grace_period(mut self, grace_period: Duration) -> Self {
+        self.grace_period = grace_period;
+        self
+    }
+
+    /// Close connections that send a message larger than `max_bytes`
+    pub fn with_max_message_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_message_bytes = max_bytes;
+        self
+    }
+
+    /// Limit how fast each peer may send; over the limit, messages are
+    /// dropped, and a peer that keeps it up is disconnected
+    pub fn with_rate_limit(mut self, config: RateLimitConfig) -> Self {
+        self.rate_limiter = Arc::new(RateLimiter::new(config));
+        self
+    }
+
+    /// Check auth requests against `store`, so rotating or revoking a
+    /// peer's tokens takes effect. Without one, a peer authenticates with
+    /// the token recorded in the peer registry.
+    pub fn with_token_store(mut self, store: Arc<RwLock<AuthTokenStore>>) -> Self {
+        self.token_store = Some(store);
+        self
+    }
+
+    /// Accept only TLS connections, presenting the certificate in `config`
+    pub fn with_tls(mut self, config: Arc<rustls::ServerConfig>) -> Self {
+        self.tls = Some(TlsAcceptor::from(config));
+        self
+    }
+
     pub async fn start(&self) -> Result<(), String> {
         let addr = format!("0.0.0.0:{}", self.port);
         let listener = TcpListener::bind(&addr)
@@ -51,39 +138,74 @@ impl NetworkServer {
             .map_err(|e| format!("Failed to bind to {}: {}", addr, e))?;
 
         *self.running.write().await = true;
+        self.shutdown.send_replace(None);
 
         let connections = self.connections.clone();
         let peer_registry = self.peer_registry.clone();
+        let token_store = self.token_store.clone();
         let sessions = self.sessions.clone();
         let local_sessions = self.local_sessions.clone();
         let running = self.running.clone();
         let message_handler = self.message_handler.clone();
+        let tls = self.tls.clone();
+        let rate_limiter = self.rate_limiter.clone();
+        let max_message_bytes = self.max_message_bytes;
+        let mut shutdown = self.shutdown.subscribe();
+        let connection_tasks = self.connection_tasks.clone();
 
         tokio::spawn(async move {
             while *running.read().await {
-                match listener.accept().await {
+                let accepted = tokio::select! {
+                    accepted = listener.accept() => accepted,
+                    _ = shutdown.wait_for(Option::is_some) => break,
+                };
+                match accepted {
                     Ok((stream, addr)) => {
                         let conn_id = uuid::Uuid::new_v4().to_string();
                         let connections = connections.clone();
                         let peer_registry = peer_registry.clone();
+                        let token_store = token_store.clone();
                         let sessions = sessions.clone();
                         let local_sessions = local_sessions.clone();
                         let message_handler = message_handler.clone();
-
-                        tokio::spawn(async move {
-                            if let Err(e) = Self::handle_connection(
+                        let tls = tls.clone();
+                        let rate_limiter = rate_limiter.clone();
+                        let shutdown = shutdown.clone();
+
+                        let mut tasks = connection_tasks.lock().await;
+                        while tasks.try_join_next().is_some() {}
+                        tasks.spawn(async move {
+                            let stream: BoxedStream = match tls {
+                                Some(acceptor) => match acceptor.accept(stream).await {
+                                    Ok(stream) => Box::new(stream),
+                                    Err(e) => {
+                                        eprintln!("TLS handshake with {} failed: {}", addr, e);
+                                        return true;
+                                    }
+                                },
+                                None => Box::new(stream),
+                            };
+                            match Self::handle_connection(
                                 conn_id,
                                 stream,
                                 addr,
                                 connections,
                                 peer_registry,
+                                token_store,
                                 sessions,
                                 local_sessions,
                                 message_handler,
+                                rate_limiter,
+                                max_message_bytes,
+                                shutdown,
                             )
                             .await
                             {
-                                eprintln!("Connection error: {}", e);
+                                Ok(drained) => drained,
+                                Err(e) => {
+                                    eprintln!("Connection error: {}", e);
+                                    false
+                                }
                             }
                         });
                     }
@@ -99,8 +221,36 @@ impl NetworkServer {
         Ok(())
     }
 
-    pub async fn stop(&self) {
+    /// Stop accepting connections, then give open ones until the grace
+    /// period ends to send what is queued for them before closing them
+    pub async fn stop(&self) -> ShutdownSummary {
         *self.running.write().await = false;
+
+        // Signalled under the lock so a connection accepted meanwhile is
+        // still among the tasks waited for
+        let deadline = Instant::now() + self.grace_period;
+        let mut tasks = {
+            let mut tasks = self.connection_tasks.lock().await;
+            while tasks.try_join_next().is_some() {}
+            self.shutdown.send_replace(Some(deadline));
+            std::mem::take(&mut *tasks)
+        };
+
+        let mut summary = ShutdownSummary::default();
+        let mut count = |result: Result<bool, _>| match result {
+            Ok(true) => summary.drained += 1,
+            _ => summary.force_closed += 1,
+        };
+        while let Ok(Some(result)) = tokio::time::timeout_at(deadline, tasks.join_next()).await {
+            count(result);
+        }
+        // Whatever is left is stuck before a connection was set up, such as
+        // in a TLS handshake, or is past the deadline
+        tasks.abort_all();
+        while let Some(result) = tasks.join_next().await {
+            count(result);
+        }
+        summary
     }
 
     pub async fn is_running(&self) -> bool {
@@ -110,14 +260,18 @@ impl NetworkServer {
     #[allow(clippy::too_many_arguments)]
     async fn handle_connection(
         conn_id: ConnectionId,
-        stream: TcpStream,
+        stream: BoxedStream,
         addr: SocketAddr,
         connections: Arc<RwLock<HashMap<ConnectionId, Connection>>>,
         peer_registry: Arc<RwLock<PeerRegistry>>,
+        token_store: Option<Arc<RwLock<AuthTokenStore>>>,
         sessions: Arc<RwLock<Vec<RemoteSession>>>,
         local_sessions: Arc<RwLock<Vec<RemoteSession>>>,
         message_handler: MessageHandler,
-    ) -> Result<(), String> {
+        rate_limiter: Arc<RateLimiter>,
+        max_message_bytes: usize,
+        mut shutdown: ShutdownSignal,
+    ) -> Result<bool, String> {
         let (tx, mut rx) = mpsc::channel::<NetworkMessage>(32);
 
         let connection = Connection {
@@ -134,77 +288,126 @@ impl NetworkServer {
             .await
             .insert(conn_id.clone(), connection);
 
-        let stream = Arc::new(tokio::sync::Mutex::new(stream));
-        let stream_writer = stream.clone();
+        // Separate halves, so a read waiting for the next message never
+        // holds up a reply to the previous one
+        let (mut reader, mut writer) = tokio::io::split(stream);
 
-        let write_task = tokio::spawn(async move {
+        // Aborted when this task ends or is aborted by `stop`, so a writer
+        // stuck on a peer that stopped reading doesn't outlive it
+        let mut write_task = AbortOnDrop(tokio::spawn(async move {
             while let Some(msg) = rx.recv().await {
                 if let Ok(bytes) = msg.to_bytes() {
-                    let mut stream = stream_writer.lock().await;
-                    if stream.write_all(&bytes).await.is_err() {
-                        break;
+                    if writer.write_all(&bytes).await.is_err() {
+                        return;
                     }
                 }
             }
-        });
-
-        let mut buffer = vec![0u8; 65536];
-        let mut data = Vec::new();
+            let _ = writer.shutdown().await;
+        }));
 
         loop {
-            let n = {
-                let mut stream_guard = stream.lock().await;
-                match stream_guard.read(&mut buffer).await {
-                    Ok(0) => break,
-                    Ok(n) => n,
-                    Err(_) => break,
+            let frame = tokio::select! {
+                frame = read_frame(&mut reader, max_message_bytes) => Some(frame),
+                _ = shutdown.wait_for(Option::is_some) => None,
+            };
+            let Some(frame) = frame else {
+                // Queued behind anything still in flight, so the peer sees
+                // those first
+                if let Some(conn) = connections.read().await.get(&conn_id) {
+                    let _ = conn.sender.send(NetworkMessage::disconnect()).await;
+                }
+                break;
+            };
+            let payload = match frame {
+                Ok(Some(payload)) => payload,
+                Ok(None) => break,
+                Err(e) => {
+                    eprintln!("Closing connection {} from {}: {}", conn_id, addr, e);
+                    if let Some(code) = e.error_code() {
+                        if let Some(conn) = connections.read().await.get(&conn_id) {
+                            let _ = conn
+                                .sender
+                                .send(NetworkMessage::error(code, &e.to_string()))
+                                .await;
+                        }
+                    }
+                    break;
                 }
             };
 
-            data.extend_from_slice(&buffer[..n]);
-
-            while data.len() >= 4 {
-                let msg_len = u32::from_be_bytes([data[0], data[1], data[2], data[3]]) as usize;
-
-                if data.len() < 4 + msg_len {
+            let (key, authenticated) = match connections.read().await.get(&conn_id) {
+                Some(conn) if conn.authenticated => (
+                    conn.peer_id.clone().unwrap_or_else(|| conn_id.clone()),
+                    true,
+                ),
+                _ => (conn_id.clone(), false),
+            };
+            match rate_limiter.check(&key, authenticated) {
+                Verdict::Allow => {}
+                Verdict::Drop => continue,
+                Verdict::Disconnect => {
+                    eprintln!(
+                        "Closing connection {} from {}: kept exceeding the message rate limit",
+                        conn_id, addr
+                    );
                     break;
                 }
+            }
 
-                let msg_bytes: Vec<u8> = data.drain(..4 + msg_len).collect();
-
-                if let Ok(msg) = NetworkMessage::from_bytes(&msg_bytes) {
-                    let msg_clone = msg.clone();
-                    let response = Self::process_message(
-                        &conn_id,
-                        msg,
-                        connections.clone(),
-                        peer_registry.clone(),
-                        sessions.clone(),
-                        local_sessions.clone(),
-                    )
-                    .await;
-
-                    if let Some(response) = response {
-                        if let Some(conn) = connections.read().await.get(&conn_id) {
-                            let _ = conn.sender.send(response).await;
-                        }
+            if let Ok(msg) = NetworkMessage::from_payload(&payload) {
+                let msg_clone = msg.clone();
+                let response = Self::process_message(
+                    &conn_id,
+                    msg,
+                    connections.clone(),
+                    peer_registry.clone(),
+                    token_store.as_ref(),
+                    sessions.clone(),
+                    local_sessions.clone(),
+                )
+                .await;
+
+                let rejected = matches!(
+                    response,
+                    Some(NetworkMessage {
+                        command: NetworkCommand::Error {
+                            code: ERROR_VERSION_MISMATCH,
+                            ..
+                        },
+                        ..
+                    })
+                );
+                if let Some(response) = response {
+                    if let Some(conn) = connections.read().await.get(&conn_id) {
+                        let _ = conn.sender.send(response).await;
                     }
+                }
+                if rejected {
+                    break;
+                }
 
-                    if let Some(handler) = message_handler.read().await.as_ref() {
-                        let _ = handler.send((conn_id.clone(), msg_clone.clone())).await;
-                    }
+                if let Some(handler) = message_handler.read().await.as_ref() {
+                    let _ = handler.send((conn_id.clone(), msg_clone.clone())).await;
+                }
 
-                    if matches!(msg_clone.command, NetworkCommand::Disconnect) {
-                        break;
-                    }
+                if matches!(msg_clone.command, NetworkCommand::Disconnect) {
+                    break;
                 }
             }
         }
 
+        // Dropping the connection's sender lets the writer send what is
+        // queued, then close; on shutdown it has until the grace period ends
         connections.write().await.remove(&conn_id);
-        write_task.abort();
+        rate_limiter.forget(&conn_id);
+        let deadline = shutdown
+            .borrow()
+            .unwrap_or_else(|| Instant::now() + Duration::from_secs(1));
+        let drained = tokio::time::timeout_at(deadline, &mut write_task.0)
+            .await
+            .is_ok();
 
-        Ok(())
+        Ok(drained)
     }
 
     async fn process_message(
@@ -212,6 +415,7 @@ impl NetworkServer {
         msg: NetworkMessage,
         connections: Arc<RwLock<HashMap<ConnectionId, Connection>>>,
         peer_registry: Arc<RwLock<PeerRegistry>>,
+        token_store: Option<&Arc<RwLock<AuthTokenStore>>>,
         sessions: Arc<RwLock<Vec<RemoteSession>>>,
         local_sessions: Arc<RwLock<Vec<RemoteSession>>>,
     ) -> Option<NetworkMessage> {
@@ -221,8 +425,18 @@ impl NetworkServer {
             NetworkCommand::Handshake {
                 peer_id,
                 peer_name,
-                version: _,
+                version,
             } => {
+                if !is_compatible_version(&version) {
+                    return Some(NetworkMessage::error(
+                        ERROR_VERSION_MISMATCH,
+                        &format!(
+                            "Protocol version {} is not supported; this hub speaks {}",
+                            version, PROTOCOL_VERSION
+                        ),
+                    ));
+                }
+
                 let registry = peer_registry.read().await;
                 let local_id = registry.local_peer_id.clone();
                 let local_name = registry.local_peer_name.clone();
@@ -241,27 +455,38 @@ impl NetworkServer {
             }
 
             NetworkCommand::AuthRequest { token } => {
-                let registry = peer_registry.read().await;
+                let peer_id = connections
+                    .read()
+                    .await
+                    .get(conn_id)
+                    .and_then(|conn| conn.peer_id.clone());
+                let Some(peer_id) = peer_id else {
+                    return Some(NetworkMessage::auth_response(false, "Handshake first"));
+                };
 
-                let mut authorized = false;
-                if let Some(conn) = connections.read().await.get(conn_id) {
-                    if let Some(ref peer_id) = conn.peer_id {
-                        if let Some(peer) = registry.get_peer(peer_id) {
-                            if peer.auth_token.as_deref() == Some(&token) {
-                                authorized = true;
-                            }
+                let verdict = match token_store {
+                    Some(store) => store
+                        .write()
+                        .await
+                        .validate_token(&token, &peer_id)
+                        .map_err(|e| e.to_string()),
+                    None => {
+                        let registry = peer_registry.read().await;
+                        match registry.get_peer(&peer_id) {
+                            Some(peer) if peer.auth_token.as_deref() == Some(&token) => Ok(()),
+                            _ => Err("Invalid token".to_string()),
                         }
                     }
-                }
-                drop(registry);
+                };
 
-                if authorized {
-                    if let Some(conn) = connections.write().await.get_mut(conn_id) {
-                        conn.authenticated = true;
+                match verdict {
+                    Ok(()) => {
+                        if let Some(conn) = connections.write().await.get_mut(conn_id) {
+                            conn.authenticated = true;
+                        }
+                        Some(NetworkMessage::auth_response(true, "Authorized"))
                     }
-                    Some(NetworkMessage::auth_response(true, "Authorized"))
-                } else {
-                    Some(NetworkMessage::auth_response(false, "Invalid token"))
+                    Err(reason) => Some(NetworkMessage::auth_response(false, &reason)),
                 }
             }
 
@@ -381,27 +606,85 @@ impl NetworkServer {
 
 pub struct NetworkClient {
     peer_registry: Arc<RwLock<PeerRegistry>>,
+    tls: bool,
+    max_message_bytes: usize,
 }
 
 impl NetworkClient {
     pub fn new(peer_registry: Arc<RwLock<PeerRegistry>>) -> Self {
-        Self { peer_registry }
+        Self {
+            peer_registry,
+            tls: false,
+            max_message_bytes: DEFAULT_MAX_MESSAGE_BYTES,
+        }
+    }
+
+    /// Refuse replies larger than `max_bytes`
+    pub fn with_max_message_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_message_bytes = max_bytes;
+        self
+    }
+
+    /// Connect over TLS, checking the peer's certificate against the
+    /// fingerprint pinned for its address
+    pub fn with_tls(mut self, enabled: bool) -> Self {
+        self.tls = enabled;
+        self
     }
 
     pub async fn connect(&self, address: &str, port: u16) -> Result<ClientConnection, String> {
         let addr = format!("{}:{}", address, port);
-        let stream = TcpStream::connect(&addr)
+        let tcp = TcpStream::connect(&addr)
             .await
             .map_err(|e| format!("Failed to connect to {}: {}", addr, e))?;
 
         let registry = self.peer_registry.read().await;
         let local_id = registry.local_peer_id.clone();
         let local_name = registry.local_peer_name.clone();
+        let pinned = registry
+            .get_peer_by_address(address, port)
+            .map(|peer| (peer.id.clone(), peer.cert_fingerprint.clone()));
         drop(registry);
 
+        let (stream, fingerprint): (BoxedStream, Option<String>) = if self.tls {
+            let pin = pinned.as_ref().and_then(|(_, pin)| pin.as_deref());
+            let verifier = Arc::new(PinnedCertVerifier::new(pin));
+            let server_name = rustls::pki_types::ServerName::try_from(address.to_string())
+                .map_err(|e| format!("Invalid peer address {}: {}", address, e))?;
+            let stream = TlsConnector::from(verifier.client_config())
+                .connect(server_name, tcp)
+                .await
+                .map_err(|e| {
+                    if verifier.mismatched() {
+                        let peer_id = pinned.as_ref().map_or("", |(id, _)| id.as_str());
+                        pin_mismatch_error(&addr, peer_id)
+                    } else {
+                        format!("TLS handshake with {} failed: {}", addr, e)
+                    }
+                })?;
+            (Box::new(stream), verifier.presented())
+        } else {
+            (Box::new(tcp), None)
+        };
+
         let mut client = ClientConnection::new(stream, local_id, local_name);
+        client.peer_fingerprint = fingerprint;
+        client.max_message_bytes = self.max_message_bytes;
         client.handshake().await?;
 
+        // The address may have moved; the pin belongs to the peer id
+        if let (Some(peer_id), Some(presented)) =
+            (client.remote_peer_id(), client.peer_fingerprint())
+        {
+            let registry = self.peer_registry.read().await;
+            let pin = registry
+                .get_peer(peer_id)
+                .and_then(|peer| peer.cert_fingerprint.as_deref());
+            if pin.is_some_and(|pin| pin != presented) {
+                return Err(pin_mismatch_error(&addr, peer_id));
+            }
+        }
+
         Ok(client)
     }
 
@@ -413,28 +696,54 @@ impl NetworkClient {
     ) -> Result<ClientConnection, String> {
         let mut client = self.connect(address, port).await?;
         client.authenticate(token).await?;
+
+        if let (Some(peer_id), Some(fingerprint)) =
+            (client.remote_peer_id(), client.peer_fingerprint())
+        {
+            self.peer_registry.write().await.pin_certificate(
+                peer_id,
+                client.remote_peer_name().unwrap_or("Unknown"),
+                address,
+                port,
+                fingerprint,
+            )?;
+        }
         Ok(client)
     }
 }
 
+fn pin_mismatch_error(addr: &str, peer_id: &str) -> String {
+    format!(
+        "Certificate of {} does not match the one pinned when it was approved; \
+         refusing to connect. If the peer rotated its certificate, run: \
+         sena peer reauthorize {}",
+        addr,
+        peer_id.get(..8).unwrap_or(peer_id)
+    )
+}
+
 pub struct ClientConnection {
-    stream: TcpStream,
+    stream: BoxedStream,
     local_peer_id: String,
     local_peer_name: String,
     remote_peer_id: Option<String>,
     remote_peer_name: Option<String>,
+    peer_fingerprint: Option<String>,
     authenticated: bool,
+    max_message_bytes: usize,
 }
 
 impl ClientConnection {
-    fn new(stream: TcpStream, local_peer_id: String, local_peer_name: String) -> Self {
+    fn new(stream: BoxedStream, local_peer_id: String, local_peer_name: String) -> Self {
         Self {
             stream,
             local_peer_id,
             local_peer_name,
             remote_peer_id: None,
             remote_peer_name: None,
+            peer_fingerprint: None,
             authenticated: false,
+            max_message_bytes: DEFAULT_MAX_MESSAGE_BYTES,
         }
     }
 
@@ -447,23 +756,13 @@ impl ClientConnection {
     }
 
     async fn receive(&mut self) -> Result<NetworkMessage, String> {
-        let mut len_buf = [0u8; 4];
-        self.stream
-            .read_exact(&mut len_buf)
+        match read_frame(&mut self.stream, self.max_message_bytes)
             .await
-            .map_err(|e| format!("Failed to read length: {}", e))?;
-
-        let len = u32::from_be_bytes(len_buf) as usize;
-        let mut msg_buf = vec![0u8; len];
-        self.stream
-            .read_exact(&mut msg_buf)
-            .await
-            .map_err(|e| format!("Failed to read message: {}", e))?;
-
-        let mut full_buf = len_buf.to_vec();
-        full_buf.extend(msg_buf);
-
-        NetworkMessage::from_bytes(&full_buf)
+            .map_err(|e| e.to_string())?
+        {
+            Some(payload) => NetworkMessage::from_payload(&payload),
+            None => Err("Connection closed by peer".to_string()),
+        }
     }
 
     pub async fn handshake(&mut self) -> Result<(), String> {
@@ -472,17 +771,26 @@ impl ClientConnection {
         self.send(msg).await?;
 
         let response = self.receive().await?;
-        if let NetworkCommand::HandshakeAck {
-            peer_id,
-            peer_name,
-            version: _,
-        } = response.command
-        {
-            self.remote_peer_id = Some(peer_id);
-            self.remote_peer_name = Some(peer_name);
-            Ok(())
-        } else {
-            Err("Invalid handshake response".to_string())
+        match response.command {
+            NetworkCommand::HandshakeAck {
+                peer_id,
+                peer_name,
+                version,
+            } => {
+                if !is_compatible_version(&version) {
+                    return Err(format!(
+                        "Peer speaks protocol version {}, which is not compatible with {}",
+                        version, PROTOCOL_VERSION
+                    ));
+                }
+                self.remote_peer_id = Some(peer_id);
+                self.remote_peer_name = Some(peer_name);
+                Ok(())
+            }
+            NetworkCommand::Error { message, .. } => {
+                Err(format!("Peer rejected the handshake: {}", message))
+            }
+            _ => Err("Invalid handshake response".to_string()),
         }
     }
 
@@ -542,6 +850,11 @@ impl ClientConnection {
         self.remote_peer_name.as_deref()
     }
 
+    /// Fingerprint of the certificate the peer presented, on TLS connections
+    pub fn peer_fingerprint(&self) -> Option<&str> {
+        self.peer_fingerprint.as_deref()
+    }
+
     pub fn is_authenticated(&self) -> bool {
         self.authenticated
     }
@@ -549,7 +862,9 @@ impl ClientConnection {
 
 #[cfg(test)]
 mod tests {
+    use super::super::protocol::ERROR_MESSAGE_TOO_LARGE;
     use super::*;
+    use tokio::io::AsyncReadExt;
 
     #[tokio::test]
     async fn test_network_server_creation() {
@@ -559,4 +874,322 @@ mod tests {
         let server = NetworkServer::new(0, registry);
         assert!(!server.is_running().await);
     }
+
+    #[tokio::test]
+    async fn test_burst_past_rate_limit_is_throttled() {
+        let dir = std::env::temp_dir().join(format!("sena_test_rate_{}", uuid::Uuid::new_v4()));
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let config = RateLimitConfig {
+            unauthenticated: super::super::RateLimit {
+                per_second: 20.0,
+                burst: 3,
+            },
+            disconnect_after: 50,
+            ..Default::default()
+        };
+        let registry = Arc::new(RwLock::new(PeerRegistry::new(dir.join("server.json"))));
+        let server = NetworkServer::new(port, registry).with_rate_limit(config);
+        server.start().await.unwrap();
+
+        let client_registry = Arc::new(RwLock::new(PeerRegistry::new(dir.join("client.json"))));
+        let mut client = NetworkClient::new(client_registry)
+            .connect("127.0.0.1", port)
+            .await
+            .unwrap();
+
+        // The handshake used one token, so at least 2 of these 10 pings get
+        // through; a slow run may refill a few more, but never all of them
+        for _ in 0..10 {
+            client.send(NetworkMessage::ping()).await.unwrap();
+        }
+        let mut pongs = 0;
+        while let Ok(Ok(_)) =
+            tokio::time::timeout(std::time::Duration::from_millis(200), client.receive()).await
+        {
+            pongs += 1;
+        }
+        assert!((2..10).contains(&pongs), "{} of 10 pings answered", pongs);
+
+        // Throttled, not disconnected: once tokens refill, pings work again
+        assert!(client.ping().await.unwrap());
+
+        server.stop().await;
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    async fn start_plain_server(
+        dir: &std::path::Path,
+        configure: impl FnOnce(NetworkServer) -> NetworkServer,
+    ) -> (NetworkServer, u16) {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let registry = Arc::new(RwLock::new(PeerRegistry::new(dir.join("server.json"))));
+        let server = configure(NetworkServer::new(port, registry));
+        server.start().await.unwrap();
+        (server, port)
+    }
+
+    #[tokio::test]
+    async fn test_auth_goes_through_token_store() {
+        let dir = std::env::temp_dir().join(format!("sena_test_tokens_{}", uuid::Uuid::new_v4()));
+        let store = Arc::new(RwLock::new(AuthTokenStore::new(dir.join("tokens.json"))));
+        let (server, port) = start_plain_server(&dir, |s| s.with_token_store(store.clone())).await;
+
+        let client_registry = PeerRegistry::new(dir.join("client.json"));
+        let client_id = client_registry.local_peer_id.clone();
+        let client = NetworkClient::new(Arc::new(RwLock::new(client_registry)));
+        let token = store
+            .write()
+            .await
+            .create_session_token(&client_id, 300)
+            .unwrap();
+
+        // Reusable until revoked
+        for _ in 0..2 {
+            let connection = client
+                .connect_and_auth("127.0.0.1", port, &token.token)
+                .await
+                .unwrap();
+            assert!(connection.is_authenticated());
+        }
+
+        store.write().await.revoke(&client_id).unwrap();
+        let err = client
+            .connect_and_auth("127.0.0.1", port, &token.token)
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(err, "Token has been revoked");
+
+        server.stop().await;
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_oversized_message_closes_connection() {
+        let dir = std::env::temp_dir().join(format!("sena_test_size_{}", uuid::Uuid::new_v4()));
+        let (server, port) = start_plain_server(&dir, |s| s.with_max_message_bytes(1024)).await;
+
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+        // Announce 4 GiB and send no payload: the server must refuse on the
+        // length alone rather than wait to buffer the message
+        stream.write_all(&[0xFF, 0xFF, 0xFF, 0xFF]).await.unwrap();
+
+        let reply = read_frame(&mut stream, 1024).await.unwrap().unwrap();
+        let reply = NetworkMessage::from_payload(&reply).unwrap();
+        assert!(matches!(
+            reply.command,
+            NetworkCommand::Error {
+                code: ERROR_MESSAGE_TOO_LARGE,
+                ..
+            }
+        ));
+        assert_eq!(read_frame(&mut stream, 1024).await.unwrap(), None);
+        server.stop().await;
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_incompatible_version_is_rejected() {
+        let dir = std::env::temp_dir().join(format!("sena_test_version_{}", uuid::Uuid::new_v4()));
+        let (server, port) = start_plain_server(&dir, |s| s).await;
+
+        let stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+        let mut client = ClientConnection::new(Box::new(stream), "old".into(), "Old Hub".into());
+        client
+            .send(NetworkMessage::handshake("old", "Old Hub", "1.0"))
+            .await
+            .unwrap();
+
+        match client.receive().await.unwrap().command {
+            NetworkCommand::Error { code, message } => {
+                assert_eq!(code, ERROR_VERSION_MISMATCH);
+                assert!(message.contains("1.0 is not supported"));
+            }
+            other => panic!("expected a version error, got {:?}", other),
+        }
+        assert!(client.receive().await.is_err());
+        server.stop().await;
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_message_sent_before_stop_is_drained() {
+        let dir = std::env::temp_dir().join(format!("sena_test_drain_{}", uuid::Uuid::new_v4()));
+        let (server, port) = start_plain_server(&dir, |s| s).await;
+        let registry = Arc::new(RwLock::new(PeerRegistry::new(dir.join("client.json"))));
+        let mut client = NetworkClient::new(registry)
+            .connect("127.0.0.1", port)
+            .await
+            .unwrap();
+
+        // Larger than the socket buffers, so it is still being written when
+        // the server is told to stop
+        let content = "x".repeat(512 * 1024);
+        let (conn_id, _, _) = server.get_connections().await.remove(0);
+        server
+            .send_to_connection(&conn_id, NetworkMessage::broadcast("hub", "s1", &content))
+            .await
+            .unwrap();
+        let server = Arc::new(server);
+        let stopping = tokio::spawn({
+            let server = server.clone();
+            async move { server.stop().await }
+        });
+
+        match client.receive().await.unwrap().command {
+            NetworkCommand::Broadcast {
+                content: received, ..
+            } => assert_eq!(received, content),
+            other => panic!("expected the broadcast, got {:?}", other),
+        }
+        assert!(matches!(
+            client.receive().await.unwrap().command,
+            NetworkCommand::Disconnect
+        ));
+        assert_eq!(
+            stopping.await.unwrap(),
+            ShutdownSummary {
+                drained: 1,
+                force_closed: 0
+            }
+        );
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_stop_force_closes_after_grace_period() {
+        let dir = std::env::temp_dir().join(format!("sena_test_force_{}", uuid::Uuid::new_v4()));
+        let (server, port) =
+            start_plain_server(&dir, |s| s.with_grace_period(Duration::from_millis(200))).await;
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+        let handshake = NetworkMessage::handshake("slow", "Slow Hub", PROTOCOL_VERSION);
+        stream
+            .write_all(&handshake.to_bytes().unwrap())
+            .await
+            .unwrap();
+        read_frame(&mut stream, DEFAULT_MAX_MESSAGE_BYTES)
+            .await
+            .unwrap()
+            .unwrap();
+
+        // A peer that stops reading can't hold up shutdown, and the writer
+        // stuck on it is stopped rather than left running
+        let content = "x".repeat(32 * 1024 * 1024);
+        let (conn_id, _, _) = server.get_connections().await.remove(0);
+        server
+            .send_to_connection(&conn_id, NetworkMessage::broadcast("hub", "s1", &content))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            server.stop().await,
+            ShutdownSummary {
+                drained: 0,
+                force_closed: 1
+            }
+        );
+
+        // Reading now gets only part of the broadcast before the socket
+        // closes; a leaked writer would still send all of it
+        let mut received = 0;
+        let mut buf = vec![0u8; 64 * 1024];
+        while let Ok(n) = stream.read(&mut buf).await {
+            if n == 0 {
+                break;
+            }
+            received += n;
+        }
+        assert!(received < content.len());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_changed_certificate_is_rejected() {
+        let dir = std::env::temp_dir().join(format!("sena_test_pin_{}", uuid::Uuid::new_v4()));
+        let tls = super::super::TlsConfig::new(dir.join("tls"));
+        tls.generate("localhost").unwrap();
+        let actual = tls.get_certificate_fingerprint().unwrap();
+
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let server_registry = Arc::new(RwLock::new(PeerRegistry::new(dir.join("server.json"))));
+        let server =
+            NetworkServer::new(port, server_registry).with_tls(tls.load_server_config().unwrap());
+        server.start().await.unwrap();
+
+        let mut registry = PeerRegistry::new(dir.join("client.json"));
+        let mut peer = super::super::Peer::new("hub-1", "Hub", "127.0.0.1", port);
+        peer.cert_fingerprint = Some(actual.clone());
+        registry.add_peer(peer).unwrap();
+        let registry = Arc::new(RwLock::new(registry));
+        let client = NetworkClient::new(registry.clone()).with_tls(true);
+
+        let mut connection = client.connect("127.0.0.1", port).await.unwrap();
+        assert_eq!(connection.peer_fingerprint(), Some(actual.as_str()));
+        assert!(connection.ping().await.unwrap());
+
+        registry
+            .write()
+            .await
+            .reauthorize_peer("hub-1", Some(&"0".repeat(64)))
+            .unwrap();
+        let err = client.connect("127.0.0.1", port).await.err().unwrap();
+        assert!(err.contains("does not match"), "{}", err);
+        assert!(err.contains("sena peer reauthorize hub-1"), "{}", err);
+
+        server.stop().await;
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_known_peer_at_new_address_keeps_its_pin() {
+        let dir = std::env::temp_dir().join(format!("sena_test_moved_{}", uuid::Uuid::new_v4()));
+        let tls = super::super::TlsConfig::new(dir.join("tls"));
+        tls.generate("localhost").unwrap();
+
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let server_registry = PeerRegistry::new(dir.join("server.json"));
+        let server_id = server_registry.local_peer_id.clone();
+        let server = NetworkServer::new(port, Arc::new(RwLock::new(server_registry)))
+            .with_tls(tls.load_server_config().unwrap());
+        server.start().await.unwrap();
+
+        // Approved at another address with a different certificate
+        let mut registry = PeerRegistry::new(dir.join("client.json"));
+        let mut peer = super::super::Peer::new(&server_id, "Hub", "10.0.0.9", 9876);
+        peer.cert_fingerprint = Some("0".repeat(64));
+        registry.add_peer(peer).unwrap();
+        let registry = Arc::new(RwLock::new(registry));
+        let client = NetworkClient::new(registry.clone()).with_tls(true);
+
+        let err = client.connect("127.0.0.1", port).await.err().unwrap();
+        assert!(err.contains("does not match"), "{}", err);
+        assert_eq!(
+            registry
+                .read()
+                .await
+                .get_peer(&server_id)
+                .unwrap()
+                .cert_fingerprint,
+            Some("0".repeat(64))
+        );
+
+        server.stop().await;
+        let _ = std::fs::remove_dir_all(dir);
+    }
 }
@@ -1,10 +1,12 @@
 use std::fs;
 use std::io::BufReader;
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use rcgen::generate_simple_self_signed;
+use rustls::crypto::WebPkiSupportedAlgorithms;
 use rustls::pki_types::CertificateDer;
+use sha2::{Digest, Sha256};
 
 pub struct TlsConfig {
     pub cert_path: PathBuf,
@@ -113,12 +115,113 @@ impl TlsConfig {
             .next()
             .ok_or_else(|| "No certificate found".to_string())?;
 
-        use sha2::{Digest, Sha256};
-        let mut hasher = Sha256::new();
-        hasher.update(cert.as_ref());
-        let result = hasher.finalize();
+        Ok(fingerprint(&cert))
+    }
+}
+
+/// Hex SHA-256 of a DER certificate, as shown by `sena network info`
+pub fn fingerprint(cert: &CertificateDer<'_>) -> String {
+    hex::encode(Sha256::digest(cert.as_ref()))
+}
+
+/// Lowercase hex without separators, so `AB:CD` and `abcd` compare equal
+pub fn normalize_fingerprint(fingerprint: &str) -> String {
+    fingerprint
+        .chars()
+        .filter(char::is_ascii_hexdigit)
+        .collect::<String>()
+        .to_lowercase()
+}
+
+/// Server certificate check for hub connections. Self-signed certificates
+/// can't be checked against a CA, so a peer's certificate is trusted on
+/// first use and its fingerprint pinned once the peer is approved; after
+/// that, any other certificate is rejected during the handshake.
+#[derive(Debug)]
+pub struct PinnedCertVerifier {
+    pinned: Option<String>,
+    presented: Mutex<Option<String>>,
+    algorithms: WebPkiSupportedAlgorithms,
+}
+
+impl PinnedCertVerifier {
+    /// Accept only the certificate with fingerprint `pinned`, or any
+    /// certificate when there is no pin yet
+    pub fn new(pinned: Option<&str>) -> Self {
+        Self {
+            pinned: pinned.map(normalize_fingerprint),
+            presented: Mutex::new(None),
+            algorithms: rustls::crypto::ring::default_provider().signature_verification_algorithms,
+        }
+    }
+
+    pub fn client_config(self: &Arc<Self>) -> Arc<rustls::ClientConfig> {
+        let verifier: Arc<dyn rustls::client::danger::ServerCertVerifier> = self.clone();
+        Arc::new(
+            rustls::ClientConfig::builder()
+                .dangerous()
+                .with_custom_certificate_verifier(verifier)
+                .with_no_client_auth(),
+        )
+    }
+
+    /// Fingerprint of the certificate the server presented, once the
+    /// handshake has reached it
+    pub fn presented(&self) -> Option<String> {
+        self.presented.lock().ok()?.clone()
+    }
+
+    /// Whether the server presented a certificate other than the pinned one
+    pub fn mismatched(&self) -> bool {
+        match (&self.pinned, self.presented()) {
+            (Some(pinned), Some(presented)) => *pinned != presented,
+            _ => false,
+        }
+    }
+}
+
+impl rustls::client::danger::ServerCertVerifier for PinnedCertVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &rustls::pki_types::ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: rustls::pki_types::UnixTime,
+    ) -> Result<rustls::client::danger::ServerCertVerified, rustls::Error> {
+        let presented = fingerprint(end_entity);
+        if let Ok(mut slot) = self.presented.lock() {
+            *slot = Some(presented.clone());
+        }
+        match &self.pinned {
+            Some(pinned) if *pinned != presented => Err(rustls::Error::General(format!(
+                "certificate fingerprint {} does not match pinned {}",
+                presented, pinned
+            ))),
+            _ => Ok(rustls::client::danger::ServerCertVerified::assertion()),
+        }
+    }
 
-        Ok(hex::encode(result))
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &rustls::DigitallySignedStruct,
+    ) -> Result<rustls::client::danger::HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(message, cert, dss, &self.algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &rustls::DigitallySignedStruct,
+    ) -> Result<rustls::client::danger::HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(message, cert, dss, &self.algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<rustls::SignatureScheme> {
+        self.algorithms.supported_schemes()
     }
 }
 
@@ -202,4 +305,10 @@ mod tests {
 
         let _ = fs::remove_dir_all(dir);
     }
+
+    #[test]
+    fn test_normalize_fingerprint() {
+        assert_eq!(normalize_fingerprint("AB:cd:0F"), "abcd0f");
+        assert!(!PinnedCertVerifier::new(Some("ab:cd")).mismatched());
+    }
 }
@@ -0,0 +1,341 @@
+//! Unified Diff Renderer
+//!
+//! Renders `GitDiff` hunks with line numbers, colored additions and
+//! deletions, and word-level highlighting of the parts of a changed line
+//! that actually differ. Colors and box characters follow the output theme.
+
+use super::progress::ansi;
+use super::theme::Theme;
+use crate::git::DiffHunk;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LineKind {
+    Context,
+    Removed,
+    Added,
+    /// `\ No newline at end of file`
+    Marker,
+}
+
+#[derive(Debug, Clone)]
+struct DiffLine<'a> {
+    kind: LineKind,
+    text: &'a str,
+    old: Option<usize>,
+    new: Option<usize>,
+    /// Byte range of `text` that differs from the paired line
+    changed: Option<(usize, usize)>,
+}
+
+/// Renders diff hunks for the terminal
+#[derive(Debug, Clone)]
+pub struct DiffRenderer {
+    theme: Theme,
+    word_diff: bool,
+}
+
+impl DiffRenderer {
+    pub fn new() -> Self {
+        Self {
+            theme: Theme::current(),
+            word_diff: true,
+        }
+    }
+
+    /// Style the diff with `theme` instead of the configured one
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Drop color codes, for output that is piped or redirected
+    pub fn plain(mut self) -> Self {
+        self.theme.color = false;
+        self
+    }
+
+    /// Highlight the changed words inside replaced lines
+    pub fn word_diff(mut self, enabled: bool) -> Self {
+        self.word_diff = enabled;
+        self
+    }
+
+    pub fn render(&self, hunks: &[DiffHunk]) -> String {
+        let width = hunks
+            .iter()
+            .map(|h| (h.old_start + h.old_count).max(h.new_start + h.new_count))
+            .max()
+            .unwrap_or(0)
+            .to_string()
+            .len();
+
+        let mut out = String::new();
+        let mut current_file: Option<&str> = None;
+        for hunk in hunks {
+            if current_file != Some(hunk.file.as_str()) {
+                if current_file.is_some() {
+                    out.push('\n');
+                }
+                let rule = if self.theme.unicode {
+                    "━━━"
+                } else {
+                    "==="
+                };
+                out.push_str(&self.style(ansi::BOLD, &format!("{} {}", rule, hunk.file)));
+                out.push('\n');
+                current_file = Some(&hunk.file);
+            }
+
+            out.push_str(&self.theme.accent(&format!(
+                "@@ -{},{} +{},{} @@",
+                hunk.old_start, hunk.old_count, hunk.new_start, hunk.new_count
+            )));
+            out.push('\n');
+
+            for line in self.lines(hunk) {
+                out.push_str(&self.render_line(&line, width));
+                out.push('\n');
+            }
+        }
+        out
+    }
+
+    fn lines<'a>(&self, hunk: &'a DiffHunk) -> Vec<DiffLine<'a>> {
+        let mut old = hunk.old_start;
+        let mut new = hunk.new_start;
+        let mut lines: Vec<DiffLine<'a>> = hunk
+            .content
+            .lines()
+            .map(|raw| {
+                let (kind, text) = match raw.chars().next() {
+                    Some('-') => (LineKind::Removed, &raw[1..]),
+                    Some('+') => (LineKind::Added, &raw[1..]),
+                    Some('\\') => (LineKind::Marker, raw),
+                    Some(' ') => (LineKind::Context, &raw[1..]),
+                    _ => (LineKind::Context, raw),
+                };
+                let (old_no, new_no) = match kind {
+                    LineKind::Context => (Some(old), Some(new)),
+                    LineKind::Removed => (Some(old), None),
+                    LineKind::Added => (None, Some(new)),
+                    LineKind::Marker => (None, None),
+                };
+                old += usize::from(old_no.is_some());
+                new += usize::from(new_no.is_some());
+                DiffLine {
+                    kind,
+                    text,
+                    old: old_no,
+                    new: new_no,
+                    changed: None,
+                }
+            })
+            .collect();
+
+        if self.word_diff && self.theme.color {
+            mark_changed_words(&mut lines);
+        }
+        lines
+    }
+
+    fn render_line(&self, line: &DiffLine<'_>, width: usize) -> String {
+        let number = |n: Option<usize>| match n {
+            Some(n) => format!("{:>width$}", n, width = width),
+            None => " ".repeat(width),
+        };
+        let separator = if self.theme.unicode { "│" } else { "|" };
+        let gutter = self.style(
+            ansi::DIM,
+            &format!("{} {} {}", number(line.old), number(line.new), separator),
+        );
+
+        let body = match line.kind {
+            LineKind::Context => self.style(ansi::DIM, &format!(" {}", line.text)),
+            LineKind::Marker => self.style(ansi::DIM, line.text),
+            LineKind::Removed | LineKind::Added => {
+                let (sign, color) = if line.kind == LineKind::Removed {
+                    ('-', self.theme.critical.code())
+                } else {
+                    ('+', self.theme.success.code())
+                };
+                match line.changed {
+                    Some((start, end)) => {
+                        let highlighted = format!("{}{}", ansi::REVERSE, color);
+                        [
+                            self.style(color, &format!("{}{}", sign, &line.text[..start])),
+                            self.style(&highlighted, &line.text[start..end]),
+                            self.style(color, &line.text[end..]),
+                        ]
+                        .concat()
+                    }
+                    None => self.style(color, &format!("{}{}", sign, line.text)),
+                }
+            }
+        };
+        format!("{} {}", gutter, body)
+    }
+
+    fn style(&self, code: &str, text: &str) -> String {
+        if self.theme.color && !text.is_empty() {
+            format!("{}{}{}", code, text, ansi::RESET)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+impl Default for DiffRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Pair each run of removed lines with the added lines that follow it and
+/// mark the span between their common prefix and suffix
+fn mark_changed_words(lines: &mut [DiffLine<'_>]) {
+    let mut i = 0;
+    while i < lines.len() {
+        if lines[i].kind != LineKind::Removed {
+            i += 1;
+            continue;
+        }
+        let removed_start = i;
+        while i < lines.len() && lines[i].kind == LineKind::Removed {
+            i += 1;
+        }
+        let added_start = i;
+        while i < lines.len() && lines[i].kind == LineKind::Added {
+            i += 1;
+        }
+
+        let pairs = (added_start - removed_start).min(i - added_start);
+        for offset in 0..pairs {
+            let (old, new) = (removed_start + offset, added_start + offset);
+            if let Some((old_span, new_span)) = changed_spans(lines[old].text, lines[new].text) {
+                lines[old].changed = Some(old_span);
+                lines[new].changed = Some(new_span);
+            }
+        }
+    }
+}
+
+/// Byte spans of `old` and `new` outside their common leading and trailing
+/// words, or `None` when the lines share nothing
+fn changed_spans(old: &str, new: &str) -> Option<((usize, usize), (usize, usize))> {
+    let old_words = words(old);
+    let new_words = words(new);
+    let max_common = old_words.len().min(new_words.len());
+
+    let prefix = old_words
+        .iter()
+        .zip(&new_words)
+        .take_while(|(a, b)| a.1 == b.1)
+        .count();
+    let suffix = old_words
+        .iter()
+        .rev()
+        .zip(new_words.iter().rev())
+        .take(max_common - prefix)
+        .take_while(|(a, b)| a.1 == b.1)
+        .count();
+    if prefix == 0 && suffix == 0 {
+        return None;
+    }
+
+    let span = |line: &str, words: &[(usize, &str)]| {
+        let start = words.get(prefix).map_or(line.len(), |w| w.0);
+        let end = words
+            .get(words.len() - suffix)
+            .map_or(line.len(), |w| w.0)
+            .max(start);
+        (start, end)
+    };
+    Some((span(old, &old_words), span(new, &new_words)))
+}
+
+/// Runs of word characters, and every other character on its own, with
+/// their byte offsets
+fn words(line: &str) -> Vec<(usize, &str)> {
+    let mut words = Vec::new();
+    let mut start: Option<usize> = None;
+    for (i, c) in line.char_indices() {
+        let is_word = c.is_alphanumeric() || c == '_';
+        if let Some(s) = start {
+            if !is_word {
+                words.push((s, &line[s..i]));
+                start = None;
+            }
+        }
+        if is_word {
+            start.get_or_insert(i);
+        } else {
+            words.push((i, &line[i..i + c.len_utf8()]));
+        }
+    }
+    if let Some(s) = start {
+        words.push((s, &line[s..]));
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hunk() -> DiffHunk {
+        DiffHunk {
+            file: "src/main.rs".to_string(),
+            old_start: 9,
+            old_count: 3,
+            new_start: 9,
+            new_count: 3,
+            content: " fn main() {\n-    let name = \"world\";\n+    let name = \"sena\";\n }\n"
+                .to_string(),
+        }
+    }
+
+    #[test]
+    fn test_plain_render_snapshot() {
+        let rendered = DiffRenderer::new().theme(Theme::ascii()).render(&[hunk()]);
+        assert_eq!(
+            rendered,
+            "=== src/main.rs\n\
+             @@ -9,3 +9,3 @@\n\
+             \x209  9 |  fn main() {\n\
+             10    | -    let name = \"world\";\n\
+             \x20  10 | +    let name = \"sena\";\n\
+             11 11 |  }\n"
+        );
+    }
+
+    #[test]
+    fn test_color_render_snapshot() {
+        let rendered = DiffRenderer::new()
+            .theme(Theme::default())
+            .render(&[hunk()]);
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines[0], "\x1b[1m━━━ src/main.rs\x1b[0m");
+        assert_eq!(lines[1], "\x1b[36m@@ -9,3 +9,3 @@\x1b[0m");
+        assert_eq!(lines[2], "\x1b[2m 9  9 │\x1b[0m \x1b[2m fn main() {\x1b[0m");
+        assert_eq!(
+            lines[3],
+            "\x1b[2m10    │\x1b[0m \x1b[31m-    let name = \"\x1b[0m\
+             \x1b[7m\x1b[31mworld\x1b[0m\x1b[31m\";\x1b[0m"
+        );
+        assert_eq!(
+            lines[4],
+            "\x1b[2m   10 │\x1b[0m \x1b[32m+    let name = \"\x1b[0m\
+             \x1b[7m\x1b[32msena\x1b[0m\x1b[32m\";\x1b[0m"
+        );
+
+        let whole_lines = DiffRenderer::new()
+            .theme(Theme::default())
+            .word_diff(false)
+            .render(&[hunk()]);
+        assert!(!whole_lines.contains(ansi::REVERSE));
+        assert!(!DiffRenderer::new()
+            .plain()
+            .render(&[hunk()])
+            .contains('\x1b'));
+    }
+}
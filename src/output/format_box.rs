@@ -2,27 +2,100 @@
 //!
 //! Creates SENA-branded format boxes for various output types
 
+use serde::{Deserialize, Serialize};
+
+use super::progress::ansi;
+use super::theme::Theme;
+
 /// Format box builder
 #[derive(Debug)]
 pub struct FormatBox {
     title: String,
     width: usize,
     border_style: BorderStyle,
+    theme: Theme,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum BorderStyle {
+    #[default]
     Double,
     Single,
+    Ascii,
+}
+
+/// Characters used to draw one border style
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BorderChars {
+    pub top_left: char,
+    pub top_right: char,
+    pub bottom_left: char,
+    pub bottom_right: char,
+    pub horizontal: char,
+    pub vertical: char,
+    pub left_tee: char,
+    pub right_tee: char,
+    pub top_tee: char,
+    pub bottom_tee: char,
+    pub cross: char,
+}
+
+impl BorderStyle {
+    pub fn chars(&self) -> BorderChars {
+        match self {
+            BorderStyle::Double => BorderChars {
+                top_left: '╔',
+                top_right: '╗',
+                bottom_left: '╚',
+                bottom_right: '╝',
+                horizontal: '═',
+                vertical: '║',
+                left_tee: '╠',
+                right_tee: '╣',
+                top_tee: '╦',
+                bottom_tee: '╩',
+                cross: '╬',
+            },
+            BorderStyle::Single => BorderChars {
+                top_left: '┌',
+                top_right: '┐',
+                bottom_left: '└',
+                bottom_right: '┘',
+                horizontal: '─',
+                vertical: '│',
+                left_tee: '├',
+                right_tee: '┤',
+                top_tee: '┬',
+                bottom_tee: '┴',
+                cross: '┼',
+            },
+            BorderStyle::Ascii => BorderChars {
+                top_left: '+',
+                top_right: '+',
+                bottom_left: '+',
+                bottom_right: '+',
+                horizontal: '-',
+                vertical: '|',
+                left_tee: '+',
+                right_tee: '+',
+                top_tee: '+',
+                bottom_tee: '+',
+                cross: '+',
+            },
+        }
+    }
 }
 
 impl FormatBox {
-    /// Create a new format box with title
+    /// Create a new format box with title, styled by the current theme
     pub fn new(title: &str) -> Self {
+        let theme = Theme::current();
         Self {
-            title: title.to_string(),
+            title: ansi::strip(title),
             width: 64,
-            border_style: BorderStyle::Double,
+            border_style: theme.border,
+            theme,
         }
     }
 
@@ -32,86 +105,67 @@ impl FormatBox {
         self
     }
 
-    /// Use single line borders
+    /// Use single line borders, unless the theme is ASCII-only
     pub fn single_border(mut self) -> Self {
-        self.border_style = BorderStyle::Single;
+        if self.border_style != BorderStyle::Ascii {
+            self.border_style = BorderStyle::Single;
+        }
         self
     }
 
-    /// Render the format box
-    pub fn render(&self) -> String {
-        match self.border_style {
-            BorderStyle::Double => self.render_double(),
-            BorderStyle::Single => self.render_single(),
-        }
+    /// Style the box with `theme` instead of the configured one
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.border_style = theme.border;
+        self.theme = theme;
+        self
     }
 
-    fn render_double(&self) -> String {
+    /// Render the format box. Single-line boxes have no padding rows.
+    pub fn render(&self) -> String {
+        let chars = self.border_style.chars();
+        let padded = self.border_style != BorderStyle::Single;
         let inner_width = self.width - 2;
         let title_len = self.title.chars().count();
         let left_pad = (inner_width.saturating_sub(title_len)) / 2;
         let right_pad = inner_width
             .saturating_sub(title_len)
             .saturating_sub(left_pad);
+        let horizontal = chars.horizontal.to_string().repeat(inner_width);
+        let empty_line = format!(
+            "{}{}{}\n",
+            chars.vertical,
+            " ".repeat(inner_width),
+            chars.vertical
+        );
 
         let mut output = String::new();
 
         // Top border
-        output.push('╔');
-        output.push_str(&"═".repeat(inner_width));
-        output.push_str("╗\n");
+        output.push(chars.top_left);
+        output.push_str(&horizontal);
+        output.push(chars.top_right);
+        output.push('\n');
 
-        // Empty line
-        output.push('║');
-        output.push_str(&" ".repeat(inner_width));
-        output.push_str("║\n");
+        if padded {
+            output.push_str(&empty_line);
+        }
 
         // Title line
-        output.push('║');
+        output.push(chars.vertical);
         output.push_str(&" ".repeat(left_pad));
-        output.push_str(&self.title);
+        output.push_str(&self.theme.accent(&self.title));
         output.push_str(&" ".repeat(right_pad));
-        output.push_str("║\n");
+        output.push(chars.vertical);
+        output.push('\n');
 
-        // Empty line
-        output.push('║');
-        output.push_str(&" ".repeat(inner_width));
-        output.push_str("║\n");
-
-        // Bottom border
-        output.push('╚');
-        output.push_str(&"═".repeat(inner_width));
-        output.push('╝');
-
-        output
-    }
-
-    fn render_single(&self) -> String {
-        let inner_width = self.width - 2;
-        let title_len = self.title.chars().count();
-        let left_pad = (inner_width.saturating_sub(title_len)) / 2;
-        let right_pad = inner_width
-            .saturating_sub(title_len)
-            .saturating_sub(left_pad);
-
-        let mut output = String::new();
-
-        // Top border
-        output.push('┌');
-        output.push_str(&"─".repeat(inner_width));
-        output.push_str("┐\n");
-
-        // Title line
-        output.push('│');
-        output.push_str(&" ".repeat(left_pad));
-        output.push_str(&self.title);
-        output.push_str(&" ".repeat(right_pad));
-        output.push_str("│\n");
+        if padded {
+            output.push_str(&empty_line);
+        }
 
         // Bottom border
-        output.push('└');
-        output.push_str(&"─".repeat(inner_width));
-        output.push('┘');
+        output.push(chars.bottom_left);
+        output.push_str(&horizontal);
+        output.push(chars.bottom_right);
 
         output
     }
@@ -134,13 +188,19 @@ pub fn code_analysis_box() -> String {
 
 /// Create a section separator
 pub fn section_separator(title: &str) -> String {
+    let line = Theme::current()
+        .border
+        .chars()
+        .horizontal
+        .to_string()
+        .repeat(64);
     let mut output = String::new();
-    output.push_str(&"═".repeat(64));
+    output.push_str(&line);
     output.push('\n');
     output.push_str("  ");
-    output.push_str(title);
+    output.push_str(&ansi::strip(title));
     output.push('\n');
-    output.push_str(&"═".repeat(64));
+    output.push_str(&line);
     output
 }
 
@@ -3,22 +3,29 @@
 //! Beautiful Unicode tables, progress bars, and format boxes
 //! SENA v5.0 - Personalized AI
 
+pub mod diff;
 pub mod format_box;
 pub mod progress;
 pub mod streaming;
 pub mod tables;
+pub mod theme;
 pub mod websocket;
 
-pub use format_box::FormatBox;
+pub use diff::DiffRenderer;
+pub use format_box::{BorderChars, BorderStyle, FormatBox};
 pub use progress::{
-    ansi, render_progress_box, LiveProgress, MultiProgress, ProgressBar, ProgressConfig, Spinner,
-    SPINNERS, SPINNER_DOTS, SPINNER_SIMPLE,
+    ansi, format_eta, render_progress_box, LiveProgress, MultiProgress, ProgressBar,
+    ProgressConfig, Spinner, Throughput, SPINNERS, SPINNER_DOTS, SPINNER_SIMPLE,
 };
 pub use streaming::{
-    create_stream, ConsoleStreamRenderer, JsonStreamRenderer, StreamEvent, StreamEventType,
-    StreamReader, StreamWriter, TypewriterEffect,
+    create_stream, create_stream_with, push_dropping_oldest, stream_channel, Coalesce,
+    ConsoleStreamRenderer, JsonStreamRenderer, Overflow, StreamEvent, StreamEventType,
+    StreamMetrics, StreamReader, StreamReceiver, StreamSendError, StreamSender, StreamWriter,
+    TypewriterEffect, DEFAULT_STREAM_CAPACITY,
 };
-pub use tables::TableBuilder;
+pub use tables::{CellOverflow, TableBuilder};
+pub use theme::{Theme, ThemeColor, ThemeConfig, BUILTIN_THEMES};
 pub use websocket::{
-    ClientId, MessageType, StreamToWebSocket, WebSocketBroadcaster, WebSocketMessage,
+    ClientId, MessageType, SessionFilter, StreamToWebSocket, WebSocketAuthError,
+    WebSocketBroadcaster, WebSocketMessage, CLOSE_POLICY_VIOLATION,
 };
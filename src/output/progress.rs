@@ -5,8 +5,11 @@
 //! - Custom emoji/prefix support
 //! - Spinner animation
 //! - Color support
+//! - ETA and throughput from a rolling window of updates
 
+use super::theme::Theme;
 use crate::config::SenaConfig;
+use std::collections::VecDeque;
 use std::io::{self, Write};
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
@@ -30,6 +33,82 @@ pub mod ansi {
     pub const RED: &str = "\x1b[31m";
     pub const RESET: &str = "\x1b[0m";
     pub const BOLD: &str = "\x1b[1m";
+    pub const DIM: &str = "\x1b[2m";
+    pub const REVERSE: &str = "\x1b[7m";
+
+    /// `text` without escape sequences or other control characters, so
+    /// caller-supplied text can't restyle the terminal or throw off width
+    /// calculations
+    pub fn strip(text: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c != '\x1b' {
+                if !c.is_control() || c == '\n' || c == '\t' {
+                    out.push(c);
+                }
+                continue;
+            }
+            match chars.next() {
+                // CSI runs to its final byte
+                Some('[') => {
+                    for c in chars.by_ref() {
+                        if ('\x40'..='\x7e').contains(&c) {
+                            break;
+                        }
+                    }
+                }
+                // OSC runs to BEL or ESC \
+                Some(']') => {
+                    while let Some(c) = chars.next() {
+                        if c == '\x07' || (c == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                            break;
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        out
+    }
+}
+
+/// Columns inside the progress boxes' borders
+const BOX_INNER_WIDTH: usize = 62;
+
+/// `text` cut to at most `width` visible characters, ending in an ellipsis
+/// when cut, along with its visible width. A cut line loses its colors, so
+/// no escape sequence is left open.
+fn fit_to_width(text: &str, width: usize, unicode: bool) -> (String, usize) {
+    let plain = ansi::strip(text);
+    let visible = plain.chars().count();
+    if visible <= width {
+        return (text.to_string(), visible);
+    }
+    let ellipsis = if unicode { "…" } else { "..." };
+    let keep = width.saturating_sub(ellipsis.chars().count());
+    let cut: String = plain.chars().take(keep).collect();
+    let cut = format!("{}{}", cut, ellipsis);
+    let visible = cut.chars().count();
+    (cut, visible)
+}
+
+/// A `│ … │` box row holding `line`, padded or cut to the box width
+fn box_row(line: &str, unicode: bool) -> String {
+    let (line, visible) = fit_to_width(line, BOX_INNER_WIDTH - 2, unicode);
+    format!("│ {}{} │", line, " ".repeat(BOX_INNER_WIDTH - 2 - visible))
+}
+
+/// A `║ … ║` title row with `title` centred, cut to the box width
+fn title_row(title: &str, unicode: bool) -> String {
+    let (title, visible) = fit_to_width(title, BOX_INNER_WIDTH, unicode);
+    let left = (BOX_INNER_WIDTH - visible) / 2;
+    format!(
+        "║{}{}{}║",
+        " ".repeat(left),
+        title,
+        " ".repeat(BOX_INNER_WIDTH - visible - left)
+    )
 }
 
 /// Spinner characters for animation
@@ -49,6 +128,17 @@ pub struct ProgressConfig {
     pub use_colors: bool,
     pub filled_char: char,
     pub empty_char: char,
+    pub theme: Theme,
+    /// Show the estimated time left for tasks with a known total
+    pub show_eta: bool,
+    /// Show completed units per second
+    pub show_rate: bool,
+    /// Name of the counted units, e.g. "items" or "MB"
+    pub rate_unit: String,
+    /// How far back updates count towards the rate
+    pub rate_window: Duration,
+    /// No progress for this long shows the ETA as unknown
+    pub stall_after: Duration,
 }
 
 impl Default for ProgressConfig {
@@ -64,7 +154,14 @@ impl Default for ProgressConfig {
             use_colors: true,
             filled_char: '█',
             empty_char: '░',
+            theme: Theme::default(),
+            show_eta: true,
+            show_rate: true,
+            rate_unit: "items".to_string(),
+            rate_window: Duration::from_secs(10),
+            stall_after: Duration::from_secs(5),
         }
+        .with_theme(Theme::current())
     }
 }
 
@@ -81,6 +178,123 @@ impl ProgressConfig {
             ..Default::default()
         }
     }
+
+    /// Apply a theme's colors and, for ASCII themes, plain bar characters
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.use_colors = theme.color;
+        if !theme.unicode {
+            self.show_emoji = false;
+            self.filled_char = '#';
+            self.empty_char = '-';
+        }
+        self.theme = theme;
+        self
+    }
+
+    /// Measure throughput over the last `window` of updates
+    pub fn with_rate_window(mut self, window: Duration) -> Self {
+        self.rate_window = window;
+        self
+    }
+
+    /// Treat a task as stalled after `after` without progress
+    pub fn with_stall_after(mut self, after: Duration) -> Self {
+        self.stall_after = after;
+        self
+    }
+
+    pub fn with_rate_unit(mut self, unit: &str) -> Self {
+        self.rate_unit = unit.to_string();
+        self
+    }
+}
+
+/// Rolling window of progress updates, used for throughput and ETA
+#[derive(Debug, Clone)]
+pub struct Throughput {
+    samples: VecDeque<(Instant, f64)>,
+    window: Duration,
+    stall_after: Duration,
+    last_change: Option<Instant>,
+}
+
+impl Throughput {
+    pub fn new(window: Duration, stall_after: Duration) -> Self {
+        Self {
+            samples: VecDeque::new(),
+            window,
+            stall_after,
+            last_change: None,
+        }
+    }
+
+    pub fn record(&mut self, completed: f64) {
+        self.record_at(Instant::now(), completed);
+    }
+
+    /// Record that `completed` units were done at `at`
+    pub fn record_at(&mut self, at: Instant, completed: f64) {
+        let changed = self
+            .samples
+            .back()
+            .map_or(true, |&(_, last)| (completed - last).abs() > f64::EPSILON);
+        if changed {
+            self.last_change = Some(at);
+        }
+        self.samples.push_back((at, completed));
+        // Keep one sample from before the window as the baseline
+        while self.samples.len() > 2
+            && at.saturating_duration_since(self.samples[1].0) >= self.window
+        {
+            self.samples.pop_front();
+        }
+    }
+
+    pub fn completed(&self) -> f64 {
+        self.samples.back().map_or(0.0, |&(_, completed)| completed)
+    }
+
+    /// Units per second across the window, measured up to `now` so a stall
+    /// drags the rate down
+    pub fn rate_at(&self, now: Instant) -> Option<f64> {
+        let &(first_at, first) = self.samples.front()?;
+        let elapsed = now.saturating_duration_since(first_at).as_secs_f64();
+        if self.samples.len() < 2 || elapsed <= 0.0 {
+            return None;
+        }
+        Some(((self.completed() - first) / elapsed).max(0.0))
+    }
+
+    pub fn is_stalled_at(&self, now: Instant) -> bool {
+        self.last_change
+            .is_some_and(|at| now.saturating_duration_since(at) >= self.stall_after)
+    }
+
+    /// Time left to reach `total`, or `None` while stalled or before the
+    /// rate is known
+    pub fn eta_at(&self, now: Instant, total: f64) -> Option<Duration> {
+        let remaining = (total - self.completed()).max(0.0);
+        if remaining == 0.0 {
+            return Some(Duration::ZERO);
+        }
+        if self.is_stalled_at(now) {
+            return None;
+        }
+        let rate = self.rate_at(now).filter(|rate| *rate > 0.0)?;
+        Some(Duration::from_secs_f64(remaining / rate))
+    }
+}
+
+/// Short human duration: `45s`, `3m05s`, `1h02m`
+pub fn format_eta(eta: Duration) -> String {
+    let secs = eta.as_secs();
+    if secs >= 3600 {
+        format!("{}h{:02}m", secs / 3600, (secs % 3600) / 60)
+    } else if secs >= 60 {
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}s", secs)
+    }
 }
 
 /// Live Progress Bar with in-place updates
@@ -180,7 +394,11 @@ impl ProgressBar {
         };
 
         // Status indicator
-        let status = if self.percent >= 100.0 { " ✅" } else { "" };
+        let status = if self.percent >= 100.0 {
+            format!(" {}", self.config.theme.status(true))
+        } else {
+            String::new()
+        };
 
         // Spinner
         let spinner = if self.config.show_spinner && self.percent < 100.0 {
@@ -191,14 +409,15 @@ impl ProgressBar {
 
         // Color the bar
         let colored_bar = if self.config.use_colors {
+            let theme = &self.config.theme;
             let color = if self.percent >= 100.0 {
-                ansi::GREEN
+                theme.success
             } else if self.percent >= 50.0 {
-                ansi::CYAN
+                theme.accent
             } else {
-                ansi::YELLOW
+                theme.warning
             };
-            format!("{}[{}]{}", color, bar, ansi::RESET)
+            format!("{}[{}]{}", color.code(), bar, ansi::RESET)
         } else {
             format!("[{}]", bar)
         };
@@ -238,9 +457,33 @@ impl ProgressBar {
     }
 }
 
+/// One task shown by `LiveProgress`
+#[derive(Debug, Clone)]
+struct LiveTask {
+    name: String,
+    completed: f64,
+    /// `None` for open-ended work, shown with a spinner instead of a bar
+    total: Option<f64>,
+    /// Updates are counted units rather than percentages
+    counted: bool,
+    throughput: Throughput,
+}
+
+impl LiveTask {
+    fn percent(&self) -> f32 {
+        match self.total {
+            Some(total) if total > 0.0 => {
+                ((self.completed / total) * 100.0).clamp(0.0, 100.0) as f32
+            }
+            Some(_) => 100.0,
+            None => 0.0,
+        }
+    }
+}
+
 /// Live Multi-Task Progress Display
 pub struct LiveProgress {
-    tasks: Vec<(String, f32)>,
+    tasks: Vec<LiveTask>,
     config: ProgressConfig,
     lines_printed: usize,
     spinner_index: usize,
@@ -249,12 +492,7 @@ pub struct LiveProgress {
 impl LiveProgress {
     /// Create new multi-progress display
     pub fn new() -> Self {
-        Self {
-            tasks: Vec::new(),
-            config: ProgressConfig::from_user_config(),
-            lines_printed: 0,
-            spinner_index: 0,
-        }
+        Self::with_config(ProgressConfig::from_user_config())
     }
 
     /// Create with custom config
@@ -267,28 +505,77 @@ impl LiveProgress {
         }
     }
 
+    fn push_task(&mut self, name: &str, total: Option<f64>, counted: bool, completed: f64) {
+        let mut throughput = Throughput::new(self.config.rate_window, self.config.stall_after);
+        throughput.record(completed);
+        self.tasks.push(LiveTask {
+            name: name.to_string(),
+            completed,
+            total,
+            counted,
+            throughput,
+        });
+    }
+
     /// Add a task
     pub fn add_task(&mut self, name: &str, percent: f32) {
-        self.tasks.push((name.to_string(), percent));
+        self.push_task(name, Some(100.0), false, percent.clamp(0.0, 100.0) as f64);
+    }
+
+    /// Add a task measured in units, with `None` when the total is unknown
+    pub fn add_counted_task(&mut self, name: &str, total: Option<u64>) {
+        self.push_task(name, total.map(|t| t as f64), true, 0.0);
     }
 
     /// Update a task by index
     pub fn update_task(&mut self, index: usize, percent: f32) {
-        if let Some(task) = self.tasks.get_mut(index) {
-            task.1 = percent.clamp(0.0, 100.0);
+        if let Some(task) = self.tasks.get(index) {
+            let completed = task.total.unwrap_or(100.0) * percent.clamp(0.0, 100.0) as f64 / 100.0;
+            self.set_completed_at(index, completed, Instant::now());
         }
     }
 
     /// Update a task by name
     pub fn update_by_name(&mut self, name: &str, percent: f32) {
-        for task in &mut self.tasks {
-            if task.0 == name {
-                task.1 = percent.clamp(0.0, 100.0);
-                break;
-            }
+        if let Some(index) = self.tasks.iter().position(|task| task.name == name) {
+            self.update_task(index, percent);
         }
     }
 
+    /// Set how many units a counted task has done
+    pub fn set_completed(&mut self, index: usize, completed: u64) {
+        self.set_completed_at(index, completed as f64, Instant::now());
+    }
+
+    pub fn set_completed_at(&mut self, index: usize, completed: f64, at: Instant) {
+        if let Some(task) = self.tasks.get_mut(index) {
+            task.completed = match task.total {
+                Some(total) => completed.clamp(0.0, total),
+                None => completed.max(0.0),
+            };
+            task.throughput.record_at(at, task.completed);
+        }
+    }
+
+    /// Units per second over the configured window
+    pub fn rate(&self, index: usize) -> Option<f64> {
+        self.rate_at(index, Instant::now())
+    }
+
+    pub fn rate_at(&self, index: usize, now: Instant) -> Option<f64> {
+        self.tasks.get(index)?.throughput.rate_at(now)
+    }
+
+    /// Time left for a task, or `None` when stalled or open-ended
+    pub fn eta(&self, index: usize) -> Option<Duration> {
+        self.eta_at(index, Instant::now())
+    }
+
+    pub fn eta_at(&self, index: usize, now: Instant) -> Option<Duration> {
+        let task = self.tasks.get(index)?;
+        task.throughput.eta_at(now, task.total?)
+    }
+
     /// Tick spinner
     pub fn tick(&mut self) {
         self.spinner_index = (self.spinner_index + 1) % SPINNERS.len();
@@ -296,7 +583,9 @@ impl LiveProgress {
 
     /// Check if all tasks are complete
     pub fn is_complete(&self) -> bool {
-        self.tasks.iter().all(|(_, pct)| *pct >= 100.0)
+        self.tasks
+            .iter()
+            .all(|task| task.total.is_some() && task.percent() >= 100.0)
     }
 
     /// Clear previously printed lines
@@ -309,6 +598,61 @@ impl LiveProgress {
         }
     }
 
+    /// A task's bar, or spinner and count when its total is unknown,
+    /// followed by its ETA and rate
+    fn task_line(&self, task: &LiveTask, now: Instant) -> String {
+        let config = &self.config;
+        let mut line = match task.total {
+            Some(_) => {
+                let mut bar = ProgressBar::with_config(&task.name, task.percent(), config.clone());
+                bar.spinner_index = self.spinner_index;
+                bar.render()
+            }
+            None => {
+                let frames = if config.theme.unicode {
+                    SPINNERS
+                } else {
+                    SPINNER_SIMPLE
+                };
+                format!(
+                    "{} {}: {} {}",
+                    frames[self.spinner_index % frames.len()],
+                    task.name,
+                    task.completed as u64,
+                    config.rate_unit
+                )
+            }
+        };
+
+        if config.show_eta && task.total.is_some() && task.percent() < 100.0 {
+            let eta = match task.throughput.eta_at(now, task.total.unwrap_or_default()) {
+                Some(eta) => format_eta(eta),
+                None if config.theme.unicode => "—".to_string(),
+                None => "--".to_string(),
+            };
+            line.push_str(&format!("  ETA {}", eta));
+        }
+        if config.show_rate && (task.counted || task.total.is_none()) {
+            if let Some(rate) = task.throughput.rate_at(now) {
+                line.push_str(&format!("  {:.1} {}/s", rate, config.rate_unit));
+            }
+        }
+        line
+    }
+
+    fn task_lines(&self) -> Vec<String> {
+        let now = Instant::now();
+        self.tasks
+            .iter()
+            .map(|task| self.task_line(task, now))
+            .collect()
+    }
+
+    /// Render one task's line as it would look at `now`
+    pub fn render_task_at(&self, index: usize, now: Instant) -> Option<String> {
+        self.tasks.get(index).map(|task| self.task_line(task, now))
+    }
+
     /// Render to string (static, no ANSI)
     pub fn render(&self) -> String {
         let mut output = String::new();
@@ -316,14 +660,15 @@ impl LiveProgress {
         // Title box
         let title = format!("{} {} TASK PROGRESS", self.config.prefix, self.config.emoji);
         output.push_str("╔══════════════════════════════════════════════════════════════╗\n");
-        output.push_str(&format!("║{:^62}║\n", title));
+        output.push_str(&title_row(&title, self.config.theme.unicode));
+        output.push('\n');
         output.push_str("╚══════════════════════════════════════════════════════════════╝\n\n");
 
         // Progress bars box
         output.push_str("┌──────────────────────────────────────────────────────────────┐\n");
-        for (name, pct) in &self.tasks {
-            let bar = ProgressBar::with_config(name, *pct, self.config.clone());
-            output.push_str(&format!("│ {:58} │\n", bar.render()));
+        for line in self.task_lines() {
+            output.push_str(&box_row(&line, self.config.theme.unicode));
+            output.push('\n');
         }
         output.push_str("└──────────────────────────────────────────────────────────────┘");
 
@@ -343,16 +688,14 @@ impl LiveProgress {
         // Title box
         let title = format!("{} {} TASK PROGRESS", self.config.prefix, self.config.emoji);
         println!("╔══════════════════════════════════════════════════════════════╗");
-        println!("║{:^62}║", title);
+        println!("{}", title_row(&title, self.config.theme.unicode));
         println!("╚══════════════════════════════════════════════════════════════╝");
         println!();
 
         // Progress bars box
         println!("┌──────────────────────────────────────────────────────────────┐");
-        for (name, pct) in &self.tasks {
-            let mut bar = ProgressBar::with_config(name, *pct, self.config.clone());
-            bar.spinner_index = self.spinner_index;
-            println!("│ {:58} │", bar.render());
+        for line in self.task_lines() {
+            println!("{}", box_row(&line, self.config.theme.unicode));
         }
         println!("└──────────────────────────────────────────────────────────────┘");
 
@@ -365,20 +708,24 @@ impl LiveProgress {
         self.lines_printed = 0;
 
         // Mark all complete
+        let now = Instant::now();
         for task in &mut self.tasks {
-            task.1 = 100.0;
+            if let Some(total) = task.total {
+                task.completed = total;
+                task.throughput.record_at(now, total);
+            }
         }
 
         // Print final state (without clearing)
         let title = format!("{} {} COMPLETE!", self.config.prefix, self.config.emoji);
         println!("╔══════════════════════════════════════════════════════════════╗");
-        println!("║{}{:^62}{}║", ansi::GREEN, title, ansi::RESET);
+        let title = format!("{}{}{}", ansi::GREEN, title, ansi::RESET);
+        println!("{}", title_row(&title, self.config.theme.unicode));
         println!("╚══════════════════════════════════════════════════════════════╝");
         println!();
         println!("┌──────────────────────────────────────────────────────────────┐");
-        for (name, pct) in &self.tasks {
-            let bar = ProgressBar::with_config(name, *pct, self.config.clone());
-            println!("│ {:58} │", bar.render());
+        for line in self.task_lines() {
+            println!("{}", box_row(&line, self.config.theme.unicode));
         }
         println!("└──────────────────────────────────────────────────────────────┘");
     }
@@ -456,12 +803,14 @@ pub fn render_progress_box(title: &str, bars: &[ProgressBar]) -> String {
     let mut output = String::new();
 
     output.push_str("╔══════════════════════════════════════════════════════════════╗\n");
-    output.push_str(&format!("║{:^62}║\n", title));
+    output.push_str(&title_row(title, true));
+    output.push('\n');
     output.push_str("╚══════════════════════════════════════════════════════════════╝\n\n");
 
     output.push_str("┌──────────────────────────────────────────────────────────────┐\n");
     for bar in bars {
-        output.push_str(&format!("│ {:58} │\n", bar.render()));
+        output.push_str(&box_row(&bar.render(), bar.config.theme.unicode));
+        output.push('\n');
     }
     output.push_str("└──────────────────────────────────────────────────────────────┘");
 
@@ -562,6 +911,74 @@ mod tests {
         assert!(lp.is_complete());
     }
 
+    #[test]
+    fn test_live_progress_rate_and_eta_from_timed_updates() {
+        let config = ProgressConfig::custom("SENA", "🦁")
+            .with_theme(Theme::no_color())
+            .with_rate_window(Duration::from_secs(2))
+            .with_stall_after(Duration::from_secs(1));
+        let mut lp = LiveProgress::with_config(config);
+        lp.add_counted_task("Download", Some(1000));
+
+        // 25 items every 100ms is 250 items/s; the window only sees the last 2s
+        let start = Instant::now();
+        let mut at = start;
+        for step in 1..=30u32 {
+            at = start + Duration::from_millis(100 * step as u64);
+            lp.set_completed_at(0, step as f64 * 25.0, at);
+        }
+
+        let rate = lp.rate_at(0, at).unwrap();
+        assert!((rate - 250.0).abs() < 5.0, "rate was {}", rate);
+        // 250 left at 250/s
+        let eta = lp.eta_at(0, at).unwrap();
+        assert!((eta.as_secs_f64() - 1.0).abs() < 0.05, "eta was {:?}", eta);
+        let line = lp.render_task_at(0, at).unwrap();
+        assert!(line.contains("ETA 1s"), "{}", line);
+        assert!(line.contains("250.0 items/s"), "{}", line);
+
+        // No progress for longer than the stall limit
+        let stalled = at + Duration::from_secs(3);
+        assert_eq!(lp.eta_at(0, stalled), None);
+        assert!(lp.render_task_at(0, stalled).unwrap().contains("ETA —"));
+    }
+
+    #[test]
+    fn test_live_progress_unknown_total_shows_spinner_and_rate() {
+        let config = ProgressConfig::custom("SENA", "🦁").with_theme(Theme::ascii());
+        let mut lp = LiveProgress::with_config(config.with_rate_unit("rows"));
+        lp.add_counted_task("Import", None);
+
+        let start = Instant::now();
+        lp.set_completed_at(0, 0.0, start);
+        lp.set_completed_at(0, 40.0, start + Duration::from_secs(2));
+
+        let line = lp
+            .render_task_at(0, start + Duration::from_secs(2))
+            .unwrap();
+        assert_eq!(line, "| Import: 40 rows  20.0 rows/s");
+        assert_eq!(lp.eta(0), None);
+        assert!(!lp.is_complete());
+    }
+
+    #[test]
+    fn test_long_task_lines_fit_the_box() {
+        let config = ProgressConfig::custom("SENA", "🦁").with_theme(Theme::ascii());
+        let mut lp = LiveProgress::with_config(config);
+        lp.add_task(&"Reindexing every document ".repeat(4), 40.0);
+        lp.add_task("Short", 10.0);
+
+        let output = lp.render();
+        let border = output.lines().nth(4).unwrap().chars().count();
+        let rows: Vec<&str> = output.lines().filter(|l| l.starts_with('│')).collect();
+        assert_eq!(rows.len(), 2);
+        for row in &rows {
+            assert_eq!(ansi::strip(row).chars().count(), border);
+        }
+        assert!(rows[0].ends_with("... │"));
+        assert!(rows[1].contains("Short"));
+    }
+
     #[test]
     fn test_multi_progress_legacy() {
         let mp = MultiProgress::new()
@@ -1,7 +1,75 @@
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::io::{self, Write};
-use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Condvar, Mutex};
 use std::time::{Duration, Instant};
+use thiserror::Error;
+
+/// Events a stream buffers before its overflow policy applies
+pub const DEFAULT_STREAM_CAPACITY: usize = 256;
+
+/// What a producer does when the consumer has fallen a full buffer behind
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Overflow {
+    /// Wait for the consumer to make room
+    Block,
+    /// Merge consecutive text chunks to make room, dropping the oldest
+    /// event only when nothing can be merged
+    #[default]
+    DropOldest,
+    /// Cut the consumer off
+    Disconnect,
+}
+
+/// Counts of what backpressure did to a stream
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StreamMetrics {
+    pub sent: u64,
+    pub dropped: u64,
+    pub coalesced: u64,
+    pub disconnected: u64,
+}
+
+/// Items a full buffer can merge instead of dropping
+pub trait Coalesce {
+    fn can_coalesce(&self, next: &Self) -> bool;
+    /// Fold `next` into `self`; only called after `can_coalesce`
+    fn coalesce(&mut self, next: Self);
+}
+
+/// Queue `item` under `Overflow::DropOldest`: append it while there is room,
+/// otherwise fold it into the newest item, merge the oldest mergeable pair,
+/// or drop the oldest item, in that order
+pub fn push_dropping_oldest<T: Coalesce>(
+    buffer: &mut VecDeque<T>,
+    item: T,
+    capacity: usize,
+    metrics: &mut StreamMetrics,
+) {
+    if buffer.len() < capacity.max(1) {
+        buffer.push_back(item);
+        return;
+    }
+    if let Some(last) = buffer.back_mut() {
+        if last.can_coalesce(&item) {
+            last.coalesce(item);
+            metrics.coalesced += 1;
+            return;
+        }
+    }
+    match (1..buffer.len()).find(|&i| buffer[i - 1].can_coalesce(&buffer[i])) {
+        Some(i) => {
+            let next = buffer.remove(i).expect("index within buffer");
+            buffer[i - 1].coalesce(next);
+            metrics.coalesced += 1;
+        }
+        None => {
+            buffer.pop_front();
+            metrics.dropped += 1;
+        }
+    }
+    buffer.push_back(item);
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum StreamEventType {
@@ -74,15 +142,200 @@ impl StreamEvent {
     }
 }
 
+impl Coalesce for StreamEvent {
+    /// Consecutive text or thinking chunks join into one
+    fn can_coalesce(&self, next: &Self) -> bool {
+        self.event_type == next.event_type
+            && matches!(
+                self.event_type,
+                StreamEventType::Text | StreamEventType::Thinking
+            )
+    }
+
+    fn coalesce(&mut self, next: Self) {
+        self.content.push_str(&next.content);
+        self.timestamp_ms = next.timestamp_ms;
+    }
+}
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamSendError {
+    #[error("stream reader was dropped")]
+    Closed,
+    #[error("stream reader fell behind and was disconnected")]
+    Disconnected,
+}
+
+struct StreamQueue {
+    buffer: VecDeque<StreamEvent>,
+    metrics: StreamMetrics,
+    writer_closed: bool,
+    reader_closed: bool,
+}
+
+struct StreamChannel {
+    queue: Mutex<StreamQueue>,
+    not_empty: Condvar,
+    not_full: Condvar,
+    capacity: usize,
+    overflow: Overflow,
+}
+
+impl StreamChannel {
+    fn lock(&self) -> std::sync::MutexGuard<'_, StreamQueue> {
+        self.queue.lock().expect("stream queue lock poisoned")
+    }
+}
+
+/// Producer half of a bounded stream
+pub struct StreamSender {
+    channel: Arc<StreamChannel>,
+}
+
+impl StreamSender {
+    pub fn send(&self, event: StreamEvent) -> Result<(), StreamSendError> {
+        let channel = &self.channel;
+        let mut queue = channel.lock();
+        if queue.reader_closed {
+            return Err(StreamSendError::Closed);
+        }
+        if queue.metrics.disconnected > 0 {
+            return Err(StreamSendError::Disconnected);
+        }
+
+        if queue.buffer.len() >= channel.capacity {
+            match channel.overflow {
+                Overflow::Block => {
+                    while queue.buffer.len() >= channel.capacity && !queue.reader_closed {
+                        queue = channel
+                            .not_full
+                            .wait(queue)
+                            .expect("stream queue lock poisoned");
+                    }
+                    if queue.reader_closed {
+                        return Err(StreamSendError::Closed);
+                    }
+                }
+                Overflow::DropOldest => {
+                    let StreamQueue {
+                        buffer, metrics, ..
+                    } = &mut *queue;
+                    push_dropping_oldest(buffer, event, channel.capacity, metrics);
+                    metrics.sent += 1;
+                    channel.not_empty.notify_one();
+                    return Ok(());
+                }
+                Overflow::Disconnect => {
+                    queue.metrics.disconnected = 1;
+                    channel.not_empty.notify_all();
+                    return Err(StreamSendError::Disconnected);
+                }
+            }
+        }
+
+        queue.buffer.push_back(event);
+        queue.metrics.sent += 1;
+        channel.not_empty.notify_one();
+        Ok(())
+    }
+
+    pub fn metrics(&self) -> StreamMetrics {
+        self.channel.lock().metrics
+    }
+}
+
+impl Drop for StreamSender {
+    fn drop(&mut self) {
+        self.channel.lock().writer_closed = true;
+        self.channel.not_empty.notify_all();
+    }
+}
+
+/// Consumer half of a bounded stream
+pub struct StreamReceiver {
+    channel: Arc<StreamChannel>,
+}
+
+impl StreamReceiver {
+    pub fn try_recv(&self) -> Option<StreamEvent> {
+        let event = self.channel.lock().buffer.pop_front();
+        if event.is_some() {
+            self.channel.not_full.notify_one();
+        }
+        event
+    }
+
+    /// Wait up to `timeout` for an event; returns at once when the writer is
+    /// gone or the reader was disconnected and nothing is buffered
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<StreamEvent> {
+        let deadline = Instant::now() + timeout;
+        let mut queue = self.channel.lock();
+        loop {
+            if let Some(event) = queue.buffer.pop_front() {
+                self.channel.not_full.notify_one();
+                return Some(event);
+            }
+            let now = Instant::now();
+            if queue.writer_closed || queue.metrics.disconnected > 0 || now >= deadline {
+                return None;
+            }
+            queue = self
+                .channel
+                .not_empty
+                .wait_timeout(queue, deadline - now)
+                .expect("stream queue lock poisoned")
+                .0;
+        }
+    }
+
+    /// Events waiting to be received
+    pub fn buffered(&self) -> usize {
+        self.channel.lock().buffer.len()
+    }
+
+    pub fn metrics(&self) -> StreamMetrics {
+        self.channel.lock().metrics
+    }
+}
+
+impl Drop for StreamReceiver {
+    fn drop(&mut self) {
+        self.channel.lock().reader_closed = true;
+        self.channel.not_full.notify_all();
+    }
+}
+
+/// A stream holding at most `capacity` undelivered events
+pub fn stream_channel(capacity: usize, overflow: Overflow) -> (StreamSender, StreamReceiver) {
+    let channel = Arc::new(StreamChannel {
+        queue: Mutex::new(StreamQueue {
+            buffer: VecDeque::new(),
+            metrics: StreamMetrics::default(),
+            writer_closed: false,
+            reader_closed: false,
+        }),
+        not_empty: Condvar::new(),
+        not_full: Condvar::new(),
+        capacity: capacity.max(1),
+        overflow,
+    });
+    (
+        StreamSender {
+            channel: Arc::clone(&channel),
+        },
+        StreamReceiver { channel },
+    )
+}
+
 pub struct StreamWriter {
-    sender: Sender<StreamEvent>,
+    sender: StreamSender,
     start_time: Instant,
     buffer: String,
     flush_threshold: usize,
 }
 
 impl StreamWriter {
-    pub fn new(sender: Sender<StreamEvent>) -> Self {
+    pub fn new(sender: StreamSender) -> Self {
         let _ = sender.send(StreamEvent::start());
         Self {
             sender,
@@ -142,6 +395,10 @@ impl StreamWriter {
         self.start_time.elapsed().as_millis() as u64
     }
 
+    pub fn metrics(&self) -> StreamMetrics {
+        self.sender.metrics()
+    }
+
     pub fn complete(mut self) {
         self.flush();
         let _ = self.sender.send(StreamEvent::complete());
@@ -155,13 +412,13 @@ impl Drop for StreamWriter {
 }
 
 pub struct StreamReader {
-    receiver: Receiver<StreamEvent>,
+    receiver: StreamReceiver,
     accumulated: String,
     events: Vec<StreamEvent>,
 }
 
 impl StreamReader {
-    pub fn new(receiver: Receiver<StreamEvent>) -> Self {
+    pub fn new(receiver: StreamReceiver) -> Self {
         Self {
             receiver,
             accumulated: String::new(),
@@ -170,29 +427,36 @@ impl StreamReader {
     }
 
     pub fn try_recv(&mut self) -> Option<StreamEvent> {
-        match self.receiver.try_recv() {
-            Ok(event) => {
-                if event.event_type == StreamEventType::Text {
-                    self.accumulated.push_str(&event.content);
-                }
-                self.events.push(event.clone());
-                Some(event)
-            }
-            Err(_) => None,
-        }
+        let event = self.receiver.try_recv()?;
+        self.record(&event);
+        Some(event)
     }
 
     pub fn recv_timeout(&mut self, timeout: Duration) -> Option<StreamEvent> {
-        match self.receiver.recv_timeout(timeout) {
-            Ok(event) => {
-                if event.event_type == StreamEventType::Text {
-                    self.accumulated.push_str(&event.content);
-                }
-                self.events.push(event.clone());
-                Some(event)
-            }
-            Err(_) => None,
+        let event = self.receiver.recv_timeout(timeout)?;
+        self.record(&event);
+        Some(event)
+    }
+
+    fn record(&mut self, event: &StreamEvent) {
+        if event.event_type == StreamEventType::Text {
+            self.accumulated.push_str(&event.content);
         }
+        self.events.push(event.clone());
+    }
+
+    /// Events sent but not yet received
+    pub fn buffered(&self) -> usize {
+        self.receiver.buffered()
+    }
+
+    pub fn metrics(&self) -> StreamMetrics {
+        self.receiver.metrics()
+    }
+
+    /// The writer overflowed an `Overflow::Disconnect` stream
+    pub fn is_disconnected(&self) -> bool {
+        self.metrics().disconnected > 0
     }
 
     pub fn accumulated_text(&self) -> &str {
@@ -210,8 +474,14 @@ impl StreamReader {
     }
 }
 
+/// A stream of `DEFAULT_STREAM_CAPACITY` events whose writer waits for a
+/// slow reader
 pub fn create_stream() -> (StreamWriter, StreamReader) {
-    let (sender, receiver) = mpsc::channel();
+    create_stream_with(DEFAULT_STREAM_CAPACITY, Overflow::Block)
+}
+
+pub fn create_stream_with(capacity: usize, overflow: Overflow) -> (StreamWriter, StreamReader) {
+    let (sender, receiver) = stream_channel(capacity, overflow);
     let writer = StreamWriter::new(sender);
     let reader = StreamReader::new(receiver);
     (writer, reader)
@@ -417,6 +687,77 @@ mod tests {
         assert!(reader.is_complete());
     }
 
+    /// Receive until the writer is gone or the reader was cut off
+    fn drain(reader: &mut StreamReader) {
+        while reader.recv_timeout(Duration::from_secs(5)).is_some() {}
+    }
+
+    #[test]
+    fn test_block_waits_for_stalled_reader() {
+        let (writer, mut reader) = create_stream_with(4, Overflow::Block);
+        let (written_tx, written_rx) = std::sync::mpsc::channel();
+        let producer = std::thread::spawn(move || {
+            let mut writer = writer.with_flush_threshold(1);
+            for i in 0..20 {
+                writer.write_text(&format!("t{} ", i));
+                let _ = written_tx.send(i);
+            }
+            writer.complete();
+        });
+
+        // Start plus three chunks fill the buffer; later writes wait rather
+        // than grow it
+        for _ in 0..3 {
+            written_rx.recv().unwrap();
+        }
+        assert_eq!(reader.buffered(), 4);
+
+        drain(&mut reader);
+        producer.join().unwrap();
+        let expected: String = (0..20).map(|i| format!("t{} ", i)).collect();
+        assert_eq!(reader.accumulated_text(), expected);
+        assert!(reader.is_complete());
+        assert_eq!(reader.metrics().dropped, 0);
+    }
+
+    #[test]
+    fn test_drop_oldest_coalesces_text_for_stalled_reader() {
+        let (writer, mut reader) = create_stream_with(4, Overflow::DropOldest);
+        let mut writer = writer.with_flush_threshold(1);
+        for i in 0..20 {
+            writer.progress(&format!("step {}", i));
+        }
+        for i in 0..20 {
+            writer.write_text(&format!("t{} ", i));
+        }
+        writer.complete();
+
+        drain(&mut reader);
+        let expected: String = (0..20).map(|i| format!("t{} ", i)).collect();
+        assert_eq!(reader.accumulated_text(), expected);
+        assert!(reader.is_complete());
+        let metrics = reader.metrics();
+        assert!(metrics.coalesced > 0);
+        assert!(metrics.dropped > 0);
+        assert_eq!(reader.all_events().len(), 4);
+    }
+
+    #[test]
+    fn test_disconnect_cuts_off_stalled_reader() {
+        let (writer, mut reader) = create_stream_with(4, Overflow::Disconnect);
+        let mut writer = writer.with_flush_threshold(1);
+        for i in 0..20 {
+            writer.write_text(&format!("t{} ", i));
+        }
+        writer.complete();
+
+        drain(&mut reader);
+        assert!(reader.is_disconnected());
+        assert_eq!(reader.all_events().len(), 4);
+        assert!(!reader.is_complete());
+        assert_eq!(reader.accumulated_text(), "t0 t1 t2 ");
+    }
+
     #[test]
     fn test_json_stream_renderer() {
         let event = StreamEvent::text("Test content");
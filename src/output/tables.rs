@@ -2,13 +2,30 @@
 //!
 //! Creates beautifully formatted tables using Unicode box-drawing characters
 
+use super::format_box::{BorderChars, BorderStyle};
+use super::progress::ansi;
+use super::theme::Theme;
+
+/// How cells wider than the maximum column width are rendered
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CellOverflow {
+    /// Cut the cell and end it with "..."
+    #[default]
+    Truncate,
+    /// Continue the cell on additional lines
+    Wrap,
+}
+
 /// Table builder for creating Unicode tables
 #[derive(Debug, Default)]
 pub struct TableBuilder {
     title: Option<String>,
     rows: Vec<Vec<String>>,
     min_col_width: usize,
+    max_col_width: Option<usize>,
+    overflow: CellOverflow,
     padding: usize,
+    theme: Theme,
 }
 
 impl TableBuilder {
@@ -18,13 +35,16 @@ impl TableBuilder {
             title: None,
             rows: Vec::new(),
             min_col_width: 10,
+            max_col_width: None,
+            overflow: CellOverflow::Truncate,
             padding: 1,
+            theme: Theme::current(),
         }
     }
 
     /// Set the table title
     pub fn title(mut self, title: &str) -> Self {
-        self.title = Some(title.to_string());
+        self.title = Some(ansi::strip(title));
         self
     }
 
@@ -40,21 +60,61 @@ impl TableBuilder {
         self
     }
 
+    /// Limit cell content to `width` characters per column
+    pub fn max_width(mut self, width: usize) -> Self {
+        self.max_col_width = Some(width.max(1));
+        self
+    }
+
+    /// Set how cells wider than the maximum width are rendered
+    pub fn overflow(mut self, overflow: CellOverflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    /// Style the table with `theme` instead of the configured one
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    fn border_chars(&self, style: BorderStyle) -> BorderChars {
+        match self.theme.border {
+            BorderStyle::Ascii => BorderStyle::Ascii.chars(),
+            _ => style.chars(),
+        }
+    }
+
     /// Build the table string
     pub fn build(&self) -> String {
         if self.rows.is_empty() {
             return String::new();
         }
 
-        // Calculate column widths
+        // Pad ragged rows so every row has the same number of cells
         let col_count = self.rows.iter().map(|r| r.len()).max().unwrap_or(0);
+        let rows: Vec<Vec<String>> = self
+            .rows
+            .iter()
+            .map(|row| {
+                let mut row = row.clone();
+                row.resize(col_count, String::new());
+                row
+            })
+            .collect();
+
+        // Calculate column widths
         let mut col_widths = vec![self.min_col_width; col_count];
 
-        for row in &self.rows {
+        for row in &rows {
             for (i, cell) in row.iter().enumerate() {
-                if i < col_widths.len() {
-                    col_widths[i] = col_widths[i].max(cell.len() + self.padding * 2);
-                }
+                col_widths[i] = col_widths[i].max(cell.chars().count() + self.padding * 2);
+            }
+        }
+
+        if let Some(max) = self.max_col_width {
+            for width in &mut col_widths {
+                *width = (*width).min(max + self.padding * 2);
             }
         }
 
@@ -68,62 +128,93 @@ impl TableBuilder {
             output.push('\n');
         }
 
+        let chars = self.border_chars(BorderStyle::Single);
+
         // Top border
-        output.push_str(&self.build_border(&col_widths, '┌', '─', '┬', '┐'));
+        output.push_str(&self.build_border(
+            &col_widths,
+            chars.top_left,
+            chars.horizontal,
+            chars.top_tee,
+            chars.top_right,
+        ));
         output.push('\n');
 
         // Rows
-        for (i, row) in self.rows.iter().enumerate() {
-            output.push_str(&self.build_row(row, &col_widths));
+        for (i, row) in rows.iter().enumerate() {
+            output.push_str(&self.build_row(row, &col_widths, chars.vertical));
             output.push('\n');
 
             // Separator after first row (header)
-            if i == 0 && self.rows.len() > 1 {
-                output.push_str(&self.build_border(&col_widths, '├', '─', '┼', '┤'));
+            if i == 0 && rows.len() > 1 {
+                output.push_str(&self.build_border(
+                    &col_widths,
+                    chars.left_tee,
+                    chars.horizontal,
+                    chars.cross,
+                    chars.right_tee,
+                ));
                 output.push('\n');
             }
         }
 
         // Bottom border
-        output.push_str(&self.build_border(&col_widths, '└', '─', '┴', '┘'));
+        output.push_str(&self.build_border(
+            &col_widths,
+            chars.bottom_left,
+            chars.horizontal,
+            chars.bottom_tee,
+            chars.bottom_right,
+        ));
 
         output
     }
 
     fn build_title_box(&self, title: &str, width: usize) -> String {
+        let chars = self.border_chars(BorderStyle::Double);
+        let (marker, marker_width) = if self.theme.unicode {
+            ("📊 ", 3)
+        } else {
+            ("", 0)
+        };
         let box_width = width.max(title.len() + 8);
         let title_padding = (box_width - title.len() - 2) / 2;
+        let horizontal = chars.horizontal.to_string().repeat(box_width - 2);
 
         let mut output = String::new();
 
         // Top
-        output.push('╔');
-        output.push_str(&"═".repeat(box_width - 2));
-        output.push_str("╗\n");
+        output.push(chars.top_left);
+        output.push_str(&horizontal);
+        output.push(chars.top_right);
+        output.push('\n');
 
         // Empty line
-        output.push('║');
+        output.push(chars.vertical);
         output.push_str(&" ".repeat(box_width - 2));
-        output.push_str("║\n");
+        output.push(chars.vertical);
+        output.push('\n');
 
         // Title line
-        output.push('║');
+        output.push(chars.vertical);
         output.push_str(&" ".repeat(title_padding));
-        output.push_str("📊 ");
-        output.push_str(title);
-        let remaining = box_width - 2 - title_padding - title.len() - 3;
+        output.push_str(marker);
+        output.push_str(&self.theme.accent(title));
+        let remaining = (box_width - 2 - title_padding - title.len()).saturating_sub(marker_width);
         output.push_str(&" ".repeat(remaining));
-        output.push_str("║\n");
+        output.push(chars.vertical);
+        output.push('\n');
 
         // Empty line
-        output.push('║');
+        output.push(chars.vertical);
         output.push_str(&" ".repeat(box_width - 2));
-        output.push_str("║\n");
+        output.push(chars.vertical);
+        output.push('\n');
 
         // Bottom
-        output.push('╚');
-        output.push_str(&"═".repeat(box_width - 2));
-        output.push('╝');
+        output.push(chars.bottom_left);
+        output.push_str(&horizontal);
+        output.push(chars.bottom_right);
 
         output
     }
@@ -150,29 +241,84 @@ impl TableBuilder {
         output
     }
 
-    fn build_row(&self, cells: &[String], widths: &[usize]) -> String {
-        let mut output = String::new();
-        output.push('│');
+    fn build_row(&self, cells: &[String], widths: &[usize], vertical: char) -> String {
+        let columns: Vec<Vec<String>> = widths
+            .iter()
+            .enumerate()
+            .map(|(i, width)| {
+                let cell = cells.get(i).map(String::as_str).unwrap_or_default();
+                let content_width = width - self.padding * 2;
+                match self.overflow {
+                    CellOverflow::Truncate => vec![truncate_cell(cell, content_width)],
+                    CellOverflow::Wrap => wrap_cell(cell, content_width),
+                }
+            })
+            .collect();
+        let height = columns.iter().map(Vec::len).max().unwrap_or(1);
+
+        let mut lines = Vec::with_capacity(height);
+        for line in 0..height {
+            let mut output = String::new();
+            output.push(vertical);
+
+            for (column, width) in columns.iter().zip(widths) {
+                let display = column.get(line).map(String::as_str).unwrap_or_default();
+                let padding_right = width - self.padding - display.chars().count();
+
+                output.push_str(&" ".repeat(self.padding));
+                output.push_str(display);
+                output.push_str(&" ".repeat(padding_right));
+                output.push(vertical);
+            }
 
-        for (i, width) in widths.iter().enumerate() {
-            let cell = cells.get(i).cloned().unwrap_or_default();
-            let padding_left = self.padding;
-            let content_width = width - self.padding * 2;
-            let display = if cell.len() > content_width {
-                format!("{}...", &cell[..content_width.saturating_sub(3)])
+            lines.push(output);
+        }
+
+        lines.join("\n")
+    }
+}
+
+fn truncate_cell(cell: &str, width: usize) -> String {
+    if cell.chars().count() <= width {
+        return cell.to_string();
+    }
+    if width < 3 {
+        return cell.chars().take(width).collect();
+    }
+    let kept: String = cell.chars().take(width - 3).collect();
+    format!("{}...", kept)
+}
+
+fn wrap_cell(cell: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+    let mut current = String::new();
+
+    for word in cell.split_whitespace() {
+        let word: Vec<char> = word.chars().collect();
+        let current_len = current.chars().count();
+        if current_len > 0 && current_len + 1 + word.len() <= width {
+            current.push(' ');
+            current.extend(&word);
+            continue;
+        }
+        if current_len > 0 {
+            lines.push(std::mem::take(&mut current));
+        }
+        let mut chunks = word.chunks(width).peekable();
+        while let Some(chunk) = chunks.next() {
+            if chunks.peek().is_some() {
+                lines.push(chunk.iter().collect());
             } else {
-                cell.clone()
-            };
-            let padding_right = width - padding_left - display.len();
-
-            output.push_str(&" ".repeat(padding_left));
-            output.push_str(&display);
-            output.push_str(&" ".repeat(padding_right));
-            output.push('│');
+                current.extend(chunk);
+            }
         }
+    }
 
-        output
+    if !current.is_empty() || lines.is_empty() {
+        lines.push(current);
     }
+    lines
 }
 
 #[cfg(test)]
@@ -218,4 +364,60 @@ mod tests {
         assert!(table.contains("foo"));
         assert!(table.contains("qux"));
     }
+
+    #[test]
+    fn test_ragged_rows_are_padded() {
+        let table = TableBuilder::new()
+            .min_width(4)
+            .row(vec!["a".to_string(), "b".to_string(), "c".to_string()])
+            .row(vec!["d".to_string()])
+            .build();
+
+        let lines: Vec<&str> = table.lines().collect();
+        let width = lines[0].chars().count();
+        assert!(lines.iter().all(|l| l.chars().count() == width));
+        assert_eq!(lines[3], "│ d  │    │    │");
+    }
+
+    #[test]
+    fn test_long_cell_is_truncated_to_max_width() {
+        let table = TableBuilder::new()
+            .min_width(4)
+            .max_width(8)
+            .row(vec!["Name".to_string(), "Note".to_string()])
+            .row(vec!["x".to_string(), "a very long cell value".to_string()])
+            .build();
+
+        let lines: Vec<&str> = table.lines().collect();
+        let width = lines[0].chars().count();
+        assert!(lines.iter().all(|l| l.chars().count() == width));
+        assert!(table.contains("│ a ver... │"));
+        assert!(!table.contains("cell value"));
+    }
+
+    #[test]
+    fn test_long_cell_wraps_onto_extra_lines() {
+        let table = TableBuilder::new()
+            .min_width(4)
+            .max_width(8)
+            .overflow(CellOverflow::Wrap)
+            .row(vec!["id".to_string(), "a very long cell value".to_string()])
+            .row(vec!["2".to_string()])
+            .build();
+
+        let lines: Vec<&str> = table.lines().collect();
+        let width = lines[0].chars().count();
+        assert!(lines.iter().all(|l| l.chars().count() == width));
+        assert_eq!(lines[1], "│ id │ a very   │");
+        assert_eq!(lines[2], "│    │ long     │");
+        assert_eq!(lines[3], "│    │ cell     │");
+        assert_eq!(lines[4], "│    │ value    │");
+        assert_eq!(lines[6], "│ 2  │          │");
+    }
+
+    #[test]
+    fn test_wrap_splits_words_longer_than_width() {
+        assert_eq!(wrap_cell("abcdefghij", 4), vec!["abcd", "efgh", "ij"]);
+        assert_eq!(wrap_cell("", 4), vec![""]);
+    }
 }
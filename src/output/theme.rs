@@ -0,0 +1,314 @@
+//! Output Theme
+//!
+//! Central colors, border style and symbols for terminal output. The active
+//! theme comes from `[output.theme]` in the SENA config and is adjusted by the
+//! `color` and `unicode` output switches.
+
+use serde::{Deserialize, Serialize};
+
+use super::format_box::BorderStyle;
+use super::progress::ansi;
+use crate::agents::Severity;
+use crate::config::{OutputConfig, SenaConfig};
+
+/// Names of the built-in themes
+pub const BUILTIN_THEMES: &[&str] = &["default", "no_color", "ascii"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemeColor {
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+}
+
+impl ThemeColor {
+    pub fn code(&self) -> &'static str {
+        match self {
+            ThemeColor::Red => ansi::RED,
+            ThemeColor::Green => ansi::GREEN,
+            ThemeColor::Yellow => ansi::YELLOW,
+            ThemeColor::Blue => ansi::BLUE,
+            ThemeColor::Magenta => "\x1b[35m",
+            ThemeColor::Cyan => ansi::CYAN,
+            ThemeColor::White => "\x1b[37m",
+        }
+    }
+}
+
+/// `[output.theme]` config: a built-in theme name plus optional overrides
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThemeConfig {
+    #[serde(default = "default_theme_name")]
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub border: Option<BorderStyle>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accent: Option<ThemeColor>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub critical: Option<ThemeColor>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warning: Option<ThemeColor>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub info: Option<ThemeColor>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub success: Option<ThemeColor>,
+}
+
+fn default_theme_name() -> String {
+    "default".to_string()
+}
+
+impl Default for ThemeConfig {
+    fn default() -> Self {
+        Self::named("default")
+    }
+}
+
+impl ThemeConfig {
+    /// Config selecting a theme by name with no overrides
+    pub fn named(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            border: None,
+            accent: None,
+            critical: None,
+            warning: None,
+            info: None,
+            success: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Theme {
+    pub name: String,
+    pub border: BorderStyle,
+    pub color: bool,
+    pub unicode: bool,
+    pub accent: ThemeColor,
+    pub critical: ThemeColor,
+    pub warning: ThemeColor,
+    pub info: ThemeColor,
+    pub success: ThemeColor,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            name: "default".to_string(),
+            border: BorderStyle::Double,
+            color: true,
+            unicode: true,
+            accent: ThemeColor::Cyan,
+            critical: ThemeColor::Red,
+            warning: ThemeColor::Yellow,
+            info: ThemeColor::Blue,
+            success: ThemeColor::Green,
+        }
+    }
+}
+
+impl Theme {
+    /// Unicode borders and symbols without color codes
+    pub fn no_color() -> Self {
+        Self {
+            name: "no_color".to_string(),
+            color: false,
+            ..Self::default()
+        }
+    }
+
+    /// Plain `+-|` borders, text symbols and no color codes
+    pub fn ascii() -> Self {
+        Self {
+            name: "ascii".to_string(),
+            border: BorderStyle::Ascii,
+            color: false,
+            unicode: false,
+            ..Self::default()
+        }
+    }
+
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "default" => Some(Self::default()),
+            "no_color" => Some(Self::no_color()),
+            "ascii" => Some(Self::ascii()),
+            _ => None,
+        }
+    }
+
+    /// Resolve the configured theme. Unknown names fall back to the default
+    /// theme; `color = false` and `unicode = false` always win.
+    pub fn from_config(output: &OutputConfig) -> Self {
+        let config = &output.theme;
+        let mut theme = Self::builtin(&config.name).unwrap_or_default();
+        if let Some(border) = config.border {
+            theme.border = border;
+        }
+        let overrides = [
+            (config.accent, &mut theme.accent),
+            (config.critical, &mut theme.critical),
+            (config.warning, &mut theme.warning),
+            (config.info, &mut theme.info),
+            (config.success, &mut theme.success),
+        ];
+        for (value, slot) in overrides {
+            if let Some(value) = value {
+                *slot = value;
+            }
+        }
+        if !output.color {
+            theme.color = false;
+        }
+        if !output.unicode {
+            theme.unicode = false;
+            theme.border = BorderStyle::Ascii;
+        }
+        theme
+    }
+
+    /// Theme from the global config, with color off when `NO_COLOR` is set
+    pub fn current() -> Self {
+        let mut theme = Self::from_config(&SenaConfig::global().output);
+        if std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+            theme.color = false;
+        }
+        theme
+    }
+
+    /// Wrap `text` in `color` when colors are enabled
+    pub fn paint(&self, color: ThemeColor, text: &str) -> String {
+        if self.color {
+            format!("{}{}{}", color.code(), text, ansi::RESET)
+        } else {
+            text.to_string()
+        }
+    }
+
+    pub fn accent(&self, text: &str) -> String {
+        self.paint(self.accent, text)
+    }
+
+    pub fn severity_color(&self, severity: Severity) -> ThemeColor {
+        match severity {
+            Severity::Critical => self.critical,
+            Severity::Warning => self.warning,
+            Severity::Info => self.info,
+            Severity::Success => self.success,
+        }
+    }
+
+    /// Severity marker: an emoji, or a colored text label without Unicode
+    pub fn severity(&self, severity: Severity) -> String {
+        if self.unicode {
+            return match severity {
+                Severity::Critical => "🔴",
+                Severity::Warning => "🟡",
+                Severity::Info => "🔵",
+                Severity::Success => "✅",
+            }
+            .to_string();
+        }
+        let label = match severity {
+            Severity::Critical => "[CRITICAL]",
+            Severity::Warning => "[WARNING]",
+            Severity::Info => "[INFO]",
+            Severity::Success => "[OK]",
+        };
+        self.paint(self.severity_color(severity), label)
+    }
+
+    /// Pass/fail marker for status lines
+    pub fn status(&self, ok: bool) -> String {
+        match (self.unicode, ok) {
+            (true, true) => "✅".to_string(),
+            (true, false) => "❌".to_string(),
+            (false, true) => self.paint(self.success, "[OK]"),
+            (false, false) => self.paint(self.critical, "[FAIL]"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::{FormatBox, ProgressBar, ProgressConfig, TableBuilder};
+
+    fn is_plain_ascii(text: &str) -> bool {
+        text.is_ascii() && !text.contains('\x1b')
+    }
+
+    #[test]
+    fn test_ascii_theme_uses_plain_borders_without_color() {
+        let theme = Theme::ascii();
+
+        let boxed = FormatBox::new("Report").theme(theme.clone()).render();
+        assert!(is_plain_ascii(&boxed));
+        assert!(boxed.starts_with("+---"));
+        assert!(boxed.contains("|"));
+
+        let table = TableBuilder::new()
+            .theme(theme.clone())
+            .title("Stats")
+            .row(vec!["Name".to_string(), "Value".to_string()])
+            .row(vec!["a".to_string(), "1".to_string()])
+            .build();
+        assert!(is_plain_ascii(&table));
+        assert!(table.contains("+-"));
+        assert!(table.contains("| a"));
+
+        let config = ProgressConfig::custom("SENA", "🦁").with_theme(theme.clone());
+        let bar = ProgressBar::with_config("Build", 100.0, config).render();
+        assert!(is_plain_ascii(&bar));
+        assert!(bar.contains("[OK]"));
+
+        assert_eq!(theme.severity(Severity::Critical), "[CRITICAL]");
+        assert_eq!(theme.status(false), "[FAIL]");
+    }
+
+    #[test]
+    fn test_titles_are_stripped_of_escapes() {
+        let title = "\x1b[31mAlert\x1b[0m \x1b]0;pwned\x07done";
+        let boxed = FormatBox::new(title).theme(Theme::ascii()).render();
+        assert!(is_plain_ascii(&boxed));
+        let title_line = boxed.lines().nth(2).unwrap();
+        assert_eq!(title_line.trim_matches(['|', ' ']), "Alert done");
+        assert_eq!(title_line.len(), 64);
+
+        let table = TableBuilder::new()
+            .theme(Theme::ascii())
+            .title("\x1b[1mStats\x1b[0m")
+            .row(vec!["a".to_string()])
+            .build();
+        assert!(is_plain_ascii(&table));
+        assert!(table.contains("Stats"));
+    }
+
+    #[test]
+    fn test_output_switches_override_configured_theme() {
+        let mut output = OutputConfig::default();
+        output.theme.accent = Some(ThemeColor::Magenta);
+        let theme = Theme::from_config(&output);
+        assert_eq!(theme.name, "default");
+        assert_eq!(theme.accent, ThemeColor::Magenta);
+        assert!(theme.accent("x").starts_with("\x1b[35m"));
+
+        output.color = false;
+        output.unicode = false;
+        let theme = Theme::from_config(&output);
+        assert_eq!(theme.border, BorderStyle::Ascii);
+        assert_eq!(theme.accent("x"), "x");
+
+        let output = OutputConfig {
+            theme: ThemeConfig::named("missing"),
+            ..OutputConfig::default()
+        };
+        assert_eq!(Theme::from_config(&output).name, "default");
+    }
+}
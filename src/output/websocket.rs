@@ -1,8 +1,17 @@
-use super::streaming::{StreamEvent, StreamEventType};
+use super::streaming::{
+    push_dropping_oldest, Coalesce, Overflow, StreamEvent, StreamEventType, StreamMetrics,
+    DEFAULT_STREAM_CAPACITY,
+};
+use crate::network::AuthTokenStore;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::{Arc, RwLock};
+use thiserror::Error;
 use tokio::sync::mpsc;
+use tokio::sync::mpsc::error::TrySendError;
+
+/// WebSocket close code sent to clients that fail the auth handshake
+pub const CLOSE_POLICY_VIOLATION: u16 = 1008;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WebSocketMessage {
@@ -25,6 +34,18 @@ pub enum MessageType {
     Pong,
     Subscribe,
     Unsubscribe,
+    AuthOk,
+    Close,
+}
+
+impl MessageType {
+    /// Connection-level messages that belong to no session
+    pub fn is_control(self) -> bool {
+        matches!(
+            self,
+            MessageType::Ping | MessageType::Pong | MessageType::AuthOk | MessageType::Close
+        )
+    }
 }
 
 impl WebSocketMessage {
@@ -67,6 +88,18 @@ impl WebSocketMessage {
         Self::new(MessageType::Pong, serde_json::json!({}))
     }
 
+    pub fn close(code: u16, reason: &str) -> Self {
+        Self::new(
+            MessageType::Close,
+            serde_json::json!({ "code": code, "reason": reason }),
+        )
+    }
+
+    /// Session the message belongs to, when its payload names one
+    pub fn session_id(&self) -> Option<&str> {
+        self.payload.get("session_id").and_then(|v| v.as_str())
+    }
+
     pub fn to_json(&self) -> String {
         serde_json::to_string(self).unwrap_or_else(|_| "{}".to_string())
     }
@@ -76,11 +109,84 @@ impl WebSocketMessage {
     }
 }
 
+impl Coalesce for WebSocketMessage {
+    /// Consecutive text or thinking chunks of the same session join into one
+    fn can_coalesce(&self, next: &Self) -> bool {
+        self.message_type == next.message_type
+            && matches!(
+                self.message_type,
+                MessageType::StreamText | MessageType::StreamThinking
+            )
+            && self.session_id() == next.session_id()
+            && self.payload["content"].is_string()
+            && next.payload["content"].is_string()
+    }
+
+    fn coalesce(&mut self, next: Self) {
+        let joined = format!(
+            "{}{}",
+            self.payload["content"].as_str().unwrap_or_default(),
+            next.payload["content"].as_str().unwrap_or_default()
+        );
+        self.payload["content"] = serde_json::Value::String(joined);
+        self.timestamp_ms = next.timestamp_ms;
+    }
+}
+
 pub type ClientId = String;
 
+/// Messages held for a slow client under `Overflow::DropOldest`
+#[derive(Default)]
+struct Backlog {
+    queue: VecDeque<WebSocketMessage>,
+    /// A task is waiting for the client to make room for them
+    flushing: bool,
+}
+
+/// First message a client sends when auth is required
+#[derive(Debug, Clone, Deserialize)]
+struct AuthRequest {
+    auth: String,
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum WebSocketAuthError {
+    #[error("first message must be {{\"auth\": \"<token>\"}}")]
+    MalformedHandshake,
+    #[error("invalid or expired token")]
+    InvalidToken,
+}
+
+/// Sessions whose events a client may receive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionFilter {
+    All,
+    Only(HashSet<String>),
+}
+
+impl SessionFilter {
+    pub fn allows(&self, session_id: &str) -> bool {
+        match self {
+            SessionFilter::All => true,
+            SessionFilter::Only(sessions) => sessions.contains(session_id),
+        }
+    }
+}
+
 pub struct WebSocketBroadcaster {
     clients: Arc<RwLock<HashMap<ClientId, mpsc::Sender<WebSocketMessage>>>>,
     subscriptions: Arc<RwLock<HashMap<String, Vec<ClientId>>>>,
+    auth_required: bool,
+    token_store: Option<Arc<tokio::sync::RwLock<AuthTokenStore>>>,
+    /// Clients that passed the handshake, with the sessions they may see
+    filters: Arc<RwLock<HashMap<ClientId, SessionFilter>>>,
+    /// Sessions each token unlocks; a token without a grant sees all
+    grants: Arc<RwLock<HashMap<String, HashSet<String>>>>,
+    overflow: Overflow,
+    /// Messages held for a slow client under `Overflow::DropOldest`
+    capacity: usize,
+    backlogs: Arc<RwLock<HashMap<ClientId, Backlog>>>,
+    metrics: Arc<RwLock<StreamMetrics>>,
 }
 
 impl WebSocketBroadcaster {
@@ -88,6 +194,203 @@ impl WebSocketBroadcaster {
         Self {
             clients: Arc::new(RwLock::new(HashMap::new())),
             subscriptions: Arc::new(RwLock::new(HashMap::new())),
+            auth_required: false,
+            token_store: None,
+            filters: Arc::new(RwLock::new(HashMap::new())),
+            grants: Arc::new(RwLock::new(HashMap::new())),
+            overflow: Overflow::default(),
+            capacity: DEFAULT_STREAM_CAPACITY,
+            backlogs: Arc::new(RwLock::new(HashMap::new())),
+            metrics: Arc::new(RwLock::new(StreamMetrics::default())),
+        }
+    }
+
+    /// How to treat a client whose channel is full. `Block` holds up every
+    /// other client until it makes room.
+    pub fn with_overflow(mut self, overflow: Overflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    /// Messages kept for a slow client before `Overflow::DropOldest` starts
+    /// coalescing or dropping them
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    pub fn overflow(&self) -> Overflow {
+        self.overflow
+    }
+
+    pub fn stream_metrics(&self) -> StreamMetrics {
+        self.metrics.read().map(|m| *m).unwrap_or_default()
+    }
+
+    /// Messages waiting for a slow client to make room
+    pub fn pending_count(&self, client_id: &str) -> usize {
+        self.backlogs
+            .read()
+            .ok()
+            .and_then(|b| b.get(client_id).map(|backlog| backlog.queue.len()))
+            .unwrap_or(0)
+    }
+
+    async fn deliver(
+        &self,
+        client_id: &str,
+        sender: &mpsc::Sender<WebSocketMessage>,
+        message: WebSocketMessage,
+    ) {
+        match self.overflow {
+            Overflow::Block => {
+                if sender.send(message).await.is_ok() {
+                    self.record(|m| m.sent += 1);
+                }
+            }
+            Overflow::DropOldest => {
+                if let Ok(mut backlogs) = self.backlogs.write() {
+                    let backlog = backlogs.entry(client_id.to_string()).or_default();
+                    if let Ok(mut metrics) = self.metrics.write() {
+                        push_dropping_oldest(
+                            &mut backlog.queue,
+                            message,
+                            self.capacity,
+                            &mut metrics,
+                        );
+                    }
+                }
+                self.drain_backlog(client_id, sender);
+                self.flush_when_ready(client_id, sender);
+            }
+            Overflow::Disconnect => match sender.try_send(message) {
+                Ok(()) => self.record(|m| m.sent += 1),
+                Err(TrySendError::Full(_)) => {
+                    self.remove_client(client_id);
+                    self.record(|m| m.disconnected += 1);
+                }
+                Err(TrySendError::Closed(_)) => self.remove_client(client_id),
+            },
+        }
+    }
+
+    /// Move as much of a client's backlog into its channel as fits
+    fn drain_backlog(&self, client_id: &str, sender: &mpsc::Sender<WebSocketMessage>) {
+        let Ok(mut backlogs) = self.backlogs.write() else {
+            return;
+        };
+        let Some(backlog) = backlogs.get_mut(client_id) else {
+            return;
+        };
+        let mut sent = 0;
+        let mut closed = false;
+        while let Some(message) = backlog.queue.pop_front() {
+            match sender.try_send(message) {
+                Ok(()) => sent += 1,
+                Err(TrySendError::Full(message)) => {
+                    backlog.queue.push_front(message);
+                    break;
+                }
+                Err(TrySendError::Closed(_)) => {
+                    closed = true;
+                    break;
+                }
+            }
+        }
+        drop(backlogs);
+
+        self.record(|m| m.sent += sent);
+        if closed {
+            self.remove_client(client_id);
+        }
+    }
+
+    /// Hand the rest of a client's backlog over as it makes room, so it
+    /// arrives even if nothing else is broadcast
+    fn flush_when_ready(&self, client_id: &str, sender: &mpsc::Sender<WebSocketMessage>) {
+        match self.backlogs.write() {
+            Ok(mut backlogs) => match backlogs.get_mut(client_id) {
+                Some(backlog) if !backlog.queue.is_empty() && !backlog.flushing => {
+                    backlog.flushing = true;
+                }
+                _ => return,
+            },
+            Err(_) => return,
+        }
+
+        let client_id = client_id.to_string();
+        let sender = sender.clone();
+        let backlogs = Arc::clone(&self.backlogs);
+        let metrics = Arc::clone(&self.metrics);
+        tokio::spawn(async move {
+            // Gone clients are cleaned up by the next delivery to them
+            while let Ok(permit) = sender.reserve().await {
+                let Ok(mut backlogs) = backlogs.write() else {
+                    return;
+                };
+                let Some(backlog) = backlogs.get_mut(&client_id) else {
+                    return;
+                };
+                // Sent under the lock, so a concurrent drain can't overtake
+                match backlog.queue.pop_front() {
+                    Some(message) => permit.send(message),
+                    None => {
+                        backlog.flushing = false;
+                        return;
+                    }
+                }
+                drop(backlogs);
+                if let Ok(mut metrics) = metrics.write() {
+                    metrics.sent += 1;
+                }
+            }
+        });
+    }
+
+    /// Retry delivering backlogged messages to clients that caught up
+    pub fn flush_pending(&self) {
+        let senders: Vec<_> = match self.clients.read() {
+            Ok(clients) => clients
+                .iter()
+                .map(|(id, sender)| (id.clone(), sender.clone()))
+                .collect(),
+            Err(_) => return,
+        };
+        for (client_id, sender) in senders {
+            self.drain_backlog(&client_id, &sender);
+        }
+    }
+
+    fn record(&self, update: impl FnOnce(&mut StreamMetrics)) {
+        if let Ok(mut metrics) = self.metrics.write() {
+            update(&mut metrics);
+        }
+    }
+
+    /// Require each client to open with `{"auth": "<token>"}`, checked
+    /// against the network module's token store
+    pub fn with_auth(mut self, token_store: Arc<tokio::sync::RwLock<AuthTokenStore>>) -> Self {
+        self.auth_required = true;
+        self.token_store = Some(token_store);
+        self
+    }
+
+    pub fn with_auth_required(mut self, auth_required: bool) -> Self {
+        self.auth_required = auth_required;
+        self
+    }
+
+    pub fn auth_required(&self) -> bool {
+        self.auth_required
+    }
+
+    /// Limit clients that authenticate with `token` to these sessions
+    pub fn grant_sessions(&self, token: &str, sessions: &[&str]) {
+        if let Ok(mut grants) = self.grants.write() {
+            grants
+                .entry(token.to_string())
+                .or_default()
+                .extend(sessions.iter().map(|s| s.to_string()));
         }
     }
 
@@ -97,11 +400,116 @@ impl WebSocketBroadcaster {
         }
     }
 
+    /// Check a client's first message. On success the client starts
+    /// receiving events for the sessions its token was granted; on failure
+    /// it is sent a close message and removed.
+    pub async fn authenticate(
+        &self,
+        client_id: &str,
+        first_message: &str,
+    ) -> Result<SessionFilter, WebSocketAuthError> {
+        if !self.auth_required {
+            return Ok(SessionFilter::All);
+        }
+
+        let result = match serde_json::from_str::<AuthRequest>(first_message) {
+            Ok(request) => self.check_token(client_id, &request.auth).await,
+            Err(_) => Err(WebSocketAuthError::MalformedHandshake),
+        };
+
+        match &result {
+            Ok(filter) => {
+                if let Ok(mut filters) = self.filters.write() {
+                    filters.insert(client_id.to_string(), filter.clone());
+                }
+                self.send_to_client(
+                    client_id,
+                    WebSocketMessage::new(MessageType::AuthOk, serde_json::json!({})),
+                )
+                .await;
+            }
+            Err(e) => {
+                self.send_to_client(
+                    client_id,
+                    WebSocketMessage::close(CLOSE_POLICY_VIOLATION, &e.to_string()),
+                )
+                .await;
+                self.remove_client(client_id);
+            }
+        }
+        result
+    }
+
+    async fn check_token(
+        &self,
+        client_id: &str,
+        token: &str,
+    ) -> Result<SessionFilter, WebSocketAuthError> {
+        let Some(store) = &self.token_store else {
+            return Err(WebSocketAuthError::InvalidToken);
+        };
+        let valid = store.write().await.validate_token(token, client_id).is_ok();
+        if !valid {
+            return Err(WebSocketAuthError::InvalidToken);
+        }
+
+        let granted = self
+            .grants
+            .read()
+            .ok()
+            .and_then(|grants| grants.get(token).cloned());
+        Ok(granted.map_or(SessionFilter::All, SessionFilter::Only))
+    }
+
+    pub fn is_authenticated(&self, client_id: &str) -> bool {
+        !self.auth_required
+            || self
+                .filters
+                .read()
+                .map(|f| f.contains_key(client_id))
+                .unwrap_or(false)
+    }
+
+    /// Whether the client may receive `message`. Session-less messages
+    /// other than control ones only reach clients granted every session.
+    fn may_receive_message(&self, client_id: &str, message: &WebSocketMessage) -> bool {
+        match message.session_id() {
+            Some(session) => self.may_receive(client_id, Some(session)),
+            None if message.message_type.is_control() => self.is_authenticated(client_id),
+            None => self.may_receive(client_id, None),
+        }
+    }
+
+    /// Whether the client may receive events for `session_id`, or events of
+    /// no session when it is `None`
+    fn may_receive(&self, client_id: &str, session_id: Option<&str>) -> bool {
+        if !self.auth_required {
+            return true;
+        }
+        let filters = match self.filters.read() {
+            Ok(f) => f,
+            Err(_) => return false,
+        };
+        match (filters.get(client_id), session_id) {
+            (None, _) => false,
+            (Some(filter), None) => *filter == SessionFilter::All,
+            (Some(filter), Some(session)) => filter.allows(session),
+        }
+    }
+
     pub fn remove_client(&self, client_id: &str) {
         if let Ok(mut clients) = self.clients.write() {
             clients.remove(client_id);
         }
 
+        if let Ok(mut filters) = self.filters.write() {
+            filters.remove(client_id);
+        }
+
+        if let Ok(mut backlogs) = self.backlogs.write() {
+            backlogs.remove(client_id);
+        }
+
         if let Ok(mut subs) = self.subscriptions.write() {
             for clients in subs.values_mut() {
                 clients.retain(|id| id != client_id);
@@ -109,7 +517,12 @@ impl WebSocketBroadcaster {
         }
     }
 
+    /// Subscribe a client to a channel, named after the session it streams;
+    /// ignored for channels the client is not authorized for
     pub fn subscribe(&self, client_id: ClientId, channel: &str) {
+        if !self.may_receive(&client_id, Some(channel)) {
+            return;
+        }
         if let Ok(mut subs) = self.subscriptions.write() {
             subs.entry(channel.to_string()).or_default().push(client_id);
         }
@@ -129,8 +542,10 @@ impl WebSocketBroadcaster {
             Err(_) => return,
         };
 
-        for sender in clients.values() {
-            let _ = sender.send(message.clone()).await;
+        for (client_id, sender) in &clients {
+            if self.may_receive_message(client_id, &message) {
+                self.deliver(client_id, sender, message.clone()).await;
+            }
         }
     }
 
@@ -147,12 +562,13 @@ impl WebSocketBroadcaster {
             };
             subscribers
                 .iter()
-                .filter_map(|id| clients.get(id).cloned())
+                .filter(|id| self.may_receive(id, Some(channel)))
+                .filter_map(|id| clients.get(id).map(|sender| (id.clone(), sender.clone())))
                 .collect()
         };
 
-        for sender in senders {
-            let _ = sender.send(message.clone()).await;
+        for (client_id, sender) in senders {
+            self.deliver(&client_id, &sender, message.clone()).await;
         }
     }
 
@@ -273,4 +689,181 @@ mod tests {
 
         assert_eq!(broadcaster.channel_subscriber_count("stream1"), 1);
     }
+
+    fn token_store() -> Arc<tokio::sync::RwLock<AuthTokenStore>> {
+        let path = std::env::temp_dir()
+            .join(format!("sena-ws-{}", uuid::Uuid::new_v4()))
+            .join("tokens.json");
+        Arc::new(tokio::sync::RwLock::new(AuthTokenStore::new(path)))
+    }
+
+    #[tokio::test]
+    async fn test_bad_token_is_rejected_and_closed() {
+        let broadcaster = WebSocketBroadcaster::new().with_auth(token_store());
+        let (tx, mut rx) = mpsc::channel(10);
+        broadcaster.add_client("client1".to_string(), tx);
+
+        let result = broadcaster
+            .authenticate("client1", r#"{"auth": "not-a-token"}"#)
+            .await;
+        assert_eq!(result, Err(WebSocketAuthError::InvalidToken));
+
+        let close = rx.recv().await.unwrap();
+        assert_eq!(close.message_type, MessageType::Close);
+        assert_eq!(close.payload["code"], CLOSE_POLICY_VIOLATION);
+        assert_eq!(broadcaster.client_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_authenticated_client_only_sees_granted_sessions() {
+        let store = token_store();
+        let token = store
+            .write()
+            .await
+            .create_session_token("client1", 300)
+            .unwrap();
+        let broadcaster = WebSocketBroadcaster::new().with_auth(Arc::clone(&store));
+        broadcaster.grant_sessions(&token.token, &["session-a"]);
+
+        let (tx, mut rx) = mpsc::channel(10);
+        broadcaster.add_client("client1".to_string(), tx);
+        // Nothing reaches a client before it authenticates
+        broadcaster.broadcast(WebSocketMessage::ping()).await;
+        assert!(rx.try_recv().is_err());
+
+        let handshake = serde_json::json!({ "auth": token.token }).to_string();
+        broadcaster
+            .authenticate("client1", &handshake)
+            .await
+            .unwrap();
+        assert_eq!(rx.recv().await.unwrap().message_type, MessageType::AuthOk);
+
+        broadcaster.subscribe("client1".to_string(), "session-a");
+        broadcaster.subscribe("client1".to_string(), "session-b");
+        assert_eq!(broadcaster.channel_subscriber_count("session-b"), 0);
+
+        let event = |session: &str| {
+            WebSocketMessage::new(
+                MessageType::StreamText,
+                serde_json::json!({ "session_id": session, "content": "hi" }),
+            )
+        };
+        broadcaster.broadcast(event("session-b")).await;
+        broadcaster.broadcast(event("session-a")).await;
+        let received = rx.recv().await.unwrap();
+        assert_eq!(received.session_id(), Some("session-a"));
+        assert!(rx.try_recv().is_err());
+
+        // Session-less events stay with clients granted every session
+        broadcaster
+            .broadcast(WebSocketMessage::new(
+                MessageType::StreamProgress,
+                serde_json::json!({ "content": "50%" }),
+            ))
+            .await;
+        assert!(rx.try_recv().is_err());
+        broadcaster.broadcast(WebSocketMessage::ping()).await;
+        assert_eq!(rx.recv().await.unwrap().message_type, MessageType::Ping);
+
+        // A reconnecting client can present its session token again
+        broadcaster.remove_client("client1");
+        let (tx2, mut rx2) = mpsc::channel(10);
+        broadcaster.add_client("client1".to_string(), tx2);
+        broadcaster
+            .authenticate("client1", &handshake)
+            .await
+            .unwrap();
+        assert_eq!(rx2.recv().await.unwrap().message_type, MessageType::AuthOk);
+    }
+
+    #[tokio::test]
+    async fn test_unrestricted_client_sees_sessionless_events() {
+        let store = token_store();
+        let token = store.write().await.create_token(300).unwrap();
+        let broadcaster = WebSocketBroadcaster::new().with_auth(Arc::clone(&store));
+
+        let (tx, mut rx) = mpsc::channel(10);
+        broadcaster.add_client("client1".to_string(), tx);
+        let handshake = serde_json::json!({ "auth": token.token }).to_string();
+        broadcaster
+            .authenticate("client1", &handshake)
+            .await
+            .unwrap();
+        assert_eq!(rx.recv().await.unwrap().message_type, MessageType::AuthOk);
+
+        broadcaster
+            .broadcast(WebSocketMessage::new(
+                MessageType::StreamProgress,
+                serde_json::json!({ "content": "50%" }),
+            ))
+            .await;
+        assert_eq!(
+            rx.recv().await.unwrap().message_type,
+            MessageType::StreamProgress
+        );
+    }
+
+    fn text(content: &str) -> WebSocketMessage {
+        WebSocketMessage::new(
+            MessageType::StreamText,
+            serde_json::json!({ "session_id": "s1", "content": content }),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_drop_oldest_coalesces_text_for_stalled_client() {
+        let broadcaster = WebSocketBroadcaster::new()
+            .with_overflow(Overflow::DropOldest)
+            .with_capacity(4);
+        let (tx, mut rx) = mpsc::channel(2);
+        broadcaster.add_client("client1".to_string(), tx);
+
+        broadcaster
+            .broadcast(WebSocketMessage::new(
+                MessageType::StreamStart,
+                serde_json::json!({ "session_id": "s1" }),
+            ))
+            .await;
+        for i in 0..50 {
+            broadcaster.broadcast(text(&format!("t{} ", i))).await;
+        }
+        assert!(broadcaster.pending_count("client1") <= 4);
+
+        // The backlog follows as the client reads, with nothing more broadcast
+        let expected: String = (0..50).map(|i| format!("t{} ", i)).collect();
+        let mut received = String::new();
+        while received.len() < expected.len() {
+            let message = rx.recv().await.unwrap();
+            if let Some(content) = message.payload["content"].as_str() {
+                received.push_str(content);
+            }
+        }
+        assert_eq!(received, expected);
+
+        let metrics = broadcaster.stream_metrics();
+        assert!(metrics.coalesced > 0);
+        assert_eq!(metrics.dropped, 0);
+        assert_eq!(broadcaster.pending_count("client1"), 0);
+    }
+
+    #[tokio::test]
+    async fn test_disconnect_drops_stalled_client() {
+        let broadcaster = WebSocketBroadcaster::new().with_overflow(Overflow::Disconnect);
+        let (slow_tx, _slow_rx) = mpsc::channel(2);
+        let (fast_tx, mut fast_rx) = mpsc::channel(10);
+        broadcaster.add_client("slow".to_string(), slow_tx);
+        broadcaster.add_client("fast".to_string(), fast_tx);
+
+        for i in 0..5 {
+            broadcaster.broadcast(text(&format!("t{} ", i))).await;
+        }
+
+        assert_eq!(broadcaster.client_count(), 1);
+        assert_eq!(broadcaster.stream_metrics().disconnected, 1);
+        let mut fast_count = 0;
+        while fast_rx.try_recv().is_ok() {
+            fast_count += 1;
+        }
+        assert_eq!(fast_count, 5);
+    }
 }
@@ -0,0 +1,350 @@
+//! Processing Result Diff
+//!
+//! Structured comparison of two `ProcessingResult`s, used when tuning the
+//! pipeline to see which phases, scores, warnings and errors changed between
+//! runs, with regressions called out.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+use crate::ProcessingResult;
+
+/// Score changes smaller than this are treated as unchanged
+pub const SCORE_EPSILON: f64 = 1e-6;
+
+/// A score before and after
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoreChange {
+    pub name: String,
+    pub before: f64,
+    pub after: f64,
+}
+
+impl ScoreChange {
+    pub fn delta(&self) -> f64 {
+        self.after - self.before
+    }
+
+    pub fn is_regression(&self) -> bool {
+        self.delta() < -SCORE_EPSILON
+    }
+}
+
+/// How one phase differs between the two results. A `None` side means the
+/// phase did not run in that result.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PhaseDiff {
+    pub phase: String,
+    pub score_before: Option<f64>,
+    pub score_after: Option<f64>,
+    pub success_before: Option<bool>,
+    pub success_after: Option<bool>,
+}
+
+impl PhaseDiff {
+    pub fn score_delta(&self) -> Option<f64> {
+        Some(self.score_after? - self.score_before?)
+    }
+
+    pub fn is_regression(&self) -> bool {
+        self.score_delta().is_some_and(|d| d < -SCORE_EPSILON)
+            || (self.success_before == Some(true) && self.success_after == Some(false))
+            || (self.score_before.is_some() && self.score_after.is_none())
+    }
+}
+
+/// Everything that changed between a prior result and a new one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResultDiff {
+    pub before_id: String,
+    pub after_id: String,
+    pub success_before: bool,
+    pub success_after: bool,
+    pub content_changed: bool,
+    pub removed_lines: Vec<String>,
+    pub added_lines: Vec<String>,
+    pub scores: Vec<ScoreChange>,
+    pub phases: Vec<PhaseDiff>,
+    pub added_warnings: Vec<String>,
+    pub removed_warnings: Vec<String>,
+    pub added_errors: Vec<String>,
+    pub removed_errors: Vec<String>,
+    pub time_before_ms: u64,
+    pub time_after_ms: u64,
+}
+
+impl ResultDiff {
+    pub fn between(before: &ProcessingResult, after: &ProcessingResult) -> Self {
+        let scores = [
+            (
+                "validation",
+                before.validation_score,
+                after.validation_score,
+            ),
+            ("safety", before.safety_score, after.safety_score),
+            ("harmony", before.harmony_score, after.harmony_score),
+            ("overall", before.overall_score(), after.overall_score()),
+        ]
+        .into_iter()
+        .filter(|(_, b, a)| (a - b).abs() > SCORE_EPSILON)
+        .map(|(name, before, after)| ScoreChange {
+            name: name.to_string(),
+            before,
+            after,
+        })
+        .collect();
+
+        let phase_names: BTreeSet<&String> = before
+            .phase_results
+            .keys()
+            .chain(after.phase_results.keys())
+            .collect();
+        let phases = phase_names
+            .into_iter()
+            .map(|phase| {
+                let b = before.phase_results.get(phase);
+                let a = after.phase_results.get(phase);
+                PhaseDiff {
+                    phase: phase.clone(),
+                    score_before: b.map(|p| p.score),
+                    score_after: a.map(|p| p.score),
+                    success_before: b.map(|p| p.success),
+                    success_after: a.map(|p| p.success),
+                }
+            })
+            .filter(|diff| {
+                diff.score_delta()
+                    .map_or(true, |delta| delta.abs() > SCORE_EPSILON)
+                    || diff.success_before != diff.success_after
+            })
+            .collect();
+
+        let before_lines: Vec<&str> = before.content.lines().collect();
+        let after_lines: Vec<&str> = after.content.lines().collect();
+
+        Self {
+            before_id: before.id.clone(),
+            after_id: after.id.clone(),
+            success_before: before.success,
+            success_after: after.success,
+            content_changed: before.content != after.content,
+            removed_lines: missing_from(&before_lines, &after_lines),
+            added_lines: missing_from(&after_lines, &before_lines),
+            scores,
+            phases,
+            added_warnings: missing_from(&after.warnings, &before.warnings),
+            removed_warnings: missing_from(&before.warnings, &after.warnings),
+            added_errors: missing_from(&after.errors, &before.errors),
+            removed_errors: missing_from(&before.errors, &after.errors),
+            time_before_ms: before.processing_time_ms(),
+            time_after_ms: after.processing_time_ms(),
+        }
+    }
+
+    /// Human-readable list of changes for the worse
+    pub fn regressions(&self) -> Vec<String> {
+        let mut regressions = Vec::new();
+        if self.success_before && !self.success_after {
+            regressions.push("Result no longer succeeds".to_string());
+        }
+        for score in self.scores.iter().filter(|s| s.is_regression()) {
+            regressions.push(format!(
+                "{} score dropped {:.1}% -> {:.1}%",
+                score.name,
+                score.before * 100.0,
+                score.after * 100.0
+            ));
+        }
+        for phase in self.phases.iter().filter(|p| p.is_regression()) {
+            regressions.push(match (phase.score_before, phase.score_after) {
+                (Some(_), None) => format!("phase '{}' no longer runs", phase.phase),
+                (Some(before), Some(after)) if after < before - SCORE_EPSILON => format!(
+                    "phase '{}' score dropped {:.1}% -> {:.1}%",
+                    phase.phase,
+                    before * 100.0,
+                    after * 100.0
+                ),
+                _ => format!("phase '{}' now fails", phase.phase),
+            });
+        }
+        for error in &self.added_errors {
+            regressions.push(format!("new error: {}", error));
+        }
+        regressions
+    }
+
+    pub fn has_regressions(&self) -> bool {
+        !self.regressions().is_empty()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.success_before == self.success_after
+            && !self.content_changed
+            && self.scores.is_empty()
+            && self.phases.is_empty()
+            && self.added_warnings.is_empty()
+            && self.removed_warnings.is_empty()
+            && self.added_errors.is_empty()
+            && self.removed_errors.is_empty()
+    }
+
+    pub fn format(&self) -> String {
+        let mut output = format!("Comparing {} -> {}\n", self.before_id, self.after_id);
+        if self.is_empty() {
+            output.push_str("\nNo differences.\n");
+            return output;
+        }
+
+        if self.success_before != self.success_after {
+            output.push_str(&format!(
+                "\nSuccess: {} -> {}\n",
+                self.success_before, self.success_after
+            ));
+        }
+
+        if !self.scores.is_empty() {
+            output.push_str("\nScores:\n");
+            for score in &self.scores {
+                output.push_str(&format!(
+                    "  {} {:<10} {:.1}% -> {:.1}% ({:+.1})\n",
+                    if score.is_regression() { "▼" } else { "▲" },
+                    score.name,
+                    score.before * 100.0,
+                    score.after * 100.0,
+                    score.delta() * 100.0
+                ));
+            }
+        }
+
+        if !self.phases.is_empty() {
+            output.push_str("\nPhases:\n");
+            let pct = |score: Option<f64>| {
+                score.map_or("-".to_string(), |s| format!("{:.1}%", s * 100.0))
+            };
+            for phase in &self.phases {
+                output.push_str(&format!(
+                    "  {} {:<20} {} -> {}\n",
+                    if phase.is_regression() { "▼" } else { "▲" },
+                    phase.phase,
+                    pct(phase.score_before),
+                    pct(phase.score_after)
+                ));
+            }
+        }
+
+        let sections = [
+            ("New errors", "+", &self.added_errors),
+            ("Resolved errors", "-", &self.removed_errors),
+            ("New warnings", "+", &self.added_warnings),
+            ("Resolved warnings", "-", &self.removed_warnings),
+        ];
+        for (title, marker, items) in sections {
+            if !items.is_empty() {
+                output.push_str(&format!("\n{}:\n", title));
+                for item in items {
+                    output.push_str(&format!("  {} {}\n", marker, item));
+                }
+            }
+        }
+
+        if self.content_changed {
+            output.push_str("\nContent:\n");
+            for line in &self.removed_lines {
+                output.push_str(&format!("  - {}\n", line));
+            }
+            for line in &self.added_lines {
+                output.push_str(&format!("  + {}\n", line));
+            }
+        }
+
+        let regressions = self.regressions();
+        if !regressions.is_empty() {
+            output.push_str(&format!("\n⚠ {} regression(s):\n", regressions.len()));
+            for regression in regressions {
+                output.push_str(&format!("  - {}\n", regression));
+            }
+        }
+        output
+    }
+}
+
+fn missing_from<T: AsRef<str>>(items: &[T], other: &[T]) -> Vec<String> {
+    items
+        .iter()
+        .filter(|item| !other.iter().any(|o| o.as_ref() == item.as_ref()))
+        .map(|item| item.as_ref().to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PhaseResult;
+    use std::collections::HashMap;
+
+    fn phase(name: &str, score: f64) -> PhaseResult {
+        PhaseResult {
+            phase: name.to_string(),
+            success: true,
+            output: HashMap::new(),
+            score,
+            ..Default::default()
+        }
+    }
+
+    fn result(safety_phase: f64, warnings: Vec<&str>) -> ProcessingResult {
+        let mut result = ProcessingResult::new("req");
+        result.success = true;
+        result.content = "Hello".to_string();
+        result.validation_score = 0.9;
+        result.safety_score = 0.8;
+        result.harmony_score = 0.7;
+        result
+            .phase_results
+            .insert("first_principles".into(), phase("first_principles", 0.9));
+        result.phase_results.insert(
+            "negative_space".into(),
+            phase("negative_space", safety_phase),
+        );
+        result.warnings = warnings.into_iter().map(str::to_string).collect();
+        result
+    }
+
+    #[test]
+    fn test_diff_reports_score_drop_and_new_warning() {
+        let before = result(0.95, vec![]);
+        let after = result(0.6, vec!["Low harmony"]);
+
+        let diff = before.diff(&after);
+        assert_eq!(diff.phases.len(), 1);
+        assert_eq!(diff.phases[0].phase, "negative_space");
+        assert!(diff.phases[0].is_regression());
+        assert_eq!(diff.added_warnings, vec!["Low harmony"]);
+        assert!(diff.removed_warnings.is_empty());
+        assert!(!diff.content_changed);
+
+        let regressions = diff.regressions();
+        assert_eq!(regressions.len(), 1);
+        assert!(regressions[0].contains("negative_space"));
+
+        let text = diff.format();
+        assert!(text.contains("negative_space"));
+        assert!(text.contains("+ Low harmony"));
+    }
+
+    #[test]
+    fn test_identical_results_have_no_diff_and_new_errors_regress() {
+        let before = result(0.95, vec![]);
+        assert!(before.diff(&before).is_empty());
+
+        let mut after = before.clone();
+        after.errors.push("Validation failed".to_string());
+        after.safety_score = 0.5;
+        let diff = before.diff(&after);
+
+        assert_eq!(diff.added_errors, vec!["Validation failed"]);
+        let names: Vec<&str> = diff.scores.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["safety", "overall"]);
+        assert_eq!(diff.regressions().len(), 3);
+    }
+}
@@ -0,0 +1,184 @@
+//! Atomic File Writes
+//!
+//! Writes go to a sibling `*.tmp` file that is renamed over the target only
+//! once fully written, so a write interrupted by cancellation or shutdown
+//! never leaves a half-written target. Temp files orphaned by a killed
+//! process are removed by a startup scan.
+
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Temp files younger than this may belong to a live writer and are kept
+pub const ORPHAN_MIN_AGE: Duration = Duration::from_secs(60);
+
+const TMP_EXTENSION: &str = "tmp";
+const MAX_SCAN_DEPTH: usize = 4;
+
+static NEXT_TMP_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Contents written to a temp file but not yet moved over the target.
+/// Dropping it without [`StagedWrite::commit`] removes the temp file and
+/// leaves the target untouched.
+#[derive(Debug)]
+pub struct StagedWrite {
+    target: PathBuf,
+    tmp: PathBuf,
+    committed: bool,
+}
+
+impl StagedWrite {
+    /// Write `contents` to a unique temp file next to `target`
+    pub fn new(target: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> io::Result<Self> {
+        let target = target.as_ref().to_path_buf();
+        let file_name = target
+            .file_name()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no file name"))?
+            .to_string_lossy()
+            .to_string();
+        let tmp = target.with_file_name(format!(
+            "{}.{}-{}.{}",
+            file_name,
+            std::process::id(),
+            NEXT_TMP_ID.fetch_add(1, Ordering::Relaxed),
+            TMP_EXTENSION
+        ));
+
+        let staged = Self {
+            target,
+            tmp,
+            committed: false,
+        };
+        let mut file = File::create(&staged.tmp)?;
+        file.write_all(contents.as_ref())?;
+        file.sync_all()?;
+        Ok(staged)
+    }
+
+    pub fn tmp_path(&self) -> &Path {
+        &self.tmp
+    }
+
+    /// Atomically replace the target with the staged contents
+    pub fn commit(mut self) -> io::Result<()> {
+        fs::rename(&self.tmp, &self.target)?;
+        self.committed = true;
+        Ok(())
+    }
+}
+
+impl Drop for StagedWrite {
+    fn drop(&mut self) {
+        if !self.committed {
+            let _ = fs::remove_file(&self.tmp);
+        }
+    }
+}
+
+/// Write `contents` to `path` via temp file and rename
+pub fn write_atomic(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    StagedWrite::new(path, contents)?.commit()
+}
+
+/// Remove `*.tmp` files under `dir` older than `min_age`, returning the
+/// paths removed
+pub fn cleanup_orphaned_tmp(dir: &Path, min_age: Duration) -> Vec<PathBuf> {
+    let mut removed = Vec::new();
+    scan(dir, min_age, MAX_SCAN_DEPTH, &mut removed);
+    removed
+}
+
+fn scan(dir: &Path, min_age: Duration, depth: usize, removed: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+
+        if file_type.is_dir() {
+            if depth > 0 {
+                scan(&path, min_age, depth - 1, removed);
+            }
+            continue;
+        }
+
+        let is_tmp = path.extension().is_some_and(|ext| ext == TMP_EXTENSION);
+        let old_enough = entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_some_and(|age| age >= min_age);
+        if is_tmp && file_type.is_file() && old_enough && fs::remove_file(&path).is_ok() {
+            removed.push(path);
+        }
+    }
+}
+
+/// Startup scan of `~/.sena` and the hub directory for temp files left by
+/// interrupted writes
+pub fn cleanup_sena_dir() -> Vec<PathBuf> {
+    let Some(home) = dirs::home_dir() else {
+        return Vec::new();
+    };
+    [home.join(".sena"), home.join(".claude").join("hub")]
+        .iter()
+        .flat_map(|dir| cleanup_orphaned_tmp(dir, ORPHAN_MIN_AGE))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("sena-atomic-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_write_atomic_replaces_target_without_leftovers() {
+        let dir = temp_dir();
+        let target = dir.join("state.json");
+        write_atomic(&target, "{\"v\":1}").unwrap();
+        write_atomic(&target, "{\"v\":2}").unwrap();
+
+        assert_eq!(fs::read_to_string(&target).unwrap(), "{\"v\":2}");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_interrupted_write_keeps_original_and_tmp_is_cleaned_on_start() {
+        let dir = temp_dir();
+        let target = dir.join("peers.json");
+        write_atomic(&target, "original").unwrap();
+
+        let cancelled = StagedWrite::new(&target, "half-writ").unwrap();
+        let cancelled_tmp = cancelled.tmp_path().to_path_buf();
+        drop(cancelled);
+        assert!(!cancelled_tmp.exists());
+        assert_eq!(fs::read_to_string(&target).unwrap(), "original");
+
+        let killed = StagedWrite::new(&target, "half-writ").unwrap();
+        let orphan = killed.tmp_path().to_path_buf();
+        std::mem::forget(killed);
+        assert!(orphan.exists());
+        assert_eq!(fs::read_to_string(&target).unwrap(), "original");
+
+        assert!(cleanup_orphaned_tmp(&dir, ORPHAN_MIN_AGE).is_empty());
+        let removed = cleanup_orphaned_tmp(&dir, Duration::ZERO);
+        assert_eq!(removed, vec![orphan.clone()]);
+        assert!(!orphan.exists());
+        assert_eq!(fs::read_to_string(&target).unwrap(), "original");
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -228,6 +228,17 @@ impl CRDT {
         &self.tombstones
     }
 
+    /// Store an entry exactly as another replica holds it, bypassing
+    /// conflict resolution
+    pub fn adopt(&mut self, key: &str, entry: ValueEntry) {
+        for (author, clock) in &entry.vector_clock {
+            let current = self.vector_clock.entry(author.clone()).or_insert(0);
+            *current = (*current).max(*clock);
+        }
+        self.tombstones.remove(key);
+        self.data.insert(key.to_string(), entry);
+    }
+
     /// Restore state from persisted data
     pub fn restore(
         &mut self,
@@ -2,8 +2,10 @@
 //!
 //! Offline-first architecture with CRDT-based conflict resolution
 
+pub mod atomic;
 pub mod crdt;
 pub mod offline;
 
+pub use atomic::{cleanup_orphaned_tmp, write_atomic, StagedWrite};
 pub use crdt::CRDT;
-pub use offline::{Change, OfflineSync};
+pub use offline::{Change, ConflictResolution, OfflineSync, ReplayResult, SyncConflict};
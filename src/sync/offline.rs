@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs::{self, File, OpenOptions};
 use std::io::{BufRead, BufReader, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::RwLock;
 
 use super::crdt::{ValueEntry, CRDT};
@@ -37,6 +37,33 @@ pub struct SyncResult {
     pub total: u64,
 }
 
+/// Which side of a conflict the CRDT kept
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictResolution {
+    LocalWins,
+    RemoteWins,
+}
+
+/// A queued change whose key the remote had changed concurrently
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncConflict {
+    pub key: String,
+    /// `None` for a deletion
+    pub local_value: Option<serde_json::Value>,
+    /// `None` when the remote had deleted the key
+    pub remote_value: Option<serde_json::Value>,
+    pub resolution: ConflictResolution,
+    pub resolved_value: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayResult {
+    pub applied: u64,
+    pub conflicts: Vec<SyncConflict>,
+    pub total: u64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct PersistedData {
     version: String,
@@ -120,16 +147,32 @@ impl OfflineSync {
         format!("{}-{}", hostname, timestamp)
     }
 
-    pub fn set(&self, key: &str, value: serde_json::Value) {
-        let change = {
-            let mut crdt = self.crdt.write().expect("CRDT lock poisoned");
-            crdt.set(key, value)
-        };
+    /// Keep data, change log and pending queue under `dir` instead of the
+    /// default location
+    pub fn with_storage_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.storage_dir = dir.into();
+        let _ = fs::create_dir_all(&self.storage_dir);
+        self
+    }
 
+    /// Apply a change made elsewhere while disconnected and hold it for the
+    /// next `replay`
+    pub fn queue(&self, change: Change) {
+        {
+            let mut crdt = self.crdt.write().expect("CRDT lock poisoned");
+            crdt.merge(&change);
+        }
         {
             let mut log = self.change_log.write().expect("change_log lock poisoned");
             log.push(change.clone());
         }
+
+        self.save_local_data();
+        self.append_to_change_log(&change);
+        self.enqueue(change);
+    }
+
+    fn enqueue(&self, change: Change) {
         {
             let mut pending = self
                 .pending_changes
@@ -137,9 +180,107 @@ impl OfflineSync {
                 .expect("pending_changes lock poisoned");
             pending.push(change.clone());
         }
+        append_change(&self.pending_file(), &change);
+    }
+
+    /// Send the queued changes to `remote` in the order they were made.
+    /// A change conflicts when the remote changed its key concurrently; the
+    /// remote's CRDT policy picks the winner, and a remote win is copied
+    /// back so both sides agree.
+    pub fn replay(&self, remote: &mut CRDT) -> ReplayResult {
+        let pending = self.get_pending_changes();
+        let mut applied = 0;
+        let mut conflicts = Vec::new();
+
+        for change in &pending {
+            let remote_entry = remote.get_data().get(&change.key).cloned();
+            let remote_deleted = remote.get_tombstones().contains(&change.key);
+            let concurrent = match &remote_entry {
+                Some(entry) => {
+                    entry.value != change.value.clone().unwrap_or_default()
+                        && is_concurrent(&entry.vector_clock, &change.vector_clock)
+                }
+                None => remote_deleted && change.value.is_some(),
+            };
+
+            let local_wins = remote.merge(change);
+            if local_wins {
+                applied += 1;
+            }
+
+            if concurrent {
+                let remote_value = remote_entry.as_ref().map(|e| e.value.clone());
+                let resolution = if local_wins {
+                    ConflictResolution::LocalWins
+                } else {
+                    ConflictResolution::RemoteWins
+                };
+                if resolution == ConflictResolution::RemoteWins {
+                    self.adopt_remote(&change.key, remote_entry.as_ref());
+                }
+                conflicts.push(SyncConflict {
+                    key: change.key.clone(),
+                    local_value: change.value.clone(),
+                    resolved_value: remote.get(&change.key),
+                    remote_value,
+                    resolution,
+                });
+            }
+        }
+
+        {
+            let mut pending = self
+                .pending_changes
+                .write()
+                .expect("pending_changes lock poisoned");
+            pending.clear();
+        }
+        let _ = super::write_atomic(self.pending_file(), "");
+        self.save_local_data();
+
+        {
+            let mut last_sync = self.last_sync.write().expect("last_sync lock poisoned");
+            *last_sync = Some(Utc::now());
+        }
+        {
+            let mut metrics = self.metrics.write().expect("metrics lock poisoned");
+            metrics.syncs += 1;
+            metrics.changes_applied += applied;
+            metrics.conflicts_resolved += conflicts.len() as u64;
+        }
+
+        ReplayResult {
+            applied,
+            conflicts,
+            total: pending.len() as u64,
+        }
+    }
+
+    /// Take the remote's side of a conflict it won
+    fn adopt_remote(&self, key: &str, entry: Option<&ValueEntry>) {
+        let mut crdt = self.crdt.write().expect("CRDT lock poisoned");
+        match entry {
+            Some(entry) => crdt.adopt(key, entry.clone()),
+            None => {
+                crdt.delete(key);
+            }
+        }
+    }
+
+    pub fn set(&self, key: &str, value: serde_json::Value) {
+        let change = {
+            let mut crdt = self.crdt.write().expect("CRDT lock poisoned");
+            crdt.set(key, value)
+        };
+
+        {
+            let mut log = self.change_log.write().expect("change_log lock poisoned");
+            log.push(change.clone());
+        }
 
         self.save_local_data();
         self.append_to_change_log(&change);
+        self.enqueue(change);
 
         {
             let mut metrics = self.metrics.write().expect("metrics lock poisoned");
@@ -167,16 +308,10 @@ impl OfflineSync {
             let mut log = self.change_log.write().expect("change_log lock poisoned");
             log.push(change.clone());
         }
-        {
-            let mut pending = self
-                .pending_changes
-                .write()
-                .expect("pending_changes lock poisoned");
-            pending.push(change.clone());
-        }
 
         self.save_local_data();
         self.append_to_change_log(&change);
+        self.enqueue(change);
 
         {
             let mut metrics = self.metrics.write().expect("metrics lock poisoned");
@@ -333,6 +468,18 @@ impl OfflineSync {
         self.storage_dir.join("change_log.jsonl")
     }
 
+    fn pending_file(&self) -> PathBuf {
+        self.storage_dir.join("pending_changes.jsonl")
+    }
+
+    fn load_pending(&self) {
+        let mut pending = self
+            .pending_changes
+            .write()
+            .expect("pending_changes lock poisoned");
+        *pending = read_changes(&self.pending_file());
+    }
+
     fn save_local_data(&self) {
         let crdt = self.crdt.read().expect("CRDT lock poisoned");
 
@@ -345,11 +492,8 @@ impl OfflineSync {
             last_updated: Utc::now().to_rfc3339(),
         };
 
-        let temp_file = self.data_file().with_extension("tmp");
         if let Ok(json) = serde_json::to_string_pretty(&data) {
-            if fs::write(&temp_file, &json).is_ok() {
-                let _ = fs::rename(&temp_file, self.data_file());
-            }
+            let _ = super::write_atomic(self.data_file(), json);
         }
     }
 
@@ -367,31 +511,45 @@ impl OfflineSync {
     }
 
     fn append_to_change_log(&self, change: &Change) {
-        if let Ok(json) = serde_json::to_string(change) {
-            if let Ok(mut file) = OpenOptions::new()
-                .create(true)
-                .append(true)
-                .open(self.change_log_file())
-            {
-                let _ = writeln!(file, "{}", json);
-            }
-        }
+        append_change(&self.change_log_file(), change);
     }
 
     fn load_change_log(&self) {
-        if let Ok(file) = File::open(self.change_log_file()) {
-            let reader = BufReader::new(file);
-            let mut log = self.change_log.write().expect("change_log lock poisoned");
+        let mut log = self.change_log.write().expect("change_log lock poisoned");
+        log.extend(read_changes(&self.change_log_file()));
+    }
+}
 
-            for line in reader.lines().map_while(Result::ok) {
-                if let Ok(change) = serde_json::from_str::<Change>(&line) {
-                    log.push(change);
-                }
-            }
+/// Add one change to a JSONL file without rewriting what is already there
+fn append_change(path: &Path, change: &Change) {
+    if let Ok(json) = serde_json::to_string(change) {
+        if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) {
+            let _ = writeln!(file, "{}", json);
         }
     }
 }
 
+/// Changes in a JSONL file, skipping unreadable lines
+fn read_changes(path: &Path) -> Vec<Change> {
+    let Ok(file) = File::open(path) else {
+        return Vec::new();
+    };
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str::<Change>(&line).ok())
+        .collect()
+}
+
+/// Neither clock has seen everything the other has
+fn is_concurrent(a: &HashMap<String, u64>, b: &HashMap<String, u64>) -> bool {
+    let dominates = |x: &HashMap<String, u64>, y: &HashMap<String, u64>| {
+        y.iter()
+            .all(|(author, count)| x.get(author).copied().unwrap_or(0) >= *count)
+    };
+    !dominates(a, b) && !dominates(b, a)
+}
+
 impl BaseComponent for OfflineSync {
     fn name(&self) -> &str {
         &self.state.name
@@ -400,6 +558,7 @@ impl BaseComponent for OfflineSync {
     fn initialize(&mut self) -> Result<(), String> {
         self.load_local_data();
         self.load_change_log();
+        self.load_pending();
         self.state.mark_initialized();
         Ok(())
     }
@@ -477,4 +636,62 @@ mod tests {
         let all = sync.get_all();
         assert_eq!(all.len(), 2);
     }
+
+    #[test]
+    fn test_replay_reports_conflicts_with_diverged_remote() {
+        let dir = std::env::temp_dir().join(format!("sena-offline-{}", uuid::Uuid::new_v4()));
+        let mut remote = CRDT::new("remote");
+        remote.set("title", serde_json::json!("remote title"));
+        remote.set("owner", serde_json::json!("bob"));
+        remote.set("draft", serde_json::json!(true));
+        remote.delete("draft");
+
+        // Offline edits, then a restart before reconnecting
+        {
+            let sync = OfflineSync::new(Some("laptop")).with_storage_dir(&dir);
+            sync.set("title", serde_json::json!("local title"));
+            sync.set("notes", serde_json::json!("written offline"));
+            sync.set("draft", serde_json::json!(false));
+        }
+        // The queue is appended to, one line per change
+        let queued = fs::read_to_string(dir.join("pending_changes.jsonl")).unwrap();
+        assert_eq!(queued.lines().count(), 3);
+        let mut sync = OfflineSync::new(Some("laptop")).with_storage_dir(&dir);
+        sync.initialize().unwrap();
+        assert_eq!(sync.get_pending_changes().len(), 3);
+
+        let result = sync.replay(&mut remote);
+        assert_eq!(result.total, 3);
+        assert_eq!(result.applied, 2);
+
+        let conflicts: HashMap<&str, &SyncConflict> = result
+            .conflicts
+            .iter()
+            .map(|c| (c.key.as_str(), c))
+            .collect();
+        assert_eq!(conflicts.len(), 2);
+
+        let title = conflicts["title"];
+        assert_eq!(title.remote_value, Some(serde_json::json!("remote title")));
+        assert_eq!(title.local_value, Some(serde_json::json!("local title")));
+        assert_eq!(title.resolution, ConflictResolution::LocalWins);
+        assert_eq!(remote.get("title"), Some(serde_json::json!("local title")));
+
+        // The remote deletion is not undone
+        let draft = conflicts["draft"];
+        assert_eq!(draft.remote_value, None);
+        assert_eq!(draft.resolution, ConflictResolution::RemoteWins);
+        assert_eq!(sync.get("draft"), None);
+
+        assert_eq!(
+            remote.get("notes"),
+            Some(serde_json::json!("written offline"))
+        );
+        assert!(sync.get_pending_changes().is_empty());
+        let restarted = OfflineSync::new(Some("laptop")).with_storage_dir(&dir);
+        restarted.load_pending();
+        assert!(restarted.get_pending_changes().is_empty());
+
+        let _ = fs::remove_dir_all(dir);
+    }
 }
@@ -67,6 +67,12 @@ impl BuiltinTools {
         ToolCall::new("web_fetch", params)
     }
 
+    pub fn web_search(query: &str) -> ToolCall {
+        let mut params = HashMap::new();
+        params.insert("query".to_string(), serde_json::json!(query));
+        ToolCall::new("web_search", params)
+    }
+
     pub fn web_post(url: &str, body: Option<&str>) -> ToolCall {
         let mut params = HashMap::new();
         params.insert("url".to_string(), serde_json::json!(url));
@@ -0,0 +1,247 @@
+//! Tool Conversation
+//!
+//! Drives a chat in which the model may call tools: each requested call is
+//! run through the `ToolSystem` and its result sent back to the model, until
+//! it answers without calling a tool or the round limit is reached.
+
+use sena_providers::{AIProvider, ChatRequest, ChatResponse, Message};
+
+use super::{ToolCall, ToolError, ToolResponse, ToolResult, ToolSystem};
+
+/// Model round trips allowed by default before giving up
+pub const DEFAULT_MAX_TOOL_ITERATIONS: usize = 8;
+
+pub struct ToolConversation<'a> {
+    tools: &'a mut ToolSystem,
+    max_iterations: usize,
+}
+
+impl<'a> ToolConversation<'a> {
+    /// Conversation running calls through `tools`, capped at the configured
+    /// `tools.max_tool_iterations`
+    pub fn new(tools: &'a mut ToolSystem) -> Self {
+        Self {
+            tools,
+            max_iterations: crate::config::SenaConfig::global()
+                .tools
+                .max_tool_iterations,
+        }
+    }
+
+    pub fn with_max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    /// Send `request` and answer tool calls until the model gives a final
+    /// response. Only tools offered in `request.tools` may be run.
+    pub async fn run<P: AIProvider + ?Sized>(
+        &mut self,
+        provider: &P,
+        mut request: ChatRequest,
+    ) -> ToolResult<ChatResponse> {
+        for _ in 0..self.max_iterations {
+            let response = provider.chat(request.clone()).await?;
+            if response.tool_calls.is_empty() {
+                return Ok(response);
+            }
+
+            request.messages.push(Message::assistant_with_tool_calls(
+                response.content.clone(),
+                response.tool_calls.clone(),
+            ));
+            for call in &response.tool_calls {
+                let result = self.execute(&request, call).await;
+                request.messages.push(Message::tool_result(
+                    &result.call_id,
+                    &result.tool_name,
+                    model_content(&result),
+                ));
+            }
+        }
+
+        Err(ToolError::IterationLimit(self.max_iterations))
+    }
+
+    async fn execute(
+        &mut self,
+        request: &ChatRequest,
+        call: &sena_providers::ToolCall,
+    ) -> ToolResponse {
+        let name = &call.function.name;
+        let offered = request
+            .tools
+            .iter()
+            .flatten()
+            .any(|tool| tool.function.name == *name);
+        if !offered {
+            return ToolResponse::failure(
+                call.id.clone(),
+                name.clone(),
+                format!("Tool {} was not offered", name),
+                0,
+            );
+        }
+
+        match ToolCall::from_provider(call) {
+            Ok(tool_call) => self.tools.execute(tool_call).await,
+            Err(e) => ToolResponse::failure(call.id.clone(), name.clone(), e.to_string(), 0),
+        }
+    }
+}
+
+/// The result as the model sees it: the output, or the error on failure
+fn model_content(response: &ToolResponse) -> String {
+    if response.success {
+        response.output.to_string()
+    } else {
+        serde_json::json!({ "error": response.error }).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use sena_providers::provider::ChatStream;
+    use sena_providers::{
+        ModelInfo, ProviderCapabilities, ProviderError, ProviderMetadata, ProviderStatus, Role,
+    };
+    use std::sync::Mutex;
+
+    /// Asks for `file_exists` on its first turn, then answers with the
+    /// tool result it was sent
+    struct ScriptedProvider {
+        capabilities: ProviderCapabilities,
+        requests: Mutex<Vec<ChatRequest>>,
+        path: String,
+    }
+
+    #[async_trait]
+    impl AIProvider for ScriptedProvider {
+        fn provider_id(&self) -> &str {
+            "scripted"
+        }
+
+        fn display_name(&self) -> &str {
+            "Scripted"
+        }
+
+        fn capabilities(&self) -> &ProviderCapabilities {
+            &self.capabilities
+        }
+
+        fn default_model(&self) -> &str {
+            "mock-model"
+        }
+
+        fn available_models(&self) -> &[ModelInfo] {
+            &[]
+        }
+
+        fn status(&self) -> ProviderStatus {
+            ProviderStatus::Connected
+        }
+
+        fn provider_metadata(&self) -> ProviderMetadata {
+            ProviderMetadata::new("scripted", "Scripted")
+        }
+
+        async fn chat(&self, request: ChatRequest) -> sena_providers::Result<ChatResponse> {
+            self.requests.lock().unwrap().push(request.clone());
+            let mut response = ChatResponse::new("scripted", "mock-model", String::new());
+            match request.messages.last() {
+                Some(last) if last.role == Role::Tool => {
+                    response.content =
+                        format!("Tool said {}", last.content.as_text().unwrap_or_default());
+                }
+                _ => {
+                    response.tool_calls = vec![sena_providers::ToolCall::function(
+                        "call_1",
+                        "file_exists",
+                        serde_json::json!({ "path": self.path }).to_string(),
+                    )];
+                }
+            }
+            Ok(response)
+        }
+
+        async fn chat_stream(&self, _request: ChatRequest) -> sena_providers::Result<ChatStream> {
+            Err(ProviderError::Unsupported("streaming".into()))
+        }
+    }
+
+    fn scripted(path: &str) -> ScriptedProvider {
+        ScriptedProvider {
+            capabilities: ProviderCapabilities::default(),
+            requests: Mutex::new(Vec::new()),
+            path: path.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_runs_requested_tool_and_returns_final_answer() {
+        let root = std::env::temp_dir().join(format!("sena_tool_chat_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        let mut system = ToolSystem::new().with_root(&root);
+        let offered = system.get_tool("file_exists").unwrap().clone();
+        let provider = scripted(&root.to_string_lossy());
+
+        let request =
+            ChatRequest::new(vec![Message::user("Does the root exist?")]).with_tools(vec![offered]);
+        let response = ToolConversation::new(&mut system)
+            .with_max_iterations(3)
+            .run(&provider, request)
+            .await
+            .unwrap();
+
+        assert!(response.tool_calls.is_empty());
+        assert!(response.content.contains("\"exists\":true"));
+
+        let requests = provider.requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        let follow_up = &requests[1].messages;
+        assert_eq!(follow_up[1].tool_calls[0].id, "call_1");
+        assert_eq!(follow_up[2].role, Role::Tool);
+        assert_eq!(follow_up[2].tool_call_id.as_deref(), Some("call_1"));
+        assert_eq!(system.get_history().len(), 1);
+
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[tokio::test]
+    async fn test_unoffered_tool_is_refused_and_loop_is_capped() {
+        let root = std::env::temp_dir().join(format!("sena_tool_chat_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        let mut system = ToolSystem::new().with_root(&root);
+        let provider = scripted(&root.to_string_lossy());
+
+        let response = ToolConversation::new(&mut system)
+            .with_max_iterations(2)
+            .run(&provider, ChatRequest::new(vec![Message::user("Hi")]))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            response.content,
+            r#"Tool said {"error":"Tool file_exists was not offered"}"#
+        );
+        let refusal = provider.requests.lock().unwrap()[1].messages[2].clone();
+        assert_eq!(refusal.role, Role::Tool);
+        assert_eq!(refusal.tool_call_id.as_deref(), Some("call_1"));
+        assert!(system.get_history().is_empty());
+
+        let provider = scripted(&root.to_string_lossy());
+        let err = ToolConversation::new(&mut system)
+            .with_max_iterations(1)
+            .run(&provider, ChatRequest::new(vec![Message::user("Hi")]))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, ToolError::IterationLimit(1)));
+        assert_eq!(provider.requests.lock().unwrap().len(), 1);
+        assert!(system.get_history().is_empty());
+
+        std::fs::remove_dir_all(&root).ok();
+    }
+}
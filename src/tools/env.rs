@@ -0,0 +1,208 @@
+//! Session Tool Environment
+//!
+//! Environment variables injected into tools run on behalf of a session.
+//! Values marked secret are resolved from the credential store and are
+//! redacted from everything a tool returns.
+
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+
+/// Session env values starting with this prefix reference a stored
+/// credential as `secret:<provider>/<field>`
+pub const SECRET_PREFIX: &str = "secret:";
+
+/// Replacement text for secret values in tool output
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Looks up stored credentials by provider and field
+pub trait SecretResolver {
+    fn resolve(&self, provider: &str, field: &str) -> Option<String>;
+}
+
+/// Credentials saved to `~/.sena/credentials.toml` by the desktop
+/// credential manager's config-file storage. Keychain entries are not read.
+#[derive(Debug, Clone)]
+pub struct CredentialFile {
+    path: PathBuf,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct CredentialsData {
+    #[serde(default)]
+    credentials: HashMap<String, CredentialFields>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct CredentialFields {
+    #[serde(default)]
+    fields: HashMap<String, String>,
+}
+
+impl CredentialFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl SecretResolver for CredentialFile {
+    fn resolve(&self, provider: &str, field: &str) -> Option<String> {
+        let content = std::fs::read_to_string(&self.path).ok()?;
+        let mut data: CredentialsData = toml::from_str(&content).ok()?;
+        data.credentials.remove(provider)?.fields.remove(field)
+    }
+}
+
+/// Split `secret:<provider>/<field>` into its parts
+fn parse_secret_ref(value: &str) -> Option<(&str, &str)> {
+    let (provider, field) = value.strip_prefix(SECRET_PREFIX)?.split_once('/')?;
+    (!provider.is_empty() && !field.is_empty()).then_some((provider, field))
+}
+
+/// Resolved environment for one session's tool calls. `Debug` lists only
+/// variable names so values never reach logs.
+#[derive(Clone, Default)]
+pub struct SessionEnv {
+    vars: HashMap<String, String>,
+    secrets: Vec<String>,
+}
+
+impl SessionEnv {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Resolve a session env map, looking up `secret:` references with
+    /// `resolver`. Fails on the first secret that cannot be found.
+    pub fn resolve(
+        env: &HashMap<String, String>,
+        resolver: &dyn SecretResolver,
+    ) -> Result<Self, String> {
+        let mut resolved = Self::new();
+        for (key, value) in env {
+            resolved = match parse_secret_ref(value) {
+                Some((provider, field)) => {
+                    let secret = resolver.resolve(provider, field).ok_or_else(|| {
+                        format!(
+                            "Secret '{}/{}' for {} not found in the credential store",
+                            provider, field, key
+                        )
+                    })?;
+                    resolved.with_secret(key, secret)
+                }
+                None => resolved.with_var(key, value),
+            };
+        }
+        Ok(resolved)
+    }
+
+    pub fn with_var(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.vars.insert(key.into(), value.into());
+        self
+    }
+
+    /// Add a variable whose value is redacted from tool output
+    pub fn with_secret(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        let value = value.into();
+        if !value.is_empty() && !self.secrets.contains(&value) {
+            self.secrets.push(value.clone());
+        }
+        self.vars.insert(key.into(), value);
+        self
+    }
+
+    pub fn vars(&self) -> &HashMap<String, String> {
+        &self.vars
+    }
+
+    /// Replace every secret value in `text`
+    pub fn redact(&self, text: &str) -> String {
+        let mut secrets: Vec<&String> = self.secrets.iter().collect();
+        secrets.sort_by_key(|s| std::cmp::Reverse(s.len()));
+        secrets.into_iter().fold(text.to_string(), |text, secret| {
+            text.replace(secret.as_str(), REDACTED)
+        })
+    }
+
+    /// Redact every string inside a JSON value
+    pub fn redact_value(&self, value: serde_json::Value) -> serde_json::Value {
+        if self.secrets.is_empty() {
+            return value;
+        }
+        match value {
+            serde_json::Value::String(s) => serde_json::Value::String(self.redact(&s)),
+            serde_json::Value::Array(items) => {
+                serde_json::Value::Array(items.into_iter().map(|v| self.redact_value(v)).collect())
+            }
+            serde_json::Value::Object(map) => serde_json::Value::Object(
+                map.into_iter()
+                    .map(|(k, v)| (k, self.redact_value(v)))
+                    .collect(),
+            ),
+            other => other,
+        }
+    }
+}
+
+impl fmt::Debug for SessionEnv {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut keys: Vec<&String> = self.vars.keys().collect();
+        keys.sort();
+        f.debug_struct("SessionEnv")
+            .field("vars", &keys)
+            .field("secrets", &self.secrets.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct StaticResolver;
+
+    impl SecretResolver for StaticResolver {
+        fn resolve(&self, provider: &str, field: &str) -> Option<String> {
+            (provider == "github" && field == "token").then(|| "ghp_secret123".to_string())
+        }
+    }
+
+    #[test]
+    fn test_resolve_secret_refs_and_redact() {
+        let mut env = HashMap::new();
+        env.insert("PROJECT".to_string(), "sena".to_string());
+        env.insert("GH_TOKEN".to_string(), "secret:github/token".to_string());
+
+        let resolved = SessionEnv::resolve(&env, &StaticResolver).unwrap();
+        assert_eq!(resolved.vars()["GH_TOKEN"], "ghp_secret123");
+        assert_eq!(
+            resolved.redact("token=ghp_secret123 sena"),
+            "token=[REDACTED] sena"
+        );
+        assert!(!format!("{:?}", resolved).contains("ghp_secret123"));
+
+        env.insert("NPM_TOKEN".to_string(), "secret:npm/token".to_string());
+        assert!(SessionEnv::resolve(&env, &StaticResolver).is_err());
+    }
+
+    #[test]
+    fn test_credential_file_resolves_fields() {
+        let path = std::env::temp_dir().join(format!("sena-creds-{}.toml", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "[credentials.github.fields]\ntoken = \"ghp_file\"\n").unwrap();
+
+        let store = CredentialFile::new(&path);
+        assert_eq!(
+            store.resolve("github", "token").as_deref(),
+            Some("ghp_file")
+        );
+        assert!(store.resolve("github", "user").is_none());
+        assert_eq!(
+            parse_secret_ref("secret:github/token"),
+            Some(("github", "token"))
+        );
+        assert_eq!(parse_secret_ref("github/token"), None);
+
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -1,20 +1,26 @@
 use std::collections::HashMap;
 use std::fs::{self, OpenOptions};
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::Arc;
 use std::time::Duration;
 
 use glob::glob;
 use regex::Regex;
 use tokio::time::timeout;
 
+use super::env::SessionEnv;
+use super::search::{self, SearchOptions, SearchProvider};
 use super::{ToolDefinition, ToolError, ToolResult};
 
 pub struct ToolExecutor {
     allowed_paths: Vec<String>,
+    root: Option<PathBuf>,
+    search: Option<Arc<dyn SearchProvider>>,
     blocked_commands: Vec<String>,
     max_output_size: usize,
+    env: SessionEnv,
 }
 
 #[derive(Debug, Clone)]
@@ -33,6 +39,8 @@ impl ToolExecutor {
     pub fn new() -> Self {
         Self {
             allowed_paths: vec![],
+            root: None,
+            search: None,
             blocked_commands: vec![
                 "rm -rf /".to_string(),
                 "sudo rm".to_string(),
@@ -41,6 +49,7 @@ impl ToolExecutor {
                 "> /dev/".to_string(),
             ],
             max_output_size: 1024 * 1024,
+            env: SessionEnv::new(),
         }
     }
 
@@ -49,6 +58,38 @@ impl ToolExecutor {
         self
     }
 
+    /// Confine file system tools to paths under `root`
+    pub fn with_root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.root = Some(root.into());
+        self
+    }
+
+    /// Lift the root confinement, for trusted callers only
+    pub fn unrestricted(mut self) -> Self {
+        self.root = None;
+        self
+    }
+
+    /// Use `provider` for `web_search` instead of the `[search]` config
+    pub fn with_search_provider(mut self, provider: Arc<dyn SearchProvider>) -> Self {
+        self.search = Some(provider);
+        self
+    }
+
+    pub fn set_root(&mut self, root: Option<PathBuf>) {
+        self.root = root;
+    }
+
+    /// Inject `env` into commands run by this executor and redact its
+    /// secrets from every result
+    pub fn set_session_env(&mut self, env: SessionEnv) {
+        self.env = env;
+    }
+
+    pub fn root(&self) -> Option<&Path> {
+        self.root.as_deref()
+    }
+
     pub async fn execute(
         &self,
         tool: &ToolDefinition,
@@ -64,6 +105,7 @@ impl ToolExecutor {
                 "file_exists" => self.execute_file_exists(params).await,
                 "shell_exec" => self.execute_shell(params).await,
                 "web_fetch" => self.execute_web_fetch(params).await,
+                "web_search" => self.execute_web_search(params).await,
                 "code_search" => self.execute_code_search(params).await,
                 "code_analyze" => self.execute_code_analyze(params).await,
                 _ => Err(ToolError::NotFound(tool.name.clone())),
@@ -72,7 +114,9 @@ impl ToolExecutor {
         .await;
 
         match result {
-            Ok(inner) => inner,
+            Ok(inner) => inner
+                .map(|output| self.env.redact_value(output))
+                .map_err(|e| self.redact_error(e)),
             Err(_) => Err(ToolError::Timeout(format!(
                 "Tool {} timed out after {} seconds",
                 tool.name, tool.timeout_seconds
@@ -80,6 +124,20 @@ impl ToolExecutor {
         }
     }
 
+    fn redact_error(&self, error: ToolError) -> ToolError {
+        let redact = |message: String| self.env.redact(&message);
+        match error {
+            ToolError::NotFound(m) => ToolError::NotFound(redact(m)),
+            ToolError::ExecutionFailed(m) => ToolError::ExecutionFailed(redact(m)),
+            ToolError::InvalidParameters(m) => ToolError::InvalidParameters(redact(m)),
+            ToolError::PermissionDenied(m) => ToolError::PermissionDenied(redact(m)),
+            ToolError::Timeout(m) => ToolError::Timeout(redact(m)),
+            ToolError::RateLimited(m) => ToolError::RateLimited(redact(m)),
+            ToolError::SerializationError(m) => ToolError::SerializationError(redact(m)),
+            other => other,
+        }
+    }
+
     async fn execute_file_read(
         &self,
         params: &HashMap<String, serde_json::Value>,
@@ -89,9 +147,9 @@ impl ToolExecutor {
             .and_then(|v| v.as_str())
             .ok_or_else(|| ToolError::InvalidParameters("path is required".to_string()))?;
 
-        self.validate_path(path)?;
+        let target = self.validate_path(path)?;
 
-        let content = fs::read_to_string(path)?;
+        let content = fs::read_to_string(&target)?;
 
         let truncated = content.len() > self.max_output_size;
         let content = if truncated {
@@ -127,16 +185,16 @@ impl ToolExecutor {
             .and_then(|v| v.as_bool())
             .unwrap_or(false);
 
-        self.validate_path(path)?;
+        let target = self.validate_path(path)?;
 
         let mut file = if append {
-            OpenOptions::new().append(true).create(true).open(path)?
+            OpenOptions::new().append(true).create(true).open(&target)?
         } else {
             OpenOptions::new()
                 .write(true)
                 .create(true)
                 .truncate(true)
-                .open(path)?
+                .open(&target)?
         };
 
         let bytes_written = file.write(content.as_bytes())?;
@@ -165,15 +223,16 @@ impl ToolExecutor {
 
         let pattern = params.get("pattern").and_then(|v| v.as_str());
 
-        self.validate_path(path)?;
+        let target = self.validate_path(path)?;
+        let dir = target.display();
 
         let mut files = Vec::new();
 
         if recursive {
             let glob_pattern = if let Some(pat) = pattern {
-                format!("{}/**/{}", path, pat)
+                format!("{}/**/{}", dir, pat)
             } else {
-                format!("{}/**/*", path)
+                format!("{}/**/*", dir)
             };
 
             for p in glob(&glob_pattern)
@@ -186,9 +245,9 @@ impl ToolExecutor {
             }
         } else {
             let glob_pattern = if let Some(pat) = pattern {
-                format!("{}/{}", path, pat)
+                format!("{}/{}", dir, pat)
             } else {
-                format!("{}/*", path)
+                format!("{}/*", dir)
             };
 
             for p in glob(&glob_pattern)
@@ -216,9 +275,11 @@ impl ToolExecutor {
             .and_then(|v| v.as_str())
             .ok_or_else(|| ToolError::InvalidParameters("path is required".to_string()))?;
 
-        let exists = Path::new(path).exists();
-        let is_file = Path::new(path).is_file();
-        let is_dir = Path::new(path).is_dir();
+        let target = self.validate_path(path)?;
+
+        let exists = target.exists();
+        let is_file = target.is_file();
+        let is_dir = target.is_dir();
 
         Ok(serde_json::json!({
             "exists": exists,
@@ -254,7 +315,7 @@ impl ToolExecutor {
         };
 
         let mut cmd = Command::new(shell);
-        cmd.arg(shell_arg).arg(command);
+        cmd.arg(shell_arg).arg(command).envs(self.env.vars());
 
         if let Some(dir) = cwd {
             cmd.current_dir(dir);
@@ -288,6 +349,40 @@ impl ToolExecutor {
         }))
     }
 
+    async fn execute_web_search(
+        &self,
+        params: &HashMap<String, serde_json::Value>,
+    ) -> ToolResult<serde_json::Value> {
+        let query = params
+            .get("query")
+            .and_then(|v| v.as_str())
+            .filter(|q| !q.trim().is_empty())
+            .ok_or_else(|| ToolError::InvalidParameters("query is required".to_string()))?;
+
+        let mut options = SearchOptions::default();
+        if let Some(max) = params.get("max_results").and_then(|v| v.as_u64()) {
+            options.max_results = max.clamp(1, 50) as usize;
+        }
+
+        let provider = match &self.search {
+            Some(provider) => Arc::clone(provider),
+            None => search::from_config(&crate::config::SenaConfig::global().search)?,
+        };
+
+        let hits = provider.search(query, &options).await?;
+
+        let mut output = serde_json::json!({
+            "query": query,
+            "provider": provider.name(),
+            "results": hits,
+            "count": hits.len()
+        });
+        if hits.is_empty() {
+            output["message"] = serde_json::json!("No results found");
+        }
+        Ok(output)
+    }
+
     async fn execute_web_fetch(
         &self,
         params: &HashMap<String, serde_json::Value>,
@@ -365,15 +460,15 @@ impl ToolExecutor {
 
         let file_pattern = params.get("file_pattern").and_then(|v| v.as_str());
 
-        self.validate_path(path)?;
+        let target = self.validate_path(path)?;
 
         let regex = Regex::new(pattern)
             .map_err(|e| ToolError::InvalidParameters(format!("Invalid regex: {}", e)))?;
 
         let glob_pattern = if let Some(fp) = file_pattern {
-            format!("{}/**/{}", path, fp)
+            format!("{}/**/{}", target.display(), fp)
         } else {
-            format!("{}/**/*", path)
+            format!("{}/**/*", target.display())
         };
 
         let mut matches = Vec::new();
@@ -430,9 +525,7 @@ impl ToolExecutor {
             .and_then(|v| v.as_str())
             .unwrap_or("structure");
 
-        self.validate_path(path)?;
-
-        let file_path = Path::new(path);
+        let file_path = self.validate_path(path)?;
 
         if !file_path.exists() {
             return Err(ToolError::ExecutionFailed(format!(
@@ -442,7 +535,7 @@ impl ToolExecutor {
         }
 
         let content = if file_path.is_file() {
-            fs::read_to_string(path)?
+            fs::read_to_string(&file_path)?
         } else {
             String::new()
         };
@@ -579,7 +672,10 @@ impl ToolExecutor {
         })
     }
 
-    fn validate_path(&self, path: &str) -> ToolResult<()> {
+    /// Check `path` against the allow list and sandbox root, returning the
+    /// path the tool should open. Under a root, relative paths are taken
+    /// relative to it and the result has its symlinks resolved.
+    fn validate_path(&self, path: &str) -> ToolResult<PathBuf> {
         let path = Path::new(path);
 
         if path.to_string_lossy().contains("..") {
@@ -601,7 +697,51 @@ impl ToolExecutor {
             }
         }
 
-        Ok(())
+        if let Some(root) = &self.root {
+            let root = root.canonicalize().map_err(|e| {
+                ToolError::PermissionDenied(format!(
+                    "Sandbox root {} is not accessible: {}",
+                    root.display(),
+                    e
+                ))
+            })?;
+            let resolved = Self::resolve_path(&root.join(path))?;
+
+            if !resolved.starts_with(&root) {
+                return Err(ToolError::PermissionDenied(format!(
+                    "Path outside sandbox root {}: {}",
+                    root.display(),
+                    path.display()
+                )));
+            }
+            return Ok(resolved);
+        }
+
+        Ok(path.to_path_buf())
+    }
+
+    /// Resolve symlinks in the longest existing prefix of the absolute
+    /// `path`, so paths to files that don't exist yet can still be checked
+    /// against the sandbox.
+    fn resolve_path(path: &Path) -> ToolResult<PathBuf> {
+        let mut existing = path;
+        let mut missing = Vec::new();
+        while !existing.exists() {
+            match (existing.parent(), existing.file_name()) {
+                (Some(parent), Some(name)) => {
+                    missing.push(name.to_os_string());
+                    existing = parent;
+                }
+                _ => break,
+            }
+        }
+
+        let mut resolved = existing.canonicalize()?;
+        for name in missing.into_iter().rev() {
+            resolved.push(name);
+        }
+
+        Ok(resolved)
     }
 
     fn validate_command(&self, command: &str) -> ToolResult<()> {
@@ -644,6 +784,143 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_sandbox_root_allows_inside_and_denies_traversal() {
+        let root = std::env::temp_dir().join(format!("sena_sandbox_{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        let inside = root.join("notes.txt");
+        fs::write(&inside, "ok").unwrap();
+
+        let executor = ToolExecutor::new().with_root(&root);
+        assert!(executor.validate_path(&inside.to_string_lossy()).is_ok());
+        assert!(executor
+            .validate_path(&root.join("new_file.txt").to_string_lossy())
+            .is_ok());
+
+        let traversal = format!("{}/../etc/passwd", root.display());
+        assert!(matches!(
+            executor.validate_path(&traversal),
+            Err(ToolError::PermissionDenied(_))
+        ));
+        assert!(matches!(
+            executor.validate_path("/etc/passwd"),
+            Err(ToolError::PermissionDenied(_))
+        ));
+        assert!(ToolExecutor::new()
+            .with_root(&root)
+            .unrestricted()
+            .validate_path("/etc/passwd")
+            .is_ok());
+
+        #[cfg(unix)]
+        {
+            let link = root.join("escape");
+            let _ = fs::remove_file(&link);
+            std::os::unix::fs::symlink("/etc", &link).unwrap();
+            assert!(matches!(
+                executor.validate_path(&link.join("passwd").to_string_lossy()),
+                Err(ToolError::PermissionDenied(_))
+            ));
+        }
+
+        fs::remove_dir_all(&root).ok();
+    }
+
+    #[tokio::test]
+    async fn test_relative_paths_resolve_against_sandbox_root() {
+        let root =
+            std::env::temp_dir().join(format!("sena_sandbox_relative_{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(root.join("docs")).unwrap();
+        fs::write(root.join("docs/notes.txt"), "inside").unwrap();
+        let executor = ToolExecutor::new().with_root(&root);
+        let params = |path: &str| HashMap::from([("path".to_string(), serde_json::json!(path))]);
+
+        assert_eq!(
+            executor.validate_path("docs/notes.txt").unwrap(),
+            root.canonicalize().unwrap().join("docs/notes.txt")
+        );
+        let read = executor
+            .execute_file_read(&params("docs/notes.txt"))
+            .await
+            .unwrap();
+        assert_eq!(read["content"], "inside");
+        assert_eq!(read["path"], "docs/notes.txt");
+
+        // Nothing under the process working directory is reachable this way
+        assert_eq!(
+            executor
+                .execute_file_exists(&params("Cargo.toml"))
+                .await
+                .unwrap()["exists"],
+            false
+        );
+
+        fs::remove_dir_all(&root).ok();
+    }
+
+    #[tokio::test]
+    async fn test_file_exists_stays_inside_sandbox() {
+        let root =
+            std::env::temp_dir().join(format!("sena_sandbox_exists_{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&root).unwrap();
+        let executor = ToolExecutor::new().with_root(&root);
+        let params = |path: &str| HashMap::from([("path".to_string(), serde_json::json!(path))]);
+
+        let inside = executor
+            .execute_file_exists(&params(&root.to_string_lossy()))
+            .await
+            .unwrap();
+        assert_eq!(inside["is_directory"], true);
+
+        assert!(matches!(
+            executor.execute_file_exists(&params("/etc/passwd")).await,
+            Err(ToolError::PermissionDenied(_))
+        ));
+
+        fs::remove_dir_all(&root).ok();
+    }
+
+    #[tokio::test]
+    async fn test_web_search_routes_through_provider() {
+        struct FixedSearch(Vec<search::SearchHit>);
+
+        #[async_trait::async_trait]
+        impl SearchProvider for FixedSearch {
+            fn name(&self) -> &str {
+                "fixed"
+            }
+
+            async fn search(
+                &self,
+                _query: &str,
+                options: &SearchOptions,
+            ) -> ToolResult<Vec<search::SearchHit>> {
+                Ok(self.0.iter().take(options.max_results).cloned().collect())
+            }
+        }
+
+        let hit = search::SearchHit {
+            title: "Rust".to_string(),
+            url: "https://www.rust-lang.org/".to_string(),
+            snippet: "A language".to_string(),
+        };
+        let executor =
+            ToolExecutor::new().with_search_provider(Arc::new(FixedSearch(vec![hit.clone(); 3])));
+        let mut params = HashMap::new();
+        params.insert("query".to_string(), serde_json::json!("rust"));
+        params.insert("max_results".to_string(), serde_json::json!(2));
+
+        let output = executor.execute_web_search(&params).await.unwrap();
+        assert_eq!(output["provider"], "fixed");
+        assert_eq!(output["count"], 2);
+        assert_eq!(output["results"][0]["url"], hit.url);
+
+        let empty = ToolExecutor::new().with_search_provider(Arc::new(FixedSearch(Vec::new())));
+        let output = empty.execute_web_search(&params).await.unwrap();
+        assert_eq!(output["count"], 0);
+        assert_eq!(output["message"], "No results found");
+    }
+
     #[test]
     fn test_validate_blocked_command() {
         let executor = ToolExecutor::new();
@@ -0,0 +1,244 @@
+//! Tool Call History
+//!
+//! Bounded record of tool responses. Once the cap is reached the oldest
+//! entry is dropped. Entries can also be appended to a JSONL file that is
+//! rotated when it grows too large.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use super::ToolResponse;
+
+/// Entries kept in memory by default
+pub const DEFAULT_HISTORY_LIMIT: usize = 1000;
+
+/// Size at which the history file is rotated to `history.jsonl.1`
+pub const HISTORY_ROTATE_BYTES: u64 = 1024 * 1024;
+
+/// A persisted history line
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryRecord {
+    pub recorded_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub response: ToolResponse,
+}
+
+/// Most recent tool responses, oldest first
+#[derive(Debug, Clone)]
+pub struct ToolHistory {
+    entries: VecDeque<ToolResponse>,
+    limit: usize,
+    file: Option<PathBuf>,
+}
+
+impl ToolHistory {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            entries: VecDeque::new(),
+            limit: limit.max(1),
+            file: None,
+        }
+    }
+
+    /// Also append every entry to `path`
+    pub fn with_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.file = Some(path.into());
+        self
+    }
+
+    /// Default persisted history file
+    pub fn default_path() -> PathBuf {
+        dirs::home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(".sena")
+            .join("tools")
+            .join("history.jsonl")
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    pub fn set_limit(&mut self, limit: usize) {
+        self.limit = limit.max(1);
+        self.evict();
+    }
+
+    /// Record a response, dropping the oldest entries beyond the limit.
+    /// Persistence failures are logged and never fail the tool call.
+    pub fn push(&mut self, response: ToolResponse) {
+        if let Some(path) = &self.file {
+            if let Err(e) = append_record(path, &response) {
+                log::warn!("Cannot persist tool history to {}: {}", path.display(), e);
+            }
+        }
+        self.entries.push_back(response);
+        self.evict();
+    }
+
+    fn evict(&mut self) {
+        while self.entries.len() > self.limit {
+            self.entries.pop_front();
+        }
+    }
+
+    pub fn entries(&self) -> &VecDeque<ToolResponse> {
+        &self.entries
+    }
+
+    /// Entries for one tool and/or only successful calls, oldest first
+    pub fn filtered(&self, tool_name: Option<&str>, success_only: bool) -> Vec<&ToolResponse> {
+        self.entries
+            .iter()
+            .filter(|r| matches_filter(r, tool_name, success_only))
+            .collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+fn matches_filter(response: &ToolResponse, tool_name: Option<&str>, success_only: bool) -> bool {
+    tool_name.map_or(true, |name| response.tool_name == name) && (!success_only || response.success)
+}
+
+/// Where a rotated JSONL file is kept, e.g. `history.jsonl.1`
+pub(crate) fn rotated_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".1");
+    path.with_file_name(name)
+}
+
+fn append_record(path: &Path, response: &ToolResponse) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if fs::metadata(path).is_ok_and(|m| m.len() >= HISTORY_ROTATE_BYTES) {
+        fs::rename(path, rotated_path(path))?;
+    }
+
+    let record = HistoryRecord {
+        recorded_at: Utc::now(),
+        response: response.clone(),
+    };
+    let line = serde_json::to_string(&record)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", line)
+}
+
+/// Read persisted history (including the rotated file), keeping the most
+/// recent `limit` matching records, oldest first
+pub fn load_history(
+    path: &Path,
+    tool_name: Option<&str>,
+    success_only: bool,
+    limit: usize,
+) -> Vec<HistoryRecord> {
+    let mut records: Vec<HistoryRecord> = [rotated_path(path), path.to_path_buf()]
+        .iter()
+        .filter_map(|p| fs::read_to_string(p).ok())
+        .flat_map(|content| {
+            content
+                .lines()
+                .filter_map(|line| serde_json::from_str::<HistoryRecord>(line).ok())
+                .collect::<Vec<_>>()
+        })
+        .filter(|record| matches_filter(&record.response, tool_name, success_only))
+        .collect();
+
+    if records.len() > limit {
+        records.drain(..records.len() - limit);
+    }
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(id: usize, tool: &str, success: bool) -> ToolResponse {
+        if success {
+            ToolResponse::success(
+                format!("call-{}", id),
+                tool.to_string(),
+                serde_json::Value::Null,
+                1,
+            )
+        } else {
+            ToolResponse::failure(
+                format!("call-{}", id),
+                tool.to_string(),
+                "boom".to_string(),
+                1,
+            )
+        }
+    }
+
+    #[test]
+    fn test_exceeding_limit_drops_oldest_entry() {
+        let mut history = ToolHistory::new(3);
+        for id in 0..4 {
+            history.push(response(id, "file_read", true));
+        }
+
+        let ids: Vec<&str> = history
+            .entries()
+            .iter()
+            .map(|r| r.call_id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["call-1", "call-2", "call-3"]);
+
+        history.set_limit(1);
+        assert_eq!(history.entries()[0].call_id, "call-3");
+    }
+
+    #[test]
+    fn test_filter_by_tool_and_success() {
+        let mut history = ToolHistory::new(10);
+        history.push(response(0, "file_read", true));
+        history.push(response(1, "shell", false));
+        history.push(response(2, "shell", true));
+
+        assert_eq!(history.filtered(Some("shell"), false).len(), 2);
+        assert_eq!(history.filtered(None, true).len(), 2);
+        let shell_ok = history.filtered(Some("shell"), true);
+        assert_eq!(shell_ok.len(), 1);
+        assert_eq!(shell_ok[0].call_id, "call-2");
+    }
+
+    #[test]
+    fn test_persisted_history_rotates_and_loads() {
+        let dir = std::env::temp_dir().join(format!("sena-tool-history-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("history.jsonl");
+        let mut history = ToolHistory::new(10).with_file(&path);
+        history.push(response(0, "shell", false));
+        history.push(response(1, "file_read", true));
+
+        let loaded = load_history(&path, None, false, 10);
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(load_history(&path, Some("shell"), false, 10).len(), 1);
+
+        fs::write(&path, "x".repeat(HISTORY_ROTATE_BYTES as usize)).unwrap();
+        history.push(response(2, "shell", true));
+        assert!(rotated_path(&path).exists());
+        let loaded = load_history(&path, None, true, 10);
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].response.call_id, "call-2");
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -1,15 +1,23 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
 pub mod builtin;
+pub mod conversation;
+pub mod env;
 pub mod executor;
+pub mod history;
 pub mod registry;
+pub mod search;
 
 pub use builtin::BuiltinTools;
+pub use conversation::{ToolConversation, DEFAULT_MAX_TOOL_ITERATIONS};
+pub use env::{CredentialFile, SecretResolver, SessionEnv};
 pub use executor::{ToolExecutionResult, ToolExecutor};
+pub use history::{load_history, HistoryRecord, ToolHistory, DEFAULT_HISTORY_LIMIT};
 pub use registry::ToolRegistry;
+pub use search::{SearchHit, SearchOptions, SearchProvider};
 
 #[derive(Error, Debug)]
 pub enum ToolError {
@@ -23,10 +31,16 @@ pub enum ToolError {
     PermissionDenied(String),
     #[error("Timeout: {0}")]
     Timeout(String),
+    #[error("Rate limited: {0}")]
+    RateLimited(String),
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
     #[error("Serialization error: {0}")]
     SerializationError(String),
+    #[error("Provider error: {0}")]
+    Provider(#[from] sena_providers::ProviderError),
+    #[error("No final answer after {0} tool rounds")]
+    IterationLimit(usize),
 }
 
 pub type ToolResult<T> = Result<T, ToolError>;
@@ -58,6 +72,19 @@ pub enum ParameterType {
     Object,
 }
 
+impl ParameterType {
+    /// The JSON schema `type` keyword
+    pub fn json_type(&self) -> &'static str {
+        match self {
+            ParameterType::String => "string",
+            ParameterType::Integer => "integer",
+            ParameterType::Boolean => "boolean",
+            ParameterType::Array => "array",
+            ParameterType::Object => "object",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolDefinition {
     pub name: String,
@@ -70,6 +97,53 @@ pub struct ToolDefinition {
     pub timeout_seconds: u64,
 }
 
+impl ToolDefinition {
+    /// JSON schema of the parameters, as providers expect for function calling
+    pub fn parameters_schema(&self) -> serde_json::Value {
+        let properties: serde_json::Map<String, serde_json::Value> = self
+            .parameters
+            .iter()
+            .map(|param| {
+                let mut schema = serde_json::json!({
+                    "type": param.param_type.json_type(),
+                    "description": param.description,
+                });
+                if param.param_type == ParameterType::Array {
+                    schema["items"] = serde_json::json!({});
+                }
+                if let Some(default) = &param.default {
+                    schema["default"] = default.clone();
+                }
+                (param.name.clone(), schema)
+            })
+            .collect();
+        let required: Vec<&str> = self
+            .parameters
+            .iter()
+            .filter(|param| param.required)
+            .map(|param| param.name.as_str())
+            .collect();
+
+        serde_json::json!({
+            "type": "object",
+            "properties": properties,
+            "required": required,
+        })
+    }
+}
+
+impl From<&ToolDefinition> for sena_providers::Tool {
+    fn from(tool: &ToolDefinition) -> Self {
+        sena_providers::Tool::function(&tool.name, &tool.description, tool.parameters_schema())
+    }
+}
+
+impl From<ToolDefinition> for sena_providers::Tool {
+    fn from(tool: ToolDefinition) -> Self {
+        (&tool).into()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolExample {
     pub description: String,
@@ -112,6 +186,25 @@ impl ToolCall {
         self.parameters.insert(key.into(), value.into());
         self
     }
+
+    /// A call requested by a model, keeping the provider's call id
+    pub fn from_provider(call: &sena_providers::ToolCall) -> ToolResult<Self> {
+        let parameters = match call.arguments() {
+            Ok(serde_json::Value::Object(map)) => map.into_iter().collect(),
+            Ok(other) => {
+                return Err(ToolError::InvalidParameters(format!(
+                    "expected an object of arguments, got {}",
+                    other
+                )))
+            }
+            Err(e) => return Err(ToolError::InvalidParameters(e.to_string())),
+        };
+        Ok(Self {
+            tool_name: call.function.name.clone(),
+            parameters,
+            call_id: call.id.clone(),
+        })
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -156,7 +249,7 @@ impl ToolResponse {
 pub struct ToolSystem {
     registry: ToolRegistry,
     executor: ToolExecutor,
-    call_history: Vec<ToolResponse>,
+    call_history: ToolHistory,
 }
 
 impl Default for ToolSystem {
@@ -172,8 +265,26 @@ impl ToolSystem {
 
         Self {
             registry,
-            executor: ToolExecutor::new(),
-            call_history: Vec::new(),
+            executor: Self::configured_executor(),
+            call_history: Self::configured_history(),
+        }
+    }
+
+    fn configured_executor() -> ToolExecutor {
+        let executor = ToolExecutor::new();
+        match crate::config::SenaConfig::global().tools.sandbox_root() {
+            Some(root) => executor.with_root(root),
+            None => executor,
+        }
+    }
+
+    fn configured_history() -> ToolHistory {
+        let config = &crate::config::SenaConfig::global().tools;
+        let history = ToolHistory::new(config.history_limit);
+        if config.persist_history {
+            history.with_file(ToolHistory::default_path())
+        } else {
+            history
         }
     }
 
@@ -184,11 +295,49 @@ impl ToolSystem {
 
         Ok(Self {
             registry,
-            executor: ToolExecutor::new(),
-            call_history: Vec::new(),
+            executor: Self::configured_executor(),
+            call_history: Self::configured_history(),
         })
     }
 
+    /// Confine file system tools to `root` instead of the configured sandbox
+    pub fn with_root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.executor.set_root(Some(root.into()));
+        self
+    }
+
+    /// Keep at most `limit` calls in memory, evicting the oldest first
+    pub fn with_history_limit(mut self, limit: usize) -> Self {
+        self.call_history.set_limit(limit);
+        self
+    }
+
+    /// Append every call to a JSONL file, rotated when it grows large
+    pub fn with_history_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.call_history = self.call_history.with_file(path);
+        self
+    }
+
+    /// Run tools with a session's environment, secrets redacted from responses
+    pub fn with_session_env(mut self, env: SessionEnv) -> Self {
+        self.executor.set_session_env(env);
+        self
+    }
+
+    /// Allow file system tools to reach any path, for trusted contexts only
+    pub fn unrestricted(mut self) -> Self {
+        self.executor.set_root(None);
+        self
+    }
+
+    pub fn set_root(&mut self, root: Option<PathBuf>) {
+        self.executor.set_root(root);
+    }
+
+    pub fn sandbox_root(&self) -> Option<&Path> {
+        self.executor.root()
+    }
+
     pub async fn execute(&mut self, call: ToolCall) -> ToolResponse {
         let start = std::time::Instant::now();
 
@@ -243,8 +392,17 @@ impl ToolSystem {
         self.registry.register(tool);
     }
 
-    pub fn get_history(&self) -> &[ToolResponse] {
-        &self.call_history
+    pub fn get_history(&self) -> &VecDeque<ToolResponse> {
+        self.call_history.entries()
+    }
+
+    /// Calls for one tool and/or only successful ones, oldest first
+    pub fn get_history_filtered(
+        &self,
+        tool_name: Option<&str>,
+        success_only: bool,
+    ) -> Vec<&ToolResponse> {
+        self.call_history.filtered(tool_name, success_only)
     }
 
     pub fn clear_history(&mut self) {
@@ -298,6 +456,67 @@ mod tests {
         assert!(response.error.is_some());
     }
 
+    #[tokio::test]
+    async fn test_sandboxed_file_read() {
+        let root = std::env::temp_dir().join(format!("sena_tool_root_{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        let file = root.join("inside.txt");
+        std::fs::write(&file, "sandboxed").unwrap();
+
+        let mut system = ToolSystem::new().with_root(&root);
+
+        let inside = system
+            .execute(
+                ToolCall::new("file_read", HashMap::new())
+                    .with_param("path", file.to_string_lossy().to_string()),
+            )
+            .await;
+        assert!(inside.success);
+        assert_eq!(inside.output["content"], "sandboxed");
+
+        let escape = system
+            .execute(
+                ToolCall::new("file_read", HashMap::new())
+                    .with_param("path", format!("{}/../etc/passwd", root.display())),
+            )
+            .await;
+        assert!(!escape.success);
+        assert!(escape.error.unwrap().contains("Permission denied"));
+
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_session_env_reaches_command_and_is_redacted() {
+        let creds = std::env::temp_dir().join(format!("sena_creds_{}.toml", uuid::Uuid::new_v4()));
+        std::fs::write(
+            &creds,
+            "[credentials.github.fields]\ntoken = \"ghp_session_secret\"\n",
+        )
+        .unwrap();
+
+        let mut session = crate::hub::Session::new(crate::hub::SessionRole::Backend, None);
+        session.set_env("PROJECT_NAME", "sena");
+        session.set_env("GH_TOKEN", "secret:github/token");
+        let env = session.tool_env(&CredentialFile::new(&creds)).unwrap();
+
+        let mut system = ToolSystem::new().with_session_env(env);
+        let response = system
+            .execute(
+                ToolCall::new("shell_exec", HashMap::new())
+                    .with_param("command", "echo \"$PROJECT_NAME:$GH_TOKEN\""),
+            )
+            .await;
+        assert!(response.success);
+        assert_eq!(response.output["stdout"], "sena:[REDACTED]\n");
+
+        let recorded = serde_json::to_string(&system.get_history()[0]).unwrap();
+        assert!(!recorded.contains("ghp_session_secret"));
+
+        std::fs::remove_file(&creds).ok();
+    }
+
     #[tokio::test]
     async fn test_tool_system_creation() {
         let system = ToolSystem::new();
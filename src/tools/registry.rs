@@ -57,6 +57,7 @@ impl ToolRegistry {
         self.register(Self::file_exists_tool());
         self.register(Self::shell_exec_tool());
         self.register(Self::web_fetch_tool());
+        self.register(Self::web_search_tool());
         self.register(Self::code_search_tool());
         self.register(Self::code_analyze_tool());
     }
@@ -329,6 +330,42 @@ impl ToolRegistry {
         }
     }
 
+    fn web_search_tool() -> ToolDefinition {
+        ToolDefinition {
+            name: "web_search".to_string(),
+            description: "Search the web with the configured search provider".to_string(),
+            category: ToolCategory::Web,
+            parameters: vec![
+                ToolParameter {
+                    name: "query".to_string(),
+                    description: "Search query".to_string(),
+                    param_type: ParameterType::String,
+                    required: true,
+                    default: None,
+                },
+                ToolParameter {
+                    name: "max_results".to_string(),
+                    description: "Maximum number of results".to_string(),
+                    param_type: ParameterType::Integer,
+                    required: false,
+                    default: Some(serde_json::json!(10)),
+                },
+            ],
+            returns: "List of results with title, url and snippet".to_string(),
+            examples: vec![ToolExample {
+                description: "Search for documentation".to_string(),
+                parameters: {
+                    let mut p = HashMap::new();
+                    p.insert("query".to_string(), serde_json::json!("rust async book"));
+                    p
+                },
+                expected_output: "Search results...".to_string(),
+            }],
+            requires_confirmation: false,
+            timeout_seconds: 30,
+        }
+    }
+
     fn code_search_tool() -> ToolDefinition {
         ToolDefinition {
             name: "code_search".to_string(),
@@ -0,0 +1,467 @@
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::{ToolError, ToolResult};
+use crate::config::SearchConfig;
+
+const BRAVE_ENDPOINT: &str = "https://api.search.brave.com/res/v1/web/search";
+const DUCKDUCKGO_ENDPOINT: &str = "https://html.duckduckgo.com/html/";
+const SEARCH_TIMEOUT_SECS: u64 = 15;
+
+static DDG_LINK: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?s)<a[^>]*class="[^"]*result__a[^"]*"[^>]*href="([^"]+)"[^>]*>(.*?)</a>"#)
+        .expect("valid regex")
+});
+static DDG_SNIPPET: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?s)class="[^"]*result__snippet[^"]*"[^>]*>(.*?)</(?:a|div|td)>"#)
+        .expect("valid regex")
+});
+static HTML_TAG: Lazy<Regex> = Lazy::new(|| Regex::new(r"<[^>]+>").expect("valid regex"));
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub title: String,
+    pub url: String,
+    pub snippet: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchOptions {
+    pub max_results: usize,
+    pub safe_search: bool,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            max_results: 10,
+            safe_search: true,
+        }
+    }
+}
+
+#[async_trait]
+pub trait SearchProvider: Send + Sync {
+    fn name(&self) -> &str;
+
+    async fn search(&self, query: &str, options: &SearchOptions) -> ToolResult<Vec<SearchHit>>;
+}
+
+fn http_client() -> ToolResult<reqwest::Client> {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(SEARCH_TIMEOUT_SECS))
+        .user_agent(concat!("sena/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(|e| ToolError::ExecutionFailed(e.to_string()))
+}
+
+async fn send(provider: &str, request: reqwest::RequestBuilder) -> ToolResult<String> {
+    let response = request
+        .send()
+        .await
+        .map_err(|e| ToolError::ExecutionFailed(format!("{} search failed: {}", provider, e)))?;
+
+    let status = response.status();
+    if status.as_u16() == 429 {
+        let retry_after = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| format!(", retry after {}s", v))
+            .unwrap_or_default();
+        return Err(ToolError::RateLimited(format!(
+            "{} search is rate limited{}",
+            provider, retry_after
+        )));
+    }
+    if !status.is_success() {
+        return Err(ToolError::ExecutionFailed(format!(
+            "{} search returned HTTP {}",
+            provider, status
+        )));
+    }
+
+    response
+        .text()
+        .await
+        .map_err(|e| ToolError::ExecutionFailed(e.to_string()))
+}
+
+pub struct BraveSearch {
+    endpoint: String,
+    api_key: String,
+    client: reqwest::Client,
+}
+
+impl BraveSearch {
+    pub fn new(api_key: impl Into<String>) -> ToolResult<Self> {
+        Ok(Self {
+            endpoint: BRAVE_ENDPOINT.to_string(),
+            api_key: api_key.into(),
+            client: http_client()?,
+        })
+    }
+
+    pub fn with_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = endpoint.into();
+        self
+    }
+
+    pub fn parse(body: &str) -> ToolResult<Vec<SearchHit>> {
+        let json: serde_json::Value =
+            serde_json::from_str(body).map_err(|e| ToolError::SerializationError(e.to_string()))?;
+        let results = json["web"]["results"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default();
+
+        Ok(results
+            .iter()
+            .filter_map(|r| {
+                Some(SearchHit {
+                    title: clean_text(r["title"].as_str()?),
+                    url: r["url"].as_str()?.to_string(),
+                    snippet: clean_text(r["description"].as_str().unwrap_or_default()),
+                })
+            })
+            .collect())
+    }
+}
+
+#[async_trait]
+impl SearchProvider for BraveSearch {
+    fn name(&self) -> &str {
+        "brave"
+    }
+
+    async fn search(&self, query: &str, options: &SearchOptions) -> ToolResult<Vec<SearchHit>> {
+        let request = self
+            .client
+            .get(&self.endpoint)
+            .header("Accept", "application/json")
+            .header("X-Subscription-Token", &self.api_key)
+            .query(&[
+                ("q", query.to_string()),
+                ("count", options.max_results.to_string()),
+                (
+                    "safesearch",
+                    if options.safe_search {
+                        "moderate"
+                    } else {
+                        "off"
+                    }
+                    .to_string(),
+                ),
+            ]);
+        let body = send(self.name(), request).await?;
+        let mut hits = Self::parse(&body)?;
+        hits.truncate(options.max_results);
+        Ok(hits)
+    }
+}
+
+pub struct SearxngSearch {
+    endpoint: String,
+    client: reqwest::Client,
+}
+
+impl SearxngSearch {
+    pub fn new(endpoint: impl Into<String>) -> ToolResult<Self> {
+        Ok(Self {
+            endpoint: endpoint.into(),
+            client: http_client()?,
+        })
+    }
+
+    pub fn parse(body: &str) -> ToolResult<Vec<SearchHit>> {
+        let json: serde_json::Value =
+            serde_json::from_str(body).map_err(|e| ToolError::SerializationError(e.to_string()))?;
+        let results = json["results"].as_array().cloned().unwrap_or_default();
+
+        Ok(results
+            .iter()
+            .filter_map(|r| {
+                Some(SearchHit {
+                    title: clean_text(r["title"].as_str()?),
+                    url: r["url"].as_str()?.to_string(),
+                    snippet: clean_text(r["content"].as_str().unwrap_or_default()),
+                })
+            })
+            .collect())
+    }
+}
+
+#[async_trait]
+impl SearchProvider for SearxngSearch {
+    fn name(&self) -> &str {
+        "searxng"
+    }
+
+    async fn search(&self, query: &str, options: &SearchOptions) -> ToolResult<Vec<SearchHit>> {
+        let url = format!("{}/search", self.endpoint.trim_end_matches('/'));
+        let request = self.client.get(url).query(&[
+            ("q", query),
+            ("format", "json"),
+            ("safesearch", if options.safe_search { "1" } else { "0" }),
+        ]);
+        let body = send(self.name(), request).await?;
+        let mut hits = Self::parse(&body)?;
+        hits.truncate(options.max_results);
+        Ok(hits)
+    }
+}
+
+pub struct DuckDuckGoSearch {
+    endpoint: String,
+    client: reqwest::Client,
+}
+
+impl DuckDuckGoSearch {
+    pub fn new() -> ToolResult<Self> {
+        Ok(Self {
+            endpoint: DUCKDUCKGO_ENDPOINT.to_string(),
+            client: http_client()?,
+        })
+    }
+
+    pub fn with_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = endpoint.into();
+        self
+    }
+
+    pub fn parse(html: &str) -> Vec<SearchHit> {
+        let snippets: Vec<String> = DDG_SNIPPET
+            .captures_iter(html)
+            .map(|c| clean_text(&c[1]))
+            .collect();
+
+        DDG_LINK
+            .captures_iter(html)
+            .enumerate()
+            .map(|(i, c)| SearchHit {
+                title: clean_text(&c[2]),
+                url: resolve_ddg_redirect(&decode_entities(&c[1])),
+                snippet: snippets.get(i).cloned().unwrap_or_default(),
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl SearchProvider for DuckDuckGoSearch {
+    fn name(&self) -> &str {
+        "duckduckgo"
+    }
+
+    async fn search(&self, query: &str, options: &SearchOptions) -> ToolResult<Vec<SearchHit>> {
+        let request = self.client.get(&self.endpoint).query(&[
+            ("q", query),
+            ("kp", if options.safe_search { "1" } else { "-2" }),
+        ]);
+        let body = send(self.name(), request).await?;
+
+        if body.contains("anomaly-modal") {
+            return Err(ToolError::RateLimited(
+                "duckduckgo search is rate limited, try again later".to_string(),
+            ));
+        }
+
+        let mut hits = Self::parse(&body);
+        hits.truncate(options.max_results);
+        Ok(hits)
+    }
+}
+
+/// Build the search backend selected by the `[search]` config section
+pub fn from_config(config: &SearchConfig) -> ToolResult<Arc<dyn SearchProvider>> {
+    match config.provider.to_lowercase().as_str() {
+        "brave" => {
+            let api_key = config.api_key().ok_or_else(|| {
+                ToolError::InvalidParameters(
+                    "Brave search needs [search] api_key or BRAVE_SEARCH_API_KEY".to_string(),
+                )
+            })?;
+            let mut provider = BraveSearch::new(api_key)?;
+            if let Some(endpoint) = &config.endpoint {
+                provider = provider.with_endpoint(endpoint);
+            }
+            Ok(Arc::new(provider))
+        }
+        "searxng" => {
+            let endpoint = config.endpoint.clone().ok_or_else(|| {
+                ToolError::InvalidParameters("SearXNG needs [search] endpoint".to_string())
+            })?;
+            Ok(Arc::new(SearxngSearch::new(endpoint)?))
+        }
+        "duckduckgo" | "ddg" => {
+            let mut provider = DuckDuckGoSearch::new()?;
+            if let Some(endpoint) = &config.endpoint {
+                provider = provider.with_endpoint(endpoint);
+            }
+            Ok(Arc::new(provider))
+        }
+        other => Err(ToolError::InvalidParameters(format!(
+            "Unknown search provider: {} (use brave, searxng or duckduckgo)",
+            other
+        ))),
+    }
+}
+
+fn clean_text(text: &str) -> String {
+    let stripped = HTML_TAG.replace_all(text, "");
+    decode_entities(&stripped)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&quot;", "\"")
+        .replace("&#x27;", "'")
+        .replace("&#39;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+fn resolve_ddg_redirect(href: &str) -> String {
+    let Some(start) = href.find("uddg=") else {
+        return if href.starts_with("//") {
+            format!("https:{}", href)
+        } else {
+            href.to_string()
+        };
+    };
+    let encoded = href[start + 5..].split('&').next().unwrap_or_default();
+    percent_decode(encoded)
+}
+
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+            }
+            (b'+', _) => {
+                out.push(b' ');
+                i += 1;
+            }
+            (byte, _) => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    fn mock_backend(status: &str, content_type: &str, body: &str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let response = format!(
+            "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            content_type,
+            body.len(),
+            body
+        );
+
+        std::thread::spawn(move || {
+            if let Ok((mut stream, _)) = listener.accept() {
+                let mut buffer = [0u8; 4096];
+                let _ = stream.read(&mut buffer);
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+
+        format!("http://{}", address)
+    }
+
+    #[tokio::test]
+    async fn test_brave_hits_parsed() {
+        let body = r#"{"web":{"results":[
+            {"title":"Rust <strong>Lang</strong>","url":"https://www.rust-lang.org/","description":"A language empowering everyone"},
+            {"title":"Rust Book","url":"https://doc.rust-lang.org/book/","description":"The book"}
+        ]}}"#;
+        let endpoint = mock_backend("200 OK", "application/json", body);
+        let provider = BraveSearch::new("test-key")
+            .unwrap()
+            .with_endpoint(endpoint);
+
+        let hits = provider
+            .search("rust", &SearchOptions::default())
+            .await
+            .unwrap();
+
+        assert_eq!(hits.len(), 2);
+        assert_eq!(
+            hits[0],
+            SearchHit {
+                title: "Rust Lang".to_string(),
+                url: "https://www.rust-lang.org/".to_string(),
+                snippet: "A language empowering everyone".to_string(),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_searxng_empty_results() {
+        let endpoint = mock_backend(
+            "200 OK",
+            "application/json",
+            r#"{"query":"zzqx","results":[]}"#,
+        );
+        let provider = SearxngSearch::new(endpoint).unwrap();
+
+        let hits = provider
+            .search("zzqx", &SearchOptions::default())
+            .await
+            .unwrap();
+        assert!(hits.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_reported() {
+        let endpoint = mock_backend("429 Too Many Requests", "text/plain", "slow down");
+        let provider = SearxngSearch::new(endpoint).unwrap();
+
+        let result = provider.search("rust", &SearchOptions::default()).await;
+        assert!(matches!(result, Err(ToolError::RateLimited(_))));
+    }
+
+    #[test]
+    fn test_duckduckgo_html_parsed() {
+        let html = r#"
+            <div class="result results_links">
+              <a rel="nofollow" class="result__a" href="//duckduckgo.com/l/?uddg=https%3A%2F%2Fwww.rust%2Dlang.org%2F&amp;rut=abc">The <b>Rust</b> Programming Language</a>
+              <a class="result__snippet" href="x">Fast, reliable &amp; productive.</a>
+            </div>
+        "#;
+
+        let hits = DuckDuckGoSearch::parse(html);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].title, "The Rust Programming Language");
+        assert_eq!(hits[0].url, "https://www.rust-lang.org/");
+        assert_eq!(hits[0].snippet, "Fast, reliable & productive.");
+        assert!(DuckDuckGoSearch::parse("<html>No results.</html>").is_empty());
+    }
+}
@@ -0,0 +1,224 @@
+//! Processing Traces
+//!
+//! Turns the per-phase timing in a `ProcessingResult` into spans shaped
+//! after OpenTelemetry's: a root span covering the whole request with one
+//! child per phase. The spans can be exported in the Chrome trace event
+//! format that speedscope, Perfetto and `chrome://tracing` load as a
+//! flamegraph.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+
+use crate::{ProcessingPhase, ProcessingResult};
+
+/// Name of the root span of a processing trace
+pub const ROOT_SPAN: &str = "process";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SpanStatus {
+    Ok,
+    Error,
+}
+
+/// A timed operation within a trace. Times are microseconds from the start
+/// of processing.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Span {
+    pub trace_id: String,
+    pub span_id: String,
+    pub parent_span_id: Option<String>,
+    pub name: String,
+    pub start_offset_us: u64,
+    pub duration_us: u64,
+    pub status: SpanStatus,
+    pub attributes: BTreeMap<String, String>,
+}
+
+impl Span {
+    pub fn end_offset_us(&self) -> u64 {
+        self.start_offset_us + self.duration_us
+    }
+}
+
+fn hex_id(parts: &[&str], bytes: usize) -> String {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update(part.as_bytes());
+    }
+    hex::encode(&hasher.finalize()[..bytes])
+}
+
+/// Spans for `result`: the root span first, then its phases in pipeline
+/// order. Phases that did not run, or were skipped, have no span.
+pub fn spans(result: &ProcessingResult) -> Vec<Span> {
+    let trace_id = hex_id(&[&result.request_id], 16);
+    let root_id = hex_id(&[&result.id], 8);
+
+    let mut root = Span {
+        trace_id: trace_id.clone(),
+        span_id: root_id.clone(),
+        parent_span_id: None,
+        name: ROOT_SPAN.to_string(),
+        start_offset_us: 0,
+        duration_us: result.processing_time_us,
+        status: if result.success {
+            SpanStatus::Ok
+        } else {
+            SpanStatus::Error
+        },
+        attributes: BTreeMap::from([
+            ("request.id".to_string(), result.request_id.clone()),
+            ("result.id".to_string(), result.id.clone()),
+        ]),
+    };
+
+    let mut children = Vec::new();
+    let mut failed_phase = None;
+    for phase in ProcessingPhase::all() {
+        let key = phase.name().to_lowercase();
+        let Some(phase_result) = result.phase_results.get(&key).filter(|p| !p.skipped) else {
+            continue;
+        };
+        if !phase_result.success && failed_phase.is_none() {
+            failed_phase = Some(key.clone());
+        }
+        let mut attributes: BTreeMap<String, String> = phase_result
+            .output
+            .iter()
+            .map(|(k, v)| (format!("phase.{}", k), v.clone()))
+            .collect();
+        attributes.insert(
+            "phase.score".to_string(),
+            format!("{:.2}", phase_result.score),
+        );
+        children.push(Span {
+            trace_id: trace_id.clone(),
+            span_id: hex_id(&[&result.id, &key], 8),
+            parent_span_id: Some(root_id.clone()),
+            name: key,
+            start_offset_us: phase_result.start_offset_us,
+            duration_us: phase_result.duration_us,
+            status: if phase_result.success {
+                SpanStatus::Ok
+            } else {
+                SpanStatus::Error
+            },
+            attributes,
+        });
+    }
+
+    if result.cancelled {
+        root.attributes
+            .insert("cancelled".to_string(), "true".to_string());
+    }
+    if !result.success {
+        if let Some(phase) = failed_phase {
+            root.attributes.insert("error.phase".to_string(), phase);
+        }
+        root.attributes
+            .insert("error.message".to_string(), result.errors.join("; "));
+    }
+
+    let mut spans = vec![root];
+    spans.extend(children);
+    spans
+}
+
+/// `spans` as a Chrome trace: one complete ("X") event per span on a
+/// single thread, so children nest under the root by time
+pub fn to_chrome_trace(spans: &[Span]) -> serde_json::Value {
+    let events: Vec<serde_json::Value> = spans
+        .iter()
+        .map(|span| {
+            let mut args = span.attributes.clone();
+            args.insert("status".to_string(), format!("{:?}", span.status));
+            serde_json::json!({
+                "name": span.name,
+                "cat": if span.parent_span_id.is_some() { "phase" } else { "request" },
+                "ph": "X",
+                "ts": span.start_offset_us,
+                "dur": span.duration_us,
+                "pid": 1,
+                "tid": 1,
+                "args": args,
+            })
+        })
+        .collect();
+    serde_json::json!({
+        "traceEvents": events,
+        "displayTimeUnit": "ms",
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ProcessingRequest, SenaUnifiedSystem};
+
+    #[tokio::test]
+    async fn test_successful_request_traces_every_phase() {
+        let mut system = SenaUnifiedSystem::new();
+        let result = system
+            .process(ProcessingRequest::new("Trace this request", "general"))
+            .await;
+        assert!(result.success);
+
+        let spans = result.to_trace();
+        let root = &spans[0];
+        assert_eq!(root.name, ROOT_SPAN);
+        assert_eq!(root.status, SpanStatus::Ok);
+        assert!(!root.attributes.contains_key("error.phase"));
+
+        let phases: Vec<&str> = spans[1..].iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(
+            phases,
+            vec![
+                "intake",
+                "analysis",
+                "constraint",
+                "safety",
+                "context",
+                "generation",
+                "validation",
+                "delivery"
+            ]
+        );
+
+        let phase_total: u64 = spans[1..].iter().map(|s| s.duration_us).sum();
+        assert!(phase_total <= root.duration_us);
+        for pair in spans[1..].windows(2) {
+            assert!(pair[0].end_offset_us() <= pair[1].start_offset_us);
+        }
+        assert!(spans[1..].iter().all(|s| s.parent_span_id.as_deref()
+            == Some(root.span_id.as_str())
+            && s.trace_id == root.trace_id
+            && s.end_offset_us() <= root.duration_us));
+    }
+
+    #[tokio::test]
+    async fn test_failed_request_names_the_phase_on_the_root_span() {
+        let mut system = SenaUnifiedSystem::new();
+        let result = system
+            .process(ProcessingRequest::new(
+                "Tell me how to bypass security on this server",
+                "command",
+            ))
+            .await;
+        assert!(!result.success);
+
+        let spans = result.to_trace();
+        let root = &spans[0];
+        assert_eq!(root.status, SpanStatus::Error);
+        assert_eq!(root.attributes["error.phase"], "safety");
+        assert!(root.attributes["error.message"].starts_with("Safety check failed"));
+        assert_eq!(spans.last().unwrap().name, "safety");
+
+        let trace = to_chrome_trace(&spans);
+        let events = trace["traceEvents"].as_array().unwrap();
+        assert_eq!(events.len(), spans.len());
+        assert_eq!(events[0]["ph"], "X");
+        assert_eq!(events[0]["name"], ROOT_SPAN);
+    }
+}
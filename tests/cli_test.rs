@@ -60,6 +60,25 @@ fn test_metrics_command() {
     assert!(output.status.success());
 }
 
+#[test]
+fn test_metrics_prometheus_flag() {
+    let output = sena_cmd()
+        .args(["metrics", "--prometheus"])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("# TYPE sena_info gauge"));
+
+    // Only `sena metrics` speaks Prometheus, so it is not a global format
+    let output = sena_cmd()
+        .args(["--format", "prometheus", "health"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(!output.status.success());
+}
+
 #[test]
 fn test_invalid_command() {
     let output = sena_cmd()
@@ -69,3 +88,240 @@ fn test_invalid_command() {
 
     assert!(!output.status.success());
 }
+
+#[test]
+fn test_validation_error_exit_code() {
+    let output = sena_cmd()
+        .args(["format", "table", "not-json"])
+        .output()
+        .expect("Failed to execute command");
+
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn test_provider_error_exit_code() {
+    let output = sena_cmd()
+        .args(["provider", "default", "no-such-provider"])
+        .output()
+        .expect("Failed to execute command");
+
+    assert_eq!(output.status.code(), Some(3));
+}
+
+#[test]
+fn test_guardian_blocked_exit_code() {
+    let output = sena_cmd()
+        .args(["guardian", "execute", "sudo", "true"])
+        .output()
+        .expect("Failed to execute command");
+
+    assert_eq!(output.status.code(), Some(4));
+}
+
+#[test]
+fn test_think_deep_has_more_steps_than_quick() {
+    let steps = |depth: &str| {
+        let output = sena_cmd()
+            .args([
+                "--format",
+                "json",
+                "think",
+                "--depth",
+                depth,
+                "Why does the build fail on CI?",
+            ])
+            .output()
+            .expect("Failed to execute command");
+        assert!(output.status.success());
+        let result: serde_json::Value =
+            serde_json::from_slice(&output.stdout).expect("think should print JSON");
+        result["steps"].as_array().map(Vec::len).unwrap_or(0)
+    };
+
+    assert_eq!(steps("quick"), 1);
+    assert!(steps("deep") > steps("quick"));
+}
+
+#[test]
+fn test_json_format_is_parseable_for_every_command() {
+    let home = std::env::temp_dir().join(format!("sena-cli-json-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&home).unwrap();
+    let commands: &[&[&str]] = &[
+        &["health"],
+        &["metrics"],
+        &["who"],
+        &["hub", "status"],
+        &["hub", "sessions"],
+        &["hub", "messages"],
+        &["hub", "identity"],
+        &["hub", "peers"],
+        &["hub", "conflicts"],
+        &["hub", "graph"],
+        &["hub", "stop"],
+        &["peer", "list"],
+        &["network", "status"],
+        &["network", "info"],
+        &["guardian", "status"],
+        &["task", "list"],
+    ];
+
+    let json = |command: &[&str]| -> serde_json::Value {
+        let output = sena_cmd()
+            .env("HOME", &home)
+            .args(["--format", "json"])
+            .args(command)
+            .output()
+            .expect("Failed to execute command");
+        assert!(output.status.success(), "{:?} failed", command);
+        serde_json::from_slice(&output.stdout)
+            .unwrap_or_else(|e| panic!("{:?} printed invalid JSON: {}", command, e))
+    };
+
+    for command in commands {
+        let value = json(command);
+        assert!(
+            value.get("output").is_none(),
+            "{:?} fell back to wrapped text: {}",
+            command,
+            value
+        );
+    }
+
+    assert_eq!(json(&["who"]), serde_json::json!([]));
+    assert_eq!(json(&["task", "list"]), serde_json::json!([]));
+    assert_eq!(json(&["hub", "status"])["online_sessions"], 0);
+    assert_eq!(json(&["network", "status"])["running"], false);
+    assert!(json(&["hub", "identity"])["hub_id"].is_string());
+
+    let output = sena_cmd()
+        .env("HOME", &home)
+        .args(["--format", "yaml", "hub", "status"])
+        .output()
+        .expect("Failed to execute command");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("online_sessions: 0"), "{}", stdout);
+
+    let joined = json(&["join", "--role", "backend", "--name", "api"]);
+    assert_eq!(joined["action"], "joined");
+    assert_eq!(joined["name"], "api");
+    let created = json(&["task", "new", "Add login", "--to", "api"]);
+    assert_eq!(created["title"], "Add login");
+    let tasks = json(&["task", "list"]);
+    assert_eq!(tasks[0]["id"], created["id"]);
+    assert_eq!(tasks[0]["status"], "Pending");
+    let who = json(&["who"]);
+    assert_eq!(who[0]["role"], joined["role"]);
+
+    let _ = std::fs::remove_dir_all(home);
+}
+
+#[test]
+fn test_mcp_logs_correlate_request_and_response() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let home = std::env::temp_dir().join(format!("sena-mcp-log-{}", std::process::id()));
+    std::fs::create_dir_all(&home).unwrap();
+
+    let mut child = sena_cmd()
+        .arg("mcp")
+        .env("HOME", &home)
+        .env("RUST_LOG", "sena1996_ai::mcp=debug")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to start MCP server");
+    {
+        let mut stdin = child.stdin.take().unwrap();
+        writeln!(stdin, r#"{{"jsonrpc":"2.0","id":42,"method":"ping"}}"#).unwrap();
+        writeln!(
+            stdin,
+            r#"{{"jsonrpc":"2.0","id":"req-7","method":"resources/read","params":{{"uri":"sena://nope"}}}}"#
+        )
+        .unwrap();
+    }
+    let output = child.wait_with_output().unwrap();
+    let _ = std::fs::remove_dir_all(&home);
+
+    // Stdout is the JSON-RPC channel: nothing but responses
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let responses: Vec<serde_json::Value> = stdout
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("stdout line is not JSON-RPC"))
+        .collect();
+    assert_eq!(responses.len(), 2);
+    assert_eq!(responses[0]["id"], 42);
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("--> id=42 method=ping"), "{}", stderr);
+    assert!(stderr.contains("<-- id=42 method=ping"), "{}", stderr);
+    let failed = stderr
+        .lines()
+        .find(|line| line.contains("<-- id=\"req-7\""))
+        .expect("error response not logged");
+    assert!(failed.contains("sena://nope"), "{}", failed);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_daemon_detaches_and_stops() {
+    let home = std::env::temp_dir().join(format!("sena-daemon-{}", std::process::id()));
+    std::fs::create_dir_all(&home).unwrap();
+    let pid_file = home.join(".claude").join("sena_daemon.pid");
+    let alive = |pid: &str| {
+        Command::new("kill")
+            .args(["-0", pid])
+            .stderr(std::process::Stdio::null())
+            .status()
+            .map(|s| s.success())
+            .unwrap_or(false)
+    };
+
+    let start = sena_cmd()
+        .args(["daemon", "start"])
+        .env("HOME", &home)
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .expect("Failed to execute command");
+    let cli_pid = start.id().to_string();
+    let output = start.wait_with_output().unwrap();
+    assert!(output.status.success());
+
+    // The daemon outlives the command that started it, under its own PID
+    let pid = std::fs::read_to_string(&pid_file)
+        .unwrap()
+        .trim()
+        .to_string();
+    assert_ne!(pid, cli_pid);
+    assert!(alive(&pid));
+
+    let status = sena_cmd()
+        .args(["daemon", "status"])
+        .env("HOME", &home)
+        .output()
+        .unwrap();
+    assert!(String::from_utf8_lossy(&status.stdout).contains(&format!("PID: {}", pid)));
+
+    let stop = sena_cmd()
+        .args(["daemon", "stop"])
+        .env("HOME", &home)
+        .output()
+        .unwrap();
+    let stopped = !alive(&pid);
+    if !stopped {
+        let _ = Command::new("kill").args(["-9", &pid]).status();
+    }
+    assert!(
+        stop.status.success(),
+        "{}",
+        String::from_utf8_lossy(&stop.stderr)
+    );
+    assert!(stopped);
+    assert!(!pid_file.exists());
+    // It shut down through its SIGTERM handler rather than being killed
+    let log = std::fs::read_to_string(home.join(".claude").join("sena_daemon.log")).unwrap();
+    assert!(log.contains("Received SIGTERM"));
+    let _ = std::fs::remove_dir_all(home);
+}